@@ -0,0 +1,83 @@
+//! Build script
+//!
+//! Compiles the bundled OUI registry subset (`data/oui.csv`) into a sorted
+//! static table so `MacAddress::vendor` can binary-search it without parsing
+//! anything at runtime.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const OUI_DATA: &str = "data/oui.csv";
+const DEVICE_TYPES: &[&str] = &["gateway", "switch", "access-point", "firewall"];
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={}", OUI_DATA);
+
+    let data = fs::read_to_string(OUI_DATA)
+        .unwrap_or_else(|e| panic!("failed to read {}: {}", OUI_DATA, e));
+
+    let mut entries = Vec::new();
+    for (number, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        entries.push(parse_entry(line).unwrap_or_else(|e| {
+            panic!("{}:{}: {}", OUI_DATA, number + 1, e)
+        }));
+    }
+
+    entries.sort_by_key(|(oui, _, _)| *oui);
+    if let Some(pair) = entries.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        panic!("{}: duplicate OUI {:02X?}", OUI_DATA, pair[0].0);
+    }
+
+    let mut table = String::from(
+        "/// OUI registry subset generated from data/oui.csv, sorted by OUI\n\
+         pub(crate) static OUI_TABLE: &[([u8; 3], &str, &str)] = &[\n",
+    );
+    for (oui, vendor, device_type) in &entries {
+        table.push_str(&format!(
+            "    ([0x{:02x}, 0x{:02x}, 0x{:02x}], {:?}, {:?}),\n",
+            oui[0], oui[1], oui[2], vendor, device_type
+        ));
+    }
+    table.push_str("];\n");
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    fs::write(Path::new(&out_dir).join("oui_table.rs"), table)
+        .expect("failed to write generated OUI table");
+}
+
+/// Parse one `oui,vendor,device_type` line
+fn parse_entry(line: &str) -> Result<([u8; 3], String, String), String> {
+    let mut columns = line.splitn(3, ',').map(str::trim);
+    let (Some(oui), Some(vendor)) = (columns.next(), columns.next()) else {
+        return Err(format!("expected 'oui,vendor,device_type', got '{}'", line));
+    };
+    let device_type = columns.next().unwrap_or("");
+
+    let hex: String = oui.chars().filter(|c| !matches!(c, ':' | '-' | '.')).collect();
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(format!("OUI '{}' must be three octets", oui));
+    }
+    let mut bytes = [0u8; 3];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map_err(|_| format!("OUI '{}' is not hex", oui))?;
+    }
+
+    if vendor.is_empty() {
+        return Err(format!("OUI '{}' has no vendor", oui));
+    }
+    if !device_type.is_empty() && !DEVICE_TYPES.contains(&device_type) {
+        return Err(format!(
+            "unknown device type '{}' (expected one of {:?})",
+            device_type, DEVICE_TYPES
+        ));
+    }
+
+    Ok((bytes, vendor.to_string(), device_type.to_string()))
+}
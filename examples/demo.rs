@@ -18,7 +18,7 @@ use cim_network::{
     domain::{
         aggregates::DeviceState,
         ports::{
-            DeviceControlPort, DeviceStats, EventStorePort, PortError,
+            DeviceConfigRequest, DeviceControlPort, DeviceStats, EventStorePort, PortError,
             VendorConfig, VendorDevice,
         },
         value_objects::{DeviceId, MacAddress},
@@ -110,11 +110,16 @@ impl DeviceControlPort for MockUniFiAdapter {
         Ok(())
     }
 
-    async fn apply_config(&self, vendor_id: &str, _config: VendorConfig) -> Result<(), PortError> {
+    async fn apply_config(&self, vendor_id: &str, _config: DeviceConfigRequest) -> Result<(), PortError> {
         println!("  → Applying config to device: {}", vendor_id);
         Ok(())
     }
 
+    async fn apply_raw_config(&self, vendor_id: &str, _config: VendorConfig) -> Result<(), PortError> {
+        println!("  → Applying raw config to device: {}", vendor_id);
+        Ok(())
+    }
+
     async fn restart_device(&self, vendor_id: &str) -> Result<(), PortError> {
         println!("  → Restarting device: {}", vendor_id);
         Ok(())
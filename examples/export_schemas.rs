@@ -0,0 +1,14 @@
+//! Write the JSON Schemas of the event and command wire format
+//!
+//! Usage: `cargo run --example export_schemas -- [dir]` (default `schemas/`)
+
+use cim_network::domain::schemas::write_schemas;
+use std::path::PathBuf;
+
+fn main() -> std::io::Result<()> {
+    let dir = std::env::args().nth(1).map(PathBuf::from).unwrap_or_else(|| PathBuf::from("schemas"));
+    for path in write_schemas(&dir)? {
+        println!("wrote {}", path.display());
+    }
+    Ok(())
+}
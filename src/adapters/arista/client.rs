@@ -0,0 +1,137 @@
+//! Arista eAPI HTTP client
+//!
+//! Talks to the JSON-RPC eAPI (`/command-api`) that EOS serves once
+//! `management api http-commands` is enabled, using HTTP basic
+//! authentication. Switches are addressed by base URL, so one client serves
+//! every switch sharing the same credentials.
+
+use super::types::*;
+use reqwest::Client;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Arista eAPI client
+pub struct AristaClient {
+    /// HTTP client
+    http: Client,
+    /// API user
+    username: String,
+    /// API password
+    password: String,
+    /// Next JSON-RPC request ID
+    next_id: AtomicU64,
+}
+
+impl AristaClient {
+    /// Create a new eAPI client
+    pub fn new(username: &str, password: &str) -> Result<Self, AristaError> {
+        let http = Client::builder()
+            // EOS ships with a self-signed certificate for the HTTPS API
+            .danger_accept_invalid_certs(true)
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| AristaError::Http(e.to_string()))?;
+
+        Ok(Self {
+            http,
+            username: username.to_string(),
+            password: password.to_string(),
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    // =========================================================================
+    // Commands
+    // =========================================================================
+
+    /// Run one show command and deserialize its output
+    pub async fn show<T: serde::de::DeserializeOwned>(&self, host: &str, cmd: &str) -> Result<T, AristaError> {
+        let output = self.run_cmds(host, &[cmd.to_string()]).await?;
+        parse_output(output.into_iter().next(), cmd)
+    }
+
+    /// Apply configuration lines in one `configure` session
+    pub async fn configure(&self, host: &str, lines: &[String]) -> Result<(), AristaError> {
+        let mut cmds = Vec::with_capacity(lines.len() + 3);
+        cmds.push("enable".to_string());
+        cmds.push("configure".to_string());
+        cmds.extend(lines.iter().cloned());
+        cmds.push("end".to_string());
+        self.run_cmds(host, &cmds).await.map(|_| ())
+    }
+
+    /// Reload the switch immediately
+    pub async fn reload(&self, host: &str) -> Result<(), AristaError> {
+        self.run_cmds(host, &["enable".to_string(), "reload now".to_string()])
+            .await
+            .map(|_| ())
+    }
+
+    // =========================================================================
+    // HTTP Helpers
+    // =========================================================================
+
+    /// Run commands with `runCmds` against `{host}/command-api`
+    ///
+    /// EOS runs the batch in one session and stops at the first failing
+    /// command, so either every output is returned or nothing after the
+    /// failure was applied.
+    pub async fn run_cmds(&self, host: &str, cmds: &[String]) -> Result<Vec<serde_json::Value>, AristaError> {
+        let url = format!("{}/command-api", host.trim_end_matches('/'));
+        tracing::debug!("eAPI {} {:?}", url, cmds);
+
+        let request = EapiRequest {
+            jsonrpc: "2.0",
+            method: "runCmds",
+            params: EapiParams { version: 1, cmds, format: "json" },
+            id: self.next_id.fetch_add(1, Ordering::Relaxed).to_string(),
+        };
+
+        let response = self.http
+            .post(&url)
+            .basic_auth(&self.username, Some(&self.password))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| AristaError::Http(e.to_string()))?;
+
+        let status = response.status();
+        let text = response.text().await.map_err(|e| AristaError::Http(e.to_string()))?;
+
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(AristaError::Auth(format!("Rejected by {}", host)));
+        }
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(AristaError::NotFound(format!("{} (is eAPI enabled?)", url)));
+        }
+        if !status.is_success() {
+            return Err(AristaError::Api(format!("{}: {}", status, text)));
+        }
+
+        let response: EapiResponse = serde_json::from_str(&text)
+            .map_err(|e| AristaError::Parse(format!("runCmds: {}", e)))?;
+        if let Some(error) = response.error {
+            let errors = error.command_errors();
+            let detail = if errors.is_empty() { error.message } else { errors.join("; ") };
+            return Err(AristaError::Api(format!("{}: {}", error.code, detail)));
+        }
+        let result = response.result.unwrap_or_default();
+        if result.len() != cmds.len() {
+            return Err(AristaError::Parse(format!(
+                "runCmds returned {} outputs for {} commands",
+                result.len(),
+                cmds.len()
+            )));
+        }
+        Ok(result)
+    }
+}
+
+/// Deserialize the output of one command
+pub fn parse_output<T: serde::de::DeserializeOwned>(
+    output: Option<serde_json::Value>,
+    cmd: &str,
+) -> Result<T, AristaError> {
+    let output = output.ok_or_else(|| AristaError::Parse(format!("No output for '{}'", cmd)))?;
+    serde_json::from_value(output).map_err(|e| AristaError::Parse(format!("{}: {}", cmd, e)))
+}
@@ -0,0 +1,563 @@
+//! # Arista EOS Adapter
+//!
+//! Implements network management ports for Arista EOS switches through the
+//! JSON-RPC eAPI.
+//!
+//! ## Supported Operations
+//!
+//! - Inventory of the configured switches (`show version`, `show hostname`)
+//!   with their LLDP neighbors (`show lldp neighbors`)
+//! - Configuration push through `configure`
+//! - Reload (`reload now`)
+//! - CPU and memory (`show processes top once`), uptime and interface
+//!   statistics (`show interfaces status` / `counters` / `counters errors`)
+//!
+//! ## Device Identity
+//!
+//! EOS has no controller, so every switch is addressed directly by the base
+//! URL of its eAPI (e.g. `https://192.0.2.11`). That base URL is the vendor
+//! ID; the switch's MAC is its system MAC address.
+//!
+//! ## Config Payload
+//!
+//! `apply_config` translates a `DeviceConfigRequest` into EOS configuration
+//! lines. `apply_raw_config` expects the lines themselves, either as a
+//! string or a list of strings, bare or under a `config` key. They are run
+//! between `configure` and `end` in a single eAPI call, which EOS stops at
+//! the first rejected line:
+//!
+//! ```json
+//! { "config": ["hostname leaf1", "vlan 10", " name users"] }
+//! ```
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::domain::aggregates::NetworkDeviceAggregate;
+use crate::domain::ports::*;
+use crate::domain::functor::*;
+use crate::domain::events::*;
+use crate::domain::value_objects::*;
+
+mod client;
+mod types;
+
+pub use client::{parse_output, AristaClient};
+pub use types::*;
+
+/// Commands read for each switch in the inventory
+const INVENTORY_CMDS: [&str; 3] = ["show version", "show hostname", "show lldp neighbors"];
+
+/// Commands read for device statistics
+const STATS_CMDS: [&str; 5] = [
+    "show version",
+    "show processes top once",
+    "show interfaces status",
+    "show interfaces counters",
+    "show interfaces counters errors",
+];
+
+/// Arista EOS adapter
+///
+/// Implements both:
+/// - `DeviceControlPort` for hexagonal architecture
+/// - `VendorExtension` for Kan extension mapping
+pub struct AristaAdapter {
+    /// eAPI client shared by all switches
+    client: Arc<AristaClient>,
+    /// Base URLs of the managed switches
+    hosts: Vec<String>,
+    /// Whether every switch has been reached
+    connected: AtomicBool,
+    /// Mapping from domain DeviceId to vendor ID (base URL)
+    device_mapping: Arc<RwLock<HashMap<DeviceId, String>>>,
+    /// Mapping from vendor ID to domain DeviceId
+    reverse_mapping: Arc<RwLock<HashMap<String, DeviceId>>>,
+}
+
+impl AristaAdapter {
+    /// Create an adapter for switches sharing one set of eAPI credentials
+    ///
+    /// # Arguments
+    /// * `hosts` - eAPI base URLs, e.g. `["https://192.0.2.11"]`
+    /// * `username` / `password` - EOS user allowed to run `configure` and `reload`
+    pub fn new(hosts: &[&str], username: &str, password: &str) -> Result<Self, AristaError> {
+        Ok(Self {
+            client: Arc::new(AristaClient::new(username, password)?),
+            hosts: hosts.iter().map(|h| h.trim_end_matches('/').to_string()).collect(),
+            connected: AtomicBool::new(false),
+            device_mapping: Arc::new(RwLock::new(HashMap::new())),
+            reverse_mapping: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Map a domain device to its vendor ID (eAPI base URL)
+    pub async fn map_device(&self, device_id: DeviceId, vendor_id: String) {
+        let mut mapping = self.device_mapping.write().await;
+        let mut reverse = self.reverse_mapping.write().await;
+        mapping.insert(device_id, vendor_id.clone());
+        reverse.insert(vendor_id, device_id);
+    }
+
+    /// Get vendor ID for a domain device
+    pub async fn get_vendor_id(&self, device_id: DeviceId) -> Option<String> {
+        let mapping = self.device_mapping.read().await;
+        mapping.get(&device_id).cloned()
+    }
+
+    /// Get domain device ID for a vendor ID
+    pub async fn get_device_id(&self, vendor_id: &str) -> Option<DeviceId> {
+        let reverse = self.reverse_mapping.read().await;
+        reverse.get(vendor_id).copied()
+    }
+
+    /// Resolve a vendor ID to one of the configured hosts
+    fn host(&self, vendor_id: &str) -> Result<&str, AristaError> {
+        let vendor_id = vendor_id.trim_end_matches('/');
+        self.hosts
+            .iter()
+            .find(|h| *h == vendor_id)
+            .map(String::as_str)
+            .ok_or_else(|| AristaError::NotFound(format!("Switch {} is not configured", vendor_id)))
+    }
+
+    /// Read one switch's version, hostname and LLDP neighbors
+    async fn read_device(&self, host: &str) -> Result<VendorDevice, AristaError> {
+        let cmds: Vec<String> = INVENTORY_CMDS.iter().map(|c| c.to_string()).collect();
+        let mut outputs = self.client.run_cmds(host, &cmds).await?.into_iter();
+        let version: ShowVersion = parse_output(outputs.next(), INVENTORY_CMDS[0])?;
+        let hostname: ShowHostname = parse_output(outputs.next(), INVENTORY_CMDS[1])?;
+        let lldp: ShowLldpNeighbors = parse_output(outputs.next(), INVENTORY_CMDS[2])?;
+
+        let mac = version.system_mac_address
+            .as_deref()
+            .and_then(|m| MacAddress::parse(m).ok())
+            .ok_or_else(|| AristaError::Parse(format!("No system MAC address from {}", host)))?;
+
+        let mut properties = HashMap::new();
+        if let Some(ref eos) = version.version {
+            properties.insert("eos_version".to_string(), serde_json::json!(eos));
+        }
+        if let Some(ref serial) = version.serial_number {
+            properties.insert("serial_number".to_string(), serde_json::json!(serial));
+        }
+        properties.insert("lldp_neighbors".to_string(), serde_json::json!(lldp.lldp_neighbors));
+
+        Ok(VendorDevice {
+            vendor_id: host.to_string(),
+            device_id: self.get_device_id(host).await,
+            mac,
+            model: version.model_name.unwrap_or_else(|| "EOS".to_string()),
+            name: hostname.hostname,
+            ip_address: host_ip(host),
+            // EOS switches are managed directly; there is no adoption step
+            adopted: true,
+            properties,
+        })
+    }
+}
+
+/// IP address in a base URL such as `https://192.0.2.11:8443`
+fn host_ip(host: &str) -> Option<std::net::IpAddr> {
+    let authority = host.split("://").last()?.split('/').next()?;
+    authority.parse::<std::net::SocketAddr>()
+        .map(|addr| addr.ip())
+        .or_else(|_| authority.parse())
+        .ok()
+}
+
+/// Sort key that orders `Ethernet2` before `Ethernet10` and `Ethernet49/1` after both
+fn interface_sort_key(name: &str) -> (String, Vec<u32>) {
+    let split = name.find(|c: char| c.is_ascii_digit()).unwrap_or(name.len());
+    let (prefix, numbers) = name.split_at(split);
+    let numbers = numbers.split(['/', '.']).filter_map(|n| n.parse().ok()).collect();
+    (prefix.to_string(), numbers)
+}
+
+/// Extract configuration lines from a vendor config payload
+fn config_lines(config: &VendorConfig) -> Result<Vec<String>, AristaError> {
+    let source = config.payload.get("config").unwrap_or(&config.payload);
+
+    let lines: Vec<String> = match source {
+        serde_json::Value::String(text) => text.lines().map(str::to_string).collect(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(|v| {
+                v.as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| AristaError::Parse("Config lines must be strings".to_string()))
+            })
+            .collect::<Result<_, _>>()?,
+        _ => {
+            return Err(AristaError::Parse(format!(
+                "Unsupported config payload for type '{}'",
+                config.config_type
+            )))
+        }
+    };
+
+    Ok(lines.into_iter().filter(|l| !l.trim().is_empty()).collect())
+}
+
+/// Generate EOS configuration for a device aggregate
+pub fn generate_eos_config(device: &NetworkDeviceAggregate) -> Vec<String> {
+    eos_config_lines(&DeviceConfigRequest::from_device(device))
+}
+
+/// Translate a typed config request into EOS configuration lines
+pub fn eos_config_lines(request: &DeviceConfigRequest) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if let Some(ref hostname) = request.hostname {
+        lines.push(format!("hostname {}", hostname.split_whitespace().collect::<Vec<_>>().join("-")));
+    }
+
+    for vlan in &request.vlans {
+        lines.push(format!("vlan {}", vlan.id));
+        lines.push(format!(" name {}", vlan.name));
+    }
+
+    for iface in &request.interfaces {
+        lines.push(format!("interface {}", iface.name));
+        // Front-panel ports and port-channels are switched until told otherwise
+        let switched = ["ethernet", "port-channel"]
+            .iter()
+            .any(|prefix| iface.name.to_ascii_lowercase().starts_with(prefix));
+        match (iface.ip_address, iface.prefix_len) {
+            (Some(addr), Some(prefix)) => {
+                if switched {
+                    lines.push(" no switchport".to_string());
+                }
+                let family = if addr.is_ipv4() { "ip" } else { "ipv6" };
+                lines.push(format!(" {} address {}/{}", family, addr, prefix));
+            }
+            _ => {
+                if let Some(vlan_id) = iface.vlan_id {
+                    lines.push(" switchport mode access".to_string());
+                    lines.push(format!(" switchport access vlan {}", vlan_id));
+                }
+            }
+        }
+        if let Some(mtu) = iface.mtu {
+            lines.push(format!(" mtu {}", mtu));
+        }
+        lines.push(if iface.enabled { " no shutdown" } else { " shutdown" }.to_string());
+    }
+
+    for server in &request.ntp_servers {
+        lines.push(format!("ntp server {}", server));
+    }
+
+    if !request.dns_servers.is_empty() {
+        let servers: Vec<String> = request.dns_servers.iter().map(|s| s.to_string()).collect();
+        lines.push(format!("ip name-server vrf default {}", servers.join(" ")));
+    }
+
+    lines
+}
+
+/// Map Arista errors onto port errors
+fn port_error(error: AristaError) -> PortError {
+    match error {
+        AristaError::Auth(msg) => PortError::AuthenticationFailed(msg),
+        AristaError::Http(msg) => PortError::ConnectionFailed(msg),
+        other => PortError::VendorError(other.to_string()),
+    }
+}
+
+#[async_trait]
+impl DeviceControlPort for AristaAdapter {
+    fn vendor_name(&self) -> &str {
+        "arista"
+    }
+
+    async fn connect(&self) -> Result<(), PortError> {
+        for host in &self.hosts {
+            self.client.show::<ShowHostname>(host, "show hostname").await.map_err(port_error)?;
+        }
+        self.connected.store(true, Ordering::SeqCst);
+        tracing::info!("Connected to {} EOS switches", self.hosts.len());
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> Result<(), PortError> {
+        // eAPI is stateless; nothing to tear down
+        self.connected.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError> {
+        let mut vendor_devices = Vec::new();
+        for host in &self.hosts {
+            match self.read_device(host).await {
+                Ok(device) => vendor_devices.push(device),
+                // An unreachable switch is simply not reported
+                Err(AristaError::Http(e)) => tracing::warn!("EOS switch {} unreachable: {}", host, e),
+                Err(e) => return Err(port_error(e)),
+            }
+        }
+        Ok(vendor_devices)
+    }
+
+    async fn get_device(&self, vendor_id: &str) -> Result<VendorDevice, PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        self.read_device(host).await.map_err(port_error)
+    }
+
+    async fn adopt_device(&self, vendor_id: &str) -> Result<(), PortError> {
+        // No controller adoption on EOS; just verify we can manage the switch
+        let host = self.host(vendor_id).map_err(port_error)?;
+        self.client.show::<ShowHostname>(host, "show hostname").await.map(|_| ()).map_err(port_error)
+    }
+
+    async fn apply_config(&self, vendor_id: &str, config: DeviceConfigRequest) -> Result<(), PortError> {
+        config.validate()?;
+        if !config.lags.is_empty() {
+            return Err(PortError::NotSupported("Arista adapter cannot configure LAGs".to_string()));
+        }
+        self.apply_raw_config(vendor_id, VendorConfig {
+            config_type: "eos".to_string(),
+            payload: serde_json::json!({ "config": eos_config_lines(&config) }),
+        })
+        .await
+    }
+
+    async fn apply_raw_config(&self, vendor_id: &str, config: VendorConfig) -> Result<(), PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        let lines = config_lines(&config).map_err(port_error)?;
+
+        self.client.configure(host, &lines).await.map_err(port_error)?;
+
+        tracing::info!("Applied {} EOS config lines to {}", lines.len(), host);
+        Ok(())
+    }
+
+    async fn restart_device(&self, vendor_id: &str) -> Result<(), PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        self.client.reload(host).await.map_err(port_error)
+    }
+
+    async fn get_device_stats(&self, vendor_id: &str) -> Result<DeviceStats, PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        let cmds: Vec<String> = STATS_CMDS.iter().map(|c| c.to_string()).collect();
+        let mut outputs = self.client.run_cmds(host, &cmds).await.map_err(port_error)?.into_iter();
+
+        let version: ShowVersion = parse_output(outputs.next(), STATS_CMDS[0]).map_err(port_error)?;
+        let top: ShowProcessesTop = parse_output(outputs.next(), STATS_CMDS[1]).map_err(port_error)?;
+        let status: ShowInterfacesStatus = parse_output(outputs.next(), STATS_CMDS[2]).map_err(port_error)?;
+        let counters: ShowInterfacesCounters = parse_output(outputs.next(), STATS_CMDS[3]).map_err(port_error)?;
+        let errors: ShowInterfacesCountersErrors = parse_output(outputs.next(), STATS_CMDS[4]).map_err(port_error)?;
+
+        let mut names: Vec<&String> = status.interface_statuses.keys().collect();
+        names.sort_by_key(|name| interface_sort_key(name));
+
+        Ok(DeviceStats {
+            uptime_seconds: version.uptime.map(|s| s as u64).unwrap_or(0),
+            cpu_percent: top.cpu_percent(),
+            memory_percent: top.memory_percent(),
+            temperature_celsius: None,
+            port_stats: names.into_iter().map(|name| {
+                let port = &status.interface_statuses[name];
+                let counters = counters.interfaces.get(name).cloned().unwrap_or_default();
+                let errors = errors.interface_error_counters.get(name).cloned().unwrap_or_default();
+                PortStats {
+                    port_id: PortId::new(name.clone()),
+                    link_up: port.is_connected(),
+                    speed: port.speed_mbps().and_then(LinkSpeed::from_mbps),
+                    rx_bytes: counters.in_octets,
+                    tx_bytes: counters.out_octets,
+                    rx_errors: errors.in_errors,
+                    tx_errors: errors.out_errors,
+                    rx_bps: None,
+                    tx_bps: None,
+                }
+            }).collect(),
+        })
+    }
+}
+
+impl VendorExtension for AristaAdapter {
+    fn vendor_name(&self) -> &str {
+        "arista"
+    }
+
+    fn extend(&self, domain_obj: &DomainObject) -> Result<VendorRepresentation, FunctorError> {
+        match domain_obj {
+            DomainObject::Device(device) => {
+                let payload = serde_json::json!({
+                    "hostname": device.name(),
+                    "mac": device.mac().to_string(),
+                    "state": device.state().name(),
+                    "config": generate_eos_config(device),
+                });
+
+                Ok(VendorRepresentation {
+                    vendor: "arista".to_string(),
+                    vendor_id: device.vendor_id().unwrap_or("pending").to_string(),
+                    device_id: device.id(),
+                    payload,
+                })
+            }
+            _ => Err(FunctorError::MappingFailed(
+                "Only Device objects can be extended to Arista".to_string()
+            )),
+        }
+    }
+
+    fn to_domain_event(&self, _vendor_event: &serde_json::Value) -> Result<NetworkEvent, FunctorError> {
+        // eAPI is request/response only; EOS pushes nothing over it to translate
+        Err(FunctorError::MappingFailed(
+            "EOS does not emit events over eAPI".to_string()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ==========================================================================
+    // Parser Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_processes_top_percentages() {
+        let top: ShowProcessesTop = serde_json::from_value(serde_json::json!({
+            "cpuInfo": { "%Cpu(s)": { "idle": 92.5, "user": 5.0, "system": 2.5 } },
+            "memInfo": { "physicalMem": { "total": 8000000, "free": 2000000 } },
+        }))
+        .unwrap();
+
+        assert_eq!(top.cpu_percent(), Some(7.5));
+        assert_eq!(top.memory_percent(), Some(75.0));
+        assert_eq!(ShowProcessesTop::default().cpu_percent(), None);
+    }
+
+    #[test]
+    fn test_interface_status_speed() {
+        let port = InterfaceStatus {
+            link_status: Some("connected".to_string()),
+            bandwidth: Some(25_000_000_000),
+        };
+        assert!(port.is_connected());
+        assert_eq!(port.speed_mbps(), Some(25000));
+
+        let down = InterfaceStatus { link_status: Some("notconnect".to_string()), bandwidth: Some(0) };
+        assert!(!down.is_connected());
+        assert_eq!(down.speed_mbps(), None);
+    }
+
+    #[test]
+    fn test_interface_sort_key() {
+        let mut names = vec!["Ethernet10", "Port-Channel1", "Ethernet49/1", "Ethernet2", "Management1"];
+        names.sort_by_key(|name| interface_sort_key(name));
+        assert_eq!(names, ["Ethernet2", "Ethernet10", "Ethernet49/1", "Management1", "Port-Channel1"]);
+    }
+
+    #[test]
+    fn test_command_errors_from_eapi_error() {
+        let error: EapiError = serde_json::from_value(serde_json::json!({
+            "code": 1002,
+            "message": "CLI command 3 of 4 'vlan 5000' failed: invalid command",
+            "data": [{}, {}, { "errors": ["Invalid input (at token 1: '5000')"] }],
+        }))
+        .unwrap();
+
+        assert_eq!(error.command_errors(), ["Invalid input (at token 1: '5000')"]);
+    }
+
+    // ==========================================================================
+    // Config Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_typed_request_to_eos_lines() {
+        let request = DeviceConfigRequest::default()
+            .with_hostname("leaf 1")
+            .with_vlan(VlanConfig::new(10, "users").unwrap())
+            .with_interface(InterfaceConfig {
+                name: "Ethernet49/1".to_string(),
+                ip_address: Some("10.0.0.1".parse().unwrap()),
+                prefix_len: Some(31),
+                vlan_id: None,
+                enabled: true,
+                mtu: None,
+                speed: None,
+            })
+            .with_interface(InterfaceConfig {
+                name: "Ethernet1".to_string(),
+                ip_address: None,
+                prefix_len: None,
+                vlan_id: Some(10),
+                enabled: false,
+                mtu: None,
+                speed: None,
+            })
+            .with_ntp_server("0.pool.ntp.org")
+            .with_dns_server("1.1.1.1".parse().unwrap())
+            .with_dns_server("9.9.9.9".parse().unwrap());
+
+        assert_eq!(eos_config_lines(&request), [
+            "hostname leaf-1",
+            "vlan 10",
+            " name users",
+            "interface Ethernet49/1",
+            " no switchport",
+            " ip address 10.0.0.1/31",
+            " no shutdown",
+            "interface Ethernet1",
+            " switchport mode access",
+            " switchport access vlan 10",
+            " shutdown",
+            "ntp server 0.pool.ntp.org",
+            "ip name-server vrf default 1.1.1.1 9.9.9.9",
+        ]);
+    }
+
+    #[test]
+    fn test_routed_svi_keeps_switchport() {
+        let request = DeviceConfigRequest::default().with_interface(InterfaceConfig {
+            name: "Vlan10".to_string(),
+            ip_address: Some("2001:db8::1".parse().unwrap()),
+            prefix_len: Some(64),
+            vlan_id: None,
+            enabled: true,
+            mtu: None,
+            speed: None,
+        });
+
+        assert_eq!(eos_config_lines(&request), ["interface Vlan10", " ipv6 address 2001:db8::1/64", " no shutdown"]);
+    }
+
+    #[test]
+    fn test_jumbo_mtu_is_rendered() {
+        let request = DeviceConfigRequest::default().with_interface(InterfaceConfig {
+            name: "Ethernet49/1".to_string(),
+            ip_address: None,
+            prefix_len: None,
+            vlan_id: None,
+            enabled: true,
+            mtu: Some(9214),
+            speed: None,
+        });
+
+        assert!(request.validate().is_ok());
+        assert_eq!(eos_config_lines(&request), ["interface Ethernet49/1", " mtu 9214", " no shutdown"]);
+    }
+
+    #[test]
+    fn test_config_lines_rejects_objects() {
+        let config = VendorConfig {
+            config_type: "eos".to_string(),
+            payload: serde_json::json!({ "config": { "hostname": "leaf1" } }),
+        };
+        assert!(matches!(config_lines(&config), Err(AristaError::Parse(_))));
+    }
+}
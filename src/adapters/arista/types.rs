@@ -0,0 +1,260 @@
+//! Arista eAPI types
+//!
+//! eAPI answers `runCmds` with one JSON object per command, in the format
+//! EOS uses for `| json` output: camelCase keys, counters as numbers and
+//! rates in bits per second. Only the fields the adapter uses are modelled;
+//! everything else is ignored.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// JSON-RPC 2.0 request for `runCmds`
+#[derive(Debug, Clone, Serialize)]
+pub struct EapiRequest<'a> {
+    /// Always `2.0`
+    pub jsonrpc: &'static str,
+    /// Always `runCmds`
+    pub method: &'static str,
+    /// Commands and output format
+    pub params: EapiParams<'a>,
+    /// Request ID echoed in the response
+    pub id: String,
+}
+
+/// Parameters of a `runCmds` request
+#[derive(Debug, Clone, Serialize)]
+pub struct EapiParams<'a> {
+    /// eAPI version, always 1
+    pub version: u32,
+    /// Commands, run in order in one CLI session
+    pub cmds: &'a [String],
+    /// Output format, always `json`
+    pub format: &'static str,
+}
+
+/// JSON-RPC 2.0 response to `runCmds`
+#[derive(Debug, Clone, Deserialize)]
+pub struct EapiResponse {
+    /// One output object per command, when every command succeeded
+    pub result: Option<Vec<serde_json::Value>>,
+    /// Failure of the command batch
+    pub error: Option<EapiError>,
+}
+
+/// JSON-RPC error returned when a command fails
+#[derive(Debug, Clone, Deserialize)]
+pub struct EapiError {
+    /// eAPI error code, e.g. 1002 for an invalid command
+    pub code: i64,
+    /// Summary message
+    pub message: String,
+    /// Per-command output up to the failing command; its `errors` explain why
+    #[serde(default)]
+    pub data: Vec<serde_json::Value>,
+}
+
+impl EapiError {
+    /// Error messages reported by the failing command
+    pub fn command_errors(&self) -> Vec<String> {
+        self.data
+            .iter()
+            .filter_map(|output| output.get("errors")?.as_array())
+            .flatten()
+            .filter_map(|e| e.as_str().map(str::to_string))
+            .collect()
+    }
+}
+
+/// `show version`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShowVersion {
+    /// Hardware model, e.g. `DCS-7050SX3-48YC8`
+    pub model_name: Option<String>,
+    /// EOS version, e.g. `4.28.3M`
+    pub version: Option<String>,
+    /// Chassis serial number
+    pub serial_number: Option<String>,
+    /// System MAC address
+    pub system_mac_address: Option<String>,
+    /// Seconds since boot
+    pub uptime: Option<f64>,
+}
+
+/// `show hostname`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShowHostname {
+    /// Short hostname
+    pub hostname: String,
+    /// Fully qualified name
+    pub fqdn: Option<String>,
+}
+
+/// `show lldp neighbors`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShowLldpNeighbors {
+    /// One entry per neighbor seen on a local port
+    #[serde(default)]
+    pub lldp_neighbors: Vec<LldpNeighbor>,
+}
+
+/// `show lldp neighbors` entry
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LldpNeighbor {
+    /// Local port, e.g. `Ethernet49/1`
+    pub port: String,
+    /// Neighbor system name
+    pub neighbor_device: String,
+    /// Neighbor port
+    pub neighbor_port: String,
+}
+
+/// `show processes top once`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShowProcessesTop {
+    /// CPU summary keyed by `%Cpu(s)`
+    #[serde(default)]
+    pub cpu_info: HashMap<String, CpuInfo>,
+    /// Memory summary
+    pub mem_info: Option<MemInfo>,
+}
+
+impl ShowProcessesTop {
+    /// Busy CPU in percent
+    pub fn cpu_percent(&self) -> Option<f64> {
+        let idle = self.cpu_info.values().next()?.idle?;
+        Some((100.0 - idle).clamp(0.0, 100.0))
+    }
+
+    /// Used physical memory in percent
+    pub fn memory_percent(&self) -> Option<f64> {
+        let mem = self.mem_info.as_ref()?.physical_mem.as_ref()?;
+        let total = mem.total? as f64;
+        let used = match mem.used {
+            Some(used) => used,
+            None => mem.total?.saturating_sub(mem.free?),
+        } as f64;
+        (total > 0.0).then(|| used / total * 100.0)
+    }
+}
+
+/// CPU line of `top`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CpuInfo {
+    /// Idle CPU in percent
+    pub idle: Option<f64>,
+}
+
+/// Memory lines of `top`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemInfo {
+    /// Physical memory, in KiB
+    pub physical_mem: Option<PhysicalMem>,
+}
+
+/// Physical memory line of `top`, in KiB
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PhysicalMem {
+    /// Total memory
+    pub total: Option<u64>,
+    /// Free memory
+    pub free: Option<u64>,
+    /// Used memory
+    pub used: Option<u64>,
+}
+
+/// `show interfaces status`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShowInterfacesStatus {
+    /// Status keyed by interface name
+    #[serde(default)]
+    pub interface_statuses: HashMap<String, InterfaceStatus>,
+}
+
+/// `show interfaces status` entry
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterfaceStatus {
+    /// `connected`, `notconnect`, `disabled`, `errdisabled`, ...
+    pub link_status: Option<String>,
+    /// Operational rate in bits per second
+    pub bandwidth: Option<u64>,
+}
+
+impl InterfaceStatus {
+    /// Whether the link is up
+    pub fn is_connected(&self) -> bool {
+        self.link_status.as_deref() == Some("connected")
+    }
+
+    /// Operational rate in Mbps
+    pub fn speed_mbps(&self) -> Option<u32> {
+        self.bandwidth.filter(|&bps| bps > 0).map(|bps| (bps / 1_000_000) as u32)
+    }
+}
+
+/// `show interfaces counters`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShowInterfacesCounters {
+    /// Counters keyed by interface name
+    #[serde(default)]
+    pub interfaces: HashMap<String, InterfaceCounters>,
+}
+
+/// `show interfaces counters` entry
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterfaceCounters {
+    /// Received bytes
+    #[serde(default)]
+    pub in_octets: u64,
+    /// Transmitted bytes
+    #[serde(default)]
+    pub out_octets: u64,
+}
+
+/// `show interfaces counters errors`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShowInterfacesCountersErrors {
+    /// Error counters keyed by interface name
+    #[serde(default)]
+    pub interface_error_counters: HashMap<String, InterfaceErrorCounters>,
+}
+
+/// `show interfaces counters errors` entry
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterfaceErrorCounters {
+    /// Receive errors
+    #[serde(default)]
+    pub in_errors: u64,
+    /// Transmit errors
+    #[serde(default)]
+    pub out_errors: u64,
+}
+
+/// Arista adapter error
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum AristaError {
+    /// HTTP/network error
+    #[error("HTTP error: {0}")]
+    Http(String),
+    /// Credentials were rejected
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+    /// EOS rejected a command
+    #[error("eAPI error: {0}")]
+    Api(String),
+    /// Response or config payload could not be parsed
+    #[error("Parse error: {0}")]
+    Parse(String),
+    /// No such switch
+    #[error("Not found: {0}")]
+    NotFound(String),
+}
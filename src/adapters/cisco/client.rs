@@ -0,0 +1,148 @@
+//! Cisco IOS CLI transport
+//!
+//! IOS is driven over SSH in two ways:
+//! - exec channels for single `show` commands (one command per channel)
+//! - an interactive shell for anything that needs modes or prompts
+//!   (`configure terminal`, `reload`)
+//!
+//! `CliTransport` abstracts both so the adapter can be exercised against a
+//! mocked device in tests.
+
+use super::types::CiscoError;
+use async_trait::async_trait;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+/// Transport for running IOS CLI commands on a host
+#[async_trait]
+pub trait CliTransport: Send + Sync {
+    /// Run a single exec-mode command and return its output
+    async fn exec(&self, host: &str, command: &str) -> Result<String, CiscoError>;
+
+    /// Send lines through an interactive shell and return the session transcript
+    async fn shell(&self, host: &str, lines: &[String]) -> Result<String, CiscoError>;
+}
+
+/// SSH credentials and connection settings
+#[derive(Debug, Clone)]
+pub struct SshCredentials {
+    /// Login username
+    pub username: String,
+    /// Login password
+    pub password: String,
+    /// SSH port (defaults to 22)
+    pub port: u16,
+    /// Per-operation timeout
+    pub timeout: Duration,
+}
+
+impl SshCredentials {
+    /// Create credentials with the default port and timeout
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            username: username.into(),
+            password: password.into(),
+            port: 22,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// `CliTransport` over SSH using libssh2
+///
+/// libssh2 is blocking, so every operation runs on the blocking thread pool.
+pub struct SshTransport {
+    credentials: SshCredentials,
+}
+
+impl SshTransport {
+    /// Create a new SSH transport
+    pub fn new(credentials: SshCredentials) -> Self {
+        Self { credentials }
+    }
+
+    /// Open an authenticated session to `host`
+    pub(crate) fn open_session(credentials: &SshCredentials, host: &str) -> Result<ssh2::Session, CiscoError> {
+        let tcp = TcpStream::connect((host, credentials.port))
+            .map_err(|e| CiscoError::Ssh(format!("connect {}:{}: {}", host, credentials.port, e)))?;
+
+        let mut session = ssh2::Session::new().map_err(|e| CiscoError::Ssh(e.to_string()))?;
+        session.set_timeout(credentials.timeout.as_millis().min(u32::MAX as u128) as u32);
+        session.set_tcp_stream(tcp);
+        session.handshake().map_err(|e| CiscoError::Ssh(format!("handshake with {}: {}", host, e)))?;
+
+        session
+            .userauth_password(&credentials.username, &credentials.password)
+            .map_err(|e| CiscoError::Auth(format!("{}@{}: {}", credentials.username, host, e)))?;
+
+        Ok(session)
+    }
+
+    fn exec_blocking(credentials: &SshCredentials, host: &str, command: &str) -> Result<String, CiscoError> {
+        let session = Self::open_session(credentials, host)?;
+        let mut channel = session.channel_session().map_err(|e| CiscoError::Ssh(e.to_string()))?;
+
+        channel.exec(command).map_err(|e| CiscoError::Ssh(format!("exec '{}': {}", command, e)))?;
+
+        let mut output = String::new();
+        channel
+            .read_to_string(&mut output)
+            .map_err(|e| CiscoError::Ssh(format!("read '{}': {}", command, e)))?;
+        let _ = channel.wait_close();
+
+        Ok(output)
+    }
+
+    fn shell_blocking(credentials: &SshCredentials, host: &str, lines: &[String]) -> Result<String, CiscoError> {
+        let session = Self::open_session(credentials, host)?;
+        let mut channel = session.channel_session().map_err(|e| CiscoError::Ssh(e.to_string()))?;
+
+        channel
+            .request_pty("vt100", None, None)
+            .map_err(|e| CiscoError::Ssh(format!("pty: {}", e)))?;
+        channel.shell().map_err(|e| CiscoError::Ssh(format!("shell: {}", e)))?;
+
+        let mut script = String::from("terminal length 0\n");
+        for line in lines {
+            script.push_str(line);
+            script.push('\n');
+        }
+        script.push_str("exit\n");
+
+        channel
+            .write_all(script.as_bytes())
+            .map_err(|e| CiscoError::Ssh(format!("write: {}", e)))?;
+
+        let mut output = String::new();
+        channel
+            .read_to_string(&mut output)
+            .map_err(|e| CiscoError::Ssh(format!("read: {}", e)))?;
+        let _ = channel.wait_close();
+
+        Ok(output)
+    }
+}
+
+#[async_trait]
+impl CliTransport for SshTransport {
+    async fn exec(&self, host: &str, command: &str) -> Result<String, CiscoError> {
+        let credentials = self.credentials.clone();
+        let host = host.to_string();
+        let command = command.to_string();
+
+        tokio::task::spawn_blocking(move || Self::exec_blocking(&credentials, &host, &command))
+            .await
+            .map_err(|e| CiscoError::Ssh(format!("SSH task failed: {}", e)))?
+    }
+
+    async fn shell(&self, host: &str, lines: &[String]) -> Result<String, CiscoError> {
+        let credentials = self.credentials.clone();
+        let host = host.to_string();
+        let lines = lines.to_vec();
+
+        tokio::task::spawn_blocking(move || Self::shell_blocking(&credentials, &host, &lines))
+            .await
+            .map_err(|e| CiscoError::Ssh(format!("SSH task failed: {}", e)))?
+    }
+}
@@ -0,0 +1,856 @@
+//! # Cisco IOS Adapter
+//!
+//! Implements network management ports for Cisco IOS / IOS-XE devices over SSH.
+//!
+//! ## Supported Operations
+//!
+//! - Neighbor discovery (CDP, falling back to LLDP)
+//! - Configuration push through `configure terminal`
+//! - Port-channels with `channel-group` members from `LagConfig`
+//! - Access and trunk switchport generation (`CiscoIosSwitchConfig`)
+//! - Reload
+//! - CPU, memory, uptime and interface statistics
+//!
+//! ## Device Identity
+//!
+//! IOS has no controller, so every device is addressed directly. The adapter
+//! is seeded with one reachable host; neighbors discovered from it are
+//! reported with their management address as the vendor ID, and all later
+//! operations connect to that address with the same credentials.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::domain::aggregates::NetworkDeviceAggregate;
+use crate::domain::ports::*;
+use crate::domain::functor::*;
+use crate::domain::events::*;
+use crate::domain::value_objects::*;
+
+/// Sync host registry for use in synchronous trait methods
+type SyncHostRegistry = std::sync::RwLock<HashMap<String, DeviceId>>;
+
+mod client;
+mod switchport;
+mod types;
+
+pub use client::{CliTransport, SshCredentials, SshTransport};
+pub use switchport::{AccessPort, CiscoIosSwitchConfig, SwitchportError, TrunkPort};
+pub use types::*;
+
+/// Cisco IOS adapter
+///
+/// Implements both:
+/// - `DeviceControlPort` for hexagonal architecture
+/// - `VendorExtension` for Kan extension mapping
+pub struct CiscoIosAdapter {
+    /// CLI transport (SSH in production)
+    transport: Arc<dyn CliTransport>,
+    /// Host used for discovery and connectivity checks
+    seed_host: String,
+    /// Whether the seed host has been reached
+    connected: AtomicBool,
+    /// Mapping from domain DeviceId to vendor ID (management address)
+    device_mapping: Arc<RwLock<HashMap<DeviceId, String>>>,
+    /// Mapping from vendor ID to domain DeviceId
+    reverse_mapping: Arc<RwLock<HashMap<String, DeviceId>>>,
+    /// Mapping from syslog host to domain DeviceId for event translation
+    host_registry: Arc<SyncHostRegistry>,
+}
+
+impl CiscoIosAdapter {
+    /// Create a new Cisco adapter using SSH
+    pub fn new(seed_host: &str, credentials: SshCredentials) -> Self {
+        Self::with_transport(seed_host, Arc::new(SshTransport::new(credentials)))
+    }
+
+    /// Create a Cisco adapter with a custom CLI transport
+    pub fn with_transport(seed_host: &str, transport: Arc<dyn CliTransport>) -> Self {
+        Self {
+            transport,
+            seed_host: seed_host.to_string(),
+            connected: AtomicBool::new(false),
+            device_mapping: Arc::new(RwLock::new(HashMap::new())),
+            reverse_mapping: Arc::new(RwLock::new(HashMap::new())),
+            host_registry: Arc::new(std::sync::RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Map a domain device to its Cisco vendor ID (management address)
+    pub async fn map_device(&self, device_id: DeviceId, vendor_id: String) {
+        let mut mapping = self.device_mapping.write().await;
+        let mut reverse = self.reverse_mapping.write().await;
+        mapping.insert(device_id, vendor_id.clone());
+        reverse.insert(vendor_id.clone(), device_id);
+        self.register_host(&vendor_id, device_id);
+    }
+
+    /// Register a syslog host to device ID mapping
+    pub fn register_host(&self, host: &str, device_id: DeviceId) {
+        if let Ok(mut registry) = self.host_registry.write() {
+            registry.insert(host.to_string(), device_id);
+        }
+    }
+
+    /// Look up device ID by syslog host (synchronous for trait methods)
+    pub fn get_device_by_host(&self, host: &str) -> Option<DeviceId> {
+        self.host_registry.read()
+            .ok()
+            .and_then(|reg| reg.get(host).copied())
+    }
+
+    /// Get domain device ID for a Cisco vendor ID
+    pub async fn get_device_id(&self, vendor_id: &str) -> Option<DeviceId> {
+        let reverse = self.reverse_mapping.read().await;
+        reverse.get(vendor_id).copied()
+    }
+
+    /// Run an exec command, failing on IOS `%` errors
+    async fn exec_checked(&self, host: &str, command: &str) -> Result<String, CiscoError> {
+        let output = self.transport.exec(host, command).await?;
+        match find_cli_error(&output) {
+            Some(message) => Err(CiscoError::CommandRejected {
+                command: command.to_string(),
+                message: message.to_string(),
+            }),
+            None => Ok(output),
+        }
+    }
+
+    /// Discover neighbors of the seed host via CDP, falling back to LLDP
+    async fn discover_neighbors(&self) -> Result<Vec<CiscoNeighbor>, CiscoError> {
+        let cdp = match self.exec_checked(&self.seed_host, "show cdp neighbors detail").await {
+            Ok(output) => parse_cdp_neighbors_detail(&output),
+            Err(CiscoError::CommandRejected { message, .. }) => {
+                tracing::debug!("CDP unavailable on {}: {}", self.seed_host, message);
+                Vec::new()
+            }
+            Err(e) => return Err(e),
+        };
+
+        if !cdp.is_empty() {
+            return Ok(cdp);
+        }
+
+        let output = self.exec_checked(&self.seed_host, "show lldp neighbors detail").await?;
+        Ok(parse_lldp_neighbors_detail(&output))
+    }
+
+    /// Convert a neighbor to vendor device representation
+    fn to_vendor_device(
+        &self,
+        neighbor: &CiscoNeighbor,
+        mac: MacAddress,
+        device_id: Option<DeviceId>,
+    ) -> VendorDevice {
+        let mut properties = HashMap::new();
+        properties.insert("cisco_device_id".to_string(), serde_json::json!(neighbor.device_id));
+        if let Some(ref local) = neighbor.local_interface {
+            properties.insert("local_interface".to_string(), serde_json::json!(local));
+        }
+        if let Some(ref remote) = neighbor.remote_interface {
+            properties.insert("remote_interface".to_string(), serde_json::json!(remote));
+        }
+        if let Some(ref version) = neighbor.software_version {
+            properties.insert("software_version".to_string(), serde_json::json!(version));
+        }
+
+        VendorDevice {
+            vendor_id: neighbor_vendor_id(neighbor),
+            device_id,
+            mac,
+            model: neighbor.platform.clone().unwrap_or_else(|| "unknown".to_string()),
+            name: neighbor.device_id.clone(),
+            ip_address: neighbor.ip_address,
+            // IOS devices are managed directly; there is no adoption step
+            adopted: true,
+            properties,
+        }
+    }
+}
+
+/// Vendor ID for a neighbor: its management address, else its device ID
+fn neighbor_vendor_id(neighbor: &CiscoNeighbor) -> String {
+    neighbor.ip_address
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| neighbor.device_id.clone())
+}
+
+/// Extract IOS configuration lines from a vendor config payload
+///
+/// Accepts either the `extend` payload (`{"config": [..]}`), a bare array of
+/// lines, or a single newline-separated string.
+fn config_lines(config: &VendorConfig) -> Result<Vec<String>, CiscoError> {
+    let source = config.payload.get("config").unwrap_or(&config.payload);
+
+    let lines: Vec<String> = match source {
+        serde_json::Value::String(text) => text.lines().map(str::to_string).collect(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(|v| {
+                v.as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| CiscoError::Parse("Config lines must be strings".to_string()))
+            })
+            .collect::<Result<_, _>>()?,
+        _ => {
+            return Err(CiscoError::Parse(format!(
+                "Unsupported config payload for type '{}'",
+                config.config_type
+            )))
+        }
+    };
+
+    Ok(lines.into_iter().filter(|l| !l.trim().is_empty()).collect())
+}
+
+/// Generate IOS configuration for a device aggregate
+pub fn generate_ios_config(device: &NetworkDeviceAggregate) -> Vec<String> {
+    ios_config_lines(&DeviceConfigRequest::from_device(device))
+}
+
+/// Translate a typed config request into IOS configuration lines
+pub fn ios_config_lines(request: &DeviceConfigRequest) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if let Some(ref hostname) = request.hostname {
+        lines.push(format!("hostname {}", hostname.split_whitespace().collect::<Vec<_>>().join("-")));
+    }
+
+    for vlan in &request.vlans {
+        lines.push(format!("vlan {}", vlan.id));
+        lines.push(format!(" name {}", vlan.name));
+    }
+
+    for iface in &request.interfaces {
+        lines.push(format!("interface {}", iface.name));
+        match (iface.ip_address, iface.prefix_len) {
+            (Some(std::net::IpAddr::V4(addr)), Some(prefix)) => {
+                if let Ok(net) = ipnetwork::Ipv4Network::new(addr, prefix) {
+                    lines.push(format!(" ip address {} {}", addr, net.mask()));
+                }
+            }
+            (Some(std::net::IpAddr::V6(addr)), Some(prefix)) => {
+                lines.push(format!(" ipv6 address {}/{}", addr, prefix));
+            }
+            _ => {
+                if let Some(vlan_id) = iface.vlan_id {
+                    lines.push(" switchport mode access".to_string());
+                    lines.push(format!(" switchport access vlan {}", vlan_id));
+                }
+            }
+        }
+        if let Some(mtu) = iface.mtu {
+            lines.push(format!(" mtu {}", mtu));
+        }
+        if let Some(speed) = iface.speed {
+            lines.push(format!(" speed {}", speed.mbps()));
+        }
+        if let Some(lag) = LagConfig::containing(&request.lags, &iface.name) {
+            lines.push(format!(" channel-group {} mode {}", lag.id, channel_group_mode(lag.mode)));
+        }
+        lines.push(if iface.enabled { " no shutdown" } else { " shutdown" }.to_string());
+    }
+
+    for lag in &request.lags {
+        lines.push(format!("interface Port-channel{}", lag.id));
+        lines.push(" no shutdown".to_string());
+    }
+    if let Some(policy) = request.lags.iter().find_map(|lag| lag.hash_policy) {
+        lines.push(format!("port-channel load-balance {}", load_balance_method(policy)));
+    }
+
+    for server in &request.ntp_servers {
+        lines.push(format!("ntp server {}", server));
+    }
+
+    if !request.dns_servers.is_empty() {
+        let servers: Vec<String> = request.dns_servers.iter().map(|s| s.to_string()).collect();
+        lines.push(format!("ip name-server {}", servers.join(" ")));
+    }
+
+    lines
+}
+
+/// `channel-group ... mode` keyword for an LACP mode
+fn channel_group_mode(mode: LacpMode) -> &'static str {
+    match mode {
+        LacpMode::Active => "active",
+        LacpMode::Passive => "passive",
+        LacpMode::Static => "on",
+    }
+}
+
+/// `port-channel load-balance` method for a hash policy
+fn load_balance_method(policy: LagHashPolicy) -> &'static str {
+    match policy {
+        LagHashPolicy::Layer2 => "src-dst-mac",
+        LagHashPolicy::Layer2And3 => "src-dst-ip",
+        LagHashPolicy::Layer3And4 => "src-dst-mixed-ip-port",
+    }
+}
+
+/// Reject LAGs asking for different hash policies
+///
+/// IOS sets `port-channel load-balance` once for the whole switch.
+fn check_lag_hash_policies(request: &DeviceConfigRequest) -> Result<(), PortError> {
+    let mut policies = request.lags.iter().filter_map(|lag| lag.hash_policy);
+    match policies.next() {
+        Some(first) if policies.any(|policy| policy != first) => Err(PortError::NotSupported(
+            "IOS applies one port-channel load-balance policy to every LAG".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Flash file holding the configuration `configure replace` rolls back to
+const ROLLBACK_FILE: &str = "flash:cim-rollback.cfg";
+
+/// Map Cisco errors onto port errors
+fn port_error(error: CiscoError) -> PortError {
+    match error {
+        CiscoError::Auth(msg) => PortError::AuthenticationFailed(msg),
+        CiscoError::Ssh(msg) => PortError::ConnectionFailed(msg),
+        other => PortError::VendorError(other.to_string()),
+    }
+}
+
+#[async_trait]
+impl DeviceControlPort for CiscoIosAdapter {
+    fn vendor_name(&self) -> &str {
+        "cisco"
+    }
+
+    async fn connect(&self) -> Result<(), PortError> {
+        self.exec_checked(&self.seed_host, "show version")
+            .await
+            .map_err(port_error)?;
+        self.connected.store(true, Ordering::SeqCst);
+        tracing::info!("Connected to Cisco seed device {}", self.seed_host);
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> Result<(), PortError> {
+        // Sessions are per-operation; nothing to tear down
+        self.connected.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError> {
+        let neighbors = self.discover_neighbors().await.map_err(port_error)?;
+
+        // CDP does not advertise MACs; resolve them from the seed's ARP table
+        let arp = if neighbors.iter().any(|n| n.mac.is_none()) {
+            let output = self.exec_checked(&self.seed_host, "show ip arp")
+                .await
+                .map_err(port_error)?;
+            parse_ip_arp(&output)
+        } else {
+            HashMap::new()
+        };
+
+        let mut vendor_devices = Vec::new();
+        for neighbor in &neighbors {
+            let mac = neighbor.mac
+                .or_else(|| neighbor.ip_address.and_then(|ip| arp.get(&ip).copied()));
+
+            let Some(mac) = mac else {
+                tracing::debug!("Skipping neighbor {} with unresolved MAC", neighbor.device_id);
+                continue;
+            };
+
+            let device_id = self.get_device_id(&neighbor_vendor_id(neighbor)).await;
+            vendor_devices.push(self.to_vendor_device(neighbor, mac, device_id));
+        }
+
+        Ok(vendor_devices)
+    }
+
+    async fn get_device(&self, vendor_id: &str) -> Result<VendorDevice, PortError> {
+        self.list_devices()
+            .await?
+            .into_iter()
+            .find(|d| d.vendor_id == vendor_id)
+            .ok_or_else(|| port_error(CiscoError::NotFound(vendor_id.to_string())))
+    }
+
+    async fn adopt_device(&self, vendor_id: &str) -> Result<(), PortError> {
+        // No controller adoption on IOS; just verify we can manage the device
+        self.exec_checked(vendor_id, "show version")
+            .await
+            .map(|_| ())
+            .map_err(port_error)
+    }
+
+    async fn apply_config(&self, vendor_id: &str, config: DeviceConfigRequest) -> Result<(), PortError> {
+        config.validate()?;
+        check_lag_hash_policies(&config)?;
+        self.apply_raw_config(vendor_id, VendorConfig {
+            config_type: "cli".to_string(),
+            payload: serde_json::json!({ "config": ios_config_lines(&config) }),
+        })
+        .await
+    }
+
+    async fn apply_raw_config(&self, vendor_id: &str, config: VendorConfig) -> Result<(), PortError> {
+        let lines = config_lines(&config).map_err(port_error)?;
+
+        let mut session = Vec::with_capacity(lines.len() + 2);
+        session.push("configure terminal".to_string());
+        session.extend(lines);
+        session.push("end".to_string());
+
+        let output = self.transport
+            .shell(vendor_id, &session)
+            .await
+            .map_err(port_error)?;
+
+        if let Some(message) = find_cli_error(&output) {
+            return Err(port_error(CiscoError::CommandRejected {
+                command: "configure terminal".to_string(),
+                message: message.to_string(),
+            }));
+        }
+
+        tracing::info!("Applied {} config lines to {}", session.len() - 2, vendor_id);
+        Ok(())
+    }
+
+    /// Copy the running configuration to flash for `configure replace`
+    async fn snapshot_config(&self, vendor_id: &str) -> Result<VendorConfig, PortError> {
+        // Empty line accepts the suggested destination filename
+        let copy = format!("copy running-config {}", ROLLBACK_FILE);
+        let output = self.transport
+            .shell(vendor_id, &[copy.clone(), String::new()])
+            .await
+            .map_err(port_error)?;
+
+        if let Some(message) = find_cli_error(&output) {
+            return Err(port_error(CiscoError::CommandRejected {
+                command: copy,
+                message: message.to_string(),
+            }));
+        }
+
+        Ok(VendorConfig {
+            config_type: "ios-archive".to_string(),
+            payload: serde_json::json!({ "path": ROLLBACK_FILE }),
+        })
+    }
+
+    /// Replace the running configuration with the snapshot's file
+    async fn restore_config(&self, vendor_id: &str, snapshot: VendorConfig) -> Result<(), PortError> {
+        let path = snapshot.payload
+            .get("path")
+            .and_then(|p| p.as_str())
+            .ok_or_else(|| port_error(CiscoError::Parse("Snapshot has no 'path'".to_string())))?;
+
+        self.exec_checked(vendor_id, &format!("configure replace {} force", path))
+            .await
+            .map(|_| ())
+            .map_err(port_error)
+    }
+
+    async fn check_reachable(&self, vendor_id: &str) -> Result<(), PortError> {
+        self.exec_checked(vendor_id, "show version")
+            .await
+            .map(|_| ())
+            .map_err(port_error)
+    }
+
+    async fn restart_device(&self, vendor_id: &str) -> Result<(), PortError> {
+        // Empty line confirms "Proceed with reload? [confirm]"
+        let output = self.transport
+            .shell(vendor_id, &["reload".to_string(), String::new()])
+            .await
+            .map_err(port_error)?;
+
+        if output.contains("System configuration has been modified") {
+            return Err(port_error(CiscoError::CommandRejected {
+                command: "reload".to_string(),
+                message: "unsaved configuration; save before reloading".to_string(),
+            }));
+        }
+        if let Some(message) = find_cli_error(&output) {
+            return Err(port_error(CiscoError::CommandRejected {
+                command: "reload".to_string(),
+                message: message.to_string(),
+            }));
+        }
+
+        Ok(())
+    }
+
+    async fn get_device_stats(&self, vendor_id: &str) -> Result<DeviceStats, PortError> {
+        let version = self.exec_checked(vendor_id, "show version").await.map_err(port_error)?;
+        let cpu = self.exec_checked(vendor_id, "show processes cpu | include CPU utilization")
+            .await
+            .map_err(port_error)?;
+        let memory = self.exec_checked(vendor_id, "show processes memory | include Processor Pool")
+            .await
+            .map_err(port_error)?;
+        let interfaces = self.exec_checked(vendor_id, "show interfaces").await.map_err(port_error)?;
+
+        Ok(DeviceStats {
+            uptime_seconds: parse_uptime_seconds(&version).unwrap_or(0),
+            cpu_percent: parse_cpu_utilization(&cpu),
+            memory_percent: parse_memory_utilization(&memory),
+            temperature_celsius: None,
+            port_stats: parse_show_interfaces(&interfaces).into_iter().map(|iface| PortStats {
+                speed: iface.bandwidth_mbps().and_then(LinkSpeed::from_mbps),
+                port_id: PortId::new(iface.name),
+                link_up: iface.line_protocol_up,
+                rx_bytes: iface.rx_bytes,
+                tx_bytes: iface.tx_bytes,
+                rx_errors: iface.rx_errors,
+                tx_errors: iface.tx_errors,
+                rx_bps: None,
+                tx_bps: None,
+            }).collect(),
+        })
+    }
+}
+
+impl VendorExtension for CiscoIosAdapter {
+    fn vendor_name(&self) -> &str {
+        "cisco"
+    }
+
+    fn extend(&self, domain_obj: &DomainObject) -> Result<VendorRepresentation, FunctorError> {
+        match domain_obj {
+            DomainObject::Device(device) => {
+                let payload = serde_json::json!({
+                    "hostname": device.name(),
+                    "mac": device.mac().to_string(),
+                    "state": device.state().name(),
+                    "config": generate_ios_config(device),
+                });
+
+                Ok(VendorRepresentation {
+                    vendor: "cisco".to_string(),
+                    vendor_id: device.vendor_id().unwrap_or("pending").to_string(),
+                    device_id: device.id(),
+                    payload,
+                })
+            }
+            _ => Err(FunctorError::MappingFailed(
+                "Only Device objects can be extended to Cisco".to_string()
+            )),
+        }
+    }
+
+    fn to_domain_event(&self, vendor_event: &serde_json::Value) -> Result<NetworkEvent, FunctorError> {
+        // Syslog-derived events: {"host": "...", "mnemonic": "SYS-5-RESTART", "message": "..."}
+        let mnemonic = vendor_event.get("mnemonic")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| FunctorError::MappingFailed("Missing syslog mnemonic".to_string()))?;
+
+        let host = vendor_event.get("host")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| FunctorError::MappingFailed("Missing syslog host".to_string()))?;
+
+        let device_id = self.get_device_by_host(host)
+            .ok_or_else(|| FunctorError::MappingFailed(
+                format!("Unknown device host: {}. Register device first.", host)
+            ))?;
+
+        match mnemonic {
+            "SYS-5-RESTART" => Ok(NetworkEvent::DeviceProvisioned {
+                device_id,
+                model: vendor_event.get("platform")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown")
+                    .to_string(),
+                firmware_version: vendor_event.get("version")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown")
+                    .to_string(),
+            }),
+            "SYS-5-RELOAD" => Ok(NetworkEvent::DeviceError {
+                device_id,
+                message: vendor_event.get("message")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Reload requested")
+                    .to_string(),
+            }),
+            _ => Err(FunctorError::MappingFailed(format!("Unknown syslog mnemonic: {}", mnemonic))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CDP_DETAIL: &str = "\
+-------------------------
+Device ID: access-sw1.example.net
+Entry address(es):
+  IP address: 10.0.0.2
+Platform: cisco WS-C2960X-48FPD-L,  Capabilities: Switch IGMP
+Interface: GigabitEthernet0/1,  Port ID (outgoing port): GigabitEthernet1/0/24
+Holdtime : 141 sec
+
+Version :
+Cisco IOS Software, C2960X Software (C2960X-UNIVERSALK9-M), Version 15.2(7)E4, RELEASE SOFTWARE (fc2)
+
+-------------------------
+Device ID: ap-lobby
+Entry address(es):
+  IP address: 10.0.0.3
+Platform: cisco AIR-AP2802I-B-K9,  Capabilities: Trans-Bridge
+Interface: GigabitEthernet0/2,  Port ID (outgoing port): GigabitEthernet0
+";
+
+    const LLDP_DETAIL: &str = "\
+------------------------------------------------
+Local Intf: Gi0/3
+Chassis id: 0011.2233.4455
+Port id: ge-0/0/1
+Port Description: uplink
+System Name: edge-fw
+
+System Description:
+Juniper Networks, Inc. srx300
+
+Time remaining: 112 seconds
+Management Addresses:
+    IP: 10.0.0.4
+Auto Negotiation - supported, enabled
+";
+
+    const SHOW_INTERFACES: &str = "\
+GigabitEthernet0/1 is up, line protocol is up (connected)
+  Hardware is Gigabit Ethernet, address is 0011.2233.0001 (bia 0011.2233.0001)
+  MTU 1500 bytes, BW 1000000 Kbit/sec, DLY 10 usec,
+  5 minute input rate 2000 bits/sec, 3 packets/sec
+     123456 packets input, 98765432 bytes, 0 no buffer
+     Received 1000 broadcasts (900 multicasts)
+     2 input errors, 1 CRC, 0 frame, 0 overrun, 0 ignored
+     654321 packets output, 12345678 bytes, 0 underruns
+     3 output errors, 0 collisions, 1 interface resets
+GigabitEthernet0/2 is down, line protocol is down (notconnect)
+  MTU 1500 bytes, BW 100000 Kbit/sec, DLY 100 usec,
+     0 packets input, 0 bytes, 0 no buffer
+     0 input errors, 0 CRC, 0 frame, 0 overrun, 0 ignored
+     0 packets output, 0 bytes, 0 underruns
+     0 output errors, 0 collisions, 0 interface resets
+";
+
+    // ==========================================================================
+    // Parser Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_parse_cdp_neighbors() {
+        let neighbors = parse_cdp_neighbors_detail(CDP_DETAIL);
+        assert_eq!(neighbors.len(), 2);
+
+        let sw = &neighbors[0];
+        assert_eq!(sw.device_id, "access-sw1.example.net");
+        assert_eq!(sw.ip_address, Some("10.0.0.2".parse().unwrap()));
+        assert_eq!(sw.platform.as_deref(), Some("WS-C2960X-48FPD-L"));
+        assert_eq!(sw.local_interface.as_deref(), Some("GigabitEthernet0/1"));
+        assert_eq!(sw.remote_interface.as_deref(), Some("GigabitEthernet1/0/24"));
+        assert_eq!(sw.software_version.as_deref(), Some("15.2(7)E4"));
+        assert!(sw.mac.is_none());
+    }
+
+    #[test]
+    fn test_parse_lldp_neighbors() {
+        let neighbors = parse_lldp_neighbors_detail(LLDP_DETAIL);
+        assert_eq!(neighbors.len(), 1);
+
+        let fw = &neighbors[0];
+        assert_eq!(fw.device_id, "edge-fw");
+        assert_eq!(fw.mac, Some(MacAddress::parse("00:11:22:33:44:55").unwrap()));
+        assert_eq!(fw.ip_address, Some("10.0.0.4".parse().unwrap()));
+        assert_eq!(fw.remote_interface.as_deref(), Some("ge-0/0/1"));
+    }
+
+    #[test]
+    fn test_parse_ip_arp() {
+        let output = "\
+Protocol  Address          Age (min)  Hardware Addr   Type   Interface
+Internet  10.0.0.1                -   0011.2233.0001  ARPA   Vlan1
+Internet  10.0.0.2                5   0011.2233.0002  ARPA   Vlan1
+";
+        let arp = parse_ip_arp(output);
+        assert_eq!(arp.len(), 2);
+        assert_eq!(
+            arp[&"10.0.0.2".parse().unwrap()],
+            MacAddress::parse("00:11:22:33:00:02").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_cpu_memory_uptime() {
+        assert_eq!(
+            parse_cpu_utilization("CPU utilization for five seconds: 5%/0%; one minute: 6%; five minutes: 5%"),
+            Some(6.0)
+        );
+        assert_eq!(
+            parse_memory_utilization("Processor Pool Total:  1000000 Used:  250000 Free:  750000"),
+            Some(25.0)
+        );
+        assert_eq!(
+            parse_uptime_seconds("core-sw1 uptime is 1 week, 2 days, 3 hours, 4 minutes"),
+            Some(7 * 86400 + 2 * 86400 + 3 * 3600 + 4 * 60)
+        );
+    }
+
+    #[test]
+    fn test_parse_show_interfaces() {
+        let interfaces = parse_show_interfaces(SHOW_INTERFACES);
+        assert_eq!(interfaces.len(), 2);
+
+        let gi1 = &interfaces[0];
+        assert_eq!(gi1.name, "GigabitEthernet0/1");
+        assert!(gi1.line_protocol_up);
+        assert_eq!(gi1.bandwidth_mbps(), Some(1000));
+        assert_eq!(gi1.rx_bytes, 98765432);
+        assert_eq!(gi1.tx_bytes, 12345678);
+        assert_eq!(gi1.rx_errors, 2);
+        assert_eq!(gi1.tx_errors, 3);
+
+        assert!(!interfaces[1].line_protocol_up);
+        assert_eq!(interfaces[1].bandwidth_mbps(), Some(100));
+    }
+
+    #[test]
+    fn test_find_cli_error_ignores_syslog() {
+        assert!(find_cli_error("%SYS-5-CONFIG_I: Configured from console").is_none());
+        assert_eq!(
+            find_cli_error("sw1(config)#foo\n% Invalid input detected at '^' marker.\n"),
+            Some("% Invalid input detected at '^' marker.")
+        );
+    }
+
+    // ==========================================================================
+    // Config Generation Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_generate_ios_config() {
+        let device_id = DeviceId::new();
+        let device = NetworkDeviceAggregate::from_events(vec![
+            NetworkEvent::DeviceDiscovered {
+                device_id,
+                mac: MacAddress::parse("00:11:22:33:44:55").unwrap(),
+                device_type: DeviceType::Switch,
+                ip_address: None,
+            },
+            NetworkEvent::DeviceRenamed {
+                device_id,
+                old_name: "Device".to_string(),
+                new_name: "core sw1".to_string(),
+            },
+            NetworkEvent::DeviceConfigured {
+                device_id,
+                interfaces: vec![
+                    InterfaceConfig {
+                        name: "Vlan10".to_string(),
+                        ip_address: Some("10.0.10.1".parse().unwrap()),
+                        prefix_len: Some(24),
+                        vlan_id: None,
+                        enabled: true,
+                        mtu: None,
+                        speed: None,
+                    },
+                    InterfaceConfig {
+                        name: "GigabitEthernet0/2".to_string(),
+                        ip_address: None,
+                        prefix_len: None,
+                        vlan_id: Some(10),
+                        enabled: false,
+                        mtu: None,
+                        speed: None,
+                    },
+                ],
+                vlans: vec![VlanConfig::new(10, "users").unwrap()],
+            },
+        ]).unwrap();
+
+        assert_eq!(generate_ios_config(&device), vec![
+            "hostname core-sw1",
+            "vlan 10",
+            " name users",
+            "interface Vlan10",
+            " ip address 10.0.10.1 255.255.255.0",
+            " no shutdown",
+            "interface GigabitEthernet0/2",
+            " switchport mode access",
+            " switchport access vlan 10",
+            " shutdown",
+        ]);
+    }
+
+    fn member(name: &str) -> InterfaceConfig {
+        InterfaceConfig {
+            name: name.to_string(),
+            ip_address: None,
+            prefix_len: None,
+            vlan_id: None,
+            enabled: true,
+            mtu: None,
+            speed: None,
+        }
+    }
+
+    #[test]
+    fn test_lacp_bond_generates_port_channel() {
+        let request = DeviceConfigRequest::default()
+            .with_interface(member("GigabitEthernet1/0/47"))
+            .with_interface(member("GigabitEthernet1/0/48"))
+            .with_lag(LagConfig::new(1)
+                .with_member(PortId::new("GigabitEthernet1/0/47"))
+                .with_member(PortId::new("GigabitEthernet1/0/48"))
+                .with_hash_policy(LagHashPolicy::Layer2And3));
+        request.validate().unwrap();
+
+        assert_eq!(ios_config_lines(&request), vec![
+            "interface GigabitEthernet1/0/47",
+            " channel-group 1 mode active",
+            " no shutdown",
+            "interface GigabitEthernet1/0/48",
+            " channel-group 1 mode active",
+            " no shutdown",
+            "interface Port-channel1",
+            " no shutdown",
+            "port-channel load-balance src-dst-ip",
+        ]);
+
+        let mixed = request.with_interface(member("GigabitEthernet1/0/1")).with_lag(LagConfig::new(2)
+            .with_member(PortId::new("GigabitEthernet1/0/1"))
+            .with_hash_policy(LagHashPolicy::Layer2));
+        assert!(matches!(check_lag_hash_policies(&mixed), Err(PortError::NotSupported(_))));
+    }
+
+    #[test]
+    fn test_config_lines_from_payload() {
+        let config = VendorConfig {
+            config_type: "cli".to_string(),
+            payload: serde_json::json!({ "config": ["hostname sw1", "", "vlan 10"] }),
+        };
+        assert_eq!(config_lines(&config).unwrap(), vec!["hostname sw1", "vlan 10"]);
+
+        let config = VendorConfig {
+            config_type: "cli".to_string(),
+            payload: serde_json::json!("interface Gi0/1\n shutdown\n"),
+        };
+        assert_eq!(config_lines(&config).unwrap(), vec!["interface Gi0/1", " shutdown"]);
+
+        let config = VendorConfig {
+            config_type: "cli".to_string(),
+            payload: serde_json::json!(42),
+        };
+        assert!(config_lines(&config).is_err());
+    }
+}
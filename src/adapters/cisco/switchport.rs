@@ -0,0 +1,296 @@
+//! IOS switchport configuration
+//!
+//! Generates VLAN definitions and access/trunk port stanzas for Catalyst
+//! switches from the domain's `VlanConfig`s. Access ports come from each
+//! VLAN's `access_ports` and from interfaces with a `vlan_id`; trunks are
+//! added explicitly with their allowed VLANs. Every VLAN a port refers to
+//! must be defined, so a trunk cannot silently carry a VLAN the switch does
+//! not know.
+
+use std::collections::{BTreeSet, HashSet};
+
+use crate::domain::value_objects::{InterfaceConfig, PortId, VlanConfig, VlanConflict};
+
+/// An access port and its VLAN
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessPort {
+    /// Interface, e.g. `GigabitEthernet1/0/1`
+    pub port: PortId,
+    /// VLAN the port is a member of
+    pub vlan: u16,
+}
+
+/// A trunk port and the VLANs it carries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrunkPort {
+    /// Interface, e.g. `GigabitEthernet1/0/48`
+    pub port: PortId,
+    /// VLANs allowed on the trunk
+    pub allowed_vlans: BTreeSet<u16>,
+    /// Untagged VLAN; defaults to the VLAN flagged `native`, if allowed
+    pub native_vlan: Option<u16>,
+}
+
+/// Why a switchport configuration cannot be generated
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SwitchportError {
+    /// The VLANs disagree with each other
+    #[error(transparent)]
+    Conflict(#[from] VlanConflict),
+    /// A port refers to a VLAN that is not defined
+    #[error("Port {port} uses VLAN {vlan}, which is not defined")]
+    UndefinedVlan {
+        /// Port referring to the VLAN
+        port: PortId,
+        /// Undefined VLAN ID
+        vlan: u16,
+    },
+    /// A trunk's native VLAN is not among its allowed VLANs
+    #[error("Native VLAN {vlan} of trunk {port} is not allowed on it")]
+    NativeNotAllowed {
+        /// Trunk port
+        port: PortId,
+        /// Native VLAN ID
+        vlan: u16,
+    },
+    /// A port is configured twice, e.g. both as an access port and as a trunk
+    #[error("Port {0} has more than one switchport configuration")]
+    ModeConflict(PortId),
+}
+
+/// Switchport configuration for a Cisco IOS switch
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CiscoIosSwitchConfig {
+    /// VLAN definitions
+    pub vlans: Vec<VlanConfig>,
+    /// Access ports besides the VLANs' own `access_ports`
+    pub access_ports: Vec<AccessPort>,
+    /// Trunk ports
+    pub trunks: Vec<TrunkPort>,
+}
+
+impl CiscoIosSwitchConfig {
+    /// Configuration for `vlans`, with `interfaces` that have a `vlan_id` as access ports
+    pub fn new(vlans: Vec<VlanConfig>, interfaces: &[InterfaceConfig]) -> Self {
+        let access_ports = interfaces
+            .iter()
+            .filter_map(|iface| Some(AccessPort { port: PortId::new(iface.name.clone()), vlan: iface.vlan_id? }))
+            .collect();
+        Self { vlans, access_ports, trunks: Vec::new() }
+    }
+
+    /// Add a trunk port carrying `allowed_vlans`
+    pub fn with_trunk(
+        mut self,
+        port: PortId,
+        allowed_vlans: impl IntoIterator<Item = u16>,
+        native_vlan: Option<u16>,
+    ) -> Self {
+        self.trunks.push(TrunkPort {
+            port,
+            allowed_vlans: allowed_vlans.into_iter().collect(),
+            native_vlan,
+        });
+        self
+    }
+
+    /// Check that every port refers only to defined VLANs and is configured once
+    pub fn validate(&self) -> Result<(), SwitchportError> {
+        VlanConfig::check_conflicts(&self.vlans)?;
+        let defined: HashSet<u16> = self.vlans.iter().map(|v| v.id).collect();
+
+        let mut ports = HashSet::new();
+        for access in self.all_access_ports() {
+            if !defined.contains(&access.vlan) {
+                return Err(SwitchportError::UndefinedVlan { port: access.port, vlan: access.vlan });
+            }
+            if !ports.insert(access.port.clone()) {
+                return Err(SwitchportError::ModeConflict(access.port));
+            }
+        }
+        for trunk in &self.trunks {
+            if !ports.insert(trunk.port.clone()) {
+                return Err(SwitchportError::ModeConflict(trunk.port.clone()));
+            }
+            if let Some(&vlan) = trunk.allowed_vlans.iter().find(|id| !defined.contains(id)) {
+                return Err(SwitchportError::UndefinedVlan { port: trunk.port.clone(), vlan });
+            }
+            if let Some(vlan) = trunk.native_vlan {
+                if !trunk.allowed_vlans.contains(&vlan) {
+                    return Err(SwitchportError::NativeNotAllowed { port: trunk.port.clone(), vlan });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate, then generate the configuration lines
+    pub fn generate(&self) -> Result<Vec<String>, SwitchportError> {
+        self.validate()?;
+        let mut lines = Vec::new();
+
+        for vlan in &self.vlans {
+            lines.push(format!("vlan {}", vlan.id));
+            lines.push(format!(" name {}", vlan.name));
+        }
+
+        for access in self.all_access_ports() {
+            lines.push(format!("interface {}", access.port.name));
+            lines.push(" switchport mode access".to_string());
+            lines.push(format!(" switchport access vlan {}", access.vlan));
+        }
+
+        let flagged_native = self.vlans.iter().find(|v| v.native).map(|v| v.id);
+        for trunk in &self.trunks {
+            lines.push(format!("interface {}", trunk.port.name));
+            lines.push(" switchport mode trunk".to_string());
+            let native = trunk.native_vlan.or(flagged_native.filter(|id| trunk.allowed_vlans.contains(id)));
+            if let Some(native) = native {
+                lines.push(format!(" switchport trunk native vlan {}", native));
+            }
+            lines.push(format!(" switchport trunk allowed vlan {}", vlan_ranges(&trunk.allowed_vlans)));
+        }
+
+        Ok(lines)
+    }
+
+    /// The VLANs' own access ports, then the extra ones, without exact repeats
+    fn all_access_ports(&self) -> Vec<AccessPort> {
+        let mut all: Vec<AccessPort> = Vec::new();
+        let own = self.vlans.iter().flat_map(|vlan| {
+            vlan.access_ports.iter().map(|port| AccessPort { port: port.clone(), vlan: vlan.id })
+        });
+        for access in own.chain(self.access_ports.iter().cloned()) {
+            if !all.contains(&access) {
+                all.push(access);
+            }
+        }
+        all
+    }
+}
+
+/// IOS VLAN list such as `10,20-22,30`
+fn vlan_ranges(vlans: &BTreeSet<u16>) -> String {
+    let mut ranges: Vec<(u16, u16)> = Vec::new();
+    for &id in vlans {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == id => *end = id,
+            _ => ranges.push((id, id)),
+        }
+    }
+    if ranges.is_empty() {
+        return "none".to_string();
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| if start == end { start.to_string() } else { format!("{}-{}", start, end) })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vlans() -> Vec<VlanConfig> {
+        vec![
+            VlanConfig::new(10, "users").unwrap(),
+            VlanConfig::new(20, "voice").unwrap(),
+            VlanConfig::new(21, "cameras").unwrap(),
+        ]
+    }
+
+    fn access(name: &str, vlan_id: u16) -> InterfaceConfig {
+        InterfaceConfig {
+            name: name.to_string(),
+            ip_address: None,
+            prefix_len: None,
+            vlan_id: Some(vlan_id),
+            enabled: true,
+            mtu: None,
+            speed: None,
+        }
+    }
+
+    #[test]
+    fn test_access_port_config() {
+        let config = CiscoIosSwitchConfig::new(vlans(), &[access("GigabitEthernet1/0/1", 10)]);
+
+        assert_eq!(config.generate().unwrap(), [
+            "vlan 10",
+            " name users",
+            "vlan 20",
+            " name voice",
+            "vlan 21",
+            " name cameras",
+            "interface GigabitEthernet1/0/1",
+            " switchport mode access",
+            " switchport access vlan 10",
+        ]);
+    }
+
+    #[test]
+    fn test_trunk_config() {
+        let mut vlans = vlans();
+        vlans[0].native = true;
+        let config = CiscoIosSwitchConfig::new(vlans, &[])
+            .with_trunk(PortId::new("GigabitEthernet1/0/48"), [21, 10, 20], None)
+            .with_trunk(PortId::new("GigabitEthernet1/0/47"), [20, 21], Some(20));
+
+        let lines = config.generate().unwrap();
+        assert_eq!(&lines[6..], [
+            "interface GigabitEthernet1/0/48",
+            " switchport mode trunk",
+            " switchport trunk native vlan 10",
+            " switchport trunk allowed vlan 10,20-21",
+            "interface GigabitEthernet1/0/47",
+            " switchport mode trunk",
+            " switchport trunk native vlan 20",
+            " switchport trunk allowed vlan 20-21",
+        ]);
+    }
+
+    #[test]
+    fn test_trunk_allowing_undefined_vlan_is_rejected() {
+        let config = CiscoIosSwitchConfig::new(vlans(), &[])
+            .with_trunk(PortId::new("GigabitEthernet1/0/48"), [10, 30], None);
+
+        assert_eq!(config.validate(), Err(SwitchportError::UndefinedVlan {
+            port: PortId::new("GigabitEthernet1/0/48"),
+            vlan: 30,
+        }));
+        assert!(config.generate().is_err());
+    }
+
+    #[test]
+    fn test_access_port_in_undefined_vlan_is_rejected() {
+        let config = CiscoIosSwitchConfig::new(vlans(), &[access("GigabitEthernet1/0/2", 99)]);
+
+        assert!(matches!(config.validate(), Err(SwitchportError::UndefinedVlan { vlan: 99, .. })));
+    }
+
+    #[test]
+    fn test_port_with_two_modes_is_rejected() {
+        let config = CiscoIosSwitchConfig::new(vlans(), &[access("GigabitEthernet1/0/1", 10)])
+            .with_trunk(PortId::new("GigabitEthernet1/0/1"), [10, 20], None);
+
+        assert_eq!(config.validate(), Err(SwitchportError::ModeConflict(PortId::new("GigabitEthernet1/0/1"))));
+    }
+
+    #[test]
+    fn test_vlan_access_ports_and_interfaces_are_merged() {
+        let mut vlans = vlans();
+        vlans[1] = vlans[1].clone().with_access_port(PortId::new("GigabitEthernet1/0/5"));
+        let config = CiscoIosSwitchConfig::new(vlans, &[access("GigabitEthernet1/0/5", 20), access("GigabitEthernet1/0/6", 21)]);
+
+        let lines = config.generate().unwrap();
+        assert_eq!(lines.iter().filter(|l| l.starts_with("interface")).count(), 2);
+        assert!(lines.contains(&" switchport access vlan 21".to_string()));
+    }
+
+    #[test]
+    fn test_vlan_ranges() {
+        assert_eq!(vlan_ranges(&BTreeSet::from([1, 2, 3, 5, 7, 8])), "1-3,5,7-8");
+        assert_eq!(vlan_ranges(&BTreeSet::new()), "none");
+    }
+}
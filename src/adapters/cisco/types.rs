@@ -0,0 +1,358 @@
+//! Cisco IOS CLI types and output parsers
+//!
+//! IOS has no structured API on most platforms, so everything here is parsed
+//! from human-readable `show` output. Parsers are tolerant: lines they do not
+//! recognise are skipped rather than treated as errors.
+
+use crate::domain::value_objects::MacAddress;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+/// Neighbor learned via CDP or LLDP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CiscoNeighbor {
+    /// Neighbor device ID / system name
+    pub device_id: String,
+    /// Management address advertised by the neighbor
+    pub ip_address: Option<IpAddr>,
+    /// Chassis MAC (LLDP only; CDP neighbors are resolved through ARP)
+    pub mac: Option<MacAddress>,
+    /// Platform / model string
+    pub platform: Option<String>,
+    /// Local interface the neighbor was seen on
+    pub local_interface: Option<String>,
+    /// Neighbor's interface
+    pub remote_interface: Option<String>,
+    /// Advertised software version
+    pub software_version: Option<String>,
+}
+
+/// Counters for a single interface from `show interfaces`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CiscoInterfaceCounters {
+    /// Interface name (e.g., "GigabitEthernet0/1")
+    pub name: String,
+    /// Whether line protocol is up
+    pub line_protocol_up: bool,
+    /// Bandwidth in Kbit/sec
+    pub bandwidth_kbps: Option<u64>,
+    /// Input bytes
+    pub rx_bytes: u64,
+    /// Output bytes
+    pub tx_bytes: u64,
+    /// Input errors
+    pub rx_errors: u64,
+    /// Output errors
+    pub tx_errors: u64,
+}
+
+impl CiscoInterfaceCounters {
+    /// Interface bandwidth in Mbps
+    pub fn bandwidth_mbps(&self) -> Option<u32> {
+        self.bandwidth_kbps.and_then(|kbps| u32::try_from(kbps / 1000).ok())
+    }
+}
+
+/// Cisco adapter error
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum CiscoError {
+    /// Transport-level SSH failure
+    #[error("SSH error: {0}")]
+    Ssh(String),
+    /// Credentials were rejected
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+    /// The device answered a command with an IOS `%` error
+    #[error("Command rejected: {command}: {message}")]
+    CommandRejected {
+        /// Command (or config block) that was sent
+        command: String,
+        /// Error line reported by the device
+        message: String,
+    },
+    /// Output could not be parsed
+    #[error("Parse error: {0}")]
+    Parse(String),
+    /// No device with the given identifier
+    #[error("Device not found: {0}")]
+    NotFound(String),
+}
+
+/// Find the first IOS error marker (`% ...`) in command output
+///
+/// Syslog lines such as `%SYS-5-CONFIG_I` have no space after the percent
+/// sign and are not treated as errors.
+pub fn find_cli_error(output: &str) -> Option<&str> {
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with("% "))
+}
+
+/// Parse `show cdp neighbors detail`
+pub fn parse_cdp_neighbors_detail(output: &str) -> Vec<CiscoNeighbor> {
+    let mut neighbors = Vec::new();
+    let mut current: Option<CiscoNeighbor> = None;
+
+    for line in output.lines() {
+        let line = line.trim();
+
+        if let Some(device_id) = line.strip_prefix("Device ID:") {
+            if let Some(done) = current.take() {
+                neighbors.push(done);
+            }
+            current = Some(CiscoNeighbor {
+                device_id: device_id.trim().to_string(),
+                ip_address: None,
+                mac: None,
+                platform: None,
+                local_interface: None,
+                remote_interface: None,
+                software_version: None,
+            });
+            continue;
+        }
+
+        let Some(neighbor) = current.as_mut() else {
+            continue;
+        };
+
+        if let Some(addr) = line.strip_prefix("IP address:") {
+            // Only the first (entry) address is kept
+            if neighbor.ip_address.is_none() {
+                neighbor.ip_address = addr.trim().parse().ok();
+            }
+        } else if let Some(rest) = line.strip_prefix("Platform:") {
+            // "Platform: cisco WS-C2960X-48FPD-L,  Capabilities: Switch IGMP"
+            let platform = rest.split(',').next().unwrap_or("").trim();
+            let platform = platform.strip_prefix("cisco ").unwrap_or(platform);
+            neighbor.platform = Some(platform.to_string());
+        } else if let Some(rest) = line.strip_prefix("Interface:") {
+            // "Interface: GigabitEthernet0/1,  Port ID (outgoing port): GigabitEthernet1/0/24"
+            let mut parts = rest.split(',');
+            neighbor.local_interface = parts.next().map(|s| s.trim().to_string());
+            neighbor.remote_interface = parts
+                .next()
+                .and_then(|s| s.split_once(':'))
+                .map(|(_, port)| port.trim().to_string());
+        } else if line.starts_with("Cisco IOS Software") || line.starts_with("Cisco Nexus") {
+            neighbor.software_version = line
+                .split(", Version ")
+                .nth(1)
+                .map(|v| v.split([',', ' ']).next().unwrap_or(v).to_string());
+        }
+    }
+
+    if let Some(done) = current {
+        neighbors.push(done);
+    }
+
+    neighbors
+}
+
+/// Parse `show lldp neighbors detail`
+pub fn parse_lldp_neighbors_detail(output: &str) -> Vec<CiscoNeighbor> {
+    let mut neighbors: Vec<CiscoNeighbor> = Vec::new();
+    let mut current: Option<CiscoNeighbor> = None;
+    let mut in_management_addresses = false;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        if let Some(local) = trimmed.strip_prefix("Local Intf:") {
+            if let Some(done) = current.take() {
+                neighbors.push(done);
+            }
+            current = Some(CiscoNeighbor {
+                device_id: String::new(),
+                ip_address: None,
+                mac: None,
+                platform: None,
+                local_interface: Some(local.trim().to_string()),
+                remote_interface: None,
+                software_version: None,
+            });
+            in_management_addresses = false;
+            continue;
+        }
+
+        let Some(neighbor) = current.as_mut() else {
+            continue;
+        };
+
+        if let Some(chassis) = trimmed.strip_prefix("Chassis id:") {
+            neighbor.mac = MacAddress::parse(chassis.trim()).ok();
+        } else if let Some(port) = trimmed.strip_prefix("Port id:") {
+            neighbor.remote_interface = Some(port.trim().to_string());
+        } else if let Some(name) = trimmed.strip_prefix("System Name:") {
+            neighbor.device_id = name.trim().to_string();
+        } else if trimmed.starts_with("Management Addresses:") {
+            in_management_addresses = true;
+        } else if in_management_addresses {
+            if let Some(addr) = trimmed.strip_prefix("IP:") {
+                if neighbor.ip_address.is_none() {
+                    neighbor.ip_address = addr.trim().parse().ok();
+                }
+            } else if !trimmed.is_empty() && !line.starts_with(' ') {
+                in_management_addresses = false;
+            }
+        } else if trimmed.starts_with("Cisco IOS Software") {
+            neighbor.software_version = trimmed
+                .split(", Version ")
+                .nth(1)
+                .map(|v| v.split([',', ' ']).next().unwrap_or(v).to_string());
+        }
+    }
+
+    if let Some(done) = current {
+        neighbors.push(done);
+    }
+
+    // Neighbors without a system name fall back to their chassis id
+    for neighbor in &mut neighbors {
+        if neighbor.device_id.is_empty() {
+            neighbor.device_id = neighbor
+                .mac
+                .map(|m| m.to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+        }
+    }
+
+    neighbors
+}
+
+/// Parse `show ip arp` into an IP → MAC table
+pub fn parse_ip_arp(output: &str) -> HashMap<IpAddr, MacAddress> {
+    output
+        .lines()
+        .filter_map(|line| {
+            // "Internet  10.0.0.2   5   0011.2233.4455  ARPA   GigabitEthernet0/1"
+            let mut cols = line.split_whitespace();
+            if cols.next()? != "Internet" {
+                return None;
+            }
+            let ip = cols.next()?.parse().ok()?;
+            let _age = cols.next()?;
+            let mac = MacAddress::parse(cols.next()?).ok()?;
+            Some((ip, mac))
+        })
+        .collect()
+}
+
+/// Parse the one-minute CPU load from `show processes cpu`
+pub fn parse_cpu_utilization(output: &str) -> Option<f64> {
+    // "CPU utilization for five seconds: 5%/0%; one minute: 6%; five minutes: 5%"
+    let line = output.lines().find(|l| l.contains("CPU utilization"))?;
+    let one_minute = line.split("one minute:").nth(1)?;
+    one_minute.trim().split('%').next()?.trim().parse().ok()
+}
+
+/// Parse processor pool usage from `show processes memory`
+pub fn parse_memory_utilization(output: &str) -> Option<f64> {
+    // "Processor Pool Total:  858993459 Used:  254781728 Free:  604211731"
+    let line = output.lines().find(|l| l.contains("Processor Pool Total:"))?;
+    let number_after = |label: &str| -> Option<f64> {
+        line.split(label).nth(1)?.split_whitespace().next()?.parse().ok()
+    };
+
+    let total = number_after("Total:")?;
+    let used = number_after("Used:")?;
+    if total <= 0.0 {
+        return None;
+    }
+    Some((used / total * 1000.0).round() / 10.0)
+}
+
+/// Parse uptime in seconds from `show version`
+pub fn parse_uptime_seconds(output: &str) -> Option<u64> {
+    // "core-sw1 uptime is 1 year, 2 weeks, 3 days, 4 hours, 5 minutes"
+    let line = output.lines().find(|l| l.contains(" uptime is "))?;
+    let spec = line.split(" uptime is ").nth(1)?;
+
+    let mut total = 0u64;
+    for part in spec.split(',') {
+        let mut words = part.split_whitespace();
+        let (Some(value), Some(unit)) = (words.next(), words.next()) else {
+            continue;
+        };
+        let Ok(value) = value.parse::<u64>() else {
+            continue;
+        };
+        let multiplier = match unit.trim_end_matches('s') {
+            "year" => 365 * 24 * 3600,
+            "week" => 7 * 24 * 3600,
+            "day" => 24 * 3600,
+            "hour" => 3600,
+            "minute" => 60,
+            "second" => 1,
+            _ => continue,
+        };
+        total += value * multiplier;
+    }
+
+    Some(total)
+}
+
+/// Parse `show interfaces`
+pub fn parse_show_interfaces(output: &str) -> Vec<CiscoInterfaceCounters> {
+    let mut interfaces = Vec::new();
+    let mut current: Option<CiscoInterfaceCounters> = None;
+
+    for line in output.lines() {
+        // Interface headers are the only unindented lines
+        if !line.starts_with(' ') && line.contains(" is ") && line.contains("line protocol") {
+            if let Some(done) = current.take() {
+                interfaces.push(done);
+            }
+            let name = line.split_whitespace().next().unwrap_or_default().to_string();
+            let line_protocol_up = line
+                .split("line protocol is ")
+                .nth(1)
+                .map(|s| s.trim_start().starts_with("up"))
+                .unwrap_or(false);
+            current = Some(CiscoInterfaceCounters {
+                name,
+                line_protocol_up,
+                ..Default::default()
+            });
+            continue;
+        }
+
+        let Some(iface) = current.as_mut() else {
+            continue;
+        };
+        let trimmed = line.trim();
+
+        if let Some(bw) = trimmed.split("BW ").nth(1) {
+            if trimmed.starts_with("MTU") || trimmed.starts_with("BW") {
+                iface.bandwidth_kbps = leading_number(bw);
+            }
+        }
+        if trimmed.contains("packets input,") {
+            iface.rx_bytes = number_before(trimmed, " bytes").unwrap_or(0);
+        } else if trimmed.contains("packets output,") {
+            iface.tx_bytes = number_before(trimmed, " bytes").unwrap_or(0);
+        } else if trimmed.contains(" input errors") {
+            iface.rx_errors = leading_number(trimmed).unwrap_or(0);
+        } else if trimmed.contains(" output errors") {
+            iface.tx_errors = leading_number(trimmed).unwrap_or(0);
+        }
+    }
+
+    if let Some(done) = current {
+        interfaces.push(done);
+    }
+
+    interfaces
+}
+
+/// First whitespace-separated token parsed as a number
+fn leading_number(s: &str) -> Option<u64> {
+    s.split_whitespace().next()?.trim_end_matches(',').parse().ok()
+}
+
+/// Number immediately preceding `suffix` (e.g. "4567" in "..., 4567 bytes")
+fn number_before(s: &str, suffix: &str) -> Option<u64> {
+    let head = s.split(suffix).next()?;
+    head.rsplit([' ', ',']).find(|t| !t.is_empty())?.parse().ok()
+}
@@ -0,0 +1,266 @@
+//! # Adapter Credentials
+//!
+//! Where the REST adapters get their secrets from. A `CredentialSource` is
+//! asked for credentials only when the adapter authenticates, so secrets can
+//! live in the environment, in a mounted secrets file or behind a caller's
+//! own vault lookup instead of in code. Secrets are wrapped in `Secret`,
+//! whose `Debug` output is redacted.
+//!
+//! ```rust,ignore
+//! let source = EnvCredentials::new("UNIFI_PASSWORD").with_username_var("UNIFI_USERNAME");
+//! let adapter = UniFiAdapter::with_credentials("https://192.168.1.1:8443", "default", source).await?;
+//! ```
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// A secret value that never appears in `Debug` output
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    /// Wrap a secret value
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// The secret itself, for handing to the remote system
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(<redacted>)")
+    }
+}
+
+/// Resolved credentials: an optional username and a secret
+///
+/// The secret is a password or an API token, depending on the adapter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    /// Login username, for systems that authenticate with one
+    pub username: Option<String>,
+    /// Password or API token
+    pub secret: Secret,
+}
+
+impl Credentials {
+    /// Username and password credentials
+    pub fn login(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            username: Some(username.into()),
+            secret: Secret::new(password),
+        }
+    }
+
+    /// Token-only credentials
+    pub fn token(token: impl Into<String>) -> Self {
+        Self {
+            username: None,
+            secret: Secret::new(token),
+        }
+    }
+}
+
+/// Errors resolving credentials
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum CredentialError {
+    /// An environment variable is unset or not valid unicode
+    #[error("Environment variable {0} is not set")]
+    MissingEnv(String),
+    /// A secrets file could not be read
+    #[error("Failed to read credentials from {path}: {message}")]
+    File {
+        /// File that was read
+        path: PathBuf,
+        /// Why reading failed
+        message: String,
+    },
+    /// The source resolved to an empty secret
+    #[error("Credential source yielded an empty secret")]
+    Empty,
+    /// A caller-supplied source failed
+    #[error("Credential source failed: {0}")]
+    Source(String),
+}
+
+/// Where an adapter gets its credentials from
+///
+/// Implementations are consulted each time the adapter authenticates, so a
+/// rotated secret is picked up on the next login. `Debug` must not reveal
+/// the secret.
+pub trait CredentialSource: Send + Sync + fmt::Debug {
+    /// Fetch the current credentials
+    fn resolve(&self) -> Result<Credentials, CredentialError>;
+}
+
+/// Fixed credentials, as passed to the adapters' `&str` constructors
+impl CredentialSource for Credentials {
+    fn resolve(&self) -> Result<Credentials, CredentialError> {
+        Ok(self.clone())
+    }
+}
+
+impl<S: CredentialSource + ?Sized> CredentialSource for Arc<S> {
+    fn resolve(&self) -> Result<Credentials, CredentialError> {
+        (**self).resolve()
+    }
+}
+
+/// Credentials read from environment variables
+#[derive(Debug, Clone)]
+pub struct EnvCredentials {
+    secret_var: String,
+    username_var: Option<String>,
+}
+
+impl EnvCredentials {
+    /// Read the secret from `secret_var`
+    pub fn new(secret_var: impl Into<String>) -> Self {
+        Self {
+            secret_var: secret_var.into(),
+            username_var: None,
+        }
+    }
+
+    /// Also read the username from `username_var`
+    pub fn with_username_var(mut self, username_var: impl Into<String>) -> Self {
+        self.username_var = Some(username_var.into());
+        self
+    }
+}
+
+impl CredentialSource for EnvCredentials {
+    fn resolve(&self) -> Result<Credentials, CredentialError> {
+        let read = |var: &str| std::env::var(var).map_err(|_| CredentialError::MissingEnv(var.to_string()));
+        let username = self.username_var.as_deref().map(read).transpose()?;
+        Ok(Credentials {
+            username,
+            secret: non_empty(read(&self.secret_var)?)?,
+        })
+    }
+}
+
+/// Credentials read from a secrets file, such as a mounted container secret
+///
+/// The whole file is the secret, minus trailing whitespace.
+#[derive(Debug, Clone)]
+pub struct FileCredentials {
+    path: PathBuf,
+    username: Option<String>,
+}
+
+impl FileCredentials {
+    /// Read the secret from the file at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            username: None,
+        }
+    }
+
+    /// Log in as `username`
+    pub fn with_username(mut self, username: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self
+    }
+}
+
+impl CredentialSource for FileCredentials {
+    fn resolve(&self) -> Result<Credentials, CredentialError> {
+        let contents = std::fs::read_to_string(&self.path).map_err(|e| CredentialError::File {
+            path: self.path.clone(),
+            message: e.to_string(),
+        })?;
+        Ok(Credentials {
+            username: self.username.clone(),
+            secret: non_empty(contents.trim_end().to_string())?,
+        })
+    }
+}
+
+/// Credentials produced by a caller-supplied closure, e.g. a vault lookup
+pub struct FnCredentials<F> {
+    resolve: F,
+}
+
+impl<F> FnCredentials<F>
+where
+    F: Fn() -> Result<Credentials, CredentialError> + Send + Sync,
+{
+    /// Call `resolve` whenever credentials are needed
+    pub fn new(resolve: F) -> Self {
+        Self { resolve }
+    }
+}
+
+impl<F> fmt::Debug for FnCredentials<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnCredentials").finish_non_exhaustive()
+    }
+}
+
+impl<F> CredentialSource for FnCredentials<F>
+where
+    F: Fn() -> Result<Credentials, CredentialError> + Send + Sync,
+{
+    fn resolve(&self) -> Result<Credentials, CredentialError> {
+        (self.resolve)()
+    }
+}
+
+fn non_empty(secret: String) -> Result<Secret, CredentialError> {
+    if secret.is_empty() {
+        Err(CredentialError::Empty)
+    } else {
+        Ok(Secret(secret))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_credentials_resolve() {
+        let (user_var, secret_var) = ("CIM_NETWORK_TEST_ENV_USER", "CIM_NETWORK_TEST_ENV_SECRET");
+        std::env::set_var(user_var, "admin");
+        std::env::set_var(secret_var, "hunter2");
+
+        let credentials = EnvCredentials::new(secret_var).with_username_var(user_var).resolve().unwrap();
+        assert_eq!(credentials, Credentials::login("admin", "hunter2"));
+    }
+
+    #[test]
+    fn test_env_credentials_missing_var() {
+        let result = EnvCredentials::new("CIM_NETWORK_TEST_UNSET_SECRET").resolve();
+        assert!(matches!(result, Err(CredentialError::MissingEnv(var)) if var == "CIM_NETWORK_TEST_UNSET_SECRET"));
+    }
+
+    #[test]
+    fn test_file_credentials_trim_trailing_newline() {
+        let path = std::env::temp_dir().join(format!("cim-network-secret-{}", uuid::Uuid::now_v7()));
+        std::fs::write(&path, "s3cr3t-token\n").unwrap();
+
+        let credentials = FileCredentials::new(&path).resolve().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(credentials, Credentials::token("s3cr3t-token"));
+    }
+
+    #[test]
+    fn test_fn_credentials_called_on_resolve() {
+        let source = FnCredentials::new(|| Ok(Credentials::token("from-vault")));
+        assert_eq!(source.resolve().unwrap().secret.expose(), "from-vault");
+    }
+
+    #[test]
+    fn test_debug_redacts_secret() {
+        let debug = format!("{:?}", Credentials::login("admin", "hunter2"));
+        assert!(debug.contains("admin"));
+        assert!(!debug.contains("hunter2"));
+    }
+}
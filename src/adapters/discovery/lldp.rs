@@ -0,0 +1,641 @@
+//! # LLDP Neighbor Discovery
+//!
+//! Builds `DiscoveredDevice` and `ConnectionInfo` records from the LLDP
+//! neighbor table of one local device.
+//!
+//! ## Table Formats
+//!
+//! Tables are fetched through an [`LldpSource`] in one of two textual forms:
+//!
+//! - `show lldp neighbors detail` output (IOS style; most NOS CLIs are close)
+//! - `snmpwalk -On` output covering `LLDP-MIB::lldpLocPortTable`,
+//!   `lldpRemTable` and `lldpRemManAddrTable`
+//!
+//! Both parsers are tolerant: lines they do not recognise are skipped.
+//!
+//! ## Identity
+//!
+//! Neighbors are identified by chassis id. Only neighbors whose chassis id is
+//! a MAC address are reported as discovered devices, since `DiscoveredDevice`
+//! is keyed by MAC; all neighbors can still be turned into connections.
+
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
+
+use crate::domain::ports::{
+    ConnectionInfo, DeviceDetails, DiscoveredDevice, DiscoveryPort, EventSubscription, PortError,
+};
+use crate::domain::value_objects::{ConnectionId, ConnectionType, DeviceId, DeviceType, MacAddress, PortId};
+
+/// `LLDP-MIB::lldpLocPortEntry`
+const LLDP_LOC_PORT_ENTRY: &str = "1.0.8802.1.1.2.1.3.7.1.";
+/// `LLDP-MIB::lldpRemEntry`
+const LLDP_REM_ENTRY: &str = "1.0.8802.1.1.2.1.4.1.1.";
+/// `LLDP-MIB::lldpRemManAddrEntry`
+const LLDP_REM_MAN_ADDR_ENTRY: &str = "1.0.8802.1.1.2.1.4.2.1.";
+
+/// Raw LLDP neighbor table of one device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LldpTable {
+    /// `show lldp neighbors detail` output
+    Cli(String),
+    /// `snmpwalk -On` output of the LLDP-MIB tables
+    Snmp(String),
+}
+
+impl LldpTable {
+    /// Parse the table into neighbors
+    pub fn neighbors(&self) -> Vec<LldpNeighbor> {
+        match self {
+            LldpTable::Cli(output) => parse_lldp_cli(output),
+            LldpTable::Snmp(output) => parse_lldp_snmp(output),
+        }
+    }
+}
+
+/// Source of a device's current LLDP neighbor table
+#[async_trait]
+pub trait LldpSource: Send + Sync {
+    /// Fetch the neighbor table
+    async fn fetch(&self) -> Result<LldpTable, PortError>;
+}
+
+/// A captured table is its own (unchanging) source
+#[async_trait]
+impl LldpSource for LldpTable {
+    async fn fetch(&self) -> Result<LldpTable, PortError> {
+        Ok(self.clone())
+    }
+}
+
+/// LLDP system capabilities relevant to classification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LldpCapabilities {
+    /// MAC bridge (switch)
+    pub bridge: bool,
+    /// WLAN access point
+    pub wlan_access_point: bool,
+    /// Router
+    pub router: bool,
+}
+
+/// One neighbor entry from an LLDP table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LldpNeighbor {
+    /// Local port the neighbor was seen on
+    pub local_port: String,
+    /// Chassis id as advertised
+    pub chassis_id: String,
+    /// Chassis id as a MAC address, when it is one
+    pub mac: Option<MacAddress>,
+    /// Neighbor's port id
+    pub port_id: String,
+    /// Neighbor's port description
+    pub port_description: Option<String>,
+    /// Neighbor's system name
+    pub system_name: Option<String>,
+    /// Neighbor's system description
+    pub system_description: Option<String>,
+    /// Enabled system capabilities
+    pub capabilities: LldpCapabilities,
+    /// First advertised management address
+    pub management_address: Option<IpAddr>,
+}
+
+impl LldpNeighbor {
+    fn new(local_port: String) -> Self {
+        Self {
+            local_port,
+            chassis_id: String::new(),
+            mac: None,
+            port_id: String::new(),
+            port_description: None,
+            system_name: None,
+            system_description: None,
+            capabilities: LldpCapabilities::default(),
+            management_address: None,
+        }
+    }
+
+    /// Classify the neighbor from its enabled capabilities, then its OUI
+    ///
+    /// Access points usually also bridge and gateways usually also route, so
+    /// WLAN wins over router, which wins over bridge.
+    pub fn device_type(&self) -> DeviceType {
+        let caps = self.capabilities;
+        if caps.wlan_access_point {
+            DeviceType::AccessPoint
+        } else if caps.router {
+            DeviceType::Gateway
+        } else if caps.bridge {
+            DeviceType::Switch
+        } else {
+            self.mac
+                .and_then(DeviceType::from_mac_oui)
+                .unwrap_or_else(|| DeviceType::Generic { model: self.model().unwrap_or("unknown").to_string() })
+        }
+    }
+
+    /// Model string: the first line of the system description
+    pub fn model(&self) -> Option<&str> {
+        self.system_description
+            .as_deref()
+            .and_then(|d| d.lines().map(str::trim).find(|l| !l.is_empty()))
+    }
+
+    /// Convert to a discovered device (requires a MAC chassis id)
+    pub fn to_discovered_device(&self) -> Option<DiscoveredDevice> {
+        Some(DiscoveredDevice {
+            mac: self.mac?,
+            ip_address: self.management_address,
+            device_type: self.device_type(),
+            model: self.model().map(str::to_string),
+            vendor_id: None,
+            adopted: false,
+            name: self.system_name.clone(),
+            port_id: Some(self.port_id.clone()),
+        })
+    }
+
+    /// Connection from `local_device`'s local port to `remote_device`'s port
+    pub fn connection(&self, local_device: DeviceId, remote_device: DeviceId) -> ConnectionInfo {
+        ConnectionInfo {
+            connection_id: ConnectionId::new(),
+            source_device: local_device,
+            source_port: PortId::new(self.local_port.clone()),
+            target_device: remote_device,
+            target_port: PortId::new(self.port_id.clone()),
+            connection_type: ConnectionType::Ethernet,
+            speed: None,
+        }
+    }
+}
+
+/// LLDP discovery adapter
+///
+/// Implements `DiscoveryPort` from the neighbor table of one local device.
+pub struct LldpDiscovery {
+    /// Device whose neighbor table is read
+    local_device: DeviceId,
+    /// Where the table comes from
+    source: Arc<dyn LldpSource>,
+}
+
+impl LldpDiscovery {
+    /// Create a discovery adapter for `local_device`
+    pub fn new<S: LldpSource + 'static>(local_device: DeviceId, source: S) -> Self {
+        Self { local_device, source: Arc::new(source) }
+    }
+
+    /// Device whose neighbors are discovered
+    pub fn local_device(&self) -> DeviceId {
+        self.local_device
+    }
+
+    /// Fetch and parse the current neighbor table
+    pub async fn neighbors(&self) -> Result<Vec<LldpNeighbor>, PortError> {
+        Ok(self.source.fetch().await?.neighbors())
+    }
+
+    /// Infer topology edges from the local device to its neighbors
+    ///
+    /// `resolve` maps a neighbor to its domain device; neighbors it cannot
+    /// resolve are skipped.
+    pub async fn connections<F>(&self, resolve: F) -> Result<Vec<ConnectionInfo>, PortError>
+    where
+        F: Fn(&LldpNeighbor) -> Option<DeviceId>,
+    {
+        let connections = self.neighbors().await?
+            .iter()
+            .filter_map(|neighbor| {
+                let remote = resolve(neighbor);
+                if remote.is_none() {
+                    tracing::debug!(
+                        "No device for LLDP neighbor {} on {}",
+                        neighbor.chassis_id,
+                        neighbor.local_port
+                    );
+                }
+                remote.map(|remote| neighbor.connection(self.local_device, remote))
+            })
+            .collect();
+        Ok(connections)
+    }
+}
+
+#[async_trait]
+impl DiscoveryPort for LldpDiscovery {
+    async fn discover_devices(&self) -> Result<Vec<DiscoveredDevice>, PortError> {
+        let mut seen = HashSet::new();
+        let devices = self.neighbors().await?
+            .iter()
+            .filter_map(LldpNeighbor::to_discovered_device)
+            // A neighbor seen on several local ports (e.g. a LAG) is one device
+            .filter(|device| seen.insert(device.mac))
+            .collect();
+        Ok(devices)
+    }
+
+    async fn get_device_details(&self, device_id: DeviceId) -> Result<DeviceDetails, PortError> {
+        // LLDP only knows chassis ids; domain ids are assigned by the service
+        Err(PortError::DeviceNotFound(device_id))
+    }
+
+    async fn subscribe_events(&self) -> Result<EventSubscription, PortError> {
+        Err(PortError::NotSupported("LLDP discovery has no event feed".to_string()))
+    }
+}
+
+// ============================================================================
+// CLI parser
+// ============================================================================
+
+/// Parse `show lldp neighbors detail`
+pub fn parse_lldp_cli(output: &str) -> Vec<LldpNeighbor> {
+    let mut neighbors = Vec::new();
+    let mut current: Option<LldpNeighbor> = None;
+    let mut in_system_description = false;
+    let mut in_management_addresses = false;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        if let Some(local) = trimmed.strip_prefix("Local Intf:") {
+            neighbors.extend(current.take());
+            current = Some(LldpNeighbor::new(local.trim().to_string()));
+            in_system_description = false;
+            in_management_addresses = false;
+            continue;
+        }
+
+        let Some(neighbor) = current.as_mut() else {
+            continue;
+        };
+
+        if in_system_description {
+            // The description runs until the next blank line
+            if trimmed.is_empty() {
+                in_system_description = false;
+            } else {
+                let description = neighbor.system_description.get_or_insert_with(String::new);
+                if !description.is_empty() {
+                    description.push('\n');
+                }
+                description.push_str(trimmed);
+            }
+        } else if let Some(chassis) = trimmed.strip_prefix("Chassis id:") {
+            neighbor.chassis_id = chassis.trim().to_string();
+            neighbor.mac = MacAddress::parse(&neighbor.chassis_id).ok();
+        } else if let Some(port) = trimmed.strip_prefix("Port id:") {
+            neighbor.port_id = port.trim().to_string();
+        } else if let Some(description) = trimmed.strip_prefix("Port Description:") {
+            neighbor.port_description = non_empty(description);
+        } else if let Some(name) = trimmed.strip_prefix("System Name:") {
+            neighbor.system_name = non_empty(name);
+        } else if trimmed.starts_with("System Description:") {
+            in_system_description = true;
+        } else if let Some(codes) = trimmed.strip_prefix("Enabled Capabilities:") {
+            neighbor.capabilities = parse_capability_codes(codes);
+        } else if trimmed.starts_with("Management Addresses:") {
+            in_management_addresses = true;
+        } else if in_management_addresses {
+            if let Some(addr) = trimmed.strip_prefix("IP:").or_else(|| trimmed.strip_prefix("IPV6:")) {
+                if neighbor.management_address.is_none() {
+                    neighbor.management_address = addr.trim().parse().ok();
+                }
+            } else if !trimmed.is_empty() && !line.starts_with(' ') {
+                in_management_addresses = false;
+            }
+        }
+    }
+
+    neighbors.extend(current);
+    neighbors
+}
+
+/// Parse an IOS capability code list such as `B,R`
+fn parse_capability_codes(codes: &str) -> LldpCapabilities {
+    let mut capabilities = LldpCapabilities::default();
+    for code in codes.split(',').map(str::trim) {
+        match code {
+            "B" => capabilities.bridge = true,
+            "W" => capabilities.wlan_access_point = true,
+            "R" => capabilities.router = true,
+            _ => {}
+        }
+    }
+    capabilities
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+// ============================================================================
+// SNMP parser
+// ============================================================================
+
+/// A value from `snmpwalk` output
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SnmpValue {
+    /// `STRING: "..."`
+    Text(String),
+    /// `Hex-STRING: 00 11 ..`
+    Octets(Vec<u8>),
+    /// Any other type, raw
+    Other(String),
+}
+
+impl SnmpValue {
+    fn parse(raw: &str) -> Self {
+        match raw.split_once(": ") {
+            Some(("STRING", text)) => SnmpValue::Text(text.trim().trim_matches('"').to_string()),
+            Some(("Hex-STRING", hex)) => {
+                let octets: Option<Vec<u8>> = hex
+                    .split_whitespace()
+                    .map(|byte| u8::from_str_radix(byte, 16).ok())
+                    .collect();
+                octets.map(SnmpValue::Octets).unwrap_or_else(|| SnmpValue::Other(raw.to_string()))
+            }
+            _ => SnmpValue::Other(raw.to_string()),
+        }
+    }
+
+    /// Octets of a string-typed value
+    fn octets(&self) -> Vec<u8> {
+        match self {
+            SnmpValue::Text(text) => text.as_bytes().to_vec(),
+            SnmpValue::Octets(octets) => octets.clone(),
+            SnmpValue::Other(_) => Vec::new(),
+        }
+    }
+
+    /// Printable form; six raw octets are shown as a MAC address
+    fn display(&self) -> String {
+        match self {
+            SnmpValue::Text(text) => text.clone(),
+            SnmpValue::Octets(octets) => match <[u8; 6]>::try_from(octets.as_slice()) {
+                Ok(bytes) => MacAddress::from_bytes(bytes).to_string(),
+                Err(_) => String::from_utf8_lossy(octets).into_owned(),
+            },
+            SnmpValue::Other(raw) => raw.clone(),
+        }
+    }
+
+    /// MAC address carried by the value, as octets or as text
+    fn mac(&self) -> Option<MacAddress> {
+        match self {
+            SnmpValue::Octets(octets) => <[u8; 6]>::try_from(octets.as_slice()).ok().map(MacAddress::from_bytes),
+            SnmpValue::Text(text) => MacAddress::parse(text).ok(),
+            SnmpValue::Other(_) => None,
+        }
+    }
+}
+
+/// Parse `snmpwalk -On` output of the LLDP-MIB tables
+///
+/// Remote entries are indexed `timeMark.localPortNum.remIndex`; the local
+/// port name comes from `lldpLocPortId` when it was walked, otherwise the
+/// port number is used.
+pub fn parse_lldp_snmp(output: &str) -> Vec<LldpNeighbor> {
+    let mut local_ports: HashMap<u32, String> = HashMap::new();
+    let mut remotes: BTreeMap<(u32, u32), LldpNeighbor> = BTreeMap::new();
+    let mut addresses: BTreeMap<(u32, u32), IpAddr> = BTreeMap::new();
+
+    for line in output.lines() {
+        let Some((oid, value)) = line.split_once(" = ") else {
+            continue;
+        };
+        let oid = oid.trim().trim_start_matches('.');
+
+        if let Some(rest) = oid.strip_prefix(LLDP_LOC_PORT_ENTRY) {
+            // lldpLocPortId.localPortNum
+            if let [3, port] = oid_arcs(rest).as_slice() {
+                local_ports.insert(*port, SnmpValue::parse(value).display());
+            }
+        } else if let Some(rest) = oid.strip_prefix(LLDP_REM_ENTRY) {
+            let arcs = oid_arcs(rest);
+            let [column, _time_mark, port, index] = arcs.as_slice() else {
+                continue;
+            };
+            let neighbor = remotes
+                .entry((*port, *index))
+                .or_insert_with(|| LldpNeighbor::new(port.to_string()));
+            let value = SnmpValue::parse(value);
+            match column {
+                5 => {
+                    neighbor.chassis_id = value.display();
+                    neighbor.mac = value.mac();
+                }
+                7 => neighbor.port_id = value.display(),
+                8 => neighbor.port_description = non_empty(&value.display()),
+                9 => neighbor.system_name = non_empty(&value.display()),
+                10 => neighbor.system_description = non_empty(&value.display()),
+                12 => neighbor.capabilities = parse_capability_bits(&value.octets()),
+                _ => {}
+            }
+        } else if let Some(rest) = oid.strip_prefix(LLDP_REM_MAN_ADDR_ENTRY) {
+            // column.timeMark.localPortNum.remIndex.addrSubtype.addrLen.addr...
+            let arcs = oid_arcs(rest);
+            if let [_column, _time_mark, port, index, subtype, _len, addr @ ..] = arcs.as_slice() {
+                if let Some(ip) = management_address(*subtype, addr) {
+                    addresses.entry((*port, *index)).or_insert(ip);
+                }
+            }
+        }
+    }
+
+    remotes
+        .into_iter()
+        .map(|(key, mut neighbor)| {
+            if let Some(name) = local_ports.get(&key.0) {
+                neighbor.local_port = name.clone();
+            }
+            neighbor.management_address = addresses.get(&key).copied();
+            neighbor
+        })
+        .collect()
+}
+
+fn oid_arcs(oid: &str) -> Vec<u32> {
+    oid.split('.').filter_map(|arc| arc.parse().ok()).collect()
+}
+
+/// Decode an address from an `lldpRemManAddrTable` index (IANA address family)
+fn management_address(subtype: u32, addr: &[u32]) -> Option<IpAddr> {
+    let octets: Vec<u8> = addr.iter().map(|&arc| u8::try_from(arc).ok()).collect::<Option<_>>()?;
+    match subtype {
+        1 => <[u8; 4]>::try_from(octets).ok().map(|o| IpAddr::V4(Ipv4Addr::from(o))),
+        2 => <[u8; 16]>::try_from(octets).ok().map(|o| IpAddr::V6(Ipv6Addr::from(o))),
+        _ => None,
+    }
+}
+
+/// Decode `LldpSystemCapabilitiesMap` BITS (bit 0 is the MSB of the first octet)
+fn parse_capability_bits(octets: &[u8]) -> LldpCapabilities {
+    let bit = |n: usize| octets.get(n / 8).is_some_and(|byte| byte & (0x80 >> (n % 8)) != 0);
+    LldpCapabilities {
+        bridge: bit(2),
+        wlan_access_point: bit(3),
+        router: bit(4),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LLDP_CLI: &str = "\
+Capability codes:
+    (R) Router, (B) Bridge, (T) Telephone, (C) DOCSIS Cable Device
+    (W) WLAN Access Point, (P) Repeater, (S) Station, (O) Other
+------------------------------------------------
+Local Intf: Gi1/0/24
+Chassis id: 001c.7311.2233
+Port id: Ethernet48
+Port Description: uplink to campus
+System Name: dc-leaf1
+
+System Description:
+Arista Networks EOS version 4.28.3M
+running on an Arista Networks DCS-7050SX3-48YC8
+
+Time remaining: 98 seconds
+System Capabilities: B,R
+Enabled Capabilities: B
+Management Addresses:
+    IP: 10.0.10.11
+Auto Negotiation - not supported
+------------------------------------------------
+Local Intf: Gi1/0/7
+Chassis id: lab-ap-17
+Port id: eth0
+System Name: lab-ap-17
+
+System Description:
+Embedded AP
+
+Enabled Capabilities: B,W
+Total entries displayed: 2
+";
+
+    const LLDP_SNMP: &str = "\
+.1.0.8802.1.1.2.1.3.7.1.3.5 = STRING: \"ether5\"
+.1.0.8802.1.1.2.1.3.7.1.3.7 = STRING: \"ether7\"
+.1.0.8802.1.1.2.1.4.1.1.4.0.5.1 = INTEGER: 4
+.1.0.8802.1.1.2.1.4.1.1.5.0.5.1 = Hex-STRING: 4C 5E 0C 12 34 56
+.1.0.8802.1.1.2.1.4.1.1.6.0.5.1 = INTEGER: 5
+.1.0.8802.1.1.2.1.4.1.1.7.0.5.1 = STRING: \"sfp-sfpplus1\"
+.1.0.8802.1.1.2.1.4.1.1.8.0.5.1 = STRING: \"\"
+.1.0.8802.1.1.2.1.4.1.1.9.0.5.1 = STRING: \"border-rtr\"
+.1.0.8802.1.1.2.1.4.1.1.10.0.5.1 = STRING: \"MikroTik RouterOS 7.14\"
+.1.0.8802.1.1.2.1.4.1.1.12.0.5.1 = Hex-STRING: 08 00
+.1.0.8802.1.1.2.1.4.1.1.5.0.7.2 = Hex-STRING: 24 A4 3C 00 00 01
+.1.0.8802.1.1.2.1.4.1.1.7.0.7.2 = Hex-STRING: 24 A4 3C 00 00 02
+.1.0.8802.1.1.2.1.4.1.1.9.0.7.2 = STRING: \"office-ap\"
+.1.0.8802.1.1.2.1.4.1.1.12.0.7.2 = Hex-STRING: 30 00
+.1.0.8802.1.1.2.1.4.2.1.3.0.5.1.1.4.192.168.88.1 = INTEGER: 2
+";
+
+    // ========================================================================
+    // CLI Parser Tests
+    // ========================================================================
+
+    #[test]
+    fn test_parse_lldp_cli() {
+        let neighbors = parse_lldp_cli(LLDP_CLI);
+        assert_eq!(neighbors.len(), 2);
+
+        let leaf = &neighbors[0];
+        assert_eq!(leaf.local_port, "Gi1/0/24");
+        assert_eq!(leaf.mac, Some(MacAddress::parse("00:1c:73:11:22:33").unwrap()));
+        assert_eq!(leaf.port_id, "Ethernet48");
+        assert_eq!(leaf.port_description.as_deref(), Some("uplink to campus"));
+        assert_eq!(leaf.system_name.as_deref(), Some("dc-leaf1"));
+        assert_eq!(leaf.model(), Some("Arista Networks EOS version 4.28.3M"));
+        assert_eq!(leaf.management_address, Some("10.0.10.11".parse().unwrap()));
+        assert_eq!(leaf.device_type(), DeviceType::Switch);
+
+        let ap = &neighbors[1];
+        assert_eq!(ap.chassis_id, "lab-ap-17");
+        assert!(ap.mac.is_none());
+        assert_eq!(ap.device_type(), DeviceType::AccessPoint);
+    }
+
+    // ========================================================================
+    // SNMP Parser Tests
+    // ========================================================================
+
+    #[test]
+    fn test_parse_lldp_snmp() {
+        let neighbors = parse_lldp_snmp(LLDP_SNMP);
+        assert_eq!(neighbors.len(), 2);
+
+        let router = &neighbors[0];
+        assert_eq!(router.local_port, "ether5");
+        assert_eq!(router.mac, Some(MacAddress::parse("4c:5e:0c:12:34:56").unwrap()));
+        assert_eq!(router.port_id, "sfp-sfpplus1");
+        assert_eq!(router.port_description, None);
+        assert_eq!(router.system_name.as_deref(), Some("border-rtr"));
+        assert_eq!(router.management_address, Some("192.168.88.1".parse().unwrap()));
+        assert_eq!(router.device_type(), DeviceType::Gateway);
+
+        let ap = &neighbors[1];
+        assert_eq!(ap.local_port, "ether7");
+        // A MAC-subtype port id is shown as a MAC address
+        assert_eq!(ap.port_id, "24:a4:3c:00:00:02");
+        assert!(ap.capabilities.bridge && ap.capabilities.wlan_access_point);
+        assert_eq!(ap.device_type(), DeviceType::AccessPoint);
+        assert_eq!(ap.management_address, None);
+    }
+
+    #[test]
+    fn test_capability_bits() {
+        assert_eq!(parse_capability_bits(&[0x28]), LldpCapabilities { bridge: true, wlan_access_point: false, router: true });
+        assert_eq!(parse_capability_bits(&[]), LldpCapabilities::default());
+    }
+
+    // ========================================================================
+    // DiscoveryPort Tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn test_discover_devices_from_captured_table() {
+        let discovery = LldpDiscovery::new(DeviceId::new(), LldpTable::Cli(LLDP_CLI.to_string()));
+
+        let devices = discovery.discover_devices().await.unwrap();
+
+        // The AP advertises a local chassis id, so only the leaf is a device
+        assert_eq!(devices.len(), 1);
+        let leaf = &devices[0];
+        assert_eq!(leaf.mac, MacAddress::parse("00:1c:73:11:22:33").unwrap());
+        assert_eq!(leaf.name.as_deref(), Some("dc-leaf1"));
+        assert_eq!(leaf.port_id.as_deref(), Some("Ethernet48"));
+        assert_eq!(leaf.ip_address, Some("10.0.10.11".parse().unwrap()));
+        assert!(!leaf.adopted);
+    }
+
+    #[tokio::test]
+    async fn test_connections_map_local_to_remote_ports() {
+        let local = DeviceId::new();
+        let router = DeviceId::new();
+        let discovery = LldpDiscovery::new(local, LldpTable::Snmp(LLDP_SNMP.to_string()));
+
+        let connections = discovery
+            .connections(|neighbor| (neighbor.system_name.as_deref() == Some("border-rtr")).then_some(router))
+            .await
+            .unwrap();
+
+        // The unresolved AP is skipped
+        assert_eq!(connections.len(), 1);
+        let link = &connections[0];
+        assert_eq!(link.source_device, local);
+        assert_eq!(link.source_port, PortId::new("ether5"));
+        assert_eq!(link.target_device, router);
+        assert_eq!(link.target_port, PortId::new("sfp-sfpplus1"));
+        assert_eq!(link.connection_type, ConnectionType::Ethernet);
+    }
+}
@@ -0,0 +1,10 @@
+//! # Discovery Adapters
+//!
+//! Implement `DiscoveryPort` from sources other than a vendor controller's
+//! own device list.
+//!
+//! - `lldp` - LLDP neighbor tables (CLI or SNMP `LLDP-MIB`)
+
+pub mod lldp;
+
+pub use lldp::{LldpCapabilities, LldpDiscovery, LldpNeighbor, LldpSource, LldpTable};
@@ -0,0 +1,129 @@
+//! Junos NETCONF transport
+//!
+//! Junos serves NETCONF as the `netconf` SSH subsystem. Every call opens a
+//! session, exchanges `<hello>` messages, sends its RPCs in order and closes
+//! the session. The client only advertises NETCONF 1.0, so messages use the
+//! `]]>]]>` end-of-message framing.
+//!
+//! `NetconfTransport` abstracts the session so the adapter can be exercised
+//! against a mocked device in tests.
+
+use super::types::{reply_error, JuniperError};
+use crate::adapters::cisco::{CiscoError, SshCredentials, SshTransport};
+use async_trait::async_trait;
+use std::io::{BufReader, Read, Write};
+
+/// Marker ending every NETCONF 1.0 message
+pub const END_OF_MESSAGE: &str = "]]>]]>";
+
+/// Hello sent by the client, advertising NETCONF 1.0 only
+const CLIENT_HELLO: &str = concat!(
+    r#"<hello xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><capabilities>"#,
+    "<capability>urn:ietf:params:netconf:base:1.0</capability>",
+    "</capabilities></hello>]]>]]>",
+);
+
+/// Transport for running Junos XML RPCs on a host
+#[async_trait]
+pub trait NetconfTransport: Send + Sync {
+    /// Send `rpcs` in order over one NETCONF session and return their replies
+    ///
+    /// Each RPC is the bare operation, e.g. `<get-system-information/>`. The
+    /// transport stops after the first reply carrying an error, so a
+    /// `<commit/>` following a rejected load is never sent and fewer replies
+    /// than RPCs may come back.
+    async fn session(&self, host: &str, rpcs: &[String]) -> Result<Vec<String>, JuniperError>;
+}
+
+/// `<rpc>` message for an operation, framed for NETCONF 1.0
+pub fn rpc_message(message_id: usize, operation: &str) -> String {
+    format!(
+        r#"<rpc message-id="{}" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">{}</rpc>{}"#,
+        message_id, operation, END_OF_MESSAGE
+    )
+}
+
+/// Read one NETCONF 1.0 message, without its end-of-message marker
+pub fn read_message(reader: &mut impl Read) -> Result<String, JuniperError> {
+    let mut message = Vec::new();
+    let mut byte = [0u8; 1];
+    while !message.ends_with(END_OF_MESSAGE.as_bytes()) {
+        match reader.read(&mut byte) {
+            Ok(0) => return Err(JuniperError::Session("session closed mid-message".to_string())),
+            Ok(_) => message.push(byte[0]),
+            Err(e) => return Err(JuniperError::Session(format!("read: {}", e))),
+        }
+    }
+    message.truncate(message.len() - END_OF_MESSAGE.len());
+    String::from_utf8(message).map_err(|e| JuniperError::Parse(format!("reply is not UTF-8: {}", e)))
+}
+
+/// `NetconfTransport` over the SSH `netconf` subsystem using libssh2
+///
+/// Junos accepts NETCONF on port 830 once `system services netconf ssh` is
+/// configured; set `SshCredentials::port` to match. libssh2 is blocking, so
+/// every session runs on the blocking thread pool.
+pub struct NetconfSshTransport {
+    credentials: SshCredentials,
+}
+
+impl NetconfSshTransport {
+    /// Create a new NETCONF transport
+    pub fn new(credentials: SshCredentials) -> Self {
+        Self { credentials }
+    }
+
+    fn session_blocking(credentials: &SshCredentials, host: &str, rpcs: &[String]) -> Result<Vec<String>, JuniperError> {
+        let session = SshTransport::open_session(credentials, host).map_err(|e| match e {
+            CiscoError::Auth(msg) => JuniperError::Auth(msg),
+            other => JuniperError::Session(other.to_string()),
+        })?;
+        let mut channel = session.channel_session().map_err(|e| JuniperError::Session(e.to_string()))?;
+        channel
+            .subsystem("netconf")
+            .map_err(|e| JuniperError::Session(format!("netconf subsystem on {}: {}", host, e)))?;
+
+        let mut channel = BufReader::new(channel);
+        let write = |channel: &mut BufReader<ssh2::Channel>, message: &str| {
+            channel
+                .get_mut()
+                .write_all(message.as_bytes())
+                .map_err(|e| JuniperError::Session(format!("write: {}", e)))
+        };
+
+        // The server's capabilities are not needed: everything sent is base 1.0
+        read_message(&mut channel)?;
+        write(&mut channel, CLIENT_HELLO)?;
+
+        let mut replies = Vec::new();
+        for (index, rpc) in rpcs.iter().enumerate() {
+            write(&mut channel, &rpc_message(index + 1, rpc))?;
+            let reply = read_message(&mut channel)?;
+            let failed = reply_error(&reply).is_some();
+            replies.push(reply);
+            if failed {
+                break;
+            }
+        }
+
+        // Closing the session discards anything left uncommitted
+        let _ = write(&mut channel, &rpc_message(rpcs.len() + 1, "<close-session/>"));
+        let _ = channel.get_mut().send_eof();
+        let _ = channel.get_mut().wait_close();
+
+        Ok(replies)
+    }
+}
+
+#[async_trait]
+impl NetconfTransport for NetconfSshTransport {
+    async fn session(&self, host: &str, rpcs: &[String]) -> Result<Vec<String>, JuniperError> {
+        let credentials = self.credentials.clone();
+        let host = host.to_string();
+        let rpcs = rpcs.to_vec();
+
+        tokio::task::spawn_blocking(move || Self::session_blocking(&credentials, &host, &rpcs))
+            .await
+            .map_err(|e| JuniperError::Session(format!("NETCONF task failed: {}", e)))?
+    }
+}
@@ -0,0 +1,570 @@
+//! # Juniper Junos Adapter
+//!
+//! Implements network management ports for Junos routers, switches and
+//! firewalls through the Junos XML API over NETCONF.
+//!
+//! ## Supported Operations
+//!
+//! - Inventory of the configured devices (`<get-system-information>`,
+//!   `<get-chassis-mac-addresses>`)
+//! - Configuration push through a private candidate and `<commit>`
+//! - Reboot (`<request-reboot>`)
+//! - CPU, memory, temperature and uptime (`<get-route-engine-information>`)
+//!   and interface statistics (`<get-interface-information>`)
+//!
+//! ## Device Identity
+//!
+//! Junos devices are managed directly, so the host name or address given to
+//! the adapter is the vendor ID. The device's MAC is its chassis base MAC.
+//!
+//! ## Config Payload
+//!
+//! `apply_config` translates a `DeviceConfigRequest` into `set` commands.
+//! `apply_raw_config` expects the commands themselves, either as a string or
+//! a list of strings, bare or under a `config` key:
+//!
+//! ```json
+//! { "config": ["set system host-name edge-01", "set vlans users vlan-id 10"] }
+//! ```
+//!
+//! The commands are loaded into a private candidate configuration and
+//! committed in one NETCONF session. Junos commits all of them or none: if
+//! the load or the commit is rejected, the session closes and the candidate
+//! is discarded, leaving the running configuration untouched.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::adapters::cisco::SshCredentials;
+use crate::domain::aggregates::NetworkDeviceAggregate;
+use crate::domain::ports::*;
+use crate::domain::functor::*;
+use crate::domain::events::*;
+use crate::domain::value_objects::*;
+
+mod client;
+mod types;
+
+pub use client::{read_message, rpc_message, NetconfSshTransport, NetconfTransport, END_OF_MESSAGE};
+pub use types::*;
+
+/// RPCs read for each device in the inventory
+const INVENTORY_RPCS: [&str; 2] = ["<get-system-information/>", "<get-chassis-mac-addresses/>"];
+
+/// RPCs read for device statistics
+const STATS_RPCS: [&str; 2] = [
+    "<get-route-engine-information/>",
+    "<get-interface-information><extensive/></get-interface-information>",
+];
+
+/// Juniper Junos adapter
+///
+/// Implements both:
+/// - `DeviceControlPort` for hexagonal architecture
+/// - `VendorExtension` for Kan extension mapping
+pub struct JuniperAdapter {
+    /// NETCONF transport (SSH in production)
+    transport: Arc<dyn NetconfTransport>,
+    /// Hosts of the managed devices
+    hosts: Vec<String>,
+    /// Whether every device has been reached
+    connected: AtomicBool,
+    /// Mapping from domain DeviceId to vendor ID (host)
+    device_mapping: Arc<RwLock<HashMap<DeviceId, String>>>,
+    /// Mapping from vendor ID to domain DeviceId
+    reverse_mapping: Arc<RwLock<HashMap<String, DeviceId>>>,
+}
+
+impl JuniperAdapter {
+    /// Create an adapter for devices sharing one set of SSH credentials
+    ///
+    /// # Arguments
+    /// * `hosts` - device addresses, e.g. `["192.0.2.21"]`
+    /// * `credentials` - Junos user allowed to commit and reboot; NETCONF
+    ///   usually listens on port 830
+    pub fn new(hosts: &[&str], credentials: SshCredentials) -> Self {
+        Self::with_transport(hosts, Arc::new(NetconfSshTransport::new(credentials)))
+    }
+
+    /// Create an adapter with a custom NETCONF transport
+    pub fn with_transport(hosts: &[&str], transport: Arc<dyn NetconfTransport>) -> Self {
+        Self {
+            transport,
+            hosts: hosts.iter().map(|h| h.to_string()).collect(),
+            connected: AtomicBool::new(false),
+            device_mapping: Arc::new(RwLock::new(HashMap::new())),
+            reverse_mapping: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Map a domain device to its vendor ID (host)
+    pub async fn map_device(&self, device_id: DeviceId, vendor_id: String) {
+        let mut mapping = self.device_mapping.write().await;
+        let mut reverse = self.reverse_mapping.write().await;
+        mapping.insert(device_id, vendor_id.clone());
+        reverse.insert(vendor_id, device_id);
+    }
+
+    /// Get vendor ID for a domain device
+    pub async fn get_vendor_id(&self, device_id: DeviceId) -> Option<String> {
+        let mapping = self.device_mapping.read().await;
+        mapping.get(&device_id).cloned()
+    }
+
+    /// Get domain device ID for a vendor ID
+    pub async fn get_device_id(&self, vendor_id: &str) -> Option<DeviceId> {
+        let reverse = self.reverse_mapping.read().await;
+        reverse.get(vendor_id).copied()
+    }
+
+    /// Resolve a vendor ID to one of the configured hosts
+    fn host(&self, vendor_id: &str) -> Result<&str, JuniperError> {
+        self.hosts
+            .iter()
+            .find(|h| *h == vendor_id)
+            .map(String::as_str)
+            .ok_or_else(|| JuniperError::NotFound(format!("Device {} is not configured", vendor_id)))
+    }
+
+    /// Run RPCs in one session, failing on the first error reply
+    async fn rpcs(&self, host: &str, rpcs: &[String]) -> Result<Vec<String>, JuniperError> {
+        let replies = self.transport.session(host, rpcs).await?;
+        if let Some(error) = replies.iter().find_map(|reply| reply_error(reply)) {
+            return Err(JuniperError::Rpc(error));
+        }
+        if replies.len() < rpcs.len() {
+            return Err(JuniperError::Session(format!(
+                "{} answered {} of {} RPCs",
+                host,
+                replies.len(),
+                rpcs.len()
+            )));
+        }
+        Ok(replies)
+    }
+
+    /// Load `set` commands into a private candidate and commit them
+    async fn commit(&self, host: &str, lines: &[String]) -> Result<(), JuniperError> {
+        let rpcs = [
+            "<open-configuration><private/></open-configuration>".to_string(),
+            format!(
+                r#"<load-configuration action="set" format="text"><configuration-set>{}</configuration-set></load-configuration>"#,
+                escape(&lines.join("\n"))
+            ),
+            "<commit/>".to_string(),
+            "<close-configuration/>".to_string(),
+        ];
+        self.rpcs(host, &rpcs).await?;
+
+        tracing::info!("Committed {} Junos config lines on {}", lines.len(), host);
+        Ok(())
+    }
+
+    /// Read one device's system information and chassis MAC
+    async fn read_device(&self, host: &str) -> Result<VendorDevice, JuniperError> {
+        let rpcs: Vec<String> = INVENTORY_RPCS.iter().map(|r| r.to_string()).collect();
+        let replies = self.rpcs(host, &rpcs).await?;
+        let system = SystemInformation::from_reply(&replies[0]);
+
+        let mac = text(&replies[1], "public-base-address")
+            .and_then(|m| MacAddress::parse(&m).ok())
+            .ok_or_else(|| JuniperError::Parse(format!("No chassis MAC address from {}", host)))?;
+
+        let mut properties = HashMap::new();
+        if let Some(ref version) = system.os_version {
+            properties.insert("junos_version".to_string(), serde_json::json!(version));
+        }
+        if let Some(ref serial) = system.serial_number {
+            properties.insert("serial_number".to_string(), serde_json::json!(serial));
+        }
+
+        Ok(VendorDevice {
+            vendor_id: host.to_string(),
+            device_id: self.get_device_id(host).await,
+            mac,
+            model: system.hardware_model.unwrap_or_else(|| "Junos".to_string()),
+            name: system.host_name.unwrap_or_else(|| host.to_string()),
+            ip_address: host.parse().ok(),
+            // Junos devices are managed directly; there is no adoption step
+            adopted: true,
+            properties,
+        })
+    }
+}
+
+/// Extract `set` commands from a vendor config payload
+fn config_lines(config: &VendorConfig) -> Result<Vec<String>, JuniperError> {
+    let source = config.payload.get("config").unwrap_or(&config.payload);
+
+    let lines: Vec<String> = match source {
+        serde_json::Value::String(text) => text.lines().map(str::to_string).collect(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(|v| {
+                v.as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| JuniperError::Parse("Config lines must be strings".to_string()))
+            })
+            .collect::<Result<_, _>>()?,
+        _ => {
+            return Err(JuniperError::Parse(format!(
+                "Unsupported config payload for type '{}'",
+                config.config_type
+            )))
+        }
+    };
+
+    Ok(lines.into_iter().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect())
+}
+
+/// Junos identifiers cannot contain spaces
+fn identifier(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+/// Generate Junos `set` commands for a device aggregate
+pub fn generate_junos_config(device: &NetworkDeviceAggregate) -> Vec<String> {
+    junos_config_lines(&DeviceConfigRequest::from_device(device))
+}
+
+/// Validate a request and reject settings `junos_config_lines` cannot express
+fn check_request(request: &DeviceConfigRequest) -> Result<(), PortError> {
+    request.validate()?;
+    if !request.lags.is_empty() {
+        return Err(PortError::NotSupported("Juniper adapter cannot configure LAGs".to_string()));
+    }
+    Ok(())
+}
+
+/// Translate a typed config request into Junos `set` commands
+pub fn junos_config_lines(request: &DeviceConfigRequest) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if let Some(ref hostname) = request.hostname {
+        lines.push(format!("set system host-name {}", identifier(hostname)));
+    }
+
+    for vlan in &request.vlans {
+        lines.push(format!("set vlans {} vlan-id {}", identifier(&vlan.name), vlan.id));
+    }
+
+    for iface in &request.interfaces {
+        let name = &iface.name;
+        match (iface.ip_address, iface.prefix_len) {
+            (Some(addr), Some(prefix)) => {
+                let family = if addr.is_ipv4() { "inet" } else { "inet6" };
+                lines.push(format!("set interfaces {} unit 0 family {} address {}/{}", name, family, addr, prefix));
+            }
+            _ => {
+                if let Some(vlan_id) = iface.vlan_id {
+                    lines.push(format!("set interfaces {} unit 0 family ethernet-switching interface-mode access", name));
+                    lines.push(format!("set interfaces {} unit 0 family ethernet-switching vlan members {}", name, vlan_id));
+                }
+            }
+        }
+        if let Some(mtu) = iface.mtu {
+            lines.push(format!("set interfaces {} mtu {}", name, mtu));
+        }
+        // Deleting a `disable` that is not there is only a warning
+        lines.push(if iface.enabled {
+            format!("delete interfaces {} disable", name)
+        } else {
+            format!("set interfaces {} disable", name)
+        });
+    }
+
+    for server in &request.ntp_servers {
+        lines.push(format!("set system ntp server {}", server));
+    }
+
+    for server in &request.dns_servers {
+        lines.push(format!("set system name-server {}", server));
+    }
+
+    lines
+}
+
+/// Map Juniper errors onto port errors
+fn port_error(error: JuniperError) -> PortError {
+    match error {
+        JuniperError::Auth(msg) => PortError::AuthenticationFailed(msg),
+        JuniperError::Session(msg) => PortError::ConnectionFailed(msg),
+        other => PortError::VendorError(other.to_string()),
+    }
+}
+
+#[async_trait]
+impl DeviceControlPort for JuniperAdapter {
+    fn vendor_name(&self) -> &str {
+        "juniper"
+    }
+
+    async fn connect(&self) -> Result<(), PortError> {
+        for host in &self.hosts {
+            self.rpcs(host, &[INVENTORY_RPCS[0].to_string()]).await.map_err(port_error)?;
+        }
+        self.connected.store(true, Ordering::SeqCst);
+        tracing::info!("Connected to {} Junos devices", self.hosts.len());
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> Result<(), PortError> {
+        // Every operation opens its own NETCONF session; nothing to tear down
+        self.connected.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError> {
+        let mut vendor_devices = Vec::new();
+        for host in &self.hosts {
+            match self.read_device(host).await {
+                Ok(device) => vendor_devices.push(device),
+                // An unreachable device is simply not reported
+                Err(JuniperError::Session(e)) => tracing::warn!("Junos device {} unreachable: {}", host, e),
+                Err(e) => return Err(port_error(e)),
+            }
+        }
+        Ok(vendor_devices)
+    }
+
+    async fn get_device(&self, vendor_id: &str) -> Result<VendorDevice, PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        self.read_device(host).await.map_err(port_error)
+    }
+
+    async fn adopt_device(&self, vendor_id: &str) -> Result<(), PortError> {
+        // No controller adoption on Junos; just verify we can manage the device
+        let host = self.host(vendor_id).map_err(port_error)?;
+        self.rpcs(host, &[INVENTORY_RPCS[0].to_string()]).await.map(|_| ()).map_err(port_error)
+    }
+
+    async fn apply_config(&self, vendor_id: &str, config: DeviceConfigRequest) -> Result<(), PortError> {
+        check_request(&config)?;
+        self.apply_raw_config(vendor_id, VendorConfig {
+            config_type: "junos-set".to_string(),
+            payload: serde_json::json!({ "config": junos_config_lines(&config) }),
+        })
+        .await
+    }
+
+    async fn apply_raw_config(&self, vendor_id: &str, config: VendorConfig) -> Result<(), PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        let lines = config_lines(&config).map_err(port_error)?;
+        self.commit(host, &lines).await.map_err(port_error)
+    }
+
+    /// Commit `config`, reporting a rejected load or commit as rolled back
+    ///
+    /// The commit is atomic, so no snapshot is needed: a rejection leaves
+    /// the running configuration as it was.
+    async fn apply_config_transactional(
+        &self,
+        vendor_id: &str,
+        config: DeviceConfigRequest,
+    ) -> Result<ConfigApplyOutcome, PortError> {
+        check_request(&config)?;
+        let host = self.host(vendor_id).map_err(port_error)?;
+
+        match self.commit(host, &junos_config_lines(&config)).await {
+            Ok(()) => Ok(ConfigApplyOutcome::Applied),
+            Err(JuniperError::Rpc(reason)) => Ok(ConfigApplyOutcome::RolledBack { reason }),
+            Err(e) => Err(port_error(e)),
+        }
+    }
+
+    async fn restart_device(&self, vendor_id: &str) -> Result<(), PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        self.rpcs(host, &["<request-reboot/>".to_string()]).await.map(|_| ()).map_err(port_error)
+    }
+
+    async fn get_device_stats(&self, vendor_id: &str) -> Result<DeviceStats, PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        let rpcs: Vec<String> = STATS_RPCS.iter().map(|r| r.to_string()).collect();
+        let replies = self.rpcs(host, &rpcs).await.map_err(port_error)?;
+
+        let engine = RouteEngine::from_reply(&replies[0]);
+        let interfaces = PhysicalInterface::from_reply(&replies[1]);
+
+        Ok(DeviceStats {
+            uptime_seconds: engine.uptime_seconds.unwrap_or(0),
+            cpu_percent: engine.cpu_percent(),
+            memory_percent: engine.memory_utilization,
+            temperature_celsius: engine.temperature_celsius,
+            port_stats: interfaces.into_iter().map(|iface| PortStats {
+                link_up: iface.is_up(),
+                speed: iface.speed_mbps().and_then(LinkSpeed::from_mbps),
+                port_id: PortId::new(iface.name),
+                rx_bytes: iface.input_bytes,
+                tx_bytes: iface.output_bytes,
+                rx_errors: iface.input_errors,
+                tx_errors: iface.output_errors,
+                rx_bps: None,
+                tx_bps: None,
+            }).collect(),
+        })
+    }
+}
+
+impl VendorExtension for JuniperAdapter {
+    fn vendor_name(&self) -> &str {
+        "juniper"
+    }
+
+    fn extend(&self, domain_obj: &DomainObject) -> Result<VendorRepresentation, FunctorError> {
+        match domain_obj {
+            DomainObject::Device(device) => {
+                let payload = serde_json::json!({
+                    "hostname": device.name(),
+                    "mac": device.mac().to_string(),
+                    "state": device.state().name(),
+                    "config": generate_junos_config(device),
+                });
+
+                Ok(VendorRepresentation {
+                    vendor: "juniper".to_string(),
+                    vendor_id: device.vendor_id().unwrap_or("pending").to_string(),
+                    device_id: device.id(),
+                    payload,
+                })
+            }
+            _ => Err(FunctorError::MappingFailed(
+                "Only Device objects can be extended to Juniper".to_string()
+            )),
+        }
+    }
+
+    fn to_domain_event(&self, _vendor_event: &serde_json::Value) -> Result<NetworkEvent, FunctorError> {
+        // The adapter does not subscribe to NETCONF notifications
+        Err(FunctorError::MappingFailed(
+            "Junos notifications are not subscribed to".to_string()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ==========================================================================
+    // XML Tests
+    // ==========================================================================
+
+    const ROUTE_ENGINE: &str = r#"<rpc-reply xmlns:junos="http://xml.juniper.net/junos/21.4R3/junos">
+<route-engine-information xmlns="http://xml.juniper.net/junos/21.4R3/junos-chassis">
+<route-engine>
+<slot>0</slot>
+<temperature junos:celsius="41">41 degrees C / 105 degrees F</temperature>
+<memory-buffer-utilization>37</memory-buffer-utilization>
+<cpu-idle>93</cpu-idle>
+<up-time junos:seconds="86461">1 day, 1 minute, 1 second</up-time>
+</route-engine>
+</route-engine-information>
+</rpc-reply>"#;
+
+    #[test]
+    fn test_elements_and_text() {
+        let xml = "<a><name>\nge-0/0/0\n</name><name-server>x</name-server><flag/><name>b &amp; c</name></a>";
+
+        assert_eq!(elements(xml, "name").len(), 2);
+        assert_eq!(text(xml, "name").as_deref(), Some("ge-0/0/0"));
+        assert_eq!(elements(xml, "flag"), [""]);
+        assert_eq!(text(elements(xml, "a")[0], "missing"), None);
+        assert_eq!(escape("a < b & c"), "a &lt; b &amp; c");
+    }
+
+    #[test]
+    fn test_route_engine() {
+        let engine = RouteEngine::from_reply(ROUTE_ENGINE);
+
+        assert_eq!(engine.cpu_percent(), Some(7.0));
+        assert_eq!(engine.memory_utilization, Some(37.0));
+        assert_eq!(engine.uptime_seconds, Some(86461));
+        assert_eq!(engine.temperature_celsius, Some(41.0));
+    }
+
+    #[test]
+    fn test_reply_error_ignores_warnings() {
+        let warning = "<rpc-reply><rpc-error><error-severity>warning</error-severity>\
+            <error-message>statement not found</error-message></rpc-error><ok/></rpc-reply>";
+        let error = "<rpc-reply><rpc-error><error-severity>error</error-severity>\
+            <error-message>\nsyntax error\n</error-message></rpc-error></rpc-reply>";
+
+        assert_eq!(reply_error(warning), None);
+        assert_eq!(reply_error(error).as_deref(), Some("syntax error"));
+    }
+
+    #[test]
+    fn test_read_message_splits_on_marker() {
+        let mut stream = std::io::Cursor::new(format!("<hello/>{0}<rpc-reply><ok/></rpc-reply>{0}", END_OF_MESSAGE));
+
+        assert_eq!(read_message(&mut stream).unwrap(), "<hello/>");
+        assert_eq!(read_message(&mut stream).unwrap(), "<rpc-reply><ok/></rpc-reply>");
+        assert!(matches!(read_message(&mut stream), Err(JuniperError::Session(_))));
+    }
+
+    // ==========================================================================
+    // Config Rendering Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_junos_config_lines() {
+        let request = DeviceConfigRequest::default()
+            .with_hostname("edge fw1")
+            .with_vlan(VlanConfig::new(10, "users").unwrap())
+            .with_interface(InterfaceConfig {
+                name: "ge-0/0/0".to_string(),
+                ip_address: Some("192.0.2.1".parse().unwrap()),
+                prefix_len: Some(30),
+                vlan_id: None,
+                enabled: true,
+                mtu: Some(9000),
+                speed: None,
+            })
+            .with_interface(InterfaceConfig {
+                name: "ge-0/0/1".to_string(),
+                ip_address: None,
+                prefix_len: None,
+                vlan_id: Some(10),
+                enabled: false,
+                mtu: None,
+                speed: None,
+            })
+            .with_ntp_server("0.pool.ntp.org")
+            .with_dns_server("9.9.9.9".parse().unwrap());
+
+        assert_eq!(junos_config_lines(&request), [
+            "set system host-name edge-fw1",
+            "set vlans users vlan-id 10",
+            "set interfaces ge-0/0/0 unit 0 family inet address 192.0.2.1/30",
+            "set interfaces ge-0/0/0 mtu 9000",
+            "delete interfaces ge-0/0/0 disable",
+            "set interfaces ge-0/0/1 unit 0 family ethernet-switching interface-mode access",
+            "set interfaces ge-0/0/1 unit 0 family ethernet-switching vlan members 10",
+            "set interfaces ge-0/0/1 disable",
+            "set system ntp server 0.pool.ntp.org",
+            "set system name-server 9.9.9.9",
+        ]);
+    }
+
+    #[test]
+    fn test_config_lines_from_payload() {
+        let config = VendorConfig {
+            config_type: "junos-set".to_string(),
+            payload: serde_json::json!("set system host-name r1\n\n  set vlans v10 vlan-id 10\n"),
+        };
+        assert_eq!(config_lines(&config).unwrap(), ["set system host-name r1", "set vlans v10 vlan-id 10"]);
+
+        let config = VendorConfig {
+            config_type: "junos-set".to_string(),
+            payload: serde_json::json!({ "config": [1, 2] }),
+        };
+        assert!(config_lines(&config).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+//! Junos XML API types
+//!
+//! Junos answers every RPC with an `<rpc-reply>` holding the same XML its CLI
+//! prints with `| display xml`: hyphenated element names, values padded with
+//! newlines and a few units carried in `junos:` attributes. Replies are read
+//! with a handful of element lookups rather than a full XML parser; only the
+//! fields the adapter uses are modelled.
+
+/// Inner XML of every `<tag>` element in `xml`, in document order
+///
+/// Self-closing elements yield an empty string. Elements of the same name
+/// must not nest, which holds for everything Junos returns that is read here.
+pub fn elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut found = Vec::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        // `<name` must not match `<name-server>`
+        if !after.starts_with(['>', ' ', '/', '\n', '\r', '\t']) {
+            rest = after;
+            continue;
+        }
+        let Some(end_of_open) = after.find('>') else { break };
+        if after[..end_of_open].ends_with('/') {
+            found.push("");
+            rest = &after[end_of_open + 1..];
+            continue;
+        }
+        let body = &after[end_of_open + 1..];
+        let Some(end) = body.find(&close) else { break };
+        found.push(&body[..end]);
+        rest = &body[end + close.len()..];
+    }
+
+    found
+}
+
+/// Text of the first `<tag>` element in `xml`, trimmed and unescaped
+pub fn text(xml: &str, tag: &str) -> Option<String> {
+    elements(xml, tag)
+        .first()
+        .map(|inner| unescape(inner.trim()))
+        .filter(|value| !value.is_empty())
+}
+
+/// Value of `attribute` on the first `<tag>` element in `xml`
+pub fn attribute(xml: &str, tag: &str, attribute: &str) -> Option<String> {
+    let open = format!("<{} ", tag);
+    let start = xml.find(&open)? + open.len();
+    let attributes = &xml[start..start + xml[start..].find('>')?];
+    let key = format!("{}=\"", attribute);
+    let value = &attributes[attributes.find(&key)? + key.len()..];
+    Some(unescape(&value[..value.find('"')?]))
+}
+
+/// Escape text for use inside an XML element
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Undo the entity escaping Junos applies to text
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Message of the first error-severity `<rpc-error>` in a reply
+///
+/// Warnings, such as deleting a statement that is not configured, are not
+/// errors.
+pub fn reply_error(reply: &str) -> Option<String> {
+    elements(reply, "rpc-error")
+        .into_iter()
+        .find(|error| text(error, "error-severity").as_deref() != Some("warning"))
+        .map(|error| text(error, "error-message").unwrap_or_else(|| "unknown RPC error".to_string()))
+}
+
+/// `<get-system-information>`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SystemInformation {
+    /// Hardware model, e.g. `ex2300-24p`
+    pub hardware_model: Option<String>,
+    /// Operating system, `junos` or `junos-evo`
+    pub os_name: Option<String>,
+    /// Junos version, e.g. `21.4R3-S5.4`
+    pub os_version: Option<String>,
+    /// Chassis serial number
+    pub serial_number: Option<String>,
+    /// Configured host name
+    pub host_name: Option<String>,
+}
+
+impl SystemInformation {
+    /// Read the system information from an RPC reply
+    pub fn from_reply(reply: &str) -> Self {
+        Self {
+            hardware_model: text(reply, "hardware-model"),
+            os_name: text(reply, "os-name"),
+            os_version: text(reply, "os-version"),
+            serial_number: text(reply, "serial-number"),
+            host_name: text(reply, "host-name"),
+        }
+    }
+}
+
+/// First routing engine from `<get-route-engine-information>`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RouteEngine {
+    /// Idle CPU, in percent
+    pub cpu_idle: Option<f64>,
+    /// Memory in use, in percent
+    pub memory_utilization: Option<f64>,
+    /// Seconds since the routing engine booted
+    pub uptime_seconds: Option<u64>,
+    /// Routing engine temperature
+    pub temperature_celsius: Option<f64>,
+}
+
+impl RouteEngine {
+    /// Read the first routing engine from an RPC reply
+    pub fn from_reply(reply: &str) -> Self {
+        let engine = elements(reply, "route-engine").first().copied().unwrap_or(reply);
+        Self {
+            cpu_idle: text(engine, "cpu-idle").and_then(|v| v.parse().ok()),
+            memory_utilization: text(engine, "memory-buffer-utilization").and_then(|v| v.parse().ok()),
+            uptime_seconds: attribute(engine, "up-time", "junos:seconds").and_then(|v| v.parse().ok()),
+            temperature_celsius: attribute(engine, "temperature", "junos:celsius").and_then(|v| v.parse().ok()),
+        }
+    }
+
+    /// CPU in use, in percent
+    pub fn cpu_percent(&self) -> Option<f64> {
+        self.cpu_idle.map(|idle| 100.0 - idle)
+    }
+}
+
+/// `<physical-interface>` from `<get-interface-information><extensive/>`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PhysicalInterface {
+    /// Interface name, e.g. `ge-0/0/1`
+    pub name: String,
+    /// Operational status, `up` or `down`
+    pub oper_status: Option<String>,
+    /// Configured speed as Junos prints it, e.g. `1000mbps` or `10Gbps`
+    pub speed: Option<String>,
+    /// Hardware address
+    pub mac: Option<String>,
+    /// Bytes received
+    pub input_bytes: u64,
+    /// Bytes sent
+    pub output_bytes: u64,
+    /// Receive errors
+    pub input_errors: u64,
+    /// Transmit errors
+    pub output_errors: u64,
+}
+
+impl PhysicalInterface {
+    /// Read every physical interface from an RPC reply
+    ///
+    /// Counters come from the physical interface itself, which Junos lists
+    /// before its logical units.
+    pub fn from_reply(reply: &str) -> Vec<Self> {
+        elements(reply, "physical-interface")
+            .into_iter()
+            .filter_map(|iface| {
+                let counter = |tag| text(iface, tag).and_then(|v| v.parse().ok()).unwrap_or(0);
+                Some(Self {
+                    name: text(iface, "name")?,
+                    oper_status: text(iface, "oper-status"),
+                    speed: text(iface, "speed"),
+                    mac: text(iface, "current-physical-address"),
+                    input_bytes: counter("input-bytes"),
+                    output_bytes: counter("output-bytes"),
+                    input_errors: counter("input-errors"),
+                    output_errors: counter("output-errors"),
+                })
+            })
+            .collect()
+    }
+
+    /// Whether the link is up
+    pub fn is_up(&self) -> bool {
+        self.oper_status.as_deref() == Some("up")
+    }
+
+    /// Speed in Mbps, if Junos reports a fixed one
+    pub fn speed_mbps(&self) -> Option<u32> {
+        let speed = self.speed.as_deref()?.to_ascii_lowercase();
+        if let Some(gbps) = speed.strip_suffix("gbps") {
+            gbps.parse::<u32>().ok().map(|g| g * 1000)
+        } else {
+            speed.strip_suffix("mbps")?.parse().ok()
+        }
+    }
+}
+
+/// Juniper adapter error
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum JuniperError {
+    /// SSH or NETCONF session error
+    #[error("NETCONF session error: {0}")]
+    Session(String),
+    /// Credentials were rejected
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+    /// Junos answered an RPC with an error
+    #[error("RPC error: {0}")]
+    Rpc(String),
+    /// Reply or config payload could not be parsed
+    #[error("Parse error: {0}")]
+    Parse(String),
+    /// No such device
+    #[error("Not found: {0}")]
+    NotFound(String),
+}
@@ -0,0 +1,125 @@
+//! RouterOS REST API HTTP client
+//!
+//! Talks to the REST API built into RouterOS 7 (`/rest/...`) using HTTP
+//! basic authentication. Routers are addressed by base URL, so one client
+//! serves every router sharing the same credentials.
+
+use super::types::*;
+use reqwest::{Client, Method};
+use std::time::Duration;
+
+/// RouterOS REST client
+pub struct MikroTikClient {
+    /// HTTP client
+    http: Client,
+    /// API user
+    username: String,
+    /// API password
+    password: String,
+}
+
+impl MikroTikClient {
+    /// Create a new RouterOS client
+    pub fn new(username: &str, password: &str) -> Result<Self, MikroTikError> {
+        let http = Client::builder()
+            // RouterOS ships with a self-signed certificate for www-ssl
+            .danger_accept_invalid_certs(true)
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| MikroTikError::Http(e.to_string()))?;
+
+        Ok(Self {
+            http,
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+    }
+
+    // =========================================================================
+    // System
+    // =========================================================================
+
+    /// Get the router's identity
+    pub async fn get_identity(&self, host: &str) -> Result<RouterOsIdentity, MikroTikError> {
+        self.request(Method::GET, host, "system/identity", None).await
+    }
+
+    /// Get CPU, memory, uptime and board information
+    pub async fn get_resource(&self, host: &str) -> Result<RouterOsResource, MikroTikError> {
+        self.request(Method::GET, host, "system/resource", None).await
+    }
+
+    /// Reboot the router
+    pub async fn reboot(&self, host: &str) -> Result<(), MikroTikError> {
+        self.request::<serde_json::Value>(Method::POST, host, "system/reboot", Some(&serde_json::json!({})))
+            .await
+            .map(|_| ())
+    }
+
+    // =========================================================================
+    // Interfaces
+    // =========================================================================
+
+    /// List all interfaces with their counters
+    pub async fn list_interfaces(&self, host: &str) -> Result<Vec<RouterOsInterface>, MikroTikError> {
+        self.request(Method::GET, host, "interface", None).await
+    }
+
+    /// List ethernet interfaces with their link rates
+    pub async fn list_ethernet(&self, host: &str) -> Result<Vec<RouterOsEthernet>, MikroTikError> {
+        self.request(Method::GET, host, "interface/ethernet", None).await
+    }
+
+    // =========================================================================
+    // HTTP Helpers
+    // =========================================================================
+
+    /// Send a request to `{host}/rest/{path}`
+    ///
+    /// An empty response body (as returned by `reboot` and some `set`
+    /// commands) deserializes as JSON `null`.
+    pub async fn request<T: serde::de::DeserializeOwned>(
+        &self,
+        method: Method,
+        host: &str,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<T, MikroTikError> {
+        let url = format!("{}/rest/{}", host.trim_end_matches('/'), path.trim_start_matches('/'));
+        tracing::debug!("RouterOS {} {}", method, url);
+
+        let mut request = self.http
+            .request(method, &url)
+            .basic_auth(&self.username, Some(&self.password))
+            .header("Accept", "application/json");
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| MikroTikError::Http(e.to_string()))?;
+
+        let status = response.status();
+        let text = response.text().await.map_err(|e| MikroTikError::Http(e.to_string()))?;
+
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(MikroTikError::Auth(format!("Rejected by {}", host)));
+        }
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(MikroTikError::NotFound(url));
+        }
+        if !status.is_success() {
+            // RouterOS errors look like {"error":400,"message":"Bad Request","detail":"..."}
+            let detail = serde_json::from_str::<serde_json::Value>(&text)
+                .ok()
+                .and_then(|v| v.get("detail").and_then(|d| d.as_str()).map(str::to_string))
+                .unwrap_or(text);
+            return Err(MikroTikError::Api(format!("{} {}: {}", status, path, detail)));
+        }
+
+        let text = if text.trim().is_empty() { "null" } else { text.as_str() };
+        serde_json::from_str(text).map_err(|e| MikroTikError::Parse(format!("{}: {}", path, e)))
+    }
+}
@@ -0,0 +1,549 @@
+//! # MikroTik RouterOS Adapter
+//!
+//! Implements network management ports for MikroTik routers and switches
+//! through the RouterOS 7 REST API.
+//!
+//! ## Supported Operations
+//!
+//! - Inventory of the configured routers (identity, board, interfaces)
+//! - Configuration through REST calls described in the config payload
+//! - Reboot
+//! - CPU, memory, uptime and interface statistics
+//!
+//! ## Device Identity
+//!
+//! RouterOS has no controller, so every router is addressed directly by the
+//! base URL of its REST API (e.g. `https://192.0.2.1`). That base URL is the
+//! vendor ID; the router's MAC is taken from its first ethernet interface.
+//!
+//! ## Config Payload
+//!
+//! `apply_config` translates a `DeviceConfigRequest` into identity, VLAN,
+//! address, NTP client and DNS calls. `apply_raw_config` expects a list of
+//! REST calls, either bare or under a
+//! `config` key, each with a `path` below `/rest/`, an optional `body` and an
+//! optional `method` (`PUT` to add, the default; `PATCH` to update a record;
+//! `POST` for console commands such as `system/identity/set`):
+//!
+//! ```json
+//! { "config": [
+//!     { "method": "POST", "path": "system/identity/set", "body": { "name": "edge-rtr" } },
+//!     { "path": "ip/address", "body": { "address": "10.0.0.1/24", "interface": "ether2" } }
+//! ] }
+//! ```
+
+use async_trait::async_trait;
+use reqwest::Method;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::domain::aggregates::NetworkDeviceAggregate;
+use crate::domain::ports::*;
+use crate::domain::functor::*;
+use crate::domain::events::*;
+use crate::domain::value_objects::*;
+
+mod client;
+mod types;
+
+pub use client::MikroTikClient;
+pub use types::*;
+
+/// MikroTik RouterOS adapter
+///
+/// Implements both:
+/// - `DeviceControlPort` for hexagonal architecture
+/// - `VendorExtension` for Kan extension mapping
+pub struct MikroTikAdapter {
+    /// REST client shared by all routers
+    client: Arc<MikroTikClient>,
+    /// Base URLs of the managed routers
+    hosts: Vec<String>,
+    /// Whether every router has been reached
+    connected: AtomicBool,
+    /// Mapping from domain DeviceId to vendor ID (base URL)
+    device_mapping: Arc<RwLock<HashMap<DeviceId, String>>>,
+    /// Mapping from vendor ID to domain DeviceId
+    reverse_mapping: Arc<RwLock<HashMap<String, DeviceId>>>,
+}
+
+impl MikroTikAdapter {
+    /// Create an adapter for routers sharing one set of API credentials
+    ///
+    /// # Arguments
+    /// * `hosts` - REST base URLs, e.g. `["https://192.0.2.1"]`
+    /// * `username` / `password` - RouterOS user with `api` and `rest-api` policy
+    pub fn new(hosts: &[&str], username: &str, password: &str) -> Result<Self, MikroTikError> {
+        Ok(Self {
+            client: Arc::new(MikroTikClient::new(username, password)?),
+            hosts: hosts.iter().map(|h| h.trim_end_matches('/').to_string()).collect(),
+            connected: AtomicBool::new(false),
+            device_mapping: Arc::new(RwLock::new(HashMap::new())),
+            reverse_mapping: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Map a domain device to its vendor ID (REST base URL)
+    pub async fn map_device(&self, device_id: DeviceId, vendor_id: String) {
+        let mut mapping = self.device_mapping.write().await;
+        let mut reverse = self.reverse_mapping.write().await;
+        mapping.insert(device_id, vendor_id.clone());
+        reverse.insert(vendor_id, device_id);
+    }
+
+    /// Get vendor ID for a domain device
+    pub async fn get_vendor_id(&self, device_id: DeviceId) -> Option<String> {
+        let mapping = self.device_mapping.read().await;
+        mapping.get(&device_id).cloned()
+    }
+
+    /// Get domain device ID for a vendor ID
+    pub async fn get_device_id(&self, vendor_id: &str) -> Option<DeviceId> {
+        let reverse = self.reverse_mapping.read().await;
+        reverse.get(vendor_id).copied()
+    }
+
+    /// Resolve a vendor ID to one of the configured hosts
+    fn host(&self, vendor_id: &str) -> Result<&str, MikroTikError> {
+        let vendor_id = vendor_id.trim_end_matches('/');
+        self.hosts
+            .iter()
+            .find(|h| *h == vendor_id)
+            .map(String::as_str)
+            .ok_or_else(|| MikroTikError::NotFound(format!("Router {} is not configured", vendor_id)))
+    }
+
+    /// Read one router's identity, board and interfaces
+    async fn read_device(&self, host: &str) -> Result<VendorDevice, MikroTikError> {
+        let identity = self.client.get_identity(host).await?;
+        let resource = self.client.get_resource(host).await?;
+        let interfaces = self.client.list_interfaces(host).await?;
+
+        let mac = router_mac(&interfaces)
+            .ok_or_else(|| MikroTikError::Parse(format!("No interface with a MAC address on {}", host)))?;
+
+        let mut properties = HashMap::new();
+        if let Some(ref version) = resource.version {
+            properties.insert("routeros_version".to_string(), serde_json::json!(version));
+        }
+        properties.insert("interface_count".to_string(), serde_json::json!(interfaces.len()));
+
+        Ok(VendorDevice {
+            vendor_id: host.to_string(),
+            device_id: self.get_device_id(host).await,
+            mac,
+            model: resource.board_name.unwrap_or_else(|| "RouterOS".to_string()),
+            name: identity.name,
+            ip_address: host_ip(host),
+            // RouterOS devices are managed directly; there is no adoption step
+            adopted: true,
+            properties,
+        })
+    }
+}
+
+/// MAC of the first ethernet interface, else of any interface that has one
+fn router_mac(interfaces: &[RouterOsInterface]) -> Option<MacAddress> {
+    let mac_of = |iface: &RouterOsInterface| iface.mac_address.as_deref().and_then(|m| MacAddress::parse(m).ok());
+
+    interfaces
+        .iter()
+        .filter(|iface| iface.interface_type.as_deref() == Some("ether"))
+        .find_map(mac_of)
+        .or_else(|| interfaces.iter().find_map(mac_of))
+}
+
+/// IP address in a base URL such as `https://192.0.2.1:8443`
+fn host_ip(host: &str) -> Option<std::net::IpAddr> {
+    let authority = host.split("://").last()?.split('/').next()?;
+    authority.parse::<std::net::SocketAddr>()
+        .map(|addr| addr.ip())
+        .or_else(|_| authority.parse())
+        .ok()
+}
+
+/// One REST call from a config payload
+#[derive(Debug, Clone, PartialEq)]
+struct RestCall {
+    method: Method,
+    path: String,
+    body: serde_json::Value,
+}
+
+/// Extract REST calls from a vendor config payload
+fn config_calls(config: &VendorConfig) -> Result<Vec<RestCall>, MikroTikError> {
+    let source = config.payload.get("config").unwrap_or(&config.payload);
+    let items = source.as_array().ok_or_else(|| {
+        MikroTikError::Parse(format!("Unsupported config payload for type '{}'", config.config_type))
+    })?;
+
+    items
+        .iter()
+        .map(|item| {
+            let path = item.get("path")
+                .and_then(|p| p.as_str())
+                .ok_or_else(|| MikroTikError::Parse("Config call is missing 'path'".to_string()))?;
+            let method = match item.get("method").and_then(|m| m.as_str()).unwrap_or("PUT") {
+                m if m.eq_ignore_ascii_case("PUT") => Method::PUT,
+                m if m.eq_ignore_ascii_case("PATCH") => Method::PATCH,
+                m if m.eq_ignore_ascii_case("POST") => Method::POST,
+                other => return Err(MikroTikError::Parse(format!("Unsupported method '{}'", other))),
+            };
+            Ok(RestCall {
+                method,
+                path: path.trim_start_matches('/').to_string(),
+                body: item.get("body").cloned().unwrap_or_else(|| serde_json::json!({})),
+            })
+        })
+        .collect()
+}
+
+/// Generate RouterOS REST calls for a device aggregate
+pub fn generate_routeros_config(device: &NetworkDeviceAggregate) -> Vec<serde_json::Value> {
+    routeros_config_calls(&DeviceConfigRequest::from_device(device))
+}
+
+/// Translate a typed config request into RouterOS REST calls
+pub fn routeros_config_calls(request: &DeviceConfigRequest) -> Vec<serde_json::Value> {
+    let mut calls = Vec::new();
+
+    if let Some(ref hostname) = request.hostname {
+        calls.push(serde_json::json!({
+            "method": "POST",
+            "path": "system/identity/set",
+            "body": { "name": hostname },
+        }));
+    }
+
+    for vlan in &request.vlans {
+        calls.push(serde_json::json!({
+            "path": "interface/vlan",
+            "body": { "name": vlan.name, "vlan-id": vlan.id.to_string(), "interface": "bridge" },
+        }));
+    }
+
+    for iface in &request.interfaces {
+        if let (Some(addr), Some(prefix)) = (iface.ip_address, iface.prefix_len) {
+            calls.push(serde_json::json!({
+                "path": "ip/address",
+                "body": { "address": format!("{}/{}", addr, prefix), "interface": iface.name },
+            }));
+        }
+    }
+
+    if !request.ntp_servers.is_empty() {
+        calls.push(serde_json::json!({
+            "method": "POST",
+            "path": "system/ntp/client/set",
+            "body": { "enabled": "yes", "servers": request.ntp_servers.join(",") },
+        }));
+    }
+
+    if !request.dns_servers.is_empty() {
+        let servers: Vec<String> = request.dns_servers.iter().map(|s| s.to_string()).collect();
+        calls.push(serde_json::json!({
+            "method": "POST",
+            "path": "ip/dns/set",
+            "body": { "servers": servers.join(",") },
+        }));
+    }
+
+    calls
+}
+
+/// Map MikroTik errors onto port errors
+fn port_error(error: MikroTikError) -> PortError {
+    match error {
+        MikroTikError::Auth(msg) => PortError::AuthenticationFailed(msg),
+        MikroTikError::Http(msg) => PortError::ConnectionFailed(msg),
+        other => PortError::VendorError(other.to_string()),
+    }
+}
+
+#[async_trait]
+impl DeviceControlPort for MikroTikAdapter {
+    fn vendor_name(&self) -> &str {
+        "mikrotik"
+    }
+
+    async fn connect(&self) -> Result<(), PortError> {
+        for host in &self.hosts {
+            self.client.get_identity(host).await.map_err(port_error)?;
+        }
+        self.connected.store(true, Ordering::SeqCst);
+        tracing::info!("Connected to {} RouterOS devices", self.hosts.len());
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> Result<(), PortError> {
+        // REST is stateless; nothing to tear down
+        self.connected.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError> {
+        let mut vendor_devices = Vec::new();
+        for host in &self.hosts {
+            match self.read_device(host).await {
+                Ok(device) => vendor_devices.push(device),
+                // An unreachable router is simply not reported
+                Err(MikroTikError::Http(e)) => tracing::warn!("RouterOS device {} unreachable: {}", host, e),
+                Err(e) => return Err(port_error(e)),
+            }
+        }
+        Ok(vendor_devices)
+    }
+
+    async fn get_device(&self, vendor_id: &str) -> Result<VendorDevice, PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        self.read_device(host).await.map_err(port_error)
+    }
+
+    async fn adopt_device(&self, vendor_id: &str) -> Result<(), PortError> {
+        // No controller adoption on RouterOS; just verify we can manage the device
+        let host = self.host(vendor_id).map_err(port_error)?;
+        self.client.get_identity(host).await.map(|_| ()).map_err(port_error)
+    }
+
+    async fn apply_config(&self, vendor_id: &str, config: DeviceConfigRequest) -> Result<(), PortError> {
+        if !config.lags.is_empty() {
+            return Err(PortError::NotSupported("MikroTik adapter cannot configure LAGs".to_string()));
+        }
+        self.apply_raw_config(vendor_id, VendorConfig {
+            config_type: "routeros".to_string(),
+            payload: serde_json::json!({ "config": routeros_config_calls(&config) }),
+        })
+        .await
+    }
+
+    async fn apply_raw_config(&self, vendor_id: &str, config: VendorConfig) -> Result<(), PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        let calls = config_calls(&config).map_err(port_error)?;
+
+        for call in &calls {
+            self.client
+                .request::<serde_json::Value>(call.method.clone(), host, &call.path, Some(&call.body))
+                .await
+                .map_err(port_error)?;
+        }
+
+        tracing::info!("Applied {} RouterOS config calls to {}", calls.len(), host);
+        Ok(())
+    }
+
+    async fn restart_device(&self, vendor_id: &str) -> Result<(), PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        self.client.reboot(host).await.map_err(port_error)
+    }
+
+    async fn get_device_stats(&self, vendor_id: &str) -> Result<DeviceStats, PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        let resource = self.client.get_resource(host).await.map_err(port_error)?;
+        let interfaces = self.client.list_interfaces(host).await.map_err(port_error)?;
+
+        // Link rates are only reported for ethernet ports
+        let speeds: HashMap<String, u32> = match self.client.list_ethernet(host).await {
+            Ok(ports) => ports
+                .into_iter()
+                .filter_map(|port| Some((port.name, parse_rate_mbps(port.speed.as_deref()?)?)))
+                .collect(),
+            Err(e) => {
+                tracing::debug!("No ethernet link rates from {}: {}", host, e);
+                HashMap::new()
+            }
+        };
+
+        Ok(DeviceStats {
+            uptime_seconds: resource.uptime.as_deref().and_then(parse_uptime_seconds).unwrap_or(0),
+            cpu_percent: parse_u64(resource.cpu_load.as_deref()).map(|load| load as f64),
+            memory_percent: resource.memory_percent(),
+            temperature_celsius: None,
+            port_stats: interfaces.iter().map(|iface| PortStats {
+                port_id: PortId::new(iface.name.clone()),
+                link_up: iface.is_running(),
+                speed: speeds.get(&iface.name).copied().and_then(LinkSpeed::from_mbps),
+                rx_bytes: parse_u64(iface.rx_byte.as_deref()).unwrap_or(0),
+                tx_bytes: parse_u64(iface.tx_byte.as_deref()).unwrap_or(0),
+                rx_errors: parse_u64(iface.rx_error.as_deref()).unwrap_or(0),
+                tx_errors: parse_u64(iface.tx_error.as_deref()).unwrap_or(0),
+                rx_bps: None,
+                tx_bps: None,
+            }).collect(),
+        })
+    }
+}
+
+impl VendorExtension for MikroTikAdapter {
+    fn vendor_name(&self) -> &str {
+        "mikrotik"
+    }
+
+    fn extend(&self, domain_obj: &DomainObject) -> Result<VendorRepresentation, FunctorError> {
+        match domain_obj {
+            DomainObject::Device(device) => {
+                let payload = serde_json::json!({
+                    "identity": device.name(),
+                    "mac": device.mac().to_string(),
+                    "state": device.state().name(),
+                    "config": generate_routeros_config(device),
+                });
+
+                Ok(VendorRepresentation {
+                    vendor: "mikrotik".to_string(),
+                    vendor_id: device.vendor_id().unwrap_or("pending").to_string(),
+                    device_id: device.id(),
+                    payload,
+                })
+            }
+            _ => Err(FunctorError::MappingFailed(
+                "Only Device objects can be extended to MikroTik".to_string()
+            )),
+        }
+    }
+
+    fn to_domain_event(&self, _vendor_event: &serde_json::Value) -> Result<NetworkEvent, FunctorError> {
+        // The REST API is request/response only; RouterOS pushes nothing to translate
+        Err(FunctorError::MappingFailed(
+            "RouterOS does not emit events over the REST API".to_string()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ==========================================================================
+    // Parser Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_parse_uptime() {
+        assert_eq!(parse_uptime_seconds("2w1d5h31m12s"), Some(14 * 86400 + 86400 + 5 * 3600 + 31 * 60 + 12));
+        assert_eq!(parse_uptime_seconds("45s"), Some(45));
+        assert_eq!(parse_uptime_seconds("3x"), None);
+        assert_eq!(parse_uptime_seconds("12"), None);
+    }
+
+    #[test]
+    fn test_parse_rate_mbps() {
+        assert_eq!(parse_rate_mbps("1G-baseT-full"), Some(1000));
+        assert_eq!(parse_rate_mbps("2.5G-baseT"), Some(2500));
+        assert_eq!(parse_rate_mbps("10G-baseSR-LR"), Some(10000));
+        assert_eq!(parse_rate_mbps("100M-baseT-half"), Some(100));
+        assert_eq!(parse_rate_mbps("1Gbps"), Some(1000));
+        assert_eq!(parse_rate_mbps("auto"), None);
+    }
+
+    #[test]
+    fn test_memory_percent() {
+        let resource = RouterOsResource {
+            free_memory: Some("268435456".to_string()),
+            total_memory: Some("1073741824".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(resource.memory_percent(), Some(75.0));
+    }
+
+    #[test]
+    fn test_router_mac_prefers_ethernet() {
+        let interfaces = vec![
+            RouterOsInterface {
+                name: "bridge".to_string(),
+                interface_type: Some("bridge".to_string()),
+                mac_address: Some("48:8F:5A:00:00:FF".to_string()),
+                ..Default::default()
+            },
+            RouterOsInterface {
+                name: "ether1".to_string(),
+                interface_type: Some("ether".to_string()),
+                mac_address: Some("48:8F:5A:00:00:01".to_string()),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(router_mac(&interfaces), Some(MacAddress::parse("48:8f:5a:00:00:01").unwrap()));
+    }
+
+    #[test]
+    fn test_host_ip() {
+        assert_eq!(host_ip("https://192.0.2.1"), Some("192.0.2.1".parse().unwrap()));
+        assert_eq!(host_ip("http://127.0.0.1:8080"), Some("127.0.0.1".parse().unwrap()));
+        assert_eq!(host_ip("https://edge-rtr.example.net"), None);
+    }
+
+    // ==========================================================================
+    // Config Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_config_calls() {
+        let config = VendorConfig {
+            config_type: "routeros".to_string(),
+            payload: serde_json::json!({ "config": [
+                { "method": "post", "path": "/system/identity/set", "body": { "name": "edge" } },
+                { "path": "ip/address", "body": { "address": "10.0.0.1/24", "interface": "ether2" } },
+            ] }),
+        };
+
+        let calls = config_calls(&config).unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].method, Method::POST);
+        assert_eq!(calls[0].path, "system/identity/set");
+        assert_eq!(calls[1].method, Method::PUT);
+        assert_eq!(calls[1].body["interface"], "ether2");
+    }
+
+    #[test]
+    fn test_config_calls_rejects_unknown_method() {
+        let config = VendorConfig {
+            config_type: "routeros".to_string(),
+            payload: serde_json::json!([{ "method": "DELETE", "path": "ip/address/*1" }]),
+        };
+        assert!(matches!(config_calls(&config), Err(MikroTikError::Parse(_))));
+    }
+
+    #[test]
+    fn test_generated_config_is_accepted_by_apply() {
+        let mut device = NetworkDeviceAggregate::new_discovered(
+            MacAddress::parse("48:8f:5a:00:00:01").unwrap(),
+            DeviceType::Gateway,
+            None,
+        );
+        device.rename("edge-rtr".to_string()).unwrap();
+
+        let config = VendorConfig {
+            config_type: "routeros".to_string(),
+            payload: serde_json::json!({ "config": generate_routeros_config(&device) }),
+        };
+
+        let calls = config_calls(&config).unwrap();
+        assert_eq!(calls[0].path, "system/identity/set");
+        assert_eq!(calls[0].body["name"], "edge-rtr");
+    }
+
+    #[test]
+    fn test_typed_request_to_routeros_calls() {
+        let request = DeviceConfigRequest::default()
+            .with_hostname("edge-rtr")
+            .with_ntp_server("0.pool.ntp.org")
+            .with_ntp_server("1.pool.ntp.org")
+            .with_dns_server("1.1.1.1".parse().unwrap());
+
+        let config = VendorConfig {
+            config_type: "routeros".to_string(),
+            payload: serde_json::json!({ "config": routeros_config_calls(&request) }),
+        };
+
+        let calls = config_calls(&config).unwrap();
+        let paths: Vec<_> = calls.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, ["system/identity/set", "system/ntp/client/set", "ip/dns/set"]);
+        assert_eq!(calls[1].body["servers"], "0.pool.ntp.org,1.pool.ntp.org");
+        assert_eq!(calls[2].body["servers"], "1.1.1.1");
+    }
+}
@@ -0,0 +1,151 @@
+//! RouterOS REST API types
+//!
+//! The RouterOS REST API returns every value as a JSON string (`"true"`,
+//! `"1234"`, `"1w2d3h"`), so records are deserialized as strings and
+//! converted through the helpers here.
+
+use serde::{Deserialize, Serialize};
+
+/// `/rest/system/identity`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouterOsIdentity {
+    /// Router name
+    pub name: String,
+}
+
+/// `/rest/system/resource`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RouterOsResource {
+    /// Uptime, e.g. `2w1d5h31m12s`
+    pub uptime: Option<String>,
+    /// CPU load in percent
+    pub cpu_load: Option<String>,
+    /// Free memory in bytes
+    pub free_memory: Option<String>,
+    /// Total memory in bytes
+    pub total_memory: Option<String>,
+    /// Hardware model, e.g. `RB5009UG+S+`
+    pub board_name: Option<String>,
+    /// RouterOS version, e.g. `7.14.3 (stable)`
+    pub version: Option<String>,
+}
+
+impl RouterOsResource {
+    /// Used memory in percent
+    pub fn memory_percent(&self) -> Option<f64> {
+        let free = parse_u64(self.free_memory.as_deref())? as f64;
+        let total = parse_u64(self.total_memory.as_deref())? as f64;
+        (total > 0.0).then(|| (total - free) / total * 100.0)
+    }
+}
+
+/// `/rest/interface` entry
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RouterOsInterface {
+    /// Record ID, e.g. `*1`
+    #[serde(rename = ".id")]
+    pub id: Option<String>,
+    /// Interface name, e.g. `ether1`
+    pub name: String,
+    /// Interface type, e.g. `ether`, `bridge`, `vlan`
+    #[serde(rename = "type")]
+    pub interface_type: Option<String>,
+    /// MAC address
+    pub mac_address: Option<String>,
+    /// Whether the link is up
+    pub running: Option<String>,
+    /// Whether the interface is disabled
+    pub disabled: Option<String>,
+    /// Received bytes
+    pub rx_byte: Option<String>,
+    /// Transmitted bytes
+    pub tx_byte: Option<String>,
+    /// Receive errors
+    pub rx_error: Option<String>,
+    /// Transmit errors
+    pub tx_error: Option<String>,
+}
+
+impl RouterOsInterface {
+    /// Whether the link is up
+    pub fn is_running(&self) -> bool {
+        parse_bool(self.running.as_deref())
+    }
+}
+
+/// `/rest/interface/ethernet` entry (only the fields used for link speed)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouterOsEthernet {
+    /// Interface name
+    pub name: String,
+    /// Configured or negotiated rate, e.g. `1G-baseT-full` or `1Gbps`
+    pub speed: Option<String>,
+}
+
+/// MikroTik adapter error
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum MikroTikError {
+    /// HTTP/network error
+    #[error("HTTP error: {0}")]
+    Http(String),
+    /// Credentials were rejected
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+    /// RouterOS rejected the request
+    #[error("API error: {0}")]
+    Api(String),
+    /// Response or config payload could not be parsed
+    #[error("Parse error: {0}")]
+    Parse(String),
+    /// No such router or resource
+    #[error("Not found: {0}")]
+    NotFound(String),
+}
+
+/// Parse a RouterOS numeric string
+pub fn parse_u64(value: Option<&str>) -> Option<u64> {
+    value?.trim().parse().ok()
+}
+
+/// Parse a RouterOS boolean string
+pub fn parse_bool(value: Option<&str>) -> bool {
+    matches!(value, Some("true" | "yes"))
+}
+
+/// Parse a RouterOS duration such as `2w1d5h31m12s` into seconds
+pub fn parse_uptime_seconds(uptime: &str) -> Option<u64> {
+    let mut total = 0u64;
+    let mut digits = String::new();
+
+    for c in uptime.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let value: u64 = digits.parse().ok()?;
+        digits.clear();
+        total += value * match c {
+            'w' => 7 * 24 * 3600,
+            'd' => 24 * 3600,
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+    }
+
+    digits.is_empty().then_some(total)
+}
+
+/// Parse an ethernet rate such as `1G-baseT-full`, `2.5G-baseT` or `100Mbps` into Mbps
+pub fn parse_rate_mbps(rate: &str) -> Option<u32> {
+    let rate = rate.split('-').next()?.trim_end_matches("bps");
+    let (value, multiplier) = match rate.strip_suffix('G') {
+        Some(gbps) => (gbps, 1000.0),
+        None => (rate.strip_suffix('M')?, 1.0),
+    };
+    let mbps = value.parse::<f64>().ok()? * multiplier;
+    Some(mbps.round() as u32)
+}
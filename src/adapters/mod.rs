@@ -6,7 +6,12 @@
 //!
 //! ### Vendor Adapters (DeviceControlPort)
 //! - `unifi/` - Ubiquiti UniFi Controller
-//! - Future: Cisco, Arista, MikroTik
+//! - `cisco/` - Cisco IOS / IOS-XE over SSH
+//! - `mikrotik/` - MikroTik RouterOS 7 REST API
+//! - `opnsense/` - OPNsense firewalls over the REST API
+//! - `arista/` - Arista EOS over the JSON-RPC eAPI
+//! - `juniper/` - Juniper Junos over NETCONF
+//! - `ssh_generic/` - Any CLI-over-SSH device, driven by a command profile
 //!
 //! ### Inventory Adapters (InventoryPort)
 //! - `netbox/` - NetBox DCIM/IPAM
@@ -14,6 +19,16 @@
 //! ### Event Store Adapters (EventStorePort)
 //! - `nats/` - NATS JetStream event sourcing
 //!
+//! ### Event Consumers (EventSource)
+//! - `webhook/` - Forwards events to HTTP endpoints, signed with HMAC
+//!
+//! ### Discovery Adapters (DiscoveryPort)
+//! - `discovery/lldp` - LLDP neighbor tables (CLI or SNMP)
+//!
+//! ### Shared
+//! - `retry` - Retry with exponential backoff for throttled HTTP calls
+//! - `credentials` - Credential sources for adapter secrets
+//!
 //! ## Kan Extension Integration
 //!
 //! Each adapter implements both:
@@ -25,9 +40,28 @@
 //! - Categorically through the Kan extension
 
 pub mod unifi;
+pub mod cisco;
+pub mod mikrotik;
+pub mod opnsense;
+pub mod arista;
+pub mod juniper;
+pub mod ssh_generic;
 pub mod netbox;
 pub mod nats;
+pub mod webhook;
+pub mod discovery;
+pub mod retry;
+pub mod credentials;
 
 pub use unifi::UniFiAdapter;
+pub use cisco::CiscoIosAdapter;
+pub use mikrotik::MikroTikAdapter;
+pub use opnsense::OpnSenseAdapter;
+pub use arista::AristaAdapter;
+pub use juniper::JuniperAdapter;
+pub use ssh_generic::GenericSshAdapter;
 pub use netbox::NetBoxAdapter;
+pub use discovery::LldpDiscovery;
+pub use credentials::{CredentialSource, Credentials, EnvCredentials, FileCredentials, FnCredentials, Secret};
 pub use nats::{NatsEventStore, NatsEventStoreConfig, NatsEventSubscriber, NatsEventAck};
+pub use webhook::{WebhookConfig, WebhookForwarder};
@@ -9,8 +9,8 @@
 //! │                     NATS JetStream                                  │
 //! │  ┌─────────────────────────────────────────────────────────────┐   │
 //! │  │              Stream: network-events                         │   │
-//! │  │  Subjects: network.device.*, network.connection.*,          │   │
-//! │  │            network.topology.*, network.inventory.*          │   │
+//! │  │  Subjects: network.>  (network.{type}.{id}.{event})         │   │
+//! │  │                                                             │   │
 //! │  └─────────────────────────────────────────────────────────────┘   │
 //! │                              │                                      │
 //! │        ┌────────────────────┼────────────────────┐                 │
@@ -24,31 +24,124 @@
 //!
 //! ## Event Subjects
 //!
-//! Events are published to subjects based on aggregate type:
-//! - `network.device.{event_type}` - Device lifecycle events
-//! - `network.connection.{event_type}` - Connection events
-//! - `network.topology.{event_type}` - Topology events
-//! - `network.inventory.{event_type}` - Inventory sync events
+//! Events are published to subjects based on aggregate type and id:
+//! - `network.device.{aggregate_id}.{event_type}` - Device lifecycle events
+//! - `network.connection.{aggregate_id}.{event_type}` - Connection events
+//! - `network.topology.{aggregate_id}.{event_type}` - Topology events
+//! - `network.inventory.{aggregate_id}.{event_type}` - Inventory sync events
+//!
+//! Replaying one aggregate filters on `network.*.{aggregate_id}.>`, so the
+//! cost is proportional to that aggregate's history, not the whole stream.
+//!
+//! ## Legacy Subjects
+//!
+//! Streams created before per-aggregate partitioning used
+//! `network.{aggregate_type}.{event_type}` and were bound to `network.*.*`.
+//! On startup the stream is migrated to `network.>` so both layouts are
+//! accepted. `migrate_legacy_subjects` is a one-shot upgrade that
+//! republishes legacy events onto their partitioned subjects. Until it has
+//! run, `read_legacy_subjects` can be enabled so replays also scan legacy
+//! subjects (matched by the `CIM-Aggregate-Id` header); that scan covers
+//! every legacy event on each read, so the flag is off by default.
+//!
+//! ## Streaming Replay
+//!
+//! `load_events_stream` pulls and decodes messages as the stream is polled;
+//! `load_events` collects that stream. Undecodable events are yielded as
+//! `PortError::Deserialization` items, which `load_events` logs and skips.
+//!
+//! ## Snapshots
+//!
+//! Aggregate snapshots live in a separate KV bucket (`network-snapshots` by
+//! default) keyed by aggregate id. Each entry records the aggregate version and
+//! the stream sequence of the last event folded into it, so
+//! `load_events_after` can start a consumer right after the snapshot instead of
+//! reading the aggregate's full history.
+//!
+//! ## Optimistic Concurrency
+//!
+//! `append_expected` publishes the first event of a batch with
+//! `Nats-Expected-Last-Subject-Sequence` scoped to the aggregate's subjects via
+//! `Nats-Expected-Last-Subject-Sequence-Subject` (NATS server 2.11+), so two
+//! writers racing on the same aggregate cannot both succeed.
+//!
+//! ## Subscriptions
+//!
+//! `subscribe_events` binds a durable pull consumer to a subject and returns a
+//! `NatsEventSubscriber` yielding each new event with its ack handle, also
+//! usable as an `EventSource` by generic consumers such as the webhook
+//! forwarder. The
+//! consumer name is derived from the subject plus a hash of it, so distinct
+//! subjects never share a consumer and a restarted process resumes where it
+//! left off. `EventStorePort::subscribe` creates the same consumer and
+//! returns its name as the subscription id. Bound consumers are cached by
+//! subject, so subscribing again does not go back to the server.
+//! `subscribe_stream` is the unacknowledged alternative for live feeds: a
+//! core NATS subscription on the subject, with nothing kept on the server.
+//!
+//! Replays use ephemeral consumers instead, one per read, which are deleted
+//! as soon as the replay is done so repeated loads don't pile them up.
+//! `load_recorded_events` also reports each event's stream sequence and its
+//! id, correlation and causation headers, for audit trails.
+//!
+//! ## Purging
+//!
+//! `purge_aggregate` purges an aggregate's partitioned subjects and its
+//! snapshot; events it still has on legacy subjects are deleted one by one.
+//! Tombstones recording a purge go to a separate retention stream
+//! (`network-retention` by default, on `{prefix}-retention.>`), which
+//! replays never read.
+//!
+//! ## Projections
+//!
+//! `fold_all` reads the whole stream through a single replay consumer, so a
+//! projection sees events in exactly the order they were recorded, legacy
+//! and partitioned subjects interleaved.
+//!
+//! ## Reconnection
+//!
+//! The client reconnects on its own with exponential backoff (see
+//! `ReconnectPolicy`). A background health watch follows the client's
+//! connection events: after a reconnect it re-runs `ensure_stream`, so the
+//! cached `Stream` handle is fresh even if the server restarted with the
+//! stream recreated. Appends made while the connection is down wait for
+//! that refresh, up to `ReconnectPolicy::append_wait`, instead of failing
+//! straight away.
 //!
 //! ## Message Headers
 //!
 //! Each message includes CIM-standard headers:
-//! - `Nats-Msg-Id` - Unique message ID (for deduplication)
+//! - `Nats-Msg-Id` - The event id (for deduplication)
 //! - `CIM-Aggregate-Id` - The aggregate this event belongs to
 //! - `CIM-Event-Type` - The event type name
-//! - `CIM-Correlation-Id` - Correlation ID for tracing
-//! - `CIM-Causation-Id` - The event that caused this event
-//! - `CIM-Timestamp` - Event timestamp (RFC3339)
-
-use async_nats::jetstream::{self, consumer::PullConsumer, stream::Stream, Context};
-use async_nats::{Client, HeaderMap, HeaderValue};
+//! - `CIM-Correlation-Id` - Correlation ID for tracing; taken from the
+//!   `EventEnvelope`, or generated per batch when it has none
+//! - `CIM-Causation-Id` - The event that caused this event, when known
+//! - `CIM-Timestamp` - Event timestamp (RFC3339), read from the store's
+//!   `Clock` (`SystemClock` unless `with_clock` injects another)
+
+use async_nats::jetstream::{self, consumer::{DeliverPolicy, PullConsumer}, kv, stream::Stream, Context};
+use async_nats::jetstream::context::{PublishError, PublishErrorKind};
+use async_nats::{Client, ConnectErrorKind, Event, HeaderMap, HeaderValue};
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 use futures::StreamExt;
-use std::sync::Arc;
-use tokio::sync::RwLock;
-
-use crate::domain::events::NetworkEvent;
-use crate::domain::ports::{EventStorePort, PortError};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch, RwLock};
+use tokio::task::JoinHandle;
+
+use crate::domain::events::{EventEnvelope, NetworkEvent, EVENT_SCHEMA_VERSION};
+use crate::domain::aggregates::AggregateError;
+use crate::domain::clock::{Clock, SystemClock};
+use crate::domain::ports::{
+    AggregateSnapshot, EventAck, EventSource, EventStorePort, EventStream, PortError, RecordedEvent,
+    TimestampedEvent,
+};
+use crate::domain::projections::Projection;
+use crate::telemetry;
 
 /// Stream name for network events
 pub const STREAM_NAME: &str = "network-events";
@@ -56,6 +149,47 @@ pub const STREAM_NAME: &str = "network-events";
 /// Subject prefix for all network events
 pub const SUBJECT_PREFIX: &str = "network";
 
+/// KV bucket holding aggregate snapshots
+pub const SNAPSHOT_BUCKET: &str = "network-snapshots";
+
+/// Stream holding tombstones of purged aggregates
+pub const RETENTION_STREAM_NAME: &str = "network-retention";
+
+/// Maximum time to wait for the next message while draining a replay consumer
+const REPLAY_IDLE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How the client reconnects after losing the NATS server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Delay before the first reconnect attempt; doubled for each further attempt
+    pub base_delay: Duration,
+    /// Upper bound on any single delay
+    pub max_delay: Duration,
+    /// Consecutive attempts before the client gives up (`None` = never)
+    pub max_reconnects: Option<usize>,
+    /// How long an append waits for a lost connection to come back
+    pub append_wait: Duration,
+}
+
+impl ReconnectPolicy {
+    /// Delay before reconnect attempt number `attempt` (1-based)
+    pub fn delay(&self, attempt: usize) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1).min(31) as u32);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(8),
+            max_reconnects: None,
+            append_wait: Duration::from_secs(30),
+        }
+    }
+}
+
 /// Configuration for the NATS event store
 #[derive(Debug, Clone)]
 pub struct NatsEventStoreConfig {
@@ -72,6 +206,18 @@ pub struct NatsEventStoreConfig {
     pub max_age_seconds: u64,
     /// Number of replicas (for HA)
     pub replicas: usize,
+    /// Also scan pre-partitioning `{prefix}.{type}.{event}` subjects on replay
+    ///
+    /// Temporary compatibility switch for streams not yet migrated with
+    /// `NatsEventStore::migrate_legacy_subjects`. While on, every replay,
+    /// version check, listing and purge scans all legacy subjects.
+    pub read_legacy_subjects: bool,
+    /// KV bucket for aggregate snapshots (defaults to "network-snapshots")
+    pub snapshot_bucket: String,
+    /// Stream for tombstones of purged aggregates (defaults to "network-retention")
+    pub retention_stream_name: String,
+    /// Reconnect backoff and how long appends wait for a reconnect
+    pub reconnect: ReconnectPolicy,
 }
 
 impl Default for NatsEventStoreConfig {
@@ -83,6 +229,10 @@ impl Default for NatsEventStoreConfig {
             max_messages: 0,        // Unlimited
             max_age_seconds: 0,     // Keep forever
             replicas: 1,            // Single node
+            read_legacy_subjects: false,
+            snapshot_bucket: SNAPSHOT_BUCKET.to_string(),
+            retention_stream_name: RETENTION_STREAM_NAME.to_string(),
+            reconnect: ReconnectPolicy::default(),
         }
     }
 }
@@ -100,6 +250,10 @@ impl NatsEventStoreConfig {
             max_messages: 0,
             max_age_seconds: 0,
             replicas: 1,
+            read_legacy_subjects: false,
+            snapshot_bucket: format!("test-{}-snapshots", short_id),
+            retention_stream_name: format!("test-{}-retention", short_id),
+            reconnect: ReconnectPolicy::default(),
         }
     }
 }
@@ -114,8 +268,18 @@ pub struct NatsEventStore {
     jetstream: Context,
     /// Stream reference
     stream: Arc<RwLock<Option<Stream>>>,
+    /// Snapshot bucket
+    snapshots: kv::Store,
     /// Configuration
     config: NatsEventStoreConfig,
+    /// Subscription consumers already bound, by filter subject
+    consumers: Arc<Mutex<HashMap<String, PullConsumer>>>,
+    /// Whether the client is connected and the stream handle is current
+    ready: watch::Receiver<bool>,
+    /// Background task following connection events
+    health_watch: JoinHandle<()>,
+    /// Source of `CIM-Timestamp` headers
+    clock: Arc<dyn Clock>,
 }
 
 impl NatsEventStore {
@@ -130,27 +294,72 @@ impl NatsEventStore {
     /// let store = NatsEventStore::new(config).await?;
     /// ```
     pub async fn new(config: NatsEventStoreConfig) -> Result<Self, PortError> {
+        let policy = config.reconnect;
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+
         // Connect to NATS
-        let client = async_nats::connect(&config.nats_url)
+        let client = async_nats::ConnectOptions::new()
+            .max_reconnects(policy.max_reconnects)
+            .reconnect_delay_callback(move |attempt| policy.delay(attempt))
+            .event_callback(move |event| {
+                let events_tx = events_tx.clone();
+                async move {
+                    let _ = events_tx.send(event);
+                }
+            })
+            .connect(&config.nats_url)
             .await
-            .map_err(|e| PortError::ConnectionFailed(format!("NATS connection failed: {}", e)))?;
+            .map_err(|e| match e.kind() {
+                ConnectErrorKind::Authentication | ConnectErrorKind::AuthorizationViolation => {
+                    PortError::AuthenticationFailed(format!("NATS connection refused: {}", e))
+                }
+                ConnectErrorKind::TimedOut => PortError::Timeout(format!("NATS connection timed out: {}", e)),
+                _ => PortError::ConnectionFailed(format!("NATS connection failed: {}", e)),
+            })?;
 
         tracing::info!("Connected to NATS at {}", config.nats_url);
 
         // Create JetStream context
         let jetstream = jetstream::new(client.clone());
 
-        let store = Self {
+        let snapshots = Self::open_snapshot_bucket(&jetstream, &config).await?;
+
+        // Initialize the stream
+        let stream = Arc::new(RwLock::new(None));
+        Self::ensure_stream(&jetstream, &config, &stream).await?;
+
+        let consumers = Arc::new(Mutex::new(HashMap::new()));
+        let (ready_tx, ready) = watch::channel(true);
+        let health_watch = {
+            let (jetstream, config, stream) = (jetstream.clone(), config.clone(), stream.clone());
+            let consumers = consumers.clone();
+            tokio::spawn(watch_connection(events_rx, ready_tx, policy, move || {
+                // A recreated stream has none of the cached consumers
+                if let Ok(mut consumers) = consumers.lock() {
+                    consumers.clear();
+                }
+                let (jetstream, config, stream) = (jetstream.clone(), config.clone(), stream.clone());
+                async move { Self::ensure_stream(&jetstream, &config, &stream).await }
+            }))
+        };
+
+        Ok(Self {
             client,
             jetstream,
-            stream: Arc::new(RwLock::new(None)),
+            stream,
+            snapshots,
             config,
-        };
-
-        // Initialize the stream
-        store.ensure_stream().await?;
+            consumers,
+            ready,
+            health_watch,
+            clock: Arc::new(SystemClock),
+        })
+    }
 
-        Ok(store)
+    /// Stamp events with `clock` instead of the system clock
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
     }
 
     /// Connect with default configuration
@@ -162,105 +371,263 @@ impl NatsEventStore {
         Self::new(config).await
     }
 
-    /// Ensure the stream exists with proper configuration
-    async fn ensure_stream(&self) -> Result<(), PortError> {
+    /// Subjects the stream is bound to
+    ///
+    /// `{prefix}.>` covers both the partitioned
+    /// `{prefix}.{type}.{id}.{event}` layout and legacy `{prefix}.{type}.{event}`.
+    fn stream_subjects(config: &NatsEventStoreConfig) -> Vec<String> {
+        vec![format!("{}.>", config.subject_prefix)]
+    }
+
+    /// Filter subject matching every event of a single aggregate
+    fn aggregate_filter_subject(&self, aggregate_id: &str) -> String {
+        format!("{}.*.{}.>", self.config.subject_prefix, aggregate_id)
+    }
+
+    /// Filter subject matching only legacy (pre-partitioning) events
+    fn legacy_filter_subject(&self) -> String {
+        format!("{}.*.*", self.config.subject_prefix)
+    }
+
+    /// Filter subject matching every event replays may read, in stream order
+    fn fold_filter_subject(&self) -> String {
+        if self.config.read_legacy_subjects {
+            format!("{}.>", self.config.subject_prefix)
+        } else {
+            format!("{}.*.*.*", self.config.subject_prefix)
+        }
+    }
+
+    /// Filter subject matching every partitioned event of one aggregate type
+    fn aggregate_type_filter_subject(&self, aggregate_type: &str) -> String {
+        format!("{}.{}.*.*", self.config.subject_prefix, aggregate_type)
+    }
+
+    /// Subject prefix of the retention stream, outside `{prefix}.>`
+    fn retention_subject_prefix(&self) -> String {
+        format!("{}-retention", self.config.subject_prefix)
+    }
+
+    /// Ensure the stream exists with proper configuration and cache its handle
+    async fn ensure_stream(
+        jetstream: &Context,
+        config: &NatsEventStoreConfig,
+        stream_handle: &RwLock<Option<Stream>>,
+    ) -> Result<(), PortError> {
         let stream_config = jetstream::stream::Config {
-            name: self.config.stream_name.clone(),
+            name: config.stream_name.clone(),
             description: Some("Network domain events for CIM".to_string()),
-            subjects: vec![format!("{}.*.*", self.config.subject_prefix)],
+            subjects: Self::stream_subjects(config),
             retention: jetstream::stream::RetentionPolicy::Limits,
-            max_messages: self.config.max_messages,
-            max_age: if self.config.max_age_seconds > 0 {
-                std::time::Duration::from_secs(self.config.max_age_seconds)
+            max_messages: config.max_messages,
+            max_age: if config.max_age_seconds > 0 {
+                std::time::Duration::from_secs(config.max_age_seconds)
             } else {
                 std::time::Duration::ZERO
             },
             storage: jetstream::stream::StorageType::File,
-            num_replicas: self.config.replicas,
+            num_replicas: config.replicas,
             duplicate_window: std::time::Duration::from_secs(120),
             ..Default::default()
         };
 
-        let stream = self.jetstream
+        let mut stream = jetstream
             .get_or_create_stream(stream_config)
             .await
             .map_err(|e| PortError::ConnectionFailed(format!("Failed to create stream: {}", e)))?;
 
+        // Migrate streams created with the legacy `{prefix}.*.*` binding
+        let desired_subjects = Self::stream_subjects(config);
+        if stream.cached_info().config.subjects != desired_subjects {
+            tracing::info!(
+                "Migrating stream '{}' subjects {:?} -> {:?}",
+                config.stream_name,
+                stream.cached_info().config.subjects,
+                desired_subjects
+            );
+
+            let mut migrated = stream.cached_info().config.clone();
+            migrated.subjects = desired_subjects;
+
+            jetstream
+                .update_stream(&migrated)
+                .await
+                .map_err(|e| PortError::ConnectionFailed(format!("Failed to migrate stream: {}", e)))?;
+
+            stream = jetstream
+                .get_stream(&config.stream_name)
+                .await
+                .map_err(|e| PortError::ConnectionFailed(format!("Failed to reload stream: {}", e)))?;
+        }
+
         tracing::info!(
             "JetStream stream '{}' ready with {} messages",
-            self.config.stream_name,
+            config.stream_name,
             stream.cached_info().state.messages
         );
 
-        let mut stream_lock = self.stream.write().await;
+        let mut stream_lock = stream_handle.write().await;
         *stream_lock = Some(stream);
 
         Ok(())
     }
 
+    /// Open the snapshot bucket, creating it on first use
+    async fn open_snapshot_bucket(
+        jetstream: &Context,
+        config: &NatsEventStoreConfig,
+    ) -> Result<kv::Store, PortError> {
+        if let Ok(store) = jetstream.get_key_value(&config.snapshot_bucket).await {
+            return Ok(store);
+        }
+
+        jetstream
+            .create_key_value(kv::Config {
+                bucket: config.snapshot_bucket.clone(),
+                description: "Network aggregate snapshots".to_string(),
+                history: 1,
+                storage: jetstream::stream::StorageType::File,
+                num_replicas: config.replicas,
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| PortError::ConnectionFailed(format!("Failed to create snapshot bucket: {}", e)))
+    }
+
     /// Get the NATS subject for an event using the configured prefix
     fn event_subject(&self, event: &NetworkEvent) -> String {
-        event.nats_subject_with_prefix(&self.config.subject_prefix)
+        event.nats_subject_with_prefix(&self.config.subject_prefix, &event.aggregate_id())
     }
 
     /// Create headers for an event message
-    fn create_headers(event: &NetworkEvent, correlation_id: Option<&str>) -> HeaderMap {
+    ///
+    /// `default_correlation_id` is used when the envelope carries none.
+    /// `timestamp` becomes the `CIM-Timestamp` header.
+    fn create_headers(
+        envelope: &EventEnvelope,
+        default_correlation_id: &str,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    ) -> HeaderMap {
+        let event = &envelope.event;
         let mut headers = HeaderMap::new();
 
-        // Message ID for deduplication (aggregate_id + event_type + timestamp)
-        let msg_id = format!(
-            "{}-{}-{}",
-            event.aggregate_id(),
-            event.event_type(),
-            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
-        );
-        headers.insert("Nats-Msg-Id", HeaderValue::from(msg_id.as_str()));
+        // The event id doubles as the message id, so a retried publish of the
+        // same envelope is deduplicated by the server
+        headers.insert("Nats-Msg-Id", HeaderValue::from(envelope.event_id.as_str()));
 
         // CIM standard headers
         headers.insert("CIM-Aggregate-Id", HeaderValue::from(event.aggregate_id().as_str()));
         headers.insert("CIM-Event-Type", HeaderValue::from(event.event_type()));
+        headers.insert(
+            "CIM-Schema-Version",
+            HeaderValue::from(EVENT_SCHEMA_VERSION.to_string().as_str()),
+        );
         headers.insert(
             "CIM-Timestamp",
-            HeaderValue::from(chrono::Utc::now().to_rfc3339().as_str()),
+            HeaderValue::from(timestamp.to_rfc3339().as_str()),
         );
 
-        if let Some(corr_id) = correlation_id {
-            headers.insert("CIM-Correlation-Id", HeaderValue::from(corr_id));
+        let correlation_id = envelope.correlation_id.as_deref().unwrap_or(default_correlation_id);
+        headers.insert("CIM-Correlation-Id", HeaderValue::from(correlation_id));
+        if let Some(causation_id) = &envelope.causation_id {
+            headers.insert("CIM-Causation-Id", HeaderValue::from(causation_id.as_str()));
         }
 
         headers
     }
 
-    /// Publish a single event
-    async fn publish_event(
+    /// Serialize an event payload
+    fn encode_event(event: &NetworkEvent) -> Result<Vec<u8>, PortError> {
+        event
+            .encode()
+            .map_err(|e| PortError::VendorError(format!("Serialization failed: {}", e)))
+    }
+
+    /// Publish an event with prepared headers, waiting for the ack
+    async fn send_event(
         &self,
         event: &NetworkEvent,
-        correlation_id: Option<&str>,
-    ) -> Result<(), PortError> {
+        headers: HeaderMap,
+        payload: Vec<u8>,
+    ) -> Result<(), PublishError> {
         let subject = self.event_subject(event);
-        let headers = Self::create_headers(event, correlation_id);
-
-        let payload = serde_json::to_vec(event)
-            .map_err(|e| PortError::VendorError(format!("Serialization failed: {}", e)))?;
 
         self.jetstream
             .publish_with_headers(subject.clone(), headers, payload.into())
-            .await
-            .map_err(|e| PortError::VendorError(format!("Publish failed: {}", e)))?
-            .await
-            .map_err(|e| PortError::VendorError(format!("Publish ack failed: {}", e)))?;
+            .await?
+            .await?;
 
         tracing::debug!("Published event {} to {}", event.event_type(), subject);
 
         Ok(())
     }
 
+    /// Publish a batch of events and wait for all acks together
+    ///
+    /// Every event is sent before any ack is awaited, so a batch costs one
+    /// round-trip instead of one per event. `first_index` offsets the event
+    /// index reported when an ack fails.
+    async fn publish_batch(
+        &self,
+        envelopes: &[EventEnvelope],
+        correlation_id: &str,
+        first_index: usize,
+    ) -> Result<(), PortError> {
+        // Encode up front so a bad event fails the batch before anything is sent
+        let payloads = envelopes
+            .iter()
+            .map(|envelope| Self::encode_event(&envelope.event))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut acks = Vec::with_capacity(envelopes.len());
+        for (offset, (envelope, payload)) in envelopes.iter().zip(payloads).enumerate() {
+            let headers = Self::create_headers(envelope, correlation_id, self.clock.now());
+            let ack = self
+                .jetstream
+                .publish_with_headers(self.event_subject(&envelope.event), headers, payload.into())
+                .await
+                .map_err(|e| publish_error(format!("Publish of event {}", first_index + offset), e))?;
+            acks.push(ack);
+        }
+
+        await_acks(acks, first_index).await?;
+
+        tracing::debug!("Published batch of {} events", envelopes.len());
+        Ok(())
+    }
+
+    /// Current version (event count) and last stream sequence of an aggregate
+    async fn aggregate_position(&self, aggregate_id: &str) -> Result<(u64, u64), PortError> {
+        let consumer_name = format!("count-{}-{}", aggregate_id, uuid::Uuid::now_v7());
+        let consumer = self
+            .create_replay_consumer(&self.aggregate_filter_subject(aggregate_id), &consumer_name, DeliverPolicy::All)
+            .await?;
+        let mut version = consumer.pending();
+        consumer.delete().await;
+
+        // Legacy events have no per-aggregate subject, so they must be scanned
+        if self.config.read_legacy_subjects {
+            let consumer_name = format!("count-legacy-{}-{}", aggregate_id, uuid::Uuid::now_v7());
+            let consumer = self
+                .create_replay_consumer(&self.legacy_filter_subject(), &consumer_name, DeliverPolicy::All)
+                .await?;
+            version += Self::drain_replay_consumer(consumer, Some(aggregate_id)).await?.len() as u64;
+        }
+
+        let last_sequence = self.last_aggregate_sequence(aggregate_id).await?;
+        Ok((version, last_sequence))
+    }
+
     /// Create a consumer for replaying events
+    ///
+    /// The consumer is deleted from the server once the replay is done (see
+    /// `ReplayConsumer`) rather than left to expire.
     async fn create_replay_consumer(
         &self,
         filter_subject: &str,
         consumer_name: &str,
-    ) -> Result<PullConsumer, PortError> {
+        deliver_policy: DeliverPolicy,
+    ) -> Result<ReplayConsumer, PortError> {
         let stream = self.stream.read().await;
         let stream = stream
             .as_ref()
@@ -270,7 +637,7 @@ impl NatsEventStore {
             name: Some(consumer_name.to_string()),
             durable_name: None, // Ephemeral consumer for replay
             filter_subject: filter_subject.to_string(),
-            deliver_policy: jetstream::consumer::DeliverPolicy::All,
+            deliver_policy,
             ack_policy: jetstream::consumer::AckPolicy::None, // Replay doesn't need acks
             ..Default::default()
         };
@@ -280,108 +647,353 @@ impl NatsEventStore {
             .await
             .map_err(|e| PortError::ConnectionFailed(format!("Failed to create consumer: {}", e)))?;
 
-        Ok(consumer)
+        Ok(ReplayConsumer { consumer, stream: stream.clone(), deleted: false })
     }
 
-    /// Get the underlying NATS client
-    pub fn client(&self) -> &Client {
-        &self.client
-    }
+    /// Stream every message pending on a replay consumer
+    ///
+    /// The number of messages to read is taken from the consumer's pending
+    /// count at creation, so replay never stops early because of a deadline.
+    /// When `aggregate_id` is given, only messages whose `CIM-Aggregate-Id`
+    /// header matches are yielded (used for legacy subjects). Messages are
+    /// pulled and decoded by `decode` as the stream is polled; nothing is
+    /// buffered here. The consumer is deleted once every message is read.
+    async fn replay_stream<T: Send + 'static>(
+        consumer: ReplayConsumer,
+        aggregate_id: Option<String>,
+        decode: ReplayDecoder<T>,
+    ) -> Result<BoxStream<'static, Result<T, PortError>>, PortError> {
+        let pending = consumer.pending();
+        if pending == 0 {
+            consumer.delete().await;
+            return Ok(Box::pin(futures::stream::empty()));
+        }
 
-    /// Get the JetStream context
-    pub fn jetstream(&self) -> &Context {
-        &self.jetstream
+        let messages = consumer.consumer.messages().await
+            .map_err(|e| PortError::VendorError(format!("Failed to get messages: {}", e)))?;
+
+        let stream = futures::stream::unfold(
+            (messages, 0u64, aggregate_id, consumer),
+            move |(mut messages, mut received, aggregate_id, consumer)| async move {
+                while received < pending {
+                    let msg = match tokio::time::timeout(REPLAY_IDLE_TIMEOUT, messages.next()).await {
+                        Ok(Some(Ok(msg))) => msg,
+                        Ok(Some(Err(e))) => {
+                            tracing::warn!("Error reading message: {}", e);
+                            continue;
+                        }
+                        Ok(None) => return None,
+                        Err(_) => {
+                            let error = PortError::Timeout(format!(
+                                "Replay stalled after {} of {} messages",
+                                received, pending
+                            ));
+                            // End the stream after reporting the stall
+                            return Some((Err(error), (messages, pending, aggregate_id, consumer)));
+                        }
+                    };
+                    received += 1;
+
+                    if let Some(item) = decode(&msg, aggregate_id.as_deref()) {
+                        return Some((item, (messages, received, aggregate_id, consumer)));
+                    }
+                }
+                consumer.delete().await;
+                None
+            },
+        );
+
+        Ok(Box::pin(stream))
     }
-}
 
-#[async_trait]
-impl EventStorePort for NatsEventStore {
-    async fn append(&self, events: Vec<NetworkEvent>) -> Result<(), PortError> {
-        if events.is_empty() {
-            return Ok(());
+    /// Stream an aggregate's full history, legacy subjects first
+    async fn replay_aggregate<T: Send + 'static>(
+        &self,
+        aggregate_id: &str,
+        decode: ReplayDecoder<T>,
+    ) -> Result<BoxStream<'static, Result<T, PortError>>, PortError> {
+        let mut streams = Vec::with_capacity(2);
+
+        // Legacy events predate partitioning, so they are always older
+        if self.config.read_legacy_subjects {
+            let consumer_name = format!("replay-legacy-{}-{}", aggregate_id, uuid::Uuid::now_v7());
+            let consumer = self
+                .create_replay_consumer(&self.legacy_filter_subject(), &consumer_name, DeliverPolicy::All)
+                .await?;
+            streams.push(Self::replay_stream(consumer, Some(aggregate_id.to_string()), decode).await?);
         }
 
-        // Generate correlation ID for this batch
-        let correlation_id = uuid::Uuid::now_v7().to_string();
+        // Partitioned subjects: the filter already selects this aggregate only
+        let consumer_name = format!("replay-{}-{}", aggregate_id, uuid::Uuid::now_v7());
+        let consumer = self
+            .create_replay_consumer(&self.aggregate_filter_subject(aggregate_id), &consumer_name, DeliverPolicy::All)
+            .await?;
+        streams.push(Self::replay_stream(consumer, None, decode).await?);
 
-        tracing::info!(
-            "Appending {} events with correlation_id {}",
-            events.len(),
-            correlation_id
-        );
+        Ok(Box::pin(futures::stream::iter(streams).flatten()))
+    }
+
+    /// Drain every message pending on a replay consumer into memory
+    ///
+    /// Undecodable events are logged and skipped.
+    async fn drain_replay_consumer(
+        consumer: ReplayConsumer,
+        aggregate_id: Option<&str>,
+    ) -> Result<Vec<NetworkEvent>, PortError> {
+        let stream = Self::replay_stream(consumer, aggregate_id.map(str::to_string), decode_replay_message).await?;
+        collect_events(stream).await
+    }
+
+    /// Stream sequence of the newest partitioned event for an aggregate (0 if none)
+    async fn last_aggregate_sequence(&self, aggregate_id: &str) -> Result<u64, PortError> {
+        let consumer_name = format!("last-{}-{}", aggregate_id, uuid::Uuid::now_v7());
+        let consumer = self
+            .create_replay_consumer(&self.aggregate_filter_subject(aggregate_id), &consumer_name, DeliverPolicy::Last)
+            .await?;
 
-        for event in &events {
-            self.publish_event(event, Some(&correlation_id)).await?;
+        if consumer.pending() == 0 {
+            consumer.delete().await;
+            return Ok(0);
         }
 
-        Ok(())
+        let mut messages = consumer.consumer.messages().await
+            .map_err(|e| PortError::VendorError(format!("Failed to get messages: {}", e)))?;
+
+        let sequence = match tokio::time::timeout(REPLAY_IDLE_TIMEOUT, messages.next()).await {
+            Ok(Some(Ok(msg))) => msg
+                .info()
+                .map(|info| info.stream_sequence)
+                .map_err(|e| PortError::VendorError(format!("Invalid message metadata: {}", e))),
+            Ok(Some(Err(e))) => Err(PortError::VendorError(format!("Message error: {}", e))),
+            Ok(None) => Ok(0),
+            Err(_) => Err(PortError::Timeout(format!(
+                "Timed out reading last event for {}",
+                aggregate_id
+            ))),
+        };
+        drop(messages);
+        consumer.delete().await;
+        sequence
     }
 
-    async fn load_events(&self, aggregate_id: &str) -> Result<Vec<NetworkEvent>, PortError> {
-        // Create a filter subject that matches all events for this aggregate
-        // Events are published to {prefix}.{aggregate_type}.{event_type}
-        // We need to filter by aggregate_id in the message body
+    /// Delete an aggregate's events on legacy subjects, one message at a time
+    ///
+    /// Legacy subjects are shared by every aggregate of a type, so they
+    /// cannot be purged by subject. Returns the number of messages deleted.
+    async fn delete_legacy_events(&self, aggregate_id: &str) -> Result<u64, PortError> {
+        let consumer_name = format!("purge-legacy-{}-{}", aggregate_id, uuid::Uuid::now_v7());
+        let consumer = self
+            .create_replay_consumer(&self.legacy_filter_subject(), &consumer_name, DeliverPolicy::All)
+            .await?;
+        let pending = consumer.pending();
+        if pending == 0 {
+            consumer.delete().await;
+            return Ok(0);
+        }
 
-        // For efficiency, we'll filter by the aggregate type prefix if we can determine it
-        // This is a simplification - in production you might have aggregate-specific streams
-        let filter_subject = format!("{}.>", self.config.subject_prefix);
+        let mut messages = consumer.consumer.messages().await
+            .map_err(|e| PortError::VendorError(format!("Failed to get messages: {}", e)))?;
+        let mut sequences = Vec::new();
+        for _ in 0..pending {
+            let msg = match tokio::time::timeout(REPLAY_IDLE_TIMEOUT, messages.next()).await {
+                Ok(Some(Ok(msg))) => msg,
+                Ok(Some(Err(e))) => return Err(PortError::VendorError(format!("Message error: {}", e))),
+                Ok(None) => break,
+                Err(_) => return Err(PortError::Timeout(format!("Timed out scanning legacy events of {}", aggregate_id))),
+            };
+            let belongs = msg.headers
+                .as_ref()
+                .and_then(|h| h.get("CIM-Aggregate-Id"))
+                .is_some_and(|v| v.as_str() == aggregate_id);
+            if belongs {
+                let info = msg.info()
+                    .map_err(|e| PortError::VendorError(format!("Invalid message metadata: {}", e)))?;
+                sequences.push(info.stream_sequence);
+            }
+        }
+        drop(messages);
 
-        let consumer_name = format!("replay-{}-{}", aggregate_id, uuid::Uuid::now_v7());
-        let consumer = self.create_replay_consumer(&filter_subject, &consumer_name).await?;
+        for sequence in &sequences {
+            consumer.stream
+                .delete_message(*sequence)
+                .await
+                .map_err(|e| PortError::VendorError(format!("Failed to delete message {}: {}", sequence, e)))?;
+        }
+        consumer.delete().await;
+        Ok(sequences.len() as u64)
+    }
 
-        let mut events = Vec::new();
-        let mut messages = consumer.messages().await
-            .map_err(|e| PortError::VendorError(format!("Failed to get messages: {}", e)))?;
+    /// Republish every legacy-subject event onto its partitioned subject
+    ///
+    /// One-shot upgrade for streams written before per-aggregate
+    /// partitioning, after which `read_legacy_subjects` can stay off. Run it
+    /// while nothing else writes to the stream. Each affected aggregate is
+    /// rewritten as a whole, legacy events first and then any partitioned
+    /// ones, so its history keeps its order; messages keep their headers,
+    /// including the original `CIM-Timestamp`. Returns the number of legacy
+    /// events migrated. Running it again once done migrates nothing.
+    pub async fn migrate_legacy_subjects(&self) -> Result<u64, PortError> {
+        self.wait_until_ready().await?;
+
+        let consumer_name = format!("migrate-legacy-{}", uuid::Uuid::now_v7());
+        let consumer = self
+            .create_replay_consumer(&self.legacy_filter_subject(), &consumer_name, DeliverPolicy::All)
+            .await?;
+        let legacy = collect_events(Self::replay_stream(consumer, None, capture_message).await?).await?;
+
+        let mut by_aggregate: std::collections::BTreeMap<String, Vec<CapturedMessage>> = Default::default();
+        for message in legacy {
+            match message.header("CIM-Aggregate-Id") {
+                Some(aggregate_id) => by_aggregate.entry(aggregate_id).or_default().push(message),
+                None => tracing::warn!(
+                    "Leaving legacy message {} on {} without CIM-Aggregate-Id",
+                    message.sequence,
+                    message.subject
+                ),
+            }
+        }
+
+        let mut migrated = 0;
+        for (aggregate_id, legacy) in by_aggregate {
+            migrated += self.migrate_aggregate(&aggregate_id, legacy).await?;
+        }
 
-        // Fetch messages with a timeout
-        let timeout = tokio::time::Duration::from_secs(5);
-        let deadline = tokio::time::Instant::now() + timeout;
+        tracing::info!("Migrated {} legacy events onto partitioned subjects", migrated);
+        Ok(migrated)
+    }
 
-        loop {
-            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
-            if remaining.is_zero() {
-                break;
+    /// Rewrite one aggregate's history onto partitioned subjects
+    ///
+    /// Nothing is deleted until every copy is acked; if any copy fails, or is
+    /// swallowed as a duplicate because its original is still inside the
+    /// stream's duplicate window, the copies made so far are deleted and the
+    /// originals kept. The aggregate's snapshot is purged on success, since
+    /// it records a stream sequence that no longer exists.
+    async fn migrate_aggregate(&self, aggregate_id: &str, legacy: Vec<CapturedMessage>) -> Result<u64, PortError> {
+        // Partitioned events are newer than any legacy one, so they are
+        // rewritten after them
+        let consumer_name = format!("migrate-{}-{}", aggregate_id, uuid::Uuid::now_v7());
+        let consumer = self
+            .create_replay_consumer(&self.aggregate_filter_subject(aggregate_id), &consumer_name, DeliverPolicy::All)
+            .await?;
+        let partitioned = collect_events(Self::replay_stream(consumer, None, capture_message).await?).await?;
+
+        let migrated = legacy.len() as u64;
+        let originals: Vec<CapturedMessage> = legacy.into_iter().chain(partitioned).collect();
+
+        let mut acks = Vec::with_capacity(originals.len());
+        let mut failure = None;
+        for message in &originals {
+            let subject = partitioned_subject(&self.config.subject_prefix, &message.subject, aggregate_id);
+            match self
+                .jetstream
+                .publish_with_headers(subject, message.headers.clone(), message.payload.clone().into())
+                .await
+            {
+                Ok(ack) => acks.push(ack),
+                Err(e) => {
+                    failure = Some(publish_error(format!("Republish of message {}", message.sequence), e));
+                    break;
+                }
             }
+        }
 
-            match tokio::time::timeout(remaining, messages.next()).await {
-                Ok(Some(msg)) => {
-                    match msg {
-                        Ok(msg) => {
-                            // Check if this message belongs to our aggregate
-                            let matches_aggregate = msg.headers
-                                .as_ref()
-                                .and_then(|h| h.get("CIM-Aggregate-Id"))
-                                .map(|v| v.as_str() == aggregate_id)
-                                .unwrap_or(false);
-
-                            if matches_aggregate {
-                                if let Ok(event) = serde_json::from_slice::<NetworkEvent>(&msg.payload) {
-                                    events.push(event);
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            tracing::warn!("Error reading message: {}", e);
-                        }
-                    }
+        let mut copies = Vec::with_capacity(acks.len());
+        for (message, ack) in originals.iter().zip(acks) {
+            match ack.await {
+                Ok(ack) if ack.duplicate => {
+                    failure.get_or_insert_with(|| PortError::VendorError(format!(
+                        "Message {} of {} is still inside the duplicate window; retry the migration later",
+                        message.sequence, aggregate_id
+                    )));
+                }
+                Ok(ack) => copies.push(ack.sequence),
+                Err(e) => {
+                    failure.get_or_insert_with(|| {
+                        publish_error(format!("Republish of message {}", message.sequence), e)
+                    });
                 }
-                Ok(None) => break,
-                Err(_) => break, // Timeout
             }
         }
 
-        tracing::debug!(
-            "Loaded {} events for aggregate {}",
-            events.len(),
-            aggregate_id
-        );
+        let stream = self.stream.read().await;
+        let stream = stream
+            .as_ref()
+            .ok_or_else(|| PortError::ConnectionFailed("Stream not initialized".to_string()))?;
 
-        Ok(events)
+        let (doomed, result) = match failure {
+            Some(e) => (copies, Err(e)),
+            None => (originals.iter().map(|m| m.sequence).collect(), Ok(migrated)),
+        };
+        for sequence in doomed {
+            stream
+                .delete_message(sequence)
+                .await
+                .map_err(|e| PortError::VendorError(format!("Failed to delete message {}: {}", sequence, e)))?;
+        }
+        drop(stream);
+
+        if result.is_ok() {
+            self.snapshots
+                .purge(aggregate_id)
+                .await
+                .map_err(|e| PortError::VendorError(format!("Failed to purge snapshot: {}", e)))?;
+            tracing::debug!("Migrated {} legacy events of aggregate {}", migrated, aggregate_id);
+        }
+        result
     }
 
-    async fn subscribe(&self, subject: &str) -> Result<crate::domain::ports::EventSubscription, PortError> {
-        // Create a durable consumer for this subscription
-        let consumer_name = format!("sub-{}", subject.replace('.', "-").replace('*', "all").replace('>', "gt"));
+    /// Make sure the retention stream exists
+    async fn ensure_retention_stream(&self) -> Result<(), PortError> {
+        let stream_config = jetstream::stream::Config {
+            name: self.config.retention_stream_name.clone(),
+            description: Some("Tombstones of purged network aggregates".to_string()),
+            subjects: vec![format!("{}.>", self.retention_subject_prefix())],
+            retention: jetstream::stream::RetentionPolicy::Limits,
+            storage: jetstream::stream::StorageType::File,
+            num_replicas: self.config.replicas,
+            ..Default::default()
+        };
+
+        self.jetstream
+            .get_or_create_stream(stream_config)
+            .await
+            .map_err(|e| PortError::ConnectionFailed(format!("Failed to create retention stream: {}", e)))?;
+        Ok(())
+    }
+
+    /// Read and decode the snapshot entry for an aggregate
+    async fn read_snapshot_entry(&self, aggregate_id: &str) -> Result<Option<SnapshotEntry>, PortError> {
+        let bytes = self.snapshots
+            .get(aggregate_id)
+            .await
+            .map_err(|e| PortError::VendorError(format!("Failed to read snapshot: {}", e)))?;
+
+        Ok(bytes.and_then(|b| SnapshotEntry::decode(&b)))
+    }
+
+    /// Subscribe to events published on `subject` from now on
+    ///
+    /// Creates (or rebinds to) a durable consumer for the subject, so events
+    /// published while the subscriber is gone are delivered once it returns.
+    /// Each event must be acked through its `NatsEventAck`.
+    pub async fn subscribe_events(&self, subject: &str) -> Result<NatsEventSubscriber, PortError> {
+        let consumer = self.subscription_consumer(subject).await?;
+        tracing::info!("Subscribed to {} via {}", subject, consumer.cached_info().name);
+        Ok(NatsEventSubscriber::new(consumer))
+    }
+
+    /// Get or create the durable consumer backing a subscription to `subject`
+    ///
+    /// Consumers are cached by subject, so subscribing again reuses the one
+    /// already bound instead of asking the server for it.
+    async fn subscription_consumer(&self, subject: &str) -> Result<PullConsumer, PortError> {
+        let cached = self.consumers.lock().ok().and_then(|consumers| consumers.get(subject).cloned());
+        if let Some(consumer) = cached {
+            return Ok(consumer);
+        }
+
+        let consumer_name = durable_consumer_name(subject);
 
         let stream = self.stream.read().await;
         let stream = stream
@@ -390,94 +1002,1107 @@ impl EventStorePort for NatsEventStore {
 
         let consumer_config = jetstream::consumer::pull::Config {
             name: Some(consumer_name.clone()),
-            durable_name: Some(consumer_name),
+            durable_name: Some(consumer_name.clone()),
             filter_subject: subject.to_string(),
             deliver_policy: jetstream::consumer::DeliverPolicy::New,
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
             ..Default::default()
         };
 
-        let _consumer = stream
-            .get_or_create_consumer(&consumer_config.name.clone().unwrap(), consumer_config)
+        let consumer: PullConsumer = stream
+            .get_or_create_consumer(&consumer_name, consumer_config)
             .await
             .map_err(|e| PortError::ConnectionFailed(format!("Failed to create consumer: {}", e)))?;
 
-        tracing::info!("Created subscription for subject: {}", subject);
+        // An existing consumer is returned as-is, so make sure it is ours
+        let bound_subject = &consumer.cached_info().config.filter_subject;
+        if bound_subject != subject {
+            return Err(PortError::ConnectionFailed(format!(
+                "Consumer {} is bound to {}, not {}",
+                consumer_name, bound_subject, subject
+            )));
+        }
 
-        // Return a subscription handle
-        // Note: The actual message iteration would be done through the consumer
-        Ok(crate::domain::ports::EventSubscription::new())
+        if let Ok(mut consumers) = self.consumers.lock() {
+            consumers.insert(subject.to_string(), consumer.clone());
+        }
+        Ok(consumer)
     }
-}
-
-/// Event subscriber for streaming events
-pub struct NatsEventSubscriber {
-    consumer: PullConsumer,
-}
 
-impl NatsEventSubscriber {
-    /// Create a new subscriber from a NATS consumer
-    pub fn new(consumer: PullConsumer) -> Self {
-        Self { consumer }
+    /// Wait until the client is connected and the stream handle refreshed
+    ///
+    /// Returns at once while connected. After a disconnect, waits up to
+    /// `ReconnectPolicy::append_wait` for the health watch to report the
+    /// connection restored.
+    async fn wait_until_ready(&self) -> Result<(), PortError> {
+        wait_until_ready(self.ready.clone(), self.config.reconnect.append_wait).await
     }
 
-    /// Get the next event from the subscription
-    pub async fn next(&mut self) -> Option<Result<(NetworkEvent, NatsEventAck), PortError>> {
-        let mut messages = match self.consumer.messages().await {
-            Ok(m) => m,
-            Err(e) => return Some(Err(PortError::VendorError(format!("Failed to get messages: {}", e)))),
-        };
+    /// Get the underlying NATS client
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
 
-        match messages.next().await {
-            Some(Ok(msg)) => {
-                match serde_json::from_slice::<NetworkEvent>(&msg.payload) {
-                    Ok(event) => Some(Ok((event, NatsEventAck { message: msg }))),
-                    Err(e) => Some(Err(PortError::VendorError(format!("Deserialization failed: {}", e)))),
-                }
-            }
-            Some(Err(e)) => Some(Err(PortError::VendorError(format!("Message error: {}", e)))),
-            None => None,
-        }
+    /// Get the JetStream context
+    pub fn jetstream(&self) -> &Context {
+        &self.jetstream
     }
 }
 
-/// Acknowledgment handle for a received event
-pub struct NatsEventAck {
-    message: async_nats::jetstream::message::Message,
+impl Drop for NatsEventStore {
+    fn drop(&mut self) {
+        self.health_watch.abort();
+    }
 }
 
-impl NatsEventAck {
-    /// Acknowledge successful processing of the event
-    pub async fn ack(self) -> Result<(), PortError> {
-        self.message.ack()
-            .await
-            .map_err(|e| PortError::VendorError(format!("Ack failed: {}", e)))
+#[async_trait]
+impl EventStorePort for NatsEventStore {
+    async fn append(&self, events: Vec<NetworkEvent>) -> Result<(), PortError> {
+        self.append_envelopes(events.into_iter().map(EventEnvelope::new).collect()).await
     }
 
-    /// Negative acknowledgment - request redelivery
-    pub async fn nak(self) -> Result<(), PortError> {
-        self.message.ack_with(async_nats::jetstream::AckKind::Nak(None))
-            .await
-            .map_err(|e| PortError::VendorError(format!("Nak failed: {}", e)))
-    }
+    async fn append_envelopes(&self, envelopes: Vec<EventEnvelope>) -> Result<(), PortError> {
+        if envelopes.is_empty() {
+            return Ok(());
+        }
 
-    /// Get the correlation ID from the message headers
-    pub fn correlation_id(&self) -> Option<String> {
-        self.message.headers
-            .as_ref()
-            .and_then(|h| h.get("CIM-Correlation-Id"))
-            .map(|v| v.to_string())
-    }
-}
+        self.wait_until_ready().await?;
 
-#[cfg(test)]
-mod tests {
+        // Only used for envelopes that arrive without a correlation id
+        let correlation_id = default_correlation_id();
+
+        tracing::info!(
+            "Appending {} events with default correlation_id {}",
+            envelopes.len(),
+            correlation_id
+        );
+
+        self.publish_batch(&envelopes, &correlation_id, 0).await
+    }
+
+    async fn append_expected(
+        &self,
+        aggregate_id: &str,
+        expected_version: u64,
+        events: Vec<NetworkEvent>,
+    ) -> Result<(), PortError> {
+        let envelopes: Vec<EventEnvelope> = events.into_iter().map(EventEnvelope::new).collect();
+        let Some((first, rest)) = envelopes.split_first() else {
+            return Ok(());
+        };
+        self.wait_until_ready().await?;
+
+        let (actual, last_sequence) = self.aggregate_position(aggregate_id).await?;
+        if actual != expected_version {
+            return Err(AggregateError::ConcurrencyConflict { expected: expected_version, actual }.into());
+        }
+
+        let correlation_id = default_correlation_id();
+
+        // The server rejects the first event if anything was appended to this
+        // aggregate since we read its position; later events in the batch
+        // then land behind it and need no check of their own.
+        let mut headers = Self::create_headers(first, &correlation_id, self.clock.now());
+        headers.insert(
+            "Nats-Expected-Last-Subject-Sequence",
+            HeaderValue::from(last_sequence.to_string().as_str()),
+        );
+        headers.insert(
+            "Nats-Expected-Last-Subject-Sequence-Subject",
+            HeaderValue::from(self.aggregate_filter_subject(aggregate_id).as_str()),
+        );
+
+        if let Err(e) = self.send_event(&first.event, headers, Self::encode_event(&first.event)?).await {
+            if e.kind() == PublishErrorKind::WrongLastSequence {
+                let (actual, _) = self.aggregate_position(aggregate_id).await?;
+                return Err(AggregateError::ConcurrencyConflict { expected: expected_version, actual }.into());
+            }
+            return Err(publish_error("Publish".to_string(), e));
+        }
+
+        self.publish_batch(rest, &correlation_id, 1).await
+    }
+
+    async fn load_events(&self, aggregate_id: &str) -> Result<Vec<NetworkEvent>, PortError> {
+        let events = collect_events(self.load_events_stream(aggregate_id).await?).await?;
+
+        tracing::debug!(
+            "Loaded {} events for aggregate {}",
+            events.len(),
+            aggregate_id
+        );
+
+        Ok(events)
+    }
+
+    async fn load_events_stream(&self, aggregate_id: &str) -> Result<EventStream, PortError> {
+        self.replay_aggregate(aggregate_id, decode_replay_message).await
+    }
+
+    async fn load_timestamped_events(&self, aggregate_id: &str) -> Result<Vec<TimestampedEvent>, PortError> {
+        collect_events(self.replay_aggregate(aggregate_id, decode_timestamped_message).await?).await
+    }
+
+    async fn load_recorded_events(&self, aggregate_id: &str) -> Result<Vec<RecordedEvent>, PortError> {
+        let mut events = collect_events(self.replay_aggregate(aggregate_id, decode_recorded_message).await?).await?;
+        // Legacy and partitioned subjects are replayed one after the other
+        events.sort_by_key(|recorded| recorded.sequence);
+        Ok(events)
+    }
+
+    async fn load_events_after(&self, aggregate_id: &str, version: u64) -> Result<Vec<NetworkEvent>, PortError> {
+        if version == 0 {
+            return self.load_events(aggregate_id).await;
+        }
+
+        // Seek past the snapshot when we know where it ends in the stream
+        if let Some(entry) = self.read_snapshot_entry(aggregate_id).await? {
+            if entry.version == version && entry.sequence > 0 {
+                let consumer_name = format!("replay-{}-{}", aggregate_id, uuid::Uuid::now_v7());
+                let consumer = self
+                    .create_replay_consumer(
+                        &self.aggregate_filter_subject(aggregate_id),
+                        &consumer_name,
+                        DeliverPolicy::ByStartSequence { start_sequence: entry.sequence + 1 },
+                    )
+                    .await?;
+                return Self::drain_replay_consumer(consumer, None).await;
+            }
+        }
+
+        let events = self.load_events(aggregate_id).await?;
+        Ok(events.into_iter().skip(version as usize).collect())
+    }
+
+    async fn save_snapshot(&self, aggregate_id: &str, version: u64, data: Vec<u8>) -> Result<(), PortError> {
+        let entry = SnapshotEntry {
+            version,
+            sequence: self.last_aggregate_sequence(aggregate_id).await?,
+            data,
+        };
+
+        self.snapshots
+            .put(aggregate_id, entry.encode().into())
+            .await
+            .map_err(|e| PortError::VendorError(format!("Failed to save snapshot: {}", e)))?;
+
+        tracing::debug!(
+            "Saved snapshot for aggregate {} at version {} (stream sequence {})",
+            aggregate_id,
+            entry.version,
+            entry.sequence
+        );
+
+        Ok(())
+    }
+
+    async fn load_latest_snapshot(&self, aggregate_id: &str) -> Result<Option<AggregateSnapshot>, PortError> {
+        Ok(self.read_snapshot_entry(aggregate_id).await?.map(|entry| AggregateSnapshot {
+            aggregate_id: aggregate_id.to_string(),
+            version: entry.version,
+            data: entry.data,
+        }))
+    }
+
+    async fn list_aggregate_ids(&self, aggregate_type: &str) -> Result<Vec<String>, PortError> {
+        let mut ids = std::collections::BTreeSet::new();
+
+        // Partitioned subjects carry the aggregate id as their third token,
+        // so the stream's per-subject counts name every aggregate
+        {
+            let stream = self.stream.read().await;
+            let stream = stream
+                .as_ref()
+                .ok_or_else(|| PortError::ConnectionFailed("Stream not initialized".to_string()))?;
+            let mut subjects = stream
+                .info_with_subjects(self.aggregate_type_filter_subject(aggregate_type))
+                .await
+                .map_err(|e| PortError::VendorError(format!("Failed to list subjects: {}", e)))?;
+
+            while let Some(item) = subjects.next().await {
+                let (subject, _count) =
+                    item.map_err(|e| PortError::VendorError(format!("Failed to list subjects: {}", e)))?;
+                if let Some(id) = subject.split('.').nth(2) {
+                    ids.insert(id.to_string());
+                }
+            }
+        }
+
+        // Legacy subjects have no id; it is only in the event itself
+        if self.config.read_legacy_subjects {
+            let consumer_name = format!("list-legacy-{}-{}", aggregate_type, uuid::Uuid::now_v7());
+            let consumer = self
+                .create_replay_consumer(
+                    &format!("{}.{}.*", self.config.subject_prefix, aggregate_type),
+                    &consumer_name,
+                    DeliverPolicy::All,
+                )
+                .await?;
+            ids.extend(
+                Self::drain_replay_consumer(consumer, None)
+                    .await?
+                    .iter()
+                    .filter(|e| e.aggregate_type() == aggregate_type)
+                    .map(|e| e.aggregate_id()),
+            );
+        }
+
+        Ok(ids.into_iter().collect())
+    }
+
+    async fn purge_aggregate(&self, aggregate_id: &str) -> Result<(), PortError> {
+        self.wait_until_ready().await?;
+
+        let mut purged = {
+            let stream = self.stream.read().await;
+            let stream = stream
+                .as_ref()
+                .ok_or_else(|| PortError::ConnectionFailed("Stream not initialized".to_string()))?;
+            stream
+                .purge()
+                .filter(self.aggregate_filter_subject(aggregate_id))
+                .await
+                .map_err(|e| PortError::VendorError(format!("Failed to purge aggregate: {}", e)))?
+                .purged
+        };
+        if self.config.read_legacy_subjects {
+            purged += self.delete_legacy_events(aggregate_id).await?;
+        }
+
+        self.snapshots
+            .purge(aggregate_id)
+            .await
+            .map_err(|e| PortError::VendorError(format!("Failed to purge snapshot: {}", e)))?;
+
+        tracing::info!("Purged {} events of aggregate {}", purged, aggregate_id);
+        Ok(())
+    }
+
+    async fn append_tombstone(&self, event: NetworkEvent) -> Result<(), PortError> {
+        self.wait_until_ready().await?;
+        self.ensure_retention_stream().await?;
+
+        let envelope = EventEnvelope::new(event);
+        let headers = Self::create_headers(&envelope, &default_correlation_id(), self.clock.now());
+        let subject = envelope.event.nats_subject_with_prefix(&self.retention_subject_prefix(), &envelope.event.aggregate_id());
+
+        self.jetstream
+            .publish_with_headers(subject.clone(), headers, Self::encode_event(&envelope.event)?.into())
+            .await
+            .map_err(|e| publish_error("Tombstone publish".to_string(), e))?
+            .await
+            .map_err(|e| publish_error("Tombstone publish".to_string(), e))?;
+
+        tracing::info!("Recorded tombstone {} on {}", envelope.event.event_type(), subject);
+        Ok(())
+    }
+
+    async fn fold_all(&self, projection: &mut dyn Projection) -> Result<(), PortError> {
+        let consumer_name = format!("fold-{}", uuid::Uuid::now_v7());
+        let consumer = self
+            .create_replay_consumer(&self.fold_filter_subject(), &consumer_name, DeliverPolicy::All)
+            .await?;
+        let mut events = Self::replay_stream(consumer, None, decode_replay_message).await?;
+
+        let mut folded = 0usize;
+        while let Some(item) = events.next().await {
+            match item {
+                Ok(event) => {
+                    projection.apply(&event);
+                    folded += 1;
+                }
+                Err(PortError::Deserialization(e)) => tracing::warn!("Skipping undecodable event: {}", e),
+                Err(e) => return Err(e),
+            }
+        }
+
+        tracing::debug!("Folded {} events into projection", folded);
+        Ok(())
+    }
+
+    async fn subscribe_stream(&self, subject: &str) -> Result<EventStream, PortError> {
+        // A plain core subscription: JetStream publishes reach it as they are
+        // stored, and no consumer is left behind when the stream is dropped
+        let subscriber = self.client
+            .subscribe(subject.to_string())
+            .await
+            .map_err(|e| PortError::ConnectionFailed(format!("Failed to subscribe to {}: {}", subject, e)))?;
+
+        tracing::debug!("Streaming live events on {}", subject);
+        Ok(Box::pin(subscriber.filter_map(|msg| async move { decode_replay_message(&msg, None) })))
+    }
+
+    async fn subscribe(&self, subject: &str) -> Result<crate::domain::ports::EventSubscription, PortError> {
+        let consumer = self.subscription_consumer(subject).await?;
+        let consumer_name = consumer.cached_info().name.clone();
+
+        tracing::info!("Created subscription {} for subject: {}", consumer_name, subject);
+
+        // The id names the durable consumer, which `subscribe_events` binds to
+        Ok(crate::domain::ports::EventSubscription::with_id(consumer_name, subject))
+    }
+}
+
+/// Ephemeral consumer created for a single replay
+///
+/// `delete` removes it from the server as soon as the replay is done. One
+/// dropped before that, e.g. by abandoning a replay stream part way, is
+/// deleted in the background instead of lingering until it expires.
+struct ReplayConsumer {
+    consumer: PullConsumer,
+    stream: Stream,
+    deleted: bool,
+}
+
+impl ReplayConsumer {
+    /// Messages pending when the consumer was created
+    fn pending(&self) -> u64 {
+        self.consumer.cached_info().num_pending
+    }
+
+    /// Delete the consumer from the server
+    async fn delete(mut self) {
+        self.deleted = true;
+        let name = &self.consumer.cached_info().name;
+        if let Err(e) = self.stream.delete_consumer(name).await {
+            tracing::warn!("Failed to delete replay consumer {}: {}", name, e);
+        }
+    }
+}
+
+impl Drop for ReplayConsumer {
+    fn drop(&mut self) {
+        if self.deleted {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let stream = self.stream.clone();
+        let name = self.consumer.cached_info().name.clone();
+        runtime.spawn(async move {
+            if let Err(e) = stream.delete_consumer(&name).await {
+                tracing::warn!("Failed to delete replay consumer {}: {}", name, e);
+            }
+        });
+    }
+}
+
+/// Snapshot bucket entry: `version (u64 BE) | stream sequence (u64 BE) | data`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SnapshotEntry {
+    version: u64,
+    sequence: u64,
+    data: Vec<u8>,
+}
+
+impl SnapshotEntry {
+    const HEADER_LEN: usize = 16;
+
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::HEADER_LEN + self.data.len());
+        bytes.extend_from_slice(&self.version.to_be_bytes());
+        bytes.extend_from_slice(&self.sequence.to_be_bytes());
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < Self::HEADER_LEN {
+            return None;
+        }
+        Some(Self {
+            version: u64::from_be_bytes(bytes[0..8].try_into().ok()?),
+            sequence: u64::from_be_bytes(bytes[8..16].try_into().ok()?),
+            data: bytes[Self::HEADER_LEN..].to_vec(),
+        })
+    }
+}
+
+/// Event subscriber for streaming events
+pub struct NatsEventSubscriber {
+    consumer: PullConsumer,
+    /// Message stream, opened on the first `next` and kept across calls
+    messages: Option<jetstream::consumer::pull::Stream>,
+}
+
+impl NatsEventSubscriber {
+    /// Create a new subscriber from a NATS consumer
+    pub fn new(consumer: PullConsumer) -> Self {
+        Self { consumer, messages: None }
+    }
+
+    /// Name of the durable consumer backing this subscriber
+    pub fn consumer_name(&self) -> &str {
+        &self.consumer.cached_info().name
+    }
+
+    /// Get the next event from the subscription
+    pub async fn next(&mut self) -> Option<Result<(NetworkEvent, NatsEventAck), PortError>> {
+        if self.messages.is_none() {
+            match self.consumer.messages().await {
+                Ok(messages) => self.messages = Some(messages),
+                Err(e) => return Some(Err(PortError::VendorError(format!("Failed to get messages: {}", e)))),
+            }
+        }
+        let messages = self.messages.as_mut()?;
+
+        match messages.next().await {
+            Some(Ok(msg)) => {
+                match NetworkEvent::decode(&msg.payload) {
+                    Ok(event) => Some(Ok((event, NatsEventAck { message: msg }))),
+                    Err(e) => Some(Err(PortError::Deserialization(e.to_string()))),
+                }
+            }
+            Some(Err(e)) => Some(Err(PortError::VendorError(format!("Message error: {}", e)))),
+            None => None,
+        }
+    }
+}
+
+#[async_trait]
+impl EventSource for NatsEventSubscriber {
+    type Ack = NatsEventAck;
+
+    async fn next(&mut self) -> Option<Result<(NetworkEvent, NatsEventAck), PortError>> {
+        NatsEventSubscriber::next(self).await
+    }
+}
+
+/// Acknowledgment handle for a received event
+pub struct NatsEventAck {
+    message: async_nats::jetstream::message::Message,
+}
+
+impl NatsEventAck {
+    /// Acknowledge successful processing of the event
+    pub async fn ack(self) -> Result<(), PortError> {
+        self.message.ack()
+            .await
+            .map_err(|e| PortError::VendorError(format!("Ack failed: {}", e)))
+    }
+
+    /// Negative acknowledgment - request redelivery
+    pub async fn nak(self) -> Result<(), PortError> {
+        self.message.ack_with(async_nats::jetstream::AckKind::Nak(None))
+            .await
+            .map_err(|e| PortError::VendorError(format!("Nak failed: {}", e)))
+    }
+
+    /// Get the correlation ID from the message headers
+    pub fn correlation_id(&self) -> Option<String> {
+        self.header("CIM-Correlation-Id")
+    }
+
+    /// Root span for handling this event
+    ///
+    /// Opens the event's correlation again, so work done in response joins
+    /// the trace of the flow that produced it. Events without a correlation
+    /// id get a span of their own.
+    pub fn span(&self) -> tracing::Span {
+        match self.correlation_id() {
+            Some(correlation_id) => telemetry::correlation_span(&correlation_id),
+            None => tracing::info_span!(parent: None, "cim.event", event_id = self.event_id()),
+        }
+    }
+
+    /// Get the causation ID from the message headers
+    pub fn causation_id(&self) -> Option<String> {
+        self.header("CIM-Causation-Id")
+    }
+
+    /// Get the event ID from the message headers
+    pub fn event_id(&self) -> Option<String> {
+        self.header("Nats-Msg-Id")
+    }
+
+    /// Get the `CIM-Timestamp` header, as RFC3339
+    pub fn timestamp(&self) -> Option<String> {
+        self.header("CIM-Timestamp")
+    }
+
+    fn header(&self, name: &str) -> Option<String> {
+        self.message.headers
+            .as_ref()
+            .and_then(|h| h.get(name))
+            .map(|v| v.to_string())
+    }
+}
+
+#[async_trait]
+impl EventAck for NatsEventAck {
+    async fn ack(self) -> Result<(), PortError> {
+        NatsEventAck::ack(self).await
+    }
+
+    async fn nak(self) -> Result<(), PortError> {
+        NatsEventAck::nak(self).await
+    }
+}
+
+/// Follow the client's connection events, refreshing the stream after a reconnect
+///
+/// `ready` goes false on a disconnect and back to true once the client has
+/// reconnected and `refresh` succeeded. A failed refresh is retried with the
+/// reconnect backoff. Ends when the client, and with it the event sender, is
+/// dropped.
+async fn watch_connection<F, Fut>(
+    mut events: mpsc::UnboundedReceiver<Event>,
+    ready: watch::Sender<bool>,
+    policy: ReconnectPolicy,
+    refresh: F,
+) where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<(), PortError>>,
+{
+    while let Some(event) = events.recv().await {
+        match event {
+            Event::Disconnected => {
+                tracing::warn!("Lost connection to NATS; appends wait for reconnection");
+                ready.send_replace(false);
+            }
+            // The initial connect needs no refresh: the stream was just ensured
+            Event::Connected if !*ready.borrow() => {
+                let mut attempt = 0;
+                while let Err(e) = refresh().await {
+                    attempt += 1;
+                    let delay = policy.delay(attempt);
+                    tracing::warn!("Reconnected to NATS but refreshing the stream failed ({}); retrying in {:?}", e, delay);
+                    tokio::time::sleep(delay).await;
+                }
+                tracing::info!("Reconnected to NATS; stream handle refreshed");
+                ready.send_replace(true);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Wait up to `max_wait` for `ready` to become true
+async fn wait_until_ready(mut ready: watch::Receiver<bool>, max_wait: Duration) -> Result<(), PortError> {
+    if *ready.borrow() {
+        return Ok(());
+    }
+    tracing::info!("Waiting up to {:?} for NATS to reconnect", max_wait);
+    match tokio::time::timeout(max_wait, ready.wait_for(|ready| *ready)).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(_)) => Err(PortError::ConnectionFailed("NATS connection watch stopped".to_string())),
+        Err(_) => Err(PortError::Timeout(format!("NATS did not reconnect within {:?}", max_wait))),
+    }
+}
+
+/// Correlation id for envelopes appended without one
+///
+/// Joins the flow running on the current task, if any, so events recorded
+/// by one operation share its correlation.
+fn default_correlation_id() -> String {
+    telemetry::current_correlation_id().unwrap_or_else(|| uuid::Uuid::now_v7().to_string())
+}
+
+/// Durable consumer name for a subscription subject
+///
+/// Consumer names cannot contain `.`, `*` or `>`, so those are replaced for
+/// readability, and a hash of the original subject is appended so subjects
+/// that sanitize alike (`a.b` and `a-b`) still get distinct consumers.
+fn durable_consumer_name(subject: &str) -> String {
+    // FNV-1a: stable across builds, unlike `DefaultHasher`, so a restarted
+    // process rebinds to the same consumer
+    let hash = subject.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    let readable = subject.replace('.', "-").replace('*', "all").replace('>', "gt");
+    format!("sub-{}-{:016x}", readable, hash)
+}
+
+/// Decodes one replayed message, given the aggregate to filter on
+type ReplayDecoder<T> = fn(&async_nats::Message, Option<&str>) -> Option<Result<T, PortError>>;
+
+/// Decode a replayed message, or `None` if it belongs to another aggregate
+///
+/// `aggregate_id` filters on the `CIM-Aggregate-Id` header; a payload that
+/// fails to deserialize is returned as a `PortError::Deserialization` item.
+fn decode_replay_message(
+    msg: &async_nats::Message,
+    aggregate_id: Option<&str>,
+) -> Option<Result<NetworkEvent, PortError>> {
+    if let Some(expected) = aggregate_id {
+        let matches_aggregate = msg.headers
+            .as_ref()
+            .and_then(|h| h.get("CIM-Aggregate-Id"))
+            .map(|v| v.as_str() == expected)
+            .unwrap_or(false);
+
+        if !matches_aggregate {
+            return None;
+        }
+    }
+
+    Some(NetworkEvent::decode(&msg.payload).map_err(|e| {
+        PortError::Deserialization(format!("{} on {}", e, msg.subject))
+    }))
+}
+
+/// A stored message copied verbatim, for rewriting it elsewhere
+struct CapturedMessage {
+    sequence: u64,
+    subject: String,
+    headers: HeaderMap,
+    payload: Vec<u8>,
+}
+
+impl CapturedMessage {
+    fn header(&self, name: &str) -> Option<String> {
+        self.headers.get(name).map(|v| v.to_string())
+    }
+}
+
+/// Capture a replayed message as stored, whatever its payload
+fn capture_message(
+    msg: &async_nats::Message,
+    _aggregate_id: Option<&str>,
+) -> Option<Result<CapturedMessage, PortError>> {
+    let Some(sequence) = msg.reply.as_deref().and_then(ack_stream_sequence) else {
+        return Some(Err(PortError::VendorError(format!("missing stream sequence on {}", msg.subject))));
+    };
+    Some(Ok(CapturedMessage {
+        sequence,
+        subject: msg.subject.to_string(),
+        headers: msg.headers.clone().unwrap_or_default(),
+        payload: msg.payload.to_vec(),
+    }))
+}
+
+/// Partitioned subject for a message of `aggregate_id`
+///
+/// A legacy `{prefix}.{type}.{event}` subject gets the id inserted; any
+/// other subject is already partitioned and kept.
+fn partitioned_subject(prefix: &str, subject: &str, aggregate_id: &str) -> String {
+    let tokens: Vec<&str> = subject
+        .strip_prefix(prefix)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .map(|rest| rest.split('.').collect())
+        .unwrap_or_default();
+    match tokens.as_slice() {
+        [aggregate_type, event_type] => format!("{}.{}.{}.{}", prefix, aggregate_type, aggregate_id, event_type),
+        _ => subject.to_string(),
+    }
+}
+
+/// Decode a replayed message along with its `CIM-Timestamp` header
+///
+/// A missing or malformed timestamp is reported like an undecodable payload.
+fn decode_timestamped_message(
+    msg: &async_nats::Message,
+    aggregate_id: Option<&str>,
+) -> Option<Result<TimestampedEvent, PortError>> {
+    let event = match decode_replay_message(msg, aggregate_id)? {
+        Ok(event) => event,
+        Err(e) => return Some(Err(e)),
+    };
+
+    let recorded_at = msg.headers
+        .as_ref()
+        .and_then(|h| h.get("CIM-Timestamp"))
+        .and_then(|v| chrono::DateTime::parse_from_rfc3339(v.as_str()).ok())
+        .map(|at| at.with_timezone(&chrono::Utc));
+
+    Some(match recorded_at {
+        Some(recorded_at) => Ok(TimestampedEvent { recorded_at, event }),
+        None => Err(PortError::Deserialization(format!("missing or invalid CIM-Timestamp on {}", msg.subject))),
+    })
+}
+
+/// Decode a replayed message along with its sequence and metadata headers
+///
+/// The stream sequence comes from the JetStream ack subject the message was
+/// delivered with; a message without one is reported like an undecodable
+/// payload, as is a missing timestamp.
+fn decode_recorded_message(
+    msg: &async_nats::Message,
+    aggregate_id: Option<&str>,
+) -> Option<Result<RecordedEvent, PortError>> {
+    let TimestampedEvent { recorded_at, event } = match decode_timestamped_message(msg, aggregate_id)? {
+        Ok(timestamped) => timestamped,
+        Err(e) => return Some(Err(e)),
+    };
+
+    let Some(sequence) = msg.reply.as_deref().and_then(ack_stream_sequence) else {
+        return Some(Err(PortError::Deserialization(format!("missing stream sequence on {}", msg.subject))));
+    };
+    let header = |name: &str| msg.headers.as_ref().and_then(|h| h.get(name)).map(|v| v.to_string());
+
+    Some(Ok(RecordedEvent {
+        sequence,
+        recorded_at,
+        event_id: header("Nats-Msg-Id"),
+        correlation_id: header("CIM-Correlation-Id"),
+        causation_id: header("CIM-Causation-Id"),
+        event,
+    }))
+}
+
+/// Stream sequence from a JetStream ack subject
+///
+/// Handles both `$JS.ACK.<stream>.<consumer>.<delivered>.<stream seq>...`
+/// and the newer form with a domain and account hash after `$JS.ACK`.
+fn ack_stream_sequence(reply: &str) -> Option<u64> {
+    let tokens: Vec<&str> = reply.strip_prefix("$JS.ACK.")?.split('.').collect();
+    let index = match tokens.len() {
+        7 => 3,
+        n if n >= 9 => 5,
+        _ => return None,
+    };
+    tokens[index].parse().ok()
+}
+
+/// Collect an event stream, skipping undecodable events
+async fn collect_events<T>(mut stream: BoxStream<'static, Result<T, PortError>>) -> Result<Vec<T>, PortError> {
+    let mut events = Vec::new();
+    while let Some(item) = stream.next().await {
+        match item {
+            Ok(event) => events.push(event),
+            Err(PortError::Deserialization(e)) => tracing::warn!("Skipping undecodable event: {}", e),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(events)
+}
+
+/// Await publish acks concurrently
+///
+/// Fails with the index (offset by `first_index`) of the first event whose
+/// ack reported an error.
+async fn await_acks<A, T, E>(acks: Vec<A>, first_index: usize) -> Result<(), PortError>
+where
+    A: std::future::IntoFuture<Output = Result<T, E>>,
+    E: PublishFailure,
+{
+    futures::future::try_join_all(acks.into_iter().enumerate().map(|(offset, ack)| async move {
+        ack.await.map_err(|e| publish_error(format!("Ack for event {}", first_index + offset), e))
+    }))
+    .await?;
+
+    Ok(())
+}
+
+/// A failed publish or ack
+trait PublishFailure: std::fmt::Display {
+    /// What went wrong, when the client says
+    fn publish_kind(&self) -> Option<PublishErrorKind>;
+}
+
+impl PublishFailure for PublishError {
+    fn publish_kind(&self) -> Option<PublishErrorKind> {
+        Some(self.kind())
+    }
+}
+
+/// Classify a failed publish or ack so callers can tell what is worth retrying
+fn publish_error(context: String, e: impl PublishFailure) -> PortError {
+    let message = format!("{} failed: {}", context, e);
+    match e.publish_kind() {
+        Some(PublishErrorKind::TimedOut) => PortError::Timeout(message),
+        Some(PublishErrorKind::BrokenPipe) => PortError::Transient(message),
+        Some(PublishErrorKind::StreamNotFound) => PortError::NotFound(message),
+        _ => PortError::VendorError(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
     use super::*;
+    use std::time::Duration;
 
     #[test]
     fn test_config_default() {
         let config = NatsEventStoreConfig::default();
         assert_eq!(config.stream_name, "network-events");
         assert_eq!(config.nats_url, "nats://localhost:4222");
+        assert!(!config.read_legacy_subjects);
+        assert_eq!(config.snapshot_bucket, "network-snapshots");
+    }
+
+    #[test]
+    fn test_partitioned_subject_inserts_aggregate_id() {
+        assert_eq!(
+            partitioned_subject("network", "network.device.DeviceDiscovered", "d1"),
+            "network.device.d1.DeviceDiscovered"
+        );
+        assert_eq!(
+            partitioned_subject("network", "network.device.d1.DeviceDiscovered", "d1"),
+            "network.device.d1.DeviceDiscovered"
+        );
+    }
+
+    #[test]
+    fn test_headers_preserve_causal_chain() {
+        let device_id = crate::domain::value_objects::DeviceId::new();
+        let cause = EventEnvelope::new(NetworkEvent::DeviceReappeared { device_id })
+            .with_correlation_id("operation-1");
+        let effect = EventEnvelope::new(NetworkEvent::DeviceConfiguring { device_id }).caused_by(&cause);
+
+        let now = chrono::Utc::now();
+        let cause_headers = NatsEventStore::create_headers(&cause, "batch-default", now);
+        let effect_headers = NatsEventStore::create_headers(&effect, "batch-default", now);
+        let header = |headers: &HeaderMap, name: &str| headers.get(name).map(|v| v.to_string());
+
+        assert_eq!(header(&cause_headers, "Nats-Msg-Id"), Some(cause.event_id.clone()));
+        assert_eq!(header(&cause_headers, "CIM-Correlation-Id").as_deref(), Some("operation-1"));
+        assert_eq!(header(&cause_headers, "CIM-Causation-Id"), None);
+        assert_eq!(header(&effect_headers, "CIM-Correlation-Id").as_deref(), Some("operation-1"));
+        assert_eq!(header(&effect_headers, "CIM-Causation-Id"), Some(cause.event_id.clone()));
+
+        // Envelopes without a correlation id fall back to the batch's
+        let bare = EventEnvelope::new(NetworkEvent::DeviceReappeared { device_id });
+        let bare_headers = NatsEventStore::create_headers(&bare, "batch-default", now);
+        assert_eq!(header(&bare_headers, "CIM-Correlation-Id").as_deref(), Some("batch-default"));
+    }
+
+    #[test]
+    fn test_headers_take_the_given_timestamp() {
+        let clock = crate::domain::clock::MockClock::new(
+            chrono::DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z").unwrap().with_timezone(&chrono::Utc),
+        );
+        let device_id = crate::domain::value_objects::DeviceId::new();
+        let envelope = EventEnvelope::new(NetworkEvent::DeviceReappeared { device_id });
+
+        let headers = NatsEventStore::create_headers(&envelope, "batch-default", clock.now());
+
+        assert_eq!(headers.get("CIM-Timestamp").map(|v| v.to_string()).as_deref(), Some("2024-05-01T12:00:00+00:00"));
+    }
+
+    #[test]
+    fn test_durable_consumer_names_do_not_collide() {
+        let subjects = ["network.device.>", "network-device->", "network.*", "network.all", "a.b", "a-b"];
+        let names: std::collections::HashSet<_> = subjects.iter().map(|s| durable_consumer_name(s)).collect();
+        assert_eq!(names.len(), subjects.len());
+
+        // Stable, so a restarted process rebinds to the same consumer
+        assert_eq!(durable_consumer_name("network.device.>"), durable_consumer_name("network.device.>"));
+        assert!(durable_consumer_name("network.device.>").starts_with("sub-network-device-gt-"));
+        assert!(!durable_consumer_name("network.*.>").contains(['.', '*', '>']));
+    }
+
+    #[test]
+    fn test_snapshot_entry_roundtrip() {
+        let entry = SnapshotEntry {
+            version: 42,
+            sequence: 1337,
+            data: vec![1, b'{', b'}'],
+        };
+
+        let decoded = SnapshotEntry::decode(&entry.encode()).unwrap();
+        assert_eq!(decoded, entry);
+    }
+
+    #[test]
+    fn test_snapshot_entry_rejects_truncated() {
+        assert!(SnapshotEntry::decode(&[0u8; 15]).is_none());
+    }
+
+    fn replay_message(aggregate_id: &str, payload: &[u8]) -> async_nats::Message {
+        let mut headers = HeaderMap::new();
+        headers.insert("CIM-Aggregate-Id", aggregate_id);
+        async_nats::Message {
+            subject: "network.device.DeviceRenamed".into(),
+            reply: None,
+            payload: payload.to_vec().into(),
+            headers: Some(headers),
+            status: None,
+            description: None,
+            length: payload.len(),
+        }
+    }
+
+    #[test]
+    fn test_decode_replay_message_surfaces_bad_payload() {
+        let msg = replay_message("dev-1", b"not json");
+
+        let decoded = decode_replay_message(&msg, None);
+
+        assert!(matches!(decoded, Some(Err(PortError::Deserialization(_)))));
+    }
+
+    #[test]
+    fn test_decode_replay_message_filters_other_aggregates() {
+        let msg = replay_message("dev-2", b"not json");
+
+        assert!(decode_replay_message(&msg, Some("dev-1")).is_none());
+        assert!(decode_replay_message(&msg, Some("dev-2")).is_some());
+    }
+
+    #[test]
+    fn test_decode_timestamped_message_reads_header() {
+        let event = NetworkEvent::DeviceRenamed {
+            device_id: crate::domain::DeviceId::new(),
+            old_name: "a".to_string(),
+            new_name: "b".to_string(),
+        };
+        let mut msg = replay_message("dev-1", &event.encode().unwrap());
+
+        // Without the header the event cannot be placed in time
+        let decoded = decode_timestamped_message(&msg, None);
+        assert!(matches!(decoded, Some(Err(PortError::Deserialization(_)))));
+
+        msg.headers.as_mut().unwrap().insert("CIM-Timestamp", "2024-05-01T12:00:00+02:00");
+        let decoded = decode_timestamped_message(&msg, None).unwrap().unwrap();
+        assert_eq!(decoded.recorded_at.to_rfc3339(), "2024-05-01T10:00:00+00:00");
+        assert_eq!(decoded.event.event_type(), "DeviceRenamed");
+    }
+
+    #[test]
+    fn test_decode_recorded_message_reads_sequence_and_ids() {
+        let event = NetworkEvent::DeviceRenamed {
+            device_id: crate::domain::DeviceId::new(),
+            old_name: "a".to_string(),
+            new_name: "b".to_string(),
+        };
+        let mut msg = replay_message("dev-1", &event.encode().unwrap());
+        let headers = msg.headers.as_mut().unwrap();
+        headers.insert("CIM-Timestamp", "2024-05-01T12:00:00Z");
+        headers.insert("Nats-Msg-Id", "evt-3");
+        headers.insert("CIM-Correlation-Id", "run-1");
+        headers.insert("CIM-Causation-Id", "evt-2");
+
+        // Only a message delivered by a consumer knows its stream sequence
+        assert!(matches!(decode_recorded_message(&msg, None), Some(Err(PortError::Deserialization(_)))));
+
+        msg.reply = Some("$JS.ACK.NETWORK_EVENTS.replay-dev-1.1.42.3.1714564800000000000.0".into());
+        let recorded = decode_recorded_message(&msg, None).unwrap().unwrap();
+        assert_eq!(recorded.sequence, 42);
+        assert_eq!(recorded.event_id.as_deref(), Some("evt-3"));
+        assert_eq!(recorded.correlation_id.as_deref(), Some("run-1"));
+        assert_eq!(recorded.causation_id.as_deref(), Some("evt-2"));
+    }
+
+    #[test]
+    fn test_ack_stream_sequence_formats() {
+        assert_eq!(ack_stream_sequence("$JS.ACK.EVENTS.c1.1.42.3.1714564800000000000.0"), Some(42));
+        assert_eq!(ack_stream_sequence("$JS.ACK.hub.ACCHASH.EVENTS.c1.1.42.3.1714564800000000000.0.x7"), Some(42));
+        assert_eq!(ack_stream_sequence("_INBOX.abc"), None);
+        assert_eq!(ack_stream_sequence("$JS.ACK.EVENTS.c1.1"), None);
+    }
+
+    impl PublishFailure for &'static str {
+        fn publish_kind(&self) -> Option<PublishErrorKind> {
+            None
+        }
+    }
+
+    /// Simulated ack that resolves after a fixed round-trip
+    async fn delayed_ack(delay: Duration, fail: bool) -> Result<(), &'static str> {
+        tokio::time::sleep(delay).await;
+        if fail { Err("stream unavailable") } else { Ok(()) }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_await_acks_concurrently() {
+        let round_trip = Duration::from_millis(50);
+        let acks: Vec<_> = (0..10).map(|_| delayed_ack(round_trip, false)).collect();
+
+        let started = tokio::time::Instant::now();
+        await_acks(acks, 0).await.unwrap();
+
+        // Paused time only advances to the next timer; serial acks would take 10 round-trips
+        assert_eq!(started.elapsed(), round_trip, "acks were awaited sequentially");
+    }
+
+    #[tokio::test]
+    async fn test_await_acks_reports_failed_index() {
+        let acks: Vec<_> = (0..5)
+            .map(|i| delayed_ack(Duration::from_millis(1), i == 3))
+            .collect();
+
+        let result = await_acks(acks, 1).await;
+
+        assert!(matches!(
+            result,
+            Err(PortError::VendorError(msg)) if msg.contains("event 4") && msg.contains("stream unavailable")
+        ));
+    }
+
+    #[test]
+    fn test_reconnect_backoff_doubles_up_to_max_delay() {
+        let policy = ReconnectPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+            ..Default::default()
+        };
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+        assert_eq!(policy.delay(4), Duration::from_millis(500));
+        assert_eq!(policy.delay(usize::MAX), Duration::from_millis(500));
+    }
+
+    /// A health watch driven by hand, counting stream refreshes
+    ///
+    /// The first `failing_refreshes` refreshes fail.
+    fn scripted_watch(
+        failing_refreshes: usize,
+    ) -> (mpsc::UnboundedSender<Event>, watch::Receiver<bool>, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        let (ready_tx, ready) = watch::channel(true);
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let policy = ReconnectPolicy { base_delay: Duration::from_millis(1), ..Default::default() };
+
+        let counter = refreshes.clone();
+        tokio::spawn(watch_connection(events_rx, ready_tx, policy, move || {
+            let attempt = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if attempt < failing_refreshes {
+                    Err(PortError::ConnectionFailed("stream not yet available".to_string()))
+                } else {
+                    Ok(())
+                }
+            }
+        }));
+        (events_tx, ready, refreshes)
+    }
+
+    #[tokio::test]
+    async fn test_append_waits_for_reconnect_and_stream_refresh() {
+        use std::sync::atomic::Ordering;
+
+        let (events, ready, refreshes) = scripted_watch(2);
+        events.send(Event::Connected).unwrap();
+        events.send(Event::Disconnected).unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!*ready.borrow());
+
+        let waiting = tokio::spawn(wait_until_ready(ready.clone(), Duration::from_secs(5)));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!waiting.is_finished(), "append went ahead while disconnected");
+
+        events.send(Event::Connected).unwrap();
+        waiting.await.unwrap().expect("append should proceed after reconnecting");
+
+        // The initial connect needs no refresh; the reconnect retries until one succeeds
+        assert_eq!(refreshes.load(Ordering::SeqCst), 3);
+        assert!(*ready.borrow());
+    }
+
+    #[tokio::test]
+    async fn test_append_wait_is_bounded() {
+        let (events, ready, _) = scripted_watch(0);
+        events.send(Event::Disconnected).unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let result = wait_until_ready(ready, Duration::from_millis(20)).await;
+
+        assert!(matches!(result, Err(PortError::Timeout(_))));
+    }
+
+    #[test]
+    fn test_publish_errors_are_classified() {
+        let timed_out = publish_error("Ack for event 2".to_string(), PublishError::from(PublishErrorKind::TimedOut));
+        let rejected = publish_error("Publish".to_string(), PublishError::from(PublishErrorKind::Other));
+
+        assert!(matches!(timed_out, PortError::Timeout(ref msg) if msg.contains("event 2")));
+        assert!(timed_out.is_transient());
+        assert!(matches!(rejected, PortError::VendorError(_)));
+        assert!(!rejected.is_transient());
     }
 }
@@ -1,9 +1,15 @@
 //! NetBox API HTTP client
 //!
 //! Handles communication with NetBox DCIM/IPAM system.
+//!
+//! The API token comes from a `CredentialSource` consulted per request, so
+//! a rotated token is used as soon as the source returns it.
 
 use super::types::*;
+use crate::adapters::credentials::{CredentialSource, Credentials};
+use crate::adapters::retry::{retry_after, RetryPolicy};
 use reqwest::Client;
+use std::net::IpAddr;
 use std::time::Duration;
 
 /// NetBox API client
@@ -12,8 +18,19 @@ pub struct NetBoxClient {
     http: Client,
     /// Base URL of NetBox instance
     base_url: String,
-    /// API token
-    api_token: String,
+    /// Where the API token comes from
+    credentials: Box<dyn CredentialSource>,
+    /// Retry policy for throttled or transiently failing requests
+    retry: RetryPolicy,
+}
+
+impl std::fmt::Debug for NetBoxClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NetBoxClient")
+            .field("base_url", &self.base_url)
+            .field("credentials", &self.credentials)
+            .finish_non_exhaustive()
+    }
 }
 
 impl NetBoxClient {
@@ -23,6 +40,11 @@ impl NetBoxClient {
     /// * `base_url` - NetBox URL (e.g., "https://netbox.example.com")
     /// * `api_token` - API authentication token
     pub fn new(base_url: &str, api_token: &str) -> Result<Self, NetBoxError> {
+        Self::with_credentials(base_url, Credentials::token(api_token))
+    }
+
+    /// Create a NetBox client that authenticates with the token from `source`
+    pub fn with_credentials(base_url: &str, source: impl CredentialSource + 'static) -> Result<Self, NetBoxError> {
         let http = Client::builder()
             .timeout(Duration::from_secs(30))
             .build()
@@ -31,19 +53,35 @@ impl NetBoxClient {
         Ok(Self {
             http,
             base_url: base_url.trim_end_matches('/').to_string(),
-            api_token: api_token.to_string(),
+            credentials: Box::new(source),
+            retry: RetryPolicy::default(),
         })
     }
 
+    /// Use `retry` instead of the default retry policy
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    // =========================================================================
+    // Status
+    // =========================================================================
+
+    /// Get the NetBox version and worker status
+    pub async fn get_status(&self) -> Result<NetBoxSystemStatus, NetBoxError> {
+        let url = format!("{}/api/status/", self.base_url);
+        self.get(&url).await
+    }
+
     // =========================================================================
     // Device Operations
     // =========================================================================
 
-    /// List all devices
+    /// List all devices, across every page
     pub async fn list_devices(&self) -> Result<Vec<NetBoxDevice>, NetBoxError> {
         let url = format!("{}/api/dcim/devices/", self.base_url);
-        let response: NetBoxResponse<NetBoxDevice> = self.get(&url).await?;
-        Ok(response.results)
+        self.get_all(&url).await
     }
 
     /// Get a device by ID
@@ -59,6 +97,28 @@ impl NetBoxClient {
         Ok(response.results.into_iter().next())
     }
 
+    /// Get a device by custom field value
+    ///
+    /// Results are re-checked against the field, since NetBox versions that
+    /// don't know the field ignore the filter and return every device.
+    pub async fn get_device_by_custom_field(
+        &self,
+        field: &str,
+        value: &str,
+    ) -> Result<Option<NetBoxDevice>, NetBoxError> {
+        let url = format!(
+            "{}/api/dcim/devices/?cf_{}={}",
+            self.base_url,
+            urlencoding::encode(field),
+            urlencoding::encode(value)
+        );
+        let response: NetBoxResponse<NetBoxDevice> = self.get(&url).await?;
+        Ok(response
+            .results
+            .into_iter()
+            .find(|device| device.custom_fields.get(field).and_then(|v| v.as_str()) == Some(value)))
+    }
+
     /// Create a new device
     pub async fn create_device(&self, device: &NetBoxDeviceCreate) -> Result<NetBoxDevice, NetBoxError> {
         let url = format!("{}/api/dcim/devices/", self.base_url);
@@ -77,6 +137,142 @@ impl NetBoxClient {
         self.delete(&url).await
     }
 
+    // =========================================================================
+    // Site Operations
+    // =========================================================================
+
+    /// Get a site by slug
+    pub async fn get_site_by_slug(&self, slug: &str) -> Result<Option<NetBoxNestedObject>, NetBoxError> {
+        let url = format!("{}/api/dcim/sites/?slug={}", self.base_url, urlencoding::encode(slug));
+        let response: NetBoxResponse<NetBoxNestedObject> = self.get(&url).await?;
+        Ok(response.results.into_iter().find(|site| site.slug.as_deref() == Some(slug)))
+    }
+
+    /// Create a site
+    pub async fn create_site(&self, site: &NetBoxSiteCreate) -> Result<NetBoxNestedObject, NetBoxError> {
+        let url = format!("{}/api/dcim/sites/", self.base_url);
+        self.post(&url, site).await
+    }
+
+    // =========================================================================
+    // Virtual Chassis Operations
+    // =========================================================================
+
+    /// Get a virtual chassis by name
+    pub async fn get_virtual_chassis_by_name(&self, name: &str) -> Result<Option<NetBoxNestedObject>, NetBoxError> {
+        let url = format!("{}/api/dcim/virtual-chassis/?name={}", self.base_url, urlencoding::encode(name));
+        let response: NetBoxResponse<NetBoxNestedObject> = self.get(&url).await?;
+        Ok(response.results.into_iter().find(|chassis| chassis.name == name))
+    }
+
+    /// Create a virtual chassis
+    pub async fn create_virtual_chassis(
+        &self,
+        chassis: &NetBoxVirtualChassisCreate,
+    ) -> Result<NetBoxNestedObject, NetBoxError> {
+        let url = format!("{}/api/dcim/virtual-chassis/", self.base_url);
+        self.post(&url, chassis).await
+    }
+
+    /// Update a virtual chassis
+    pub async fn update_virtual_chassis(
+        &self,
+        id: u64,
+        chassis: &serde_json::Value,
+    ) -> Result<NetBoxNestedObject, NetBoxError> {
+        let url = format!("{}/api/dcim/virtual-chassis/{}/", self.base_url, id);
+        self.patch(&url, chassis).await
+    }
+
+    // =========================================================================
+    // Device Type Operations
+    // =========================================================================
+
+    /// Get a manufacturer by slug
+    pub async fn get_manufacturer_by_slug(&self, slug: &str) -> Result<Option<NetBoxNestedObject>, NetBoxError> {
+        let url = format!("{}/api/dcim/manufacturers/?slug={}", self.base_url, urlencoding::encode(slug));
+        let response: NetBoxResponse<NetBoxNestedObject> = self.get(&url).await?;
+        Ok(response.results.into_iter().find(|m| m.slug.as_deref() == Some(slug)))
+    }
+
+    /// Create a manufacturer
+    pub async fn create_manufacturer(
+        &self,
+        manufacturer: &NetBoxManufacturerCreate,
+    ) -> Result<NetBoxNestedObject, NetBoxError> {
+        let url = format!("{}/api/dcim/manufacturers/", self.base_url);
+        self.post(&url, manufacturer).await
+    }
+
+    /// Get a manufacturer's device type by model name
+    ///
+    /// Results are checked against the model, so a NetBox that ignores the
+    /// filters cannot return some other device type.
+    pub async fn get_device_type(
+        &self,
+        manufacturer_id: u64,
+        model: &str,
+    ) -> Result<Option<NetBoxNestedDeviceType>, NetBoxError> {
+        let url = format!(
+            "{}/api/dcim/device-types/?manufacturer_id={}&model={}",
+            self.base_url,
+            manufacturer_id,
+            urlencoding::encode(model)
+        );
+        let response: NetBoxResponse<NetBoxNestedDeviceType> = self.get(&url).await?;
+        Ok(response.results.into_iter().find(|device_type| {
+            device_type.model == model
+                && device_type.manufacturer.as_ref().is_none_or(|m| m.id == manufacturer_id)
+        }))
+    }
+
+    /// Create a device type
+    pub async fn create_device_type(
+        &self,
+        device_type: &NetBoxDeviceTypeCreate,
+    ) -> Result<NetBoxNestedDeviceType, NetBoxError> {
+        let url = format!("{}/api/dcim/device-types/", self.base_url);
+        self.post(&url, device_type).await
+    }
+
+    // =========================================================================
+    // Tag Operations
+    // =========================================================================
+
+    /// Get a tag by name
+    pub async fn get_tag_by_name(&self, name: &str) -> Result<Option<NetBoxNestedObject>, NetBoxError> {
+        let url = format!("{}/api/extras/tags/?name={}", self.base_url, urlencoding::encode(name));
+        let response: NetBoxResponse<NetBoxNestedObject> = self.get(&url).await?;
+        Ok(response.results.into_iter().find(|tag| tag.name == name))
+    }
+
+    /// Create a tag
+    pub async fn create_tag(&self, tag: &NetBoxTagCreate) -> Result<NetBoxNestedObject, NetBoxError> {
+        let url = format!("{}/api/extras/tags/", self.base_url);
+        self.post(&url, tag).await
+    }
+
+    // =========================================================================
+    // Interface Operations
+    // =========================================================================
+
+    /// Get a device's interface by name
+    ///
+    /// Results are checked against the device and name, so a NetBox that
+    /// ignores the filters cannot return some other interface.
+    pub async fn get_interface(&self, device_id: u64, name: &str) -> Result<Option<NetBoxInterface>, NetBoxError> {
+        let url = format!(
+            "{}/api/dcim/interfaces/?device_id={}&name={}",
+            self.base_url,
+            device_id,
+            urlencoding::encode(name)
+        );
+        let response: NetBoxResponse<NetBoxInterface> = self.get(&url).await?;
+        Ok(response.results.into_iter().find(|interface| {
+            interface.name == name && interface.device.as_ref().is_none_or(|d| d.id == device_id)
+        }))
+    }
+
     // =========================================================================
     // Cable Operations
     // =========================================================================
@@ -93,6 +289,16 @@ impl NetBoxClient {
         self.get(&url).await
     }
 
+    /// Get a cable by label
+    ///
+    /// Results are checked against the label, so a NetBox that ignores the
+    /// filter cannot return some other cable.
+    pub async fn get_cable_by_label(&self, label: &str) -> Result<Option<NetBoxCable>, NetBoxError> {
+        let url = format!("{}/api/dcim/cables/?label={}", self.base_url, urlencoding::encode(label));
+        let response: NetBoxResponse<NetBoxCable> = self.get(&url).await?;
+        Ok(response.results.into_iter().find(|cable| cable.label.as_deref() == Some(label)))
+    }
+
     /// Delete a cable
     pub async fn delete_cable(&self, id: u64) -> Result<(), NetBoxError> {
         let url = format!("{}/api/dcim/cables/{}/", self.base_url, id);
@@ -103,15 +309,55 @@ impl NetBoxClient {
     // IPAM Operations
     // =========================================================================
 
-    /// Get IP addresses for a prefix
+    /// Get every IP address within a prefix, across every page
     pub async fn get_ip_addresses(&self, prefix: &str) -> Result<Vec<NetBoxIpAddress>, NetBoxError> {
         let url = format!(
             "{}/api/ipam/ip-addresses/?parent={}",
             self.base_url,
             urlencoding::encode(prefix)
         );
-        let response: NetBoxResponse<NetBoxIpAddress> = self.get(&url).await?;
-        Ok(response.results)
+        self.get_all(&url).await
+    }
+
+    /// Get every IP address assigned to one of a device's interfaces
+    ///
+    /// Results are checked against the device, so a NetBox that ignores the
+    /// filter cannot return addresses of some other device.
+    pub async fn get_device_ip_addresses(&self, device_id: u64) -> Result<Vec<NetBoxIpAddress>, NetBoxError> {
+        let url = format!("{}/api/ipam/ip-addresses/?device_id={}", self.base_url, device_id);
+        let ips: Vec<NetBoxIpAddress> = self.get_all(&url).await?;
+        Ok(ips
+            .into_iter()
+            .filter(|ip| {
+                ip.assigned_object
+                    .as_ref()
+                    .and_then(|obj| obj.device.as_ref())
+                    .is_some_and(|device| device.id == device_id)
+            })
+            .collect())
+    }
+
+    /// Get the IP addresses recorded for a host address, whatever their prefix length
+    pub async fn get_ip_addresses_by_address(&self, address: IpAddr) -> Result<Vec<NetBoxIpAddress>, NetBoxError> {
+        let url = format!(
+            "{}/api/ipam/ip-addresses/?address={}",
+            self.base_url,
+            urlencoding::encode(&address.to_string())
+        );
+        let ips: Vec<NetBoxIpAddress> = self.get_all(&url).await?;
+        Ok(ips.into_iter().filter(|ip| host_address(&ip.address) == Some(address)).collect())
+    }
+
+    /// Create an IP address
+    pub async fn create_ip_address(&self, ip: &NetBoxIpAddressCreate) -> Result<NetBoxIpAddress, NetBoxError> {
+        let url = format!("{}/api/ipam/ip-addresses/", self.base_url);
+        self.post(&url, ip).await
+    }
+
+    /// Update an IP address
+    pub async fn update_ip_address(&self, id: u64, ip: &NetBoxIpAddressCreate) -> Result<NetBoxIpAddress, NetBoxError> {
+        let url = format!("{}/api/ipam/ip-addresses/{}/", self.base_url, id);
+        self.patch(&url, ip).await
     }
 
     /// Get a prefix by CIDR
@@ -145,21 +391,48 @@ impl NetBoxClient {
     // HTTP Helpers
     // =========================================================================
 
+    /// `Authorization` header value, with the token resolved afresh
+    fn authorization(&self) -> Result<String, NetBoxError> {
+        let credentials = self.credentials.resolve().map_err(|e| NetBoxError::Auth(e.to_string()))?;
+        Ok(format!("Token {}", credentials.secret.expose()))
+    }
+
     /// Make a GET request
     async fn get<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, NetBoxError> {
         tracing::debug!("NetBox GET {}", url);
 
-        let response = self.http
-            .get(url)
-            .header("Authorization", format!("Token {}", self.api_token))
-            .header("Accept", "application/json")
-            .send()
+        let authorization = self.authorization()?;
+        let response = self.retry
+            .send(|| {
+                self.http
+                    .get(url)
+                    .header("Authorization", &authorization)
+                    .header("Accept", "application/json")
+            })
             .await
-            .map_err(|e| NetBoxError::Http(e.to_string()))?;
+            .map_err(transport_error)?;
 
         self.handle_response(response).await
     }
 
+    /// GET a paginated list, following `next` links until exhausted
+    ///
+    /// NetBox pages list endpoints (50 results by default), so a single GET
+    /// silently truncates large result sets.
+    async fn get_all<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<Vec<T>, NetBoxError> {
+        let mut results = Vec::new();
+        let mut next = Some(url.to_string());
+
+        while let Some(url) = next.take() {
+            let page: NetBoxResponse<T> = self.get(&url).await?;
+            results.extend(page.results);
+            // Guard against a misbehaving server pointing a page at itself
+            next = page.next.filter(|n| *n != url);
+        }
+
+        Ok(results)
+    }
+
     /// Make a POST request
     async fn post<T, B>(&self, url: &str, body: &B) -> Result<T, NetBoxError>
     where
@@ -168,14 +441,17 @@ impl NetBoxClient {
     {
         tracing::debug!("NetBox POST {}", url);
 
-        let response = self.http
-            .post(url)
-            .header("Authorization", format!("Token {}", self.api_token))
-            .header("Accept", "application/json")
-            .json(body)
-            .send()
+        let authorization = self.authorization()?;
+        let response = self.retry
+            .send(|| {
+                self.http
+                    .post(url)
+                    .header("Authorization", &authorization)
+                    .header("Accept", "application/json")
+                    .json(body)
+            })
             .await
-            .map_err(|e| NetBoxError::Http(e.to_string()))?;
+            .map_err(transport_error)?;
 
         self.handle_response(response).await
     }
@@ -188,14 +464,17 @@ impl NetBoxClient {
     {
         tracing::debug!("NetBox PATCH {}", url);
 
-        let response = self.http
-            .patch(url)
-            .header("Authorization", format!("Token {}", self.api_token))
-            .header("Accept", "application/json")
-            .json(body)
-            .send()
+        let authorization = self.authorization()?;
+        let response = self.retry
+            .send(|| {
+                self.http
+                    .patch(url)
+                    .header("Authorization", &authorization)
+                    .header("Accept", "application/json")
+                    .json(body)
+            })
             .await
-            .map_err(|e| NetBoxError::Http(e.to_string()))?;
+            .map_err(transport_error)?;
 
         self.handle_response(response).await
     }
@@ -204,23 +483,21 @@ impl NetBoxClient {
     async fn delete(&self, url: &str) -> Result<(), NetBoxError> {
         tracing::debug!("NetBox DELETE {}", url);
 
-        let response = self.http
-            .delete(url)
-            .header("Authorization", format!("Token {}", self.api_token))
-            .send()
+        let authorization = self.authorization()?;
+        let response = self.retry
+            .send(|| {
+                self.http
+                    .delete(url)
+                    .header("Authorization", &authorization)
+            })
             .await
-            .map_err(|e| NetBoxError::Http(e.to_string()))?;
+            .map_err(transport_error)?;
 
         let status = response.status();
         if status == reqwest::StatusCode::NO_CONTENT || status.is_success() {
             Ok(())
-        } else if status == reqwest::StatusCode::NOT_FOUND {
-            Err(NetBoxError::NotFound("Resource not found".to_string()))
-        } else if status == reqwest::StatusCode::UNAUTHORIZED {
-            Err(NetBoxError::Auth("Invalid API token".to_string()))
         } else {
-            let body = response.text().await.unwrap_or_default();
-            Err(NetBoxError::Api(format!("Request failed: {} - {}", status, body)))
+            Err(status_error(response).await)
         }
     }
 
@@ -229,28 +506,49 @@ impl NetBoxClient {
         &self,
         response: reqwest::Response,
     ) -> Result<T, NetBoxError> {
-        let status = response.status();
-
-        if status == reqwest::StatusCode::NOT_FOUND {
-            return Err(NetBoxError::NotFound("Resource not found".to_string()));
+        if !response.status().is_success() {
+            return Err(status_error(response).await);
         }
 
-        if status == reqwest::StatusCode::UNAUTHORIZED {
-            return Err(NetBoxError::Auth("Invalid API token".to_string()));
-        }
+        response.json::<T>()
+            .await
+            .map_err(|e| NetBoxError::Parse(e.to_string()))
+    }
+}
 
-        if status == reqwest::StatusCode::BAD_REQUEST {
-            let body = response.text().await.unwrap_or_default();
-            return Err(NetBoxError::Validation(body));
-        }
+/// Host part of a NetBox address such as `10.0.0.6/24`
+pub(crate) fn host_address(address: &str) -> Option<IpAddr> {
+    address.split('/').next()?.parse().ok()
+}
 
-        if !status.is_success() {
+/// Classify a request that never got a response
+fn transport_error(e: reqwest::Error) -> NetBoxError {
+    if e.is_timeout() {
+        NetBoxError::Timeout(e.to_string())
+    } else {
+        NetBoxError::Http(e.to_string())
+    }
+}
+
+/// Classify an unsuccessful response by its status
+async fn status_error(response: reqwest::Response) -> NetBoxError {
+    let status = response.status();
+    match status {
+        reqwest::StatusCode::NOT_FOUND => NetBoxError::NotFound("Resource not found".to_string()),
+        // NetBox answers 403 rather than 401 for an unknown token
+        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+            NetBoxError::Auth("Invalid API token".to_string())
+        }
+        reqwest::StatusCode::TOO_MANY_REQUESTS => NetBoxError::RateLimited(retry_after(&response)),
+        reqwest::StatusCode::BAD_REQUEST => NetBoxError::Validation(response.text().await.unwrap_or_default()),
+        status => {
             let body = response.text().await.unwrap_or_default();
-            return Err(NetBoxError::Api(format!("Request failed: {} - {}", status, body)));
+            let message = format!("Request failed: {} - {}", status, body);
+            if status.is_server_error() {
+                NetBoxError::Unavailable(message)
+            } else {
+                NetBoxError::Api(message)
+            }
         }
-
-        response.json::<T>()
-            .await
-            .map_err(|e| NetBoxError::Parse(e.to_string()))
     }
 }
@@ -4,9 +4,9 @@
 //!
 //! ## Features
 //!
-//! - Device synchronization to NetBox devices
+//! - Device synchronization to NetBox devices, stacks as virtual chassis
 //! - Interface/connection documentation
-//! - IP address management (IPAM)
+//! - IP address management (IPAM), including a device's interface addresses
 //! - Site and rack management
 //!
 //! ## Kan Extension Integration
@@ -15,7 +15,7 @@
 //! from domain objects to NetBox representations.
 
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::RwLock;
 
 mod client;
@@ -24,23 +24,32 @@ mod types;
 pub use client::NetBoxClient;
 pub use types::*;
 
+use crate::adapters::credentials::CredentialSource;
+use crate::adapters::retry::RetryPolicy;
 use crate::domain::ports::{
-    InventoryPort, PortError, ConnectionInfo as PortConnectionInfo, IpAssignment, IpStatus,
+    InventoryPort, PortError, ConnectionInfo as PortConnectionInfo, HealthStatus, IpAssignment, IpStatus,
 };
 use crate::domain::functor::{
-    InventoryExtension, InventoryRepresentation, DomainObject, FunctorError,
+    InventoryExtension, InventoryRepresentation, DomainMorphism, DomainMorphismType, DomainObject, FunctorError,
 };
-use crate::domain::aggregates::{NetworkDeviceAggregate, DeviceState};
-use crate::domain::value_objects::{DeviceId, DeviceType, ConnectionType};
+use crate::domain::aggregates::{NetworkDeviceAggregate, DeviceState, SiteAggregate};
+use crate::domain::value_objects::{ConnectionId, DeviceId, DeviceType, ConnectionType, PortId, SiteId};
 
 /// NetBox adapter configuration
 pub struct NetBoxConfig {
-    /// Default site ID for new devices
+    /// Site ID for new devices that belong to no synced site
     pub default_site_id: u64,
     /// Default device role ID
     pub default_role_id: u64,
     /// Device type mappings (model name -> NetBox device_type ID)
     pub device_type_mappings: HashMap<String, u64>,
+    /// Look up or create the device type of a model missing from
+    /// `device_type_mappings`, instead of refusing to sync the device
+    pub auto_create_device_types: bool,
+    /// Manufacturer of auto-created device types whose MAC has no known vendor
+    pub default_manufacturer: String,
+    /// Retry policy for throttled or transiently failing API calls
+    pub retry: RetryPolicy,
 }
 
 impl Default for NetBoxConfig {
@@ -49,6 +58,9 @@ impl Default for NetBoxConfig {
             default_site_id: 1,
             default_role_id: 1,
             device_type_mappings: HashMap::new(),
+            auto_create_device_types: false,
+            default_manufacturer: "Generic".to_string(),
+            retry: RetryPolicy::default(),
         }
     }
 }
@@ -65,25 +77,56 @@ pub struct NetBoxAdapter {
     config: NetBoxConfig,
     /// Cache of device_id -> netbox_id mappings
     device_cache: RwLock<HashMap<DeviceId, u64>>,
+    /// Cache of (netbox device id, interface name) -> netbox interface id
+    interface_cache: RwLock<HashMap<(u64, String), u64>>,
+    /// Cache of (manufacturer slug, model) -> auto-created netbox device type id
+    device_type_cache: RwLock<HashMap<(String, String), u64>>,
+    /// Cache of tag name -> netbox tag id
+    tag_cache: RwLock<HashMap<String, u64>>,
+    /// Cache of site_id -> netbox site id
+    site_cache: RwLock<HashMap<SiteId, u64>>,
+    /// NetBox site of each member device, as of its site's last sync
+    device_sites: RwLock<HashMap<DeviceId, u64>>,
+}
+
+impl std::fmt::Debug for NetBoxAdapter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NetBoxAdapter")
+            .field("client", &self.client)
+            .finish_non_exhaustive()
+    }
 }
 
 impl NetBoxAdapter {
     /// Create a new NetBox adapter
     pub fn new(base_url: &str, api_token: &str) -> Result<Self, NetBoxError> {
-        Ok(Self {
-            client: NetBoxClient::new(base_url, api_token)?,
-            config: NetBoxConfig::default(),
-            device_cache: RwLock::new(HashMap::new()),
-        })
+        Ok(Self::from_client(NetBoxClient::new(base_url, api_token)?, NetBoxConfig::default()))
     }
 
     /// Create with custom configuration
     pub fn with_config(base_url: &str, api_token: &str, config: NetBoxConfig) -> Result<Self, NetBoxError> {
-        Ok(Self {
-            client: NetBoxClient::new(base_url, api_token)?,
+        let client = NetBoxClient::new(base_url, api_token)?.with_retry_policy(config.retry);
+        Ok(Self::from_client(client, config))
+    }
+
+    /// Create an adapter that authenticates with the API token from `source`
+    ///
+    /// The token is resolved for each API call rather than up front.
+    pub fn with_credentials(base_url: &str, source: impl CredentialSource + 'static) -> Result<Self, NetBoxError> {
+        Ok(Self::from_client(NetBoxClient::with_credentials(base_url, source)?, NetBoxConfig::default()))
+    }
+
+    fn from_client(client: NetBoxClient, config: NetBoxConfig) -> Self {
+        Self {
+            client,
             config,
             device_cache: RwLock::new(HashMap::new()),
-        })
+            interface_cache: RwLock::new(HashMap::new()),
+            device_type_cache: RwLock::new(HashMap::new()),
+            tag_cache: RwLock::new(HashMap::new()),
+            site_cache: RwLock::new(HashMap::new()),
+            device_sites: RwLock::new(HashMap::new()),
+        }
     }
 
     /// Get the underlying client for advanced operations
@@ -91,19 +134,170 @@ impl NetBoxAdapter {
         &self.client
     }
 
-    /// Get device type ID for a model, using config mappings
-    fn get_device_type_id(&self, device_type: &DeviceType) -> u64 {
-        let model_name = match device_type {
-            DeviceType::Gateway => "Gateway",
-            DeviceType::Switch => "Switch",
-            DeviceType::AccessPoint => "Access Point",
-            DeviceType::Generic { model } => model.as_str(),
+    /// Resolve the NetBox device type ID for a device
+    ///
+    /// Uses `device_type_mappings` first. An unmapped model is an error
+    /// unless `auto_create_device_types` is set, in which case the
+    /// manufacturer (the MAC's vendor) and device type are looked up or
+    /// created in NetBox and the ID is cached for later syncs.
+    async fn resolve_device_type_id(&self, device: &NetworkDeviceAggregate) -> Result<u64, PortError> {
+        let model = model_name(device.device_type());
+        if let Some(&id) = self.config.device_type_mappings.get(model) {
+            return Ok(id);
+        }
+        if !self.config.auto_create_device_types {
+            return Err(PortError::InventoryError(format!(
+                "No NetBox device type mapped for model '{}' (device {}); add it to device_type_mappings \
+                 or enable auto_create_device_types",
+                model,
+                device.id()
+            )));
+        }
+
+        let manufacturer = device.mac().vendor().unwrap_or(&self.config.default_manufacturer);
+        let key = (slugify(manufacturer), model.to_string());
+        let cached = self.device_type_cache.read().ok().and_then(|cache| cache.get(&key).copied());
+        if let Some(id) = cached {
+            return Ok(id);
+        }
+
+        let id = self.find_or_create_device_type(manufacturer, &key.0, model)
+            .await?;
+        if let Ok(mut cache) = self.device_type_cache.write() {
+            cache.insert(key, id);
+        }
+        Ok(id)
+    }
+
+    /// Look up a manufacturer's device type, creating either if missing
+    async fn find_or_create_device_type(
+        &self,
+        manufacturer: &str,
+        manufacturer_slug: &str,
+        model: &str,
+    ) -> Result<u64, NetBoxError> {
+        let manufacturer_id = match self.client.get_manufacturer_by_slug(manufacturer_slug).await? {
+            Some(existing) => existing.id,
+            None => {
+                tracing::info!("Creating NetBox manufacturer {}", manufacturer);
+                self.client
+                    .create_manufacturer(&NetBoxManufacturerCreate {
+                        name: manufacturer.to_string(),
+                        slug: manufacturer_slug.to_string(),
+                    })
+                    .await?
+                    .id
+            }
         };
 
-        self.config.device_type_mappings
-            .get(model_name)
-            .copied()
-            .unwrap_or(1) // Default device type ID
+        if let Some(existing) = self.client.get_device_type(manufacturer_id, model).await? {
+            return Ok(existing.id);
+        }
+        tracing::info!("Creating NetBox device type {} {}", manufacturer, model);
+        let created = self.client
+            .create_device_type(&NetBoxDeviceTypeCreate {
+                manufacturer: manufacturer_id,
+                model: model.to_string(),
+                slug: slugify(model),
+            })
+            .await?;
+        Ok(created.id)
+    }
+
+    /// Resolve NetBox tag references for a device's tags
+    ///
+    /// Each tag is named `key:value` in NetBox (or just `key` for a plain
+    /// label) and is looked up or created once, then its ID is cached.
+    async fn resolve_tag_refs(&self, device: &NetworkDeviceAggregate) -> Result<Vec<NetBoxTagRef>, NetBoxError> {
+        let mut refs = Vec::with_capacity(device.tags().len());
+        for tag in device.tags() {
+            let name = tag.to_string();
+            let cached = self.tag_cache.read().ok().and_then(|cache| cache.get(&name).copied());
+            let id = match cached {
+                Some(id) => id,
+                None => {
+                    let id = match self.client.get_tag_by_name(&name).await? {
+                        Some(existing) => existing.id,
+                        None => {
+                            tracing::info!("Creating NetBox tag {}", name);
+                            self.client
+                                .create_tag(&NetBoxTagCreate { slug: slugify(&name), name: name.clone() })
+                                .await?
+                                .id
+                        }
+                    };
+                    if let Ok(mut cache) = self.tag_cache.write() {
+                        cache.insert(name, id);
+                    }
+                    id
+                }
+            };
+            refs.push(NetBoxTagRef { id });
+        }
+        Ok(refs)
+    }
+
+    /// Resolve the NetBox site ID for a domain site
+    ///
+    /// Sites are matched by the slug of their name and created when
+    /// missing, then the ID is cached.
+    async fn resolve_site_id(&self, site: &SiteAggregate) -> Result<u64, NetBoxError> {
+        let cached = self.site_cache.read().ok().and_then(|cache| cache.get(&site.id()).copied());
+        if let Some(id) = cached {
+            return Ok(id);
+        }
+
+        let slug = slugify(site.name());
+        let id = match self.client.get_site_by_slug(&slug).await? {
+            Some(existing) => existing.id,
+            None => {
+                tracing::info!("Creating NetBox site {}", site.name());
+                self.client
+                    .create_site(&NetBoxSiteCreate { name: site.name().to_string(), slug })
+                    .await?
+                    .id
+            }
+        };
+        if let Ok(mut cache) = self.site_cache.write() {
+            cache.insert(site.id(), id);
+        }
+        Ok(id)
+    }
+
+    /// NetBox site of a device, if its site has been synced
+    fn device_site(&self, device_id: &DeviceId) -> Option<u64> {
+        self.device_sites.read()
+            .ok()
+            .and_then(|sites| sites.get(device_id).copied())
+    }
+
+    /// Represent a stack device as a NetBox virtual chassis it masters
+    ///
+    /// The stack stays a single NetBox device. The chassis is named after it
+    /// and created when missing; the device is placed in it at its own
+    /// unit's member number, then made the chassis master.
+    async fn sync_virtual_chassis(&self, device: &NetworkDeviceAggregate, netbox_id: u64) -> Result<(), NetBoxError> {
+        let chassis = match self.client.get_virtual_chassis_by_name(device.name()).await? {
+            Some(existing) => existing,
+            None => {
+                tracing::info!("Creating NetBox virtual chassis {}", device.name());
+                self.client
+                    .create_virtual_chassis(&NetBoxVirtualChassisCreate { name: device.name().to_string() })
+                    .await?
+            }
+        };
+        let position = device.stack_members()
+            .iter()
+            .find(|member| member.mac == device.mac())
+            .map_or(1, |member| member.member);
+
+        self.client
+            .update_device(netbox_id, &serde_json::json!({"virtual_chassis": chassis.id, "vc_position": position}))
+            .await?;
+        self.client
+            .update_virtual_chassis(chassis.id, &serde_json::json!({"master": netbox_id}))
+            .await?;
+        Ok(())
     }
 
     /// Get cached NetBox ID for a device
@@ -122,18 +316,116 @@ impl NetBoxAdapter {
 
     /// Remove a device from cache
     fn uncache_device(&self, device_id: &DeviceId) {
-        if let Ok(mut cache) = self.device_cache.write() {
-            cache.remove(device_id);
+        let netbox_id = self.device_cache.write().ok().and_then(|mut cache| cache.remove(device_id));
+        if let (Some(netbox_id), Ok(mut interfaces)) = (netbox_id, self.interface_cache.write()) {
+            interfaces.retain(|(device, _), _| *device != netbox_id);
+        }
+    }
+
+    /// Find the NetBox ID of a device this adapter synced
+    ///
+    /// The cache is only an optimization; falls back to the `cim_device_id`
+    /// custom field written by `sync_device` (e.g. after a restart).
+    async fn find_netbox_id(&self, device_id: DeviceId) -> Result<Option<u64>, NetBoxError> {
+        if let Some(netbox_id) = self.get_cached_netbox_id(&device_id) {
+            return Ok(Some(netbox_id));
+        }
+
+        let netbox_id = self.client
+            .get_device_by_custom_field("cim_device_id", &device_id.to_string())
+            .await?
+            .map(|device| device.id);
+        if let Some(netbox_id) = netbox_id {
+            self.cache_netbox_id(device_id, netbox_id);
+        }
+        Ok(netbox_id)
+    }
+
+    /// Resolve a device's port to its NetBox interface ID
+    async fn resolve_interface_id(&self, device_id: DeviceId, port: &PortId) -> Result<u64, PortError> {
+        let netbox_device = self.find_netbox_id(device_id)
+            .await
+            .map_err(PortError::from)?
+            .ok_or_else(|| PortError::InventoryError(format!("Device {} has not been synced to NetBox", device_id)))?;
+
+        let key = (netbox_device, port.name.clone());
+        let cached = self.interface_cache.read().ok().and_then(|cache| cache.get(&key).copied());
+        if let Some(interface_id) = cached {
+            return Ok(interface_id);
+        }
+
+        let interface_id = self.client.get_interface(netbox_device, &port.name)
+            .await
+            .map_err(PortError::from)?
+            .map(|interface| interface.id)
+            .ok_or_else(|| PortError::InventoryError(format!(
+                "Interface {} not found on NetBox device {} (device {})",
+                port.name, netbox_device, device_id
+            )))?;
+
+        if let Ok(mut cache) = self.interface_cache.write() {
+            cache.insert(key, interface_id);
         }
+        Ok(interface_id)
+    }
+
+    /// Resolve a NetBox device ID to the domain device it projects
+    ///
+    /// Checks the cache first, then reads the device's `cim_device_id`
+    /// custom field. Devices not created by this adapter resolve to `None`.
+    async fn resolve_device_id(&self, netbox_id: u64) -> Result<Option<DeviceId>, NetBoxError> {
+        let cached = self.device_cache.read()
+            .ok()
+            .and_then(|cache| cache.iter().find(|(_, id)| **id == netbox_id).map(|(device_id, _)| *device_id));
+        if cached.is_some() {
+            return Ok(cached);
+        }
+
+        let device = match self.client.get_device(netbox_id).await {
+            Ok(device) => device,
+            Err(NetBoxError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let device_id = device.custom_fields
+            .get("cim_device_id")
+            .and_then(|v| v.as_str())
+            .and_then(|v| uuid::Uuid::parse_str(v).ok())
+            .map(DeviceId::from_uuid);
+
+        if let Some(device_id) = device_id {
+            self.cache_netbox_id(device_id, netbox_id);
+        }
+        Ok(device_id)
+    }
+}
+
+/// NetBox model name for a domain device type
+fn model_name(device_type: &DeviceType) -> &str {
+    match device_type {
+        DeviceType::Gateway => "Gateway",
+        DeviceType::Switch => "Switch",
+        DeviceType::AccessPoint => "Access Point",
+        DeviceType::Firewall => "Firewall",
+        DeviceType::Generic { model } => model.as_str(),
     }
 }
 
+/// NetBox slug for a name: lowercase alphanumerics separated by single hyphens
+fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
 #[async_trait]
 impl InventoryPort for NetBoxAdapter {
     fn system_name(&self) -> &str {
         "netbox"
     }
 
+    #[tracing::instrument(name = "netbox.sync_device", skip_all, fields(device_id = %device.id()))]
     async fn sync_device(&self, device: &NetworkDeviceAggregate) -> Result<(), PortError> {
         tracing::info!(
             "Syncing device {} ({}) to NetBox",
@@ -143,14 +435,14 @@ impl InventoryPort for NetBoxAdapter {
 
         // Check if device already exists in NetBox
         let existing = self.client.get_device_by_name(device.name())
-            .await
-            .map_err(|e| PortError::InventoryError(e.to_string()))?;
+            .await?;
 
         let status = match device.state() {
             DeviceState::Provisioned => "active",
             DeviceState::Discovered => "planned",
             DeviceState::Configuring => "staged",
             DeviceState::Error => "failed",
+            DeviceState::Missing => "offline",
             DeviceState::Decommissioned => "decommissioning",
             _ => "inventory",
         };
@@ -159,58 +451,62 @@ impl InventoryPort for NetBoxAdapter {
             "mac_address": device.mac().to_string(),
             "cim_device_id": device.id().to_string(),
         });
+        let tags = self.resolve_tag_refs(device).await?;
+        let site = self.device_site(&device.id());
 
-        if let Some(existing_device) = existing {
-            // Update existing device
-            let update = serde_json::json!({
+        let netbox_id = if let Some(existing_device) = existing {
+            // Update existing device; the tag list replaces NetBox's, so removed tags are dropped
+            let mut update = serde_json::json!({
                 "status": status,
                 "custom_fields": custom_fields,
+                "tags": tags,
             });
+            if let Some(site) = site {
+                update["site"] = site.into();
+            }
 
             self.client.update_device(existing_device.id, &update)
-                .await
-                .map_err(|e| PortError::InventoryError(e.to_string()))?;
+                .await?;
 
             self.cache_netbox_id(device.id(), existing_device.id);
+            existing_device.id
         } else {
             // Create new device
             let create = NetBoxDeviceCreate {
                 name: device.name().to_string(),
-                device_type: self.get_device_type_id(device.device_type()),
-                site: self.config.default_site_id,
+                device_type: self.resolve_device_type_id(device).await?,
+                site: site.unwrap_or(self.config.default_site_id),
                 role: self.config.default_role_id,
                 status: Some(status.to_string()),
                 serial: None,
                 custom_fields: Some(custom_fields),
+                tags,
             };
 
             let created = self.client.create_device(&create)
-                .await
-                .map_err(|e| PortError::InventoryError(e.to_string()))?;
+                .await?;
 
             self.cache_netbox_id(device.id(), created.id);
-        }
+            created.id
+        };
 
+        if device.is_stack() {
+            self.sync_virtual_chassis(device, netbox_id).await?;
+        }
         Ok(())
     }
 
+    #[tracing::instrument(name = "netbox.remove_device", skip(self))]
     async fn remove_device(&self, device_id: DeviceId) -> Result<(), PortError> {
         tracing::info!("Removing device {} from NetBox", device_id);
 
-        // Try to find NetBox ID from cache or by searching
-        let netbox_id = if let Some(id) = self.get_cached_netbox_id(&device_id) {
-            id
-        } else {
-            // Search by custom field
-            // Note: This is a simplified approach; real implementation would search properly
-            return Err(PortError::InventoryError(
-                "Device not found in NetBox cache".to_string()
-            ));
-        };
+        let netbox_id = self.find_netbox_id(device_id)
+            .await
+            .map_err(PortError::from)?
+            .ok_or(PortError::DeviceNotFound(device_id))?;
 
         self.client.delete_device(netbox_id)
-            .await
-            .map_err(|e| PortError::InventoryError(e.to_string()))?;
+            .await?;
 
         self.uncache_device(&device_id);
 
@@ -229,16 +525,17 @@ impl InventoryPort for NetBoxAdapter {
             _ => None,
         };
 
-        // Note: This requires interface IDs, which would need to be looked up
-        // This is a simplified implementation
+        let a_interface = self.resolve_interface_id(connection.source_device, &connection.source_port).await?;
+        let b_interface = self.resolve_interface_id(connection.target_device, &connection.target_port).await?;
+
         let cable = NetBoxCableCreate {
             a_terminations: vec![NetBoxTermination {
                 object_type: "dcim.interface".to_string(),
-                object_id: connection.source_port.index.unwrap_or(0) as u64,
+                object_id: a_interface,
             }],
             b_terminations: vec![NetBoxTermination {
                 object_type: "dcim.interface".to_string(),
-                object_id: connection.target_port.index.unwrap_or(0) as u64,
+                object_id: b_interface,
             }],
             cable_type,
             status: Some("connected".to_string()),
@@ -246,8 +543,22 @@ impl InventoryPort for NetBoxAdapter {
         };
 
         self.client.create_cable(&cable)
-            .await
-            .map_err(|e| PortError::InventoryError(e.to_string()))?;
+            .await?;
+
+        Ok(())
+    }
+
+    async fn remove_connection(&self, connection_id: ConnectionId) -> Result<(), PortError> {
+        tracing::info!("Removing connection {} from NetBox", connection_id);
+
+        // Cables are labelled with the connection ID when synced
+        let cable = self.client.get_cable_by_label(&connection_id.to_string())
+            .await?;
+
+        if let Some(cable) = cable {
+            self.client.delete_cable(cable.id)
+                .await?;
+        }
 
         Ok(())
     }
@@ -256,33 +567,39 @@ impl InventoryPort for NetBoxAdapter {
         tracing::debug!("Getting IP assignments for prefix {}", prefix);
 
         let ips = self.client.get_ip_addresses(prefix)
-            .await
-            .map_err(|e| PortError::InventoryError(e.to_string()))?;
-
-        let assignments = ips.into_iter()
-            .map(|ip| {
-                // Parse address to extract IP and prefix length
-                let parts: Vec<&str> = ip.address.split('/').collect();
-                let address = parts[0].parse().unwrap_or_else(|_| "0.0.0.0".parse().unwrap());
-
-                IpAssignment {
-                    address,
-                    prefix_len: parts.get(1)
-                        .and_then(|p| p.parse().ok())
-                        .unwrap_or(24),
-                    device_id: None, // Would need to look up from assigned_object
-                    interface: ip.description.clone(),
-                    status: ip.status
-                        .map(|s| match s.value.as_str() {
-                            "active" => IpStatus::Active,
-                            "reserved" => IpStatus::Reserved,
-                            "deprecated" => IpStatus::Deprecated,
-                            _ => IpStatus::Active,
-                        })
-                        .unwrap_or(IpStatus::Active),
-                }
-            })
-            .collect();
+            .await?;
+
+        let mut assignments = Vec::with_capacity(ips.len());
+        for ip in ips {
+            // Parse address to extract IP and prefix length
+            let parts: Vec<&str> = ip.address.split('/').collect();
+            let address = parts[0].parse().unwrap_or_else(|_| "0.0.0.0".parse().unwrap());
+
+            let assigned = ip.assigned_object.as_ref();
+            let device_id = match assigned.and_then(|obj| obj.device.as_ref()) {
+                Some(device) => self.resolve_device_id(device.id)
+                    .await
+                    .map_err(PortError::from)?,
+                None => None,
+            };
+
+            assignments.push(IpAssignment {
+                address,
+                prefix_len: parts.get(1)
+                    .and_then(|p| p.parse().ok())
+                    .unwrap_or(24),
+                device_id,
+                interface: assigned.and_then(|obj| obj.name.clone()),
+                status: ip.status
+                    .map(|s| match s.value.as_str() {
+                        "active" => IpStatus::Active,
+                        "reserved" => IpStatus::Reserved,
+                        "deprecated" => IpStatus::Deprecated,
+                        _ => IpStatus::Active,
+                    })
+                    .unwrap_or(IpStatus::Active),
+            });
+        }
 
         Ok(assignments)
     }
@@ -301,7 +618,7 @@ impl InventoryPort for NetBoxAdapter {
         // Find the prefix
         let netbox_prefix = self.client.get_prefix(prefix)
             .await
-            .map_err(|e| PortError::InventoryError(e.to_string()))?
+            .map_err(PortError::from)?
             .ok_or_else(|| PortError::InventoryError(format!("Prefix {} not found", prefix)))?;
 
         let allocation = NetBoxIpAllocate {
@@ -312,8 +629,7 @@ impl InventoryPort for NetBoxAdapter {
         };
 
         let ip = self.client.allocate_ip(netbox_prefix.id, &allocation)
-            .await
-            .map_err(|e| PortError::InventoryError(e.to_string()))?;
+            .await?;
 
         // Parse the allocated address
         let parts: Vec<&str> = ip.address.split('/').collect();
@@ -330,6 +646,116 @@ impl InventoryPort for NetBoxAdapter {
             status: IpStatus::Active,
         })
     }
+
+    #[tracing::instrument(name = "netbox.sync_ip_assignments", skip_all, fields(device_id = %device.id()))]
+    async fn sync_ip_assignments(&self, device: &NetworkDeviceAggregate) -> Result<(), PortError> {
+        let netbox_device = self.find_netbox_id(device.id())
+            .await
+            .map_err(PortError::from)?
+            .ok_or_else(|| PortError::InventoryError(format!("Device {} has not been synced to NetBox", device.id())))?;
+
+        let recorded = self.client.get_device_ip_addresses(netbox_device)
+            .await?;
+        let mut kept = HashSet::new();
+
+        for interface in device.interfaces() {
+            let Some(ip) = interface.ip_address else {
+                continue;
+            };
+            let host_len = if ip.is_ipv4() { 32 } else { 128 };
+            let address = NetBoxIpAddressCreate {
+                address: format!("{}/{}", ip, interface.prefix_len.unwrap_or(host_len)),
+                status: Some("active".to_string()),
+                assigned_object_type: "dcim.interface".to_string(),
+                assigned_object_id: self.resolve_interface_id(device.id(), &PortId::new(&interface.name)).await?,
+            };
+
+            // Already on the device, possibly on another interface or with another prefix length
+            if let Some(existing) = recorded.iter().find(|r| client::host_address(&r.address) == Some(ip)) {
+                kept.insert(existing.id);
+                if existing.address != address.address
+                    || existing.assigned_object_id != Some(address.assigned_object_id)
+                {
+                    tracing::info!("Moving NetBox IP {} to {} on {}", address.address, interface.name, device.name());
+                    self.client.update_ip_address(existing.id, &address)
+                        .await?;
+                }
+                continue;
+            }
+
+            // Reuse an unassigned record, e.g. one reserved with `allocate_ip`
+            let unassigned = self.client.get_ip_addresses_by_address(ip)
+                .await?
+                .into_iter()
+                .find(|r| r.assigned_object_id.is_none());
+            if let Some(unassigned) = unassigned {
+                tracing::info!("Assigning NetBox IP {} to {} on {}", address.address, interface.name, device.name());
+                self.client.update_ip_address(unassigned.id, &address)
+                    .await?;
+            } else {
+                tracing::info!("Creating NetBox IP {} on {} {}", address.address, device.name(), interface.name);
+                self.client.create_ip_address(&address)
+                    .await?;
+            }
+        }
+
+        // Release addresses the device no longer has
+        for stale in recorded.iter().filter(|r| !kept.contains(&r.id)) {
+            tracing::info!("Releasing NetBox IP {} from {}", stale.address, device.name());
+            self.client.delete_ip(stale.id)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "netbox.sync_site", skip_all, fields(site_id = %site.id()))]
+    async fn sync_site(&self, site: &SiteAggregate) -> Result<(), PortError> {
+        let netbox_site = self.resolve_site_id(site)
+            .await?;
+
+        for device_id in site.devices() {
+            if self.device_site(device_id) == Some(netbox_site) {
+                continue;
+            }
+            // Devices not synced yet are created in the site by `sync_device`
+            if let Some(netbox_device) = self.find_netbox_id(*device_id).await? {
+                tracing::info!("Moving NetBox device {} to site {}", netbox_device, site.name());
+                self.client.update_device(netbox_device, &serde_json::json!({ "site": netbox_site }))
+                    .await?;
+            }
+            if let Ok(mut sites) = self.device_sites.write() {
+                sites.insert(*device_id, netbox_site);
+            }
+        }
+
+        // Former members keep their NetBox site until another site claims them
+        if let Ok(mut sites) = self.device_sites.write() {
+            sites.retain(|device_id, id| *id != netbox_site || site.contains_device(*device_id));
+        }
+
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<HealthStatus, PortError> {
+        match self.client.get_status().await {
+            Ok(status) => Ok(HealthStatus {
+                reachable: true,
+                authenticated: true,
+                version: status.netbox_version,
+            }),
+            Err(NetBoxError::Auth(_)) => Ok(HealthStatus {
+                reachable: true,
+                authenticated: false,
+                version: None,
+            }),
+            Err(NetBoxError::Http(e) | NetBoxError::Timeout(e)) => {
+                tracing::warn!("NetBox unreachable: {}", e);
+                Ok(HealthStatus::unreachable())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
 }
 
 impl InventoryExtension for NetBoxAdapter {
@@ -344,18 +770,17 @@ impl InventoryExtension for NetBoxAdapter {
                 let payload = serde_json::json!({
                     "name": device.name(),
                     "device_type": {
-                        "model": match device.device_type() {
-                            DeviceType::Gateway => "Gateway",
-                            DeviceType::Switch => "Switch",
-                            DeviceType::AccessPoint => "Access Point",
-                            DeviceType::Generic { model } => model.as_str(),
-                        }
+                        "model": model_name(device.device_type()),
+                    },
+                    "role": {
+                        "slug": device_role_slug(device.device_type()),
                     },
                     "status": match device.state() {
                         DeviceState::Provisioned => "active",
                         DeviceState::Discovered => "planned",
                         DeviceState::Configuring => "staged",
                         DeviceState::Error => "failed",
+                        DeviceState::Missing => "offline",
                         DeviceState::Decommissioned => "decommissioning",
                         _ => "inventory",
                     },
@@ -419,4 +844,117 @@ impl InventoryExtension for NetBoxAdapter {
             }
         }
     }
+
+    /// Map a domain relationship onto the NetBox object graph
+    ///
+    /// Containment becomes site/rack assignment and connections become
+    /// cables, both kept as-is. NetBox has no notion of one device managing
+    /// another, so `ManagedBy` is only recorded as a dependency; since any
+    /// mixed path already composes to `DependsOn`, this keeps composition
+    /// intact. Endpoints are renamed to their NetBox inventory ids.
+    fn extend_morphism(&self, morphism: &DomainMorphism) -> Result<DomainMorphism, FunctorError> {
+        let morphism_type = match morphism.morphism_type {
+            DomainMorphismType::ManagedBy => DomainMorphismType::DependsOn,
+            ref other => other.clone(),
+        };
+        Ok(DomainMorphism::new(
+            format!("netbox-{}", morphism.source),
+            format!("netbox-{}", morphism.target),
+            morphism_type,
+        ))
+    }
+}
+
+/// NetBox device role slug for a device type
+fn device_role_slug(device_type: &DeviceType) -> &'static str {
+    match device_type {
+        DeviceType::Gateway => "router",
+        DeviceType::Switch => "switch",
+        DeviceType::AccessPoint => "access-point",
+        DeviceType::Firewall => "firewall",
+        DeviceType::Generic { .. } => "network-device",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::functor::{NetworkFunctor, NetworkGraphNode, NetworkKanExtension, NetworkNodeType};
+
+    // ==========================================================================
+    // Kan Extension Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_firewall_round_trips_through_extension() {
+        let node = NetworkGraphNode {
+            id: "fw1".to_string(),
+            node_type: NetworkNodeType::Device,
+            properties: HashMap::from([
+                ("mac".to_string(), serde_json::json!("00:09:0f:aa:bb:cc")),
+                ("device_type".to_string(), serde_json::json!("firewall")),
+            ]),
+        };
+
+        let mut kan = NetworkKanExtension::new(NetworkFunctor::new());
+        kan.register_inventory("netbox", Box::new(NetBoxAdapter::new("https://netbox.local", "token").unwrap()));
+
+        let mut functor = NetworkFunctor::new();
+        let domain_obj = functor.map_node(&node).unwrap();
+        let DomainObject::Device(device) = &domain_obj else {
+            panic!("expected a device, got {:?}", domain_obj);
+        };
+        assert_eq!(device.device_type(), &DeviceType::Firewall);
+
+        let repr = kan.extend_to_inventory(&domain_obj, "netbox").unwrap();
+        assert_eq!(repr.device_id, device.id());
+        assert_eq!(repr.payload["device_type"]["model"], "Firewall");
+        assert_eq!(repr.payload["role"]["slug"], "firewall");
+    }
+
+    #[test]
+    fn test_relationships_map_to_netbox_objects() {
+        let adapter = NetBoxAdapter::new("https://netbox.local", "token").unwrap();
+
+        let cable = DomainMorphism::new("switch", "ap", DomainMorphismType::ConnectedTo);
+        assert_eq!(
+            adapter.extend_morphism(&cable).unwrap(),
+            DomainMorphism::new("netbox-switch", "netbox-ap", DomainMorphismType::ConnectedTo)
+        );
+
+        let managed = DomainMorphism::new("ap", "controller", DomainMorphismType::ManagedBy);
+        assert_eq!(
+            adapter.extend_morphism(&managed).unwrap(),
+            DomainMorphism::new("netbox-ap", "netbox-controller", DomainMorphismType::DependsOn)
+        );
+    }
+
+    #[test]
+    fn test_extension_preserves_composition() {
+        let mut kan = NetworkKanExtension::new(NetworkFunctor::new());
+        kan.register_inventory("netbox", Box::new(NetBoxAdapter::new("https://netbox.local", "token").unwrap()));
+
+        // Management chains and management next to containment both exercise
+        // the ManagedBy → DependsOn collapse
+        let category = [
+            DomainMorphism::new("site", "rack", DomainMorphismType::Contains),
+            DomainMorphism::new("rack", "switch", DomainMorphismType::Contains),
+            DomainMorphism::new("switch", "ap", DomainMorphismType::ConnectedTo),
+            DomainMorphism::new("switch", "controller", DomainMorphismType::ManagedBy),
+            DomainMorphism::new("ap", "controller", DomainMorphismType::ManagedBy),
+            DomainMorphism::new("controller", "cloud", DomainMorphismType::ManagedBy),
+        ];
+        assert_eq!(kan.verify_composition("netbox", &category).unwrap(), vec![]);
+    }
+
+    // ==========================================================================
+    // Device Type Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Access Point"), "access-point");
+        assert_eq!(slugify("DCS-7050SX3-48YC8"), "dcs-7050sx3-48yc8");
+        assert_eq!(slugify("  Ubiquiti  Inc. "), "ubiquiti-inc");
+    }
 }
@@ -2,6 +2,7 @@
 //!
 //! Types for interacting with the NetBox DCIM/IPAM system.
 
+use crate::domain::ports::PortError;
 use serde::{Deserialize, Serialize};
 
 /// NetBox API response wrapper with pagination
@@ -106,6 +107,20 @@ pub struct NetBoxIpAddress {
     pub assigned_object_type: Option<String>,
     /// Assigned object ID
     pub assigned_object_id: Option<u64>,
+    /// Assigned object (interface) summary
+    #[serde(default)]
+    pub assigned_object: Option<NetBoxAssignedObject>,
+}
+
+/// Object an IP address is assigned to, as nested in an IP address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetBoxAssignedObject {
+    /// Object ID
+    pub id: u64,
+    /// Interface name
+    pub name: Option<String>,
+    /// Device owning the interface (absent for VM interfaces)
+    pub device: Option<NetBoxNestedObject>,
 }
 
 /// NetBox cable/connection
@@ -132,6 +147,17 @@ pub struct NetBoxCable {
     pub length_unit: Option<String>,
 }
 
+/// NetBox device interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetBoxInterface {
+    /// Interface ID
+    pub id: u64,
+    /// Interface name, e.g. `GigabitEthernet0/1`
+    pub name: String,
+    /// Device owning the interface
+    pub device: Option<NetBoxNestedObject>,
+}
+
 /// Cable termination
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetBoxTermination {
@@ -178,6 +204,61 @@ pub struct NetBoxDeviceCreate {
     /// Custom fields
     #[serde(skip_serializing_if = "Option::is_none")]
     pub custom_fields: Option<serde_json::Value>,
+    /// Tags, as references to existing NetBox tags
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<NetBoxTagRef>,
+}
+
+/// Reference to an existing NetBox tag by ID
+#[derive(Debug, Clone, Serialize)]
+pub struct NetBoxTagRef {
+    /// Tag ID
+    pub id: u64,
+}
+
+/// Request body for creating a tag
+#[derive(Debug, Clone, Serialize)]
+pub struct NetBoxTagCreate {
+    /// Tag name, e.g. `env:prod`
+    pub name: String,
+    /// URL-friendly unique name
+    pub slug: String,
+}
+
+/// Request body for creating a site
+#[derive(Debug, Clone, Serialize)]
+pub struct NetBoxSiteCreate {
+    /// Site name
+    pub name: String,
+    /// URL-friendly unique name
+    pub slug: String,
+}
+
+/// Request body for creating a virtual chassis
+#[derive(Debug, Clone, Serialize)]
+pub struct NetBoxVirtualChassisCreate {
+    /// Virtual chassis name
+    pub name: String,
+}
+
+/// Request body for creating a manufacturer
+#[derive(Debug, Clone, Serialize)]
+pub struct NetBoxManufacturerCreate {
+    /// Manufacturer name
+    pub name: String,
+    /// URL-friendly unique name
+    pub slug: String,
+}
+
+/// Request body for creating a device type
+#[derive(Debug, Clone, Serialize)]
+pub struct NetBoxDeviceTypeCreate {
+    /// Manufacturer ID
+    pub manufacturer: u64,
+    /// Model name
+    pub model: String,
+    /// URL-friendly name, unique per manufacturer
+    pub slug: String,
 }
 
 /// Request body for creating a cable
@@ -215,6 +296,34 @@ pub struct NetBoxIpAllocate {
     pub assigned_object_id: Option<u64>,
 }
 
+/// Request body for creating or updating an IP address
+#[derive(Debug, Clone, Serialize)]
+pub struct NetBoxIpAddressCreate {
+    /// IP address with prefix length, e.g. `10.0.0.6/24`
+    pub address: String,
+    /// Status
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Assigned object type, e.g. `dcim.interface`
+    pub assigned_object_type: String,
+    /// Assigned object ID
+    pub assigned_object_id: u64,
+}
+
+/// `/api/status/` response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetBoxSystemStatus {
+    /// NetBox version, e.g. `3.7.2`
+    #[serde(rename = "netbox-version")]
+    pub netbox_version: Option<String>,
+    /// Django version
+    #[serde(rename = "django-version")]
+    pub django_version: Option<String>,
+    /// Number of background workers running
+    #[serde(rename = "rq-workers-running")]
+    pub rq_workers_running: Option<u64>,
+}
+
 /// NetBox API error
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum NetBoxError {
@@ -236,4 +345,29 @@ pub enum NetBoxError {
     /// Validation error
     #[error("Validation error: {0}")]
     Validation(String),
+    /// The request timed out
+    #[error("Timed out: {0}")]
+    Timeout(String),
+    /// NetBox is throttling requests; carries its `Retry-After`
+    #[error("Rate limited by NetBox")]
+    RateLimited(Option<std::time::Duration>),
+    /// NetBox failed with a `5xx`
+    #[error("NetBox unavailable: {0}")]
+    Unavailable(String),
+}
+
+impl From<NetBoxError> for PortError {
+    fn from(e: NetBoxError) -> Self {
+        match e {
+            NetBoxError::Http(msg) => PortError::ConnectionFailed(msg),
+            NetBoxError::Timeout(msg) => PortError::Timeout(msg),
+            NetBoxError::Auth(msg) => PortError::AuthenticationFailed(msg),
+            NetBoxError::RateLimited(retry_after) => PortError::RateLimited { retry_after },
+            NetBoxError::Unavailable(msg) => PortError::Transient(msg),
+            NetBoxError::NotFound(msg) => PortError::NotFound(msg),
+            e @ (NetBoxError::Api(_) | NetBoxError::Parse(_) | NetBoxError::Validation(_)) => {
+                PortError::InventoryError(e.to_string())
+            }
+        }
+    }
 }
@@ -0,0 +1,148 @@
+//! OPNsense API HTTP client
+//!
+//! Talks to the OPNsense REST API (`/api/...`) using an API key and secret
+//! as HTTP basic authentication. Firewalls are addressed by base URL, so one
+//! client serves every firewall sharing the same key.
+
+use super::types::*;
+use reqwest::{Client, Method};
+use std::time::Duration;
+
+/// OPNsense API client
+pub struct OpnSenseClient {
+    /// HTTP client
+    http: Client,
+    /// API key
+    api_key: String,
+    /// API secret
+    api_secret: String,
+}
+
+impl OpnSenseClient {
+    /// Create a new OPNsense client
+    pub fn new(api_key: &str, api_secret: &str) -> Result<Self, OpnSenseError> {
+        let http = Client::builder()
+            // The web GUI ships with a self-signed certificate
+            .danger_accept_invalid_certs(true)
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| OpnSenseError::Http(e.to_string()))?;
+
+        Ok(Self {
+            http,
+            api_key: api_key.to_string(),
+            api_secret: api_secret.to_string(),
+        })
+    }
+
+    // =========================================================================
+    // System
+    // =========================================================================
+
+    /// Get the installed product and version
+    pub async fn get_firmware_status(&self, host: &str) -> Result<OpnSenseFirmwareStatus, OpnSenseError> {
+        self.request(Method::GET, host, "core/firmware/status", None).await
+    }
+
+    /// Get the hostname
+    pub async fn get_system_information(&self, host: &str) -> Result<OpnSenseSystemInformation, OpnSenseError> {
+        self.request(Method::GET, host, "diagnostics/system/systemInformation", None).await
+    }
+
+    /// Get memory usage
+    pub async fn get_system_resources(&self, host: &str) -> Result<OpnSenseSystemResources, OpnSenseError> {
+        self.request(Method::GET, host, "diagnostics/system/systemResources", None).await
+    }
+
+    /// Get uptime
+    pub async fn get_system_time(&self, host: &str) -> Result<OpnSenseSystemTime, OpnSenseError> {
+        self.request(Method::GET, host, "diagnostics/system/systemTime", None).await
+    }
+
+    /// Get the `top` summary, which carries CPU load
+    pub async fn get_activity(&self, host: &str) -> Result<OpnSenseActivity, OpnSenseError> {
+        self.request(Method::GET, host, "diagnostics/activity/getActivity", None).await
+    }
+
+    /// Reboot the firewall
+    pub async fn reboot(&self, host: &str) -> Result<(), OpnSenseError> {
+        self.post(host, "core/system/reboot", &serde_json::json!({})).await.map(|_| ())
+    }
+
+    // =========================================================================
+    // Interfaces
+    // =========================================================================
+
+    /// List all interfaces with their counters
+    pub async fn list_interfaces(&self, host: &str) -> Result<OpnSenseInterfaces, OpnSenseError> {
+        self.request(Method::GET, host, "interfaces/overview/interfacesInfo", None).await
+    }
+
+    // =========================================================================
+    // HTTP Helpers
+    // =========================================================================
+
+    /// POST a change and check the result OPNsense reports in the body
+    ///
+    /// Model endpoints answer `200 OK` even when validation fails, with
+    /// `{"result":"failed","validations":{...}}` in the body.
+    pub async fn post(
+        &self,
+        host: &str,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<serde_json::Value, OpnSenseError> {
+        let response: serde_json::Value = self.request(Method::POST, host, path, Some(body)).await?;
+
+        if response.get("result").and_then(|r| r.as_str()) == Some("failed") {
+            let detail = response
+                .get("validations")
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| response.to_string());
+            return Err(OpnSenseError::Api(format!("{}: {}", path, detail)));
+        }
+
+        Ok(response)
+    }
+
+    /// Send a request to `{host}/api/{path}`
+    pub async fn request<T: serde::de::DeserializeOwned>(
+        &self,
+        method: Method,
+        host: &str,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<T, OpnSenseError> {
+        let url = format!("{}/api/{}", host.trim_end_matches('/'), path.trim_start_matches('/'));
+        tracing::debug!("OPNsense {} {}", method, url);
+
+        let mut request = self.http
+            .request(method, &url)
+            .basic_auth(&self.api_key, Some(&self.api_secret))
+            .header("Accept", "application/json");
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| OpnSenseError::Http(e.to_string()))?;
+
+        let status = response.status();
+        let text = response.text().await.map_err(|e| OpnSenseError::Http(e.to_string()))?;
+
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            return Err(OpnSenseError::Auth(format!("Rejected by {}", host)));
+        }
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(OpnSenseError::NotFound(url));
+        }
+        if !status.is_success() {
+            return Err(OpnSenseError::Api(format!("{} {}: {}", status, path, text)));
+        }
+
+        let text = if text.trim().is_empty() { "null" } else { text.as_str() };
+        serde_json::from_str(text).map_err(|e| OpnSenseError::Parse(format!("{}: {}", path, e)))
+    }
+}
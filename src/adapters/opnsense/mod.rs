@@ -0,0 +1,528 @@
+//! # OPNsense Adapter
+//!
+//! Implements `DeviceControlPort` for OPNsense firewalls through the
+//! OPNsense REST API. (pfSense has no comparable built-in API and is not
+//! supported.)
+//!
+//! ## Supported Operations
+//!
+//! - Inventory of the configured firewalls, each reported as a gateway
+//! - VLAN changes from a typed `DeviceConfigRequest`
+//! - VLAN and firewall rule changes from a raw config payload
+//! - Reboot
+//! - CPU, memory, uptime and interface statistics
+//!
+//! ## Device Identity
+//!
+//! Every firewall is addressed directly by the base URL of its web GUI
+//! (e.g. `https://192.0.2.1`). That base URL is the vendor ID; the
+//! firewall's MAC is taken from its LAN interface.
+//!
+//! ## Config Payload
+//!
+//! `apply_config` creates the request's VLANs, each tagged on the interface
+//! whose `vlan_id` names it; hostname, NTP, DNS and interface addressing are
+//! not managed through this API and are rejected as `NotSupported`.
+//!
+//! `apply_raw_config` accepts `vlans` and `firewall_rules` lists, either at the
+//! top level or under a `config` key. Entries with a `uuid` update an
+//! existing item, others are added. Each section is applied (`reconfigure`
+//! for VLANs, `apply` for rules) once all of its items are saved:
+//!
+//! ```json
+//! {
+//!     "vlans": [{ "vlan": { "if": "igb1", "tag": "20", "descr": "IoT" } }],
+//!     "firewall_rules": [{
+//!         "uuid": "c0f9...",
+//!         "rule": { "action": "block", "interface": "opt1", "destination_net": "lan" }
+//!     }]
+//! }
+//! ```
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::domain::ports::*;
+use crate::domain::value_objects::*;
+
+mod client;
+mod types;
+
+pub use client::OpnSenseClient;
+pub use types::*;
+
+/// OPNsense adapter
+pub struct OpnSenseAdapter {
+    /// API client shared by all firewalls
+    client: Arc<OpnSenseClient>,
+    /// Base URLs of the managed firewalls
+    hosts: Vec<String>,
+    /// Whether every firewall has been reached
+    connected: AtomicBool,
+    /// Mapping from domain DeviceId to vendor ID (base URL)
+    device_mapping: Arc<RwLock<HashMap<DeviceId, String>>>,
+    /// Mapping from vendor ID to domain DeviceId
+    reverse_mapping: Arc<RwLock<HashMap<String, DeviceId>>>,
+}
+
+impl OpnSenseAdapter {
+    /// Create an adapter for firewalls sharing one API key
+    ///
+    /// # Arguments
+    /// * `hosts` - Web GUI base URLs, e.g. `["https://192.0.2.1"]`
+    /// * `api_key` / `api_secret` - Key pair from System > Access > Users
+    pub fn new(hosts: &[&str], api_key: &str, api_secret: &str) -> Result<Self, OpnSenseError> {
+        Ok(Self {
+            client: Arc::new(OpnSenseClient::new(api_key, api_secret)?),
+            hosts: hosts.iter().map(|h| h.trim_end_matches('/').to_string()).collect(),
+            connected: AtomicBool::new(false),
+            device_mapping: Arc::new(RwLock::new(HashMap::new())),
+            reverse_mapping: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Map a domain device to its vendor ID (base URL)
+    pub async fn map_device(&self, device_id: DeviceId, vendor_id: String) {
+        let mut mapping = self.device_mapping.write().await;
+        let mut reverse = self.reverse_mapping.write().await;
+        mapping.insert(device_id, vendor_id.clone());
+        reverse.insert(vendor_id, device_id);
+    }
+
+    /// Get vendor ID for a domain device
+    pub async fn get_vendor_id(&self, device_id: DeviceId) -> Option<String> {
+        let mapping = self.device_mapping.read().await;
+        mapping.get(&device_id).cloned()
+    }
+
+    /// Get domain device ID for a vendor ID
+    pub async fn get_device_id(&self, vendor_id: &str) -> Option<DeviceId> {
+        let reverse = self.reverse_mapping.read().await;
+        reverse.get(vendor_id).copied()
+    }
+
+    /// Resolve a vendor ID to one of the configured hosts
+    fn host(&self, vendor_id: &str) -> Result<&str, OpnSenseError> {
+        let vendor_id = vendor_id.trim_end_matches('/');
+        self.hosts
+            .iter()
+            .find(|h| *h == vendor_id)
+            .map(String::as_str)
+            .ok_or_else(|| OpnSenseError::NotFound(format!("Firewall {} is not configured", vendor_id)))
+    }
+
+    /// Read one firewall's product, hostname and interfaces
+    async fn read_device(&self, host: &str) -> Result<VendorDevice, OpnSenseError> {
+        let firmware = self.client.get_firmware_status(host).await?;
+        let system = self.client.get_system_information(host).await?;
+        let interfaces = self.client.list_interfaces(host).await?;
+
+        let mac = firewall_mac(&interfaces.rows)
+            .ok_or_else(|| OpnSenseError::Parse(format!("No interface with a MAC address on {}", host)))?;
+        let product = firmware.product.unwrap_or_default();
+
+        let mut properties = HashMap::new();
+        if let Some(ref version) = product.product_version {
+            properties.insert("firmware_version".to_string(), serde_json::json!(version));
+        }
+        properties.insert("interface_count".to_string(), serde_json::json!(interfaces.rows.len()));
+
+        Ok(VendorDevice {
+            vendor_id: host.to_string(),
+            device_id: self.get_device_id(host).await,
+            mac,
+            model: product.product_name.unwrap_or_else(|| "OPNsense".to_string()),
+            name: system.name.unwrap_or_else(|| host.to_string()),
+            ip_address: host_ip(host),
+            // Firewalls are managed directly; there is no adoption step
+            adopted: true,
+            properties,
+        })
+    }
+}
+
+/// MAC of the LAN interface, else of the first enabled interface that has one
+fn firewall_mac(interfaces: &[OpnSenseInterface]) -> Option<MacAddress> {
+    let mac_of = |iface: &OpnSenseInterface| iface.macaddr.as_deref().and_then(|m| MacAddress::parse(m).ok());
+
+    interfaces
+        .iter()
+        .filter(|iface| iface.identifier.as_deref() == Some("lan"))
+        .find_map(mac_of)
+        .or_else(|| interfaces.iter().filter(|iface| iface.enabled).find_map(mac_of))
+}
+
+/// IP address in a base URL such as `https://192.0.2.1:8443`
+fn host_ip(host: &str) -> Option<std::net::IpAddr> {
+    let authority = host.split("://").last()?.split('/').next()?;
+    authority.parse::<std::net::SocketAddr>()
+        .map(|addr| addr.ip())
+        .or_else(|_| authority.parse())
+        .ok()
+}
+
+/// One API call derived from a config payload
+#[derive(Debug, Clone, PartialEq)]
+struct ApiCall {
+    path: String,
+    body: serde_json::Value,
+}
+
+/// A config payload section: item key, settings endpoint and how to activate it
+struct Section {
+    /// Payload list, e.g. `vlans`
+    list: &'static str,
+    /// Key wrapping each item, e.g. `vlan`
+    item: &'static str,
+    /// Endpoint to add an item
+    add: &'static str,
+    /// Endpoint to update an item, followed by its UUID
+    set: &'static str,
+    /// Endpoint that activates saved changes
+    activate: &'static str,
+}
+
+/// Supported payload sections, in the order they are applied
+const SECTIONS: &[Section] = &[
+    Section {
+        list: "vlans",
+        item: "vlan",
+        add: "interfaces/vlan_settings/addItem",
+        set: "interfaces/vlan_settings/setItem",
+        activate: "interfaces/vlan_settings/reconfigure",
+    },
+    Section {
+        list: "firewall_rules",
+        item: "rule",
+        add: "firewall/filter/addRule",
+        set: "firewall/filter/setRule",
+        activate: "firewall/filter/apply",
+    },
+];
+
+/// Extract API calls from a vendor config payload
+fn config_calls(config: &VendorConfig) -> Result<Vec<ApiCall>, OpnSenseError> {
+    let source = config.payload.get("config").unwrap_or(&config.payload);
+    let mut calls = Vec::new();
+
+    for section in SECTIONS {
+        let Some(items) = source.get(section.list) else {
+            continue;
+        };
+        let items = items.as_array()
+            .ok_or_else(|| OpnSenseError::Parse(format!("'{}' must be a list", section.list)))?;
+
+        for entry in items {
+            let item = entry.get(section.item).ok_or_else(|| {
+                OpnSenseError::Parse(format!("'{}' entry is missing '{}'", section.list, section.item))
+            })?;
+            let path = match entry.get("uuid").and_then(|u| u.as_str()) {
+                Some(uuid) => format!("{}/{}", section.set, uuid),
+                None => section.add.to_string(),
+            };
+            calls.push(ApiCall { path, body: serde_json::json!({ (section.item): item }) });
+        }
+        if !items.is_empty() {
+            calls.push(ApiCall { path: section.activate.to_string(), body: serde_json::json!({}) });
+        }
+    }
+
+    if calls.is_empty() {
+        return Err(OpnSenseError::Parse(format!(
+            "Unsupported config payload for type '{}'",
+            config.config_type
+        )));
+    }
+    Ok(calls)
+}
+
+/// Translate a typed config request into an OPNsense config payload
+fn request_payload(request: &DeviceConfigRequest) -> Result<serde_json::Value, PortError> {
+    let unsupported = [
+        (request.hostname.is_some(), "hostname"),
+        (!request.ntp_servers.is_empty(), "NTP servers"),
+        (!request.dns_servers.is_empty(), "DNS servers"),
+        (request.interfaces.iter().any(|i| i.ip_address.is_some()), "interface addresses"),
+        (!request.lags.is_empty(), "LAGs"),
+    ];
+    if let Some((_, setting)) = unsupported.iter().find(|(requested, _)| *requested) {
+        return Err(PortError::NotSupported(format!("OPNsense adapter cannot set {}", setting)));
+    }
+
+    let vlans = request.vlans
+        .iter()
+        .map(|vlan| {
+            let parent = request.interfaces
+                .iter()
+                .find(|iface| iface.vlan_id == Some(vlan.id))
+                .ok_or_else(|| port_error(OpnSenseError::Parse(format!(
+                    "VLAN {} needs an interface with vlan_id {} to tag it on",
+                    vlan.id, vlan.id
+                ))))?;
+            Ok(serde_json::json!({ "vlan": {
+                "if": parent.name,
+                "tag": vlan.id.to_string(),
+                "descr": vlan.name,
+            } }))
+        })
+        .collect::<Result<Vec<_>, PortError>>()?;
+
+    Ok(serde_json::json!({ "vlans": vlans }))
+}
+
+/// Map OPNsense errors onto port errors
+fn port_error(error: OpnSenseError) -> PortError {
+    match error {
+        OpnSenseError::Auth(msg) => PortError::AuthenticationFailed(msg),
+        OpnSenseError::Http(msg) => PortError::ConnectionFailed(msg),
+        other => PortError::VendorError(other.to_string()),
+    }
+}
+
+#[async_trait]
+impl DeviceControlPort for OpnSenseAdapter {
+    fn vendor_name(&self) -> &str {
+        "opnsense"
+    }
+
+    async fn connect(&self) -> Result<(), PortError> {
+        for host in &self.hosts {
+            self.client.get_firmware_status(host).await.map_err(port_error)?;
+        }
+        self.connected.store(true, Ordering::SeqCst);
+        tracing::info!("Connected to {} OPNsense firewalls", self.hosts.len());
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> Result<(), PortError> {
+        // REST is stateless; nothing to tear down
+        self.connected.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError> {
+        let mut vendor_devices = Vec::new();
+        for host in &self.hosts {
+            match self.read_device(host).await {
+                Ok(device) => vendor_devices.push(device),
+                // An unreachable firewall is simply not reported
+                Err(OpnSenseError::Http(e)) => tracing::warn!("OPNsense firewall {} unreachable: {}", host, e),
+                Err(e) => return Err(port_error(e)),
+            }
+        }
+        Ok(vendor_devices)
+    }
+
+    async fn get_device(&self, vendor_id: &str) -> Result<VendorDevice, PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        self.read_device(host).await.map_err(port_error)
+    }
+
+    async fn adopt_device(&self, vendor_id: &str) -> Result<(), PortError> {
+        // No controller adoption; just verify we can manage the firewall
+        let host = self.host(vendor_id).map_err(port_error)?;
+        self.client.get_firmware_status(host).await.map(|_| ()).map_err(port_error)
+    }
+
+    async fn apply_config(&self, vendor_id: &str, config: DeviceConfigRequest) -> Result<(), PortError> {
+        let payload = request_payload(&config)?;
+        self.apply_raw_config(vendor_id, VendorConfig {
+            config_type: "opnsense".to_string(),
+            payload,
+        })
+        .await
+    }
+
+    async fn apply_raw_config(&self, vendor_id: &str, config: VendorConfig) -> Result<(), PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        let calls = config_calls(&config).map_err(port_error)?;
+
+        for call in &calls {
+            self.client.post(host, &call.path, &call.body).await.map_err(port_error)?;
+        }
+
+        tracing::info!("Applied {} OPNsense config calls to {}", calls.len(), host);
+        Ok(())
+    }
+
+    async fn restart_device(&self, vendor_id: &str) -> Result<(), PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        self.client.reboot(host).await.map_err(port_error)
+    }
+
+    async fn get_device_stats(&self, vendor_id: &str) -> Result<DeviceStats, PortError> {
+        let host = self.host(vendor_id).map_err(port_error)?;
+        let resources = self.client.get_system_resources(host).await.map_err(port_error)?;
+        let time = self.client.get_system_time(host).await.map_err(port_error)?;
+        let interfaces = self.client.list_interfaces(host).await.map_err(port_error)?;
+
+        // CPU load comes from the activity view, which older releases lack
+        let cpu_percent = match self.client.get_activity(host).await {
+            Ok(activity) => activity.cpu_percent(),
+            Err(e) => {
+                tracing::debug!("No CPU load from {}: {}", host, e);
+                None
+            }
+        };
+
+        Ok(DeviceStats {
+            uptime_seconds: time.uptime.as_deref().and_then(parse_uptime_seconds).unwrap_or(0),
+            cpu_percent,
+            memory_percent: resources.memory.used_percent(),
+            temperature_celsius: None,
+            port_stats: interfaces.rows.iter().map(|iface| PortStats {
+                port_id: PortId::new(iface.device.clone()),
+                link_up: iface.is_up(),
+                speed: iface.media.as_deref().and_then(parse_media_mbps).and_then(LinkSpeed::from_mbps),
+                rx_bytes: iface.counter("bytes received"),
+                tx_bytes: iface.counter("bytes transmitted"),
+                rx_errors: iface.counter("input errors"),
+                tx_errors: iface.counter("output errors"),
+                rx_bps: None,
+                tx_bps: None,
+            }).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ==========================================================================
+    // Parser Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_parse_uptime() {
+        assert_eq!(parse_uptime_seconds("21 days, 05:14:49"), Some(21 * 86400 + 5 * 3600 + 14 * 60 + 49));
+        assert_eq!(parse_uptime_seconds("1 day, 00:02"), Some(86400 + 2 * 60));
+        assert_eq!(parse_uptime_seconds("05:14:49"), Some(5 * 3600 + 14 * 60 + 49));
+        assert_eq!(parse_uptime_seconds("soon"), None);
+    }
+
+    #[test]
+    fn test_parse_cpu_percent() {
+        let line = "CPU:  2.0% user,  0.0% nice,  1.5% system,  0.1% interrupt, 96.4% idle";
+        assert_eq!(parse_cpu_percent(line), Some(3.6));
+        assert_eq!(parse_cpu_percent("CPU: busy"), None);
+    }
+
+    #[test]
+    fn test_parse_media_mbps() {
+        assert_eq!(parse_media_mbps("1000baseT <full-duplex>"), Some(1000));
+        assert_eq!(parse_media_mbps("2500Base-T <full-duplex>"), Some(2500));
+        assert_eq!(parse_media_mbps("10Gbase-SR <full-duplex>"), Some(10000));
+        assert_eq!(parse_media_mbps("autoselect"), None);
+    }
+
+    #[test]
+    fn test_value_u64_accepts_numbers_and_strings() {
+        assert_eq!(value_u64(&serde_json::json!(42)), Some(42));
+        assert_eq!(value_u64(&serde_json::json!("42")), Some(42));
+        assert_eq!(value_u64(&serde_json::json!(null)), None);
+    }
+
+    #[test]
+    fn test_firewall_mac_prefers_lan() {
+        let interfaces = vec![
+            OpnSenseInterface {
+                device: "igb0".to_string(),
+                identifier: Some("wan".to_string()),
+                macaddr: Some("00:0d:b9:00:00:01".to_string()),
+                enabled: true,
+                ..Default::default()
+            },
+            OpnSenseInterface {
+                device: "igb1".to_string(),
+                identifier: Some("lan".to_string()),
+                macaddr: Some("00:0d:b9:00:00:02".to_string()),
+                enabled: true,
+                ..Default::default()
+            },
+        ];
+        assert_eq!(firewall_mac(&interfaces), Some(MacAddress::parse("00:0d:b9:00:00:02").unwrap()));
+    }
+
+    // ==========================================================================
+    // Config Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_config_calls_add_update_and_activate() {
+        let config = VendorConfig {
+            config_type: "opnsense".to_string(),
+            payload: serde_json::json!({ "config": {
+                "firewall_rules": [
+                    { "rule": { "action": "pass", "interface": "lan" } },
+                    { "uuid": "abc", "rule": { "action": "block" } },
+                ],
+                "vlans": [{ "vlan": { "if": "igb1", "tag": "20" } }],
+            } }),
+        };
+
+        let paths: Vec<_> = config_calls(&config).unwrap().into_iter().map(|c| c.path).collect();
+        assert_eq!(paths, [
+            "interfaces/vlan_settings/addItem",
+            "interfaces/vlan_settings/reconfigure",
+            "firewall/filter/addRule",
+            "firewall/filter/setRule/abc",
+            "firewall/filter/apply",
+        ]);
+    }
+
+    #[test]
+    fn test_config_calls_rejects_unknown_payload() {
+        let config = VendorConfig {
+            config_type: "opnsense".to_string(),
+            payload: serde_json::json!({ "routes": [] }),
+        };
+        assert!(matches!(config_calls(&config), Err(OpnSenseError::Parse(_))));
+
+        let config = VendorConfig {
+            config_type: "opnsense".to_string(),
+            payload: serde_json::json!({ "vlans": [{ "tag": "20" }] }),
+        };
+        assert!(matches!(config_calls(&config), Err(OpnSenseError::Parse(msg)) if msg.contains("'vlan'")));
+    }
+
+    #[test]
+    fn test_typed_request_to_opnsense_payload() {
+        let request = DeviceConfigRequest::default()
+            .with_vlan(VlanConfig::new(20, "IoT").unwrap())
+            .with_interface(InterfaceConfig {
+                name: "igb1".to_string(),
+                ip_address: None,
+                prefix_len: None,
+                vlan_id: Some(20),
+                enabled: true,
+                mtu: None,
+                speed: None,
+            });
+
+        let payload = request_payload(&request).unwrap();
+        assert_eq!(payload, serde_json::json!({ "vlans": [
+            { "vlan": { "if": "igb1", "tag": "20", "descr": "IoT" } },
+        ] }));
+
+        let config = VendorConfig { config_type: "opnsense".to_string(), payload };
+        assert_eq!(config_calls(&config).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_typed_request_rejects_unmanaged_settings() {
+        let request = DeviceConfigRequest::default().with_hostname("fw1");
+        assert!(matches!(request_payload(&request), Err(PortError::NotSupported(msg)) if msg.contains("hostname")));
+
+        // A VLAN needs a parent interface to be tagged on
+        let request = DeviceConfigRequest::default().with_vlan(VlanConfig::new(20, "IoT").unwrap());
+        assert!(matches!(request_payload(&request), Err(PortError::VendorError(msg)) if msg.contains("VLAN 20")));
+    }
+}
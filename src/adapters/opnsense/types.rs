@@ -0,0 +1,204 @@
+//! OPNsense API types
+//!
+//! OPNsense reports counters and sizes inconsistently, sometimes as JSON
+//! numbers and sometimes as strings, so such fields are kept as raw JSON
+//! values and converted through [`value_u64`].
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// `/api/core/firmware/status`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpnSenseFirmwareStatus {
+    /// Installed product
+    #[serde(default)]
+    pub product: Option<OpnSenseProduct>,
+}
+
+/// Installed product as reported by the firmware status
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpnSenseProduct {
+    /// Product name, e.g. `OPNsense`
+    pub product_name: Option<String>,
+    /// Product version, e.g. `24.1.5`
+    pub product_version: Option<String>,
+}
+
+/// `/api/diagnostics/system/systemInformation`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpnSenseSystemInformation {
+    /// Hostname including domain, e.g. `fw1.example.net`
+    pub name: Option<String>,
+}
+
+/// `/api/diagnostics/system/systemResources`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpnSenseSystemResources {
+    /// Memory usage
+    #[serde(default)]
+    pub memory: OpnSenseMemory,
+}
+
+/// Memory section of the system resources
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpnSenseMemory {
+    /// Total memory in bytes
+    #[serde(default)]
+    pub total: serde_json::Value,
+    /// Used memory in bytes
+    #[serde(default)]
+    pub used: serde_json::Value,
+}
+
+impl OpnSenseMemory {
+    /// Used memory in percent
+    pub fn used_percent(&self) -> Option<f64> {
+        let total = value_u64(&self.total)? as f64;
+        let used = value_u64(&self.used)? as f64;
+        (total > 0.0).then(|| used / total * 100.0)
+    }
+}
+
+/// `/api/diagnostics/system/systemTime`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpnSenseSystemTime {
+    /// Uptime, e.g. `21 days, 05:14:49`
+    pub uptime: Option<String>,
+}
+
+/// `/api/diagnostics/activity/getActivity`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpnSenseActivity {
+    /// `top` header lines, including the `CPU:` summary
+    #[serde(default)]
+    pub headers: Vec<String>,
+}
+
+impl OpnSenseActivity {
+    /// CPU load in percent, from the idle share of the `CPU:` header
+    pub fn cpu_percent(&self) -> Option<f64> {
+        self.headers
+            .iter()
+            .find(|line| line.trim_start().starts_with("CPU:"))
+            .and_then(|line| parse_cpu_percent(line))
+    }
+}
+
+/// `/api/interfaces/overview/interfacesInfo`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpnSenseInterfaces {
+    /// One row per interface
+    #[serde(default)]
+    pub rows: Vec<OpnSenseInterface>,
+}
+
+/// Interface overview row
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpnSenseInterface {
+    /// Operating system device, e.g. `igb0`
+    pub device: String,
+    /// Logical interface, e.g. `lan`, `wan`, `opt1`
+    pub identifier: Option<String>,
+    /// User description, e.g. `LAN`
+    pub description: Option<String>,
+    /// Link status, e.g. `up`, `down`, `no carrier`
+    pub status: Option<String>,
+    /// MAC address
+    pub macaddr: Option<String>,
+    /// Whether the interface is enabled
+    #[serde(default)]
+    pub enabled: bool,
+    /// Negotiated media, e.g. `1000baseT <full-duplex>`
+    pub media: Option<String>,
+    /// Counters keyed by their `netstat` names, e.g. `bytes received`
+    #[serde(default)]
+    pub statistics: HashMap<String, serde_json::Value>,
+}
+
+impl OpnSenseInterface {
+    /// Whether the link is up
+    pub fn is_up(&self) -> bool {
+        self.status.as_deref().is_some_and(|s| s.eq_ignore_ascii_case("up"))
+    }
+
+    /// Counter by name, zero when absent
+    pub fn counter(&self, name: &str) -> u64 {
+        self.statistics.get(name).and_then(value_u64).unwrap_or(0)
+    }
+}
+
+/// OPNsense adapter error
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum OpnSenseError {
+    /// HTTP/network error
+    #[error("HTTP error: {0}")]
+    Http(String),
+    /// API key or secret was rejected
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+    /// OPNsense rejected the request
+    #[error("API error: {0}")]
+    Api(String),
+    /// Response or config payload could not be parsed
+    #[error("Parse error: {0}")]
+    Parse(String),
+    /// No such firewall or resource
+    #[error("Not found: {0}")]
+    NotFound(String),
+}
+
+/// Read a JSON number or numeric string
+pub fn value_u64(value: &serde_json::Value) -> Option<u64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_u64(),
+        serde_json::Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Parse an uptime such as `21 days, 05:14:49`, `1 day, 00:02` or `05:14:49` into seconds
+pub fn parse_uptime_seconds(uptime: &str) -> Option<u64> {
+    let (days, clock) = match uptime.split_once(',') {
+        Some((days, clock)) => {
+            let count = days.split_whitespace().next()?.parse::<u64>().ok()?;
+            (count, clock.trim())
+        }
+        None => (0, uptime.trim()),
+    };
+
+    let parts = clock
+        .split(':')
+        .map(|p| p.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [h, m, s] => (*h, *m, *s),
+        [h, m] => (*h, *m, 0),
+        _ => return None,
+    };
+
+    Some(days * 86400 + hours * 3600 + minutes * 60 + seconds)
+}
+
+/// Parse a `top` CPU line such as `CPU:  2.0% user, 0.0% nice, 1.5% system, 0.1% interrupt, 96.4% idle`
+pub fn parse_cpu_percent(line: &str) -> Option<f64> {
+    let idle = line
+        .split(',')
+        .find_map(|part| part.trim().strip_suffix("idle"))?
+        .trim()
+        .trim_end_matches('%')
+        .parse::<f64>()
+        .ok()?;
+    Some(((100.0 - idle) * 10.0).round() / 10.0)
+}
+
+/// Parse a media description such as `1000baseT <full-duplex>` or `10Gbase-SR` into Mbps
+pub fn parse_media_mbps(media: &str) -> Option<u32> {
+    let lower = media.trim().to_ascii_lowercase();
+    let rate = lower.split("base").next()?;
+    let (value, multiplier) = match rate.strip_suffix('g') {
+        Some(gbps) => (gbps, 1000.0),
+        None => (rate, 1.0),
+    };
+    let mbps = value.parse::<f64>().ok()? * multiplier;
+    Some(mbps.round() as u32)
+}
@@ -0,0 +1,160 @@
+//! # HTTP Retry Policy
+//!
+//! Shared retry-with-backoff for the REST adapters. Controllers and
+//! inventories rate-limit bulk discovery and sync, answering `429 Too Many
+//! Requests` or a transient `5xx`; such responses are retried with
+//! exponential backoff, honouring `Retry-After` when the server sends one.
+//! Every other response, including `404`, is returned on the first attempt.
+
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::time::Duration;
+
+/// How often and how patiently to retry throttled or transiently failing requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts per request, including the first (at least 1)
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled for each further retry
+    pub base_delay: Duration,
+    /// Upper bound on any single delay, including one asked for by `Retry-After`
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Retry up to `max_attempts` attempts in total, starting at `base_delay`
+    pub fn new(max_attempts: u32, base_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            base_delay,
+            ..Self::default()
+        }
+    }
+
+    /// Send every request exactly once
+    pub fn none() -> Self {
+        Self::new(1, Duration::ZERO)
+    }
+
+    /// Delay before retry number `retry` (1-based), ignoring `Retry-After`
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Send the request built by `build`, retrying while the policy allows
+    ///
+    /// `build` is called once per attempt, since a sent request is consumed.
+    /// Transport errors are returned immediately; after the last attempt the
+    /// final (still failing) response is returned for the caller to map.
+    pub async fn send<F>(&self, build: F) -> Result<Response, reqwest::Error>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        let mut attempt = 1;
+        loop {
+            let response = build().send().await?;
+            let status = response.status();
+            if !is_retryable(status) || attempt >= self.max_attempts {
+                return Ok(response);
+            }
+
+            let delay = retry_after(&response)
+                .unwrap_or_else(|| self.backoff(attempt))
+                .min(self.max_delay);
+            tracing::warn!(
+                "{} answered {}; retrying in {:?} (attempt {} of {})",
+                response.url(),
+                status,
+                delay,
+                attempt + 1,
+                self.max_attempts
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Whether a response status is worth retrying: throttling or a transient server error
+pub fn is_retryable(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Delay requested by a response's `Retry-After` header
+pub fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| parse_retry_after(value, chrono::Utc::now()))
+}
+
+/// Parse `Retry-After` as delay-seconds or an HTTP date relative to `now`
+pub fn parse_retry_after(value: &str, now: chrono::DateTime<chrono::Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past means "now"
+    Some((at.with_timezone(&chrono::Utc) - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_backoff_doubles_up_to_max_delay() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+        assert_eq!(policy.backoff(64), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable(StatusCode::NOT_FOUND));
+        assert!(!is_retryable(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable(StatusCode::UNAUTHORIZED));
+        assert!(!is_retryable(StatusCode::OK));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = chrono::Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap();
+
+        assert_eq!(parse_retry_after("120", now), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now), Some(Duration::from_secs(30)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+}
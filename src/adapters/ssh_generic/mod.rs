@@ -0,0 +1,374 @@
+//! # Generic SSH Adapter
+//!
+//! Implements network management ports for devices that only speak a CLI
+//! over SSH, by screen-scraping. What to run and how to read the output is
+//! described by a `CommandProfile`, so a new device family needs a profile
+//! rather than Rust code.
+//!
+//! ## Supported Operations
+//!
+//! Whatever the profile covers, out of:
+//!
+//! - Inventory of the configured hosts (`list_devices`)
+//! - Uptime, CPU, memory, temperature and port statistics (`get_device_stats`)
+//! - Configuration rendered from templates (`apply_config`)
+//! - Restart (`restart_device`)
+//!
+//! Operations the profile leaves out report `NotSupported`.
+//!
+//! ## Profiles
+//!
+//! - `CommandProfile::linux()` - generic Linux hosts (`ip -j link`, `/proc`)
+//! - `CommandProfile::custom(name)` - a blank profile to fill in or load
+//!   from JSON
+//!
+//! ## Device Identity
+//!
+//! Every host is addressed directly; the host name or address given to the
+//! adapter is the vendor ID. The device's MAC is whatever the profile's
+//! identify pattern captures.
+//!
+//! ## Config Payload
+//!
+//! `apply_raw_config` runs the commands it is given, as a string or a list of
+//! strings, bare or under a `config` key:
+//!
+//! ```json
+//! { "config": ["hostnamectl set-hostname edge-02"] }
+//! ```
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::adapters::cisco::{CiscoError, CliTransport, SshCredentials, SshTransport};
+use crate::domain::ports::*;
+use crate::domain::functor::*;
+use crate::domain::events::*;
+use crate::domain::value_objects::*;
+
+mod profile;
+
+pub use profile::{
+    CommandProfile, ConfigCommands, DeviceIdentity, IdentifyCommands, PortRule, ProfileError,
+    StatField, StatRule, StatValue, StatsCommands,
+};
+
+/// Generic SSH adapter driven by a command profile
+///
+/// Implements both:
+/// - `DeviceControlPort` for hexagonal architecture
+/// - `VendorExtension` for Kan extension mapping
+pub struct GenericSshAdapter {
+    /// Commands and parsers for the device family
+    profile: CommandProfile,
+    /// CLI transport (SSH in production)
+    transport: Arc<dyn CliTransport>,
+    /// Hosts of the managed devices
+    hosts: Vec<String>,
+    /// Whether every host has been reached
+    connected: AtomicBool,
+    /// Mapping from domain DeviceId to vendor ID (host)
+    device_mapping: Arc<RwLock<HashMap<DeviceId, String>>>,
+    /// Mapping from vendor ID to domain DeviceId
+    reverse_mapping: Arc<RwLock<HashMap<String, DeviceId>>>,
+}
+
+impl GenericSshAdapter {
+    /// Create an adapter for hosts sharing one set of SSH credentials
+    ///
+    /// Fails if any of the profile's patterns does not compile.
+    pub fn new(profile: CommandProfile, hosts: &[&str], credentials: SshCredentials) -> Result<Self, ProfileError> {
+        Self::with_transport(profile, hosts, Arc::new(SshTransport::new(credentials)))
+    }
+
+    /// Create an adapter with a custom CLI transport
+    pub fn with_transport(
+        profile: CommandProfile,
+        hosts: &[&str],
+        transport: Arc<dyn CliTransport>,
+    ) -> Result<Self, ProfileError> {
+        profile.validate()?;
+        Ok(Self {
+            profile,
+            transport,
+            hosts: hosts.iter().map(|h| h.to_string()).collect(),
+            connected: AtomicBool::new(false),
+            device_mapping: Arc::new(RwLock::new(HashMap::new())),
+            reverse_mapping: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Profile the adapter runs
+    pub fn profile(&self) -> &CommandProfile {
+        &self.profile
+    }
+
+    /// Map a domain device to its vendor ID (host)
+    pub async fn map_device(&self, device_id: DeviceId, vendor_id: String) {
+        let mut mapping = self.device_mapping.write().await;
+        let mut reverse = self.reverse_mapping.write().await;
+        mapping.insert(device_id, vendor_id.clone());
+        reverse.insert(vendor_id, device_id);
+    }
+
+    /// Get vendor ID for a domain device
+    pub async fn get_vendor_id(&self, device_id: DeviceId) -> Option<String> {
+        let mapping = self.device_mapping.read().await;
+        mapping.get(&device_id).cloned()
+    }
+
+    /// Get domain device ID for a vendor ID
+    pub async fn get_device_id(&self, vendor_id: &str) -> Option<DeviceId> {
+        let reverse = self.reverse_mapping.read().await;
+        reverse.get(vendor_id).copied()
+    }
+
+    /// Resolve a vendor ID to one of the configured hosts
+    fn host(&self, vendor_id: &str) -> Result<&str, PortError> {
+        self.hosts
+            .iter()
+            .find(|h| *h == vendor_id)
+            .map(String::as_str)
+            .ok_or_else(|| PortError::VendorError(format!("Host {} is not configured", vendor_id)))
+    }
+
+    /// Run commands on a host and return their combined output
+    ///
+    /// Fails if the output matches the profile's error pattern.
+    async fn run(&self, host: &str, commands: &[String]) -> Result<String, PortError> {
+        let output = if self.profile.interactive {
+            self.transport.shell(host, commands).await.map_err(transport_error)?
+        } else {
+            let mut outputs = Vec::with_capacity(commands.len());
+            for command in commands {
+                outputs.push(self.transport.exec(host, command).await.map_err(transport_error)?);
+            }
+            outputs.join("\n")
+        };
+
+        match self.profile.find_error(&output).map_err(profile_error)? {
+            Some(message) => Err(PortError::VendorError(format!("{} rejected a command: {}", host, message))),
+            None => Ok(output),
+        }
+    }
+
+    /// Identify one host
+    async fn read_device(&self, host: &str) -> Result<VendorDevice, PortError> {
+        let identify = self.profile.list_devices
+            .as_ref()
+            .ok_or_else(|| profile_error(self.profile.unsupported("device listing")))?;
+        let output = self.run(host, &identify.commands).await?;
+        let identity = identify.parse(&output).map_err(profile_error)?;
+
+        let mut properties = HashMap::new();
+        if let Some(ref version) = identity.version {
+            properties.insert("software_version".to_string(), serde_json::json!(version));
+        }
+
+        Ok(VendorDevice {
+            vendor_id: host.to_string(),
+            device_id: self.get_device_id(host).await,
+            mac: identity.mac,
+            model: identity.model.unwrap_or_else(|| self.profile.name.clone()),
+            name: identity.name.unwrap_or_else(|| host.to_string()),
+            ip_address: host.parse().ok(),
+            // Hosts are managed directly; there is no adoption step
+            adopted: true,
+            properties,
+        })
+    }
+}
+
+/// Extract commands from a vendor config payload
+///
+/// Accepts either the `extend` payload (`{"config": [..]}`), a bare array of
+/// commands, or a single newline-separated string.
+fn config_lines(config: &VendorConfig) -> Result<Vec<String>, PortError> {
+    let source = config.payload.get("config").unwrap_or(&config.payload);
+
+    let lines: Vec<String> = match source {
+        serde_json::Value::String(text) => text.lines().map(str::to_string).collect(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(|v| {
+                v.as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| PortError::VendorError("Config lines must be strings".to_string()))
+            })
+            .collect::<Result<_, _>>()?,
+        _ => {
+            return Err(PortError::VendorError(format!(
+                "Unsupported config payload for type '{}'",
+                config.config_type
+            )))
+        }
+    };
+
+    Ok(lines.into_iter().filter(|l| !l.trim().is_empty()).collect())
+}
+
+/// Map transport errors onto port errors
+fn transport_error(error: CiscoError) -> PortError {
+    match error {
+        CiscoError::Auth(msg) => PortError::AuthenticationFailed(msg),
+        CiscoError::Ssh(msg) => PortError::ConnectionFailed(msg),
+        other => PortError::VendorError(other.to_string()),
+    }
+}
+
+/// Map profile errors onto port errors
+fn profile_error(error: ProfileError) -> PortError {
+    match error {
+        ProfileError::Unsupported { .. } => PortError::NotSupported(error.to_string()),
+        other => PortError::VendorError(other.to_string()),
+    }
+}
+
+#[async_trait]
+impl DeviceControlPort for GenericSshAdapter {
+    fn vendor_name(&self) -> &str {
+        &self.profile.name
+    }
+
+    async fn connect(&self) -> Result<(), PortError> {
+        // Sessions are per-operation; connecting checks every host can be identified
+        if self.profile.list_devices.is_some() {
+            for host in &self.hosts {
+                self.read_device(host).await?;
+            }
+        }
+        self.connected.store(true, Ordering::SeqCst);
+        tracing::info!("Connected to {} {} hosts", self.hosts.len(), self.profile.name);
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> Result<(), PortError> {
+        self.connected.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError> {
+        let mut vendor_devices = Vec::new();
+        for host in &self.hosts {
+            match self.read_device(host).await {
+                Ok(device) => vendor_devices.push(device),
+                // An unreachable host is simply not reported
+                Err(PortError::ConnectionFailed(e)) => tracing::warn!("Host {} unreachable: {}", host, e),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(vendor_devices)
+    }
+
+    async fn get_device(&self, vendor_id: &str) -> Result<VendorDevice, PortError> {
+        let host = self.host(vendor_id)?;
+        self.read_device(host).await
+    }
+
+    async fn adopt_device(&self, vendor_id: &str) -> Result<(), PortError> {
+        // No adoption step; just verify the host can be managed
+        let host = self.host(vendor_id)?;
+        match self.profile.list_devices {
+            Some(_) => self.read_device(host).await.map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    async fn apply_config(&self, vendor_id: &str, config: DeviceConfigRequest) -> Result<(), PortError> {
+        let templates = self.profile.apply_config
+            .as_ref()
+            .ok_or_else(|| profile_error(self.profile.unsupported("configuration")))?;
+        let commands = templates.render(&config).map_err(|setting| {
+            profile_error(self.profile.unsupported(&format!("configuring {}", setting)))
+        })?;
+
+        self.apply_raw_config(vendor_id, VendorConfig {
+            config_type: "cli".to_string(),
+            payload: serde_json::json!({ "config": commands }),
+        })
+        .await
+    }
+
+    async fn apply_raw_config(&self, vendor_id: &str, config: VendorConfig) -> Result<(), PortError> {
+        let host = self.host(vendor_id)?;
+        let commands = config_lines(&config)?;
+        self.run(host, &commands).await?;
+
+        tracing::info!("Applied {} commands to {}", commands.len(), host);
+        Ok(())
+    }
+
+    async fn restart_device(&self, vendor_id: &str) -> Result<(), PortError> {
+        let host = self.host(vendor_id)?;
+        if self.profile.restart_device.is_empty() {
+            return Err(profile_error(self.profile.unsupported("restarts")));
+        }
+
+        match self.run(host, &self.profile.restart_device).await {
+            // The session usually drops as the device goes down
+            Ok(_) | Err(PortError::ConnectionFailed(_)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn get_device_stats(&self, vendor_id: &str) -> Result<DeviceStats, PortError> {
+        let host = self.host(vendor_id)?;
+        let stats = self.profile.get_device_stats
+            .as_ref()
+            .ok_or_else(|| profile_error(self.profile.unsupported("statistics")))?;
+
+        let output = self.run(host, &stats.commands).await?;
+        stats.parse(&output).map_err(profile_error)
+    }
+}
+
+impl VendorExtension for GenericSshAdapter {
+    fn vendor_name(&self) -> &str {
+        &self.profile.name
+    }
+
+    fn extend(&self, domain_obj: &DomainObject) -> Result<VendorRepresentation, FunctorError> {
+        match domain_obj {
+            DomainObject::Device(device) => {
+                let request = DeviceConfigRequest::default().with_hostname(device.name());
+                let config = self.profile.apply_config
+                    .as_ref()
+                    .and_then(|templates| templates.render(&request).ok())
+                    .unwrap_or_default();
+
+                let payload = serde_json::json!({
+                    "hostname": device.name(),
+                    "mac": device.mac().to_string(),
+                    "state": device.state().name(),
+                    "config": config,
+                });
+
+                Ok(VendorRepresentation {
+                    vendor: self.profile.name.clone(),
+                    vendor_id: device.vendor_id().unwrap_or("pending").to_string(),
+                    device_id: device.id(),
+                    payload,
+                })
+            }
+            _ => Err(FunctorError::MappingFailed(format!(
+                "Only Device objects can be extended to {}",
+                self.profile.name
+            ))),
+        }
+    }
+
+    fn to_domain_event(&self, _vendor_event: &serde_json::Value) -> Result<NetworkEvent, FunctorError> {
+        // Screen-scraping is request/response only; nothing is pushed to translate
+        Err(FunctorError::MappingFailed(format!(
+            "{} devices do not emit events over SSH",
+            self.profile.name
+        )))
+    }
+}
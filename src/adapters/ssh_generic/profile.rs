@@ -0,0 +1,604 @@
+//! Command profiles for the generic SSH adapter
+//!
+//! A `CommandProfile` describes a device family entirely as data: the
+//! commands each operation runs and the regular expressions that read their
+//! output. Profiles are serde types, so a new family can be loaded from JSON
+//! (or any other serde format) without writing Rust.
+//!
+//! Each operation's commands are run in order and their outputs joined with
+//! newlines; every pattern of the operation is matched against that combined
+//! output. Values are read from named capture groups.
+
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::ports::{DeviceConfigRequest, DeviceStats, PortStats};
+use crate::domain::value_objects::{LinkSpeed, MacAddress, PortId};
+
+/// Errors from validating a profile or reading output with it
+#[derive(Debug, thiserror::Error)]
+pub enum ProfileError {
+    /// A pattern is not a valid regular expression
+    #[error("Invalid pattern '{pattern}': {message}")]
+    InvalidPattern {
+        /// The offending pattern
+        pattern: String,
+        /// Why it does not compile
+        message: String,
+    },
+    /// Output a required pattern should have matched did not
+    #[error("Output did not match '{0}'")]
+    NoMatch(String),
+    /// A group captured text that is not a valid value
+    #[error("Group '{group}' captured unparsable value '{value}'")]
+    InvalidValue {
+        /// Capture group name
+        group: String,
+        /// Captured text
+        value: String,
+    },
+    /// The profile leaves out an operation
+    #[error("Profile '{profile}' does not support {operation}")]
+    Unsupported {
+        /// Profile name
+        profile: String,
+        /// Operation asked for
+        operation: String,
+    },
+}
+
+/// Commands and parsers for one device family
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommandProfile {
+    /// Family name, reported as the adapter's vendor name
+    pub name: String,
+    /// Send each operation's commands through one interactive shell instead
+    /// of one exec channel per command (needed by most network CLIs)
+    pub interactive: bool,
+    /// Output matching this pattern means the device rejected a command
+    pub error_pattern: Option<String>,
+    /// How to identify a host, for `list_devices` and `get_device`
+    pub list_devices: Option<IdentifyCommands>,
+    /// How to read statistics, for `get_device_stats`
+    pub get_device_stats: Option<StatsCommands>,
+    /// How to render a config request, for `apply_config`
+    pub apply_config: Option<ConfigCommands>,
+    /// Commands that restart the device; empty if it cannot be restarted
+    pub restart_device: Vec<String>,
+}
+
+impl CommandProfile {
+    /// Blank profile to be filled in for a custom device family
+    ///
+    /// Every operation is unsupported until its section is set.
+    pub fn custom(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Profile for generic Linux hosts
+    ///
+    /// Identifies the host from `hostname`, `uname -r` and the first Ethernet
+    /// address in `ip -j link`; reads uptime, CPU and memory from `/proc` and
+    /// port state from `ip -j link`. Only the hostname can be configured.
+    pub fn linux() -> Self {
+        Self {
+            name: "linux".to_string(),
+            interactive: false,
+            error_pattern: None,
+            list_devices: Some(IdentifyCommands {
+                commands: vec!["hostname".to_string(), "uname -r".to_string(), "ip -j link".to_string()],
+                pattern: r#"(?s)\A\s*(?P<name>\S+)\s+(?P<version>\S+)\s.*?"link_type":"ether","address":"(?P<mac>[0-9a-f:]{17})""#
+                    .to_string(),
+            }),
+            get_device_stats: Some(StatsCommands {
+                commands: vec![
+                    "cat /proc/uptime".to_string(),
+                    "head -n 1 /proc/stat".to_string(),
+                    "cat /proc/meminfo".to_string(),
+                    "ip -j link".to_string(),
+                ],
+                fields: vec![
+                    StatRule {
+                        field: StatField::UptimeSeconds,
+                        pattern: r"(?m)^(?P<value>\d+)(?:\.\d+)? \d+(?:\.\d+)?$".to_string(),
+                        value: StatValue::default(),
+                    },
+                    StatRule {
+                        field: StatField::CpuPercent,
+                        pattern: r"(?m)^cpu\s+(?P<busy_user>\d+)\s+(?P<busy_nice>\d+)\s+(?P<busy_system>\d+)\s+(?P<idle>\d+)\s+(?P<idle_iowait>\d+)"
+                            .to_string(),
+                        value: StatValue::BusyShare,
+                    },
+                    StatRule {
+                        field: StatField::MemoryPercent,
+                        pattern: r"(?s)MemTotal:\s+(?P<total>\d+).*?MemAvailable:\s+(?P<available>\d+)".to_string(),
+                        value: StatValue::UsedShare,
+                    },
+                ],
+                ports: Some(PortRule {
+                    pattern: r#""ifname":"(?P<port>[^"]+)"[^}]*?"operstate":"(?P<state>[A-Z]+)""#.to_string(),
+                    up_state: "UP".to_string(),
+                }),
+            }),
+            apply_config: Some(ConfigCommands {
+                hostname: Some("hostnamectl set-hostname {hostname}".to_string()),
+                ..ConfigCommands::default()
+            }),
+            restart_device: vec!["systemctl reboot".to_string()],
+        }
+    }
+
+    /// Compile every pattern, so a broken profile fails when it is loaded
+    pub fn validate(&self) -> Result<(), ProfileError> {
+        let mut patterns: Vec<&str> = Vec::new();
+        patterns.extend(self.error_pattern.as_deref());
+        if let Some(ref identify) = self.list_devices {
+            patterns.push(&identify.pattern);
+        }
+        if let Some(ref stats) = self.get_device_stats {
+            patterns.extend(stats.fields.iter().map(|rule| rule.pattern.as_str()));
+            patterns.extend(stats.ports.as_ref().map(|rule| rule.pattern.as_str()));
+        }
+
+        for pattern in patterns {
+            compile(pattern)?;
+        }
+        Ok(())
+    }
+
+    /// Message of the first line matching `error_pattern`, if any
+    pub fn find_error<'a>(&self, output: &'a str) -> Result<Option<&'a str>, ProfileError> {
+        let Some(ref pattern) = self.error_pattern else {
+            return Ok(None);
+        };
+        let regex = compile(pattern)?;
+        Ok(output.lines().find(|line| regex.is_match(line)).map(str::trim))
+    }
+
+    /// Error for an operation this profile leaves out
+    pub fn unsupported(&self, operation: &str) -> ProfileError {
+        ProfileError::Unsupported {
+            profile: self.name.clone(),
+            operation: operation.to_string(),
+        }
+    }
+}
+
+/// Identity of one host as read by `IdentifyCommands`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceIdentity {
+    /// MAC address (group `mac`)
+    pub mac: MacAddress,
+    /// Hostname (group `name`), if captured
+    pub name: Option<String>,
+    /// Model (group `model`), if captured
+    pub model: Option<String>,
+    /// Software version (group `version`), if captured
+    pub version: Option<String>,
+}
+
+/// Commands that identify a host
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IdentifyCommands {
+    /// Commands to run
+    pub commands: Vec<String>,
+    /// Pattern with a required `mac` group and optional `name`, `model` and
+    /// `version` groups
+    pub pattern: String,
+}
+
+impl IdentifyCommands {
+    /// Read a host's identity from the commands' combined output
+    pub fn parse(&self, output: &str) -> Result<DeviceIdentity, ProfileError> {
+        let regex = compile(&self.pattern)?;
+        let captures = regex.captures(output).ok_or_else(|| ProfileError::NoMatch(self.pattern.clone()))?;
+
+        let mac = group(&captures, "mac").ok_or_else(|| ProfileError::NoMatch(self.pattern.clone()))?;
+        let mac = MacAddress::parse(mac).map_err(|_| ProfileError::InvalidValue {
+            group: "mac".to_string(),
+            value: mac.to_string(),
+        })?;
+
+        Ok(DeviceIdentity {
+            mac,
+            name: group(&captures, "name").map(str::to_string),
+            model: group(&captures, "model").map(str::to_string),
+            version: group(&captures, "version").map(str::to_string),
+        })
+    }
+}
+
+/// Commands that read device statistics
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatsCommands {
+    /// Commands to run
+    pub commands: Vec<String>,
+    /// One rule per statistic; statistics without a rule are not reported
+    #[serde(default)]
+    pub fields: Vec<StatRule>,
+    /// How to read per-port state, if the device reports it
+    #[serde(default)]
+    pub ports: Option<PortRule>,
+}
+
+impl StatsCommands {
+    /// Read device statistics from the commands' combined output
+    ///
+    /// A rule whose pattern does not match leaves its statistic unreported
+    /// (uptime reads as zero), since many devices lack a sensor or counter.
+    pub fn parse(&self, output: &str) -> Result<DeviceStats, ProfileError> {
+        let mut stats = DeviceStats {
+            uptime_seconds: 0,
+            cpu_percent: None,
+            memory_percent: None,
+            temperature_celsius: None,
+            port_stats: Vec::new(),
+        };
+
+        for rule in &self.fields {
+            let Some(value) = rule.read(output)? else { continue };
+            match rule.field {
+                StatField::UptimeSeconds => stats.uptime_seconds = value.max(0.0) as u64,
+                StatField::CpuPercent => stats.cpu_percent = Some(value),
+                StatField::MemoryPercent => stats.memory_percent = Some(value),
+                StatField::TemperatureCelsius => stats.temperature_celsius = Some(value),
+            }
+        }
+
+        if let Some(ref ports) = self.ports {
+            stats.port_stats = ports.read(output)?;
+        }
+
+        Ok(stats)
+    }
+}
+
+/// Statistic a `StatRule` fills in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatField {
+    /// `DeviceStats::uptime_seconds`
+    UptimeSeconds,
+    /// `DeviceStats::cpu_percent`
+    CpuPercent,
+    /// `DeviceStats::memory_percent`
+    MemoryPercent,
+    /// `DeviceStats::temperature_celsius`
+    TemperatureCelsius,
+}
+
+/// How a rule's capture groups become a value
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StatValue {
+    /// Group `value` times `scale` (e.g. 0.001 for millidegrees)
+    Number {
+        /// Factor applied to the captured number
+        #[serde(default = "unit_scale")]
+        scale: f64,
+    },
+    /// Percentage of groups named `busy*` in the sum of `busy*` and `idle*`
+    /// groups, as for `/proc/stat` CPU time
+    BusyShare,
+    /// Percentage of group `total` not in group `available`, as for memory
+    UsedShare,
+}
+
+impl Default for StatValue {
+    fn default() -> Self {
+        Self::Number { scale: 1.0 }
+    }
+}
+
+fn unit_scale() -> f64 {
+    1.0
+}
+
+/// Reads one statistic
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatRule {
+    /// Statistic to fill in
+    pub field: StatField,
+    /// Pattern with the groups `value` needs
+    pub pattern: String,
+    /// How to combine the groups
+    #[serde(default)]
+    pub value: StatValue,
+}
+
+impl StatRule {
+    /// The statistic's value, or `None` if the pattern does not match
+    fn read(&self, output: &str) -> Result<Option<f64>, ProfileError> {
+        let regex = compile(&self.pattern)?;
+        let Some(captures) = regex.captures(output) else {
+            return Ok(None);
+        };
+
+        let value = match self.value {
+            StatValue::Number { scale } => number(&captures, "value")?.map(|v| v * scale),
+            StatValue::BusyShare => {
+                let (mut busy, mut idle) = (0.0, 0.0);
+                for name in regex.capture_names().flatten() {
+                    let Some(value) = number(&captures, name)? else { continue };
+                    if name.starts_with("busy") {
+                        busy += value;
+                    } else if name.starts_with("idle") {
+                        idle += value;
+                    }
+                }
+                percent(busy, busy + idle)
+            }
+            StatValue::UsedShare => match (number(&captures, "total")?, number(&captures, "available")?) {
+                (Some(total), Some(available)) => percent(total - available, total),
+                _ => None,
+            },
+        };
+        Ok(value)
+    }
+}
+
+/// Reads the state of every port
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PortRule {
+    /// Pattern matched once per port, with a required `port` group and
+    /// optional `state`, `speed_mbps`, `rx_bytes`, `tx_bytes`, `rx_errors`
+    /// and `tx_errors` groups
+    pub pattern: String,
+    /// `state` value (compared without case) meaning the link is up
+    pub up_state: String,
+}
+
+impl PortRule {
+    fn read(&self, output: &str) -> Result<Vec<PortStats>, ProfileError> {
+        let regex = compile(&self.pattern)?;
+        let counter = |captures: &Captures, name| -> Result<u64, ProfileError> {
+            Ok(number(captures, name)?.map(|v| v as u64).unwrap_or(0))
+        };
+
+        regex
+            .captures_iter(output)
+            .filter_map(|captures| group(&captures, "port").map(|port| (port.to_string(), captures)))
+            .map(|(port, captures)| {
+                Ok(PortStats {
+                    port_id: PortId::new(port),
+                    link_up: group(&captures, "state").is_some_and(|s| s.eq_ignore_ascii_case(&self.up_state)),
+                    speed: number(&captures, "speed_mbps")?.and_then(|mbps| LinkSpeed::from_mbps(mbps as u32)),
+                    rx_bytes: counter(&captures, "rx_bytes")?,
+                    tx_bytes: counter(&captures, "tx_bytes")?,
+                    rx_errors: counter(&captures, "rx_errors")?,
+                    tx_errors: counter(&captures, "tx_errors")?,
+                    rx_bps: None,
+                    tx_bps: None,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Templates that turn a `DeviceConfigRequest` into commands
+///
+/// Placeholders in braces are replaced with the request's values. A request
+/// setting something without a template is rejected before anything runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfigCommands {
+    /// Commands run first (e.g. `configure terminal`)
+    pub prologue: Vec<String>,
+    /// Sets the hostname: `{hostname}`
+    pub hostname: Option<String>,
+    /// Creates one VLAN: `{id}`, `{name}`
+    pub vlan: Vec<String>,
+    /// Adds one NTP server: `{server}`
+    pub ntp_server: Option<String>,
+    /// Adds one DNS resolver: `{server}`
+    pub dns_server: Option<String>,
+    /// Commands run last (e.g. `end`, `write memory`)
+    pub epilogue: Vec<String>,
+}
+
+impl ConfigCommands {
+    /// Commands applying `request`, or the first setting with no template
+    pub fn render(&self, request: &DeviceConfigRequest) -> Result<Vec<String>, String> {
+        let mut commands = self.prologue.clone();
+
+        if let Some(ref hostname) = request.hostname {
+            let template = self.hostname.as_ref().ok_or("hostname")?;
+            commands.push(template.replace("{hostname}", hostname));
+        }
+        if !request.interfaces.is_empty() {
+            return Err("interfaces".to_string());
+        }
+        if !request.lags.is_empty() {
+            return Err("LAGs".to_string());
+        }
+        if !request.vlans.is_empty() && self.vlan.is_empty() {
+            return Err("VLANs".to_string());
+        }
+        for vlan in &request.vlans {
+            commands.extend(self.vlan.iter().map(|line| {
+                line.replace("{id}", &vlan.id.to_string()).replace("{name}", &vlan.name)
+            }));
+        }
+        for server in &request.ntp_servers {
+            let template = self.ntp_server.as_ref().ok_or("NTP servers")?;
+            commands.push(template.replace("{server}", server));
+        }
+        for server in &request.dns_servers {
+            let template = self.dns_server.as_ref().ok_or("DNS servers")?;
+            commands.push(template.replace("{server}", &server.to_string()));
+        }
+
+        commands.extend(self.epilogue.iter().cloned());
+        Ok(commands)
+    }
+}
+
+fn compile(pattern: &str) -> Result<Regex, ProfileError> {
+    Regex::new(pattern).map_err(|e| ProfileError::InvalidPattern {
+        pattern: pattern.to_string(),
+        message: e.to_string(),
+    })
+}
+
+fn group<'a>(captures: &Captures<'a>, name: &str) -> Option<&'a str> {
+    captures.name(name).map(|m| m.as_str()).filter(|s| !s.is_empty())
+}
+
+fn number(captures: &Captures, name: &str) -> Result<Option<f64>, ProfileError> {
+    group(captures, name)
+        .map(|value| {
+            value.parse::<f64>().map_err(|_| ProfileError::InvalidValue {
+                group: name.to_string(),
+                value: value.to_string(),
+            })
+        })
+        .transpose()
+}
+
+fn percent(part: f64, whole: f64) -> Option<f64> {
+    (whole > 0.0).then(|| part / whole * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::VlanConfig;
+
+    const HOSTNAME: &str = "edge-01\n";
+    const UNAME: &str = "6.1.0-18-amd64\n";
+    const PROC_UPTIME: &str = "86461.27 341230.55\n";
+    const PROC_STAT: &str = "cpu  2000 0 1000 6500 500 0 0 0 0 0\n";
+    const MEMINFO: &str = "\
+MemTotal:        8000000 kB
+MemFree:         1000000 kB
+MemAvailable:    2000000 kB
+Buffers:          100000 kB
+";
+    const IP_LINK: &str = r#"[{"ifindex":1,"ifname":"lo","flags":["LOOPBACK","UP","LOWER_UP"],"mtu":65536,"qdisc":"noqueue","operstate":"UNKNOWN","linkmode":"DEFAULT","group":"default","txqlen":1000,"link_type":"loopback","address":"00:00:00:00:00:00","broadcast":"00:00:00:00:00:00"},{"ifindex":2,"ifname":"eth0","flags":["BROADCAST","MULTICAST","UP","LOWER_UP"],"mtu":1500,"qdisc":"fq_codel","operstate":"UP","linkmode":"DEFAULT","group":"default","txqlen":1000,"link_type":"ether","address":"52:54:00:12:34:56","broadcast":"ff:ff:ff:ff:ff:ff"},{"ifindex":3,"ifname":"eth1","flags":["BROADCAST","MULTICAST"],"mtu":1500,"qdisc":"noop","operstate":"DOWN","linkmode":"DEFAULT","group":"default","txqlen":1000,"link_type":"ether","address":"52:54:00:12:34:57","broadcast":"ff:ff:ff:ff:ff:ff"}]
+"#;
+
+    // ==========================================================================
+    // Linux Profile Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_linux_profile_is_valid() {
+        CommandProfile::linux().validate().unwrap();
+    }
+
+    #[test]
+    fn test_linux_identity() {
+        let profile = CommandProfile::linux();
+        let output = [HOSTNAME, UNAME, IP_LINK].join("\n");
+
+        let identity = profile.list_devices.unwrap().parse(&output).unwrap();
+
+        assert_eq!(identity.mac, MacAddress::parse("52:54:00:12:34:56").unwrap());
+        assert_eq!(identity.name.as_deref(), Some("edge-01"));
+        assert_eq!(identity.version.as_deref(), Some("6.1.0-18-amd64"));
+        assert_eq!(identity.model, None);
+    }
+
+    #[test]
+    fn test_linux_stats() {
+        let profile = CommandProfile::linux();
+        let output = [PROC_UPTIME, PROC_STAT, MEMINFO, IP_LINK].join("\n");
+
+        let stats = profile.get_device_stats.unwrap().parse(&output).unwrap();
+
+        assert_eq!(stats.uptime_seconds, 86461);
+        // user + nice + system over those plus idle and iowait
+        assert_eq!(stats.cpu_percent, Some(30.0));
+        assert_eq!(stats.memory_percent, Some(75.0));
+        assert_eq!(stats.temperature_celsius, None);
+
+        let ports: Vec<(String, bool)> = stats.port_stats
+            .iter()
+            .map(|p| (p.port_id.to_string(), p.link_up))
+            .collect();
+        assert_eq!(ports, [("lo".to_string(), false), ("eth0".to_string(), true), ("eth1".to_string(), false)]);
+    }
+
+    #[test]
+    fn test_linux_config_only_sets_hostname() {
+        let config = CommandProfile::linux().apply_config.unwrap();
+
+        let commands = config.render(&DeviceConfigRequest::default().with_hostname("edge-02")).unwrap();
+        assert_eq!(commands, ["hostnamectl set-hostname edge-02"]);
+
+        let vlan = DeviceConfigRequest::default().with_vlan(VlanConfig::new(10, "users").unwrap());
+        assert_eq!(config.render(&vlan), Err("VLANs".to_string()));
+    }
+
+    // ==========================================================================
+    // Custom Profile Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_custom_profile_from_json() {
+        let profile: CommandProfile = serde_json::from_value(serde_json::json!({
+            "name": "acme-os",
+            "interactive": true,
+            "error_pattern": "^% ",
+            "get_device_stats": {
+                "commands": ["show system"],
+                "fields": [
+                    { "field": "temperature_celsius", "pattern": "Temp: (?P<value>\\d+)", "value": { "kind": "number", "scale": 0.001 } },
+                    { "field": "cpu_percent", "pattern": "CPU: (?P<value>[\\d.]+)%" }
+                ],
+                "ports": { "pattern": "(?m)^(?P<port>ge-\\d+) (?P<state>\\w+) (?P<speed_mbps>\\d+) (?P<rx_bytes>\\d+)", "up_state": "link" }
+            },
+            "apply_config": {
+                "prologue": ["configure"],
+                "vlan": ["vlan {id}", " name {name}"],
+                "epilogue": ["commit"]
+            }
+        }))
+        .unwrap();
+        profile.validate().unwrap();
+
+        let output = "Temp: 47500\nCPU: 12.5%\nge-1 link 1000 4096\nge-2 nolink 0 0\n";
+        let stats = profile.get_device_stats.as_ref().unwrap().parse(output).unwrap();
+        assert_eq!(stats.temperature_celsius, Some(47.5));
+        assert_eq!(stats.cpu_percent, Some(12.5));
+        assert_eq!(stats.memory_percent, None);
+        assert_eq!(stats.port_stats.len(), 2);
+        assert!(stats.port_stats[0].link_up);
+        assert_eq!(stats.port_stats[0].speed, Some(LinkSpeed::Gbps1));
+        assert_eq!(stats.port_stats[0].rx_bytes, 4096);
+        assert!(!stats.port_stats[1].link_up);
+
+        let request = DeviceConfigRequest::default().with_vlan(VlanConfig::new(20, "voice").unwrap());
+        let commands = profile.apply_config.as_ref().unwrap().render(&request).unwrap();
+        assert_eq!(commands, ["configure", "vlan 20", " name voice", "commit"]);
+
+        assert_eq!(profile.find_error("ok\n% Unknown command\n").unwrap(), Some("% Unknown command"));
+        assert_eq!(profile.restart_device, Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_blank_profile_supports_nothing() {
+        let profile = CommandProfile::custom("blank");
+
+        profile.validate().unwrap();
+        assert!(profile.list_devices.is_none());
+        assert!(profile.get_device_stats.is_none());
+        assert!(profile.apply_config.is_none());
+        assert!(profile.restart_device.is_empty());
+        assert_eq!(profile.find_error("% anything").unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_pattern_fails_validation() {
+        let profile = CommandProfile {
+            error_pattern: Some("(unclosed".to_string()),
+            ..CommandProfile::custom("broken")
+        };
+
+        assert!(matches!(profile.validate(), Err(ProfileError::InvalidPattern { .. })));
+    }
+}
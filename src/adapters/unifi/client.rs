@@ -1,26 +1,61 @@
 //! UniFi Controller HTTP client
 //!
 //! Handles authentication and API communication with UniFi Network Application.
+//!
+//! Controller sessions expire. A request answered `401 Unauthorized` marks
+//! the session expired, logs in again and is retried once; concurrent
+//! requests that hit the same expiry share a single re-login.
+//!
+//! Credentials come from a `CredentialSource` consulted at every login, so
+//! the client never holds the password itself.
 
 use super::types::*;
+use crate::adapters::credentials::{CredentialSource, Credentials};
+use crate::adapters::retry::{retry_after, RetryPolicy};
 use reqwest::{Client, cookie::Jar};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
+/// State of the controller session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Session {
+    /// Never logged in, or logged out explicitly
+    LoggedOut,
+    /// Logged in and not yet rejected by the controller
+    Active,
+    /// Rejected by the controller; the next request logs in again
+    Expired,
+}
+
 /// UniFi Controller client
 pub struct UniFiClient {
     /// HTTP client with cookie jar
     http: Client,
     /// Base URL of the controller
     base_url: String,
-    /// Username for authentication
-    username: String,
-    /// Password for authentication
-    password: String,
+    /// Where login credentials come from
+    credentials: Box<dyn CredentialSource>,
     /// CSRF token from login response
     csrf_token: RwLock<Option<String>>,
-    /// Whether currently authenticated
-    authenticated: RwLock<bool>,
+    /// Current session state
+    session: RwLock<Session>,
+    /// Number of successful logins, to tell whether a rejected session was already renewed
+    logins: AtomicU64,
+    /// Held while logging in again, so a burst of 401s triggers one re-login
+    relogin: tokio::sync::Mutex<()>,
+    /// Retry policy for throttled or transiently failing requests
+    retry: RetryPolicy,
+}
+
+impl std::fmt::Debug for UniFiClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UniFiClient")
+            .field("base_url", &self.base_url)
+            .field("credentials", &self.credentials)
+            .field("session", &self.session())
+            .finish_non_exhaustive()
+    }
 }
 
 impl UniFiClient {
@@ -34,6 +69,16 @@ impl UniFiClient {
         controller_url: &str,
         username: &str,
         password: &str,
+    ) -> Result<Self, UniFiError> {
+        Self::with_credentials(controller_url, Credentials::login(username, password)).await
+    }
+
+    /// Create a UniFi client that logs in with credentials from `source`
+    ///
+    /// `source` is consulted at each login, not here; it must provide a username.
+    pub async fn with_credentials(
+        controller_url: &str,
+        source: impl CredentialSource + 'static,
     ) -> Result<Self, UniFiError> {
         let base_url = controller_url.trim_end_matches('/').to_string();
 
@@ -52,30 +97,39 @@ impl UniFiClient {
         Ok(Self {
             http,
             base_url,
-            username: username.to_string(),
-            password: password.to_string(),
+            credentials: Box::new(source),
             csrf_token: RwLock::new(None),
-            authenticated: RwLock::new(false),
+            session: RwLock::new(Session::LoggedOut),
+            logins: AtomicU64::new(0),
+            relogin: tokio::sync::Mutex::new(()),
+            retry: RetryPolicy::default(),
         })
     }
 
+    /// Use `retry` instead of the default retry policy
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
     /// Login to the controller
     pub async fn login(&self) -> Result<(), UniFiError> {
         let url = format!("{}/api/login", self.base_url);
 
+        let credentials = self.credentials.resolve().map_err(|e| UniFiError::Auth(e.to_string()))?;
+        let username = credentials.username
+            .ok_or_else(|| UniFiError::Auth("Credentials have no username".to_string()))?;
         let body = serde_json::json!({
-            "username": self.username,
-            "password": self.password,
+            "username": username,
+            "password": credentials.secret.expose(),
         });
 
         tracing::info!("Logging into UniFi controller at {}", self.base_url);
 
-        let response = self.http
-            .post(&url)
-            .json(&body)
-            .send()
+        let response = self.retry
+            .send(|| self.http.post(&url).json(&body))
             .await
-            .map_err(|e| UniFiError::Http(e.to_string()))?;
+            .map_err(transport_error)?;
 
         // Check for CSRF token in headers
         if let Some(csrf) = response.headers().get("x-csrf-token") {
@@ -87,6 +141,9 @@ impl UniFiClient {
         }
 
         let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            return Err(status_error(&response));
+        }
         if !status.is_success() {
             return Err(UniFiError::Auth(format!("Login failed with status {}", status)));
         }
@@ -102,9 +159,8 @@ impl UniFiClient {
             ));
         }
 
-        let mut auth = self.authenticated.write()
-            .map_err(|_| UniFiError::Auth("Lock poisoned".to_string()))?;
-        *auth = true;
+        self.logins.fetch_add(1, Ordering::SeqCst);
+        self.set_session(Session::Active)?;
 
         tracing::info!("Successfully logged into UniFi controller");
         Ok(())
@@ -125,23 +181,20 @@ impl UniFiClient {
 
         let _ = request.send().await; // Ignore errors on logout
 
-        let mut auth = self.authenticated.write()
-            .map_err(|_| UniFiError::Auth("Lock poisoned".to_string()))?;
-        *auth = false;
-
-        Ok(())
+        self.set_session(Session::LoggedOut)
     }
 
     /// Check if authenticated
+    ///
+    /// False once the controller has rejected the session, until a re-login
+    /// succeeds.
     pub fn is_authenticated(&self) -> bool {
-        self.authenticated.read()
-            .map(|auth| *auth)
-            .unwrap_or(false)
+        self.session() == Session::Active
     }
 
     /// List all devices for a site
     pub async fn list_devices(&self, site_id: &str) -> Result<Vec<UniFiDevice>, UniFiError> {
-        self.ensure_authenticated()?;
+        self.ensure_authenticated().await?;
 
         let url = format!("{}/api/s/{}/stat/device", self.base_url, site_id);
 
@@ -163,7 +216,7 @@ impl UniFiClient {
 
     /// Get a specific device by MAC address
     pub async fn get_device(&self, site_id: &str, device_mac: &str) -> Result<UniFiDevice, UniFiError> {
-        self.ensure_authenticated()?;
+        self.ensure_authenticated().await?;
 
         // UniFi API doesn't have a direct get-by-ID, we filter from list
         let devices = self.list_devices(site_id).await?;
@@ -177,7 +230,7 @@ impl UniFiClient {
 
     /// Adopt a device
     pub async fn adopt_device(&self, site_id: &str, device_mac: &str) -> Result<(), UniFiError> {
-        self.ensure_authenticated()?;
+        self.ensure_authenticated().await?;
 
         let url = format!("{}/api/s/{}/cmd/devmgr", self.base_url, site_id);
 
@@ -209,7 +262,7 @@ impl UniFiClient {
         device_id: &str,
         config: &serde_json::Value,
     ) -> Result<(), UniFiError> {
-        self.ensure_authenticated()?;
+        self.ensure_authenticated().await?;
 
         let url = format!("{}/api/s/{}/rest/device/{}", self.base_url, site_id, device_id);
 
@@ -229,9 +282,109 @@ impl UniFiClient {
         Ok(())
     }
 
+    /// List the site's networks, including its VLANs
+    pub async fn list_networks(&self, site_id: &str) -> Result<Vec<UniFiNetwork>, UniFiError> {
+        self.ensure_authenticated().await?;
+
+        let url = format!("{}/api/s/{}/rest/networkconf", self.base_url, site_id);
+
+        let response = self.make_request(reqwest::Method::GET, &url, None).await?;
+        let api_response: UniFiResponse<UniFiNetwork> = response.json()
+            .await
+            .map_err(|e| UniFiError::Parse(e.to_string()))?;
+
+        if !api_response.meta.is_ok() {
+            return Err(UniFiError::Api(
+                api_response.meta.msg.unwrap_or_else(|| "Unknown error".to_string())
+            ));
+        }
+
+        Ok(api_response.data)
+    }
+
+    /// Create a network and return it as stored by the controller
+    pub async fn create_network(
+        &self,
+        site_id: &str,
+        network: &serde_json::Value,
+    ) -> Result<UniFiNetwork, UniFiError> {
+        self.ensure_authenticated().await?;
+
+        let url = format!("{}/api/s/{}/rest/networkconf", self.base_url, site_id);
+
+        tracing::info!("Creating network in site {}", site_id);
+
+        let response = self.make_request(reqwest::Method::POST, &url, Some(network.clone())).await?;
+        let api_response: UniFiResponse<UniFiNetwork> = response.json()
+            .await
+            .map_err(|e| UniFiError::Parse(e.to_string()))?;
+
+        if !api_response.meta.is_ok() {
+            return Err(UniFiError::Api(
+                api_response.meta.msg.unwrap_or_else(|| "Network creation failed".to_string())
+            ));
+        }
+
+        api_response.data
+            .into_iter()
+            .next()
+            .ok_or_else(|| UniFiError::Parse("Controller returned no network".to_string()))
+    }
+
+    /// Update one section of the site settings, e.g. `ntp`
+    pub async fn set_site_setting(
+        &self,
+        site_id: &str,
+        key: &str,
+        setting: &serde_json::Value,
+    ) -> Result<(), UniFiError> {
+        self.ensure_authenticated().await?;
+
+        let url = format!("{}/api/s/{}/set/setting/{}", self.base_url, site_id, key);
+
+        tracing::info!("Updating {} settings for site {}", key, site_id);
+
+        let response = self.make_request(reqwest::Method::POST, &url, Some(setting.clone())).await?;
+        let api_response: UniFiResponse<serde_json::Value> = response.json()
+            .await
+            .map_err(|e| UniFiError::Parse(e.to_string()))?;
+
+        if !api_response.meta.is_ok() {
+            return Err(UniFiError::Api(
+                api_response.meta.msg.unwrap_or_else(|| "Settings update failed".to_string())
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Read one section of the site settings, e.g. `ntp`
+    ///
+    /// Returns `None` when the site has never stored that section.
+    pub async fn get_site_setting(&self, site_id: &str, key: &str) -> Result<Option<serde_json::Value>, UniFiError> {
+        self.ensure_authenticated().await?;
+
+        let url = format!("{}/api/s/{}/get/setting/{}", self.base_url, site_id, key);
+
+        let response = self.make_request(reqwest::Method::GET, &url, None).await?;
+        let api_response: UniFiResponse<serde_json::Value> = response.json()
+            .await
+            .map_err(|e| UniFiError::Parse(e.to_string()))?;
+
+        if !api_response.meta.is_ok() {
+            return Err(UniFiError::Api(
+                api_response.meta.msg.unwrap_or_else(|| "Unknown error".to_string())
+            ));
+        }
+
+        Ok(api_response.data
+            .into_iter()
+            .find(|setting| setting.get("key").and_then(|k| k.as_str()) == Some(key)))
+    }
+
     /// Restart a device
     pub async fn restart_device(&self, site_id: &str, device_mac: &str) -> Result<(), UniFiError> {
-        self.ensure_authenticated()?;
+        self.ensure_authenticated().await?;
 
         let url = format!("{}/api/s/{}/cmd/devmgr", self.base_url, site_id);
 
@@ -256,6 +409,54 @@ impl UniFiClient {
         Ok(())
     }
 
+    /// Upgrade a device to the firmware the controller offers for it
+    pub async fn upgrade_device(&self, site_id: &str, device_mac: &str) -> Result<(), UniFiError> {
+        self.ensure_authenticated().await?;
+
+        let url = format!("{}/api/s/{}/cmd/devmgr", self.base_url, site_id);
+
+        let body = serde_json::json!({
+            "cmd": "upgrade",
+            "mac": device_mac.to_lowercase().replace([':', '-'], ""),
+        });
+
+        tracing::info!("Upgrading firmware of device {} in site {}", device_mac, site_id);
+
+        let response = self.make_request(reqwest::Method::POST, &url, Some(body)).await?;
+        let api_response: UniFiResponse<serde_json::Value> = response.json()
+            .await
+            .map_err(|e| UniFiError::Parse(e.to_string()))?;
+
+        if !api_response.meta.is_ok() {
+            return Err(UniFiError::Api(
+                api_response.meta.msg.unwrap_or_else(|| "Upgrade failed".to_string())
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Get the controller status
+    ///
+    /// `/status` needs no session, so this works before `login` and does not
+    /// touch the device list.
+    pub async fn get_status(&self) -> Result<UniFiStatus, UniFiError> {
+        let url = format!("{}/status", self.base_url);
+
+        let response = self.retry
+            .send(|| self.http.get(&url))
+            .await
+            .map_err(transport_error)?;
+
+        if !response.status().is_success() {
+            return Err(status_error(&response));
+        }
+
+        response.json()
+            .await
+            .map_err(|e| UniFiError::Parse(e.to_string()))
+    }
+
     /// Get device statistics
     pub async fn get_device_stats(
         &self,
@@ -284,40 +485,93 @@ impl UniFiClient {
     }
 
     /// Make an authenticated request
+    ///
+    /// A `401` logs in again and retries the request once.
     async fn make_request(
         &self,
         method: reqwest::Method,
         url: &str,
         body: Option<serde_json::Value>,
     ) -> Result<reqwest::Response, UniFiError> {
-        let mut request = self.http.request(method, url);
+        let logins = self.logins.load(Ordering::SeqCst);
+        let mut response = self.send_request(&method, url, body.as_ref()).await?;
 
-        // Add CSRF token if we have one
-        if let Ok(csrf_lock) = self.csrf_token.read() {
-            if let Some(ref token) = *csrf_lock {
-                request = request.header("x-csrf-token", token);
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            tracing::info!("UniFi session rejected; logging in again");
+            self.refresh_session(logins).await?;
+            response = self.send_request(&method, url, body.as_ref()).await?;
+
+            if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+                self.set_session(Session::Expired)?;
+                return Err(UniFiError::Auth("Session rejected after logging in again".to_string()));
             }
         }
 
-        if let Some(json_body) = body {
-            request = request.json(&json_body);
+        if !response.status().is_success() {
+            return Err(status_error(&response));
         }
 
-        let response = request.send()
+        Ok(response)
+    }
+
+    /// Send one request with the current CSRF token
+    async fn send_request(
+        &self,
+        method: &reqwest::Method,
+        url: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<reqwest::Response, UniFiError> {
+        let csrf_token = self.csrf_token.read().ok().and_then(|token| token.clone());
+
+        self.retry
+            .send(|| {
+                let mut request = self.http.request(method.clone(), url);
+
+                // Add CSRF token if we have one
+                if let Some(ref token) = csrf_token {
+                    request = request.header("x-csrf-token", token);
+                }
+
+                if let Some(json_body) = body {
+                    request = request.json(json_body);
+                }
+                request
+            })
             .await
-            .map_err(|e| UniFiError::Http(e.to_string()))?;
+            .map_err(transport_error)
+    }
 
-        if !response.status().is_success() {
-            return Err(UniFiError::Http(format!("Request failed with status {}", response.status())));
+    /// Log in again after the session seen at `logins` logins was rejected
+    ///
+    /// Callers that lose the race for the lock find the session already
+    /// renewed and return without logging in themselves.
+    async fn refresh_session(&self, logins: u64) -> Result<(), UniFiError> {
+        let _guard = self.relogin.lock().await;
+        if self.logins.load(Ordering::SeqCst) != logins && self.is_authenticated() {
+            return Ok(());
         }
 
-        Ok(response)
+        self.set_session(Session::Expired)?;
+        self.login().await
     }
 
-    fn ensure_authenticated(&self) -> Result<(), UniFiError> {
-        if !self.is_authenticated() {
-            return Err(UniFiError::Auth("Not authenticated".to_string()));
+    /// Fail unless logged in; an expired session logs in again first
+    async fn ensure_authenticated(&self) -> Result<(), UniFiError> {
+        match self.session() {
+            Session::Active => Ok(()),
+            Session::Expired => self.refresh_session(self.logins.load(Ordering::SeqCst)).await,
+            Session::LoggedOut => Err(UniFiError::Auth("Not authenticated".to_string())),
         }
+    }
+
+    fn session(&self) -> Session {
+        self.session.read().map(|session| *session).unwrap_or(Session::LoggedOut)
+    }
+
+    fn set_session(&self, session: Session) -> Result<(), UniFiError> {
+        let mut current = self.session.write()
+            .map_err(|_| UniFiError::Auth("Lock poisoned".to_string()))?;
+        *current = session;
         Ok(())
     }
 }
@@ -344,3 +598,25 @@ fn extract_port_stats(device: &UniFiDevice) -> Vec<UniFiPortStats> {
         })
         .unwrap_or_default()
 }
+
+/// Classify a request that never got a response
+fn transport_error(e: reqwest::Error) -> UniFiError {
+    if e.is_timeout() {
+        UniFiError::Timeout(e.to_string())
+    } else {
+        UniFiError::Http(e.to_string())
+    }
+}
+
+/// Classify an unsuccessful response by its status
+fn status_error(response: &reqwest::Response) -> UniFiError {
+    let status = response.status();
+    let message = format!("Request failed with status {}", status);
+    match status {
+        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => UniFiError::Auth(message),
+        reqwest::StatusCode::NOT_FOUND => UniFiError::NotFound(response.url().path().to_string()),
+        reqwest::StatusCode::TOO_MANY_REQUESTS => UniFiError::RateLimited(retry_after(response)),
+        status if status.is_server_error() => UniFiError::Unavailable(message),
+        _ => UniFiError::Api(message),
+    }
+}
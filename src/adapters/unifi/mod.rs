@@ -5,7 +5,8 @@
 //! ## Supported Operations
 //!
 //! - Device discovery and adoption
-//! - Configuration management
+//! - Configuration management: hostname, management address, DNS and port
+//!   overrides on the device; VLANs and NTP servers in the site settings
 //! - Statistics and monitoring
 //! - Firmware upgrades
 //!
@@ -19,6 +20,8 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::adapters::credentials::CredentialSource;
+use crate::adapters::retry::RetryPolicy;
 use crate::domain::ports::*;
 use crate::domain::functor::*;
 use crate::domain::events::*;
@@ -51,6 +54,15 @@ pub struct UniFiAdapter {
     site_id: String,
 }
 
+impl std::fmt::Debug for UniFiAdapter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UniFiAdapter")
+            .field("client", &self.client)
+            .field("site_id", &self.site_id)
+            .finish_non_exhaustive()
+    }
+}
+
 impl UniFiAdapter {
     /// Create a new UniFi adapter
     pub async fn new(
@@ -58,18 +70,50 @@ impl UniFiAdapter {
         username: &str,
         password: &str,
         site_id: &str,
+    ) -> Result<Self, PortError> {
+        Self::with_retry_policy(controller_url, username, password, site_id, RetryPolicy::default()).await
+    }
+
+    /// Create a UniFi adapter that retries throttled controller calls per `retry`
+    pub async fn with_retry_policy(
+        controller_url: &str,
+        username: &str,
+        password: &str,
+        site_id: &str,
+        retry: RetryPolicy,
     ) -> Result<Self, PortError> {
         let client = UniFiClient::new(controller_url, username, password)
+            .await
+            .map_err(|e| PortError::ConnectionFailed(e.to_string()))?
+            .with_retry_policy(retry);
+
+        Ok(Self::from_client(client, site_id))
+    }
+
+    /// Create a UniFi adapter that logs in with credentials from `source`
+    ///
+    /// Nothing is resolved until the first controller call logs in, and again
+    /// on every re-login. `source` must provide a username.
+    pub async fn with_credentials(
+        controller_url: &str,
+        site_id: &str,
+        source: impl CredentialSource + 'static,
+    ) -> Result<Self, PortError> {
+        let client = UniFiClient::with_credentials(controller_url, source)
             .await
             .map_err(|e| PortError::ConnectionFailed(e.to_string()))?;
 
-        Ok(Self {
+        Ok(Self::from_client(client, site_id))
+    }
+
+    fn from_client(client: UniFiClient, site_id: &str) -> Self {
+        Self {
             client: Arc::new(client),
             device_mapping: Arc::new(RwLock::new(HashMap::new())),
             reverse_mapping: Arc::new(RwLock::new(HashMap::new())),
             mac_registry: Arc::new(std::sync::RwLock::new(HashMap::new())),
             site_id: site_id.to_string(),
-        })
+        }
     }
 
     /// Map a domain device to UniFi device ID and MAC address
@@ -134,24 +178,24 @@ impl DeviceControlPort for UniFiAdapter {
     async fn connect(&self) -> Result<(), PortError> {
         self.client.login()
             .await
-            .map_err(|e| PortError::ConnectionFailed(e.to_string()))
+            .map_err(PortError::from)
     }
 
     async fn disconnect(&self) -> Result<(), PortError> {
         self.client.logout()
             .await
-            .map_err(|e| PortError::ConnectionFailed(e.to_string()))
+            .map_err(PortError::from)
     }
 
     fn is_connected(&self) -> bool {
         self.client.is_authenticated()
     }
 
+    #[tracing::instrument(name = "unifi.list_devices", skip_all, fields(site = %self.site_id))]
     async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError> {
         let unifi_devices = self.client
             .list_devices(&self.site_id)
-            .await
-            .map_err(|e| PortError::VendorError(e.to_string()))?;
+            .await?;
 
         let mut vendor_devices = Vec::new();
         for device in unifi_devices {
@@ -162,42 +206,164 @@ impl DeviceControlPort for UniFiAdapter {
         Ok(vendor_devices)
     }
 
+    /// The cursor is the newest `last_seen` the controller reported
+    ///
+    /// `stat/device` cannot filter server-side, so the full list is fetched
+    /// and devices not heard from since the cursor are dropped here. Devices
+    /// without a `last_seen` are always returned.
+    #[tracing::instrument(name = "unifi.list_devices_since", skip_all, fields(site = %self.site_id))]
+    async fn list_devices_since(
+        &self,
+        cursor: Option<String>,
+    ) -> Result<(Vec<VendorDevice>, Option<String>), PortError> {
+        let since = cursor.as_deref().and_then(|c| c.parse::<u64>().ok());
+        let unifi_devices = self.client
+            .list_devices(&self.site_id)
+            .await?;
+
+        let newest = unifi_devices.iter().filter_map(UniFiDevice::last_seen).max().max(since);
+
+        let mut vendor_devices = Vec::new();
+        for device in unifi_devices {
+            let changed = match (since, device.last_seen()) {
+                (Some(since), Some(last_seen)) => last_seen > since,
+                _ => true,
+            };
+            if changed {
+                let device_id = self.get_device_id(&device.id).await;
+                vendor_devices.push(self.to_vendor_device(&device, device_id));
+            }
+        }
+
+        Ok((vendor_devices, newest.map(|seen| seen.to_string())))
+    }
+
+    #[tracing::instrument(name = "unifi.get_device", skip(self))]
     async fn get_device(&self, vendor_id: &str) -> Result<VendorDevice, PortError> {
         let unifi_device = self.client
             .get_device(&self.site_id, vendor_id)
-            .await
-            .map_err(|e| PortError::VendorError(e.to_string()))?;
+            .await?;
 
         let device_id = self.get_device_id(vendor_id).await;
         Ok(self.to_vendor_device(&unifi_device, device_id))
     }
 
+    #[tracing::instrument(name = "unifi.adopt_device", skip(self))]
     async fn adopt_device(&self, vendor_id: &str) -> Result<(), PortError> {
         self.client
             .adopt_device(&self.site_id, vendor_id)
             .await
-            .map_err(|e| PortError::VendorError(e.to_string()))
+            .map_err(PortError::from)
+    }
+
+    #[tracing::instrument(name = "unifi.apply_config", skip(self, config))]
+    async fn apply_config(&self, vendor_id: &str, config: DeviceConfigRequest) -> Result<(), PortError> {
+        if !config.lags.is_empty() {
+            return Err(PortError::NotSupported("UniFi adapter cannot configure LAGs".to_string()));
+        }
+        let ntp = if config.ntp_servers.is_empty() {
+            None
+        } else {
+            Some(ntp_setting(&config.ntp_servers)?)
+        };
+
+        let networks = self.ensure_vlans(&config).await?;
+        let payload = device_payload(&config, &networks)?;
+
+        if let Some(ntp) = ntp {
+            self.client
+                .set_site_setting(&self.site_id, "ntp", &ntp)
+                .await?;
+        }
+        if payload.as_object().is_some_and(|fields| !fields.is_empty()) {
+            self.client
+                .set_device_config(&self.site_id, vendor_id, &payload)
+                .await?;
+        }
+        Ok(())
     }
 
-    async fn apply_config(&self, vendor_id: &str, config: VendorConfig) -> Result<(), PortError> {
+    async fn apply_raw_config(&self, vendor_id: &str, config: VendorConfig) -> Result<(), PortError> {
         self.client
             .set_device_config(&self.site_id, vendor_id, &config.payload)
             .await
-            .map_err(|e| PortError::VendorError(e.to_string()))
+            .map_err(PortError::from)
+    }
+
+    /// Capture the device fields `apply_config` writes
+    ///
+    /// Covers the name, management address and port overrides. Site-wide
+    /// settings such as NTP, and networks created for new VLANs, are shared
+    /// with other devices and are not part of the snapshot.
+    async fn snapshot_config(&self, vendor_id: &str) -> Result<VendorConfig, PortError> {
+        let device = self.client
+            .get_device(&self.site_id, vendor_id)
+            .await?;
+
+        let mut payload = serde_json::Map::new();
+        payload.insert("name".to_string(), serde_json::json!(device.name));
+        for field in SNAPSHOT_FIELDS {
+            if let Some(value) = device.properties.get(field) {
+                payload.insert(field.to_string(), value.clone());
+            }
+        }
+        Ok(VendorConfig {
+            config_type: "unifi-device".to_string(),
+            payload: serde_json::Value::Object(payload),
+        })
+    }
+
+    /// Read back what `apply_config` writes
+    ///
+    /// Reports the device name, the site's VLAN networks, port overrides as
+    /// interfaces, a static management address as an interface named
+    /// `management` with its DNS servers, and the site's NTP servers.
+    #[tracing::instrument(name = "unifi.get_running_config", skip(self))]
+    async fn get_running_config(&self, vendor_id: &str) -> Result<DeviceConfigRequest, PortError> {
+        let device = self.client
+            .get_device(&self.site_id, vendor_id)
+            .await?;
+        let networks = self.client
+            .list_networks(&self.site_id)
+            .await?;
+        let ntp = self.client
+            .get_site_setting(&self.site_id, "ntp")
+            .await?;
+
+        Ok(running_config(&device, &networks, ntp.as_ref()))
+    }
+
+    async fn restore_config(&self, vendor_id: &str, snapshot: VendorConfig) -> Result<(), PortError> {
+        self.client
+            .set_device_config(&self.site_id, vendor_id, &snapshot.payload)
+            .await
+            .map_err(PortError::from)
+    }
+
+    #[tracing::instrument(name = "unifi.upgrade_firmware", skip(self))]
+    async fn upgrade_firmware(&self, vendor_id: &str, target_version: &str) -> Result<(), PortError> {
+        let device = self.client
+            .get_device(&self.site_id, vendor_id)
+            .await?;
+        check_upgrade_target(&device, target_version)?;
+
+        self.client
+            .upgrade_device(&self.site_id, vendor_id)
+            .await
+            .map_err(PortError::from)
     }
 
     async fn restart_device(&self, vendor_id: &str) -> Result<(), PortError> {
         self.client
             .restart_device(&self.site_id, vendor_id)
             .await
-            .map_err(|e| PortError::VendorError(e.to_string()))
+            .map_err(PortError::from)
     }
 
     async fn get_device_stats(&self, vendor_id: &str) -> Result<DeviceStats, PortError> {
         let stats = self.client
             .get_device_stats(&self.site_id, vendor_id)
-            .await
-            .map_err(|e| PortError::VendorError(e.to_string()))?;
+            .await?;
 
         Ok(DeviceStats {
             uptime_seconds: stats.uptime.unwrap_or(0),
@@ -207,14 +373,31 @@ impl DeviceControlPort for UniFiAdapter {
             port_stats: stats.port_stats.into_iter().map(|ps| PortStats {
                 port_id: PortId::with_index("port", ps.port_idx),
                 link_up: ps.up,
-                speed: ps.speed.and_then(speed_from_mbps),
+                speed: ps.speed.and_then(LinkSpeed::from_mbps),
                 rx_bytes: ps.rx_bytes,
                 tx_bytes: ps.tx_bytes,
                 rx_errors: ps.rx_errors.unwrap_or(0),
                 tx_errors: ps.tx_errors.unwrap_or(0),
+                rx_bps: None,
+                tx_bps: None,
             }).collect(),
         })
     }
+
+    async fn health_check(&self) -> Result<HealthStatus, PortError> {
+        match self.client.get_status().await {
+            Ok(status) => Ok(HealthStatus {
+                reachable: status.meta.up,
+                authenticated: self.client.is_authenticated(),
+                version: status.meta.server_version,
+            }),
+            Err(UniFiError::Http(e) | UniFiError::Timeout(e)) => {
+                tracing::warn!("UniFi controller unreachable: {}", e);
+                Ok(HealthStatus::unreachable())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
 }
 
 impl VendorExtension for UniFiAdapter {
@@ -231,6 +414,8 @@ impl VendorExtension for UniFiAdapter {
                         DeviceType::Gateway => "ugw",
                         DeviceType::Switch => "usw",
                         DeviceType::AccessPoint => "uap",
+                        // UniFi firewalls are its gateways
+                        DeviceType::Firewall => "ugw",
                         DeviceType::Generic { model } => model.as_str(),
                     },
                     "mac": device.mac().to_string(),
@@ -258,109 +443,520 @@ impl VendorExtension for UniFiAdapter {
             .ok_or_else(|| FunctorError::MappingFailed("Missing event key".to_string()))?;
 
         match event_type {
-            "EVT_AP_Connected" | "EVT_SW_Connected" | "EVT_GW_Connected" => {
-                let mac_str = vendor_event.get("ap")
-                    .or_else(|| vendor_event.get("sw"))
-                    .or_else(|| vendor_event.get("gw"))
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| FunctorError::MappingFailed("Missing device MAC".to_string()))?;
-
-                let mac = MacAddress::parse(mac_str)
-                    .map_err(|e| FunctorError::MappingFailed(e.to_string()))?;
-
-                // Look up device ID from MAC registry
-                let device_id = self.get_device_by_mac(&mac)
-                    .ok_or_else(|| FunctorError::MappingFailed(
-                        format!("Unknown device MAC: {}. Register device first.", mac)
-                    ))?;
-
-                // Extract model and firmware from event if available
-                let model = vendor_event.get("model")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown")
-                    .to_string();
-
-                let firmware_version = vendor_event.get("version")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown")
-                    .to_string();
-
+            "EVT_AP_Connected" | "EVT_SW_Connected" | "EVT_GW_Connected"
+            // Restart events result in device reprovisioning
+            | "EVT_AP_RestartedUnknown" | "EVT_SW_RestartedUnknown" | "EVT_GW_RestartedUnknown" => {
                 Ok(NetworkEvent::DeviceProvisioned {
-                    device_id,
-                    model,
-                    firmware_version,
+                    device_id: self.event_device_id(vendor_event)?,
+                    model: event_field(vendor_event, "model", "unknown"),
+                    firmware_version: event_field(vendor_event, "version", "unknown"),
                 })
             }
             "EVT_AP_Disconnected" | "EVT_SW_Disconnected" | "EVT_GW_Disconnected" => {
-                let mac_str = vendor_event.get("ap")
-                    .or_else(|| vendor_event.get("sw"))
-                    .or_else(|| vendor_event.get("gw"))
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| FunctorError::MappingFailed("Missing device MAC".to_string()))?;
+                Ok(NetworkEvent::DeviceError {
+                    device_id: self.event_device_id(vendor_event)?,
+                    message: event_field(vendor_event, "msg", "Device disconnected"),
+                })
+            }
+            _ => Err(FunctorError::MappingFailed(format!("Unknown event type: {}", event_type))),
+        }
+    }
+}
 
-                let mac = MacAddress::parse(mac_str)
-                    .map_err(|e| FunctorError::MappingFailed(e.to_string()))?;
+impl UniFiAdapter {
+    /// Create the request's VLANs the site lacks and map VLAN IDs to network IDs
+    ///
+    /// Skips the controller entirely when the request mentions no VLANs.
+    async fn ensure_vlans(&self, request: &DeviceConfigRequest) -> Result<HashMap<u16, String>, UniFiError> {
+        if request.vlans.is_empty() && request.interfaces.iter().all(|i| i.vlan_id.is_none()) {
+            return Ok(HashMap::new());
+        }
 
-                let device_id = self.get_device_by_mac(&mac)
-                    .ok_or_else(|| FunctorError::MappingFailed(
-                        format!("Unknown device MAC: {}", mac)
-                    ))?;
+        let mut networks: HashMap<u16, String> = self.client
+            .list_networks(&self.site_id)
+            .await?
+            .into_iter()
+            .filter(|network| network.vlan_enabled)
+            .filter_map(|network| network.vlan.map(|vlan| (vlan, network.id)))
+            .collect();
+
+        for vlan in &request.vlans {
+            if let std::collections::hash_map::Entry::Vacant(entry) = networks.entry(vlan.id) {
+                let created = self.client.create_network(&self.site_id, &network_payload(vlan)).await?;
+                entry.insert(created.id);
+            }
+        }
+        Ok(networks)
+    }
 
-                let message = vendor_event.get("msg")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("Device disconnected")
-                    .to_string();
+    /// Resolve the domain device an event refers to from its `ap`/`sw`/`gw` MAC
+    ///
+    /// Fails with `FunctorError::UnknownDevice` for MACs that were never
+    /// registered rather than inventing a new device ID.
+    fn event_device_id(&self, vendor_event: &serde_json::Value) -> Result<DeviceId, FunctorError> {
+        let mac_str = vendor_event.get("ap")
+            .or_else(|| vendor_event.get("sw"))
+            .or_else(|| vendor_event.get("gw"))
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| FunctorError::MappingFailed("Missing device MAC".to_string()))?;
 
-                Ok(NetworkEvent::DeviceError {
-                    device_id,
-                    message,
-                })
+        let mac = MacAddress::parse(mac_str)
+            .map_err(|e| FunctorError::MappingFailed(e.to_string()))?;
+
+        self.get_device_by_mac(&mac)
+            .ok_or(FunctorError::UnknownDevice(mac))
+    }
+}
+
+/// String field of a UniFi event, or `default` when absent
+fn event_field(vendor_event: &serde_json::Value, key: &str, default: &str) -> String {
+    vendor_event.get(key)
+        .and_then(|v| v.as_str())
+        .unwrap_or(default)
+        .to_string()
+}
+
+/// `rest/networkconf` payload creating `vlan` as a VLAN-only network
+fn network_payload(vlan: &VlanConfig) -> serde_json::Value {
+    serde_json::json!({
+        "name": vlan.name,
+        "purpose": "vlan-only",
+        "vlan_enabled": true,
+        "vlan": vlan.id,
+        "enabled": true,
+    })
+}
+
+/// Site `ntp` setting listing `servers`; UniFi takes at most four
+fn ntp_setting(servers: &[String]) -> Result<serde_json::Value, UniFiError> {
+    if servers.len() > 4 {
+        return Err(UniFiError::Parse(format!("UniFi takes at most 4 NTP servers, got {}", servers.len())));
+    }
+
+    let mut setting = serde_json::json!({ "setting_preference": "manual" });
+    for (i, server) in servers.iter().enumerate() {
+        setting[format!("ntp_server_{}", i + 1)] = serde_json::json!(server);
+    }
+    Ok(setting)
+}
+
+/// Port index in a switch interface name such as `port[3]`, `port3` or `3`
+fn port_index(name: &str) -> Option<u32> {
+    let name = name.trim_end_matches(']');
+    let prefix_len = name.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    name[prefix_len..].parse().ok()
+}
+
+/// Device fields, besides the name, restored when a configuration is rolled back
+const SNAPSHOT_FIELDS: [&str; 2] = ["config_network", "port_overrides"];
+
+/// Device payload for the parts of a request UniFi keeps on the device
+///
+/// An interface with an IPv4 address sets the static management address,
+/// which also carries the DNS servers. Other interfaces become port
+/// overrides; their `vlan_id` is resolved to a network through `networks`.
+/// UniFi replaces the whole `port_overrides` list, so ports left out lose
+/// their overrides.
+fn device_payload(
+    request: &DeviceConfigRequest,
+    networks: &HashMap<u16, String>,
+) -> Result<serde_json::Value, UniFiError> {
+    let mut payload = serde_json::Map::new();
+    if let Some(ref hostname) = request.hostname {
+        payload.insert("name".to_string(), serde_json::json!(hostname));
+    }
+
+    let mut management = None;
+    let mut overrides = Vec::new();
+    for iface in &request.interfaces {
+        match (iface.ip_address, iface.prefix_len) {
+            (Some(std::net::IpAddr::V4(addr)), Some(prefix)) => {
+                if management.replace((addr, prefix)).is_some() {
+                    return Err(UniFiError::Parse("UniFi devices have a single management address".to_string()));
+                }
             }
-            "EVT_AP_RestartedUnknown" | "EVT_SW_RestartedUnknown" | "EVT_GW_RestartedUnknown" => {
-                let mac_str = vendor_event.get("ap")
-                    .or_else(|| vendor_event.get("sw"))
-                    .or_else(|| vendor_event.get("gw"))
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| FunctorError::MappingFailed("Missing device MAC".to_string()))?;
-
-                let mac = MacAddress::parse(mac_str)
-                    .map_err(|e| FunctorError::MappingFailed(e.to_string()))?;
-
-                let device_id = self.get_device_by_mac(&mac)
-                    .ok_or_else(|| FunctorError::MappingFailed(
-                        format!("Unknown device MAC: {}", mac)
-                    ))?;
-
-                // Restart events result in device reprovisioning
-                Ok(NetworkEvent::DeviceProvisioned {
-                    device_id,
-                    model: vendor_event.get("model")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("unknown")
-                        .to_string(),
-                    firmware_version: vendor_event.get("version")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("unknown")
-                        .to_string(),
-                })
+            (Some(addr), _) => {
+                return Err(UniFiError::Parse(format!(
+                    "Management address {} on {} must be IPv4 with a prefix length",
+                    addr, iface.name
+                )))
+            }
+            (None, _) => {
+                let port_idx = port_index(&iface.name).ok_or_else(|| {
+                    UniFiError::Parse(format!("No port index in interface name '{}'", iface.name))
+                })?;
+                let mut port = serde_json::json!({ "port_idx": port_idx, "name": iface.name });
+                if !iface.enabled {
+                    port["forward"] = serde_json::json!("disabled");
+                } else if let Some(vlan_id) = iface.vlan_id {
+                    let network = networks.get(&vlan_id).ok_or_else(|| {
+                        UniFiError::Parse(format!("No UniFi network carries VLAN {}", vlan_id))
+                    })?;
+                    port["forward"] = serde_json::json!("native");
+                    port["native_networkconf_id"] = serde_json::json!(network);
+                }
+                overrides.push(port);
             }
-            _ => Err(FunctorError::MappingFailed(format!("Unknown event type: {}", event_type))),
         }
     }
+
+    if request.dns_servers.len() > 2 {
+        return Err(UniFiError::Parse(format!(
+            "UniFi devices take at most 2 DNS servers, got {}",
+            request.dns_servers.len()
+        )));
+    }
+    match management {
+        Some((addr, prefix)) => {
+            let network = ipnetwork::Ipv4Network::new(addr, prefix)
+                .map_err(|e| UniFiError::Parse(e.to_string()))?;
+            let mut config = serde_json::json!({
+                "type": "static",
+                "ip": addr.to_string(),
+                "netmask": network.mask().to_string(),
+            });
+            for (i, server) in request.dns_servers.iter().enumerate() {
+                config[format!("dns{}", i + 1)] = serde_json::json!(server.to_string());
+            }
+            payload.insert("config_network".to_string(), config);
+        }
+        None if !request.dns_servers.is_empty() => {
+            return Err(UniFiError::Parse(
+                "UniFi devices take DNS servers only with a static management address".to_string(),
+            ))
+        }
+        None => {}
+    }
+
+    if !overrides.is_empty() {
+        payload.insert("port_overrides".to_string(), serde_json::Value::Array(overrides));
+    }
+    Ok(serde_json::Value::Object(payload))
 }
 
-/// Convert Mbps to LinkSpeed
-fn speed_from_mbps(mbps: u32) -> Option<LinkSpeed> {
-    match mbps {
-        10 => Some(LinkSpeed::Mbps10),
-        100 => Some(LinkSpeed::Mbps100),
-        1000 => Some(LinkSpeed::Gbps1),
-        2500 => Some(LinkSpeed::Gbps2_5),
-        5000 => Some(LinkSpeed::Gbps5),
-        10000 => Some(LinkSpeed::Gbps10),
-        25000 => Some(LinkSpeed::Gbps25),
-        40000 => Some(LinkSpeed::Gbps40),
-        100000 => Some(LinkSpeed::Gbps100),
-        _ => None,
+/// Typed view of a device's settings, the inverse of `device_payload`
+///
+/// Port overrides become interfaces named as they were applied, falling
+/// back to `port<idx>`; a `native` override maps its network back to a VLAN
+/// ID through `networks`. The NTP servers come from the site `ntp` setting.
+fn running_config(
+    device: &UniFiDevice,
+    networks: &[UniFiNetwork],
+    ntp: Option<&serde_json::Value>,
+) -> DeviceConfigRequest {
+    let vlan_of = |network_id: &str| {
+        networks.iter()
+            .find(|network| network.id == network_id && network.vlan_enabled)
+            .and_then(|network| network.vlan)
+    };
+
+    let mut config = DeviceConfigRequest::default().with_hostname(device.name.clone());
+    config.vlans = networks.iter()
+        .filter(|network| network.vlan_enabled)
+        .filter_map(|network| network.vlan.and_then(|id| VlanConfig::new(id, network.name.clone()).ok()))
+        .collect();
+
+    if let Some(network) = device.properties.get("config_network")
+        .filter(|network| network["type"] == "static")
+    {
+        let field = |key: &str| network.get(key).and_then(|v| v.as_str());
+        let address: Option<std::net::Ipv4Addr> = field("ip").and_then(|ip| ip.parse().ok());
+        let prefix_len = field("netmask")
+            .and_then(|mask| mask.parse().ok())
+            .and_then(|mask| ipnetwork::ipv4_mask_to_prefix(mask).ok());
+        config.interfaces.push(InterfaceConfig {
+            name: "management".to_string(),
+            ip_address: address.map(std::net::IpAddr::V4),
+            prefix_len,
+            vlan_id: None,
+            enabled: true,
+            mtu: None,
+            speed: None,
+        });
+        config.dns_servers = ["dns1", "dns2"]
+            .into_iter()
+            .filter_map(|key| field(key).and_then(|server| server.parse().ok()))
+            .collect();
+    }
+
+    let overrides = device.properties.get("port_overrides").and_then(|v| v.as_array());
+    for port in overrides.into_iter().flatten() {
+        let Some(port_idx) = port.get("port_idx").and_then(|v| v.as_u64()) else {
+            continue;
+        };
+        let forward = port.get("forward").and_then(|v| v.as_str());
+        let vlan_id = match forward {
+            Some("native") => port.get("native_networkconf_id").and_then(|v| v.as_str()).and_then(vlan_of),
+            _ => None,
+        };
+        config.interfaces.push(InterfaceConfig {
+            name: port.get("name")
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("port{}", port_idx)),
+            ip_address: None,
+            prefix_len: None,
+            vlan_id,
+            enabled: forward != Some("disabled"),
+            mtu: None,
+            speed: None,
+        });
+    }
+
+    if let Some(ntp) = ntp {
+        config.ntp_servers = (1..=4)
+            .filter_map(|i| ntp.get(format!("ntp_server_{}", i)).and_then(|v| v.as_str()))
+            .filter(|server| !server.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+    config
+}
+
+/// Check that the controller's offered upgrade is `target_version`
+///
+/// The devmgr `upgrade` command installs whatever firmware the controller
+/// offers (`upgrade_to_firmware`); it cannot select a version.
+fn check_upgrade_target(device: &UniFiDevice, target_version: &str) -> Result<(), UniFiError> {
+    match device.properties.get("upgrade_to_firmware").and_then(|v| v.as_str()) {
+        Some(offered) if offered == target_version => Ok(()),
+        Some(offered) => Err(UniFiError::Api(format!(
+            "Controller offers firmware {} for {}, not {}",
+            offered, device.mac, target_version
+        ))),
+        None => Err(UniFiError::Api(format!("No firmware upgrade available for {}", device.mac))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn adapter() -> UniFiAdapter {
+        UniFiAdapter::new("https://unifi.local:8443", "admin", "secret", "default")
+            .await
+            .unwrap()
+    }
+
+    // ==========================================================================
+    // Event Translation Tests
+    // ==========================================================================
+
+    #[tokio::test]
+    async fn test_connected_event_resolves_mapped_device() {
+        let adapter = adapter().await;
+        let device_id = DeviceId::new();
+        let mac = MacAddress::parse("24:a4:3c:01:02:03").unwrap();
+        adapter.map_device(device_id, "5f1e0c9a2b".to_string(), mac).await;
+
+        let event = adapter.to_domain_event(&serde_json::json!({
+            "key": "EVT_SW_Connected",
+            "sw": "24:a4:3c:01:02:03",
+            "sw_name": "core-sw",
+            "model": "USW-24-POE",
+            "version": "6.6.65",
+            "msg": "Switch[24:a4:3c:01:02:03] was connected",
+        })).unwrap();
+
+        match event {
+            NetworkEvent::DeviceProvisioned { device_id: id, model, firmware_version } => {
+                assert_eq!(id, device_id);
+                assert_eq!(model, "USW-24-POE");
+                assert_eq!(firmware_version, "6.6.65");
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_for_unknown_mac_is_rejected() {
+        let adapter = adapter().await;
+
+        let result = adapter.to_domain_event(&serde_json::json!({
+            "key": "EVT_AP_Disconnected",
+            "ap": "24:a4:3c:0a:0b:0c",
+        }));
+
+        let expected = MacAddress::parse("24:a4:3c:0a:0b:0c").unwrap();
+        assert!(matches!(result, Err(FunctorError::UnknownDevice(mac)) if mac == expected));
+    }
+
+    #[tokio::test]
+    async fn test_disconnected_event_defaults_message() {
+        let adapter = adapter().await;
+        let device_id = DeviceId::new();
+        adapter.register_mac(MacAddress::parse("24:a4:3c:01:02:03").unwrap(), device_id);
+
+        let event = adapter.to_domain_event(&serde_json::json!({
+            "key": "EVT_AP_Disconnected",
+            "ap": "24:A4:3C:01:02:03",
+        })).unwrap();
+
+        assert!(matches!(
+            event,
+            NetworkEvent::DeviceError { device_id: id, ref message } if id == device_id && message == "Device disconnected"
+        ));
+    }
+
+    // ==========================================================================
+    // Firmware Tests
+    // ==========================================================================
+
+    fn device_offering(upgrade_to: Option<&str>) -> UniFiDevice {
+        let mut device: UniFiDevice = serde_json::from_value(serde_json::json!({
+            "_id": "5f1e0c9a2b",
+            "mac": "24:a4:3c:01:02:03",
+            "model": "USW-24-POE",
+            "name": "core-sw",
+            "adopted": true,
+            "type": "usw",
+            "version": "6.5.59",
+        })).unwrap();
+        if let Some(version) = upgrade_to {
+            device.properties.insert("upgrade_to_firmware".to_string(), serde_json::json!(version));
+        }
+        device
+    }
+
+    #[test]
+    fn test_upgrade_target_must_match_offer() {
+        assert!(check_upgrade_target(&device_offering(Some("6.6.65")), "6.6.65").is_ok());
+        assert!(matches!(
+            check_upgrade_target(&device_offering(Some("6.6.65")), "7.0.1"),
+            Err(UniFiError::Api(msg)) if msg.contains("offers firmware 6.6.65")
+        ));
+        assert!(matches!(
+            check_upgrade_target(&device_offering(None), "6.6.65"),
+            Err(UniFiError::Api(msg)) if msg.contains("No firmware upgrade")
+        ));
+    }
+
+    // ==========================================================================
+    // Typed Config Tests
+    // ==========================================================================
+
+    fn config_request() -> DeviceConfigRequest {
+        DeviceConfigRequest::default()
+            .with_hostname("core-sw1")
+            .with_vlan(VlanConfig::new(20, "iot").unwrap())
+            .with_interface(InterfaceConfig {
+                name: "Vlan20".to_string(),
+                ip_address: Some("10.0.20.2".parse().unwrap()),
+                prefix_len: Some(24),
+                vlan_id: None,
+                enabled: true,
+                mtu: None,
+                speed: None,
+            })
+            .with_interface(InterfaceConfig {
+                name: "GigabitEthernet0/3".to_string(),
+                ip_address: None,
+                prefix_len: None,
+                vlan_id: Some(20),
+                enabled: true,
+                mtu: None,
+                speed: None,
+            })
+            .with_ntp_server("pool.ntp.org")
+            .with_dns_server("10.0.20.1".parse().unwrap())
+    }
+
+    #[test]
+    fn test_typed_request_to_unifi_payloads() {
+        let request = config_request();
+        let networks = HashMap::from([(20, "net-iot".to_string())]);
+
+        assert_eq!(device_payload(&request, &networks).unwrap(), serde_json::json!({
+            "name": "core-sw1",
+            "config_network": {
+                "type": "static",
+                "ip": "10.0.20.2",
+                "netmask": "255.255.255.0",
+                "dns1": "10.0.20.1",
+            },
+            "port_overrides": [{
+                "port_idx": 3,
+                "name": "GigabitEthernet0/3",
+                "forward": "native",
+                "native_networkconf_id": "net-iot",
+            }],
+        }));
+        assert_eq!(network_payload(&request.vlans[0])["vlan"], 20);
+        assert_eq!(ntp_setting(&request.ntp_servers).unwrap(), serde_json::json!({
+            "setting_preference": "manual",
+            "ntp_server_1": "pool.ntp.org",
+        }));
+
+        // The same request drives the Cisco adapter
+        let ios = crate::adapters::cisco::ios_config_lines(&request);
+        assert_eq!(ios.first().map(String::as_str), Some("hostname core-sw1"));
+        assert!(ios.contains(&" switchport access vlan 20".to_string()));
+        assert!(ios.contains(&"ntp server pool.ntp.org".to_string()));
+        assert!(ios.contains(&"ip name-server 10.0.20.1".to_string()));
+    }
+
+    #[test]
+    fn test_device_payload_rejects_what_unifi_cannot_hold() {
+        let dns_only = DeviceConfigRequest::default().with_dns_server("1.1.1.1".parse().unwrap());
+        assert!(matches!(device_payload(&dns_only, &HashMap::new()), Err(UniFiError::Parse(msg)) if msg.contains("management address")));
+
+        // VLAN 20 has no network yet
+        assert!(matches!(device_payload(&config_request(), &HashMap::new()), Err(UniFiError::Parse(msg)) if msg.contains("VLAN 20")));
+
+        let unnamed_port = DeviceConfigRequest::default().with_interface(InterfaceConfig {
+            name: "uplink".to_string(),
+            ip_address: None,
+            prefix_len: None,
+            vlan_id: None,
+            enabled: false,
+            mtu: None,
+            speed: None,
+        });
+        assert!(device_payload(&unnamed_port, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_running_config_reads_back_applied_payload() {
+        let request = config_request();
+        let networks: Vec<UniFiNetwork> = serde_json::from_value(serde_json::json!([
+            { "_id": "net-iot", "name": "iot", "vlan_enabled": true, "vlan": 20 },
+            { "_id": "net-lan", "name": "LAN" },
+        ])).unwrap();
+        let mut device = device_offering(None);
+        let payload = device_payload(&request, &HashMap::from([(20, "net-iot".to_string())])).unwrap();
+        device.name = payload["name"].as_str().unwrap().to_string();
+        for field in SNAPSHOT_FIELDS {
+            device.properties.insert(field.to_string(), payload[field].clone());
+        }
+        let ntp = serde_json::json!({ "key": "ntp", "ntp_server_1": "pool.ntp.org", "ntp_server_2": "" });
+
+        let running = running_config(&device, &networks, Some(&ntp));
+
+        // Only the management interface's name is lost on the way through UniFi
+        let diffs = request.diff(&running);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].setting, "interface[Vlan20]");
+        assert_eq!(running.interfaces[0].name, "management");
+        assert_eq!(running.interfaces[0].ip_address, request.interfaces[0].ip_address);
+        assert_eq!(running.interfaces[0].prefix_len, Some(24));
+    }
+
+    #[test]
+    fn test_port_index() {
+        assert_eq!(port_index("port[3]"), Some(3));
+        assert_eq!(port_index("port12"), Some(12));
+        assert_eq!(port_index("7"), Some(7));
+        assert_eq!(port_index("uplink"), None);
+    }
+
+    #[test]
+    fn test_network_vlan_accepts_number_or_string() {
+        let networks: Vec<UniFiNetwork> = serde_json::from_value(serde_json::json!([
+            { "_id": "a", "name": "iot", "vlan_enabled": true, "vlan": 20 },
+            { "_id": "b", "name": "guest", "vlan_enabled": true, "vlan": "30" },
+            { "_id": "c", "name": "LAN", "vlan": "" },
+        ])).unwrap();
+        let vlans: Vec<_> = networks.iter().map(|n| n.vlan).collect();
+        assert_eq!(vlans, [Some(20), Some(30), None]);
     }
 }
@@ -1,5 +1,6 @@
 //! UniFi API types
 
+use crate::domain::ports::PortError;
 use crate::domain::value_objects::MacAddress;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -34,6 +35,13 @@ pub struct UniFiDevice {
     pub properties: HashMap<String, serde_json::Value>,
 }
 
+impl UniFiDevice {
+    /// When the controller last heard from the device, in Unix seconds
+    pub fn last_seen(&self) -> Option<u64> {
+        self.properties.get("last_seen").and_then(|v| v.as_u64())
+    }
+}
+
 /// UniFi device statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UniFiDeviceStats {
@@ -76,6 +84,22 @@ pub struct UniFiPortStats {
     pub tx_errors: Option<u64>,
 }
 
+/// UniFi network from `rest/networkconf`; a site's VLANs are networks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniFiNetwork {
+    /// Network ID, referenced by port overrides
+    #[serde(rename = "_id")]
+    pub id: String,
+    /// Network name
+    pub name: String,
+    /// Whether the network is tagged
+    #[serde(default)]
+    pub vlan_enabled: bool,
+    /// VLAN ID (older controllers send it as a string)
+    #[serde(default, deserialize_with = "deserialize_vlan")]
+    pub vlan: Option<u16>,
+}
+
 /// UniFi API response wrapper
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UniFiResponse<T> {
@@ -96,6 +120,25 @@ impl UniFiMeta {
     }
 }
 
+/// Controller status from `/status`, served without authentication
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniFiStatus {
+    /// Status carried in the meta block; `data` is always empty
+    pub meta: UniFiStatusMeta,
+}
+
+/// Meta block of the `/status` response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniFiStatusMeta {
+    /// Result code, `ok` on success
+    pub rc: String,
+    /// Whether the Network Application is up
+    #[serde(default)]
+    pub up: bool,
+    /// Network Application version, e.g. `8.0.26`
+    pub server_version: Option<String>,
+}
+
 /// UniFi API error
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum UniFiError {
@@ -109,6 +152,29 @@ pub enum UniFiError {
     Parse(String),
     #[error("Device not found: {0}")]
     NotFound(String),
+    /// The request timed out
+    #[error("Timed out: {0}")]
+    Timeout(String),
+    /// The controller is throttling requests; carries its `Retry-After`
+    #[error("Rate limited by the controller")]
+    RateLimited(Option<std::time::Duration>),
+    /// The controller failed with a `5xx`
+    #[error("Controller unavailable: {0}")]
+    Unavailable(String),
+}
+
+impl From<UniFiError> for PortError {
+    fn from(e: UniFiError) -> Self {
+        match e {
+            UniFiError::Http(msg) => PortError::ConnectionFailed(msg),
+            UniFiError::Timeout(msg) => PortError::Timeout(msg),
+            UniFiError::Auth(msg) => PortError::AuthenticationFailed(msg),
+            UniFiError::RateLimited(retry_after) => PortError::RateLimited { retry_after },
+            UniFiError::Unavailable(msg) => PortError::Transient(msg),
+            UniFiError::NotFound(msg) => PortError::NotFound(msg),
+            e @ (UniFiError::Api(_) | UniFiError::Parse(_)) => PortError::VendorError(e.to_string()),
+        }
+    }
 }
 
 /// Deserialize MAC address from UniFi format (no colons)
@@ -119,3 +185,20 @@ where
     let s: String = String::deserialize(deserializer)?;
     MacAddress::parse(&s).map_err(serde::de::Error::custom)
 }
+
+/// Deserialize a VLAN ID sent either as a number or as a string
+fn deserialize_vlan<'de, D>(deserializer: D) -> Result<Option<u16>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match Option::<serde_json::Value>::deserialize(deserializer)? {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(serde_json::Value::Number(n)) => n.as_u64()
+            .and_then(|id| u16::try_from(id).ok())
+            .map(Some)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid VLAN ID {}", n))),
+        Some(serde_json::Value::String(s)) if s.is_empty() => Ok(None),
+        Some(serde_json::Value::String(s)) => s.parse().map(Some).map_err(serde::de::Error::custom),
+        Some(other) => Err(serde::de::Error::custom(format!("invalid VLAN ID {}", other))),
+    }
+}
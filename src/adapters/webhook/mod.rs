@@ -0,0 +1,329 @@
+//! # Webhook Forwarder
+//!
+//! Forwards domain events to HTTP endpoints, so external automation can react
+//! to device events without consuming NATS itself.
+//!
+//! ## Routing
+//!
+//! `WebhookConfig` maps event-type patterns to destination URLs. A pattern is
+//! an event type where `*` matches any run of characters: `DeviceProvisioned`,
+//! `Device*` or `*`. An event is POSTed to every route it matches; events
+//! matching no route are acked without a request.
+//!
+//! ## Delivery
+//!
+//! Each request carries a `WebhookEnvelope` as JSON, the event type in
+//! `X-CIM-Event` and an HMAC-SHA256 of the exact body bytes in
+//! `X-CIM-Signature` as `sha256=<hex>`. Throttled and transiently failing
+//! endpoints are retried following the config's `RetryPolicy`. An event is
+//! acked once every matching endpoint has answered `2xx`; otherwise it is
+//! nacked for redelivery, which sends it again to every matching endpoint,
+//! so receivers should tolerate duplicates.
+//!
+//! ## Subscribing
+//!
+//! `EventStorePort::subscribe` only names the store's durable consumer; the
+//! forwarder reads events through an `EventSource` bound to it, such as the
+//! `NatsEventSubscriber` returned by `NatsEventStore::subscribe_events`:
+//!
+//! ```rust,ignore
+//! let config = WebhookConfig::new(Secret::new("shared-secret"))
+//!     .route("DeviceProvisioned", "https://automation.example.net/hooks/provisioned");
+//! let forwarder = WebhookForwarder::new(config)?;
+//! forwarder.run(store.subscribe_events("network.device.>").await?).await;
+//! ```
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::time::Duration;
+
+use crate::adapters::credentials::Secret;
+use crate::adapters::retry::RetryPolicy;
+use crate::domain::events::NetworkEvent;
+use crate::domain::ports::{EventAck, EventSource, PortError};
+
+/// Header carrying the body's HMAC-SHA256 signature
+pub const SIGNATURE_HEADER: &str = "X-CIM-Signature";
+
+/// Header carrying the forwarded event's type
+pub const EVENT_TYPE_HEADER: &str = "X-CIM-Event";
+
+/// Events whose type matches `pattern` are POSTed to `url`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookRoute {
+    /// Event-type pattern, `*` matching any run of characters
+    pub pattern: String,
+    /// Endpoint receiving the events
+    pub url: String,
+}
+
+impl WebhookRoute {
+    /// Route events matching `pattern` to `url`
+    pub fn new(pattern: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            url: url.into(),
+        }
+    }
+
+    /// Whether events of `event_type` take this route
+    pub fn matches(&self, event_type: &str) -> bool {
+        glob_matches(&self.pattern, event_type)
+    }
+}
+
+/// Webhook forwarder configuration
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// Destinations, by event-type pattern
+    pub routes: Vec<WebhookRoute>,
+    /// Key signing every request body
+    pub secret: Secret,
+    /// Retries for throttled or transiently failing endpoints
+    pub retry: RetryPolicy,
+    /// Timeout for each request
+    pub timeout: Duration,
+}
+
+impl WebhookConfig {
+    /// No routes yet, signing with `secret`
+    pub fn new(secret: Secret) -> Self {
+        Self {
+            routes: Vec::new(),
+            secret,
+            retry: RetryPolicy::default(),
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// POST events matching `pattern` to `url`
+    pub fn route(mut self, pattern: impl Into<String>, url: impl Into<String>) -> Self {
+        self.routes.push(WebhookRoute::new(pattern, url));
+        self
+    }
+
+    /// Retry failing endpoints following `retry`
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Give up on a request after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Body POSTed for each forwarded event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEnvelope {
+    /// Event type, e.g. `DeviceProvisioned`
+    pub event_type: String,
+    /// Kind of aggregate the event belongs to
+    pub aggregate_type: String,
+    /// Aggregate the event belongs to
+    pub aggregate_id: String,
+    /// The event itself
+    pub event: NetworkEvent,
+}
+
+impl WebhookEnvelope {
+    /// Envelope for `event`
+    pub fn new(event: &NetworkEvent) -> Self {
+        Self {
+            event_type: event.event_type().to_string(),
+            aggregate_type: event.aggregate_type().to_string(),
+            aggregate_id: event.aggregate_id(),
+            event: event.clone(),
+        }
+    }
+}
+
+/// Webhook delivery errors
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    /// The HTTP client could not be built
+    #[error("Failed to build webhook client: {0}")]
+    Client(String),
+    /// The envelope could not be encoded
+    #[error("Failed to encode webhook body: {0}")]
+    Encode(String),
+    /// The request could not be sent
+    #[error("Webhook request to {url} failed: {message}")]
+    Request {
+        /// Endpoint the request was for
+        url: String,
+        /// Why sending failed
+        message: String,
+    },
+    /// The endpoint answered with a non-success status
+    #[error("Webhook {url} answered {status}")]
+    Rejected {
+        /// Endpoint that refused the event
+        url: String,
+        /// Status it answered with
+        status: u16,
+    },
+}
+
+fn port_error(error: WebhookError) -> PortError {
+    match error {
+        WebhookError::Request { .. } => PortError::ConnectionFailed(error.to_string()),
+        other => PortError::VendorError(other.to_string()),
+    }
+}
+
+/// Signature of `body` under `secret`, as sent in `X-CIM-Signature`
+pub fn sign(secret: &Secret, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.expose().as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    let digest: String = mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", digest)
+}
+
+/// Forwards events from an `EventSource` to the configured endpoints
+pub struct WebhookForwarder {
+    config: WebhookConfig,
+    http: reqwest::Client,
+}
+
+impl WebhookForwarder {
+    /// Create a forwarder for `config`
+    pub fn new(config: WebhookConfig) -> Result<Self, WebhookError> {
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .map_err(|e| WebhookError::Client(e.to_string()))?;
+        Ok(Self { config, http })
+    }
+
+    /// Routes events of `event_type` take
+    pub fn routes_for<'a>(&'a self, event_type: &'a str) -> impl Iterator<Item = &'a WebhookRoute> + 'a {
+        self.config.routes.iter().filter(move |route| route.matches(event_type))
+    }
+
+    /// POST `event` to every matching endpoint, returning how many were sent
+    ///
+    /// Every matching endpoint is tried; the first failure is returned.
+    pub async fn forward(&self, event: &NetworkEvent) -> Result<usize, WebhookError> {
+        let body = serde_json::to_vec(&WebhookEnvelope::new(event)).map_err(|e| WebhookError::Encode(e.to_string()))?;
+        let signature = sign(&self.config.secret, &body);
+
+        let mut delivered = 0;
+        let mut failure = None;
+        for route in self.routes_for(event.event_type()) {
+            match self.post(&route.url, event.event_type(), &body, &signature).await {
+                Ok(()) => delivered += 1,
+                Err(e) => {
+                    tracing::warn!("Failed to forward {}: {}", event.event_type(), e);
+                    failure.get_or_insert(e);
+                }
+            }
+        }
+
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(delivered),
+        }
+    }
+
+    /// Forward `event`, then ack it, or nak it if any endpoint failed
+    pub async fn handle<A: EventAck>(&self, event: &NetworkEvent, ack: A) -> Result<(), PortError> {
+        match self.forward(event).await {
+            Ok(_) => ack.ack().await,
+            Err(e) => {
+                ack.nak().await?;
+                Err(port_error(e))
+            }
+        }
+    }
+
+    /// Forward every event from `source` until it ends
+    ///
+    /// Failed deliveries are nacked and logged; the loop carries on with the
+    /// next event.
+    pub async fn run<S: EventSource>(&self, mut source: S) {
+        while let Some(item) = source.next().await {
+            let result = match item {
+                Ok((event, ack)) => self.handle(&event, ack).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                tracing::warn!("Webhook forwarding failed: {}", e);
+            }
+        }
+    }
+
+    async fn post(&self, url: &str, event_type: &str, body: &[u8], signature: &str) -> Result<(), WebhookError> {
+        let response = self.config.retry
+            .send(|| {
+                self.http
+                    .post(url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .header(EVENT_TYPE_HEADER, event_type)
+                    .header(SIGNATURE_HEADER, signature)
+                    .body(body.to_vec())
+            })
+            .await
+            .map_err(|e| WebhookError::Request {
+                url: url.to_string(),
+                message: e.to_string(),
+            })?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(WebhookError::Rejected {
+                url: url.to_string(),
+                status: response.status().as_u16(),
+            })
+        }
+    }
+}
+
+/// Match `text` against `pattern`, where `*` matches any run of characters
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = text.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_patterns() {
+        assert!(WebhookRoute::new("DeviceProvisioned", "u").matches("DeviceProvisioned"));
+        assert!(!WebhookRoute::new("DeviceProvisioned", "u").matches("DeviceProvisionedLater"));
+        assert!(WebhookRoute::new("Device*", "u").matches("DeviceAdopting"));
+        assert!(!WebhookRoute::new("Device*", "u").matches("ConnectionEstablished"));
+        assert!(WebhookRoute::new("*Failed", "u").matches("DeviceAdoptionFailed"));
+        assert!(WebhookRoute::new("Device*Failed", "u").matches("DeviceAdoptionFailed"));
+        assert!(!WebhookRoute::new("Device*Failed", "u").matches("DeviceFailedOver"));
+        assert!(WebhookRoute::new("*", "u").matches("AnythingAtAll"));
+    }
+
+    #[test]
+    fn test_sign_matches_rfc_4231_vector() {
+        assert_eq!(
+            sign(&Secret::new("Jefe"), b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! # HTTP API
+//!
+//! An axum router driving `NetworkService` over HTTP (feature `api`).
+//! Requests and responses carry the domain types as JSON:
+//!
+//! ```text
+//! POST /devices/discover      discover_devices  → {"device_ids": [...]}
+//! POST /devices/{id}/adopt    adopt_device      → the adopted device
+//! GET  /devices[?state=...]   find_devices      → every matching device
+//! GET  /devices/{id}          get_device        → the device
+//! GET  /events/stream         subscribe_events  → server-sent events
+//! ```
+//!
+//! `/events/stream` sends one SSE message per domain event appended after
+//! the request, named after the event type with the event as JSON data. It
+//! follows `network.>` unless a `?subject=` is given.
+//!
+//! Failures are answered `{"error": "..."}` with the status from
+//! `ApiError::status`: malformed requests are `400`, unknown devices `404`,
+//! operations the device's state forbids `409`, and adapter or event store
+//! failures `5xx`.
+//!
+//! ```rust,ignore
+//! let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await?;
+//! cim_network::api::serve(listener, Arc::new(service)).await?;
+//! ```
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::aggregates::{DeviceState, NetworkDeviceAggregate};
+use crate::domain::ports::PortError;
+use crate::domain::value_objects::DeviceId;
+use crate::service::{DeviceFilter, NetworkService, ServiceError};
+
+/// Subject `/events/stream` follows when the request names none
+pub const DEFAULT_EVENTS_SUBJECT: &str = "network.>";
+
+/// Router serving the API for `service`
+pub fn router(service: Arc<NetworkService>) -> Router {
+    Router::new()
+        .route("/devices", get(list_devices))
+        .route("/devices/discover", post(discover_devices))
+        .route("/devices/{id}", get(get_device))
+        .route("/devices/{id}/adopt", post(adopt_device))
+        .route("/events/stream", get(stream_events))
+        .with_state(service)
+}
+
+/// Serve the API for `service` on `listener` until the server fails
+pub async fn serve(listener: tokio::net::TcpListener, service: Arc<NetworkService>) -> std::io::Result<()> {
+    axum::serve(listener, router(service)).await
+}
+
+/// Response to `POST /devices/discover`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoverResponse {
+    /// Devices found by the discovery run, new and already known
+    pub device_ids: Vec<DeviceId>,
+}
+
+/// Query parameters of `GET /devices`
+#[derive(Debug, Default, Deserialize)]
+struct DevicesQuery {
+    state: Option<DeviceState>,
+}
+
+/// Query parameters of `GET /events/stream`
+#[derive(Debug, Default, Deserialize)]
+struct EventsQuery {
+    subject: Option<String>,
+}
+
+/// Body of every error response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorBody {
+    /// What went wrong
+    pub error: String,
+}
+
+/// Errors answered by the API
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    /// The request is malformed
+    #[error("{0}")]
+    BadRequest(String),
+
+    /// The service refused or failed the operation
+    #[error(transparent)]
+    Service(#[from] ServiceError),
+}
+
+impl ApiError {
+    /// HTTP status for this error
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Service(error) => service_status(error),
+        }
+    }
+}
+
+/// HTTP status for a service error; a failed batch answers as its failed command
+fn service_status(error: &ServiceError) -> StatusCode {
+    match error {
+        ServiceError::DeviceNotFound(_)
+        | ServiceError::ConnectionNotFound(_)
+        | ServiceError::SiteNotFound(_) => StatusCode::NOT_FOUND,
+        ServiceError::InvalidTransition { .. } | ServiceError::ConcurrencyConflict { .. } => StatusCode::CONFLICT,
+        ServiceError::Aggregate(_) | ServiceError::Template(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        ServiceError::BatchCommandFailed { source, .. } => service_status(source),
+        ServiceError::Port(error) => match error {
+            PortError::NotFound(_) => StatusCode::NOT_FOUND,
+            PortError::NotSupported(_) => StatusCode::NOT_IMPLEMENTED,
+            PortError::RateLimited { .. } | PortError::Transient(_) => StatusCode::SERVICE_UNAVAILABLE,
+            PortError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            _ => StatusCode::BAD_GATEWAY,
+        },
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let retry_after = match &self {
+            Self::Service(ServiceError::Port(PortError::RateLimited { retry_after })) => *retry_after,
+            _ => None,
+        };
+        let body = Json(ErrorBody { error: self.to_string() });
+
+        match retry_after {
+            Some(delay) => (status, [(header::RETRY_AFTER, delay.as_secs().max(1).to_string())], body).into_response(),
+            None => (status, body).into_response(),
+        }
+    }
+}
+
+/// Parse a device id from a path segment
+fn parse_device_id(id: &str) -> Result<DeviceId, ApiError> {
+    uuid::Uuid::parse_str(id)
+        .map(DeviceId::from_uuid)
+        .map_err(|_| ApiError::BadRequest(format!("Invalid device id: {}", id)))
+}
+
+/// Check a NATS-style subject: dot-separated non-empty tokens, no whitespace
+fn validate_subject(subject: &str) -> Result<(), ApiError> {
+    let valid = !subject.is_empty()
+        && !subject.chars().any(char::is_whitespace)
+        && subject.split('.').all(|token| !token.is_empty());
+    if valid {
+        Ok(())
+    } else {
+        Err(ApiError::BadRequest(format!("Invalid subject: {:?}", subject)))
+    }
+}
+
+async fn discover_devices(State(service): State<Arc<NetworkService>>) -> Result<Json<DiscoverResponse>, ApiError> {
+    let device_ids = service.discover_devices().await?;
+    Ok(Json(DiscoverResponse { device_ids }))
+}
+
+async fn adopt_device(
+    State(service): State<Arc<NetworkService>>,
+    Path(id): Path<String>,
+) -> Result<Json<NetworkDeviceAggregate>, ApiError> {
+    let device_id = parse_device_id(&id)?;
+    service.adopt_device(device_id).await?;
+    let device = service.get_device(device_id).await.ok_or(ServiceError::DeviceNotFound(device_id))?;
+    Ok(Json(device))
+}
+
+async fn list_devices(
+    State(service): State<Arc<NetworkService>>,
+    query: Result<Query<DevicesQuery>, QueryRejection>,
+) -> Result<Json<Vec<NetworkDeviceAggregate>>, ApiError> {
+    let Query(query) = query.map_err(|e| ApiError::BadRequest(e.body_text()))?;
+    let mut filter = DeviceFilter::new();
+    if let Some(state) = query.state {
+        filter = filter.state(state);
+    }
+
+    let mut devices = service.find_devices(filter).await;
+    devices.sort_by_key(|device| device.id().to_string());
+    Ok(Json(devices))
+}
+
+async fn get_device(
+    State(service): State<Arc<NetworkService>>,
+    Path(id): Path<String>,
+) -> Result<Json<NetworkDeviceAggregate>, ApiError> {
+    let device_id = parse_device_id(&id)?;
+    let device = service.get_device(device_id).await.ok_or(ServiceError::DeviceNotFound(device_id))?;
+    Ok(Json(device))
+}
+
+async fn stream_events(
+    State(service): State<Arc<NetworkService>>,
+    query: Result<Query<EventsQuery>, QueryRejection>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let Query(query) = query.map_err(|e| ApiError::BadRequest(e.body_text()))?;
+    let subject = query.subject.unwrap_or_else(|| DEFAULT_EVENTS_SUBJECT.to_string());
+    validate_subject(&subject)?;
+
+    let events = service.subscribe_events(&subject).await?;
+    let messages = events.filter_map(|item| async move {
+        match item {
+            Ok(event) => match Event::default().event(event.event_type()).json_data(&event) {
+                Ok(message) => Some(Ok(message)),
+                Err(e) => {
+                    tracing::warn!("Failed to encode {} for the event stream: {}", event.event_type(), e);
+                    None
+                }
+            },
+            Err(e) => {
+                tracing::warn!("Skipping event on the event stream: {}", e);
+                None
+            }
+        }
+    });
+
+    Ok(Sse::new(messages).keep_alive(KeepAlive::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::aggregates::AggregateError;
+
+    #[test]
+    fn test_service_errors_map_to_statuses() {
+        let status = |error: ServiceError| ApiError::from(error).status();
+
+        assert_eq!(status(ServiceError::DeviceNotFound(DeviceId::new())), StatusCode::NOT_FOUND);
+        assert_eq!(
+            status(ServiceError::InvalidTransition { from: DeviceState::Provisioned, to: DeviceState::Adopting }),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            status(ServiceError::Aggregate(AggregateError::InvalidState {
+                current: DeviceState::Discovered,
+                operation: "purge".to_string(),
+            })),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(status(PortError::NotSupported("x".to_string()).into()), StatusCode::NOT_IMPLEMENTED);
+        assert_eq!(status(PortError::Timeout("x".to_string()).into()), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(status(PortError::ConnectionFailed("x".to_string()).into()), StatusCode::BAD_GATEWAY);
+    }
+
+    #[test]
+    fn test_validate_subject() {
+        assert!(validate_subject("network.>").is_ok());
+        assert!(validate_subject("network.device.*.DeviceAdopting").is_ok());
+        assert!(validate_subject("").is_err());
+        assert!(validate_subject("network..device").is_err());
+        assert!(validate_subject("network. device").is_err());
+    }
+}
@@ -7,6 +7,7 @@
 use crate::domain::value_objects::*;
 use crate::domain::events::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 // ============================================================================
 // Device State Machine (Moore Machine)
@@ -45,6 +46,13 @@ use serde::{Deserialize, Serialize};
 ///                    │Decommissioned│ (terminal)
 ///                    └─────────────┘
 /// ```
+///
+/// A provisioned device that the vendor controller stops reporting moves to
+/// `Missing`. It returns to `Provisioned` when it reappears, or can be
+/// decommissioned from there.
+///
+/// `DeviceState::to_mermaid_state_diagram` renders the full machine,
+/// including `Missing`, from `valid_transitions`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DeviceState {
     /// Device discovered but not yet adopted
@@ -57,19 +65,37 @@ pub enum DeviceState {
     Configuring,
     /// Device encountered an error
     Error,
+    /// Device is no longer reported by the vendor controller
+    Missing,
     /// Device has been decommissioned (terminal state)
     Decommissioned,
 }
 
 impl DeviceState {
+    /// Every state, in lifecycle order
+    pub const ALL: [DeviceState; 7] = [
+        DeviceState::Discovered,
+        DeviceState::Adopting,
+        DeviceState::Provisioned,
+        DeviceState::Configuring,
+        DeviceState::Error,
+        DeviceState::Missing,
+        DeviceState::Decommissioned,
+    ];
+
     /// Get valid transitions from this state
     pub fn valid_transitions(&self) -> &[DeviceState] {
         match self {
             DeviceState::Discovered => &[DeviceState::Adopting, DeviceState::Decommissioned],
             DeviceState::Adopting => &[DeviceState::Provisioned, DeviceState::Error],
-            DeviceState::Provisioned => &[DeviceState::Configuring, DeviceState::Decommissioned],
+            DeviceState::Provisioned => &[
+                DeviceState::Configuring,
+                DeviceState::Missing,
+                DeviceState::Decommissioned,
+            ],
             DeviceState::Configuring => &[DeviceState::Provisioned, DeviceState::Error],
             DeviceState::Error => &[DeviceState::Adopting, DeviceState::Decommissioned],
+            DeviceState::Missing => &[DeviceState::Provisioned, DeviceState::Decommissioned],
             DeviceState::Decommissioned => &[], // Terminal state
         }
     }
@@ -92,9 +118,74 @@ impl DeviceState {
             DeviceState::Provisioned => "Provisioned",
             DeviceState::Configuring => "Configuring",
             DeviceState::Error => "Error",
+            DeviceState::Missing => "Missing",
             DeviceState::Decommissioned => "Decommissioned",
         }
     }
+
+    /// State a device is in after `event`, if the event moves it at all
+    ///
+    /// `DeviceDiscovered` yields `Discovered`; events that do not change a
+    /// device's lifecycle (renames, tags, inventory syncs) yield `None`.
+    pub fn after_event(event: &NetworkEvent) -> Option<DeviceState> {
+        match event {
+            NetworkEvent::DeviceDiscovered { .. } => Some(DeviceState::Discovered),
+            NetworkEvent::DeviceAdopting { .. } => Some(DeviceState::Adopting),
+            NetworkEvent::DeviceProvisioned { .. }
+            | NetworkEvent::DeviceConfigured { .. }
+            | NetworkEvent::DeviceReappeared { .. }
+            | NetworkEvent::ConfigRolledBack { .. }
+            | NetworkEvent::FirmwareUpgradeCompleted { .. } => Some(DeviceState::Provisioned),
+            NetworkEvent::DeviceConfiguring { .. } | NetworkEvent::FirmwareUpgradeStarted { .. } => {
+                Some(DeviceState::Configuring)
+            }
+            NetworkEvent::DeviceError { .. } => Some(DeviceState::Error),
+            NetworkEvent::DeviceWentMissing { .. } => Some(DeviceState::Missing),
+            NetworkEvent::DeviceDecommissioned { .. } => Some(DeviceState::Decommissioned),
+            _ => None,
+        }
+    }
+
+    /// Aggregate command(s) that move a device from this state to `target`
+    fn transition_command(&self, target: DeviceState) -> &'static str {
+        match (self, target) {
+            (_, DeviceState::Adopting) => "adopt",
+            (DeviceState::Adopting, DeviceState::Provisioned) => "mark_provisioned",
+            (DeviceState::Configuring, DeviceState::Provisioned) => {
+                "complete_configuration / complete_firmware_upgrade"
+            }
+            (DeviceState::Missing, DeviceState::Provisioned) => "mark_reappeared",
+            (_, DeviceState::Configuring) => "start_configuration / start_firmware_upgrade",
+            (_, DeviceState::Error) => "record_error",
+            (_, DeviceState::Missing) => "mark_missing",
+            (_, DeviceState::Decommissioned) => "decommission",
+            _ => "transition",
+        }
+    }
+
+    /// Render the lifecycle as a Mermaid `stateDiagram-v2`
+    ///
+    /// Generated from `valid_transitions`, so it cannot drift from the state
+    /// machine. Edges are labelled with the commands that cause them and
+    /// terminal states lead to the end marker `[*]`.
+    pub fn to_mermaid_state_diagram() -> String {
+        let mut diagram = String::from("stateDiagram-v2\n");
+        diagram.push_str(&format!("    [*] --> {}\n", DeviceState::default().name()));
+        for state in DeviceState::ALL {
+            for &target in state.valid_transitions() {
+                diagram.push_str(&format!(
+                    "    {} --> {} : {}\n",
+                    state.name(),
+                    target.name(),
+                    state.transition_command(target)
+                ));
+            }
+        }
+        for state in DeviceState::ALL.iter().filter(|state| state.is_terminal()) {
+            diagram.push_str(&format!("    {} --> [*]\n", state.name()));
+        }
+        diagram
+    }
 }
 
 impl Default for DeviceState {
@@ -107,6 +198,29 @@ impl Default for DeviceState {
 // Network Device Aggregate
 // ============================================================================
 
+/// An inventory sync that failed, waiting to be retried or given up on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventorySyncFailure {
+    /// Failed attempts so far
+    pub attempts: u32,
+    /// Error of the latest attempt
+    pub error: String,
+    /// When the next attempt is due; `None` once the sync was given up on
+    pub retry_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl InventorySyncFailure {
+    /// Whether the sync was given up on
+    pub fn is_dead_lettered(&self) -> bool {
+        self.retry_at.is_none()
+    }
+
+    /// Whether the next attempt is due at `now`; never for a dead letter
+    pub fn is_due(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.retry_at.is_some_and(|retry_at| retry_at <= now)
+    }
+}
+
 /// Network device aggregate - consistency boundary for a single device
 ///
 /// This aggregate:
@@ -139,6 +253,20 @@ pub struct NetworkDeviceAggregate {
     interfaces: Vec<InterfaceConfig>,
     /// VLAN configurations
     vlans: Vec<VlanConfig>,
+    /// Inventory fingerprint last synced, by inventory system
+    inventory_hashes: HashMap<String, String>,
+    /// Operator-assigned tags
+    #[serde(default)]
+    tags: BTreeSet<Tag>,
+    /// Physical units, when the device stands for a stack or virtual chassis
+    #[serde(default)]
+    stack_members: Vec<StackMember>,
+    /// Stack this unit was merged into, if any
+    #[serde(default)]
+    stacked_into: Option<DeviceId>,
+    /// Failed inventory syncs not yet retried successfully, by inventory system
+    #[serde(default)]
+    inventory_sync_failures: BTreeMap<String, InventorySyncFailure>,
     /// Pending events (not yet persisted)
     #[serde(skip)]
     pending_events: Vec<NetworkEvent>,
@@ -167,6 +295,11 @@ impl NetworkDeviceAggregate {
             vendor_id: None,
             interfaces: Vec::new(),
             vlans: Vec::new(),
+            inventory_hashes: HashMap::new(),
+            tags: BTreeSet::new(),
+            stack_members: Vec::new(),
+            stacked_into: None,
+            inventory_sync_failures: BTreeMap::new(),
             pending_events: Vec::new(),
             error_message: None,
         };
@@ -201,6 +334,11 @@ impl NetworkDeviceAggregate {
             vendor_id: None,
             interfaces: Vec::new(),
             vlans: Vec::new(),
+            inventory_hashes: HashMap::new(),
+            tags: BTreeSet::new(),
+            stack_members: Vec::new(),
+            stacked_into: None,
+            inventory_sync_failures: BTreeMap::new(),
             pending_events: Vec::new(),
             error_message: None,
         }
@@ -208,42 +346,37 @@ impl NetworkDeviceAggregate {
 
     /// Reconstruct from events
     pub fn from_events(events: impl IntoIterator<Item = NetworkEvent>) -> Option<Self> {
-        let mut device: Option<Self> = None;
+        events.into_iter().fold(None, Self::replay_event)
+    }
 
-        for event in events {
-            match &event {
-                NetworkEvent::DeviceDiscovered {
-                    device_id,
-                    mac,
-                    device_type,
-                    ip_address,
-                } => {
-                    device = Some(Self {
-                        id: *device_id,
-                        state: DeviceState::Discovered,
-                        version: 1,
-                        mac: *mac,
-                        device_type: device_type.clone(),
-                        name: format!("Device-{}", &device_id.to_string()[..8]),
-                        model: None,
-                        firmware_version: None,
-                        ip_address: *ip_address,
-                        vendor_id: None,
-                        interfaces: Vec::new(),
-                        vlans: Vec::new(),
-                        pending_events: Vec::new(),
-                        error_message: None,
-                    });
-                }
-                _ => {
-                    if let Some(ref mut d) = device {
-                        d.apply_existing_event(&event);
-                    }
-                }
-            }
+    /// Fold one persisted event onto a (possibly not yet created) aggregate
+    ///
+    /// `DeviceDiscovered` (re)creates the aggregate; any other event is applied
+    /// to an existing aggregate and ignored before discovery. Lets callers
+    /// rebuild an aggregate incrementally from a stream of events.
+    pub fn replay_event(device: Option<Self>, event: NetworkEvent) -> Option<Self> {
+        match event {
+            NetworkEvent::DeviceDiscovered {
+                device_id,
+                mac,
+                device_type,
+                ip_address,
+            } => Some(Self::from_discovered_event(device_id, mac, device_type, ip_address)),
+            event => device.map(|mut d| {
+                d.apply_existing_event(&event);
+                d
+            }),
         }
+    }
 
-        device
+    /// Fold previously persisted events onto this aggregate
+    ///
+    /// Used to bring a snapshot up to date; each event bumps the version
+    /// exactly as it does in `from_events`.
+    pub fn apply_history(&mut self, events: impl IntoIterator<Item = NetworkEvent>) {
+        for event in events {
+            self.apply_existing_event(&event);
+        }
     }
 
     // Getters
@@ -279,10 +412,93 @@ impl NetworkDeviceAggregate {
         self.vendor_id.as_deref()
     }
 
+    /// Firmware version reported at provisioning or by the last upgrade
+    pub fn firmware_version(&self) -> Option<&str> {
+        self.firmware_version.as_deref()
+    }
+
     pub fn interfaces(&self) -> &[InterfaceConfig] {
         &self.interfaces
     }
 
+    pub fn vlans(&self) -> &[VlanConfig] {
+        &self.vlans
+    }
+
+    /// Operator-assigned tags, in key order
+    pub fn tags(&self) -> &BTreeSet<Tag> {
+        &self.tags
+    }
+
+    /// Whether the device carries `tag`
+    pub fn has_tag(&self, tag: &Tag) -> bool {
+        self.tags.contains(tag)
+    }
+
+    /// Physical units of the stack, in member order (empty unless stacked)
+    pub fn stack_members(&self) -> &[StackMember] {
+        &self.stack_members
+    }
+
+    /// Whether the device stands for a stack or virtual chassis
+    pub fn is_stack(&self) -> bool {
+        !self.stack_members.is_empty()
+    }
+
+    /// The stack this unit was merged into, if any
+    pub fn stacked_into(&self) -> Option<DeviceId> {
+        self.stacked_into
+    }
+
+    /// Whether `mac` is the device's own or one of its stack members'
+    pub fn has_mac(&self, mac: MacAddress) -> bool {
+        self.mac == mac || self.stack_members.iter().any(|m| m.mac == mac)
+    }
+
+    /// Fingerprint of the fields an inventory system records
+    ///
+    /// Covers identity, state, addressing and configuration but not the
+    /// version, so it only changes when there is something new to sync.
+    pub fn inventory_fingerprint(&self) -> String {
+        let mut content = serde_json::json!({
+            "name": self.name,
+            "mac": self.mac,
+            "device_type": self.device_type,
+            "state": self.state,
+            "model": self.model,
+            "firmware_version": self.firmware_version,
+            "ip_address": self.ip_address,
+            "vendor_id": self.vendor_id,
+            "interfaces": self.interfaces,
+            "vlans": self.vlans,
+            "tags": self.tags,
+        });
+        if self.is_stack() {
+            content["stack_members"] = serde_json::json!(self.stack_members);
+        }
+        // FNV-1a: stable across builds and processes, unlike `DefaultHasher`
+        let hash = content.to_string().bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        format!("{:016x}", hash)
+    }
+
+    /// Fingerprint last synced to `system`, if the device was synced there
+    pub fn inventory_hash(&self, system: &str) -> Option<&str> {
+        self.inventory_hashes.get(system).map(String::as_str)
+    }
+
+    /// Failed inventory syncs not yet retried successfully, by inventory system
+    pub fn inventory_sync_failures(&self) -> &BTreeMap<String, InventorySyncFailure> {
+        &self.inventory_sync_failures
+    }
+
+    /// Whether `system` lacks the device's current inventory fields
+    pub fn needs_inventory_sync(&self, system: &str) -> bool {
+        self.inventory_hash(system) != Some(self.inventory_fingerprint().as_str())
+            || self.inventory_sync_failures.contains_key(system)
+    }
+
     pub fn take_pending_events(&mut self) -> Vec<NetworkEvent> {
         std::mem::take(&mut self.pending_events)
     }
@@ -328,6 +544,8 @@ impl NetworkDeviceAggregate {
         interfaces: Vec<InterfaceConfig>,
         vlans: Vec<VlanConfig>,
     ) -> Result<(), AggregateError> {
+        VlanConfig::check_conflicts(&vlans)?;
+        interfaces.iter().try_for_each(InterfaceConfig::validate)?;
         self.transition_to(DeviceState::Provisioned)?;
         self.interfaces = interfaces.clone();
         self.vlans = vlans.clone();
@@ -339,56 +557,914 @@ impl NetworkDeviceAggregate {
         Ok(())
     }
 
-    /// Record an error
-    pub fn record_error(&mut self, message: String) -> Result<(), AggregateError> {
-        self.transition_to(DeviceState::Error)?;
-        self.error_message = Some(message.clone());
-        self.apply_event(NetworkEvent::DeviceError {
-            device_id: self.id,
-            message,
-        });
-        Ok(())
+    /// Record that a configuration change was rolled back
+    ///
+    /// The device returns to `Provisioned` with the interfaces and VLANs it
+    /// had before `start_configuration`.
+    pub fn record_config_rollback(&mut self, reason: String) -> Result<(), AggregateError> {
+        self.transition_to(DeviceState::Provisioned)?;
+        self.apply_event(NetworkEvent::ConfigRolledBack {
+            device_id: self.id,
+            reason,
+        });
+        Ok(())
+    }
+
+    /// Start a firmware upgrade to `target_version`
+    ///
+    /// The device is `Configuring` until the upgrade completes or fails.
+    pub fn start_firmware_upgrade(&mut self, target_version: String) -> Result<(), AggregateError> {
+        self.transition_to(DeviceState::Configuring)?;
+        self.apply_event(NetworkEvent::FirmwareUpgradeStarted {
+            device_id: self.id,
+            from_version: self.firmware_version.clone(),
+            target_version,
+        });
+        Ok(())
+    }
+
+    /// Complete a firmware upgrade, recording the new firmware version
+    pub fn complete_firmware_upgrade(&mut self, firmware_version: String) -> Result<(), AggregateError> {
+        self.transition_to(DeviceState::Provisioned)?;
+        self.firmware_version = Some(firmware_version.clone());
+        self.apply_event(NetworkEvent::FirmwareUpgradeCompleted {
+            device_id: self.id,
+            firmware_version,
+        });
+        Ok(())
+    }
+
+    /// Record an error
+    pub fn record_error(&mut self, message: String) -> Result<(), AggregateError> {
+        self.transition_to(DeviceState::Error)?;
+        self.error_message = Some(message.clone());
+        self.apply_event(NetworkEvent::DeviceError {
+            device_id: self.id,
+            message,
+        });
+        Ok(())
+    }
+
+    /// Record that the device's current fields were synced to an inventory system
+    pub fn record_inventory_sync(&mut self, inventory_id: String, system: String) {
+        let content_hash = self.inventory_fingerprint();
+        self.inventory_hashes.insert(system.clone(), content_hash.clone());
+        self.inventory_sync_failures.remove(&system);
+        self.apply_event(NetworkEvent::DeviceSyncedToInventory {
+            device_id: self.id,
+            inventory_id,
+            system,
+            content_hash: Some(content_hash),
+        });
+    }
+
+    /// Record that a sync to `system` failed and is to be retried at `retry_at`
+    pub fn queue_inventory_sync(&mut self, system: String, error: String, retry_at: chrono::DateTime<chrono::Utc>) {
+        let attempts = self.inventory_sync_attempts(&system) + 1;
+        self.inventory_sync_failures.insert(system.clone(), InventorySyncFailure {
+            attempts,
+            error: error.clone(),
+            retry_at: Some(retry_at),
+        });
+        self.apply_event(NetworkEvent::InventorySyncQueued {
+            device_id: self.id,
+            system,
+            attempts,
+            error,
+            retry_at,
+        });
+    }
+
+    /// Record that a sync to `system` failed for the last time and was given up on
+    pub fn dead_letter_inventory_sync(&mut self, system: String, error: String) {
+        let attempts = self.inventory_sync_attempts(&system) + 1;
+        self.inventory_sync_failures.insert(system.clone(), InventorySyncFailure {
+            attempts,
+            error: error.clone(),
+            retry_at: None,
+        });
+        self.apply_event(NetworkEvent::InventorySyncDeadLettered {
+            device_id: self.id,
+            system,
+            attempts,
+            error,
+        });
+    }
+
+    /// Failed attempts to sync to `system` since it last succeeded
+    pub fn inventory_sync_attempts(&self, system: &str) -> u32 {
+        self.inventory_sync_failures.get(system).map_or(0, |f| f.attempts)
+    }
+
+    /// Record that a port's link went up or down
+    pub fn record_port_link(&mut self, port_id: PortId, up: bool) {
+        self.apply_event(NetworkEvent::PortLinkChanged {
+            device_id: self.id,
+            port_id,
+            up,
+        });
+    }
+
+    /// Record that a health metric rose above its threshold
+    pub fn record_health_degraded(&mut self, metric: HealthMetric, value: f64) {
+        self.apply_event(NetworkEvent::DeviceHealthDegraded {
+            device_id: self.id,
+            metric,
+            value,
+        });
+    }
+
+    /// Record that the running configuration drifted from the desired one
+    pub fn record_config_drift(&mut self, diffs: Vec<ConfigDiff>) {
+        self.apply_event(NetworkEvent::ConfigDriftDetected {
+            device_id: self.id,
+            diffs,
+        });
+    }
+
+    /// Mark the device as no longer reported by the vendor
+    pub fn mark_missing(
+        &mut self,
+        last_seen: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<(), AggregateError> {
+        self.transition_to(DeviceState::Missing)?;
+        self.apply_event(NetworkEvent::DeviceWentMissing {
+            device_id: self.id,
+            last_seen,
+        });
+        Ok(())
+    }
+
+    /// Mark a missing device as reported by the vendor again
+    pub fn mark_reappeared(&mut self) -> Result<(), AggregateError> {
+        if self.state != DeviceState::Missing {
+            return Err(AggregateError::InvalidState {
+                current: self.state,
+                operation: "reappear".to_string(),
+            });
+        }
+        self.transition_to(DeviceState::Provisioned)?;
+        self.apply_event(NetworkEvent::DeviceReappeared {
+            device_id: self.id,
+        });
+        Ok(())
+    }
+
+    /// Decommission the device
+    pub fn decommission(&mut self) -> Result<(), AggregateError> {
+        self.transition_to(DeviceState::Decommissioned)?;
+        self.apply_event(NetworkEvent::DeviceDecommissioned {
+            device_id: self.id,
+        });
+        Ok(())
+    }
+
+    /// Update device name
+    pub fn rename(&mut self, name: String) -> Result<(), AggregateError> {
+        self.ensure_not_decommissioned("rename")?;
+        let old_name = std::mem::replace(&mut self.name, name.clone());
+        self.apply_event(NetworkEvent::DeviceRenamed {
+            device_id: self.id,
+            old_name,
+            new_name: name,
+        });
+        Ok(())
+    }
+
+    /// Add a tag to the device
+    ///
+    /// Adding a tag the device already carries records nothing.
+    pub fn add_tag(&mut self, tag: Tag) -> Result<(), AggregateError> {
+        self.ensure_not_decommissioned("tag")?;
+        if self.tags.insert(tag.clone()) {
+            self.apply_event(NetworkEvent::DeviceTagged {
+                device_id: self.id,
+                tag,
+            });
+        }
+        Ok(())
+    }
+
+    /// Remove a tag from the device
+    ///
+    /// Removing a tag the device does not carry records nothing.
+    pub fn remove_tag(&mut self, tag: &Tag) -> Result<(), AggregateError> {
+        self.ensure_not_decommissioned("untag")?;
+        if self.tags.remove(tag) {
+            self.apply_event(NetworkEvent::DeviceUntagged {
+                device_id: self.id,
+                tag: tag.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Add a physical unit to the stack the device stands for
+    ///
+    /// Adding a unit already in the stack records nothing. Fails if another
+    /// unit has the same member number or MAC, or if the device is itself a
+    /// unit merged into another stack.
+    pub fn add_stack_member(&mut self, member: StackMember) -> Result<(), AggregateError> {
+        self.ensure_not_decommissioned("stack")?;
+        if let Some(stack_id) = self.stacked_into {
+            return Err(AggregateError::AlreadyStacked { stack_id });
+        }
+        if self.stack_members.contains(&member) {
+            return Ok(());
+        }
+        if self.stack_members.iter().any(|m| m.member == member.member || m.mac == member.mac) {
+            return Err(AggregateError::StackMemberConflict { member: member.member, mac: member.mac });
+        }
+
+        self.stack_members.push(member);
+        self.stack_members.sort_by_key(|m| m.member);
+        self.apply_event(NetworkEvent::StackMemberAdded { device_id: self.id, member });
+        Ok(())
+    }
+
+    /// Split the unit with MAC `mac` out of the stack
+    ///
+    /// Returns the removed unit; removing a MAC that is not in the stack
+    /// records nothing.
+    pub fn remove_stack_member(&mut self, mac: MacAddress) -> Option<StackMember> {
+        let position = self.stack_members.iter().position(|m| m.mac == mac)?;
+        let member = self.stack_members.remove(position);
+        self.apply_event(NetworkEvent::StackMemberRemoved { device_id: self.id, member });
+        Some(member)
+    }
+
+    /// Record that the device was merged into stack `stack_id` as one of its units
+    ///
+    /// Joining the stack the device is already in records nothing.
+    pub fn join_stack(&mut self, stack_id: DeviceId) -> Result<(), AggregateError> {
+        self.ensure_not_decommissioned("join stack")?;
+        match self.stacked_into {
+            Some(current) if current == stack_id => return Ok(()),
+            Some(current) => return Err(AggregateError::AlreadyStacked { stack_id: current }),
+            None if self.is_stack() => return Err(AggregateError::AlreadyStacked { stack_id: self.id }),
+            None => {}
+        }
+
+        self.stacked_into = Some(stack_id);
+        self.apply_event(NetworkEvent::DeviceJoinedStack { device_id: self.id, stack_id });
+        Ok(())
+    }
+
+    /// Record that the device was split back out of its stack
+    ///
+    /// A device in no stack records nothing.
+    pub fn leave_stack(&mut self) {
+        if let Some(stack_id) = self.stacked_into.take() {
+            self.apply_event(NetworkEvent::DeviceLeftStack { device_id: self.id, stack_id });
+        }
+    }
+
+    // Private helpers
+
+    fn ensure_not_decommissioned(&self, operation: &str) -> Result<(), AggregateError> {
+        if self.state == DeviceState::Decommissioned {
+            return Err(AggregateError::InvalidState {
+                current: self.state,
+                operation: operation.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn transition_to(&mut self, target: DeviceState) -> Result<(), AggregateError> {
+        if !self.state.can_transition_to(target) {
+            return Err(AggregateError::InvalidTransition {
+                from: self.state,
+                to: target,
+            });
+        }
+        self.state = target;
+        Ok(())
+    }
+
+    fn apply_event(&mut self, event: NetworkEvent) {
+        self.version += 1;
+        self.pending_events.push(event);
+    }
+
+    fn apply_existing_event(&mut self, event: &NetworkEvent) {
+        if let Some(state) = DeviceState::after_event(event) {
+            self.state = state;
+        }
+        match event {
+            NetworkEvent::DeviceAdopting { vendor_id, .. } => {
+                self.vendor_id = Some(vendor_id.clone());
+            }
+            NetworkEvent::DeviceProvisioned {
+                model,
+                firmware_version,
+                ..
+            } => {
+                self.model = Some(model.clone());
+                self.firmware_version = Some(firmware_version.clone());
+            }
+            NetworkEvent::DeviceConfigured {
+                interfaces, vlans, ..
+            } => {
+                self.interfaces = interfaces.clone();
+                self.vlans = vlans.clone();
+            }
+            NetworkEvent::DeviceError { message, .. } => {
+                self.error_message = Some(message.clone());
+            }
+            NetworkEvent::DeviceRenamed { new_name, .. } => {
+                self.name = new_name.clone();
+            }
+            NetworkEvent::DeviceTagged { tag, .. } => {
+                self.tags.insert(tag.clone());
+            }
+            NetworkEvent::DeviceUntagged { tag, .. } => {
+                self.tags.remove(tag);
+            }
+            NetworkEvent::StackMemberAdded { member, .. } => {
+                self.stack_members.push(*member);
+                self.stack_members.sort_by_key(|m| m.member);
+            }
+            NetworkEvent::StackMemberRemoved { member, .. } => {
+                self.stack_members.retain(|m| m.mac != member.mac);
+            }
+            NetworkEvent::DeviceJoinedStack { stack_id, .. } => {
+                self.stacked_into = Some(*stack_id);
+            }
+            NetworkEvent::DeviceLeftStack { .. } => {
+                self.stacked_into = None;
+            }
+            NetworkEvent::FirmwareUpgradeCompleted { firmware_version, .. } => {
+                self.firmware_version = Some(firmware_version.clone());
+            }
+            NetworkEvent::InventorySyncQueued { system, attempts, error, retry_at, .. } => {
+                self.inventory_sync_failures.insert(system.clone(), InventorySyncFailure {
+                    attempts: *attempts,
+                    error: error.clone(),
+                    retry_at: Some(*retry_at),
+                });
+            }
+            NetworkEvent::InventorySyncDeadLettered { system, attempts, error, .. } => {
+                self.inventory_sync_failures.insert(system.clone(), InventorySyncFailure {
+                    attempts: *attempts,
+                    error: error.clone(),
+                    retry_at: None,
+                });
+            }
+            NetworkEvent::DeviceSyncedToInventory { system, content_hash, .. } => {
+                // Syncs recorded before hashing leave the inventory presumed stale
+                match content_hash {
+                    Some(hash) => self.inventory_hashes.insert(system.clone(), hash.clone()),
+                    None => self.inventory_hashes.remove(system),
+                };
+                self.inventory_sync_failures.remove(system);
+            }
+            _ => {}
+        }
+        self.version += 1;
+    }
+}
+
+// ============================================================================
+// Connection State Machine (Moore Machine)
+// ============================================================================
+
+/// Connection lifecycle states
+///
+/// ```text
+///   ┌─────────┐ cable ┌────────┐ activate ┌────────┐ degrade ┌──────────┐
+///   │ Planned │──────▶│ Cabled │─────────▶│ Active │────────▶│ Degraded │
+///   └─────────┘       └────────┘          └────────┘◀────────└──────────┘
+///                                                    activate
+/// ```
+///
+/// Every non-terminal state can move to `Removed` (terminal).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ConnectionState {
+    /// Connection is designed but not yet cabled
+    Planned,
+    /// Cable is in place but the link has not come up
+    Cabled,
+    /// Link is up
+    Active,
+    /// Link is up but impaired
+    Degraded,
+    /// Connection has been removed (terminal state)
+    Removed,
+}
+
+impl ConnectionState {
+    /// Get valid transitions from this state
+    pub fn valid_transitions(&self) -> &[ConnectionState] {
+        match self {
+            ConnectionState::Planned => &[ConnectionState::Cabled, ConnectionState::Removed],
+            ConnectionState::Cabled => &[ConnectionState::Active, ConnectionState::Removed],
+            ConnectionState::Active => &[ConnectionState::Degraded, ConnectionState::Removed],
+            ConnectionState::Degraded => &[ConnectionState::Active, ConnectionState::Removed],
+            ConnectionState::Removed => &[], // Terminal state
+        }
+    }
+
+    /// Check if transition to target state is valid
+    pub fn can_transition_to(&self, target: ConnectionState) -> bool {
+        self.valid_transitions().contains(&target)
+    }
+
+    /// Is this a terminal state?
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, ConnectionState::Removed)
+    }
+
+    /// Get state name for logging/display
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConnectionState::Planned => "Planned",
+            ConnectionState::Cabled => "Cabled",
+            ConnectionState::Active => "Active",
+            ConnectionState::Degraded => "Degraded",
+            ConnectionState::Removed => "Removed",
+        }
+    }
+}
+
+// ============================================================================
+// Network Connection Aggregate
+// ============================================================================
+
+/// Network connection aggregate - consistency boundary for a single link
+///
+/// Mirrors `NetworkDeviceAggregate`: commands check the state machine and
+/// emit events, and the aggregate is reconstructed from those events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkConnectionAggregate {
+    /// Connection identifier
+    id: ConnectionId,
+    /// Current state
+    state: ConnectionState,
+    /// Version for optimistic concurrency
+    version: u64,
+    /// Device at the A end
+    source_device: DeviceId,
+    /// Port at the A end
+    source_port: PortId,
+    /// Device at the B end
+    target_device: DeviceId,
+    /// Port at the B end
+    target_port: PortId,
+    /// Physical medium
+    connection_type: ConnectionType,
+    /// Negotiated speed from the last activation
+    speed: Option<LinkSpeed>,
+    /// Why the link is degraded (if in Degraded state)
+    degraded_reason: Option<String>,
+    /// Pending events (not yet persisted)
+    #[serde(skip)]
+    pending_events: Vec<NetworkEvent>,
+}
+
+impl NetworkConnectionAggregate {
+    /// Plan a new connection between two ports
+    pub fn new_planned(
+        source_device: DeviceId,
+        source_port: PortId,
+        target_device: DeviceId,
+        target_port: PortId,
+        connection_type: ConnectionType,
+    ) -> Self {
+        let id = ConnectionId::new();
+        let mut connection = Self::from_planned_event(
+            id,
+            source_device,
+            source_port.clone(),
+            target_device,
+            target_port.clone(),
+            connection_type.clone(),
+        );
+        connection.version = 0;
+
+        connection.apply_event(NetworkEvent::ConnectionPlanned {
+            connection_id: id,
+            source_device,
+            source_port,
+            target_device,
+            target_port,
+            connection_type,
+        });
+
+        connection
+    }
+
+    /// Create from a planned event (for replay)
+    pub fn from_planned_event(
+        connection_id: ConnectionId,
+        source_device: DeviceId,
+        source_port: PortId,
+        target_device: DeviceId,
+        target_port: PortId,
+        connection_type: ConnectionType,
+    ) -> Self {
+        Self {
+            id: connection_id,
+            state: ConnectionState::Planned,
+            version: 1,
+            source_device,
+            source_port,
+            target_device,
+            target_port,
+            connection_type,
+            speed: None,
+            degraded_reason: None,
+            pending_events: Vec::new(),
+        }
+    }
+
+    /// Reconstruct from events
+    pub fn from_events(events: impl IntoIterator<Item = NetworkEvent>) -> Option<Self> {
+        events.into_iter().fold(None, Self::replay_event)
+    }
+
+    /// Fold one persisted event onto a (possibly not yet created) aggregate
+    ///
+    /// `ConnectionPlanned` creates the aggregate. `ConnectionEstablished`,
+    /// written before connections had a lifecycle, creates it already
+    /// `Active`. Other events are ignored until the aggregate exists.
+    pub fn replay_event(connection: Option<Self>, event: NetworkEvent) -> Option<Self> {
+        match event {
+            NetworkEvent::ConnectionPlanned {
+                connection_id,
+                source_device,
+                source_port,
+                target_device,
+                target_port,
+                connection_type,
+            } => Some(Self::from_planned_event(
+                connection_id,
+                source_device,
+                source_port,
+                target_device,
+                target_port,
+                connection_type,
+            )),
+            NetworkEvent::ConnectionEstablished {
+                connection_id,
+                source_device,
+                source_port,
+                target_device,
+                target_port,
+                connection_type,
+            } => {
+                let mut established = Self::from_planned_event(
+                    connection_id,
+                    source_device,
+                    source_port,
+                    target_device,
+                    target_port,
+                    connection_type,
+                );
+                established.state = ConnectionState::Active;
+                Some(established)
+            }
+            event => connection.map(|mut c| {
+                c.apply_existing_event(&event);
+                c
+            }),
+        }
+    }
+
+    /// Connection identifier
+    pub fn id(&self) -> ConnectionId {
+        self.id
+    }
+
+    /// Current lifecycle state
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    /// Number of events applied, for optimistic concurrency
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Device at the A end
+    pub fn source_device(&self) -> DeviceId {
+        self.source_device
+    }
+
+    /// Port at the A end
+    pub fn source_port(&self) -> &PortId {
+        &self.source_port
+    }
+
+    /// Device at the B end
+    pub fn target_device(&self) -> DeviceId {
+        self.target_device
+    }
+
+    /// Port at the B end
+    pub fn target_port(&self) -> &PortId {
+        &self.target_port
+    }
+
+    /// Physical medium
+    pub fn connection_type(&self) -> &ConnectionType {
+        &self.connection_type
+    }
+
+    /// Negotiated speed from the last activation
+    pub fn speed(&self) -> Option<LinkSpeed> {
+        self.speed
+    }
+
+    /// Why the link is degraded, while it is
+    pub fn degraded_reason(&self) -> Option<&str> {
+        self.degraded_reason.as_deref()
+    }
+
+    /// Whether the connection touches `device_id` at either end
+    pub fn involves(&self, device_id: DeviceId) -> bool {
+        self.source_device == device_id || self.target_device == device_id
+    }
+
+    /// Whether `other` describes the same link
+    ///
+    /// Links of the same type between the same ports match. Ends may be
+    /// swapped unless the type is directional, so A→B and B→A are one
+    /// Ethernet link but two distinct uplinks.
+    pub fn is_same_link(&self, other: &NetworkConnectionAggregate) -> bool {
+        if self.connection_type != other.connection_type {
+            return false;
+        }
+        let a = (self.source_device, &self.source_port);
+        let b = (self.target_device, &self.target_port);
+        let other_a = (other.source_device, &other.source_port);
+        let other_b = (other.target_device, &other.target_port);
+        (a == other_a && b == other_b)
+            || (!self.connection_type.is_directional() && a == other_b && b == other_a)
+    }
+
+    /// Take the events recorded since the last call
+    pub fn take_pending_events(&mut self) -> Vec<NetworkEvent> {
+        std::mem::take(&mut self.pending_events)
+    }
+
+    // Commands (state transitions)
+
+    /// Record that the cable has been run
+    pub fn cable(&mut self) -> Result<(), AggregateError> {
+        self.transition_to(ConnectionState::Cabled)?;
+        self.apply_event(NetworkEvent::ConnectionCabled {
+            connection_id: self.id,
+        });
+        Ok(())
+    }
+
+    /// Record that the link is up, or has recovered from degradation
+    pub fn activate(&mut self, speed: Option<LinkSpeed>) -> Result<(), AggregateError> {
+        self.transition_to(ConnectionState::Active)?;
+        self.speed = speed;
+        self.degraded_reason = None;
+        self.apply_event(NetworkEvent::ConnectionActivated {
+            connection_id: self.id,
+            speed,
+        });
+        Ok(())
+    }
+
+    /// Activate the link at the speed its two ports negotiate
+    ///
+    /// `source` and `target` are what the ports at the A and B ends
+    /// advertise. Ends on different media, or without a common speed, are
+    /// rejected before the state changes. Returns the negotiated speed.
+    pub fn activate_negotiated(
+        &mut self,
+        source: &PortCapabilities,
+        target: &PortCapabilities,
+    ) -> Result<LinkSpeed, AggregateError> {
+        let speed = source.negotiate(target)?;
+        self.activate(Some(speed))?;
+        Ok(speed)
+    }
+
+    /// Record that the link is impaired
+    pub fn degrade(&mut self, reason: String) -> Result<(), AggregateError> {
+        self.transition_to(ConnectionState::Degraded)?;
+        self.degraded_reason = Some(reason.clone());
+        self.apply_event(NetworkEvent::ConnectionDegraded {
+            connection_id: self.id,
+            reason,
+        });
+        Ok(())
+    }
+
+    /// Remove the connection
+    pub fn remove(&mut self) -> Result<(), AggregateError> {
+        self.transition_to(ConnectionState::Removed)?;
+        self.apply_event(NetworkEvent::ConnectionRemoved {
+            connection_id: self.id,
+        });
+        Ok(())
+    }
+
+    // Private helpers
+
+    fn transition_to(&mut self, target: ConnectionState) -> Result<(), AggregateError> {
+        if !self.state.can_transition_to(target) {
+            return Err(AggregateError::InvalidConnectionTransition {
+                from: self.state,
+                to: target,
+            });
+        }
+        self.state = target;
+        Ok(())
+    }
+
+    fn apply_event(&mut self, event: NetworkEvent) {
+        self.version += 1;
+        self.pending_events.push(event);
+    }
+
+    fn apply_existing_event(&mut self, event: &NetworkEvent) {
+        match event {
+            NetworkEvent::ConnectionCabled { .. } => {
+                self.state = ConnectionState::Cabled;
+            }
+            NetworkEvent::ConnectionActivated { speed, .. } => {
+                self.state = ConnectionState::Active;
+                self.speed = *speed;
+                self.degraded_reason = None;
+            }
+            NetworkEvent::ConnectionDegraded { reason, .. } => {
+                self.state = ConnectionState::Degraded;
+                self.degraded_reason = Some(reason.clone());
+            }
+            NetworkEvent::ConnectionRemoved { .. } => {
+                self.state = ConnectionState::Removed;
+            }
+            NetworkEvent::ConnectionLinkChanged { speed, .. } => {
+                self.speed = *speed;
+            }
+            _ => {}
+        }
+        self.version += 1;
+    }
+}
+
+// ============================================================================
+// Site Aggregate
+// ============================================================================
+
+/// Site aggregate - groups devices and the IP prefixes allocated to them
+///
+/// A site has no lifecycle of its own; it records membership and address
+/// space. Keeping a device in at most one site is up to the caller, which
+/// unassigns it from its old site before assigning it to a new one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiteAggregate {
+    /// Site identifier
+    id: SiteId,
+    /// Site name
+    name: String,
+    /// Version for optimistic concurrency
+    version: u64,
+    /// Member devices, in assignment order
+    devices: Vec<DeviceId>,
+    /// Prefixes allocated to the site, in allocation order
+    prefixes: Vec<IpNetwork>,
+    /// Pending events (not yet persisted)
+    #[serde(skip)]
+    pending_events: Vec<NetworkEvent>,
+}
+
+impl SiteAggregate {
+    /// Create a new, empty site
+    pub fn new(name: impl Into<String>) -> Self {
+        let id = SiteId::new();
+        let name = name.into();
+        let mut site = Self::from_created_event(id, name.clone());
+        site.version = 0;
+
+        site.apply_event(NetworkEvent::SiteCreated { site_id: id, name });
+
+        site
+    }
+
+    /// Create from a created event (for replay)
+    pub fn from_created_event(site_id: SiteId, name: String) -> Self {
+        Self {
+            id: site_id,
+            name,
+            version: 1,
+            devices: Vec::new(),
+            prefixes: Vec::new(),
+            pending_events: Vec::new(),
+        }
+    }
+
+    /// Reconstruct from events
+    pub fn from_events(events: impl IntoIterator<Item = NetworkEvent>) -> Option<Self> {
+        events.into_iter().fold(None, Self::replay_event)
+    }
+
+    /// Fold one persisted event onto a (possibly not yet created) aggregate
+    ///
+    /// `SiteCreated` creates the aggregate; other events are ignored until
+    /// it exists.
+    pub fn replay_event(site: Option<Self>, event: NetworkEvent) -> Option<Self> {
+        match event {
+            NetworkEvent::SiteCreated { site_id, name } => Some(Self::from_created_event(site_id, name)),
+            event => site.map(|mut s| {
+                s.apply_existing_event(&event);
+                s
+            }),
+        }
+    }
+
+    /// Site identifier
+    pub fn id(&self) -> SiteId {
+        self.id
+    }
+
+    /// Site name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Number of events applied, for optimistic concurrency
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Member devices, in assignment order
+    pub fn devices(&self) -> &[DeviceId] {
+        &self.devices
+    }
+
+    /// Whether `device_id` belongs to the site
+    pub fn contains_device(&self, device_id: DeviceId) -> bool {
+        self.devices.contains(&device_id)
+    }
+
+    /// Prefixes allocated to the site
+    pub fn prefixes(&self) -> &[IpNetwork] {
+        &self.prefixes
+    }
+
+    /// Take the events recorded since the last call
+    pub fn take_pending_events(&mut self) -> Vec<NetworkEvent> {
+        std::mem::take(&mut self.pending_events)
     }
 
-    /// Decommission the device
-    pub fn decommission(&mut self) -> Result<(), AggregateError> {
-        self.transition_to(DeviceState::Decommissioned)?;
-        self.apply_event(NetworkEvent::DeviceDecommissioned {
-            device_id: self.id,
+    // Commands
+
+    /// Add a device to the site; a no-op if it is already a member
+    pub fn assign_device(&mut self, device_id: DeviceId) {
+        if self.contains_device(device_id) {
+            return;
+        }
+        self.devices.push(device_id);
+        self.apply_event(NetworkEvent::DeviceAssignedToSite {
+            site_id: self.id,
+            device_id,
         });
-        Ok(())
     }
 
-    /// Update device name
-    pub fn rename(&mut self, name: String) -> Result<(), AggregateError> {
-        if self.state == DeviceState::Decommissioned {
-            return Err(AggregateError::InvalidState {
-                current: self.state,
-                operation: "rename".to_string(),
-            });
+    /// Remove a device from the site; a no-op if it is not a member
+    pub fn unassign_device(&mut self, device_id: DeviceId) {
+        if !self.contains_device(device_id) {
+            return;
         }
-        let old_name = std::mem::replace(&mut self.name, name.clone());
-        self.apply_event(NetworkEvent::DeviceRenamed {
-            device_id: self.id,
-            old_name,
-            new_name: name,
+        self.devices.retain(|d| *d != device_id);
+        self.apply_event(NetworkEvent::DeviceUnassignedFromSite {
+            site_id: self.id,
+            device_id,
         });
-        Ok(())
     }
 
-    // Private helpers
-
-    fn transition_to(&mut self, target: DeviceState) -> Result<(), AggregateError> {
-        if !self.state.can_transition_to(target) {
-            return Err(AggregateError::InvalidTransition {
-                from: self.state,
-                to: target,
+    /// Allocate a prefix to the site
+    ///
+    /// Re-allocating a prefix the site already holds is a no-op; one that
+    /// overlaps a different allocated prefix is rejected.
+    pub fn allocate_prefix(&mut self, prefix: IpNetwork) -> Result<(), AggregateError> {
+        if self.prefixes.contains(&prefix) {
+            return Ok(());
+        }
+        if let Some(existing) = self.prefixes.iter().find(|p| p.overlaps(&prefix)) {
+            return Err(AggregateError::OverlappingPrefix {
+                prefix,
+                existing: *existing,
             });
         }
-        self.state = target;
+        self.prefixes.push(prefix);
+        self.apply_event(NetworkEvent::SitePrefixAllocated {
+            site_id: self.id,
+            prefix,
+        });
         Ok(())
     }
 
+    // Private helpers
+
     fn apply_event(&mut self, event: NetworkEvent) {
         self.version += 1;
         self.pending_events.push(event);
@@ -396,38 +1472,14 @@ impl NetworkDeviceAggregate {
 
     fn apply_existing_event(&mut self, event: &NetworkEvent) {
         match event {
-            NetworkEvent::DeviceAdopting { vendor_id, .. } => {
-                self.state = DeviceState::Adopting;
-                self.vendor_id = Some(vendor_id.clone());
-            }
-            NetworkEvent::DeviceProvisioned {
-                model,
-                firmware_version,
-                ..
-            } => {
-                self.state = DeviceState::Provisioned;
-                self.model = Some(model.clone());
-                self.firmware_version = Some(firmware_version.clone());
-            }
-            NetworkEvent::DeviceConfiguring { .. } => {
-                self.state = DeviceState::Configuring;
-            }
-            NetworkEvent::DeviceConfigured {
-                interfaces, vlans, ..
-            } => {
-                self.state = DeviceState::Provisioned;
-                self.interfaces = interfaces.clone();
-                self.vlans = vlans.clone();
-            }
-            NetworkEvent::DeviceError { message, .. } => {
-                self.state = DeviceState::Error;
-                self.error_message = Some(message.clone());
+            NetworkEvent::DeviceAssignedToSite { device_id, .. } if !self.devices.contains(device_id) => {
+                self.devices.push(*device_id);
             }
-            NetworkEvent::DeviceDecommissioned { .. } => {
-                self.state = DeviceState::Decommissioned;
+            NetworkEvent::DeviceUnassignedFromSite { device_id, .. } => {
+                self.devices.retain(|d| d != device_id);
             }
-            NetworkEvent::DeviceRenamed { new_name, .. } => {
-                self.name = new_name.clone();
+            NetworkEvent::SitePrefixAllocated { prefix, .. } if !self.prefixes.contains(prefix) => {
+                self.prefixes.push(*prefix);
             }
             _ => {}
         }
@@ -444,6 +1496,15 @@ pub enum AggregateError {
     #[error("Invalid state transition from {from:?} to {to:?}")]
     InvalidTransition { from: DeviceState, to: DeviceState },
 
+    /// A connection command is not allowed in the connection's current state
+    #[error("Invalid connection transition from {from:?} to {to:?}")]
+    InvalidConnectionTransition {
+        /// State the connection is in
+        from: ConnectionState,
+        /// State the command would move it to
+        to: ConnectionState,
+    },
+
     #[error("Invalid operation '{operation}' in state {current:?}")]
     InvalidState {
         current: DeviceState,
@@ -452,6 +1513,110 @@ pub enum AggregateError {
 
     #[error("Concurrency conflict: expected version {expected}, found {actual}")]
     ConcurrencyConflict { expected: u64, actual: u64 },
+
+    /// Two VLAN configs on the device use the same VLAN ID
+    #[error("Conflicting configuration for VLAN {vlan_id}")]
+    ConflictingVlanConfig {
+        /// The duplicated VLAN ID
+        vlan_id: u16,
+    },
+
+    /// An access port is claimed by two VLANs
+    #[error("Port {port} is already assigned to another VLAN")]
+    PortAlreadyAssigned {
+        /// The doubly-assigned port
+        port: PortId,
+    },
+
+    /// An interface's MTU is outside the accepted range
+    #[error("Invalid MTU {mtu} on {interface}")]
+    InvalidMtu {
+        /// Interface carrying the MTU
+        interface: String,
+        /// Rejected MTU
+        mtu: u16,
+    },
+
+    /// A connection duplicates a link that already exists
+    #[error("Duplicate connection: the link already exists as {existing}")]
+    DuplicateConnection {
+        /// Connection already describing the link
+        existing: ConnectionId,
+    },
+
+    /// A command targets a device that does not exist
+    #[error("Device not found: {0}")]
+    DeviceNotFound(DeviceId),
+
+    /// A command targets a connection that does not exist
+    #[error("Connection not found: {0}")]
+    ConnectionNotFound(ConnectionId),
+
+    /// A prefix overlaps one already allocated to the site
+    #[error("Prefix {prefix} overlaps {existing}, already allocated to the site")]
+    OverlappingPrefix {
+        /// Rejected prefix
+        prefix: IpNetwork,
+        /// Allocated prefix it overlaps
+        existing: IpNetwork,
+    },
+
+    /// A command is not dispatched to any aggregate
+    #[error("Command '{0}' is not handled by an aggregate")]
+    UnsupportedCommand(String),
+
+    /// A stack unit's member number or MAC is already taken in the stack
+    #[error("Stack member {member} ({mac}) conflicts with a unit already in the stack")]
+    StackMemberConflict {
+        /// Member number of the rejected unit
+        member: u32,
+        /// MAC of the rejected unit
+        mac: MacAddress,
+    },
+
+    /// The device already belongs to a stack
+    #[error("Device already belongs to stack {stack_id}")]
+    AlreadyStacked {
+        /// Stack the device belongs to
+        stack_id: DeviceId,
+    },
+
+    /// The device's link aggregation groups are inconsistent
+    #[error(transparent)]
+    InvalidLag(LagConflict),
+
+    /// A port or link is set to a speed it cannot run at
+    #[error(transparent)]
+    InvalidLinkSpeed(LinkSpeedError),
+}
+
+impl From<LagConflict> for AggregateError {
+    fn from(conflict: LagConflict) -> Self {
+        AggregateError::InvalidLag(conflict)
+    }
+}
+
+impl From<LinkSpeedError> for AggregateError {
+    fn from(error: LinkSpeedError) -> Self {
+        AggregateError::InvalidLinkSpeed(error)
+    }
+}
+
+impl From<InterfaceError> for AggregateError {
+    fn from(error: InterfaceError) -> Self {
+        match error {
+            InterfaceError::InvalidMtu { interface, mtu } => AggregateError::InvalidMtu { interface, mtu },
+        }
+    }
+}
+
+impl From<VlanConflict> for AggregateError {
+    fn from(conflict: VlanConflict) -> Self {
+        match conflict {
+            VlanConflict::DuplicateId(vlan_id) => AggregateError::ConflictingVlanConfig { vlan_id },
+            VlanConflict::PortAlreadyAssigned(port) => AggregateError::PortAlreadyAssigned { port },
+        }
+    }
 }
 
 #[cfg(test)]
@@ -515,6 +1680,18 @@ mod tests {
         assert!(!state.can_transition_to(DeviceState::Provisioned));
     }
 
+    #[test]
+    fn test_device_state_transitions_missing() {
+        assert!(DeviceState::Provisioned.can_transition_to(DeviceState::Missing));
+        assert!(!DeviceState::Discovered.can_transition_to(DeviceState::Missing));
+
+        let state = DeviceState::Missing;
+        assert!(state.can_transition_to(DeviceState::Provisioned)); // reappeared
+        assert!(state.can_transition_to(DeviceState::Decommissioned));
+        assert!(!state.can_transition_to(DeviceState::Adopting));
+        assert!(!state.can_transition_to(DeviceState::Configuring));
+    }
+
     #[test]
     fn test_device_state_terminal() {
         assert!(DeviceState::Decommissioned.is_terminal());
@@ -530,9 +1707,24 @@ mod tests {
         assert_eq!(DeviceState::Provisioned.name(), "Provisioned");
         assert_eq!(DeviceState::Configuring.name(), "Configuring");
         assert_eq!(DeviceState::Error.name(), "Error");
+        assert_eq!(DeviceState::Missing.name(), "Missing");
         assert_eq!(DeviceState::Decommissioned.name(), "Decommissioned");
     }
 
+    #[test]
+    fn test_device_state_mermaid_diagram() {
+        let diagram = DeviceState::to_mermaid_state_diagram();
+
+        assert!(diagram.starts_with("stateDiagram-v2\n"));
+        assert!(diagram.contains("Discovered --> Adopting : adopt\n"));
+        assert!(diagram.contains("Error --> Adopting : adopt\n"));
+        assert!(diagram.contains("Decommissioned --> [*]\n"));
+        // One line per valid transition, each labelled with a real command
+        let edges = DeviceState::ALL.iter().map(|s| s.valid_transitions().len()).sum::<usize>();
+        assert_eq!(diagram.lines().filter(|line| line.contains(" : ")).count(), edges);
+        assert!(!diagram.contains(": transition"));
+    }
+
     // ==========================================================================
     // NetworkDeviceAggregate Tests
     // ==========================================================================
@@ -647,6 +1839,135 @@ mod tests {
         assert_eq!(device.state(), DeviceState::Adopting);
     }
 
+    fn configuring_switch() -> NetworkDeviceAggregate {
+        let mut device = NetworkDeviceAggregate::new_discovered(create_test_mac(), DeviceType::Switch, None);
+        device.adopt("sw-001".to_string()).unwrap();
+        device.mark_provisioned("USW-24".to_string(), "6.6.0".to_string()).unwrap();
+        device.start_configuration().unwrap();
+        device.take_pending_events();
+        device
+    }
+
+    #[test]
+    fn test_complete_configuration_accepts_consistent_vlans() {
+        let mut device = configuring_switch();
+        let vlans = vec![
+            VlanConfig::new(10, "users").unwrap().with_access_port(PortId::new("port1")),
+            VlanConfig::new(20, "voice").unwrap().with_access_port(PortId::new("port2")),
+        ];
+
+        device.complete_configuration(vec![], vlans.clone()).unwrap();
+
+        assert_eq!(device.state(), DeviceState::Provisioned);
+        assert_eq!(device.vlans, vlans);
+    }
+
+    #[test]
+    fn test_complete_configuration_rejects_duplicate_vlan() {
+        let mut device = configuring_switch();
+        let vlans = vec![
+            VlanConfig::new(10, "users").unwrap(),
+            VlanConfig::new(10, "staff").unwrap(),
+        ];
+
+        let result = device.complete_configuration(vec![], vlans);
+
+        assert!(matches!(result, Err(AggregateError::ConflictingVlanConfig { vlan_id: 10 })));
+        assert_eq!(device.state(), DeviceState::Configuring);
+        assert!(device.take_pending_events().is_empty());
+    }
+
+    #[test]
+    fn test_complete_configuration_rejects_invalid_mtu() {
+        let mut device = configuring_switch();
+        let interfaces = vec![InterfaceConfig {
+            name: "port1".to_string(),
+            ip_address: None,
+            prefix_len: None,
+            vlan_id: None,
+            enabled: true,
+            mtu: Some(16000),
+            speed: None,
+        }];
+
+        let result = device.complete_configuration(interfaces, vec![]);
+
+        assert!(matches!(result, Err(AggregateError::InvalidMtu { mtu: 16000, .. })));
+        assert_eq!(device.state(), DeviceState::Configuring);
+        assert!(device.take_pending_events().is_empty());
+    }
+
+    #[test]
+    fn test_complete_configuration_rejects_port_in_two_vlans() {
+        let mut device = configuring_switch();
+        let vlans = vec![
+            VlanConfig::new(10, "users").unwrap().with_access_port(PortId::new("port1")),
+            VlanConfig::new(20, "voice").unwrap().with_access_port(PortId::new("port1")),
+        ];
+
+        let result = device.complete_configuration(vec![], vlans);
+
+        assert!(matches!(
+            result,
+            Err(AggregateError::PortAlreadyAssigned { port }) if port == PortId::new("port1")
+        ));
+        assert_eq!(device.state(), DeviceState::Configuring);
+    }
+
+    #[test]
+    fn test_aggregate_missing_and_reappeared() {
+        let mut device = NetworkDeviceAggregate::new_discovered(
+            create_test_mac(),
+            DeviceType::Switch,
+            None,
+        );
+        device.adopt("sw-001".to_string()).unwrap();
+        device.mark_provisioned("USW-24".to_string(), "6.6.0".to_string()).unwrap();
+
+        // Reappearing is only meaningful for a missing device
+        assert!(matches!(
+            device.mark_reappeared(),
+            Err(AggregateError::InvalidState { operation, .. }) if operation == "reappear"
+        ));
+
+        device.mark_missing(None).unwrap();
+        assert_eq!(device.state(), DeviceState::Missing);
+
+        device.mark_reappeared().unwrap();
+        assert_eq!(device.state(), DeviceState::Provisioned);
+
+        // Replay yields the same state
+        let replayed = NetworkDeviceAggregate::from_events(device.take_pending_events()).unwrap();
+        assert_eq!(replayed.state(), DeviceState::Provisioned);
+        assert_eq!(replayed.version(), device.version());
+    }
+
+    #[test]
+    fn test_aggregate_firmware_upgrade() {
+        let mut device = NetworkDeviceAggregate::new_discovered(create_test_mac(), DeviceType::Switch, None);
+        device.adopt("sw-001".to_string()).unwrap();
+
+        // Only a provisioned device can be upgraded
+        assert!(matches!(
+            device.start_firmware_upgrade("6.6.65".to_string()),
+            Err(AggregateError::InvalidTransition { from: DeviceState::Adopting, to: DeviceState::Configuring })
+        ));
+
+        device.mark_provisioned("USW-24".to_string(), "6.6.0".to_string()).unwrap();
+        device.start_firmware_upgrade("6.6.65".to_string()).unwrap();
+        assert_eq!(device.state(), DeviceState::Configuring);
+        assert_eq!(device.firmware_version(), Some("6.6.0"));
+
+        device.complete_firmware_upgrade("6.6.65".to_string()).unwrap();
+        assert_eq!(device.state(), DeviceState::Provisioned);
+        assert_eq!(device.firmware_version(), Some("6.6.65"));
+
+        // Replay yields the same firmware
+        let replayed = NetworkDeviceAggregate::from_events(device.take_pending_events()).unwrap();
+        assert_eq!(replayed.firmware_version(), Some("6.6.65"));
+        assert_eq!(replayed.version(), device.version());
+    }
+
     #[test]
     fn test_aggregate_rename() {
         let mac = create_test_mac();
@@ -690,6 +2011,86 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_aggregate_add_and_remove_tags() {
+        let mut device = NetworkDeviceAggregate::new_discovered(create_test_mac(), DeviceType::Switch, None);
+        device.take_pending_events();
+        let prod = Tag::parse("env:prod").unwrap();
+        let rack = Tag::parse("rack:R12").unwrap();
+
+        device.add_tag(prod.clone()).unwrap();
+        device.add_tag(rack.clone()).unwrap();
+        device.add_tag(prod.clone()).unwrap();
+        device.remove_tag(&rack).unwrap();
+        device.remove_tag(&rack).unwrap();
+
+        // Repeated adds and removes record nothing
+        let events = device.take_pending_events();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[2], NetworkEvent::DeviceUntagged { tag, .. } if *tag == rack));
+        assert!(device.has_tag(&prod));
+        assert!(!device.has_tag(&rack));
+
+        let replayed = NetworkDeviceAggregate::from_events(
+            std::iter::once(NetworkEvent::DeviceDiscovered {
+                device_id: device.id(),
+                mac: device.mac(),
+                device_type: DeviceType::Switch,
+                ip_address: None,
+            })
+            .chain(events),
+        )
+        .unwrap();
+        assert_eq!(replayed.tags(), device.tags());
+        assert_eq!(replayed.version(), device.version());
+
+        device.decommission().unwrap();
+        assert!(matches!(
+            device.add_tag(rack),
+            Err(AggregateError::InvalidState { operation, .. }) if operation == "tag"
+        ));
+    }
+
+    #[test]
+    fn test_stack_members_replay_and_conflict() {
+        let mut stack = NetworkDeviceAggregate::new_discovered(create_test_mac(), DeviceType::Switch, None);
+        let mut unit = NetworkDeviceAggregate::new_discovered(
+            MacAddress::parse("00:11:22:33:44:66").unwrap(),
+            DeviceType::Switch,
+            None,
+        );
+        let master = StackMember::new(1, stack.mac()).discovered_as(stack.id());
+        let second = StackMember::new(2, unit.mac()).discovered_as(unit.id());
+
+        stack.add_stack_member(second).unwrap();
+        stack.add_stack_member(master).unwrap();
+        stack.add_stack_member(master).unwrap();
+        unit.join_stack(stack.id()).unwrap();
+
+        assert_eq!(stack.stack_members(), [master, second]);
+        assert!(stack.has_mac(unit.mac()));
+        assert_eq!(unit.stacked_into(), Some(stack.id()));
+        assert!(matches!(
+            stack.add_stack_member(StackMember::new(2, MacAddress::parse("00:11:22:33:44:77").unwrap())),
+            Err(AggregateError::StackMemberConflict { member: 2, .. })
+        ));
+        assert!(matches!(unit.add_stack_member(second), Err(AggregateError::AlreadyStacked { .. })));
+        assert!(matches!(stack.join_stack(unit.id()), Err(AggregateError::AlreadyStacked { .. })));
+
+        let replayed = NetworkDeviceAggregate::from_events(stack.take_pending_events()).unwrap();
+        assert_eq!(replayed.stack_members(), stack.stack_members());
+        assert_eq!(replayed.version(), stack.version());
+
+        assert_eq!(stack.remove_stack_member(unit.mac()), Some(second));
+        assert_eq!(stack.remove_stack_member(unit.mac()), None);
+        unit.leave_stack();
+        unit.leave_stack();
+        assert_eq!(stack.take_pending_events().len(), 1);
+        let unit_events = unit.take_pending_events();
+        assert!(matches!(unit_events.last(), Some(NetworkEvent::DeviceLeftStack { .. })));
+        assert_eq!(NetworkDeviceAggregate::from_events(unit_events).unwrap().stacked_into(), None);
+    }
+
     #[test]
     fn test_aggregate_from_events() {
         let device_id = DeviceId::new();
@@ -778,4 +2179,226 @@ mod tests {
         let events = device.take_pending_events();
         assert_eq!(events.len(), 1);
     }
+
+    // ==========================================================================
+    // Connection Aggregate Tests
+    // ==========================================================================
+
+    fn planned_connection() -> NetworkConnectionAggregate {
+        NetworkConnectionAggregate::new_planned(
+            DeviceId::new(),
+            PortId::new("eth1"),
+            DeviceId::new(),
+            PortId::new("eth24"),
+            ConnectionType::Ethernet,
+        )
+    }
+
+    #[test]
+    fn test_connection_state_transitions() {
+        assert!(ConnectionState::Planned.can_transition_to(ConnectionState::Cabled));
+        assert!(!ConnectionState::Planned.can_transition_to(ConnectionState::Active));
+        assert!(ConnectionState::Degraded.can_transition_to(ConnectionState::Active));
+        assert!(!ConnectionState::Cabled.can_transition_to(ConnectionState::Degraded));
+        assert!(ConnectionState::Removed.is_terminal());
+        assert!(ConnectionState::Removed.valid_transitions().is_empty());
+    }
+
+    #[test]
+    fn test_connection_full_lifecycle() {
+        let mut connection = planned_connection();
+        assert_eq!(connection.state(), ConnectionState::Planned);
+        assert_eq!(connection.version(), 1);
+
+        connection.cable().unwrap();
+        assert_eq!(connection.state(), ConnectionState::Cabled);
+
+        connection.activate(Some(LinkSpeed::Gbps1)).unwrap();
+        assert_eq!(connection.state(), ConnectionState::Active);
+        assert_eq!(connection.speed(), Some(LinkSpeed::Gbps1));
+
+        connection.degrade("CRC errors".to_string()).unwrap();
+        assert_eq!(connection.state(), ConnectionState::Degraded);
+        assert_eq!(connection.degraded_reason(), Some("CRC errors"));
+
+        connection.activate(Some(LinkSpeed::Gbps1)).unwrap();
+        assert_eq!(connection.degraded_reason(), None);
+
+        connection.remove().unwrap();
+        assert_eq!(connection.state(), ConnectionState::Removed);
+        assert_eq!(connection.version(), 6);
+        assert_eq!(connection.take_pending_events().len(), 6);
+    }
+
+    #[test]
+    fn test_connection_cannot_reactivate_after_removal() {
+        let mut connection = planned_connection();
+        connection.cable().unwrap();
+        connection.activate(None).unwrap();
+        connection.remove().unwrap();
+        connection.take_pending_events();
+
+        let result = connection.activate(Some(LinkSpeed::Gbps1));
+
+        assert!(matches!(
+            result,
+            Err(AggregateError::InvalidConnectionTransition {
+                from: ConnectionState::Removed,
+                to: ConnectionState::Active,
+            })
+        ));
+        assert_eq!(connection.state(), ConnectionState::Removed);
+        assert!(connection.take_pending_events().is_empty());
+    }
+
+    #[test]
+    fn test_connection_cannot_activate_before_cabling() {
+        let mut connection = planned_connection();
+        assert!(connection.activate(None).is_err());
+        assert!(connection.degrade("no link".to_string()).is_err());
+        assert_eq!(connection.state(), ConnectionState::Planned);
+    }
+
+    fn cabled_connection() -> NetworkConnectionAggregate {
+        let mut connection = planned_connection();
+        connection.cable().unwrap();
+        connection
+    }
+
+    #[test]
+    fn test_connection_negotiates_matched_10g_link() {
+        let sfp_plus = PortCapabilities::new(PortMedia::Fiber, [LinkSpeed::Gbps1, LinkSpeed::Gbps10]);
+        let mut connection = cabled_connection();
+
+        assert_eq!(connection.activate_negotiated(&sfp_plus, &sfp_plus).unwrap(), LinkSpeed::Gbps10);
+        assert_eq!(connection.state(), ConnectionState::Active);
+        assert_eq!(connection.speed(), Some(LinkSpeed::Gbps10));
+    }
+
+    #[test]
+    fn test_connection_negotiates_down_to_1g() {
+        let gigabit = PortCapabilities::new(PortMedia::Copper, [LinkSpeed::Mbps100, LinkSpeed::Gbps1]);
+        let multigig = PortCapabilities::new(PortMedia::Copper, [LinkSpeed::Gbps1, LinkSpeed::Gbps2_5, LinkSpeed::Gbps10]);
+        let mut connection = cabled_connection();
+
+        assert_eq!(connection.activate_negotiated(&gigabit, &multigig).unwrap(), LinkSpeed::Gbps1);
+        assert_eq!(connection.speed(), Some(LinkSpeed::Gbps1));
+    }
+
+    #[test]
+    fn test_connection_rejects_fiber_to_copper() {
+        let fiber = PortCapabilities::new(PortMedia::Fiber, [LinkSpeed::Gbps10]);
+        let copper = PortCapabilities::new(PortMedia::Copper, [LinkSpeed::Gbps1, LinkSpeed::Gbps10]);
+        let mut connection = cabled_connection();
+        connection.take_pending_events();
+
+        let result = connection.activate_negotiated(&fiber, &copper);
+
+        assert!(matches!(
+            result,
+            Err(AggregateError::InvalidLinkSpeed(LinkSpeedError::IncompatibleMedia {
+                local: PortMedia::Fiber,
+                peer: PortMedia::Copper,
+            }))
+        ));
+        assert_eq!(connection.state(), ConnectionState::Cabled);
+        assert!(connection.take_pending_events().is_empty());
+    }
+
+    #[test]
+    fn test_connection_from_events() {
+        let mut original = planned_connection();
+        original.cable().unwrap();
+        original.activate(Some(LinkSpeed::Gbps10)).unwrap();
+        original.degrade("flapping".to_string()).unwrap();
+
+        let rebuilt = NetworkConnectionAggregate::from_events(original.take_pending_events()).unwrap();
+
+        assert_eq!(rebuilt.id(), original.id());
+        assert_eq!(rebuilt.state(), ConnectionState::Degraded);
+        assert_eq!(rebuilt.version(), original.version());
+        assert_eq!(rebuilt.source_port(), original.source_port());
+        assert_eq!(rebuilt.speed(), Some(LinkSpeed::Gbps10));
+        assert_eq!(rebuilt.degraded_reason(), Some("flapping"));
+    }
+
+    #[test]
+    fn test_connection_from_established_event_is_active() {
+        let connection_id = ConnectionId::new();
+        let rebuilt = NetworkConnectionAggregate::from_events([
+            NetworkEvent::ConnectionEstablished {
+                connection_id,
+                source_device: DeviceId::new(),
+                source_port: PortId::new("ge-0/0/1"),
+                target_device: DeviceId::new(),
+                target_port: PortId::new("ge-0/0/2"),
+                connection_type: ConnectionType::Fiber,
+            },
+            NetworkEvent::ConnectionRemoved { connection_id },
+        ])
+        .unwrap();
+
+        assert_eq!(rebuilt.id(), connection_id);
+        assert_eq!(rebuilt.state(), ConnectionState::Removed);
+        assert_eq!(rebuilt.version(), 2);
+    }
+
+    // ==========================================================================
+    // Site Aggregate Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_site_assign_and_unassign_devices() {
+        let mut site = SiteAggregate::new("hq");
+        let (a, b) = (DeviceId::new(), DeviceId::new());
+
+        site.assign_device(a);
+        site.assign_device(b);
+        site.assign_device(a);
+        assert_eq!(site.devices(), &[a, b]);
+
+        site.unassign_device(a);
+        site.unassign_device(a);
+        assert_eq!(site.devices(), &[b]);
+
+        // Created + two assignments + one unassignment; repeats emit nothing
+        assert_eq!(site.version(), 4);
+        assert_eq!(site.take_pending_events().len(), 4);
+    }
+
+    #[test]
+    fn test_site_rejects_overlapping_prefix() {
+        let mut site = SiteAggregate::new("branch");
+        let lan = IpNetwork::parse("10.1.0.0/16").unwrap();
+        site.allocate_prefix(lan).unwrap();
+        site.allocate_prefix(lan).unwrap();
+        site.take_pending_events();
+
+        let result = site.allocate_prefix(IpNetwork::parse("10.1.4.0/24").unwrap());
+
+        assert!(matches!(
+            result,
+            Err(AggregateError::OverlappingPrefix { existing, .. }) if existing == lan
+        ));
+        assert_eq!(site.prefixes(), &[lan]);
+        assert!(site.take_pending_events().is_empty());
+    }
+
+    #[test]
+    fn test_site_from_events() {
+        let mut original = SiteAggregate::new("dc1");
+        let (a, b) = (DeviceId::new(), DeviceId::new());
+        original.assign_device(a);
+        original.assign_device(b);
+        original.unassign_device(a);
+        original.allocate_prefix(IpNetwork::parse("192.168.10.0/24").unwrap()).unwrap();
+
+        let rebuilt = SiteAggregate::from_events(original.take_pending_events()).unwrap();
+
+        assert_eq!(rebuilt.id(), original.id());
+        assert_eq!(rebuilt.name(), "dc1");
+        assert_eq!(rebuilt.version(), original.version());
+        assert_eq!(rebuilt.devices(), &[b]);
+        assert_eq!(rebuilt.prefixes(), original.prefixes());
+    }
 }
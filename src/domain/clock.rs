@@ -0,0 +1,89 @@
+//! # Clock
+//!
+//! Where event timestamps come from. The event store and the service read
+//! the time through an injected `Clock` instead of calling `Utc::now()`, so
+//! tests can pin timestamps with `MockClock` and assert them exactly.
+//!
+//! ```rust,ignore
+//! let clock = Arc::new(MockClock::new(start));
+//! let store = NatsEventStore::new(config).await?.with_clock(clock.clone());
+//! clock.advance(chrono::Duration::seconds(5));
+//! ```
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+
+/// Source of the current time
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// The current time
+    fn now(&self) -> DateTime<Utc>;
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> DateTime<Utc> {
+        (**self).now()
+    }
+}
+
+/// The system wall clock, used unless another clock is injected
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to
+///
+/// Share it behind an `Arc` to keep a handle for `set` and `advance` after
+/// injecting it.
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl MockClock {
+    /// A clock stopped at `now`
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { now: Mutex::new(now) }
+    }
+
+    /// Move the clock to `now`
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) = now;
+    }
+
+    /// Move the clock forward by `by`
+    pub fn advance(&self, by: chrono::Duration) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) += by;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_moves_only_when_told() {
+        let start = DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        let clock = MockClock::new(start);
+        assert_eq!(clock.now(), start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance(chrono::Duration::seconds(90));
+        assert_eq!(clock.now(), start + chrono::Duration::seconds(90));
+
+        clock.set(start);
+        assert_eq!(Arc::new(clock).now(), start);
+    }
+}
@@ -0,0 +1,305 @@
+//! # Command Dispatch
+//!
+//! `CommandHandler` is the uniform CQRS write path: it loads the aggregate a
+//! `NetworkCommand` targets, runs the command against it and returns the
+//! events produced. The handler never persists anything; the caller appends
+//! the events to its event store and folds them into its read side, for
+//! example with `MemoryRepository::apply`.
+//!
+//! Commands that need a vendor or inventory system rather than an aggregate
+//! (`DiscoverDevices`, `RestartDevice`, `SyncToInventory`, `AllocateIp`) and
+//! topology commands, which have no aggregate yet, are rejected with
+//! `AggregateError::UnsupportedCommand`. `NetworkService` covers those.
+
+use std::collections::HashMap;
+
+use crate::domain::aggregates::{AggregateError, NetworkConnectionAggregate, NetworkDeviceAggregate};
+use crate::domain::commands::NetworkCommand;
+use crate::domain::events::NetworkEvent;
+use crate::domain::value_objects::{ConnectionId, DeviceId};
+
+/// Where `CommandHandler` loads the current state of aggregates from
+pub trait AggregateRepository {
+    /// Current state of a device, if it exists
+    fn device(&self, device_id: DeviceId) -> Option<NetworkDeviceAggregate>;
+
+    /// Current state of a connection, if it exists
+    fn connection(&self, connection_id: ConnectionId) -> Option<NetworkConnectionAggregate>;
+}
+
+/// Aggregates kept in memory and rebuilt from events
+///
+/// Keyed by aggregate id, as events carry it.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryRepository {
+    devices: HashMap<String, NetworkDeviceAggregate>,
+    connections: HashMap<String, NetworkConnectionAggregate>,
+}
+
+impl MemoryRepository {
+    /// Repository holding the aggregates `events` build
+    pub fn from_events(events: impl IntoIterator<Item = NetworkEvent>) -> Self {
+        let mut repository = Self::default();
+        repository.apply(events);
+        repository
+    }
+
+    /// Add a device loaded elsewhere, replacing any with the same id
+    pub fn insert_device(&mut self, device: NetworkDeviceAggregate) {
+        self.devices.insert(device.id().to_string(), device);
+    }
+
+    /// Add a connection loaded elsewhere, replacing any with the same id
+    pub fn insert_connection(&mut self, connection: NetworkConnectionAggregate) {
+        self.connections.insert(connection.id().to_string(), connection);
+    }
+
+    /// Fold events, typically those returned by `CommandHandler::handle`, into the aggregates
+    ///
+    /// Events for aggregates the repository does not model are ignored.
+    pub fn apply(&mut self, events: impl IntoIterator<Item = NetworkEvent>) {
+        for event in events {
+            let aggregate_id = event.aggregate_id();
+            match event.aggregate_type() {
+                "device" => {
+                    let device = self.devices.remove(&aggregate_id);
+                    if let Some(device) = NetworkDeviceAggregate::replay_event(device, event) {
+                        self.devices.insert(aggregate_id, device);
+                    }
+                }
+                "connection" => {
+                    let connection = self.connections.remove(&aggregate_id);
+                    if let Some(connection) = NetworkConnectionAggregate::replay_event(connection, event) {
+                        self.connections.insert(aggregate_id, connection);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl AggregateRepository for MemoryRepository {
+    fn device(&self, device_id: DeviceId) -> Option<NetworkDeviceAggregate> {
+        self.devices.get(&device_id.to_string()).cloned()
+    }
+
+    fn connection(&self, connection_id: ConnectionId) -> Option<NetworkConnectionAggregate> {
+        self.connections.get(&connection_id.to_string()).cloned()
+    }
+}
+
+/// Dispatches `NetworkCommand`s to the aggregates they target
+pub struct CommandHandler<R> {
+    repository: R,
+}
+
+impl<R: AggregateRepository> CommandHandler<R> {
+    /// Handler loading aggregates from `repository`
+    pub fn new(repository: R) -> Self {
+        Self { repository }
+    }
+
+    /// Repository the handler loads aggregates from
+    pub fn repository(&self) -> &R {
+        &self.repository
+    }
+
+    /// Mutable access to the repository, to apply persisted events
+    pub fn repository_mut(&mut self) -> &mut R {
+        &mut self.repository
+    }
+
+    /// Run `command` and return the events it produced, in order
+    ///
+    /// A rejected command produces no events, even if it would have changed
+    /// the aggregate in several steps.
+    pub fn handle(&self, command: NetworkCommand) -> Result<Vec<NetworkEvent>, AggregateError> {
+        match command {
+            NetworkCommand::AdoptDevice { device_id, vendor_id } => {
+                self.on_device(device_id, |device| device.adopt(vendor_id))
+            }
+            NetworkCommand::ConfigureDevice { device_id, name, interfaces, vlans } => {
+                self.on_device(device_id, |device| {
+                    if let Some(name) = name.filter(|name| name != device.name()) {
+                        device.rename(name)?;
+                    }
+                    device.start_configuration()?;
+                    device.complete_configuration(interfaces, vlans)
+                })
+            }
+            NetworkCommand::RenameDevice { device_id, new_name } => {
+                self.on_device(device_id, |device| device.rename(new_name))
+            }
+            NetworkCommand::DecommissionDevice { device_id } => {
+                self.on_device(device_id, |device| device.decommission())
+            }
+            NetworkCommand::ConnectDevices {
+                source_device,
+                source_port,
+                target_device,
+                target_port,
+                connection_type,
+            } => {
+                for device_id in [source_device, target_device] {
+                    self.load_device(device_id)?;
+                }
+                let mut connection = NetworkConnectionAggregate::new_planned(
+                    source_device,
+                    source_port,
+                    target_device,
+                    target_port,
+                    connection_type,
+                );
+                Ok(connection.take_pending_events())
+            }
+            NetworkCommand::DisconnectDevices { connection_id } => {
+                let mut connection = self.repository
+                    .connection(connection_id)
+                    .ok_or(AggregateError::ConnectionNotFound(connection_id))?;
+                connection.remove()?;
+                Ok(connection.take_pending_events())
+            }
+            other => Err(AggregateError::UnsupportedCommand(other.command_type().to_string())),
+        }
+    }
+
+    fn load_device(&self, device_id: DeviceId) -> Result<NetworkDeviceAggregate, AggregateError> {
+        self.repository
+            .device(device_id)
+            .ok_or(AggregateError::DeviceNotFound(device_id))
+    }
+
+    /// Run `command` against a device and collect its events
+    fn on_device<F>(&self, device_id: DeviceId, command: F) -> Result<Vec<NetworkEvent>, AggregateError>
+    where
+        F: FnOnce(&mut NetworkDeviceAggregate) -> Result<(), AggregateError>,
+    {
+        let mut device = self.load_device(device_id)?;
+        command(&mut device)?;
+        Ok(device.take_pending_events())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::aggregates::DeviceState;
+    use crate::domain::value_objects::{ConnectionType, DeviceType, MacAddress, PortId, VlanConfig};
+
+    fn discovered(device_id: DeviceId) -> NetworkEvent {
+        NetworkEvent::DeviceDiscovered {
+            device_id,
+            mac: MacAddress::parse("24:5a:4c:00:00:01").unwrap(),
+            device_type: DeviceType::Switch,
+            ip_address: None,
+        }
+    }
+
+    // ========================================================================
+    // Device Command Tests
+    // ========================================================================
+
+    #[test]
+    fn test_adopt_command_produces_adopting_event() {
+        let device_id = DeviceId::new();
+        let handler = CommandHandler::new(MemoryRepository::from_events([discovered(device_id)]));
+
+        let events = handler
+            .handle(NetworkCommand::AdoptDevice { device_id, vendor_id: "24:5a:4c:00:00:01".to_string() })
+            .unwrap();
+
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            &events[0],
+            NetworkEvent::DeviceAdopting { device_id: id, vendor_id } if *id == device_id && vendor_id == "24:5a:4c:00:00:01"
+        ));
+        // Nothing is applied until the caller folds the events in
+        assert_eq!(handler.repository().device(device_id).unwrap().state(), DeviceState::Discovered);
+    }
+
+    #[test]
+    fn test_decommission_command_after_applying_events() {
+        let device_id = DeviceId::new();
+        let mut handler = CommandHandler::new(MemoryRepository::from_events([discovered(device_id)]));
+
+        let adopted = handler
+            .handle(NetworkCommand::AdoptDevice { device_id, vendor_id: "sw-1".to_string() })
+            .unwrap();
+        handler.repository_mut().apply(adopted);
+        // Adopting devices cannot be decommissioned until the vendor confirms them
+        assert!(handler.handle(NetworkCommand::DecommissionDevice { device_id }).is_err());
+        handler.repository_mut().apply([NetworkEvent::DeviceProvisioned {
+            device_id,
+            model: "USW-24".to_string(),
+            firmware_version: "6.5.59".to_string(),
+        }]);
+
+        let events = handler.handle(NetworkCommand::DecommissionDevice { device_id }).unwrap();
+
+        assert!(matches!(
+            events.as_slice(),
+            [NetworkEvent::DeviceDecommissioned { device_id: id }] if *id == device_id
+        ));
+        handler.repository_mut().apply(events);
+        assert_eq!(handler.repository().device(device_id).unwrap().state(), DeviceState::Decommissioned);
+    }
+
+    #[test]
+    fn test_rejected_command_produces_no_events() {
+        let device_id = DeviceId::new();
+        let handler = CommandHandler::new(MemoryRepository::from_events([discovered(device_id)]));
+
+        // Discovered devices cannot be configured; the rename must not leak out
+        let result = handler.handle(NetworkCommand::ConfigureDevice {
+            device_id,
+            name: Some("core-sw".to_string()),
+            interfaces: vec![],
+            vlans: vec![VlanConfig::new(10, "users").unwrap()],
+        });
+
+        assert!(matches!(result, Err(AggregateError::InvalidTransition { .. })));
+    }
+
+    #[test]
+    fn test_unknown_device_and_unsupported_commands() {
+        let handler = CommandHandler::new(MemoryRepository::default());
+        let device_id = DeviceId::new();
+
+        assert!(matches!(
+            handler.handle(NetworkCommand::DecommissionDevice { device_id }),
+            Err(AggregateError::DeviceNotFound(id)) if id == device_id
+        ));
+        assert!(matches!(
+            handler.handle(NetworkCommand::RestartDevice { device_id }),
+            Err(AggregateError::UnsupportedCommand(name)) if name == "RestartDevice"
+        ));
+    }
+
+    // ========================================================================
+    // Connection Command Tests
+    // ========================================================================
+
+    #[test]
+    fn test_connect_then_disconnect() {
+        let (a, b) = (DeviceId::new(), DeviceId::new());
+        let mut handler = CommandHandler::new(MemoryRepository::from_events([discovered(a), discovered(b)]));
+
+        let planned = handler
+            .handle(NetworkCommand::ConnectDevices {
+                source_device: a,
+                source_port: PortId::new("eth1"),
+                target_device: b,
+                target_port: PortId::new("eth1"),
+                connection_type: ConnectionType::Ethernet,
+            })
+            .unwrap();
+        let NetworkEvent::ConnectionPlanned { connection_id, .. } = planned[0] else {
+            panic!("expected ConnectionPlanned, got {:?}", planned);
+        };
+        handler.repository_mut().apply(planned);
+
+        let removed = handler.handle(NetworkCommand::DisconnectDevices { connection_id }).unwrap();
+        assert!(matches!(removed.as_slice(), [NetworkEvent::ConnectionRemoved { .. }]));
+    }
+}
@@ -4,10 +4,11 @@
 //! Commands are validated and either accepted (producing events) or rejected.
 
 use crate::domain::value_objects::*;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Network domain commands
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum NetworkCommand {
     // ========================================================================
     // Device Commands
@@ -356,6 +357,8 @@ mod tests {
                 prefix_len: Some(24),
                 vlan_id: Some(100),
                 enabled: true,
+                mtu: None,
+                speed: None,
             }],
             vlans: vec![VlanConfig::new(100, "Management").unwrap()],
         };
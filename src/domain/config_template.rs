@@ -0,0 +1,332 @@
+//! # Configuration Templates
+//!
+//! Reusable device configurations, such as a standard access-switch
+//! profile, parameterized per device. A `ConfigTemplate` is a
+//! `DeviceConfigRequest` as JSON whose strings may reference variables as
+//! `{{name}}`:
+//!
+//! ```text
+//! {
+//!   "hostname": "{{site}}-{{hostname}}",
+//!   "interfaces": [{"name": "vlan1", "ip_address": "{{mgmt_ip}}", "prefix_len": 24, "enabled": true}],
+//!   "vlans": "{{vlans}}"
+//! }
+//! ```
+//!
+//! A string that is exactly one placeholder is replaced by the variable's
+//! JSON value, so a VLAN set can fill a list; placeholders within a longer
+//! string are replaced by the value as text. Every referenced variable must
+//! be provided, or rendering fails listing the missing ones.
+//!
+//! ```rust,ignore
+//! let variables = TemplateVariables::new().vlans(&access_vlans).set("site", "ams1");
+//! service.configure_from_template(device_id, &access_switch, &variables).await?;
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::IpAddr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::aggregates::NetworkDeviceAggregate;
+use crate::domain::ports::DeviceConfigRequest;
+use crate::domain::value_objects::VlanConfig;
+
+/// Variable holding the device's hostname
+pub const HOSTNAME_VARIABLE: &str = "hostname";
+
+/// Variable holding the device's management IP address
+pub const MGMT_IP_VARIABLE: &str = "mgmt_ip";
+
+/// Variable holding the VLAN set to configure
+pub const VLANS_VARIABLE: &str = "vlans";
+
+/// Errors rendering a configuration template
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum TemplateError {
+    /// The template references variables that were not provided
+    #[error("Template {template} references undefined variables: {}", .names.join(", "))]
+    MissingVariables {
+        /// Template being rendered
+        template: String,
+        /// Variables referenced but not provided, sorted
+        names: Vec<String>,
+    },
+
+    /// The rendered template is not a valid configuration request
+    #[error("Template {template} does not render to a valid configuration: {message}")]
+    Invalid {
+        /// Template being rendered
+        template: String,
+        /// Why the rendered configuration was rejected
+        message: String,
+    },
+}
+
+/// Values for the variables a template references
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TemplateVariables {
+    values: BTreeMap<String, serde_json::Value>,
+}
+
+impl TemplateVariables {
+    /// No variables yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The device's name as `hostname` and, if known, its IP address as `mgmt_ip`
+    pub fn for_device(device: &NetworkDeviceAggregate) -> Self {
+        let variables = Self::new().hostname(device.name());
+        match device.ip_address() {
+            Some(ip) => variables.mgmt_ip(ip),
+            None => variables,
+        }
+    }
+
+    /// Set `name` to `value`
+    pub fn set(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.values.insert(name.into(), value.into());
+        self
+    }
+
+    /// Set `hostname`
+    pub fn hostname(self, hostname: impl Into<String>) -> Self {
+        self.set(HOSTNAME_VARIABLE, hostname.into())
+    }
+
+    /// Set `mgmt_ip`
+    pub fn mgmt_ip(self, ip: IpAddr) -> Self {
+        self.set(MGMT_IP_VARIABLE, ip.to_string())
+    }
+
+    /// Set `vlans`
+    pub fn vlans(self, vlans: &[VlanConfig]) -> Self {
+        self.set(VLANS_VARIABLE, serde_json::json!(vlans))
+    }
+
+    /// Value of `name`, if provided
+    pub fn get(&self, name: &str) -> Option<&serde_json::Value> {
+        self.values.get(name)
+    }
+
+    /// These variables, overridden by any also set in `overrides`
+    pub fn merged_with(mut self, overrides: &TemplateVariables) -> Self {
+        self.values.extend(overrides.values.iter().map(|(name, value)| (name.clone(), value.clone())));
+        self
+    }
+}
+
+/// Produces the configuration to apply to a device
+pub trait ConfigurationGenerator: Send + Sync {
+    /// Configuration for `device`, given operator-supplied `variables`
+    fn generate(
+        &self,
+        device: &NetworkDeviceAggregate,
+        variables: &TemplateVariables,
+    ) -> Result<DeviceConfigRequest, TemplateError>;
+}
+
+/// A named configuration with `{{variable}}` placeholders
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigTemplate {
+    /// Template name, e.g. `access-switch`
+    pub name: String,
+    /// `DeviceConfigRequest` as JSON, with placeholders in its strings
+    pub body: serde_json::Value,
+}
+
+impl ConfigTemplate {
+    /// Template `name` rendering `body`
+    pub fn new(name: impl Into<String>, body: serde_json::Value) -> Self {
+        Self {
+            name: name.into(),
+            body,
+        }
+    }
+
+    /// Names of the variables the template references
+    pub fn variables(&self) -> BTreeSet<String> {
+        let mut names = BTreeSet::new();
+        collect_variables(&self.body, &mut names);
+        names
+    }
+
+    /// Render the template with `variables`
+    ///
+    /// Fails before substituting anything if a referenced variable is not
+    /// provided.
+    pub fn render(&self, variables: &TemplateVariables) -> Result<DeviceConfigRequest, TemplateError> {
+        let missing: Vec<String> = self.variables()
+            .into_iter()
+            .filter(|name| variables.get(name).is_none())
+            .collect();
+        if !missing.is_empty() {
+            return Err(TemplateError::MissingVariables {
+                template: self.name.clone(),
+                names: missing,
+            });
+        }
+
+        serde_json::from_value(substitute(&self.body, variables)).map_err(|e| TemplateError::Invalid {
+            template: self.name.clone(),
+            message: e.to_string(),
+        })
+    }
+}
+
+impl ConfigurationGenerator for ConfigTemplate {
+    /// Render with the device's own variables, overridden by `variables`
+    fn generate(
+        &self,
+        device: &NetworkDeviceAggregate,
+        variables: &TemplateVariables,
+    ) -> Result<DeviceConfigRequest, TemplateError> {
+        self.render(&TemplateVariables::for_device(device).merged_with(variables))
+    }
+}
+
+/// Placeholders in `text`, as byte ranges with the variable name
+fn placeholders(text: &str) -> Vec<(std::ops::Range<usize>, &str)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(open) = text[from..].find("{{").map(|at| from + at) {
+        let Some(close) = text[open + 2..].find("}}").map(|at| open + 2 + at) else {
+            break;
+        };
+        let name = text[open + 2..close].trim();
+        if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            found.push((open..close + 2, name));
+        }
+        from = close + 2;
+    }
+    found
+}
+
+fn collect_variables(value: &serde_json::Value, names: &mut BTreeSet<String>) {
+    match value {
+        serde_json::Value::String(text) => {
+            names.extend(placeholders(text).into_iter().map(|(_, name)| name.to_string()));
+        }
+        serde_json::Value::Array(items) => items.iter().for_each(|item| collect_variables(item, names)),
+        serde_json::Value::Object(fields) => fields.values().for_each(|field| collect_variables(field, names)),
+        _ => {}
+    }
+}
+
+/// `value` with every placeholder replaced; all referenced variables must be provided
+fn substitute(value: &serde_json::Value, variables: &TemplateVariables) -> serde_json::Value {
+    match value {
+        serde_json::Value::String(text) => {
+            let found = placeholders(text);
+            if let [(range, name)] = found.as_slice() {
+                if *range == (0..text.len()) {
+                    return variables.get(name).cloned().unwrap_or_default();
+                }
+            }
+
+            let mut rendered = String::new();
+            let mut last = 0;
+            for (range, name) in found {
+                rendered.push_str(&text[last..range.start]);
+                match variables.get(name) {
+                    Some(serde_json::Value::String(s)) => rendered.push_str(s),
+                    Some(other) => rendered.push_str(&other.to_string()),
+                    None => rendered.push_str(&text[range.clone()]),
+                }
+                last = range.end;
+            }
+            rendered.push_str(&text[last..]);
+            serde_json::Value::String(rendered)
+        }
+        serde_json::Value::Array(items) => items.iter().map(|item| substitute(item, variables)).collect(),
+        serde_json::Value::Object(fields) => serde_json::Value::Object(
+            fields.iter().map(|(key, field)| (key.clone(), substitute(field, variables))).collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{DeviceType, MacAddress};
+
+    fn access_switch() -> ConfigTemplate {
+        ConfigTemplate::new("access-switch", serde_json::json!({
+            "hostname": "{{ site }}-{{hostname}}",
+            "interfaces": [{"name": "vlan1", "ip_address": "{{mgmt_ip}}", "prefix_len": 24, "enabled": true}],
+            "vlans": "{{vlans}}",
+            "ntp_servers": ["ntp.{{site}}.example.net"],
+        }))
+    }
+
+    #[test]
+    fn test_render_with_all_variables() {
+        let template = access_switch();
+        assert_eq!(
+            template.variables().into_iter().collect::<Vec<_>>(),
+            ["hostname", "mgmt_ip", "site", "vlans"]
+        );
+
+        let vlans = vec![VlanConfig::new(10, "users").unwrap(), VlanConfig::new(20, "voice").unwrap()];
+        let variables = TemplateVariables::new()
+            .hostname("sw-03")
+            .mgmt_ip("10.0.1.3".parse().unwrap())
+            .vlans(&vlans)
+            .set("site", "ams1");
+
+        let config = template.render(&variables).unwrap();
+        assert_eq!(config.hostname.as_deref(), Some("ams1-sw-03"));
+        assert_eq!(config.interfaces[0].ip_address, Some("10.0.1.3".parse().unwrap()));
+        assert_eq!(config.interfaces[0].prefix_len, Some(24));
+        assert_eq!(config.vlans, vlans);
+        assert_eq!(config.ntp_servers, ["ntp.ams1.example.net"]);
+    }
+
+    #[test]
+    fn test_missing_variables_are_rejected() {
+        let variables = TemplateVariables::new().hostname("sw-03").set("site", "ams1");
+
+        let result = access_switch().render(&variables);
+
+        assert_eq!(
+            result,
+            Err(TemplateError::MissingVariables {
+                template: "access-switch".to_string(),
+                names: vec!["mgmt_ip".to_string(), "vlans".to_string()],
+            })
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Template access-switch references undefined variables: mgmt_ip, vlans"
+        );
+    }
+
+    #[test]
+    fn test_value_of_wrong_shape_is_invalid() {
+        let template = ConfigTemplate::new("vlans-only", serde_json::json!({"vlans": "{{vlans}}"}));
+
+        let result = template.render(&TemplateVariables::new().set("vlans", "10,20"));
+
+        assert!(matches!(result, Err(TemplateError::Invalid { template, .. }) if template == "vlans-only"));
+    }
+
+    #[test]
+    fn test_device_variables_are_overridable() {
+        let device = NetworkDeviceAggregate::new_discovered(
+            MacAddress::parse("24:5a:4c:00:00:01").unwrap(),
+            DeviceType::Switch,
+            Some("10.0.1.3".parse().unwrap()),
+        );
+        let template = ConfigTemplate::new("mgmt", serde_json::json!({
+            "hostname": "{{hostname}}",
+            "interfaces": [{"name": "vlan1", "ip_address": "{{mgmt_ip}}", "enabled": true}],
+        }));
+
+        let config = template.generate(&device, &TemplateVariables::new().hostname("edge-01")).unwrap();
+
+        assert_eq!(config.hostname.as_deref(), Some("edge-01"));
+        assert_eq!(config.interfaces[0].ip_address, Some("10.0.1.3".parse().unwrap()));
+    }
+}
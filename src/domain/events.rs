@@ -2,15 +2,34 @@
 //!
 //! All state changes are expressed as immutable events.
 //! Events are the source of truth - aggregates are projections of events.
+//!
+//! ## Schema Versions
+//!
+//! Stored events outlive the code that wrote them. Events are persisted in a
+//! [`VersionedEvent`] envelope carrying `schema_version`; [`migrate`] upgrades
+//! a payload of any older version one step at a time before deserializing it.
+//!
+//! | Version | Format |
+//! |---------|--------|
+//! | 1 | Bare `NetworkEvent` JSON, no envelope |
+//! | 2 | Envelope; VLANs carry `access_ports`, `DeviceWentMissing` carries `last_seen` |
+//! | 3 | `DeviceSyncedToInventory` carries `content_hash` |
+//!
+//! When changing a variant's fields, bump [`EVENT_SCHEMA_VERSION`] and add an
+//! upgrade step from the previous version.
 
 use crate::domain::value_objects::*;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+/// Schema version written with every new event
+pub const EVENT_SCHEMA_VERSION: u16 = 3;
+
 /// Network domain events
 ///
 /// Every state change in the network domain produces an event.
 /// Events are immutable and form the audit trail.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum NetworkEvent {
     // ========================================================================
     // Device Lifecycle Events
@@ -60,6 +79,14 @@ pub enum NetworkEvent {
         device_id: DeviceId,
     },
 
+    /// A decommissioned device's history was purged
+    ///
+    /// Tombstone kept outside the device's history, which no longer exists.
+    DevicePurged {
+        /// Purged device
+        device_id: DeviceId,
+    },
+
     /// Device was renamed
     DeviceRenamed {
         device_id: DeviceId,
@@ -67,6 +94,121 @@ pub enum NetworkEvent {
         new_name: String,
     },
 
+    /// Device is no longer reported by the vendor controller
+    ///
+    /// `last_seen` is when the device last appeared in a vendor listing, if known.
+    DeviceWentMissing {
+        device_id: DeviceId,
+        last_seen: Option<chrono::DateTime<chrono::Utc>>,
+    },
+
+    /// A missing device is reported by the vendor controller again
+    DeviceReappeared {
+        device_id: DeviceId,
+    },
+
+    /// A configuration change failed and the previous configuration was restored
+    ConfigRolledBack {
+        /// Device whose configuration was rolled back
+        device_id: DeviceId,
+        /// Why the change was rolled back
+        reason: String,
+    },
+
+    /// A firmware upgrade was sent to the device
+    FirmwareUpgradeStarted {
+        /// Device being upgraded
+        device_id: DeviceId,
+        /// Firmware recorded before the upgrade, if known
+        from_version: Option<String>,
+        /// Firmware being installed
+        target_version: String,
+    },
+
+    /// The device is running the upgraded firmware
+    FirmwareUpgradeCompleted {
+        /// Upgraded device
+        device_id: DeviceId,
+        /// Firmware now running
+        firmware_version: String,
+    },
+
+    /// A port's link went up or down between two stats polls
+    PortLinkChanged {
+        /// Device owning the port
+        device_id: DeviceId,
+        /// Port whose link changed
+        port_id: PortId,
+        /// Whether the link is now up
+        up: bool,
+    },
+
+    /// A health metric rose above its threshold between two stats polls
+    DeviceHealthDegraded {
+        /// Degraded device
+        device_id: DeviceId,
+        /// Metric that crossed its threshold
+        metric: HealthMetric,
+        /// Value reported by the poll that crossed it
+        value: f64,
+    },
+
+    /// The device's running configuration no longer matches the desired one
+    ConfigDriftDetected {
+        /// Drifted device
+        device_id: DeviceId,
+        /// Desired settings the device does not match
+        diffs: Vec<ConfigDiff>,
+    },
+
+    /// A tag was added to the device
+    DeviceTagged {
+        /// Tagged device
+        device_id: DeviceId,
+        /// Added tag
+        tag: Tag,
+    },
+
+    /// A tag was removed from the device
+    DeviceUntagged {
+        /// Untagged device
+        device_id: DeviceId,
+        /// Removed tag
+        tag: Tag,
+    },
+
+    /// A physical unit was added to the stack the device stands for
+    StackMemberAdded {
+        /// Logical stack device
+        device_id: DeviceId,
+        /// Added unit
+        member: StackMember,
+    },
+
+    /// A physical unit was split out of the stack the device stands for
+    StackMemberRemoved {
+        /// Logical stack device
+        device_id: DeviceId,
+        /// Removed unit
+        member: StackMember,
+    },
+
+    /// The device was merged into a stack as one of its units
+    DeviceJoinedStack {
+        /// Merged unit
+        device_id: DeviceId,
+        /// Logical stack device it now belongs to
+        stack_id: DeviceId,
+    },
+
+    /// The device was split back out of the stack it was merged into
+    DeviceLeftStack {
+        /// Split-out unit
+        device_id: DeviceId,
+        /// Logical stack device it belonged to
+        stack_id: DeviceId,
+    },
+
     // ========================================================================
     // Connection Events
     // ========================================================================
@@ -93,6 +235,44 @@ pub enum NetworkEvent {
         speed: Option<LinkSpeed>,
     },
 
+    /// A connection between two ports was planned
+    ConnectionPlanned {
+        /// Planned connection
+        connection_id: ConnectionId,
+        /// Device at the A end
+        source_device: DeviceId,
+        /// Port at the A end
+        source_port: PortId,
+        /// Device at the B end
+        target_device: DeviceId,
+        /// Port at the B end
+        target_port: PortId,
+        /// Physical medium
+        connection_type: ConnectionType,
+    },
+
+    /// The planned connection was physically cabled
+    ConnectionCabled {
+        /// Cabled connection
+        connection_id: ConnectionId,
+    },
+
+    /// The link came up
+    ConnectionActivated {
+        /// Activated connection
+        connection_id: ConnectionId,
+        /// Negotiated speed, if known
+        speed: Option<LinkSpeed>,
+    },
+
+    /// The link is up but impaired (errors, reduced speed, flapping)
+    ConnectionDegraded {
+        /// Degraded connection
+        connection_id: ConnectionId,
+        /// What is wrong with the link
+        reason: String,
+    },
+
     // ========================================================================
     // Topology Events
     // ========================================================================
@@ -115,6 +295,43 @@ pub enum NetworkEvent {
         device_id: DeviceId,
     },
 
+    // ========================================================================
+    // Site Events
+    // ========================================================================
+
+    /// A site was created
+    SiteCreated {
+        /// New site
+        site_id: SiteId,
+        /// Site name
+        name: String,
+    },
+
+    /// A device was assigned to the site
+    DeviceAssignedToSite {
+        /// Site the device joined
+        site_id: SiteId,
+        /// Assigned device
+        device_id: DeviceId,
+    },
+
+    /// A device left the site, e.g. to move to another one
+    DeviceUnassignedFromSite {
+        /// Site the device left
+        site_id: SiteId,
+        /// Unassigned device
+        device_id: DeviceId,
+    },
+
+    /// An IP prefix was allocated to the site
+    SitePrefixAllocated {
+        /// Site owning the prefix
+        site_id: SiteId,
+        /// Allocated prefix
+        #[schemars(with = "String")]
+        prefix: IpNetwork,
+    },
+
     // ========================================================================
     // Inventory Projection Events
     // ========================================================================
@@ -124,6 +341,35 @@ pub enum NetworkEvent {
         device_id: DeviceId,
         inventory_id: String,
         system: String,
+        /// Fingerprint of the inventory fields written, so an unchanged
+        /// device need not be synced again
+        content_hash: Option<String>,
+    },
+
+    /// A failed inventory sync was queued to be retried
+    InventorySyncQueued {
+        /// Device whose sync failed
+        device_id: DeviceId,
+        /// Inventory system the sync was for
+        system: String,
+        /// Failed attempts so far
+        attempts: u32,
+        /// Error of the latest attempt
+        error: String,
+        /// When the next attempt is due
+        retry_at: chrono::DateTime<chrono::Utc>,
+    },
+
+    /// An inventory sync kept failing and was given up on
+    InventorySyncDeadLettered {
+        /// Device whose sync failed
+        device_id: DeviceId,
+        /// Inventory system the sync was for
+        system: String,
+        /// Failed attempts in total
+        attempts: u32,
+        /// Error of the last attempt
+        error: String,
     },
 
     /// IP address allocated
@@ -135,7 +381,156 @@ pub enum NetworkEvent {
     },
 }
 
+/// A serialized event together with the schema version it was written in
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VersionedEvent {
+    /// Schema version of `event`
+    pub schema_version: u16,
+    /// The event itself
+    pub event: NetworkEvent,
+}
+
+/// An event with the ids that trace it across aggregates
+///
+/// The correlation id is shared by every event of one logical operation; the
+/// causation id names the event (or command) that directly caused this one.
+/// Event stores carry these as message metadata rather than in the payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    /// Unique id of this event
+    pub event_id: String,
+    /// Id shared by every event of the same operation
+    pub correlation_id: Option<String>,
+    /// Id of whatever directly caused this event
+    pub causation_id: Option<String>,
+    /// The event itself
+    pub event: NetworkEvent,
+}
+
+impl EventEnvelope {
+    /// Wrap an event with a fresh id and no tracing ids
+    pub fn new(event: NetworkEvent) -> Self {
+        Self {
+            event_id: uuid::Uuid::now_v7().to_string(),
+            correlation_id: None,
+            causation_id: None,
+            event,
+        }
+    }
+
+    /// Set the correlation id
+    pub fn with_correlation_id(mut self, correlation_id: impl Into<String>) -> Self {
+        self.correlation_id = Some(correlation_id.into());
+        self
+    }
+
+    /// Set the causation id
+    pub fn with_causation_id(mut self, causation_id: impl Into<String>) -> Self {
+        self.causation_id = Some(causation_id.into());
+        self
+    }
+
+    /// Mark this event as caused by `cause`
+    ///
+    /// Joins the cause's correlation, or starts one rooted at the cause's id
+    /// if it has none.
+    pub fn caused_by(mut self, cause: &EventEnvelope) -> Self {
+        self.correlation_id = Some(cause.correlation_id.clone().unwrap_or_else(|| cause.event_id.clone()));
+        self.causation_id = Some(cause.event_id.clone());
+        self
+    }
+}
+
+impl From<NetworkEvent> for EventEnvelope {
+    fn from(event: NetworkEvent) -> Self {
+        Self::new(event)
+    }
+}
+
+/// Error upgrading a stored event payload
+#[derive(Debug, thiserror::Error)]
+pub enum EventMigrationError {
+    /// Written by a newer release than this one
+    #[error("Unsupported event schema version {0} (newest known is {EVENT_SCHEMA_VERSION})")]
+    UnsupportedVersion(u16),
+    /// Not an event in any known format
+    #[error("Malformed event payload: {0}")]
+    Malformed(String),
+}
+
+/// Upgrade a stored event payload of any known schema version
+///
+/// Accepts both the current envelope and bare version-1 events.
+pub fn migrate(raw: serde_json::Value) -> Result<NetworkEvent, EventMigrationError> {
+    let (mut version, mut event) = match raw {
+        serde_json::Value::Object(mut fields) if fields.contains_key("schema_version") => {
+            let version = fields
+                .get("schema_version")
+                .and_then(|v| v.as_u64())
+                .and_then(|v| u16::try_from(v).ok())
+                .ok_or_else(|| EventMigrationError::Malformed("schema_version is not a u16".to_string()))?;
+            let event = fields
+                .remove("event")
+                .ok_or_else(|| EventMigrationError::Malformed("envelope has no event".to_string()))?;
+            (version, event)
+        }
+        bare => (1, bare),
+    };
+
+    if version == 0 || version > EVENT_SCHEMA_VERSION {
+        return Err(EventMigrationError::UnsupportedVersion(version));
+    }
+
+    while version < EVENT_SCHEMA_VERSION {
+        match version {
+            1 => upgrade_v1(&mut event),
+            2 => upgrade_v2(&mut event),
+            _ => unreachable!("every version below EVENT_SCHEMA_VERSION has an upgrade step"),
+        }
+        version += 1;
+    }
+
+    serde_json::from_value(event).map_err(|e| EventMigrationError::Malformed(e.to_string()))
+}
+
+/// v1 -> v2: fill in fields added after bare events were first stored
+fn upgrade_v1(event: &mut serde_json::Value) {
+    if let Some(vlans) = event.pointer_mut("/DeviceConfigured/vlans").and_then(|v| v.as_array_mut()) {
+        for vlan in vlans.iter_mut().filter_map(|v| v.as_object_mut()) {
+            vlan.entry("access_ports").or_insert_with(|| serde_json::json!([]));
+        }
+    }
+    if let Some(missing) = event.pointer_mut("/DeviceWentMissing").and_then(|v| v.as_object_mut()) {
+        missing.entry("last_seen").or_insert(serde_json::Value::Null);
+    }
+}
+
+/// v2 -> v3: syncs recorded before content hashing have no hash
+fn upgrade_v2(event: &mut serde_json::Value) {
+    if let Some(synced) = event.pointer_mut("/DeviceSyncedToInventory").and_then(|v| v.as_object_mut()) {
+        synced.entry("content_hash").or_insert(serde_json::Value::Null);
+    }
+}
+
 impl NetworkEvent {
+    /// Every value `aggregate_type` can return
+    pub const AGGREGATE_TYPES: [&'static str; 5] = ["device", "connection", "topology", "site", "inventory"];
+
+    /// Serialize the event in the current schema version's envelope
+    pub fn encode(&self) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec(&VersionedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            event: self.clone(),
+        })
+    }
+
+    /// Deserialize a stored event of any known schema version
+    pub fn decode(payload: &[u8]) -> Result<Self, EventMigrationError> {
+        let raw = serde_json::from_slice(payload)
+            .map_err(|e| EventMigrationError::Malformed(e.to_string()))?;
+        migrate(raw)
+    }
+
     /// Get the aggregate ID this event belongs to
     pub fn aggregate_id(&self) -> String {
         match self {
@@ -147,19 +542,46 @@ impl NetworkEvent {
             | NetworkEvent::DeviceConfigured { device_id, .. }
             | NetworkEvent::DeviceError { device_id, .. }
             | NetworkEvent::DeviceDecommissioned { device_id, .. }
+            | NetworkEvent::DevicePurged { device_id, .. }
             | NetworkEvent::DeviceRenamed { device_id, .. }
+            | NetworkEvent::DeviceWentMissing { device_id, .. }
+            | NetworkEvent::DeviceReappeared { device_id, .. }
+            | NetworkEvent::ConfigRolledBack { device_id, .. }
+            | NetworkEvent::FirmwareUpgradeStarted { device_id, .. }
+            | NetworkEvent::FirmwareUpgradeCompleted { device_id, .. }
+            | NetworkEvent::PortLinkChanged { device_id, .. }
+            | NetworkEvent::DeviceHealthDegraded { device_id, .. }
+            | NetworkEvent::ConfigDriftDetected { device_id, .. }
+            | NetworkEvent::DeviceTagged { device_id, .. }
+            | NetworkEvent::DeviceUntagged { device_id, .. }
+            | NetworkEvent::StackMemberAdded { device_id, .. }
+            | NetworkEvent::StackMemberRemoved { device_id, .. }
+            | NetworkEvent::DeviceJoinedStack { device_id, .. }
+            | NetworkEvent::DeviceLeftStack { device_id, .. }
             | NetworkEvent::DeviceSyncedToInventory { device_id, .. }
+            | NetworkEvent::InventorySyncQueued { device_id, .. }
+            | NetworkEvent::InventorySyncDeadLettered { device_id, .. }
             | NetworkEvent::IpAddressAllocated { device_id, .. } => device_id.to_string(),
 
             // Connection events
             NetworkEvent::ConnectionEstablished { connection_id, .. }
             | NetworkEvent::ConnectionRemoved { connection_id, .. }
-            | NetworkEvent::ConnectionLinkChanged { connection_id, .. } => connection_id.to_string(),
+            | NetworkEvent::ConnectionLinkChanged { connection_id, .. }
+            | NetworkEvent::ConnectionPlanned { connection_id, .. }
+            | NetworkEvent::ConnectionCabled { connection_id, .. }
+            | NetworkEvent::ConnectionActivated { connection_id, .. }
+            | NetworkEvent::ConnectionDegraded { connection_id, .. } => connection_id.to_string(),
 
             // Topology events
             NetworkEvent::TopologyCreated { topology_id, .. }
             | NetworkEvent::DeviceAddedToTopology { topology_id, .. }
             | NetworkEvent::DeviceRemovedFromTopology { topology_id, .. } => topology_id.to_string(),
+
+            // Site events
+            NetworkEvent::SiteCreated { site_id, .. }
+            | NetworkEvent::DeviceAssignedToSite { site_id, .. }
+            | NetworkEvent::DeviceUnassignedFromSite { site_id, .. }
+            | NetworkEvent::SitePrefixAllocated { site_id, .. } => site_id.to_string(),
         }
     }
 
@@ -173,28 +595,46 @@ impl NetworkEvent {
             NetworkEvent::DeviceConfigured { .. } => "DeviceConfigured",
             NetworkEvent::DeviceError { .. } => "DeviceError",
             NetworkEvent::DeviceDecommissioned { .. } => "DeviceDecommissioned",
+            NetworkEvent::DevicePurged { .. } => "DevicePurged",
             NetworkEvent::DeviceRenamed { .. } => "DeviceRenamed",
+            NetworkEvent::DeviceWentMissing { .. } => "DeviceWentMissing",
+            NetworkEvent::DeviceReappeared { .. } => "DeviceReappeared",
+            NetworkEvent::ConfigRolledBack { .. } => "ConfigRolledBack",
+            NetworkEvent::FirmwareUpgradeStarted { .. } => "FirmwareUpgradeStarted",
+            NetworkEvent::FirmwareUpgradeCompleted { .. } => "FirmwareUpgradeCompleted",
+            NetworkEvent::PortLinkChanged { .. } => "PortLinkChanged",
+            NetworkEvent::DeviceHealthDegraded { .. } => "DeviceHealthDegraded",
+            NetworkEvent::ConfigDriftDetected { .. } => "ConfigDriftDetected",
+            NetworkEvent::DeviceTagged { .. } => "DeviceTagged",
+            NetworkEvent::DeviceUntagged { .. } => "DeviceUntagged",
+            NetworkEvent::StackMemberAdded { .. } => "StackMemberAdded",
+            NetworkEvent::StackMemberRemoved { .. } => "StackMemberRemoved",
+            NetworkEvent::DeviceJoinedStack { .. } => "DeviceJoinedStack",
+            NetworkEvent::DeviceLeftStack { .. } => "DeviceLeftStack",
             NetworkEvent::ConnectionEstablished { .. } => "ConnectionEstablished",
             NetworkEvent::ConnectionRemoved { .. } => "ConnectionRemoved",
             NetworkEvent::ConnectionLinkChanged { .. } => "ConnectionLinkChanged",
+            NetworkEvent::ConnectionPlanned { .. } => "ConnectionPlanned",
+            NetworkEvent::ConnectionCabled { .. } => "ConnectionCabled",
+            NetworkEvent::ConnectionActivated { .. } => "ConnectionActivated",
+            NetworkEvent::ConnectionDegraded { .. } => "ConnectionDegraded",
             NetworkEvent::TopologyCreated { .. } => "TopologyCreated",
             NetworkEvent::DeviceAddedToTopology { .. } => "DeviceAddedToTopology",
             NetworkEvent::DeviceRemovedFromTopology { .. } => "DeviceRemovedFromTopology",
+            NetworkEvent::SiteCreated { .. } => "SiteCreated",
+            NetworkEvent::DeviceAssignedToSite { .. } => "DeviceAssignedToSite",
+            NetworkEvent::DeviceUnassignedFromSite { .. } => "DeviceUnassignedFromSite",
+            NetworkEvent::SitePrefixAllocated { .. } => "SitePrefixAllocated",
             NetworkEvent::DeviceSyncedToInventory { .. } => "DeviceSyncedToInventory",
+            NetworkEvent::InventorySyncQueued { .. } => "InventorySyncQueued",
+            NetworkEvent::InventorySyncDeadLettered { .. } => "InventorySyncDeadLettered",
             NetworkEvent::IpAddressAllocated { .. } => "IpAddressAllocated",
         }
     }
 
-    /// Get NATS subject for this event
-    /// Format: network.{aggregate_type}.{event_type}
-    pub fn nats_subject(&self) -> String {
-        self.nats_subject_with_prefix("network")
-    }
-
-    /// Get NATS subject with a custom prefix
-    /// Format: {prefix}.{aggregate_type}.{event_type}
-    pub fn nats_subject_with_prefix(&self, prefix: &str) -> String {
-        let aggregate_type = match self {
+    /// Get the aggregate type this event belongs to
+    pub fn aggregate_type(&self) -> &'static str {
+        match self {
             NetworkEvent::DeviceDiscovered { .. }
             | NetworkEvent::DeviceAdopting { .. }
             | NetworkEvent::DeviceProvisioned { .. }
@@ -202,21 +642,74 @@ impl NetworkEvent {
             | NetworkEvent::DeviceConfigured { .. }
             | NetworkEvent::DeviceError { .. }
             | NetworkEvent::DeviceDecommissioned { .. }
-            | NetworkEvent::DeviceRenamed { .. } => "device",
+            | NetworkEvent::DevicePurged { .. }
+            | NetworkEvent::DeviceRenamed { .. }
+            | NetworkEvent::DeviceWentMissing { .. }
+            | NetworkEvent::DeviceReappeared { .. }
+            | NetworkEvent::ConfigRolledBack { .. }
+            | NetworkEvent::FirmwareUpgradeStarted { .. }
+            | NetworkEvent::FirmwareUpgradeCompleted { .. }
+            | NetworkEvent::PortLinkChanged { .. }
+            | NetworkEvent::DeviceHealthDegraded { .. }
+            | NetworkEvent::ConfigDriftDetected { .. }
+            | NetworkEvent::DeviceTagged { .. }
+            | NetworkEvent::DeviceUntagged { .. }
+            | NetworkEvent::StackMemberAdded { .. }
+            | NetworkEvent::StackMemberRemoved { .. }
+            | NetworkEvent::DeviceJoinedStack { .. }
+            | NetworkEvent::DeviceLeftStack { .. } => "device",
 
             NetworkEvent::ConnectionEstablished { .. }
             | NetworkEvent::ConnectionRemoved { .. }
-            | NetworkEvent::ConnectionLinkChanged { .. } => "connection",
+            | NetworkEvent::ConnectionLinkChanged { .. }
+            | NetworkEvent::ConnectionPlanned { .. }
+            | NetworkEvent::ConnectionCabled { .. }
+            | NetworkEvent::ConnectionActivated { .. }
+            | NetworkEvent::ConnectionDegraded { .. } => "connection",
 
             NetworkEvent::TopologyCreated { .. }
             | NetworkEvent::DeviceAddedToTopology { .. }
             | NetworkEvent::DeviceRemovedFromTopology { .. } => "topology",
 
+            NetworkEvent::SiteCreated { .. }
+            | NetworkEvent::DeviceAssignedToSite { .. }
+            | NetworkEvent::DeviceUnassignedFromSite { .. }
+            | NetworkEvent::SitePrefixAllocated { .. } => "site",
+
             NetworkEvent::DeviceSyncedToInventory { .. }
+            | NetworkEvent::InventorySyncQueued { .. }
+            | NetworkEvent::InventorySyncDeadLettered { .. }
             | NetworkEvent::IpAddressAllocated { .. } => "inventory",
-        };
+        }
+    }
 
-        format!("{}.{}.{}", prefix, aggregate_type, self.event_type())
+    /// Get NATS subject for this event
+    /// Format: network.{aggregate_type}.{aggregate_id}.{event_type}
+    pub fn nats_subject(&self) -> String {
+        self.nats_subject_with_prefix("network", &self.aggregate_id())
+    }
+
+    /// Get NATS subject with a custom prefix
+    /// Format: {prefix}.{aggregate_type}.{aggregate_id}.{event_type}
+    ///
+    /// Embedding the aggregate id lets consumers filter a single aggregate's
+    /// history with `{prefix}.*.{aggregate_id}.>` instead of scanning the stream.
+    pub fn nats_subject_with_prefix(&self, prefix: &str, aggregate_id: &str) -> String {
+        format!(
+            "{}.{}.{}.{}",
+            prefix,
+            self.aggregate_type(),
+            aggregate_id,
+            self.event_type()
+        )
+    }
+
+    /// Get the legacy NATS subject (without aggregate id)
+    /// Format: {prefix}.{aggregate_type}.{event_type}
+    ///
+    /// Streams written before per-aggregate partitioning use this layout.
+    pub fn legacy_nats_subject_with_prefix(&self, prefix: &str) -> String {
+        format!("{}.{}.{}", prefix, self.aggregate_type(), self.event_type())
     }
 }
 
@@ -326,6 +819,28 @@ mod tests {
         assert_eq!(event.event_type(), "DeviceRenamed");
     }
 
+    #[test]
+    fn test_device_went_missing_event() {
+        let device_id = create_test_device_id();
+        let event = NetworkEvent::DeviceWentMissing {
+            device_id,
+            last_seen: Some(chrono::Utc::now()),
+        };
+
+        assert_eq!(event.event_type(), "DeviceWentMissing");
+        assert_eq!(event.aggregate_type(), "device");
+        assert_eq!(event.aggregate_id(), device_id.to_string());
+    }
+
+    #[test]
+    fn test_device_reappeared_event() {
+        let device_id = create_test_device_id();
+        let event = NetworkEvent::DeviceReappeared { device_id };
+
+        assert_eq!(event.event_type(), "DeviceReappeared");
+        assert_eq!(event.aggregate_type(), "device");
+    }
+
     // ==========================================================================
     // Connection Event Tests
     // ==========================================================================
@@ -369,6 +884,22 @@ mod tests {
         assert_eq!(event.event_type(), "ConnectionLinkChanged");
     }
 
+    #[test]
+    fn test_connection_lifecycle_events_share_aggregate() {
+        let connection_id = ConnectionId::new();
+        let events = [
+            NetworkEvent::ConnectionCabled { connection_id },
+            NetworkEvent::ConnectionActivated { connection_id, speed: Some(LinkSpeed::Gbps10) },
+            NetworkEvent::ConnectionDegraded { connection_id, reason: "CRC errors".to_string() },
+        ];
+
+        for event in &events {
+            assert_eq!(event.aggregate_type(), "connection");
+            assert_eq!(event.aggregate_id(), connection_id.to_string());
+        }
+        assert_eq!(events[2].event_type(), "ConnectionDegraded");
+    }
+
     // ==========================================================================
     // Topology Event Tests
     // ==========================================================================
@@ -421,6 +952,7 @@ mod tests {
             device_id,
             inventory_id: "nb-123".to_string(),
             system: "netbox".to_string(),
+            content_hash: None,
         };
 
         assert_eq!(event.event_type(), "DeviceSyncedToInventory");
@@ -455,20 +987,29 @@ mod tests {
             device_type: DeviceType::Switch,
             ip_address: None,
         };
-        assert_eq!(event.nats_subject(), "network.device.DeviceDiscovered");
+        assert_eq!(
+            event.nats_subject(),
+            format!("network.device.{}.DeviceDiscovered", device_id)
+        );
 
         let event = NetworkEvent::DeviceAdopting {
             device_id,
             vendor_id: "v1".to_string(),
         };
-        assert_eq!(event.nats_subject(), "network.device.DeviceAdopting");
+        assert_eq!(
+            event.nats_subject(),
+            format!("network.device.{}.DeviceAdopting", device_id)
+        );
     }
 
     #[test]
     fn test_nats_subject_connection_events() {
         let connection_id = ConnectionId::new();
         let event = NetworkEvent::ConnectionRemoved { connection_id };
-        assert_eq!(event.nats_subject(), "network.connection.ConnectionRemoved");
+        assert_eq!(
+            event.nats_subject(),
+            format!("network.connection.{}.ConnectionRemoved", connection_id)
+        );
     }
 
     #[test]
@@ -478,7 +1019,10 @@ mod tests {
             topology_id,
             name: "Test".to_string(),
         };
-        assert_eq!(event.nats_subject(), "network.topology.TopologyCreated");
+        assert_eq!(
+            event.nats_subject(),
+            format!("network.topology.{}.TopologyCreated", topology_id)
+        );
     }
 
     #[test]
@@ -488,15 +1032,32 @@ mod tests {
             device_id,
             inventory_id: "id".to_string(),
             system: "netbox".to_string(),
+            content_hash: None,
         };
-        assert_eq!(event.nats_subject(), "network.inventory.DeviceSyncedToInventory");
+        assert_eq!(
+            event.nats_subject(),
+            format!("network.inventory.{}.DeviceSyncedToInventory", device_id)
+        );
     }
 
     #[test]
     fn test_nats_subject_with_custom_prefix() {
         let device_id = create_test_device_id();
         let event = NetworkEvent::DeviceDecommissioned { device_id };
-        assert_eq!(event.nats_subject_with_prefix("cim"), "cim.device.DeviceDecommissioned");
+        assert_eq!(
+            event.nats_subject_with_prefix("cim", &device_id.to_string()),
+            format!("cim.device.{}.DeviceDecommissioned", device_id)
+        );
+    }
+
+    #[test]
+    fn test_legacy_nats_subject() {
+        let device_id = create_test_device_id();
+        let event = NetworkEvent::DeviceDecommissioned { device_id };
+        assert_eq!(
+            event.legacy_nats_subject_with_prefix("network"),
+            "network.device.DeviceDecommissioned"
+        );
     }
 
     // ==========================================================================
@@ -545,4 +1106,123 @@ mod tests {
             panic!("Expected ConnectionLinkChanged");
         }
     }
+
+    // ==========================================================================
+    // Schema Migration Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let device_id = create_test_device_id();
+        let event = NetworkEvent::DeviceRenamed {
+            device_id,
+            old_name: "sw-1".to_string(),
+            new_name: "core-1".to_string(),
+        };
+
+        let payload = event.encode().unwrap();
+        let raw: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(raw["schema_version"], EVENT_SCHEMA_VERSION);
+
+        match NetworkEvent::decode(&payload).unwrap() {
+            NetworkEvent::DeviceRenamed { device_id: id, new_name, .. } => {
+                assert_eq!(id, device_id);
+                assert_eq!(new_name, "core-1");
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_migrate_v1_payload_missing_later_field() {
+        let device_id = create_test_device_id();
+        // Written before the envelope existed and before VLANs had access ports
+        let v1 = format!(
+            r#"{{"DeviceConfigured":{{"device_id":"{}","interfaces":[],"vlans":[{{"id":10,"name":"users","native":false}}]}}}}"#,
+            device_id.inner()
+        );
+
+        match NetworkEvent::decode(v1.as_bytes()).unwrap() {
+            NetworkEvent::DeviceConfigured { device_id: id, vlans, .. } => {
+                assert_eq!(id, device_id);
+                assert_eq!(vlans, vec![VlanConfig::new(10, "users").unwrap()]);
+                assert!(vlans[0].access_ports.is_empty());
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_migrate_v1_went_missing_without_last_seen() {
+        let device_id = create_test_device_id();
+        let v1 = serde_json::json!({ "DeviceWentMissing": { "device_id": device_id } });
+
+        let event = migrate(v1).unwrap();
+        assert!(matches!(
+            event,
+            NetworkEvent::DeviceWentMissing { device_id: id, last_seen: None } if id == device_id
+        ));
+    }
+
+    #[test]
+    fn test_migrate_v2_inventory_sync_without_hash() {
+        let device_id = create_test_device_id();
+        let v2 = serde_json::json!({
+            "schema_version": 2,
+            "event": { "DeviceSyncedToInventory": { "device_id": device_id, "inventory_id": "nb-1", "system": "netbox" } },
+        });
+
+        let event = migrate(v2).unwrap();
+        assert!(matches!(
+            event,
+            NetworkEvent::DeviceSyncedToInventory { content_hash: None, .. }
+        ));
+    }
+
+    #[test]
+    fn test_migrate_rejects_unknown_versions() {
+        let event = serde_json::to_value(NetworkEvent::DeviceReappeared {
+            device_id: create_test_device_id(),
+        }).unwrap();
+
+        let future = serde_json::json!({ "schema_version": EVENT_SCHEMA_VERSION + 1, "event": event });
+        assert!(matches!(migrate(future), Err(EventMigrationError::UnsupportedVersion(v)) if v == EVENT_SCHEMA_VERSION + 1));
+
+        let no_event = serde_json::json!({ "schema_version": EVENT_SCHEMA_VERSION });
+        assert!(matches!(migrate(no_event), Err(EventMigrationError::Malformed(_))));
+
+        assert!(matches!(NetworkEvent::decode(b"not json"), Err(EventMigrationError::Malformed(_))));
+    }
+
+    // ==========================================================================
+    // Envelope Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_envelope_caused_by_chains_ids() {
+        let device_id = create_test_device_id();
+        let root = EventEnvelope::new(NetworkEvent::DeviceAdopting {
+            device_id,
+            vendor_id: "switch-1".to_string(),
+        });
+        let child = EventEnvelope::new(NetworkEvent::DeviceRenamed {
+            device_id,
+            old_name: "switch-1".to_string(),
+            new_name: "core-sw".to_string(),
+        })
+        .caused_by(&root);
+        let grandchild = EventEnvelope::new(NetworkEvent::DeviceReappeared { device_id }).caused_by(&child);
+
+        assert_ne!(root.event_id, child.event_id);
+        // The root starts the correlation and every descendant joins it
+        assert_eq!(child.correlation_id.as_deref(), Some(root.event_id.as_str()));
+        assert_eq!(child.causation_id.as_deref(), Some(root.event_id.as_str()));
+        assert_eq!(grandchild.correlation_id.as_deref(), Some(root.event_id.as_str()));
+        assert_eq!(grandchild.causation_id.as_deref(), Some(child.event_id.as_str()));
+
+        let correlated = EventEnvelope::new(NetworkEvent::DeviceReappeared { device_id })
+            .with_correlation_id("op-1");
+        let effect = EventEnvelope::new(NetworkEvent::DeviceReappeared { device_id }).caused_by(&correlated);
+        assert_eq!(effect.correlation_id.as_deref(), Some("op-1"));
+    }
 }
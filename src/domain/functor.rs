@@ -102,7 +102,7 @@ pub enum NetworkEdgeType {
 /// A domain object (target category object)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DomainObject {
-    Device(NetworkDeviceAggregate),
+    Device(Box<NetworkDeviceAggregate>),
     Connection(ConnectionInfo),
     Topology(TopologyInfo),
 }
@@ -127,13 +127,40 @@ pub struct TopologyInfo {
 }
 
 /// A domain morphism (relationship between domain objects)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DomainMorphism {
     pub source: String,
     pub target: String,
     pub morphism_type: DomainMorphismType,
 }
 
+impl DomainMorphism {
+    /// Morphism of `morphism_type` from `source` to `target`
+    pub fn new(source: impl Into<String>, target: impl Into<String>, morphism_type: DomainMorphismType) -> Self {
+        Self {
+            source: source.into(),
+            target: target.into(),
+            morphism_type,
+        }
+    }
+
+    /// The composite `next ∘ self`, if `next` starts where `self` ends
+    ///
+    /// Two relationships of one type compose to that type; a path mixing
+    /// types only implies a dependency, so it composes to `DependsOn`.
+    pub fn then(&self, next: &DomainMorphism) -> Option<DomainMorphism> {
+        if self.target != next.source {
+            return None;
+        }
+        let morphism_type = if self.morphism_type == next.morphism_type {
+            self.morphism_type.clone()
+        } else {
+            DomainMorphismType::DependsOn
+        };
+        Some(DomainMorphism::new(self.source.clone(), next.target.clone(), morphism_type))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DomainMorphismType {
     Contains,
@@ -223,6 +250,7 @@ impl NetworkFunctor {
                         "gateway" => DeviceType::Gateway,
                         "switch" => DeviceType::Switch,
                         "access_point" => DeviceType::AccessPoint,
+                        "firewall" => DeviceType::Firewall,
                         other => DeviceType::Generic { model: other.to_string() },
                     })
                     .unwrap_or(DeviceType::Generic { model: "unknown".to_string() });
@@ -238,7 +266,7 @@ impl NetworkFunctor {
                     ip_address,
                 );
 
-                DomainObject::Device(aggregate)
+                DomainObject::Device(Box::new(aggregate))
             }
             NetworkNodeType::Port | NetworkNodeType::Vlan | NetworkNodeType::Network => {
                 // These map to properties/relationships, not standalone aggregates
@@ -392,6 +420,60 @@ impl NetworkKanExtension {
     pub fn base_functor(&self) -> &NetworkFunctor {
         &self.base_functor
     }
+
+    /// Check that an extension preserves composition over `category`
+    ///
+    /// For every composable pair `f`, `g` in `category`, the extension
+    /// registered as `name` (vendor first, then inventory) must satisfy
+    /// `extend(g ∘ f) == extend(g) ∘ extend(f)`. Returns the pairs where it
+    /// does not; adapters can run this in their tests over a few morphisms
+    /// they care about.
+    pub fn verify_composition(
+        &self,
+        name: &str,
+        category: &[DomainMorphism],
+    ) -> Result<Vec<CompositionViolation>, FunctorError> {
+        let extend = |morphism: &DomainMorphism| match self.vendor_extensions.get(name) {
+            Some(extension) => extension.extend_morphism(morphism),
+            None => match self.inventory_extensions.get(name) {
+                Some(extension) => extension.extend_morphism(morphism),
+                None => Err(FunctorError::UnknownVendor(name.to_string())),
+            },
+        };
+
+        let mut violations = Vec::new();
+        for f in category {
+            for g in category {
+                let Some(composite) = f.then(g) else {
+                    continue;
+                };
+                let extended_composite = extend(&composite)?;
+                let composed_extensions = extend(f)?.then(&extend(g)?);
+                if composed_extensions.as_ref() != Some(&extended_composite) {
+                    violations.push(CompositionViolation {
+                        f: f.clone(),
+                        g: g.clone(),
+                        extended_composite,
+                        composed_extensions,
+                    });
+                }
+            }
+        }
+        Ok(violations)
+    }
+}
+
+/// A composable pair an extension does not preserve composition for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompositionViolation {
+    /// First morphism of the pair
+    pub f: DomainMorphism,
+    /// Second morphism, starting where `f` ends
+    pub g: DomainMorphism,
+    /// `extend(g ∘ f)`
+    pub extended_composite: DomainMorphism,
+    /// `extend(g) ∘ extend(f)`, `None` if the extended morphisms do not compose
+    pub composed_extensions: Option<DomainMorphism>,
 }
 
 // ============================================================================
@@ -406,6 +488,13 @@ pub trait VendorExtension: Send + Sync {
     /// Extend domain object to vendor representation
     fn extend(&self, domain_obj: &DomainObject) -> Result<VendorRepresentation, FunctorError>;
 
+    /// Extend a domain morphism to the vendor's relationship
+    ///
+    /// Defaults to the identity, which preserves composition.
+    fn extend_morphism(&self, morphism: &DomainMorphism) -> Result<DomainMorphism, FunctorError> {
+        Ok(morphism.clone())
+    }
+
     /// Reverse mapping: vendor → domain events
     fn to_domain_event(&self, vendor_event: &serde_json::Value) -> Result<NetworkEvent, FunctorError>;
 }
@@ -417,6 +506,13 @@ pub trait InventoryExtension: Send + Sync {
 
     /// Extend domain object to inventory representation
     fn extend(&self, domain_obj: &DomainObject) -> Result<InventoryRepresentation, FunctorError>;
+
+    /// Extend a domain morphism to the inventory's relationship
+    ///
+    /// Defaults to the identity, which preserves composition.
+    fn extend_morphism(&self, morphism: &DomainMorphism) -> Result<DomainMorphism, FunctorError> {
+        Ok(morphism.clone())
+    }
 }
 
 // ============================================================================
@@ -440,6 +536,108 @@ pub enum FunctorError {
     #[error("Mapping failed: {0}")]
     MappingFailed(String),
 
+    /// A vendor event refers to a MAC with no registered domain device
+    #[error("No device registered for MAC {0}")]
+    UnknownDevice(MacAddress),
+
     #[error("Composition verification failed")]
     CompositionFailed,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ==========================================================================
+    // Composition Tests
+    // ==========================================================================
+
+    /// Vendor extension relabelling relationship types through `relabel`
+    struct RelabellingVendor {
+        relabel: fn(&DomainMorphismType) -> DomainMorphismType,
+    }
+
+    impl VendorExtension for RelabellingVendor {
+        fn vendor_name(&self) -> &str {
+            "mock"
+        }
+
+        fn extend(&self, _domain_obj: &DomainObject) -> Result<VendorRepresentation, FunctorError> {
+            Err(FunctorError::MappingFailed("objects are not under test".to_string()))
+        }
+
+        fn to_domain_event(&self, _vendor_event: &serde_json::Value) -> Result<NetworkEvent, FunctorError> {
+            Err(FunctorError::MappingFailed("events are not under test".to_string()))
+        }
+
+        fn extend_morphism(&self, morphism: &DomainMorphism) -> Result<DomainMorphism, FunctorError> {
+            Ok(DomainMorphism {
+                morphism_type: (self.relabel)(&morphism.morphism_type),
+                ..morphism.clone()
+            })
+        }
+    }
+
+    /// Uplink chain: gateway ─ConnectedTo→ core ─DependsOn→ access ─DependsOn→ ap
+    fn uplink_chain() -> Vec<DomainMorphism> {
+        vec![
+            DomainMorphism::new("gateway", "core", DomainMorphismType::ConnectedTo),
+            DomainMorphism::new("core", "access", DomainMorphismType::DependsOn),
+            DomainMorphism::new("access", "ap", DomainMorphismType::DependsOn),
+        ]
+    }
+
+    fn kan_with(relabel: fn(&DomainMorphismType) -> DomainMorphismType) -> NetworkKanExtension {
+        let mut kan = NetworkKanExtension::new(NetworkFunctor::new());
+        kan.register_vendor("mock", Box::new(RelabellingVendor { relabel }));
+        kan
+    }
+
+    #[test]
+    fn test_morphisms_compose_end_to_start() {
+        let chain = uplink_chain();
+        assert_eq!(chain[1].then(&chain[0]), None);
+        assert_eq!(
+            chain[1].then(&chain[2]),
+            Some(DomainMorphism::new("core", "ap", DomainMorphismType::DependsOn))
+        );
+        assert_eq!(
+            chain[0].then(&chain[1]),
+            Some(DomainMorphism::new("gateway", "access", DomainMorphismType::DependsOn))
+        );
+    }
+
+    #[test]
+    fn test_identity_extension_preserves_composition() {
+        let kan = kan_with(|morphism_type| morphism_type.clone());
+        assert_eq!(kan.verify_composition("mock", &uplink_chain()).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_non_compositional_extension_is_flagged() {
+        // Connections become containment, so a connection followed by a
+        // dependency no longer composes to what the composite extends to
+        let kan = kan_with(|morphism_type| match morphism_type {
+            DomainMorphismType::ConnectedTo => DomainMorphismType::Contains,
+            DomainMorphismType::DependsOn => DomainMorphismType::ManagedBy,
+            other => other.clone(),
+        });
+        let chain = uplink_chain();
+
+        let violations = kan.verify_composition("mock", &chain).unwrap();
+        assert_eq!(
+            violations,
+            vec![CompositionViolation {
+                f: chain[0].clone(),
+                g: chain[1].clone(),
+                extended_composite: DomainMorphism::new("gateway", "access", DomainMorphismType::ManagedBy),
+                composed_extensions: Some(DomainMorphism::new("gateway", "access", DomainMorphismType::DependsOn)),
+            }]
+        );
+
+        assert!(matches!(
+            kan.verify_composition("unregistered", &chain),
+            Err(FunctorError::UnknownVendor(name)) if name == "unregistered"
+        ));
+    }
+}
@@ -137,6 +137,8 @@ impl InfrastructureBridge for NetworkDeviceAggregate {
             DeviceType::Gateway => OperatingSystem::Linux,
             DeviceType::Switch => OperatingSystem::Linux,
             DeviceType::AccessPoint => OperatingSystem::Linux,
+            // FortiOS, PAN-OS and BSD-based firewalls alike
+            DeviceType::Firewall => OperatingSystem::Unknown,
             DeviceType::Generic { .. } => OperatingSystem::Unknown,
         };
 
@@ -144,6 +146,7 @@ impl InfrastructureBridge for NetworkDeviceAggregate {
             DeviceType::Gateway => vec![ManagementProtocol::RestApi, ManagementProtocol::Snmp],
             DeviceType::Switch => vec![ManagementProtocol::Snmp, ManagementProtocol::RestApi],
             DeviceType::AccessPoint => vec![ManagementProtocol::RestApi],
+            DeviceType::Firewall => vec![ManagementProtocol::RestApi, ManagementProtocol::Snmp],
             DeviceType::Generic { .. } => vec![ManagementProtocol::Snmp],
         };
 
@@ -165,6 +168,7 @@ pub fn device_type_to_compute_type(device_type: &DeviceType) -> ComputeType {
         DeviceType::Gateway => ComputeType::Physical,
         DeviceType::Switch => ComputeType::Physical,
         DeviceType::AccessPoint => ComputeType::Physical,
+        DeviceType::Firewall => ComputeType::Physical,
         DeviceType::Generic { .. } => ComputeType::Physical,
     }
 }
@@ -212,6 +216,7 @@ fn parse_device_type(s: &str) -> DeviceType {
         "Gateway" => DeviceType::Gateway,
         "Switch" => DeviceType::Switch,
         "AccessPoint" => DeviceType::AccessPoint,
+        "Firewall" => DeviceType::Firewall,
         other => {
             if other.starts_with("Generic(") && other.ends_with(')') {
                 let model = &other[8..other.len()-1];
@@ -305,6 +310,7 @@ mod tests {
         assert_eq!(parse_device_type("Gateway"), DeviceType::Gateway);
         assert_eq!(parse_device_type("Switch"), DeviceType::Switch);
         assert_eq!(parse_device_type("AccessPoint"), DeviceType::AccessPoint);
+        assert_eq!(parse_device_type("Firewall"), DeviceType::Firewall);
         assert_eq!(
             parse_device_type("Generic(Custom)"),
             DeviceType::Generic { model: "Custom".to_string() }
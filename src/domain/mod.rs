@@ -61,39 +61,53 @@
 pub mod aggregates;
 pub mod events;
 pub mod commands;
+pub mod command_handler;
 pub mod ports;
 pub mod functor;
 pub mod value_objects;
 pub mod infrastructure_bridge;
+pub mod schemas;
+pub mod projections;
+pub mod clock;
+pub mod config_template;
 
 // Re-exports - explicit to avoid ambiguity
 pub use aggregates::{
     NetworkDeviceAggregate, DeviceState, AggregateError,
+    NetworkConnectionAggregate, ConnectionState, SiteAggregate, InventorySyncFailure,
+};
+pub use events::{
+    migrate, EventEnvelope, EventMigrationError, NetworkEvent, VersionedEvent, EVENT_SCHEMA_VERSION,
 };
-pub use events::NetworkEvent;
 pub use commands::NetworkCommand;
+pub use command_handler::{AggregateRepository, CommandHandler, MemoryRepository};
 pub use ports::{
     DeviceControlPort, InventoryPort, DiscoveryPort,
     NetworkManagementPort, EventStorePort, PortError,
     DeviceConfiguration, DiscoveredDevice, DeviceDetails,
-    VendorDevice, VendorConfig, DeviceStats, PortStats,
-    IpAssignment, IpStatus, EventSubscription,
-    ConnectionInfo,
+    VendorDevice, DeviceConfigRequest, VendorConfig, DeviceStats, PortStats, HealthStatus,
+    IpAssignment, IpStatus, EventSubscription, EventSource, EventAck,
+    ConnectionInfo, AggregateSnapshot, EventStream, TimestampedEvent, RecordedEvent,
 };
 pub use functor::{
     NetworkFunctor, NetworkKanExtension, VendorExtension, InventoryExtension,
     NetworkGraphNode, NetworkGraphEdge, NetworkNodeType, NetworkEdgeType,
-    DomainObject, DomainMorphism, DomainMorphismType,
+    DomainObject, DomainMorphism, DomainMorphismType, CompositionViolation,
     ExtendedRepresentation, VendorRepresentation, InventoryRepresentation,
     FunctorError,
     // Note: functor also has ConnectionInfo and TopologyInfo as domain objects
     TopologyInfo,
 };
 pub use value_objects::{
-    DeviceId, TopologyId, ConnectionId, MacAddress, MacAddressError,
-    DeviceType, PortId, InterfaceConfig, VlanConfig, VlanError,
-    ConnectionType, LinkSpeed,
+    DeviceId, TopologyId, ConnectionId, SiteId, MacAddress, MacAddressError, MacPrefix,
+    DeviceType, PortId, PortName, PortRange, PortNameError, InterfaceConfig, InterfaceError, ConfigDiff, VlanConfig, VlanError, VlanConflict,
+    LagConfig, LagConflict, LagHashPolicy, LacpMode,
+    ConnectionType, LinkSpeed, LinkSpeedError, PortCapabilities, PortMedia, HealthMetric, IpNetwork, IpNetworkError, IpAllocator, IpAllocationError,
 };
+pub use schemas::{export_event_schemas, write_schemas};
+pub use projections::{DeviceCountByState, Projection};
+pub use clock::{Clock, MockClock, SystemClock};
+pub use config_template::{ConfigTemplate, ConfigurationGenerator, TemplateError, TemplateVariables};
 pub use infrastructure_bridge::{
     InfrastructureBridge, BridgeError,
     device_type_to_compute_type, compute_type_to_device_type,
@@ -30,11 +30,14 @@
 //! - Universal property ensures all adapters compose correctly
 
 use async_trait::async_trait;
+use futures::Stream;
 use std::collections::HashMap;
+use std::pin::Pin;
 
 use super::value_objects::*;
 use super::aggregates::*;
 use super::events::*;
+use super::projections::Projection;
 
 /// Error type for port operations
 #[derive(Debug, thiserror::Error)]
@@ -42,6 +45,10 @@ pub enum PortError {
     #[error("Device not found: {0}")]
     DeviceNotFound(DeviceId),
 
+    /// No connection with this ID is known
+    #[error("Connection not found: {0}")]
+    ConnectionNotFound(ConnectionId),
+
     #[error("Connection failed: {0}")]
     ConnectionFailed(String),
 
@@ -59,6 +66,48 @@ pub enum PortError {
 
     #[error("Inventory error: {0}")]
     InventoryError(String),
+
+    #[error("Event deserialization failed: {0}")]
+    Deserialization(String),
+
+    /// The remote system has no such resource
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    /// The remote system is throttling requests
+    #[error("Rate limited{}", retry_after.map(|d| format!(", retry after {:?}", d)).unwrap_or_default())]
+    RateLimited {
+        /// How long the remote system asked callers to wait, if it said
+        retry_after: Option<std::time::Duration>,
+    },
+
+    /// A failure expected to clear up on its own, such as a `503`
+    #[error("Temporarily unavailable: {0}")]
+    Transient(String),
+
+    #[error(transparent)]
+    Aggregate(#[from] AggregateError),
+}
+
+impl PortError {
+    /// Whether the same operation may succeed if retried later
+    ///
+    /// Timeouts, throttling, lost connections and `Transient` failures are
+    /// transient; every other error is permanent until something changes.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::Timeout(_) | Self::ConnectionFailed(_) | Self::RateLimited { .. } | Self::Transient(_)
+        )
+    }
+
+    /// Delay the remote system asked for before a retry, if any
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            Self::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
 }
 
 // ============================================================================
@@ -141,20 +190,124 @@ pub trait DeviceControlPort: Send + Sync {
     /// List all devices from the vendor controller
     async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError>;
 
+    /// List devices that changed since `cursor`
+    ///
+    /// `cursor` is the value returned by the previous call, or `None` for a
+    /// full listing; the returned cursor is handed to the next call. Adapters
+    /// without change tracking keep the default, which lists every device and
+    /// returns no cursor.
+    async fn list_devices_since(
+        &self,
+        cursor: Option<String>,
+    ) -> Result<(Vec<VendorDevice>, Option<String>), PortError> {
+        let _ = cursor;
+        Ok((self.list_devices().await?, None))
+    }
+
     /// Get device by ID
     async fn get_device(&self, vendor_id: &str) -> Result<VendorDevice, PortError>;
 
     /// Adopt a device
     async fn adopt_device(&self, vendor_id: &str) -> Result<(), PortError>;
 
-    /// Apply configuration to a device
-    async fn apply_config(&self, vendor_id: &str, config: VendorConfig) -> Result<(), PortError>;
+    /// Apply vendor-agnostic configuration to a device
+    ///
+    /// Each adapter translates the request into its own vendor payload.
+    /// Settings a vendor cannot express are rejected with `NotSupported`
+    /// before anything is sent.
+    async fn apply_config(&self, vendor_id: &str, config: DeviceConfigRequest) -> Result<(), PortError>;
+
+    /// Apply a raw vendor payload to a device
+    ///
+    /// Escape hatch for settings `DeviceConfigRequest` does not model; the
+    /// caller must know the vendor's payload shape.
+    async fn apply_raw_config(&self, vendor_id: &str, config: VendorConfig) -> Result<(), PortError>;
+
+    /// Upgrade a device's firmware to `target_version`
+    ///
+    /// Returns once the vendor has accepted the upgrade. Vendors without
+    /// remote firmware management keep the default, which reports
+    /// `NotSupported`.
+    async fn upgrade_firmware(&self, vendor_id: &str, target_version: &str) -> Result<(), PortError> {
+        let _ = (vendor_id, target_version);
+        Err(PortError::NotSupported(format!("Firmware upgrades are not supported by {}", self.vendor_name())))
+    }
+
+    /// Capture a device's current configuration for `restore_config`
+    ///
+    /// Adapters without configuration rollback keep the default, which
+    /// reports `NotSupported`.
+    async fn snapshot_config(&self, vendor_id: &str) -> Result<VendorConfig, PortError> {
+        let _ = vendor_id;
+        Err(PortError::NotSupported(format!("Configuration snapshots are not supported by {}", self.vendor_name())))
+    }
+
+    /// Put back a configuration captured by `snapshot_config`
+    async fn restore_config(&self, vendor_id: &str, snapshot: VendorConfig) -> Result<(), PortError> {
+        let _ = (vendor_id, snapshot);
+        Err(PortError::NotSupported(format!("Configuration rollback is not supported by {}", self.vendor_name())))
+    }
+
+    /// Read back the device's current settings, for drift detection
+    ///
+    /// Reports what `apply_config` would have set, as far as the vendor
+    /// exposes it. Adapters that cannot read settings back keep the default,
+    /// which reports `NotSupported`.
+    async fn get_running_config(&self, vendor_id: &str) -> Result<DeviceConfigRequest, PortError> {
+        let _ = vendor_id;
+        Err(PortError::NotSupported(format!("Reading running configuration is not supported by {}", self.vendor_name())))
+    }
+
+    /// Confirm the device still answers after a configuration change
+    ///
+    /// The default looks the device up through the vendor controller.
+    async fn check_reachable(&self, vendor_id: &str) -> Result<(), PortError> {
+        self.get_device(vendor_id).await.map(|_| ())
+    }
+
+    /// Apply configuration, rolling back if it fails part way
+    ///
+    /// Snapshots the device, applies `config` and checks the device is still
+    /// reachable. If applying or the check fails, the snapshot is restored
+    /// and `RolledBack` is returned. Failing to take the snapshot aborts
+    /// before anything is sent; failing to restore it is an error, as the
+    /// device may be left half-configured.
+    async fn apply_config_transactional(
+        &self,
+        vendor_id: &str,
+        config: DeviceConfigRequest,
+    ) -> Result<ConfigApplyOutcome, PortError> {
+        let snapshot = self.snapshot_config(vendor_id).await?;
+
+        let failure = match self.apply_config(vendor_id, config).await {
+            Ok(()) => self.check_reachable(vendor_id).await.err(),
+            Err(e) => Some(e),
+        };
+        let Some(failure) = failure else {
+            return Ok(ConfigApplyOutcome::Applied);
+        };
+
+        match self.restore_config(vendor_id, snapshot).await {
+            Ok(()) => Ok(ConfigApplyOutcome::RolledBack { reason: failure.to_string() }),
+            Err(e) => Err(PortError::VendorError(format!("{}; rollback failed: {}", failure, e))),
+        }
+    }
 
     /// Restart a device
     async fn restart_device(&self, vendor_id: &str) -> Result<(), PortError>;
 
     /// Get device statistics
     async fn get_device_stats(&self, vendor_id: &str) -> Result<DeviceStats, PortError>;
+
+    /// Probe whether the vendor controller is reachable
+    ///
+    /// Meant to be cheap: it must not list devices. An unreachable controller
+    /// is reported as `Ok` with `reachable: false`; errors are reserved for
+    /// answers that cannot be interpreted. Adapters without a probe keep the
+    /// default, which reports `NotSupported`.
+    async fn health_check(&self) -> Result<HealthStatus, PortError> {
+        Err(PortError::NotSupported(format!("Health checks are not supported by {}", self.vendor_name())))
+    }
 }
 
 /// Inventory/DCIM operations (driven port)
@@ -178,24 +331,221 @@ pub trait InventoryPort: Send + Sync {
         connection: &ConnectionInfo,
     ) -> Result<(), PortError>;
 
+    /// Remove a connection's cable from inventory
+    async fn remove_connection(&self, connection_id: ConnectionId) -> Result<(), PortError> {
+        Err(PortError::NotSupported(format!(
+            "Removing connection {} is not supported by {}",
+            connection_id,
+            self.system_name()
+        )))
+    }
+
     /// Get IP address assignments
     async fn get_ip_assignments(&self, prefix: &str) -> Result<Vec<IpAssignment>, PortError>;
 
     /// Allocate IP address
     async fn allocate_ip(&self, prefix: &str, device_id: DeviceId) -> Result<IpAssignment, PortError>;
+
+    /// Record a device's interface addresses in inventory IPAM
+    ///
+    /// Each configured interface address is created, or updated to point at
+    /// that interface, and addresses inventory still holds for the device
+    /// but the device no longer has are released. The device must already
+    /// have been synced with `sync_device`.
+    async fn sync_ip_assignments(&self, device: &NetworkDeviceAggregate) -> Result<(), PortError> {
+        Err(PortError::NotSupported(format!(
+            "Syncing IP assignments of device {} is not supported by {}",
+            device.id(),
+            self.system_name()
+        )))
+    }
+
+    /// Sync a site and its membership to inventory
+    ///
+    /// Creates the site if inventory does not have it, and moves member
+    /// devices already synced with `sync_device` into it. Devices synced
+    /// afterwards are placed in the site they were last synced with.
+    async fn sync_site(&self, site: &SiteAggregate) -> Result<(), PortError> {
+        Err(PortError::NotSupported(format!(
+            "Syncing site {} is not supported by {}",
+            site.id(),
+            self.system_name()
+        )))
+    }
+
+    /// Probe whether the inventory system is reachable
+    ///
+    /// Same contract as [`DeviceControlPort::health_check`].
+    async fn health_check(&self) -> Result<HealthStatus, PortError> {
+        Err(PortError::NotSupported(format!("Health checks are not supported by {}", self.system_name())))
+    }
 }
 
+/// Stream of an aggregate's events, oldest first
+///
+/// Undecodable events are yielded as `PortError::Deserialization` items so a
+/// single bad event does not end the stream.
+pub type EventStream = Pin<Box<dyn Stream<Item = Result<NetworkEvent, PortError>> + Send>>;
+
 /// Event store operations (driven port)
 #[async_trait]
 pub trait EventStorePort: Send + Sync {
     /// Append events to the store
     async fn append(&self, events: Vec<NetworkEvent>) -> Result<(), PortError>;
 
+    /// Append events together with their correlation and causation ids
+    ///
+    /// Stores that keep message metadata should record the ids given and
+    /// only generate a correlation id for envelopes without one. The default
+    /// drops the ids and appends the bare events.
+    async fn append_envelopes(&self, envelopes: Vec<EventEnvelope>) -> Result<(), PortError> {
+        self.append(envelopes.into_iter().map(|e| e.event).collect()).await
+    }
+
+    /// Append events for one aggregate, failing if its version is not `expected_version`
+    ///
+    /// `expected_version` is the number of events the caller last saw for the
+    /// aggregate. On mismatch this returns
+    /// `PortError::Aggregate(AggregateError::ConcurrencyConflict { .. })`.
+    ///
+    /// The default implementation checks and appends in two steps and is only
+    /// safe with a single writer; stores with conditional writes should override it.
+    async fn append_expected(
+        &self,
+        aggregate_id: &str,
+        expected_version: u64,
+        events: Vec<NetworkEvent>,
+    ) -> Result<(), PortError> {
+        let actual = self.load_events(aggregate_id).await?.len() as u64;
+        if actual != expected_version {
+            return Err(AggregateError::ConcurrencyConflict { expected: expected_version, actual }.into());
+        }
+        self.append(events).await
+    }
+
     /// Load events for an aggregate
     async fn load_events(&self, aggregate_id: &str) -> Result<Vec<NetworkEvent>, PortError>;
 
+    /// Stream events for an aggregate without materializing its history
+    ///
+    /// The default implementation wraps `load_events` and therefore still
+    /// buffers everything; stores that can read incrementally should override it.
+    async fn load_events_stream(&self, aggregate_id: &str) -> Result<EventStream, PortError> {
+        let events = self.load_events(aggregate_id).await?;
+        Ok(Box::pin(futures::stream::iter(events.into_iter().map(Ok))))
+    }
+
     /// Subscribe to events
     async fn subscribe(&self, subject: &str) -> Result<EventSubscription, PortError>;
+
+    /// Stream events on `subject` as they are appended, starting now
+    ///
+    /// Unlike `subscribe`, nothing is tracked on the store's side: every
+    /// stream sees every new event, and events appended before the call
+    /// are not replayed.
+    async fn subscribe_stream(&self, subject: &str) -> Result<EventStream, PortError> {
+        let _ = subject;
+        Err(PortError::NotSupported("Live event streams are not supported by this event store".to_string()))
+    }
+
+    /// IDs of every aggregate of `aggregate_type` (e.g. `"device"`) with stored events
+    ///
+    /// Used to rebuild in-memory state after a restart. IDs are returned
+    /// sorted and without duplicates.
+    async fn list_aggregate_ids(&self, aggregate_type: &str) -> Result<Vec<String>, PortError> {
+        let _ = aggregate_type;
+        Err(PortError::NotSupported("Listing aggregates is not supported by this event store".to_string()))
+    }
+
+    /// Load events for an aggregate recorded after `version`
+    ///
+    /// `version` counts events, so `load_events_after(id, 0)` is equivalent to
+    /// `load_events(id)`. Stores that can seek should override this to avoid
+    /// reading the skipped events at all.
+    async fn load_events_after(&self, aggregate_id: &str, version: u64) -> Result<Vec<NetworkEvent>, PortError> {
+        let events = self.load_events(aggregate_id).await?;
+        Ok(events.into_iter().skip(version as usize).collect())
+    }
+
+    /// Load events for an aggregate with the time each was recorded
+    ///
+    /// Used for point-in-time replay. Events are returned oldest first; the
+    /// default reports `NotSupported` since not every store keeps a timestamp
+    /// per event.
+    async fn load_timestamped_events(&self, aggregate_id: &str) -> Result<Vec<TimestampedEvent>, PortError> {
+        let _ = aggregate_id;
+        Err(PortError::NotSupported("Timestamped replay is not supported by this event store".to_string()))
+    }
+
+    /// Load events for an aggregate with the metadata recorded alongside them
+    ///
+    /// Events are ordered by their position in the store, oldest first. The
+    /// default reports `NotSupported` since not every store keeps ids and
+    /// timestamps per event.
+    async fn load_recorded_events(&self, aggregate_id: &str) -> Result<Vec<RecordedEvent>, PortError> {
+        let _ = aggregate_id;
+        Err(PortError::NotSupported("Recorded event metadata is not kept by this event store".to_string()))
+    }
+
+    /// Save a snapshot of an aggregate's state at `version`
+    async fn save_snapshot(&self, aggregate_id: &str, version: u64, data: Vec<u8>) -> Result<(), PortError> {
+        let _ = (aggregate_id, version, data);
+        Err(PortError::NotSupported("Snapshots are not supported by this event store".to_string()))
+    }
+
+    /// Load the most recent snapshot of an aggregate, if any
+    async fn load_latest_snapshot(&self, aggregate_id: &str) -> Result<Option<AggregateSnapshot>, PortError> {
+        let _ = aggregate_id;
+        Ok(None)
+    }
+
+    /// Delete an aggregate's events and snapshot for good
+    ///
+    /// Afterwards the aggregate loads with no events, as if it never existed.
+    /// Purging an aggregate with no events succeeds.
+    async fn purge_aggregate(&self, aggregate_id: &str) -> Result<(), PortError> {
+        let _ = aggregate_id;
+        Err(PortError::NotSupported("Purging aggregates is not supported by this event store".to_string()))
+    }
+
+    /// Record a tombstone for a purged aggregate
+    ///
+    /// Tombstones are kept apart from aggregate histories, so they survive
+    /// the purge they record and are never replayed.
+    async fn append_tombstone(&self, event: NetworkEvent) -> Result<(), PortError> {
+        let _ = event;
+        Err(PortError::NotSupported("Tombstones are not supported by this event store".to_string()))
+    }
+
+    /// Stream every stored event through `projection` exactly once
+    ///
+    /// Events of one aggregate always arrive in the order they were recorded.
+    /// The default folds one aggregate at a time using `list_aggregate_ids`,
+    /// so different aggregates are not interleaved; stores that can read
+    /// their whole log in order should override it. Undecodable events are
+    /// skipped.
+    async fn fold_all(&self, projection: &mut dyn Projection) -> Result<(), PortError> {
+        use futures::StreamExt;
+
+        // Inventory events are keyed by device, so ids repeat across types
+        let mut folded = std::collections::HashSet::new();
+        for aggregate_type in NetworkEvent::AGGREGATE_TYPES {
+            for aggregate_id in self.list_aggregate_ids(aggregate_type).await? {
+                if !folded.insert(aggregate_id.clone()) {
+                    continue;
+                }
+                let mut events = self.load_events_stream(&aggregate_id).await?;
+                while let Some(item) = events.next().await {
+                    match item {
+                        Ok(event) => projection.apply(&event),
+                        Err(PortError::Deserialization(_)) => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 // ============================================================================
@@ -220,6 +570,12 @@ pub struct DiscoveredDevice {
     pub model: Option<String>,
     pub vendor_id: Option<String>,
     pub adopted: bool,
+    /// Advertised system name
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Port the device was seen through, on the device itself
+    #[serde(default)]
+    pub port_id: Option<String>,
 }
 
 /// Device details
@@ -256,7 +612,168 @@ pub struct VendorDevice {
     pub properties: HashMap<String, serde_json::Value>,
 }
 
-/// Vendor-specific configuration
+/// `VendorDevice::properties` key naming the MAC of the stack master a unit belongs to
+///
+/// Adapters that see stacks or virtual chassis set it, together with
+/// `STACK_MEMBER_PROPERTY`, on every unit including the master; discovery
+/// merges the units into one logical device.
+pub const STACK_MASTER_PROPERTY: &str = "stack_master_mac";
+
+/// `VendorDevice::properties` key holding a unit's member number in its stack
+pub const STACK_MEMBER_PROPERTY: &str = "stack_member";
+
+impl VendorDevice {
+    /// The stack master's MAC and this unit's member number, if the vendor reports the unit as stacked
+    pub fn stack_membership(&self) -> Option<(MacAddress, u32)> {
+        let master = self.properties.get(STACK_MASTER_PROPERTY)?.as_str()?;
+        let member = self.properties.get(STACK_MEMBER_PROPERTY)?.as_u64()?;
+        Some((MacAddress::parse(master).ok()?, u32::try_from(member).ok()?))
+    }
+}
+
+/// Vendor-agnostic device configuration for `DeviceControlPort::apply_config`
+///
+/// Empty fields are left untouched on the device.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceConfigRequest {
+    /// Hostname to set
+    pub hostname: Option<String>,
+    /// Interfaces to configure
+    pub interfaces: Vec<InterfaceConfig>,
+    /// VLANs to create
+    pub vlans: Vec<VlanConfig>,
+    /// NTP servers (hostnames or addresses), in order of preference
+    pub ntp_servers: Vec<String>,
+    /// DNS resolvers, in order of preference
+    pub dns_servers: Vec<std::net::IpAddr>,
+    /// Link aggregation groups over the request's interfaces
+    pub lags: Vec<LagConfig>,
+}
+
+impl DeviceConfigRequest {
+    /// Request carrying a device aggregate's name, interfaces and VLANs
+    pub fn from_device(device: &NetworkDeviceAggregate) -> Self {
+        Self {
+            hostname: Some(device.name().to_string()),
+            interfaces: device.interfaces().to_vec(),
+            vlans: device.vlans().to_vec(),
+            ..Self::default()
+        }
+    }
+
+    /// Check the request is consistent before anything is sent
+    ///
+    /// VLANs must not conflict with each other and interface settings, such
+    /// as the MTU, must be in range. LAG members must be interfaces of the
+    /// request, each in a single group.
+    pub fn validate(&self) -> Result<(), AggregateError> {
+        VlanConfig::check_conflicts(&self.vlans)?;
+        self.interfaces.iter().try_for_each(InterfaceConfig::validate)?;
+        LagConfig::check_conflicts(&self.lags, &self.interfaces)?;
+        Ok(())
+    }
+
+    /// Check fixed interface speeds against what the ports advertise
+    ///
+    /// `ports` maps interface names to their capabilities; interfaces
+    /// without an entry are not checked.
+    pub fn check_port_speeds(&self, ports: &HashMap<String, PortCapabilities>) -> Result<(), AggregateError> {
+        for iface in &self.interfaces {
+            if let Some(capabilities) = ports.get(&iface.name) {
+                iface.check_speed(capabilities)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the hostname
+    pub fn with_hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.hostname = Some(hostname.into());
+        self
+    }
+
+    /// Add an interface
+    pub fn with_interface(mut self, interface: InterfaceConfig) -> Self {
+        self.interfaces.push(interface);
+        self
+    }
+
+    /// Add a VLAN
+    pub fn with_vlan(mut self, vlan: VlanConfig) -> Self {
+        self.vlans.push(vlan);
+        self
+    }
+
+    /// Add an NTP server
+    pub fn with_ntp_server(mut self, server: impl Into<String>) -> Self {
+        self.ntp_servers.push(server.into());
+        self
+    }
+
+    /// Add a DNS resolver
+    pub fn with_dns_server(mut self, server: std::net::IpAddr) -> Self {
+        self.dns_servers.push(server);
+        self
+    }
+
+    /// Add a link aggregation group
+    pub fn with_lag(mut self, lag: LagConfig) -> Self {
+        self.lags.push(lag);
+        self
+    }
+
+    /// Whether the request changes nothing
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Desired settings in this request that `running` does not match
+    ///
+    /// Only settings the request specifies are compared, as `apply_config`
+    /// leaves empty fields untouched. VLANs are matched by ID and interfaces
+    /// by name, and LAGs by ID, so extra ones on the device are not drift.
+    /// NTP and DNS servers are compared as ordered lists.
+    pub fn diff(&self, running: &DeviceConfigRequest) -> Vec<ConfigDiff> {
+        let mut diffs = Vec::new();
+        let mut compare = |setting: String, desired: serde_json::Value, running: Option<serde_json::Value>| {
+            if running.as_ref() != Some(&desired) {
+                diffs.push(ConfigDiff { setting, desired, running });
+            }
+        };
+
+        if let Some(hostname) = &self.hostname {
+            compare("hostname".to_string(), serde_json::json!(hostname), running.hostname.as_ref().map(|h| serde_json::json!(h)));
+        }
+        for vlan in &self.vlans {
+            let actual = running.vlans.iter().find(|v| v.id == vlan.id);
+            compare(format!("vlan[{}]", vlan.id), serde_json::json!(vlan), actual.map(|v| serde_json::json!(v)));
+        }
+        for interface in &self.interfaces {
+            let actual = running.interfaces.iter().find(|i| i.name == interface.name);
+            compare(
+                format!("interface[{}]", interface.name),
+                serde_json::json!(interface),
+                actual.map(|i| serde_json::json!(i)),
+            );
+        }
+        for lag in &self.lags {
+            let actual = running.lags.iter().find(|l| l.id == lag.id);
+            compare(format!("lag[{}]", lag.id), serde_json::json!(lag), actual.map(|l| serde_json::json!(l)));
+        }
+        if !self.ntp_servers.is_empty() {
+            let actual = (!running.ntp_servers.is_empty()).then(|| serde_json::json!(running.ntp_servers));
+            compare("ntp_servers".to_string(), serde_json::json!(self.ntp_servers), actual);
+        }
+        if !self.dns_servers.is_empty() {
+            let actual = (!running.dns_servers.is_empty()).then(|| serde_json::json!(running.dns_servers));
+            compare("dns_servers".to_string(), serde_json::json!(self.dns_servers), actual);
+        }
+        diffs
+    }
+}
+
+/// Vendor-specific configuration for `DeviceControlPort::apply_raw_config`
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VendorConfig {
     /// Configuration type
@@ -265,6 +782,18 @@ pub struct VendorConfig {
     pub payload: serde_json::Value,
 }
 
+/// Result of `DeviceControlPort::apply_config_transactional`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigApplyOutcome {
+    /// The configuration was applied and the device is reachable
+    Applied,
+    /// Applying failed and the previous configuration was restored
+    RolledBack {
+        /// Why the configuration was rolled back
+        reason: String,
+    },
+}
+
 /// Device statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceStats {
@@ -275,6 +804,29 @@ pub struct DeviceStats {
     pub port_stats: Vec<PortStats>,
 }
 
+/// Result of an adapter health check
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthStatus {
+    /// The remote system answered
+    pub reachable: bool,
+    /// The adapter holds valid credentials or an active session
+    pub authenticated: bool,
+    /// Version reported by the remote system, if any
+    pub version: Option<String>,
+}
+
+impl HealthStatus {
+    /// Status of a remote system that did not answer
+    pub fn unreachable() -> Self {
+        Self::default()
+    }
+
+    /// Whether the remote system is reachable and accepts the adapter's credentials
+    pub fn is_healthy(&self) -> bool {
+        self.reachable && self.authenticated
+    }
+}
+
 /// Port statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortStats {
@@ -285,6 +837,13 @@ pub struct PortStats {
     pub tx_bytes: u64,
     pub rx_errors: u64,
     pub tx_errors: u64,
+    /// Receive rate in bits per second, filled in by a `ThroughputTracker`
+    /// once it has a previous sample of this port
+    #[serde(default)]
+    pub rx_bps: Option<f64>,
+    /// Transmit rate in bits per second (see `rx_bps`)
+    #[serde(default)]
+    pub tx_bps: Option<f64>,
 }
 
 /// Connection info for inventory
@@ -346,6 +905,14 @@ impl EventSubscription {
         }
     }
 
+    /// Create with an adapter-assigned ID, e.g. a durable consumer name
+    pub fn with_id(id: impl Into<String>, subject: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            subject: subject.into(),
+        }
+    }
+
     /// Get the subscription ID
     pub fn id(&self) -> &str {
         &self.id
@@ -363,4 +930,61 @@ impl Default for EventSubscription {
     }
 }
 
+/// Settles one event received from an `EventSource`
+#[async_trait]
+pub trait EventAck: Send {
+    /// The event was handled; do not deliver it again
+    async fn ack(self) -> Result<(), PortError>;
+
+    /// The event was not handled; deliver it again later
+    async fn nak(self) -> Result<(), PortError>;
+}
+
+/// Events of a subscription, each delivered until it is acked
+#[async_trait]
+pub trait EventSource: Send {
+    /// Handle settling a received event
+    type Ack: EventAck;
+
+    /// The next event, or `None` once the subscription has ended
+    async fn next(&mut self) -> Option<Result<(NetworkEvent, Self::Ack), PortError>>;
+}
+
+/// An event together with the time the store recorded it
+#[derive(Debug, Clone)]
+pub struct TimestampedEvent {
+    /// When the event was appended to the store
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
+    /// The event itself
+    pub event: NetworkEvent,
+}
+
+/// An event with the metadata the store recorded alongside it
+#[derive(Debug, Clone)]
+pub struct RecordedEvent {
+    /// Position in the store; later appends have higher sequences
+    pub sequence: u64,
+    /// When the event was appended to the store
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
+    /// Unique id of the event, if the store kept one
+    pub event_id: Option<String>,
+    /// Id shared by every event of the same operation
+    pub correlation_id: Option<String>,
+    /// Id of whatever directly caused the event
+    pub causation_id: Option<String>,
+    /// The event itself
+    pub event: NetworkEvent,
+}
+
+/// Serialized aggregate state at a known event version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregateSnapshot {
+    /// Aggregate this snapshot belongs to
+    pub aggregate_id: String,
+    /// Number of events folded into the snapshot
+    pub version: u64,
+    /// Opaque snapshot bytes (format owned by the caller)
+    pub data: Vec<u8>,
+}
+
 use serde::{Deserialize, Serialize};
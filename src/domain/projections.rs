@@ -0,0 +1,114 @@
+//! # Projections
+//!
+//! Read models built by folding the event log, without materializing
+//! aggregates. A projection sees every event exactly once via
+//! `EventStorePort::fold_all` and keeps only what its queries need.
+//!
+//! ```rust,ignore
+//! let mut counts = DeviceCountByState::new();
+//! event_store.fold_all(&mut counts).await?;
+//! println!("{} devices provisioned", counts.count(DeviceState::Provisioned));
+//! ```
+
+use std::collections::HashMap;
+
+use super::aggregates::DeviceState;
+use super::events::NetworkEvent;
+
+/// A read model folded from the event log
+pub trait Projection: Send {
+    /// Fold one event into the read model
+    ///
+    /// Called once per stored event. Events the projection does not care
+    /// about should be ignored.
+    fn apply(&mut self, event: &NetworkEvent);
+}
+
+/// Number of devices in each lifecycle state
+///
+/// Tracks the current state of every device by id, so events for one
+/// device must arrive in the order they were recorded. Purged devices are
+/// dropped from the tallies.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceCountByState {
+    states: HashMap<String, DeviceState>,
+}
+
+impl DeviceCountByState {
+    /// Create an empty projection
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of devices currently in `state`
+    pub fn count(&self, state: DeviceState) -> usize {
+        self.states.values().filter(|s| **s == state).count()
+    }
+
+    /// Number of devices seen, in any state
+    pub fn total(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Device counts per state, every state included even when zero
+    pub fn counts(&self) -> Vec<(DeviceState, usize)> {
+        DeviceState::ALL.iter().map(|state| (*state, self.count(*state))).collect()
+    }
+}
+
+impl Projection for DeviceCountByState {
+    fn apply(&mut self, event: &NetworkEvent) {
+        match event {
+            NetworkEvent::DevicePurged { device_id } => {
+                self.states.remove(&device_id.to_string());
+            }
+            event => {
+                if let Some(state) = DeviceState::after_event(event) {
+                    self.states.insert(event.aggregate_id(), state);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{DeviceId, DeviceType, MacAddress};
+
+    fn discovered(device_id: DeviceId) -> NetworkEvent {
+        NetworkEvent::DeviceDiscovered {
+            device_id,
+            mac: MacAddress::parse("00:11:22:33:44:55").unwrap(),
+            device_type: DeviceType::Switch,
+            ip_address: None,
+        }
+    }
+
+    #[test]
+    fn test_device_count_follows_latest_state() {
+        let device = DeviceId::new();
+        let mut counts = DeviceCountByState::new();
+
+        counts.apply(&discovered(device));
+        assert_eq!(counts.count(DeviceState::Discovered), 1);
+
+        counts.apply(&NetworkEvent::DeviceAdopting { device_id: device, vendor_id: "v-1".to_string() });
+        assert_eq!(counts.count(DeviceState::Discovered), 0);
+        assert_eq!(counts.count(DeviceState::Adopting), 1);
+        assert_eq!(counts.total(), 1);
+    }
+
+    #[test]
+    fn test_device_count_drops_purged_devices() {
+        let device = DeviceId::new();
+        let mut counts = DeviceCountByState::new();
+
+        counts.apply(&discovered(device));
+        counts.apply(&NetworkEvent::DeviceDecommissioned { device_id: device });
+        counts.apply(&NetworkEvent::DevicePurged { device_id: device });
+
+        assert_eq!(counts.total(), 0);
+        assert!(counts.counts().iter().all(|(_, n)| *n == 0));
+    }
+}
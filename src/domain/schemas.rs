@@ -0,0 +1,127 @@
+//! # Wire Format Schemas
+//!
+//! JSON Schemas for the events and commands this crate puts on the wire, for
+//! consumers written in other languages. Generated from the Rust types with
+//! `schemars`, so they follow the serde representation exactly: enums are
+//! externally tagged and `MacAddress` is an array of six bytes.
+//!
+//! Run `cargo run --example export_schemas -- <dir>` to write them out.
+
+use schemars::{schema_for, Schema};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+use super::commands::NetworkCommand;
+use super::events::{NetworkEvent, VersionedEvent};
+
+/// Every exported schema, keyed by type name
+fn schemas() -> Vec<(&'static str, Schema)> {
+    vec![
+        ("NetworkEvent", schema_for!(NetworkEvent)),
+        ("VersionedEvent", schema_for!(VersionedEvent)),
+        ("NetworkCommand", schema_for!(NetworkCommand)),
+    ]
+}
+
+/// JSON Schemas of the event and command wire format
+///
+/// Returns an object keyed by type name: `NetworkEvent`, `VersionedEvent`
+/// (the stored payload, as produced by `NetworkEvent::encode`) and
+/// `NetworkCommand`. Each schema is self-contained, with the value objects
+/// it uses under `$defs`.
+pub fn export_event_schemas() -> Value {
+    let schemas: Map<String, Value> = schemas()
+        .into_iter()
+        .map(|(name, schema)| (name.to_string(), schema.to_value()))
+        .collect();
+    Value::Object(schemas)
+}
+
+/// Write each schema to `<dir>/<TypeName>.schema.json`
+///
+/// Creates `dir` if needed and returns the paths written.
+pub fn write_schemas(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    for (name, schema) in schemas() {
+        let path = dir.join(format!("{name}.schema.json"));
+        let json = serde_json::to_string_pretty(&schema).map_err(std::io::Error::other)?;
+        std::fs::write(&path, json + "\n")?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{DeviceId, DeviceType, MacAddress};
+
+    fn validator(name: &str) -> jsonschema::Validator {
+        let schemas = export_event_schemas();
+        jsonschema::validator_for(&schemas[name]).expect("generated schema compiles")
+    }
+
+    fn discovered() -> NetworkEvent {
+        NetworkEvent::DeviceDiscovered {
+            device_id: DeviceId::new(),
+            mac: MacAddress::parse("00:11:22:33:44:55").unwrap(),
+            device_type: DeviceType::Switch,
+            ip_address: Some("192.168.1.10".parse().unwrap()),
+        }
+    }
+
+    // ========================================================================
+    // Export
+    // ========================================================================
+
+    #[test]
+    fn test_export_names_each_schema() {
+        let schemas = export_event_schemas();
+        let names: Vec<&String> = schemas.as_object().unwrap().keys().collect();
+        assert_eq!(names, ["NetworkCommand", "NetworkEvent", "VersionedEvent"]);
+    }
+
+    #[test]
+    fn test_write_schemas() {
+        let dir = std::env::temp_dir().join(format!("cim-network-schemas-{}", uuid::Uuid::now_v7()));
+
+        let written = write_schemas(&dir).unwrap();
+        assert_eq!(written.len(), 3);
+        let event: Value = serde_json::from_slice(&std::fs::read(dir.join("NetworkEvent.schema.json")).unwrap()).unwrap();
+        assert_eq!(event, export_event_schemas()["NetworkEvent"]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    // ========================================================================
+    // Validation
+    // ========================================================================
+
+    #[test]
+    fn test_event_round_trips_through_schema() {
+        let event = discovered();
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert!(validator("NetworkEvent").is_valid(&json));
+        let back: NetworkEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(back.aggregate_id(), event.aggregate_id());
+    }
+
+    #[test]
+    fn test_encoded_payload_matches_versioned_schema() {
+        let payload: Value = serde_json::from_slice(&discovered().encode().unwrap()).unwrap();
+
+        assert!(validator("VersionedEvent").is_valid(&payload));
+    }
+
+    #[test]
+    fn test_schema_rejects_malformed_event() {
+        let mut json = serde_json::to_value(discovered()).unwrap();
+        // Five bytes is not a MAC address
+        json["DeviceDiscovered"]["mac"] = serde_json::json!([0, 17, 34, 51, 68]);
+
+        assert!(!validator("NetworkEvent").is_valid(&json));
+        assert!(!validator("NetworkEvent").is_valid(&serde_json::json!({ "NoSuchEvent": {} })));
+    }
+}
@@ -2,13 +2,21 @@
 //!
 //! Immutable domain primitives following cim-domain patterns.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
 use std::fmt;
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::ops::RangeInclusive;
 use uuid::Uuid;
 
+/// OUI registry subset compiled from `data/oui.csv` by the build script
+mod oui {
+    include!(concat!(env!("OUT_DIR"), "/oui_table.rs"));
+}
+
 /// Network device identifier (UUID v7 for time-ordering)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct DeviceId(Uuid);
 
 impl DeviceId {
@@ -41,7 +49,7 @@ impl fmt::Display for DeviceId {
 }
 
 /// Network topology identifier
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct TopologyId(Uuid);
 
 impl TopologyId {
@@ -67,7 +75,7 @@ impl fmt::Display for TopologyId {
 }
 
 /// Connection identifier
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct ConnectionId(Uuid);
 
 impl ConnectionId {
@@ -92,8 +100,41 @@ impl fmt::Display for ConnectionId {
     }
 }
 
+/// Site identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub struct SiteId(Uuid);
+
+impl SiteId {
+    /// Create a new site ID using UUID v7
+    pub fn new() -> Self {
+        Self(Uuid::now_v7())
+    }
+
+    /// Create from existing UUID
+    pub fn from_uuid(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+
+    /// Get the inner UUID
+    pub fn inner(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl Default for SiteId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for SiteId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 /// MAC address value object
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct MacAddress([u8; 6]);
 
 impl MacAddress {
@@ -102,9 +143,9 @@ impl MacAddress {
         Self(bytes)
     }
 
-    /// Parse from string (supports : and - separators)
+    /// Parse from string (supports `:`, `-` and Cisco-style `.` separators)
     pub fn parse(s: &str) -> Result<Self, MacAddressError> {
-        let cleaned = s.replace([':', '-'], "");
+        let cleaned = s.replace([':', '-', '.'], "");
         if cleaned.len() != 12 {
             return Err(MacAddressError::InvalidLength);
         }
@@ -124,6 +165,28 @@ impl MacAddress {
     pub fn as_bytes(&self) -> &[u8; 6] {
         &self.0
     }
+
+    /// Organizationally unique identifier (first three octets)
+    pub fn oui(&self) -> [u8; 3] {
+        [self.0[0], self.0[1], self.0[2]]
+    }
+
+    /// Vendor registered for this address's OUI, if it is in the bundled registry
+    ///
+    /// Only a subset of the IEEE registry is compiled in (see `data/oui.csv`);
+    /// locally administered (randomized) addresses never resolve.
+    pub fn vendor(&self) -> Option<&'static str> {
+        self.oui_entry().map(|(_, vendor, _)| *vendor)
+    }
+
+    /// Look up this address's OUI in the compiled registry
+    fn oui_entry(&self) -> Option<&'static ([u8; 3], &'static str, &'static str)> {
+        let oui = self.oui();
+        oui::OUI_TABLE
+            .binary_search_by_key(&oui, |(entry, _, _)| *entry)
+            .ok()
+            .map(|index| &oui::OUI_TABLE[index])
+    }
 }
 
 impl fmt::Display for MacAddress {
@@ -145,8 +208,66 @@ pub enum MacAddressError {
     InvalidFormat,
 }
 
+/// Leading octets of a MAC address, for matching whole OUIs or address blocks
+///
+/// Parsed from strings such as `00:11:22`, `00-11-22-*` or `00:11:22:33:*`;
+/// a trailing `*` is optional. Separators follow `MacAddress::parse`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MacPrefix {
+    bytes: [u8; 6],
+    octets: usize,
+}
+
+impl MacPrefix {
+    /// Parse a prefix of one to six octets
+    pub fn parse(s: &str) -> Result<Self, MacAddressError> {
+        let trimmed = s.trim();
+        let trimmed = trimmed.strip_suffix('*').unwrap_or(trimmed);
+        let cleaned = trimmed.replace([':', '-', '.'], "");
+        if cleaned.is_empty() || !cleaned.len().is_multiple_of(2) || cleaned.len() > 12 {
+            return Err(MacAddressError::InvalidLength);
+        }
+
+        let mut bytes = [0u8; 6];
+        for (i, chunk) in cleaned.as_bytes().chunks(2).enumerate() {
+            let hex_str =
+                std::str::from_utf8(chunk).map_err(|_| MacAddressError::InvalidFormat)?;
+            bytes[i] =
+                u8::from_str_radix(hex_str, 16).map_err(|_| MacAddressError::InvalidFormat)?;
+        }
+
+        Ok(Self { bytes, octets: cleaned.len() / 2 })
+    }
+
+    /// Prefix covering every address with this OUI
+    pub fn from_oui(oui: [u8; 3]) -> Self {
+        Self { bytes: [oui[0], oui[1], oui[2], 0, 0, 0], octets: 3 }
+    }
+
+    /// The fixed leading octets
+    pub fn octets(&self) -> &[u8] {
+        &self.bytes[..self.octets]
+    }
+
+    /// Whether `mac` starts with this prefix
+    pub fn matches(&self, mac: &MacAddress) -> bool {
+        mac.as_bytes().starts_with(self.octets())
+    }
+}
+
+impl fmt::Display for MacPrefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let octets: Vec<String> = self.octets().iter().map(|b| format!("{:02x}", b)).collect();
+        write!(f, "{}", octets.join(":"))?;
+        if self.octets < 6 {
+            write!(f, ":*")?;
+        }
+        Ok(())
+    }
+}
+
 /// Network device type
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum DeviceType {
     /// Gateway/Router device
     Gateway,
@@ -154,28 +275,50 @@ pub enum DeviceType {
     Switch,
     /// Wireless access point
     AccessPoint,
+    /// Dedicated firewall appliance
+    Firewall,
     /// Generic network device
     Generic { model: String },
 }
 
+impl DeviceType {
+    /// Classify a device from its MAC address alone
+    ///
+    /// Returns `None` for unknown OUIs and for vendors whose OUIs are shared
+    /// across gateways, switches and access points (e.g. Ubiquiti, Cisco).
+    pub fn from_mac_oui(mac: MacAddress) -> Option<DeviceType> {
+        match mac.oui_entry()?.2 {
+            "gateway" => Some(DeviceType::Gateway),
+            "switch" => Some(DeviceType::Switch),
+            "access-point" => Some(DeviceType::AccessPoint),
+            "firewall" => Some(DeviceType::Firewall),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for DeviceType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             DeviceType::Gateway => write!(f, "Gateway"),
             DeviceType::Switch => write!(f, "Switch"),
             DeviceType::AccessPoint => write!(f, "AccessPoint"),
+            DeviceType::Firewall => write!(f, "Firewall"),
             DeviceType::Generic { model } => write!(f, "Generic({})", model),
         }
     }
 }
 
 /// Port identifier on a device
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct PortId {
     /// Port number or name
     pub name: String,
     /// Port index (if applicable)
     pub index: Option<u32>,
+    /// Stack member the port is on, for ports of a stacked device
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub member: Option<u32>,
 }
 
 impl PortId {
@@ -183,6 +326,7 @@ impl PortId {
         Self {
             name: name.into(),
             index: None,
+            member: None,
         }
     }
 
@@ -190,21 +334,239 @@ impl PortId {
         Self {
             name: name.into(),
             index: Some(index),
+            member: None,
         }
     }
+
+    /// The same port, on stack member `member`
+    pub fn on_member(mut self, member: u32) -> Self {
+        self.member = Some(member);
+        self
+    }
+
+    /// Parse a vendor port name into its canonical `PortId`
+    ///
+    /// The name is normalized with `PortName`, so `Gi1/0/24` and
+    /// `GigabitEthernet1/0/24` give the same port.
+    pub fn parse(s: &str) -> Result<Self, PortNameError> {
+        Ok(Self::new(PortName::parse(s)?.to_string()))
+    }
+
+    /// The port's name split into its parts, if it follows a known layout
+    pub fn port_name(&self) -> Option<PortName> {
+        PortName::parse(&self.name).ok()
+    }
 }
 
 impl fmt::Display for PortId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.index {
-            Some(idx) => write!(f, "{}[{}]", self.name, idx),
-            None => write!(f, "{}", self.name),
+            Some(idx) => write!(f, "{}[{}]", self.name, idx)?,
+            None => write!(f, "{}", self.name)?,
+        }
+        match self.member {
+            Some(member) => write!(f, " (member {})", member),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A physical unit of a switch stack or virtual chassis
+///
+/// The stack is one logical `NetworkDeviceAggregate`; each unit keeps its
+/// own MAC and member number, which `PortId::member` refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub struct StackMember {
+    /// Member number within the stack, as reported by the vendor
+    pub member: u32,
+    /// The unit's own MAC address
+    pub mac: MacAddress,
+    /// Aggregate the unit was discovered as before being merged, if any
+    pub device_id: Option<DeviceId>,
+}
+
+impl StackMember {
+    /// Unit `member` of a stack, with MAC `mac`
+    pub fn new(member: u32, mac: MacAddress) -> Self {
+        Self { member, mac, device_id: None }
+    }
+
+    /// The unit was discovered as `device_id` before being merged
+    pub fn discovered_as(mut self, device_id: DeviceId) -> Self {
+        self.device_id = Some(device_id);
+        self
+    }
+}
+
+/// Cisco-style interface type abbreviations, by canonical name
+const PORT_PREFIX_ALIASES: &[(&str, &[&str])] = &[
+    ("FastEthernet", &["fa", "fastethernet"]),
+    ("GigabitEthernet", &["gi", "gig", "gigabitethernet"]),
+    ("TenGigabitEthernet", &["te", "ten", "tengig", "tengigabitethernet"]),
+    ("TwentyFiveGigE", &["twe", "twentyfivegige"]),
+    ("FortyGigabitEthernet", &["fo", "forty", "fortygigabitethernet"]),
+    ("HundredGigE", &["hu", "hundredgige"]),
+    ("Ethernet", &["et", "ethernet"]),
+];
+
+/// A port name split into its type prefix and position numbers
+///
+/// Parses the common layouts: Cisco `Gi1/0/24` (stack/module/port), Arista
+/// `Ethernet49/1`, Juniper `ge-0/0/1`, UniFi `port5` and Linux `eth0`.
+/// Numbers fill from the right, so the last one is always `index`. Cisco
+/// abbreviations are expanded (`Gi` to `GigabitEthernet`); other prefixes
+/// are kept as written. Displays in canonical form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub struct PortName {
+    /// Interface type, e.g. `GigabitEthernet`, `ge-` or `port`
+    pub prefix: String,
+    /// Stack member or FPC, for three-part names
+    pub stack: Option<u32>,
+    /// Module or slot, for names with two or more parts
+    pub module: Option<u32>,
+    /// Port number within the module
+    pub index: u32,
+}
+
+impl PortName {
+    /// Parse a vendor port name
+    pub fn parse(s: &str) -> Result<Self, PortNameError> {
+        let invalid = || PortNameError::InvalidFormat(s.to_string());
+        let s = s.trim();
+        let split = s.find(|c: char| c.is_ascii_digit()).ok_or_else(invalid)?;
+        let (prefix, numbers) = s.split_at(split);
+
+        let numbers = numbers
+            .split('/')
+            .map(|n| {
+                if n.is_empty() || !n.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(invalid());
+                }
+                n.parse::<u32>().map_err(|_| invalid())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let (stack, module, index) = match numbers[..] {
+            [index] => (None, None, index),
+            [module, index] => (None, Some(module), index),
+            [stack, module, index] => (Some(stack), Some(module), index),
+            _ => return Err(invalid()),
+        };
+
+        Ok(Self { prefix: canonical_port_prefix(prefix), stack, module, index })
+    }
+
+    /// The same port position with another index
+    pub fn with_index(&self, index: u32) -> Self {
+        Self { index, ..self.clone() }
+    }
+}
+
+impl fmt::Display for PortName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.prefix)?;
+        for part in [self.stack, self.module].into_iter().flatten() {
+            write!(f, "{}/", part)?;
+        }
+        write!(f, "{}", self.index)
+    }
+}
+
+impl std::str::FromStr for PortName {
+    type Err = PortNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Expand a known interface type abbreviation, keeping others as written
+fn canonical_port_prefix(prefix: &str) -> String {
+    let lower = prefix.to_ascii_lowercase();
+    PORT_PREFIX_ALIASES
+        .iter()
+        .find(|(_, aliases)| aliases.contains(&lower.as_str()))
+        .map_or_else(|| prefix.to_string(), |(canonical, _)| canonical.to_string())
+}
+
+/// Consecutive ports on one module, e.g. `Gi1/0/1-24` or `port1-4`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub struct PortRange {
+    /// First port of the range
+    first: PortName,
+    /// Index of the last port, inclusive
+    last: u32,
+}
+
+impl PortRange {
+    /// Range from `first` up to index `last`, inclusive
+    pub fn new(first: PortName, last: u32) -> Result<Self, PortNameError> {
+        if last < first.index {
+            return Err(PortNameError::InvalidRange(format!("{}-{}", first, last)));
+        }
+        Ok(Self { first, last })
+    }
+
+    /// Parse `<port>-<last index>`, or a single port name
+    pub fn parse(s: &str) -> Result<Self, PortNameError> {
+        let s = s.trim();
+        // Juniper names contain a `-` too, so only split off a trailing number
+        if let Some((first, last)) = s.rsplit_once('-') {
+            if let (Ok(first), Ok(last)) = (PortName::parse(first), last.parse::<u32>()) {
+                return Self::new(first, last);
+            }
+        }
+        let port = PortName::parse(s)?;
+        let last = port.index;
+        Self::new(port, last)
+    }
+
+    /// First port of the range
+    pub fn first(&self) -> &PortName {
+        &self.first
+    }
+
+    /// Index of the last port, inclusive
+    pub fn last(&self) -> u32 {
+        self.last
+    }
+
+    /// Whether `port` lies in the range
+    pub fn contains(&self, port: &PortName) -> bool {
+        port.prefix == self.first.prefix
+            && port.stack == self.first.stack
+            && port.module == self.first.module
+            && (self.first.index..=self.last).contains(&port.index)
+    }
+
+    /// Each port of the range, lowest first
+    pub fn ports(&self) -> impl Iterator<Item = PortId> + '_ {
+        (self.first.index..=self.last).map(|index| PortId::new(self.first.with_index(index).to_string()))
+    }
+}
+
+impl fmt::Display for PortRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.last == self.first.index {
+            write!(f, "{}", self.first)
+        } else {
+            write!(f, "{}-{}", self.first, self.last)
         }
     }
 }
 
+/// Port name or range parsing error
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PortNameError {
+    /// The string is not a recognized port name
+    #[error("Invalid port name: {0}")]
+    InvalidFormat(String),
+    /// The range ends before it starts
+    #[error("Invalid port range: {0}")]
+    InvalidRange(String),
+}
+
 /// Network interface configuration
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct InterfaceConfig {
     /// Interface name
     pub name: String,
@@ -216,10 +578,71 @@ pub struct InterfaceConfig {
     pub vlan_id: Option<u16>,
     /// Whether interface is enabled
     pub enabled: bool,
+    /// MTU in bytes; `None` keeps the device's default
+    #[serde(default)]
+    pub mtu: Option<u16>,
+    /// Fixed port speed; `None` auto-negotiates
+    #[serde(default)]
+    pub speed: Option<LinkSpeed>,
+}
+
+/// Smallest MTU accepted, the IPv4 minimum
+pub const MIN_MTU: u16 = 68;
+
+/// Largest MTU accepted, the common jumbo-frame ceiling
+pub const MAX_MTU: u16 = 9216;
+
+impl InterfaceConfig {
+    /// Check the interface's settings are within what devices accept
+    pub fn validate(&self) -> Result<(), InterfaceError> {
+        match self.mtu {
+            Some(mtu) if !(MIN_MTU..=MAX_MTU).contains(&mtu) => Err(InterfaceError::InvalidMtu {
+                interface: self.name.clone(),
+                mtu,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the port behind this interface supports its fixed speed
+    pub fn check_speed(&self, capabilities: &PortCapabilities) -> Result<(), LinkSpeedError> {
+        match self.speed {
+            Some(speed) if !capabilities.supports(speed) => Err(LinkSpeedError::UnsupportedSpeed {
+                port: self.name.clone(),
+                speed,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Invalid setting on one interface
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InterfaceError {
+    /// MTU outside `MIN_MTU..=MAX_MTU`
+    #[error("Invalid MTU {mtu} on {interface}: must be 68-9216")]
+    InvalidMtu {
+        /// Interface carrying the MTU
+        interface: String,
+        /// Rejected MTU
+        mtu: u16,
+    },
+}
+
+/// One desired setting a device's running configuration does not match
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ConfigDiff {
+    /// Setting that drifted: `hostname`, `ntp_servers`, `dns_servers`,
+    /// `vlan[<id>]`, `interface[<name>]` or `lag[<id>]`
+    pub setting: String,
+    /// Desired value
+    pub desired: serde_json::Value,
+    /// Running value, `None` when the device does not have the setting
+    pub running: Option<serde_json::Value>,
 }
 
 /// VLAN configuration
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct VlanConfig {
     /// VLAN ID (1-4094)
     pub id: u16,
@@ -227,6 +650,9 @@ pub struct VlanConfig {
     pub name: String,
     /// Whether this is the native/untagged VLAN
     pub native: bool,
+    /// Access (untagged member) ports
+    #[serde(default)]
+    pub access_ports: Vec<PortId>,
 }
 
 impl VlanConfig {
@@ -238,8 +664,35 @@ impl VlanConfig {
             id,
             name: name.into(),
             native: false,
+            access_ports: Vec::new(),
         })
     }
+
+    /// Add an access port
+    pub fn with_access_port(mut self, port: PortId) -> Self {
+        self.access_ports.push(port);
+        self
+    }
+
+    /// Check that a device's VLANs are consistent with each other
+    ///
+    /// Each VLAN ID may appear once, and each access port may belong to
+    /// only one VLAN. Adapters can call this before pushing a config.
+    pub fn check_conflicts(vlans: &[VlanConfig]) -> Result<(), VlanConflict> {
+        let mut ids = HashSet::new();
+        let mut ports = HashSet::new();
+        for vlan in vlans {
+            if !ids.insert(vlan.id) {
+                return Err(VlanConflict::DuplicateId(vlan.id));
+            }
+            for port in &vlan.access_ports {
+                if !ports.insert(port) {
+                    return Err(VlanConflict::PortAlreadyAssigned(port.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, thiserror::Error)]
@@ -248,8 +701,141 @@ pub enum VlanError {
     InvalidId(u16),
 }
 
+/// Inconsistency between the VLANs configured on one device
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum VlanConflict {
+    /// The same VLAN ID is configured twice
+    #[error("VLAN {0} is configured more than once")]
+    DuplicateId(u16),
+    /// An access port is a member of two VLANs
+    #[error("Port {0} is assigned to more than one VLAN")]
+    PortAlreadyAssigned(PortId),
+}
+
+/// LACP negotiation mode of a link aggregation group
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum LacpMode {
+    /// Send LACP packets to start negotiation
+    #[default]
+    Active,
+    /// Answer LACP packets but never start negotiation
+    Passive,
+    /// No LACP; the members are bundled unconditionally
+    Static,
+}
+
+/// How traffic is spread across the members of a link aggregation group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum LagHashPolicy {
+    /// Source and destination MAC addresses
+    Layer2,
+    /// MAC and IP addresses
+    Layer2And3,
+    /// IP addresses and TCP/UDP ports
+    Layer3And4,
+}
+
+/// Link aggregation group (port-channel, bond) bundling member ports
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub struct LagConfig {
+    /// Group number, e.g. the `1` of `Port-channel1`
+    pub id: u16,
+    /// Member ports, named as the device's interfaces
+    pub members: Vec<PortId>,
+    /// LACP negotiation mode
+    #[serde(default)]
+    pub mode: LacpMode,
+    /// Load-balancing policy; `None` keeps the device's default
+    #[serde(default)]
+    pub hash_policy: Option<LagHashPolicy>,
+}
+
+impl LagConfig {
+    /// An LACP-active group with no members yet
+    pub fn new(id: u16) -> Self {
+        Self {
+            id,
+            members: Vec::new(),
+            mode: LacpMode::default(),
+            hash_policy: None,
+        }
+    }
+
+    /// Add a member port
+    pub fn with_member(mut self, port: PortId) -> Self {
+        self.members.push(port);
+        self
+    }
+
+    /// Set the LACP mode
+    pub fn with_mode(mut self, mode: LacpMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set the load-balancing policy
+    pub fn with_hash_policy(mut self, policy: LagHashPolicy) -> Self {
+        self.hash_policy = Some(policy);
+        self
+    }
+
+    /// The group a port belongs to, if any
+    pub fn containing<'a>(lags: &'a [LagConfig], port: &str) -> Option<&'a LagConfig> {
+        lags.iter().find(|lag| lag.members.iter().any(|member| member.to_string() == port))
+    }
+
+    /// Check that a device's LAGs are consistent with each other and its interfaces
+    ///
+    /// Each group ID may appear once and needs at least one member. Members
+    /// must be among `interfaces`, matched by name, and each port may belong
+    /// to only one group.
+    pub fn check_conflicts(lags: &[LagConfig], interfaces: &[InterfaceConfig]) -> Result<(), LagConflict> {
+        let mut ids = HashSet::new();
+        let mut ports = HashSet::new();
+        for lag in lags {
+            if !ids.insert(lag.id) {
+                return Err(LagConflict::DuplicateId(lag.id));
+            }
+            if lag.members.is_empty() {
+                return Err(LagConflict::NoMembers(lag.id));
+            }
+            for port in &lag.members {
+                if !interfaces.iter().any(|iface| iface.name == port.to_string()) {
+                    return Err(LagConflict::UnknownMember { lag: lag.id, port: port.clone() });
+                }
+                if !ports.insert(port) {
+                    return Err(LagConflict::PortAlreadyAssigned(port.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Inconsistency between the LAGs configured on one device
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LagConflict {
+    /// The same group ID is configured twice
+    #[error("LAG {0} is configured more than once")]
+    DuplicateId(u16),
+    /// A group has no member ports
+    #[error("LAG {0} has no member ports")]
+    NoMembers(u16),
+    /// A member port is not one of the device's interfaces
+    #[error("LAG {lag} member {port} is not an interface of the device")]
+    UnknownMember {
+        /// Group naming the port
+        lag: u16,
+        /// Port that does not exist
+        port: PortId,
+    },
+    /// A port is a member of two groups
+    #[error("Port {0} is a member of more than one LAG")]
+    PortAlreadyAssigned(PortId),
+}
+
 /// Connection type between devices
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum ConnectionType {
     /// Physical ethernet connection
     Ethernet,
@@ -263,8 +849,18 @@ pub enum ConnectionType {
     Uplink,
 }
 
-/// Link speed
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+impl ConnectionType {
+    /// Whether source and target are distinct roles
+    ///
+    /// An uplink points from a device to its parent. Every other type is a
+    /// bidirectional link, the same link whichever end is called the source.
+    pub fn is_directional(&self) -> bool {
+        matches!(self, ConnectionType::Uplink)
+    }
+}
+
+/// Link speed, ordered slowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum LinkSpeed {
     /// 10 Mbps
     Mbps10,
@@ -286,6 +882,39 @@ pub enum LinkSpeed {
     Gbps100,
 }
 
+impl LinkSpeed {
+    /// Map a reported speed in Mbps to a standard link speed
+    pub fn from_mbps(mbps: u32) -> Option<Self> {
+        match mbps {
+            10 => Some(LinkSpeed::Mbps10),
+            100 => Some(LinkSpeed::Mbps100),
+            1000 => Some(LinkSpeed::Gbps1),
+            2500 => Some(LinkSpeed::Gbps2_5),
+            5000 => Some(LinkSpeed::Gbps5),
+            10000 => Some(LinkSpeed::Gbps10),
+            25000 => Some(LinkSpeed::Gbps25),
+            40000 => Some(LinkSpeed::Gbps40),
+            100000 => Some(LinkSpeed::Gbps100),
+            _ => None,
+        }
+    }
+
+    /// The speed in Mbps
+    pub fn mbps(&self) -> u32 {
+        match self {
+            LinkSpeed::Mbps10 => 10,
+            LinkSpeed::Mbps100 => 100,
+            LinkSpeed::Gbps1 => 1000,
+            LinkSpeed::Gbps2_5 => 2500,
+            LinkSpeed::Gbps5 => 5000,
+            LinkSpeed::Gbps10 => 10000,
+            LinkSpeed::Gbps25 => 25000,
+            LinkSpeed::Gbps40 => 40000,
+            LinkSpeed::Gbps100 => 100000,
+        }
+    }
+}
+
 impl fmt::Display for LinkSpeed {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -302,6 +931,489 @@ impl fmt::Display for LinkSpeed {
     }
 }
 
+/// Physical medium a port takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum PortMedia {
+    /// Twisted-pair copper (RJ45 or a copper SFP)
+    Copper,
+    /// Fiber optics
+    Fiber,
+}
+
+impl fmt::Display for PortMedia {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortMedia::Copper => write!(f, "copper"),
+            PortMedia::Fiber => write!(f, "fiber"),
+        }
+    }
+}
+
+/// Medium and speeds a port advertises
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PortCapabilities {
+    /// Physical medium
+    pub media: PortMedia,
+    /// Speeds the port can run at
+    pub speeds: Vec<LinkSpeed>,
+}
+
+impl PortCapabilities {
+    /// A port on `media` running at any of `speeds`
+    pub fn new(media: PortMedia, speeds: impl IntoIterator<Item = LinkSpeed>) -> Self {
+        Self {
+            media,
+            speeds: speeds.into_iter().collect(),
+        }
+    }
+
+    /// Whether the port can run at `speed`
+    pub fn supports(&self, speed: LinkSpeed) -> bool {
+        self.speeds.contains(&speed)
+    }
+
+    /// Speed a link between this port and `peer` comes up at
+    ///
+    /// Both ends must be on the same medium. The link runs at the fastest
+    /// speed both advertise, so a 1G port facing a 1G/10G port resolves to 1G.
+    pub fn negotiate(&self, peer: &PortCapabilities) -> Result<LinkSpeed, LinkSpeedError> {
+        if self.media != peer.media {
+            return Err(LinkSpeedError::IncompatibleMedia {
+                local: self.media,
+                peer: peer.media,
+            });
+        }
+        self.speeds
+            .iter()
+            .filter(|speed| peer.supports(**speed))
+            .max()
+            .copied()
+            .ok_or(LinkSpeedError::NoCommonSpeed)
+    }
+}
+
+/// Speed a port or link cannot run at
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LinkSpeedError {
+    /// A fixed speed the port does not advertise
+    #[error("Port {port} does not support {speed}")]
+    UnsupportedSpeed {
+        /// Port configured with the speed
+        port: String,
+        /// Rejected speed
+        speed: LinkSpeed,
+    },
+    /// The two ends of a link are on different media
+    #[error("Cannot link a {local} port to a {peer} port")]
+    IncompatibleMedia {
+        /// Medium at this end
+        local: PortMedia,
+        /// Medium at the far end
+        peer: PortMedia,
+    },
+    /// The two ends of a link advertise no speed in common
+    #[error("Link ends advertise no common speed")]
+    NoCommonSpeed,
+}
+
+/// Device health metric watched by the stats monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum HealthMetric {
+    /// CPU usage, percent
+    CpuPercent,
+    /// Memory usage, percent
+    MemoryPercent,
+    /// Temperature, degrees Celsius
+    TemperatureCelsius,
+}
+
+impl fmt::Display for HealthMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HealthMetric::CpuPercent => write!(f, "cpu_percent"),
+            HealthMetric::MemoryPercent => write!(f, "memory_percent"),
+            HealthMetric::TemperatureCelsius => write!(f, "temperature_celsius"),
+        }
+    }
+}
+
+/// Longest accepted tag key
+pub const MAX_TAG_KEY_LEN: usize = 32;
+
+/// Longest accepted tag value
+///
+/// Together with `MAX_TAG_KEY_LEN` this keeps `key:value` within NetBox's
+/// 100-character tag names.
+pub const MAX_TAG_VALUE_LEN: usize = 64;
+
+/// Free-form device label, e.g. `env:prod` or `rack:R12`
+///
+/// The value may be empty for plain labels such as `critical`. Keys are
+/// compared exactly, so `env:prod` and `Env:prod` are different tags.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+pub struct Tag {
+    key: String,
+    value: String,
+}
+
+impl Tag {
+    /// Create a tag from a key and value
+    ///
+    /// Keys must be non-empty and contain no `:`, whitespace or control
+    /// characters; values must contain no control characters.
+    pub fn new(key: impl Into<String>, value: impl Into<String>) -> Result<Self, TagError> {
+        let key = key.into();
+        let value = value.into();
+        if key.is_empty() {
+            return Err(TagError::EmptyKey);
+        }
+        if key.chars().any(|c| c == ':' || c.is_whitespace() || c.is_control())
+            || value.chars().any(char::is_control)
+        {
+            return Err(TagError::InvalidCharacter(format!("{}:{}", key, value)));
+        }
+        if key.chars().count() > MAX_TAG_KEY_LEN {
+            return Err(TagError::KeyTooLong(key));
+        }
+        if value.chars().count() > MAX_TAG_VALUE_LEN {
+            return Err(TagError::ValueTooLong(value));
+        }
+        Ok(Self { key, value })
+    }
+
+    /// Parse `key:value`, or a bare `key` for a tag without a value
+    pub fn parse(s: &str) -> Result<Self, TagError> {
+        match s.split_once(':') {
+            Some((key, value)) => Self::new(key, value),
+            None => Self::new(s, ""),
+        }
+    }
+
+    /// Tag key, e.g. `env`
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Tag value, e.g. `prod`; empty for a plain label
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.value.is_empty() {
+            write!(f, "{}", self.key)
+        } else {
+            write!(f, "{}:{}", self.key, self.value)
+        }
+    }
+}
+
+impl std::str::FromStr for Tag {
+    type Err = TagError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Tag validation error
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TagError {
+    /// The key is empty
+    #[error("Tag key must not be empty")]
+    EmptyKey,
+    /// The key contains `:`, whitespace or control characters, or the value control characters
+    #[error("Invalid character in tag {0}")]
+    InvalidCharacter(String),
+    /// The key is longer than `MAX_TAG_KEY_LEN` characters
+    #[error("Tag key {0} is longer than 32 characters")]
+    KeyTooLong(String),
+    /// The value is longer than `MAX_TAG_VALUE_LEN` characters
+    #[error("Tag value {0} is longer than 64 characters")]
+    ValueTooLong(String),
+}
+
+/// IP network in CIDR form, e.g. `10.0.0.0/24` or `2001:db8::/48`
+///
+/// Host bits are cleared on construction, so `10.0.0.7/24` is stored as
+/// `10.0.0.0/24`. Serialized as its CIDR string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct IpNetwork {
+    address: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNetwork {
+    /// Create a network, clearing any host bits of `address`
+    pub fn new(address: IpAddr, prefix_len: u8) -> Result<Self, IpNetworkError> {
+        let bits = address_bits(&address);
+        if prefix_len > bits {
+            return Err(IpNetworkError::InvalidPrefix(prefix_len));
+        }
+        let value = address_to_u128(address) & !host_mask(bits - prefix_len);
+        Ok(Self { address: address_from_u128(value, &address), prefix_len })
+    }
+
+    /// Parse CIDR notation
+    pub fn parse(s: &str) -> Result<Self, IpNetworkError> {
+        let invalid = || IpNetworkError::InvalidFormat(s.to_string());
+        let (address, prefix_len) = s.trim().split_once('/').ok_or_else(invalid)?;
+        let address = address.parse().map_err(|_| invalid())?;
+        let prefix_len = prefix_len.parse().map_err(|_| invalid())?;
+        Self::new(address, prefix_len)
+    }
+
+    /// Network address
+    pub fn network(&self) -> IpAddr {
+        self.address
+    }
+
+    /// Prefix length
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Whether `address` lies inside this network
+    pub fn contains(&self, address: IpAddr) -> bool {
+        address.is_ipv4() == self.address.is_ipv4()
+            && address_to_u128(address) & !self.host_mask() == self.first()
+    }
+
+    /// Whether the two networks share any address
+    pub fn overlaps(&self, other: &IpNetwork) -> bool {
+        self.contains(other.address) || other.contains(self.address)
+    }
+
+    /// The `n`th address of the network, counting the network address as 0
+    pub fn nth(&self, n: u128) -> Option<IpAddr> {
+        (n <= self.host_mask()).then(|| address_from_u128(self.first() + n, &self.address))
+    }
+
+    /// Addresses that can be assigned to hosts, lowest first
+    ///
+    /// Excludes the network address, and for IPv4 the broadcast address,
+    /// except on point-to-point `/31` and `/32` (`/127` and `/128`) networks.
+    pub fn hosts(&self) -> impl Iterator<Item = IpAddr> {
+        let family = self.address;
+        self.host_range().map(move |value| address_from_u128(value, &family))
+    }
+
+    /// Split into consecutive subnets of `new_prefix`, lowest first
+    ///
+    /// Empty if `new_prefix` is shorter than this network's prefix or longer
+    /// than the address width.
+    pub fn subnets(&self, new_prefix: u8) -> impl Iterator<Item = IpNetwork> {
+        let family = self.address;
+        let bits = address_bits(&family);
+        let valid = new_prefix >= self.prefix_len && new_prefix <= bits;
+        // `None` when a single subnet spans the whole address space
+        let step = 1u128.checked_shl(u32::from(bits.saturating_sub(new_prefix)));
+        let last = self.last();
+
+        std::iter::successors(valid.then_some(self.first()), move |current| {
+            current.checked_add(step?).filter(|next| *next <= last)
+        })
+        .map(move |value| IpNetwork { address: address_from_u128(value, &family), prefix_len: new_prefix })
+    }
+
+    fn host_mask(&self) -> u128 {
+        host_mask(address_bits(&self.address) - self.prefix_len)
+    }
+
+    fn first(&self) -> u128 {
+        address_to_u128(self.address)
+    }
+
+    fn last(&self) -> u128 {
+        self.first() | self.host_mask()
+    }
+
+    fn host_range(&self) -> RangeInclusive<u128> {
+        let host_bits = address_bits(&self.address) - self.prefix_len;
+        match (self.address, host_bits) {
+            // Point-to-point networks use every address
+            (_, 0 | 1) => self.first()..=self.last(),
+            (IpAddr::V4(_), _) => self.first() + 1..=self.last() - 1,
+            (IpAddr::V6(_), _) => self.first() + 1..=self.last(),
+        }
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix_len)
+    }
+}
+
+impl std::str::FromStr for IpNetwork {
+    type Err = IpNetworkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for IpNetwork {
+    type Error = IpNetworkError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::parse(&s)
+    }
+}
+
+impl From<IpNetwork> for String {
+    fn from(network: IpNetwork) -> Self {
+        network.to_string()
+    }
+}
+
+fn address_bits(address: &IpAddr) -> u8 {
+    if address.is_ipv4() { 32 } else { 128 }
+}
+
+fn address_to_u128(address: IpAddr) -> u128 {
+    match address {
+        IpAddr::V4(v4) => u128::from(u32::from(v4)),
+        IpAddr::V6(v6) => u128::from(v6),
+    }
+}
+
+/// Convert back to an address of the same family as `family`
+fn address_from_u128(value: u128, family: &IpAddr) -> IpAddr {
+    match family {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::from(value as u32)),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(value)),
+    }
+}
+
+fn host_mask(host_bits: u8) -> u128 {
+    1u128.checked_shl(u32::from(host_bits)).map_or(u128::MAX, |size| size - 1)
+}
+
+/// IP network parsing error
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum IpNetworkError {
+    /// Not in `address/prefix` form
+    #[error("Invalid network {0}: expected address/prefix")]
+    InvalidFormat(String),
+    /// Prefix longer than the address width
+    #[error("Invalid prefix length {0}")]
+    InvalidPrefix(u8),
+}
+
+/// Hands out non-overlapping host addresses and subnets from one network
+///
+/// Topology builders should draw every management address and sub-prefix
+/// from one allocator rather than computing offsets, so no two devices can
+/// be given the same address. Hosts and subnets are allocated lowest first;
+/// hosts skip allocated subnets, and subnets never contain an allocated host.
+#[derive(Debug, Clone)]
+pub struct IpAllocator {
+    network: IpNetwork,
+    hosts: BTreeSet<IpAddr>,
+    subnets: Vec<IpNetwork>,
+}
+
+impl IpAllocator {
+    /// Create an allocator with nothing allocated
+    pub fn new(network: IpNetwork) -> Self {
+        Self {
+            network,
+            hosts: BTreeSet::new(),
+            subnets: Vec::new(),
+        }
+    }
+
+    /// The network addresses are allocated from
+    pub fn network(&self) -> IpNetwork {
+        self.network
+    }
+
+    /// Allocate the lowest free host address
+    pub fn allocate_host(&mut self) -> Result<IpAddr, IpAllocationError> {
+        let range = self.network.host_range();
+        let mut value = *range.start();
+
+        while value <= *range.end() {
+            let address = address_from_u128(value, &self.network.address);
+            if let Some(subnet) = self.subnets.iter().find(|s| s.contains(address)) {
+                // Jump past the whole subnet rather than walking it
+                match subnet.last().checked_add(1) {
+                    Some(next) => value = next,
+                    None => break,
+                }
+                continue;
+            }
+            if self.hosts.insert(address) {
+                return Ok(address);
+            }
+            match value.checked_add(1) {
+                Some(next) => value = next,
+                None => break,
+            }
+        }
+
+        Err(IpAllocationError::Exhausted(self.network))
+    }
+
+    /// Mark a specific address as allocated, e.g. a gateway
+    pub fn reserve(&mut self, address: IpAddr) -> Result<(), IpAllocationError> {
+        if !self.network.contains(address) {
+            return Err(IpAllocationError::OutOfRange(address));
+        }
+        if self.is_allocated(address) {
+            return Err(IpAllocationError::AlreadyAllocated(address));
+        }
+        self.hosts.insert(address);
+        Ok(())
+    }
+
+    /// Allocate the lowest free subnet of `prefix_len`
+    pub fn allocate_subnet(&mut self, prefix_len: u8) -> Result<IpNetwork, IpAllocationError> {
+        if prefix_len < self.network.prefix_len || prefix_len > address_bits(&self.network.address) {
+            return Err(IpAllocationError::InvalidPrefix(prefix_len));
+        }
+
+        let subnet = self.network
+            .subnets(prefix_len)
+            .find(|candidate| {
+                let first = candidate.network();
+                let last = address_from_u128(candidate.last(), &first);
+                !self.subnets.iter().any(|s| s.overlaps(candidate))
+                    && self.hosts.range(first..=last).next().is_none()
+            })
+            .ok_or(IpAllocationError::Exhausted(self.network))?;
+
+        self.subnets.push(subnet);
+        Ok(subnet)
+    }
+
+    /// Whether `address` has been allocated, directly or inside a subnet
+    pub fn is_allocated(&self, address: IpAddr) -> bool {
+        self.hosts.contains(&address) || self.subnets.iter().any(|s| s.contains(address))
+    }
+}
+
+/// IP allocation error
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum IpAllocationError {
+    /// No free address or subnet of the requested size is left
+    #[error("Network {0} is exhausted")]
+    Exhausted(IpNetwork),
+    /// Prefix shorter than the network's or longer than the address width
+    #[error("Invalid subnet prefix length {0}")]
+    InvalidPrefix(u8),
+    /// Address lies outside the allocator's network
+    #[error("Address {0} is outside the network")]
+    OutOfRange(IpAddr),
+    /// Address has already been allocated
+    #[error("Address {0} is already allocated")]
+    AlreadyAllocated(IpAddr),
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -351,6 +1463,10 @@ mod tests {
         // Lowercase
         let mac = MacAddress::parse("de:ad:be:ef:ca:fe");
         assert!(mac.is_ok());
+
+        // Cisco dotted
+        let mac = MacAddress::parse("0011.2233.4455");
+        assert_eq!(mac.unwrap(), MacAddress::parse("00:11:22:33:44:55").unwrap());
     }
 
     #[test]
@@ -395,6 +1511,45 @@ mod tests {
         assert_eq!(mac, parsed);
     }
 
+    // ==========================================================================
+    // MacPrefix Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_mac_prefix_parse() {
+        let oui = MacPrefix::from_oui([0x00, 0x11, 0x22]);
+        assert_eq!(MacPrefix::parse("00:11:22").unwrap(), oui);
+        assert_eq!(MacPrefix::parse("00:11:22:*").unwrap(), oui);
+        assert_eq!(MacPrefix::parse("00-11-22-*").unwrap(), oui);
+        assert_eq!(MacPrefix::parse("001122*").unwrap(), oui);
+        assert_eq!(MacPrefix::parse("aa:BB").unwrap().octets(), &[0xaa, 0xbb]);
+        assert_eq!(MacPrefix::parse("00:11:22:33:44:55").unwrap().octets().len(), 6);
+
+        assert_eq!(oui.to_string(), "00:11:22:*");
+        assert_eq!(MacPrefix::parse("00:11:22:33:44:55").unwrap().to_string(), "00:11:22:33:44:55");
+    }
+
+    #[test]
+    fn test_mac_prefix_parse_invalid() {
+        assert!(matches!(MacPrefix::parse("00:11:22:33:44:55:66"), Err(MacAddressError::InvalidLength)));
+        assert!(matches!(MacPrefix::parse("*"), Err(MacAddressError::InvalidLength)));
+        assert!(matches!(MacPrefix::parse("00:1"), Err(MacAddressError::InvalidLength)));
+        assert!(matches!(MacPrefix::parse("00:GG:*"), Err(MacAddressError::InvalidFormat)));
+        assert!(matches!(MacPrefix::parse("0*:11"), Err(MacAddressError::InvalidFormat)));
+    }
+
+    #[test]
+    fn test_mac_prefix_matches() {
+        let mac = MacAddress::parse("00:11:22:33:44:55").unwrap();
+
+        for prefix in ["00", "00:11:22", "00-11-22-33-*", "00:11:22:33:44:55"] {
+            assert!(MacPrefix::parse(prefix).unwrap().matches(&mac), "{} should match", prefix);
+        }
+        for prefix in ["01", "00:11:23", "00:11:22:33:45:*", "00:11:22:33:44:56"] {
+            assert!(!MacPrefix::parse(prefix).unwrap().matches(&mac), "{} should not match", prefix);
+        }
+    }
+
     // ==========================================================================
     // DeviceType Tests
     // ==========================================================================
@@ -404,6 +1559,7 @@ mod tests {
         assert_eq!(format!("{}", DeviceType::Gateway), "Gateway");
         assert_eq!(format!("{}", DeviceType::Switch), "Switch");
         assert_eq!(format!("{}", DeviceType::AccessPoint), "AccessPoint");
+        assert_eq!(format!("{}", DeviceType::Firewall), "Firewall");
         assert_eq!(
             format!("{}", DeviceType::Generic { model: "Custom".to_string() }),
             "Generic(Custom)"
@@ -450,6 +1606,79 @@ mod tests {
         assert_eq!(format!("{}", port2), "port[3]");
     }
 
+    #[test]
+    fn test_port_name_parses_cisco() {
+        let port = PortName::parse("Gi1/0/24").unwrap();
+        assert_eq!(port.prefix, "GigabitEthernet");
+        assert_eq!((port.stack, port.module, port.index), (Some(1), Some(0), 24));
+        assert_eq!(port.to_string(), "GigabitEthernet1/0/24");
+
+        let port = PortName::parse("te0/1").unwrap();
+        assert_eq!(port.prefix, "TenGigabitEthernet");
+        assert_eq!((port.stack, port.module, port.index), (None, Some(0), 1));
+    }
+
+    #[test]
+    fn test_port_name_parses_unifi_and_generic() {
+        let port = PortName::parse("port5").unwrap();
+        assert_eq!(port.prefix, "port");
+        assert_eq!((port.stack, port.module, port.index), (None, None, 5));
+
+        let port = PortName::parse("eth0").unwrap();
+        assert_eq!(port.prefix, "eth");
+        assert_eq!(port.index, 0);
+        assert_eq!(port.to_string(), "eth0");
+    }
+
+    #[test]
+    fn test_port_name_parses_juniper_and_arista() {
+        let port = PortName::parse("ge-0/0/1").unwrap();
+        assert_eq!(port.prefix, "ge-");
+        assert_eq!((port.stack, port.module, port.index), (Some(0), Some(0), 1));
+        assert_eq!(port.to_string(), "ge-0/0/1");
+
+        assert_eq!(PortName::parse("Et49/1").unwrap().to_string(), "Ethernet49/1");
+    }
+
+    #[test]
+    fn test_port_name_rejects_invalid() {
+        for name in ["", "mgmt", "Gi1/0/x", "Gi1//2", "Gi1/0/1/2", "eth0.100"] {
+            assert!(
+                matches!(PortName::parse(name), Err(PortNameError::InvalidFormat(_))),
+                "{name} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_port_id_parse_normalizes_vendor_names() {
+        assert_eq!(PortId::parse("gi1/0/24").unwrap(), PortId::new("GigabitEthernet1/0/24"));
+        assert_eq!(PortId::parse("port5").unwrap(), PortId::new("port5"));
+        assert_eq!(PortId::new("Fa0/3").port_name().unwrap().prefix, "FastEthernet");
+        assert!(PortId::new("mgmt").port_name().is_none());
+    }
+
+    #[test]
+    fn test_port_range_expands() {
+        let range = PortRange::parse("port1-4").unwrap();
+        let ports: Vec<_> = range.ports().collect();
+        assert_eq!(ports, ["port1", "port2", "port3", "port4"].map(PortId::new));
+
+        let range = PortRange::parse("Gi1/0/1-24").unwrap();
+        assert_eq!(range.ports().count(), 24);
+        assert_eq!(range.to_string(), "GigabitEthernet1/0/1-24");
+        assert!(range.contains(&PortName::parse("GigabitEthernet1/0/12").unwrap()));
+        assert!(!range.contains(&PortName::parse("GigabitEthernet2/0/12").unwrap()));
+    }
+
+    #[test]
+    fn test_port_range_single_port_and_reversed() {
+        let single = PortRange::parse("ge-0/0/1").unwrap();
+        assert_eq!(single.ports().collect::<Vec<_>>(), vec![PortId::new("ge-0/0/1")]);
+
+        assert!(matches!(PortRange::parse("eth4-1"), Err(PortNameError::InvalidRange(_))));
+    }
+
     // ==========================================================================
     // VlanConfig Tests
     // ==========================================================================
@@ -489,6 +1718,129 @@ mod tests {
         assert!(vlan4094.is_ok());
     }
 
+    #[test]
+    fn test_vlan_check_conflicts_clean() {
+        let vlans = vec![
+            VlanConfig::new(10, "users").unwrap().with_access_port(PortId::new("ge-0/0/1")),
+            VlanConfig::new(20, "voice").unwrap().with_access_port(PortId::new("ge-0/0/2")),
+            VlanConfig::new(30, "guests").unwrap(),
+        ];
+        assert_eq!(VlanConfig::check_conflicts(&vlans), Ok(()));
+        assert_eq!(VlanConfig::check_conflicts(&[]), Ok(()));
+    }
+
+    #[test]
+    fn test_vlan_check_conflicts_duplicate_id() {
+        let vlans = vec![
+            VlanConfig::new(10, "users").unwrap(),
+            VlanConfig::new(10, "staff").unwrap(),
+        ];
+        assert_eq!(VlanConfig::check_conflicts(&vlans), Err(VlanConflict::DuplicateId(10)));
+    }
+
+    #[test]
+    fn test_vlan_check_conflicts_port_assigned_twice() {
+        let port = PortId::new("ge-0/0/1");
+        let vlans = vec![
+            VlanConfig::new(10, "users").unwrap().with_access_port(port.clone()),
+            VlanConfig::new(20, "voice").unwrap().with_access_port(port.clone()),
+        ];
+        assert_eq!(VlanConfig::check_conflicts(&vlans), Err(VlanConflict::PortAlreadyAssigned(port)));
+    }
+
+    // ==========================================================================
+    // LAG Tests
+    // ==========================================================================
+
+    fn lag_interfaces(names: &[&str]) -> Vec<InterfaceConfig> {
+        names
+            .iter()
+            .map(|name| InterfaceConfig {
+                name: name.to_string(),
+                ip_address: None,
+                prefix_len: None,
+                vlan_id: None,
+                enabled: true,
+                mtu: None,
+                speed: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lag_check_conflicts_two_member_lacp_bond() {
+        let interfaces = lag_interfaces(&["eth0", "eth1"]);
+        let lag = LagConfig::new(1)
+            .with_member(PortId::new("eth0"))
+            .with_member(PortId::new("eth1"));
+        assert_eq!(lag.mode, LacpMode::Active);
+        assert_eq!(LagConfig::check_conflicts(&[lag], &interfaces), Ok(()));
+    }
+
+    #[test]
+    fn test_lag_check_conflicts_unknown_member() {
+        let interfaces = lag_interfaces(&["eth0"]);
+        let lags = vec![LagConfig::new(1).with_member(PortId::new("eth0")).with_member(PortId::new("eth9"))];
+        assert_eq!(
+            LagConfig::check_conflicts(&lags, &interfaces),
+            Err(LagConflict::UnknownMember { lag: 1, port: PortId::new("eth9") })
+        );
+    }
+
+    #[test]
+    fn test_lag_check_conflicts_port_in_two_groups() {
+        let interfaces = lag_interfaces(&["eth0", "eth1"]);
+        let lags = vec![
+            LagConfig::new(1).with_member(PortId::new("eth0")),
+            LagConfig::new(2).with_member(PortId::new("eth1")).with_member(PortId::new("eth0")),
+        ];
+        assert_eq!(
+            LagConfig::check_conflicts(&lags, &interfaces),
+            Err(LagConflict::PortAlreadyAssigned(PortId::new("eth0")))
+        );
+        assert_eq!(
+            LagConfig::check_conflicts(&[LagConfig::new(3)], &interfaces),
+            Err(LagConflict::NoMembers(3))
+        );
+    }
+
+    // ==========================================================================
+    // OUI Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_mac_oui() {
+        let mac = MacAddress::parse("24:a4:3c:12:34:56").unwrap();
+        assert_eq!(mac.oui(), [0x24, 0xa4, 0x3c]);
+    }
+
+    #[test]
+    fn test_mac_vendor_lookup() {
+        assert_eq!(MacAddress::parse("24:a4:3c:12:34:56").unwrap().vendor(), Some("Ubiquiti"));
+        assert_eq!(MacAddress::parse("0000.0c07.ac01").unwrap().vendor(), Some("Cisco"));
+        assert_eq!(MacAddress::parse("02:00:00:12:34:56").unwrap().vendor(), None);
+    }
+
+    #[test]
+    fn test_device_type_from_mac_oui() {
+        let arista = MacAddress::parse("00:1c:73:aa:bb:cc").unwrap();
+        let mikrotik = MacAddress::parse("4c:5e:0c:aa:bb:cc").unwrap();
+        let ubiquiti = MacAddress::parse("24:a4:3c:aa:bb:cc").unwrap();
+        let fortinet = MacAddress::parse("00:09:0f:aa:bb:cc").unwrap();
+        let unknown = MacAddress::parse("02:00:00:aa:bb:cc").unwrap();
+
+        assert_eq!(DeviceType::from_mac_oui(arista), Some(DeviceType::Switch));
+        assert_eq!(DeviceType::from_mac_oui(fortinet), Some(DeviceType::Firewall));
+        assert_eq!(DeviceType::from_mac_oui(mikrotik), Some(DeviceType::Gateway));
+        assert_eq!(DeviceType::from_mac_oui(ubiquiti), None); // ships every device class
+        assert_eq!(DeviceType::from_mac_oui(unknown), None);
+    }
+
+    #[test]
+    fn test_oui_table_sorted() {
+        assert!(oui::OUI_TABLE.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
     // ==========================================================================
     // LinkSpeed Tests
     // ==========================================================================
@@ -502,6 +1854,14 @@ mod tests {
         assert_eq!(format!("{}", LinkSpeed::Gbps100), "100 Gbps");
     }
 
+    #[test]
+    fn test_link_speed_from_mbps() {
+        assert_eq!(LinkSpeed::from_mbps(1000), Some(LinkSpeed::Gbps1));
+        assert_eq!(LinkSpeed::from_mbps(2500), Some(LinkSpeed::Gbps2_5));
+        assert_eq!(LinkSpeed::from_mbps(100000), Some(LinkSpeed::Gbps100));
+        assert_eq!(LinkSpeed::from_mbps(1234), None);
+    }
+
     // ==========================================================================
     // ConnectionType Tests
     // ==========================================================================
@@ -512,6 +1872,35 @@ mod tests {
         assert_ne!(ConnectionType::Ethernet, ConnectionType::Fiber);
     }
 
+    // ==========================================================================
+    // Tag Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_tag_parse_and_display() {
+        let tag = Tag::parse("rack:R12").unwrap();
+        assert_eq!((tag.key(), tag.value()), ("rack", "R12"));
+        assert_eq!(tag.to_string(), "rack:R12");
+
+        let label = Tag::parse("critical").unwrap();
+        assert_eq!(label.value(), "");
+        assert_eq!(label.to_string(), "critical");
+
+        // Only the first colon separates key and value
+        assert_eq!(Tag::parse("url:http://x").unwrap().value(), "http://x");
+    }
+
+    #[test]
+    fn test_tag_validation() {
+        assert_eq!(Tag::parse(""), Err(TagError::EmptyKey));
+        assert_eq!(Tag::parse(":prod"), Err(TagError::EmptyKey));
+        assert!(matches!(Tag::new("my env", "prod"), Err(TagError::InvalidCharacter(_))));
+        assert!(matches!(Tag::new("env", "pr\nod"), Err(TagError::InvalidCharacter(_))));
+        assert!(matches!(Tag::new("k".repeat(33), ""), Err(TagError::KeyTooLong(_))));
+        assert!(matches!(Tag::new("env", "v".repeat(65)), Err(TagError::ValueTooLong(_))));
+        assert!(Tag::new("k".repeat(32), "v".repeat(64)).is_ok());
+    }
+
     // ==========================================================================
     // InterfaceConfig Tests
     // ==========================================================================
@@ -524,11 +1913,175 @@ mod tests {
             prefix_len: Some(24),
             vlan_id: Some(100),
             enabled: true,
+            mtu: None,
+            speed: None,
         };
 
         assert!(iface.enabled);
+        assert!(iface.validate().is_ok());
         assert_eq!(iface.name, "eth0");
         assert_eq!(iface.vlan_id, Some(100));
         assert_eq!(iface.prefix_len, Some(24));
     }
+
+    #[test]
+    fn test_interface_mtu_bounds() {
+        let iface = |mtu| InterfaceConfig {
+            name: "eth1".to_string(),
+            ip_address: None,
+            prefix_len: None,
+            vlan_id: None,
+            enabled: true,
+            mtu: Some(mtu),
+            speed: None,
+        };
+
+        assert!(iface(1500).validate().is_ok());
+        assert!(iface(9000).validate().is_ok());
+        assert!(iface(MAX_MTU).validate().is_ok());
+        assert!(matches!(
+            iface(MIN_MTU - 1).validate(),
+            Err(InterfaceError::InvalidMtu { ref interface, mtu: 67 }) if interface == "eth1"
+        ));
+        assert!(iface(MAX_MTU + 1).validate().is_err());
+    }
+
+    #[test]
+    fn test_interface_speed_must_be_advertised() {
+        let iface = |speed| InterfaceConfig {
+            name: "Gi1/0/1".to_string(),
+            ip_address: None,
+            prefix_len: None,
+            vlan_id: None,
+            enabled: true,
+            mtu: None,
+            speed,
+        };
+        let gigabit = PortCapabilities::new(PortMedia::Copper, [LinkSpeed::Mbps10, LinkSpeed::Mbps100, LinkSpeed::Gbps1]);
+
+        assert_eq!(iface(None).check_speed(&gigabit), Ok(()));
+        assert_eq!(iface(Some(LinkSpeed::Gbps1)).check_speed(&gigabit), Ok(()));
+        assert_eq!(
+            iface(Some(LinkSpeed::Gbps100)).check_speed(&gigabit),
+            Err(LinkSpeedError::UnsupportedSpeed { port: "Gi1/0/1".to_string(), speed: LinkSpeed::Gbps100 })
+        );
+        assert_eq!(
+            gigabit.negotiate(&PortCapabilities::new(PortMedia::Copper, [LinkSpeed::Gbps10])),
+            Err(LinkSpeedError::NoCommonSpeed)
+        );
+    }
+
+    // ==========================================================================
+    // IpNetwork Tests
+    // ==========================================================================
+
+    fn net(s: &str) -> IpNetwork {
+        IpNetwork::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_ip_network_clears_host_bits() {
+        let network = net("10.1.2.77/24");
+        assert_eq!(network.to_string(), "10.1.2.0/24");
+        assert_eq!(network.prefix_len(), 24);
+        assert!(network.contains("10.1.2.255".parse().unwrap()));
+        assert!(!network.contains("10.1.3.0".parse().unwrap()));
+        assert!(!network.contains("::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_ip_network_rejects_invalid_input() {
+        assert_eq!(IpNetwork::parse("10.0.0.0/33"), Err(IpNetworkError::InvalidPrefix(33)));
+        assert!(matches!(IpNetwork::parse("10.0.0.0"), Err(IpNetworkError::InvalidFormat(_))));
+        assert!(matches!(IpNetwork::parse("not-an-ip/24"), Err(IpNetworkError::InvalidFormat(_))));
+    }
+
+    #[test]
+    fn test_ip_network_subnets() {
+        let subnets: Vec<String> = net("10.0.0.0/24").subnets(26).map(|s| s.to_string()).collect();
+        assert_eq!(subnets, vec!["10.0.0.0/26", "10.0.0.64/26", "10.0.0.128/26", "10.0.0.192/26"]);
+
+        assert_eq!(net("2001:db8::/48").subnets(64).nth(1), Some(net("2001:db8:0:1::/64")));
+        assert_eq!(net("10.0.0.0/24").subnets(24).collect::<Vec<_>>(), vec![net("10.0.0.0/24")]);
+        assert_eq!(net("10.0.0.0/24").subnets(16).count(), 0);
+        assert_eq!(net("10.0.0.0/24").subnets(33).count(), 0);
+    }
+
+    #[test]
+    fn test_ip_network_hosts() {
+        let hosts: Vec<IpAddr> = net("192.168.1.0/30").hosts().collect();
+        assert_eq!(hosts, vec!["192.168.1.1".parse::<IpAddr>().unwrap(), "192.168.1.2".parse().unwrap()]);
+        assert_eq!(net("192.168.1.0/24").hosts().count(), 254);
+        assert_eq!(net("192.168.1.0/31").hosts().count(), 2);
+        assert_eq!(net("192.168.1.0/24").nth(10), Some("192.168.1.10".parse().unwrap()));
+        assert_eq!(net("192.168.1.0/24").nth(256), None);
+    }
+
+    #[test]
+    fn test_ip_network_serializes_as_cidr() {
+        let network = net("10.0.0.0/8");
+        let json = serde_json::to_string(&network).unwrap();
+        assert_eq!(json, "\"10.0.0.0/8\"");
+        assert_eq!(serde_json::from_str::<IpNetwork>(&json).unwrap(), network);
+        assert!(serde_json::from_str::<IpNetwork>("\"10.0.0.0/99\"").is_err());
+    }
+
+    // ==========================================================================
+    // IpAllocator Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_allocator_hands_out_distinct_hosts() {
+        let mut allocator = IpAllocator::new(net("10.0.0.0/24"));
+
+        let hosts: Vec<IpAddr> = (0..254).map(|_| allocator.allocate_host().unwrap()).collect();
+
+        assert_eq!(hosts.iter().collect::<HashSet<_>>().len(), 254);
+        assert_eq!(hosts[0], "10.0.0.1".parse::<IpAddr>().unwrap());
+        assert_eq!(hosts[253], "10.0.0.254".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_allocator_reports_exhaustion() {
+        let mut allocator = IpAllocator::new(net("10.0.0.0/30"));
+        allocator.allocate_host().unwrap();
+        allocator.allocate_host().unwrap();
+
+        assert_eq!(allocator.allocate_host(), Err(IpAllocationError::Exhausted(net("10.0.0.0/30"))));
+        assert_eq!(allocator.allocate_subnet(31), Err(IpAllocationError::Exhausted(net("10.0.0.0/30"))));
+        assert_eq!(allocator.allocate_subnet(29), Err(IpAllocationError::InvalidPrefix(29)));
+    }
+
+    #[test]
+    fn test_allocator_keeps_hosts_and_subnets_apart() {
+        let mut allocator = IpAllocator::new(net("10.0.0.0/24"));
+        allocator.reserve("10.0.0.1".parse().unwrap()).unwrap();
+
+        // The first /26 holds the reserved gateway, so the subnet comes after it
+        let subnet = allocator.allocate_subnet(26).unwrap();
+        assert_eq!(subnet, net("10.0.0.64/26"));
+
+        // Hosts fill the first /26 and then skip the allocated subnet
+        let hosts: Vec<IpAddr> = (0..62).map(|_| allocator.allocate_host().unwrap()).collect();
+        assert_eq!(hosts[0], "10.0.0.2".parse::<IpAddr>().unwrap());
+        assert_eq!(allocator.allocate_host().unwrap(), "10.0.0.128".parse::<IpAddr>().unwrap());
+        assert!(hosts.iter().all(|h| !subnet.contains(*h)));
+
+        assert_eq!(allocator.allocate_subnet(26).unwrap(), net("10.0.0.192/26"));
+    }
+
+    #[test]
+    fn test_allocator_reserve_rejects_taken_and_foreign_addresses() {
+        let mut allocator = IpAllocator::new(net("10.0.0.0/24"));
+        let subnet = allocator.allocate_subnet(28).unwrap();
+        let gateway: IpAddr = "10.0.0.20".parse().unwrap();
+        allocator.reserve(gateway).unwrap();
+
+        assert_eq!(allocator.reserve(gateway), Err(IpAllocationError::AlreadyAllocated(gateway)));
+        let inside = subnet.nth(3).unwrap();
+        assert_eq!(allocator.reserve(inside), Err(IpAllocationError::AlreadyAllocated(inside)));
+        let foreign: IpAddr = "10.0.1.1".parse().unwrap();
+        assert_eq!(allocator.reserve(foreign), Err(IpAllocationError::OutOfRange(foreign)));
+        assert!(allocator.is_allocated(gateway));
+    }
 }
@@ -90,15 +90,20 @@ pub mod adapters;
 // Re-export key types
 pub use domain::{
     // Value objects
-    DeviceId, TopologyId, ConnectionId, MacAddress, DeviceType,
-    PortId, InterfaceConfig, VlanConfig, ConnectionType, LinkSpeed,
+    DeviceId, TopologyId, ConnectionId, SiteId, MacAddress, MacPrefix, DeviceType,
+    PortId, PortName, PortRange, InterfaceConfig, ConfigDiff, VlanConfig, LagConfig, ConnectionType, LinkSpeed, PortCapabilities, PortMedia, HealthMetric, IpNetwork, IpAllocator,
     // Aggregates
     NetworkDeviceAggregate, DeviceState, AggregateError,
+    NetworkConnectionAggregate, ConnectionState, SiteAggregate,
     // Events and commands
-    NetworkEvent, NetworkCommand,
+    NetworkEvent, NetworkCommand, CommandHandler,
     // Ports
     DeviceControlPort, InventoryPort, DiscoveryPort,
     NetworkManagementPort, EventStorePort, PortError,
+    // Projections
+    Projection, DeviceCountByState,
+    // Clock
+    Clock, SystemClock, MockClock,
     // Functor types
     NetworkFunctor, NetworkKanExtension, VendorExtension, InventoryExtension,
     DomainObject, ExtendedRepresentation, FunctorError,
@@ -111,8 +116,14 @@ pub use domain::{
 };
 
 pub use adapters::{
-    UniFiAdapter, NetBoxAdapter,
+    UniFiAdapter, CiscoIosAdapter, MikroTikAdapter, OpnSenseAdapter, AristaAdapter, JuniperAdapter, GenericSshAdapter, NetBoxAdapter, LldpDiscovery,
     NatsEventStore, NatsEventStoreConfig, NatsEventSubscriber, NatsEventAck,
+    WebhookConfig, WebhookForwarder,
+    CredentialSource, Credentials, EnvCredentials, FileCredentials, FnCredentials,
 };
 
 pub mod service;
+pub mod telemetry;
+
+#[cfg(feature = "api")]
+pub mod api;
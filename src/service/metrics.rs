@@ -0,0 +1,117 @@
+//! Operational metrics for `NetworkService`
+//!
+//! With the `metrics` feature enabled every service records into its own
+//! Prometheus recorder, so services never fight over the process-wide
+//! `metrics` recorder and each can be scraped through
+//! `NetworkService::metrics_handle()`. Without the feature the recording
+//! calls compile to nothing.
+//!
+//! | Metric                       | Type      | Labels               |
+//! |------------------------------|-----------|----------------------|
+//! | `devices_discovered_total`   | counter   | `vendor`             |
+//! | `adoptions_failed_total`     | counter   | `vendor`             |
+//! | `inventory_syncs_total`      | counter   | `system`, `result`   |
+//! | `discovery_duration_seconds` | histogram | `vendor`             |
+
+use std::time::Duration;
+
+/// New devices found by discovery
+pub const DEVICES_DISCOVERED_TOTAL: &str = "devices_discovered_total";
+/// Adoptions the service or the vendor rejected
+pub const ADOPTIONS_FAILED_TOTAL: &str = "adoptions_failed_total";
+/// Inventory sync attempts, labelled `result="success"` or `result="failure"`
+pub const INVENTORY_SYNCS_TOTAL: &str = "inventory_syncs_total";
+/// Wall-clock time of each discovery run, including failed runs
+pub const DISCOVERY_DURATION_SECONDS: &str = "discovery_duration_seconds";
+
+/// Histogram buckets for discovery runs, in seconds
+#[cfg(feature = "metrics")]
+const DISCOVERY_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+#[cfg(feature = "metrics")]
+pub(crate) use enabled::ServiceMetrics;
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) use disabled::ServiceMetrics;
+
+#[cfg(feature = "metrics")]
+mod enabled {
+    use super::*;
+    use metrics::{Key, Label, Level, Metadata, Recorder};
+    use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle, PrometheusRecorder};
+
+    /// Prometheus recorder owned by one service
+    pub(crate) struct ServiceMetrics {
+        recorder: PrometheusRecorder,
+    }
+
+    impl ServiceMetrics {
+        pub(crate) fn new() -> Self {
+            let recorder = PrometheusBuilder::new()
+                .set_buckets_for_metric(Matcher::Full(DISCOVERY_DURATION_SECONDS.to_string()), DISCOVERY_BUCKETS)
+                .expect("discovery buckets are non-empty")
+                .build_recorder();
+            Self { recorder }
+        }
+
+        pub(crate) fn handle(&self) -> PrometheusHandle {
+            self.recorder.handle()
+        }
+
+        pub(crate) fn devices_discovered(&self, vendor: &str, count: usize) {
+            self.recorder
+                .register_counter(&key(DEVICES_DISCOVERED_TOTAL, &[("vendor", vendor)]), &metadata())
+                .increment(count as u64);
+        }
+
+        pub(crate) fn adoption_failed(&self, vendor: &str) {
+            self.recorder
+                .register_counter(&key(ADOPTIONS_FAILED_TOTAL, &[("vendor", vendor)]), &metadata())
+                .increment(1);
+        }
+
+        pub(crate) fn inventory_synced(&self, system: &str, succeeded: bool) {
+            let result = if succeeded { "success" } else { "failure" };
+            self.recorder
+                .register_counter(&key(INVENTORY_SYNCS_TOTAL, &[("system", system), ("result", result)]), &metadata())
+                .increment(1);
+        }
+
+        pub(crate) fn discovery_finished(&self, vendor: &str, elapsed: Duration) {
+            self.recorder
+                .register_histogram(&key(DISCOVERY_DURATION_SECONDS, &[("vendor", vendor)]), &metadata())
+                .record(elapsed.as_secs_f64());
+        }
+    }
+
+    fn key(name: &'static str, labels: &[(&'static str, &str)]) -> Key {
+        let labels: Vec<Label> = labels.iter().map(|(k, v)| Label::new(*k, v.to_string())).collect();
+        Key::from_parts(name, labels)
+    }
+
+    fn metadata() -> Metadata<'static> {
+        Metadata::new(module_path!(), Level::INFO, Some(module_path!()))
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+mod disabled {
+    use super::*;
+
+    /// Stand-in that records nothing when the `metrics` feature is off
+    pub(crate) struct ServiceMetrics;
+
+    impl ServiceMetrics {
+        pub(crate) fn new() -> Self {
+            Self
+        }
+
+        pub(crate) fn devices_discovered(&self, _vendor: &str, _count: usize) {}
+
+        pub(crate) fn adoption_failed(&self, _vendor: &str) {}
+
+        pub(crate) fn inventory_synced(&self, _system: &str, _succeeded: bool) {}
+
+        pub(crate) fn discovery_finished(&self, _vendor: &str, _elapsed: Duration) {}
+    }
+}
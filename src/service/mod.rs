@@ -35,20 +35,410 @@
 //!     .await?;
 //!
 //! // Discover and provision devices
-//! let devices = service.discover_and_provision().await?;
+//! let report = service.discover_and_provision().await?;
+//! for (device_id, error) in &report.adoption.failed {
+//!     eprintln!("{} was not adopted: {}", device_id, error);
+//! }
+//! ```
+//!
+//! ## Dry Run
+//!
+//! `plan_provisioning` computes what `discover_and_provision` would do, as a
+//! `ChangePlan` of events and adapter calls, without making any change:
+//!
+//! ```rust,ignore
+//! let plan = service.plan_provisioning().await?;
+//! for call in &plan.calls {
+//!     println!("would {:?}", call);
+//! }
+//! ```
+//!
+//! ## Tracing
+//!
+//! Each operation opens a span carrying the current correlation id. Run a
+//! flow under `telemetry::with_correlation` to tie its spans together:
+//!
+//! ```rust,ignore
+//! let report = telemetry::with_correlation(correlation_id, service.discover_and_provision()).await?;
 //! ```
 
-use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use futures::stream::{self, StreamExt};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
-use crate::domain::aggregates::{NetworkDeviceAggregate, DeviceState};
+use crate::adapters::retry::RetryPolicy;
+use crate::telemetry;
+use crate::domain::aggregates::{
+    NetworkDeviceAggregate, DeviceState, AggregateError, NetworkConnectionAggregate, ConnectionState,
+    SiteAggregate,
+};
+use crate::domain::value_objects::{
+    ConfigDiff, ConnectionId, ConnectionType, DeviceId, DeviceType, IpNetwork, LinkSpeed, MacAddress, MacPrefix, PortId,
+    SiteId, StackMember, Tag,
+};
+use crate::domain::clock::{Clock, SystemClock};
+use crate::domain::config_template::{ConfigurationGenerator, TemplateError, TemplateVariables};
+use crate::domain::command_handler::{CommandHandler, MemoryRepository};
+use crate::domain::commands::NetworkCommand;
 use crate::domain::events::NetworkEvent;
-use crate::domain::value_objects::{DeviceId, DeviceType, MacAddress};
 use crate::domain::ports::{
-    DeviceControlPort, InventoryPort, EventStorePort, PortError,
+    ConfigApplyOutcome, ConnectionInfo, DeviceConfigRequest, DeviceControlPort, InventoryPort,
+    EventStorePort, EventStream, HealthStatus, PortError, RecordedEvent, VendorDevice,
+};
+
+pub mod metrics;
+pub mod monitor;
+pub mod naming;
+pub mod sync_queue;
+
+use metrics::ServiceMetrics;
+use monitor::{StatsChange, StatsTracker};
+pub use monitor::{HealthThresholds, StatsMonitorHandle, ThroughputTracker};
+pub use naming::{NamingStrategy, SequentialNaming};
+pub use sync_queue::{DeadLetteredSync, InventorySyncWorkerHandle};
+
+/// Format byte prefixed to serialized aggregate snapshots
+///
+/// Bump this whenever the serialized layout of `NetworkDeviceAggregate`
+/// changes; snapshots with any other format byte are ignored on replay.
+pub const SNAPSHOT_FORMAT_VERSION: u8 = 2;
+
+/// Default number of devices adopted or synced at once by `discover_and_provision`
+pub const DEFAULT_PROVISIONING_CONCURRENCY: usize = 8;
+
+/// Default retry policy of the inventory sync queue: six attempts, backing off from 30s to 30min
+pub const DEFAULT_INVENTORY_SYNC_RETRY: RetryPolicy = RetryPolicy {
+    max_attempts: 6,
+    base_delay: Duration::from_secs(30),
+    max_delay: Duration::from_secs(30 * 60),
 };
 
+/// Outcome of a discovery reconciliation run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceReconciliation {
+    /// Devices no longer reported by the vendor, now `Missing`
+    pub missing: Vec<DeviceId>,
+    /// Missing devices reported again, back to `Provisioned`
+    pub reappeared: Vec<DeviceId>,
+}
+
+/// Result of a successful `sync_to_inventory`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncOutcome {
+    /// The device was written to the inventory and the sync recorded
+    Synced,
+    /// The inventory already held the device's current fields
+    Unchanged,
+}
+
+/// One entry of an aggregate's audit trail: what happened, when, and as part of what
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AuditEntry {
+    /// Position in the event store; entries are ordered by it
+    pub sequence: u64,
+    /// When the event was recorded
+    pub recorded_at: DateTime<Utc>,
+    /// Event type, e.g. `DeviceAdopting`
+    pub event_type: &'static str,
+    /// Unique id of the event
+    pub event_id: Option<String>,
+    /// Id shared by every event of the operation that produced this one
+    pub correlation_id: Option<String>,
+    /// Id of the event or command that directly caused this one
+    pub causation_id: Option<String>,
+    /// The event itself
+    pub event: NetworkEvent,
+}
+
+impl From<RecordedEvent> for AuditEntry {
+    fn from(recorded: RecordedEvent) -> Self {
+        Self {
+            sequence: recorded.sequence,
+            recorded_at: recorded.recorded_at,
+            event_type: recorded.event.event_type(),
+            event_id: recorded.event_id,
+            correlation_id: recorded.correlation_id,
+            causation_id: recorded.causation_id,
+            event: recorded.event,
+        }
+    }
+}
+
+/// Error from a `NetworkService` operation
+///
+/// Domain rejections keep their structure instead of being flattened into
+/// adapter errors, so callers can tell an invalid transition or a lost race
+/// from a vendor or event store failure.
+#[derive(Debug, thiserror::Error)]
+pub enum ServiceError {
+    /// The device is not known to the service
+    #[error("Device not found: {0}")]
+    DeviceNotFound(DeviceId),
+
+    /// The connection is not known to the service
+    #[error("Connection not found: {0}")]
+    ConnectionNotFound(ConnectionId),
+
+    /// The site is not known to the service
+    #[error("Site not found: {0}")]
+    SiteNotFound(SiteId),
+
+    /// The device cannot make this transition from its current state
+    #[error("Invalid state transition from {from:?} to {to:?}")]
+    InvalidTransition {
+        /// State the device is in
+        from: DeviceState,
+        /// State the operation would move it to
+        to: DeviceState,
+    },
+
+    /// Another writer appended to the aggregate since it was loaded
+    #[error("Concurrency conflict: expected version {expected}, found {actual}")]
+    ConcurrencyConflict {
+        /// Version the aggregate was loaded at
+        expected: u64,
+        /// Version the event store holds
+        actual: u64,
+    },
+
+    /// The aggregate rejected the operation for another reason
+    #[error(transparent)]
+    Aggregate(AggregateError),
+
+    /// A command in a batch failed, so none of the batch was persisted
+    #[error("Batch command {index} failed: {source}")]
+    BatchCommandFailed {
+        /// Position of the failed command in the batch
+        index: usize,
+        /// Why the command failed
+        source: Box<ServiceError>,
+    },
+
+    /// A vendor adapter, inventory adapter or the event store failed
+    #[error(transparent)]
+    Port(PortError),
+
+    /// A configuration template could not be rendered
+    #[error(transparent)]
+    Template(#[from] TemplateError),
+}
+
+impl From<AggregateError> for ServiceError {
+    fn from(error: AggregateError) -> Self {
+        match error {
+            AggregateError::InvalidTransition { from, to } => Self::InvalidTransition { from, to },
+            AggregateError::ConcurrencyConflict { expected, actual } => Self::ConcurrencyConflict { expected, actual },
+            AggregateError::DeviceNotFound(device_id) => Self::DeviceNotFound(device_id),
+            AggregateError::ConnectionNotFound(connection_id) => Self::ConnectionNotFound(connection_id),
+            other => Self::Aggregate(other),
+        }
+    }
+}
+
+impl From<PortError> for ServiceError {
+    fn from(error: PortError) -> Self {
+        match error {
+            // The event store reports version conflicts as aggregate errors
+            PortError::Aggregate(error) => error.into(),
+            PortError::DeviceNotFound(device_id) => Self::DeviceNotFound(device_id),
+            PortError::ConnectionNotFound(connection_id) => Self::ConnectionNotFound(connection_id),
+            other => Self::Port(other),
+        }
+    }
+}
+
+/// Per-device outcome of one provisioning phase
+///
+/// Devices are listed in completion order, not discovery order.
+#[derive(Debug, Default)]
+pub struct PhaseReport {
+    /// Devices for which the phase succeeded
+    pub succeeded: Vec<DeviceId>,
+    /// Devices for which the phase failed, with the error
+    pub failed: Vec<(DeviceId, ServiceError)>,
+}
+
+impl PhaseReport {
+    fn from_results(results: Vec<(DeviceId, Result<(), ServiceError>)>) -> Self {
+        let mut report = Self::default();
+        for (device_id, result) in results {
+            match result {
+                Ok(()) => report.succeeded.push(device_id),
+                Err(e) => report.failed.push((device_id, e)),
+            }
+        }
+        report
+    }
+}
+
+/// Outcome of a `discover_and_provision` run
+#[derive(Debug, Default)]
+pub struct ProvisioningReport {
+    /// Devices discovered for the first time
+    pub discovered: Vec<DeviceId>,
+    /// Adoption of the newly discovered devices
+    pub adoption: PhaseReport,
+    /// Inventory sync of all known devices (empty without an inventory adapter)
+    pub inventory_sync: PhaseReport,
+}
+
+/// Adapter call a `ChangePlan` would make
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlannedCall {
+    /// Adopt the device through the vendor adapter
+    AdoptDevice {
+        /// Device being adopted
+        device_id: DeviceId,
+        /// Vendor identifier passed to the adapter
+        vendor_id: String,
+    },
+    /// Write the device to the inventory
+    SyncDevice {
+        /// Device being synced
+        device_id: DeviceId,
+        /// Inventory system name
+        system: String,
+    },
+}
+
+/// What `discover_and_provision` would do, from `plan_provisioning`
+///
+/// Devices discovered by the plan get fresh IDs, which a later real run
+/// will not reuse; match them by MAC instead.
+#[derive(Debug, Default)]
+pub struct ChangePlan {
+    /// Devices that would be discovered for the first time
+    pub discovered: Vec<DeviceId>,
+    /// Events that would be appended, in order
+    pub events: Vec<NetworkEvent>,
+    /// Vendor and inventory calls that would be made, in order
+    pub calls: Vec<PlannedCall>,
+}
+
+impl ChangePlan {
+    /// Whether the run would change nothing
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty() && self.calls.is_empty()
+    }
+}
+
+/// Health of one adapter, as reported by `NetworkService::check_health`
+#[derive(Debug)]
+pub struct AdapterHealth {
+    /// Vendor or inventory system name
+    pub name: String,
+    /// Probe result; `NotSupported` for adapters without a health check
+    pub status: Result<HealthStatus, PortError>,
+}
+
+impl AdapterHealth {
+    /// Whether the adapter reported itself reachable and authenticated
+    pub fn is_healthy(&self) -> bool {
+        self.status.as_ref().is_ok_and(HealthStatus::is_healthy)
+    }
+}
+
+/// Health of every adapter configured on a `NetworkService`
+#[derive(Debug)]
+pub struct ServiceHealth {
+    /// The vendor adapter
+    pub vendor: AdapterHealth,
+    /// The inventory adapter, if one is configured
+    pub inventory: Option<AdapterHealth>,
+}
+
+impl ServiceHealth {
+    /// Whether every adapter that supports a health check reported healthy
+    pub fn is_healthy(&self) -> bool {
+        std::iter::once(&self.vendor)
+            .chain(self.inventory.as_ref())
+            .filter(|a| !matches!(a.status, Err(PortError::NotSupported(_))))
+            .all(AdapterHealth::is_healthy)
+    }
+}
+
+/// Query over the cached devices for `NetworkService::find_devices`
+///
+/// Every condition that is set must match (AND); an empty filter matches
+/// every device.
+///
+/// ```rust,ignore
+/// let ubiquiti_switches = service.find_devices(
+///     DeviceFilter::new()
+///         .device_type(DeviceType::Switch)
+///         .vendor_name("Ubiquiti")
+///         .tag(Tag::parse("env:prod")?),
+/// ).await;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeviceFilter {
+    state: Option<DeviceState>,
+    device_type: Option<DeviceType>,
+    vendor_name: Option<String>,
+    name_contains: Option<String>,
+    mac_prefix: Option<MacPrefix>,
+    tags: Vec<Tag>,
+}
+
+impl DeviceFilter {
+    /// Create a filter that matches every device
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only devices in this lifecycle state
+    pub fn state(mut self, state: DeviceState) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Only devices of this type
+    pub fn device_type(mut self, device_type: DeviceType) -> Self {
+        self.device_type = Some(device_type);
+        self
+    }
+
+    /// Only devices whose MAC OUI is registered to this vendor (case-insensitive)
+    pub fn vendor_name(mut self, vendor: impl Into<String>) -> Self {
+        self.vendor_name = Some(vendor.into());
+        self
+    }
+
+    /// Only devices whose name contains this text (case-insensitive)
+    pub fn name_contains(mut self, text: impl Into<String>) -> Self {
+        self.name_contains = Some(text.into().to_lowercase());
+        self
+    }
+
+    /// Only devices whose MAC address starts with this prefix
+    pub fn mac_prefix(mut self, prefix: MacPrefix) -> Self {
+        self.mac_prefix = Some(prefix);
+        self
+    }
+
+    /// Only devices carrying this tag; repeat to require several tags
+    pub fn tag(mut self, tag: Tag) -> Self {
+        self.tags.push(tag);
+        self
+    }
+
+    /// Whether a device satisfies every condition of the filter
+    pub fn matches(&self, device: &NetworkDeviceAggregate) -> bool {
+        self.state.is_none_or(|state| device.state() == state)
+            && self.device_type.as_ref().is_none_or(|t| device.device_type() == t)
+            && self.vendor_name.as_ref().is_none_or(|vendor| {
+                device.mac().vendor().is_some_and(|v| v.eq_ignore_ascii_case(vendor))
+            })
+            && self.name_contains.as_ref().is_none_or(|text| device.name().to_lowercase().contains(text))
+            && self.mac_prefix.is_none_or(|prefix| prefix.matches(&device.mac()))
+            && self.tags.iter().all(|tag| device.has_tag(tag))
+    }
+}
+
 /// Network service for orchestrating domain operations
 ///
 /// This service coordinates between:
@@ -63,7 +453,36 @@ pub struct NetworkService {
     /// Optional inventory adapter
     inventory_adapter: Option<Arc<dyn InventoryPort>>,
     /// In-memory device cache (aggregate_id -> aggregate)
-    devices: Arc<RwLock<HashMap<DeviceId, NetworkDeviceAggregate>>>,
+    ///
+    /// Sharded, so updates to different devices don't contend. Entries are
+    /// only borrowed in synchronous code: never hold a `Ref` across `.await`.
+    devices: Arc<DashMap<DeviceId, NetworkDeviceAggregate>>,
+    /// In-memory connection cache (aggregate_id -> aggregate)
+    connections: Arc<RwLock<HashMap<ConnectionId, NetworkConnectionAggregate>>>,
+    /// In-memory site cache (aggregate_id -> aggregate)
+    sites: Arc<RwLock<HashMap<SiteId, SiteAggregate>>>,
+    /// When each device was last reported by the vendor adapter
+    last_seen: Arc<RwLock<HashMap<DeviceId, DateTime<Utc>>>>,
+    /// Cursor from the last successful discovery, for incremental listing
+    discovery_cursor: Arc<RwLock<Option<String>>>,
+    /// Snapshot after replaying at least this many events (None = never)
+    snapshot_threshold: Option<u64>,
+    /// Maximum devices adopted or synced concurrently
+    provisioning_concurrency: usize,
+    /// Purge a device's history this long after decommissioning (None = keep)
+    purge_after: Option<Duration>,
+    /// How adapter calls failing with a transient error are retried
+    retry_policy: RetryPolicy,
+    /// How failed inventory syncs are retried from the sync queue
+    inventory_sync_retry: RetryPolicy,
+    /// Levels above which the stats monitor reports a device as degraded
+    health_thresholds: HealthThresholds,
+    /// Names discovered devices the vendor reports without a name
+    naming: Arc<dyn NamingStrategy>,
+    /// Source of the times recorded in events, such as `last_seen`
+    clock: Arc<dyn Clock>,
+    /// Operational metrics (no-op without the `metrics` feature)
+    metrics: ServiceMetrics,
 }
 
 impl NetworkService {
@@ -72,34 +491,57 @@ impl NetworkService {
         NetworkServiceBuilder::new()
     }
 
+    /// Prometheus handle for scraping this service's metrics
+    ///
+    /// Render it with `PrometheusHandle::render` from a `/metrics` endpoint.
+    #[cfg(feature = "metrics")]
+    pub fn metrics_handle(&self) -> metrics_exporter_prometheus::PrometheusHandle {
+        self.metrics.handle()
+    }
+
     /// Discover devices from the vendor controller
     ///
-    /// Queries the vendor adapter for all devices and creates domain aggregates
-    /// for any new devices found. Events are persisted to the event store.
-    pub async fn discover_devices(&self) -> Result<Vec<DeviceId>, PortError> {
+    /// Queries the vendor adapter for the devices changed since the previous
+    /// run (every device on the first run, or with adapters that do not track
+    /// changes) and creates domain aggregates for any new devices found.
+    /// Events are persisted to the event store.
+    #[tracing::instrument(skip_all, fields(correlation_id = telemetry::current_correlation_id()))]
+    pub async fn discover_devices(&self) -> Result<Vec<DeviceId>, ServiceError> {
+        let started = Instant::now();
+        let result = self.discover_new_devices().await;
+
+        let vendor = self.vendor_adapter.vendor_name();
+        self.metrics.discovery_finished(vendor, started.elapsed());
+        if let Ok(ref discovered) = result {
+            self.metrics.devices_discovered(vendor, discovered.len());
+        }
+        result
+    }
+
+    async fn discover_new_devices(&self) -> Result<Vec<DeviceId>, ServiceError> {
         tracing::info!("Starting device discovery via {}", self.vendor_adapter.vendor_name());
 
-        // Get devices from vendor
-        let vendor_devices = self.vendor_adapter.list_devices().await?;
+        // Get the devices changed since the last run from the vendor
+        let cursor = self.discovery_cursor.read().await.clone();
+        let (vendor_devices, next_cursor) = self
+            .retry_transient(|| self.vendor_adapter.list_devices_since(cursor.clone()))
+            .await?;
         let mut discovered_ids = Vec::new();
+        let seen_at = self.clock.now();
+        let stacked: Vec<ReportedStackUnit> = vendor_devices.iter()
+            .filter_map(|d| d.stack_membership().map(|(master, member)| (master, d.mac, member)))
+            .collect();
 
         for vendor_device in vendor_devices {
             // Check if we already know this device
             let existing = self.find_device_by_mac(&vendor_device.mac).await;
 
             if existing.is_none() {
-                // Create new domain aggregate
-                let device_type = infer_device_type(&vendor_device.model);
-                let mut aggregate = NetworkDeviceAggregate::new_discovered(
-                    vendor_device.mac,
-                    device_type,
-                    vendor_device.ip_address,
-                );
-
-                // Set name if available
-                if !vendor_device.name.is_empty() {
-                    let _ = aggregate.rename(vendor_device.name.clone());
-                }
+                let mut aggregate = {
+                    let names: Vec<String> = self.devices.iter().map(|d| d.name().to_string()).collect();
+                    let taken: Vec<&str> = names.iter().map(String::as_str).collect();
+                    self.new_device_aggregate(&vendor_device, &taken)
+                };
 
                 // Persist events
                 let events = aggregate.take_pending_events();
@@ -109,46 +551,143 @@ impl NetworkService {
 
                 let device_id = aggregate.id();
                 discovered_ids.push(device_id);
-
-                // Cache the aggregate
-                let mut devices = self.devices.write().await;
-                devices.insert(device_id, aggregate);
+                self.last_seen.write().await.insert(device_id, seen_at);
 
                 tracing::info!(
                     "Discovered device {} ({}) - {}",
-                    vendor_device.name,
+                    aggregate.name(),
                     vendor_device.mac,
                     device_id
                 );
+
+                // Cache the aggregate
+                self.devices.insert(device_id, aggregate);
+            } else if let Some(device_id) = existing {
+                self.last_seen.write().await.insert(device_id, seen_at);
             }
         }
 
+        // Units merged into a stack are no longer devices of their own
+        self.merge_reported_stacks(&stacked).await;
+        discovered_ids.retain(|device_id| self.devices.contains_key(device_id));
+
+        // Only advance once every reported device has been recorded
+        *self.discovery_cursor.write().await = next_cursor;
+
         tracing::info!("Discovery complete: {} new devices", discovered_ids.len());
         Ok(discovered_ids)
     }
 
+    /// Build the aggregate for a device the vendor reports for the first time
+    ///
+    /// The device type falls back to the MAC's OUI when the model string
+    /// doesn't identify the device. The vendor's name is kept; unnamed
+    /// devices are named by the naming strategy, avoiding `taken`.
+    fn new_device_aggregate(&self, vendor_device: &VendorDevice, taken: &[&str]) -> NetworkDeviceAggregate {
+        let mut device_type = infer_device_type(&vendor_device.model);
+        if matches!(device_type, DeviceType::Generic { .. }) {
+            if let Some(by_oui) = DeviceType::from_mac_oui(vendor_device.mac) {
+                device_type = by_oui;
+            }
+        }
+        let mut aggregate = NetworkDeviceAggregate::new_discovered(
+            vendor_device.mac,
+            device_type,
+            vendor_device.ip_address,
+        );
+
+        let name = if vendor_device.name.is_empty() {
+            self.naming.name(aggregate.device_type(), taken)
+        } else {
+            vendor_device.name.clone()
+        };
+        let _ = aggregate.rename(name);
+        aggregate
+    }
+
+    /// Cursor the next discovery run passes to the vendor adapter
+    ///
+    /// `None`, meaning a full listing, until a run against an adapter with
+    /// change tracking completes.
+    pub async fn discovery_cursor(&self) -> Option<String> {
+        self.discovery_cursor.read().await.clone()
+    }
+
+    /// Reconcile known devices against the vendor's current device list
+    ///
+    /// Provisioned devices the vendor no longer reports are marked `Missing`;
+    /// missing devices that show up again are returned to `Provisioned`.
+    /// Devices in other states are left alone. A failure to persist one
+    /// device's transition is logged and does not stop the run.
+    #[tracing::instrument(skip_all, fields(correlation_id = telemetry::current_correlation_id()))]
+    pub async fn reconcile_devices(&self) -> Result<DeviceReconciliation, ServiceError> {
+        let vendor_devices = self.vendor_adapter.list_devices().await?;
+        let present: HashSet<MacAddress> = vendor_devices.iter().map(|d| d.mac).collect();
+        let seen_at = self.clock.now();
+
+        let known: Vec<(DeviceId, MacAddress, DeviceState)> = self.devices
+            .iter()
+            .map(|d| (d.id(), d.mac(), d.state()))
+            .collect();
+        let mut report = DeviceReconciliation::default();
+
+        for (device_id, mac, state) in known {
+            let reported = present.contains(&mac);
+
+            let result = match (state, reported) {
+                (DeviceState::Provisioned, false) => {
+                    let since = self.last_seen.read().await.get(&device_id).copied();
+                    self.commit(device_id, |agg| agg.mark_missing(since)).await
+                        .map(|_| report.missing.push(device_id))
+                }
+                (DeviceState::Missing, true) => {
+                    self.commit(device_id, |agg| agg.mark_reappeared()).await
+                        .map(|_| report.reappeared.push(device_id))
+                }
+                _ => Ok(()),
+            };
+
+            if let Err(e) = result {
+                tracing::warn!("Failed to reconcile device {}: {}", device_id, e);
+            }
+            if reported {
+                self.last_seen.write().await.insert(device_id, seen_at);
+            }
+        }
+
+        tracing::info!(
+            "Reconciliation complete: {} missing, {} reappeared",
+            report.missing.len(),
+            report.reappeared.len()
+        );
+        Ok(report)
+    }
+
     /// Adopt a device through the vendor controller
     ///
     /// Transitions the device from Discovered to Adopting state,
     /// then triggers adoption via the vendor adapter.
-    pub async fn adopt_device(&self, device_id: DeviceId) -> Result<(), PortError> {
-        let mut devices = self.devices.write().await;
-        let aggregate = devices.get_mut(&device_id)
-            .ok_or_else(|| PortError::DeviceNotFound(device_id))?;
+    #[tracing::instrument(skip_all, fields(%device_id, correlation_id = telemetry::current_correlation_id()))]
+    pub async fn adopt_device(&self, device_id: DeviceId) -> Result<(), ServiceError> {
+        let result = self.try_adopt_device(device_id).await;
+        if result.is_err() {
+            self.metrics.adoption_failed(self.vendor_adapter.vendor_name());
+        }
+        result
+    }
 
+    async fn try_adopt_device(&self, device_id: DeviceId) -> Result<(), ServiceError> {
         // Get vendor ID (MAC address for UniFi)
-        let vendor_id = aggregate.mac().to_string();
+        let vendor_id = self.get_device(device_id).await
+            .ok_or(ServiceError::DeviceNotFound(device_id))?
+            .mac()
+            .to_string();
 
-        // Transition to adopting state
-        aggregate.adopt(vendor_id.clone())
-            .map_err(|e| PortError::VendorError(e.to_string()))?;
-
-        // Persist the state change
-        let events = aggregate.take_pending_events();
-        self.event_store.append(events).await?;
+        // Transition to adopting state and persist it
+        self.commit(device_id, |agg| agg.adopt(vendor_id.clone())).await?;
 
         // Trigger adoption via vendor adapter
-        self.vendor_adapter.adopt_device(&vendor_id).await?;
+        self.retry_transient(|| self.vendor_adapter.adopt_device(&vendor_id)).await?;
 
         tracing::info!("Device {} adoption initiated", device_id);
         Ok(())
@@ -156,286 +695,4212 @@ impl NetworkService {
 
     /// Mark a device as provisioned
     ///
-    /// Called when the vendor confirms the device is fully adopted.
+    /// Called when the vendor confirms the device is fully adopted. The
+    /// device is then synced to the inventory, if one is configured, through
+    /// `sync_to_inventory`: a failed sync does not fail the call, since the
+    /// provisioning is already recorded, but is queued for the sync worker.
+    /// A device whose sync is already queued is left to the worker.
+    #[tracing::instrument(skip_all, fields(%device_id, correlation_id = telemetry::current_correlation_id()))]
     pub async fn mark_provisioned(
         &self,
         device_id: DeviceId,
         model: String,
         firmware_version: String,
-    ) -> Result<(), PortError> {
-        let mut devices = self.devices.write().await;
-        let aggregate = devices.get_mut(&device_id)
-            .ok_or_else(|| PortError::DeviceNotFound(device_id))?;
-
-        aggregate.mark_provisioned(model, firmware_version)
-            .map_err(|e| PortError::VendorError(e.to_string()))?;
+    ) -> Result<(), ServiceError> {
+        let device = self.commit(device_id, |agg| agg.mark_provisioned(model, firmware_version)).await?;
 
-        // Persist events
-        let events = aggregate.take_pending_events();
-        self.event_store.append(events).await?;
-
-        // Sync to inventory if configured
-        if let Some(ref inventory) = self.inventory_adapter {
-            inventory.sync_device(aggregate).await?;
-            tracing::info!("Device {} synced to inventory", device_id);
+        if let Some(inventory) = &self.inventory_adapter {
+            if Self::sync_is_queued(&device, inventory.system_name(), self.clock.now()) {
+                tracing::debug!("Inventory sync of provisioned device {} left to the sync queue", device_id);
+            } else {
+                match self.sync_to_inventory(device_id).await {
+                    Ok(_) => tracing::info!("Device {} synced to inventory", device_id),
+                    Err(e) => tracing::warn!("Inventory sync of provisioned device {} failed: {}", device_id, e),
+                }
+            }
         }
 
         tracing::info!("Device {} provisioned", device_id);
         Ok(())
     }
 
-    /// Sync a device to inventory
-    pub async fn sync_to_inventory(&self, device_id: DeviceId) -> Result<(), PortError> {
-        let inventory = self.inventory_adapter.as_ref()
-            .ok_or_else(|| PortError::NotSupported("No inventory adapter configured".to_string()))?;
-
-        let devices = self.devices.read().await;
-        let aggregate = devices.get(&device_id)
-            .ok_or_else(|| PortError::DeviceNotFound(device_id))?;
+    /// Upgrade a device's firmware to `version`
+    ///
+    /// The device passes through `Configuring` while the vendor upgrades it
+    /// and is recorded as running `version` once the vendor accepts the
+    /// upgrade. If the vendor rejects it, the device moves to `Error`.
+    /// Upgrading to the firmware the device already runs does nothing.
+    #[tracing::instrument(skip_all, fields(%device_id, %version, correlation_id = telemetry::current_correlation_id()))]
+    pub async fn upgrade_firmware(&self, device_id: DeviceId, version: String) -> Result<(), ServiceError> {
+        let device = self.get_device(device_id).await
+            .ok_or(ServiceError::DeviceNotFound(device_id))?;
+        if device.firmware_version() == Some(version.as_str()) {
+            tracing::info!("Device {} already runs firmware {}", device_id, version);
+            return Ok(());
+        }
+        let vendor_id = device.vendor_id()
+            .map(str::to_string)
+            .unwrap_or_else(|| device.mac().to_string());
 
-        inventory.sync_device(aggregate).await?;
+        self.commit(device_id, |agg| agg.start_firmware_upgrade(version.clone())).await?;
 
-        // Record the sync event
-        let event = NetworkEvent::DeviceSyncedToInventory {
-            device_id,
-            inventory_id: format!("{}-{}", inventory.system_name(), device_id),
-            system: inventory.system_name().to_string(),
-        };
-        self.event_store.append(vec![event]).await?;
+        if let Err(e) = self.vendor_adapter.upgrade_firmware(&vendor_id, &version).await {
+            self.commit(device_id, |agg| agg.record_error(format!("Firmware upgrade failed: {}", e))).await?;
+            return Err(e.into());
+        }
 
+        self.commit(device_id, |agg| agg.complete_firmware_upgrade(version.clone())).await?;
+        tracing::info!("Device {} upgraded to firmware {}", device_id, version);
         Ok(())
     }
 
-    /// Decommission a device
-    pub async fn decommission_device(&self, device_id: DeviceId) -> Result<(), PortError> {
-        let mut devices = self.devices.write().await;
-        let aggregate = devices.get_mut(&device_id)
-            .ok_or_else(|| PortError::DeviceNotFound(device_id))?;
+    /// Apply configuration to a device, rolling back on failure
+    ///
+    /// The device passes through `Configuring` while the vendor applies
+    /// `config` with `apply_config_transactional`. Once applied, the device
+    /// records the requested interfaces and VLANs. If the vendor rolled the
+    /// change back, `ConfigRolledBack` is recorded and the device keeps its
+    /// previous configuration. Any other failure moves the device to `Error`.
+    /// An invalid request is rejected before the device is touched.
+    #[tracing::instrument(skip_all, fields(%device_id, correlation_id = telemetry::current_correlation_id()))]
+    pub async fn configure_device(
+        &self,
+        device_id: DeviceId,
+        config: DeviceConfigRequest,
+    ) -> Result<ConfigApplyOutcome, ServiceError> {
+        config.validate()?;
+        let device = self.get_device(device_id).await
+            .ok_or(ServiceError::DeviceNotFound(device_id))?;
+        let vendor_id = device.vendor_id()
+            .map(str::to_string)
+            .unwrap_or_else(|| device.mac().to_string());
 
-        aggregate.decommission()
-            .map_err(|e| PortError::VendorError(e.to_string()))?;
+        self.commit(device_id, |agg| agg.start_configuration()).await?;
 
-        // Persist events
-        let events = aggregate.take_pending_events();
-        self.event_store.append(events).await?;
+        let outcome = match self.vendor_adapter.apply_config_transactional(&vendor_id, config.clone()).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                self.commit(device_id, |agg| agg.record_error(format!("Configuration failed: {}", e))).await?;
+                return Err(e.into());
+            }
+        };
 
-        // Remove from inventory
-        if let Some(ref inventory) = self.inventory_adapter {
-            let _ = inventory.remove_device(device_id).await;
+        match &outcome {
+            ConfigApplyOutcome::Applied => {
+                self.commit(device_id, |agg| {
+                    agg.complete_configuration(config.interfaces.clone(), config.vlans.clone())
+                }).await?;
+                tracing::info!("Device {} configured", device_id);
+            }
+            ConfigApplyOutcome::RolledBack { reason } => {
+                self.commit(device_id, |agg| agg.record_config_rollback(reason.clone())).await?;
+                tracing::warn!("Device {} configuration rolled back: {}", device_id, reason);
+            }
         }
-
-        tracing::info!("Device {} decommissioned", device_id);
-        Ok(())
+        Ok(outcome)
     }
 
-    /// Get a device by ID
-    pub async fn get_device(&self, device_id: DeviceId) -> Option<NetworkDeviceAggregate> {
-        let devices = self.devices.read().await;
-        devices.get(&device_id).cloned()
+    /// Render a device's configuration with `generator` and apply it
+    ///
+    /// For a `ConfigTemplate`, the device's name and IP address fill the
+    /// `hostname` and `mgmt_ip` variables unless `variables` sets them. A
+    /// configuration that cannot be rendered, such as a template referencing
+    /// a variable that is not provided, is rejected before the device is
+    /// touched; otherwise it is applied as by `configure_device`.
+    pub async fn configure_from_template(
+        &self,
+        device_id: DeviceId,
+        generator: &dyn ConfigurationGenerator,
+        variables: &TemplateVariables,
+    ) -> Result<ConfigApplyOutcome, ServiceError> {
+        let device = self.get_device(device_id).await
+            .ok_or(ServiceError::DeviceNotFound(device_id))?;
+        let config = generator.generate(&device, variables)?;
+        self.configure_device(device_id, config).await
     }
 
-    /// List all devices
-    pub async fn list_devices(&self) -> Vec<NetworkDeviceAggregate> {
-        let devices = self.devices.read().await;
-        devices.values().cloned().collect()
-    }
+    /// Compare a device's running configuration with `desired`
+    ///
+    /// Reads the running configuration back from the vendor and diffs it
+    /// against `desired` (see `DeviceConfigRequest::diff`). Any drift is
+    /// recorded as one `ConfigDriftDetected` event; a device that matches
+    /// records nothing. Returns the diffs found.
+    #[tracing::instrument(skip_all, fields(%device_id, correlation_id = telemetry::current_correlation_id()))]
+    pub async fn detect_drift(
+        &self,
+        device_id: DeviceId,
+        desired: &DeviceConfigRequest,
+    ) -> Result<Vec<ConfigDiff>, ServiceError> {
+        let device = self.get_device(device_id).await
+            .ok_or(ServiceError::DeviceNotFound(device_id))?;
+        let vendor_id = device.vendor_id()
+            .map(str::to_string)
+            .unwrap_or_else(|| device.mac().to_string());
 
-    /// List devices by state
-    pub async fn list_devices_by_state(&self, state: DeviceState) -> Vec<NetworkDeviceAggregate> {
-        let devices = self.devices.read().await;
-        devices.values()
-            .filter(|d| d.state() == state)
-            .cloned()
-            .collect()
+        let running = self.vendor_adapter.get_running_config(&vendor_id).await?;
+        let diffs = desired.diff(&running);
+        if !diffs.is_empty() {
+            self.commit(device_id, |agg| {
+                agg.record_config_drift(diffs.clone());
+                Ok(())
+            }).await?;
+            tracing::warn!("Device {} drifted from its desired configuration in {} setting(s)", device_id, diffs.len());
+        }
+        Ok(diffs)
     }
 
-    /// Find device by MAC address
-    async fn find_device_by_mac(&self, mac: &MacAddress) -> Option<DeviceId> {
-        let devices = self.devices.read().await;
-        devices.values()
-            .find(|d| d.mac() == *mac)
-            .map(|d| d.id())
+    /// Sync a device to inventory
+    ///
+    /// Skipped, with `SyncOutcome::Unchanged`, when the device's inventory
+    /// fields match its last recorded sync to the same system: nothing is
+    /// written and no event is recorded. An inventory failure is returned
+    /// and also put on the sync queue for the worker to retry (see the
+    /// `sync_queue` module).
+    #[tracing::instrument(skip_all, fields(%device_id, correlation_id = telemetry::current_correlation_id()))]
+    pub async fn sync_to_inventory(&self, device_id: DeviceId) -> Result<SyncOutcome, ServiceError> {
+        let inventory = self.inventory_adapter.as_ref()
+            .ok_or_else(|| PortError::NotSupported("No inventory adapter configured".to_string()))?;
+
+        let result = self.try_sync_to_inventory(inventory.as_ref(), device_id).await;
+        self.metrics.inventory_synced(inventory.system_name(), result.is_ok());
+        match &result {
+            Err(ServiceError::Port(PortError::NotSupported(_))) => {}
+            Err(ServiceError::Port(e)) => self.queue_failed_sync(device_id, inventory.system_name(), e).await,
+            _ => {}
+        }
+        result
     }
 
-    /// Replay events from the event store to rebuild state
-    pub async fn replay_events(&self, aggregate_id: &str) -> Result<Option<NetworkDeviceAggregate>, PortError> {
-        let events = self.event_store.load_events(aggregate_id).await?;
+    /// Put a failed sync on the queue, or dead-letter it once the queue's
+    /// retry policy is exhausted
+    async fn queue_failed_sync(&self, device_id: DeviceId, system: &str, error: &PortError) {
+        let policy = self.inventory_sync_retry;
+        let now = self.clock.now();
+        let recorded = self.commit(device_id, |agg| {
+            let attempts = agg.inventory_sync_attempts(system) + 1;
+            if attempts >= policy.max_attempts {
+                agg.dead_letter_inventory_sync(system.to_string(), error.to_string());
+            } else {
+                let retry_at = chrono::Duration::from_std(policy.backoff(attempts))
+                    .ok()
+                    .and_then(|delay| now.checked_add_signed(delay))
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC);
+                agg.queue_inventory_sync(system.to_string(), error.to_string(), retry_at);
+            }
+            Ok(())
+        })
+        .await;
 
-        if events.is_empty() {
-            return Ok(None);
+        match recorded {
+            Ok(device) if device.inventory_sync_failures().get(system).is_some_and(|f| f.is_dead_lettered()) => {
+                tracing::warn!("Gave up syncing device {} to {}: {}", device_id, system, error);
+            }
+            Ok(_) => tracing::warn!("Sync of device {} to {} failed, queued for retry: {}", device_id, system, error),
+            Err(e) => tracing::warn!("Failed to queue sync of device {} to {}: {}", device_id, system, e),
         }
+    }
 
-        // Reconstruct aggregate from events
-        let mut aggregate: Option<NetworkDeviceAggregate> = None;
-
-        for event in events {
-            match event {
-                NetworkEvent::DeviceDiscovered { device_id, mac, device_type, ip_address } => {
-                    aggregate = Some(NetworkDeviceAggregate::from_discovered_event(
-                        device_id, mac, device_type, ip_address,
-                    ));
-                }
-                NetworkEvent::DeviceAdopting { vendor_id, .. } => {
-                    if let Some(ref mut agg) = aggregate {
-                        let _ = agg.adopt(vendor_id);
-                        agg.take_pending_events(); // Discard during replay
-                    }
-                }
-                NetworkEvent::DeviceProvisioned { model, firmware_version, .. } => {
-                    if let Some(ref mut agg) = aggregate {
-                        let _ = agg.mark_provisioned(model, firmware_version);
-                        agg.take_pending_events();
-                    }
-                }
-                NetworkEvent::DeviceDecommissioned { .. } => {
-                    if let Some(ref mut agg) = aggregate {
-                        let _ = agg.decommission();
-                        agg.take_pending_events();
-                    }
-                }
-                NetworkEvent::DeviceRenamed { new_name, .. } => {
-                    if let Some(ref mut agg) = aggregate {
-                        let _ = agg.rename(new_name);
-                        agg.take_pending_events();
-                    }
+    /// Retry the queued inventory syncs due every `interval` on a background task
+    ///
+    /// Runs until its handle is stopped or dropped. See the `sync_queue`
+    /// module for how syncs are queued and dead-lettered.
+    pub fn start_inventory_sync_worker(self: &Arc<Self>, interval: Duration) -> InventorySyncWorkerHandle {
+        let service = Arc::clone(self);
+        let (stop, mut stopped) = tokio::sync::oneshot::channel::<()>();
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = &mut stopped => break,
+                    _ = ticker.tick() => service.retry_due_syncs().await,
                 }
-                _ => {} // Other events don't affect device aggregate
             }
-        }
+            tracing::debug!("Inventory sync worker stopped");
+        });
 
-        // Cache the reconstructed aggregate
-        if let Some(ref agg) = aggregate {
-            let mut devices = self.devices.write().await;
-            devices.insert(agg.id(), agg.clone());
+        InventorySyncWorkerHandle::new(stop, task)
+    }
+
+    /// Retry every queued inventory sync whose next attempt is due
+    async fn retry_due_syncs(&self) {
+        let Some(inventory) = &self.inventory_adapter else {
+            return;
+        };
+        let system = inventory.system_name();
+        let now = self.clock.now();
+        let due: Vec<DeviceId> = self.devices.iter()
+            .filter(|d| {
+                d.inventory_sync_failures()
+                    .get(system)
+                    .is_some_and(|failure| failure.is_due(now))
+            })
+            .map(|d| d.id())
+            .collect();
+
+        for device_id in due {
+            if self.sync_to_inventory(device_id).await.is_ok() {
+                tracing::info!("Queued sync of device {} to {} succeeded", device_id, system);
+            }
         }
+    }
 
-        Ok(aggregate)
+    /// Whether the sync queue owns the device's sync to `system`
+    ///
+    /// True while a failed sync waits for its backoff or has been
+    /// dead-lettered. Callers other than the worker skip such devices, so
+    /// the retry policy, not how often they run, paces the attempts.
+    fn sync_is_queued(device: &NetworkDeviceAggregate, system: &str, now: DateTime<Utc>) -> bool {
+        device.inventory_sync_failures()
+            .get(system)
+            .is_some_and(|failure| !failure.is_due(now))
     }
 
-    /// Full discovery and provisioning workflow
+    /// Inventory syncs given up on after repeated failures
     ///
-    /// 1. Discover devices from vendor
-    /// 2. Adopt any unadopted devices
-    /// 3. Sync all to inventory
-    pub async fn discover_and_provision(&self) -> Result<Vec<DeviceId>, PortError> {
-        // Step 1: Discover
-        let discovered = self.discover_devices().await?;
+    /// A dead-lettered sync stays listed until the device is synced
+    /// successfully, e.g. by calling `sync_to_inventory` again.
+    pub async fn dead_lettered_syncs(&self) -> Vec<DeadLetteredSync> {
+        let mut dead: Vec<DeadLetteredSync> = self.devices.iter()
+            .flat_map(|device| {
+                let device_id = device.id();
+                device.inventory_sync_failures()
+                    .iter()
+                    .filter(|(_, failure)| failure.is_dead_lettered())
+                    .map(|(system, failure)| DeadLetteredSync {
+                        device_id,
+                        system: system.clone(),
+                        attempts: failure.attempts,
+                        error: failure.error.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        dead.sort_by_key(|sync| (sync.device_id.to_string(), sync.system.clone()));
+        dead
+    }
 
-        // Step 2: Adopt discovered devices
-        for device_id in &discovered {
-            if let Err(e) = self.adopt_device(*device_id).await {
-                tracing::warn!("Failed to adopt device {}: {}", device_id, e);
+    async fn try_sync_to_inventory(
+        &self,
+        inventory: &dyn InventoryPort,
+        device_id: DeviceId,
+    ) -> Result<SyncOutcome, ServiceError> {
+        let aggregate = self.get_device(device_id).await
+            .ok_or(ServiceError::DeviceNotFound(device_id))?;
+
+        // The site carries the device's placement, which the device's own
+        // sync fingerprint does not cover
+        if let Some(site) = self.site_of(device_id).await {
+            match self.retry_transient(|| inventory.sync_site(&site)).await {
+                Ok(()) | Err(PortError::NotSupported(_)) => {}
+                Err(e) => return Err(e.into()),
             }
         }
 
-        // Step 3: Sync all devices to inventory
-        if self.inventory_adapter.is_some() {
-            let devices = self.list_devices().await;
-            for device in devices {
-                if let Err(e) = self.sync_to_inventory(device.id()).await {
-                    tracing::warn!("Failed to sync device {} to inventory: {}", device.id(), e);
-                }
-            }
+        if !aggregate.needs_inventory_sync(inventory.system_name()) {
+            tracing::debug!("Device {} unchanged since its last inventory sync", device_id);
+            return Ok(SyncOutcome::Unchanged);
         }
 
-        Ok(discovered)
+        self.retry_transient(|| inventory.sync_device(&aggregate)).await?;
+
+        // Record the sync event
+        let inventory_id = format!("{}-{}", inventory.system_name(), device_id);
+        let system = inventory.system_name().to_string();
+        self.commit(device_id, |agg| {
+            agg.record_inventory_sync(inventory_id, system);
+            Ok(())
+        }).await?;
+
+        Ok(SyncOutcome::Synced)
     }
-}
 
-/// Builder for NetworkService
-pub struct NetworkServiceBuilder {
-    event_store: Option<Arc<dyn EventStorePort>>,
-    vendor_adapter: Option<Arc<dyn DeviceControlPort>>,
+    /// Decommission a device
+    #[tracing::instrument(skip_all, fields(%device_id, correlation_id = telemetry::current_correlation_id()))]
+    pub async fn decommission_device(&self, device_id: DeviceId) -> Result<(), ServiceError> {
+        self.commit(device_id, |agg| agg.decommission()).await?;
+
+        // Remove from inventory
+        if let Some(ref inventory) = self.inventory_adapter {
+            let _ = inventory.remove_device(device_id).await;
+        }
+
+        tracing::info!("Device {} decommissioned", device_id);
+
+        if let Some(retention) = self.purge_after {
+            let event_store = self.event_store.clone();
+            let (devices, last_seen) = (self.devices.clone(), self.last_seen.clone());
+            tokio::spawn(async move {
+                tokio::time::sleep(retention).await;
+                if let Err(e) = purge_device_history(event_store.as_ref(), &devices, &last_seen, device_id).await {
+                    tracing::warn!("Scheduled purge of device {} failed: {}", device_id, e);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Purge a decommissioned device's history
+    ///
+    /// Its events and snapshot are deleted from the event store and a
+    /// `DevicePurged` tombstone is recorded apart from them. The device is
+    /// then forgotten: `get_device` and `replay_events` return `None`.
+    #[tracing::instrument(skip_all, fields(%device_id, correlation_id = telemetry::current_correlation_id()))]
+    pub async fn purge_device(&self, device_id: DeviceId) -> Result<(), ServiceError> {
+        let device = self.get_device(device_id).await
+            .ok_or(ServiceError::DeviceNotFound(device_id))?;
+        if device.state() != DeviceState::Decommissioned {
+            return Err(AggregateError::InvalidState {
+                current: device.state(),
+                operation: "purge".to_string(),
+            }.into());
+        }
+
+        purge_device_history(self.event_store.as_ref(), &self.devices, &self.last_seen, device_id).await?;
+        Ok(())
+    }
+
+    /// Add a tag to a device
+    #[tracing::instrument(skip_all, fields(%device_id, %tag, correlation_id = telemetry::current_correlation_id()))]
+    pub async fn tag_device(&self, device_id: DeviceId, tag: Tag) -> Result<(), ServiceError> {
+        self.commit(device_id, |agg| agg.add_tag(tag)).await?;
+        Ok(())
+    }
+
+    /// Remove a tag from a device
+    #[tracing::instrument(skip_all, fields(%device_id, %tag, correlation_id = telemetry::current_correlation_id()))]
+    pub async fn untag_device(&self, device_id: DeviceId, tag: &Tag) -> Result<(), ServiceError> {
+        self.commit(device_id, |agg| agg.remove_tag(tag)).await?;
+        Ok(())
+    }
+
+    /// Merge discovered units into one logical stack device
+    ///
+    /// `units` lists each unit with its member number, the stack device
+    /// itself included. The stack device records every unit as a
+    /// `StackMember`; the other units record the stack they joined and leave
+    /// the device cache, so `get_device` and `list_devices` only see the
+    /// stack. Every unit is checked before anything is recorded.
+    ///
+    /// The units join first and the stack records its members last. If any
+    /// of those appends fails, the units that already joined record leaving
+    /// the stack again, so a failed merge leaves every unit a device of its
+    /// own and the stack unchanged.
+    #[tracing::instrument(skip_all, fields(%stack_id, correlation_id = telemetry::current_correlation_id()))]
+    pub async fn merge_stack(&self, stack_id: DeviceId, units: &[(DeviceId, u32)]) -> Result<(), ServiceError> {
+        let stack = self.get_device(stack_id).await
+            .ok_or(ServiceError::DeviceNotFound(stack_id))?;
+
+        let mut members = Vec::with_capacity(units.len());
+        for &(device_id, member) in units {
+            let mut unit = self.get_device(device_id).await
+                .ok_or(ServiceError::DeviceNotFound(device_id))?;
+            if device_id != stack_id {
+                unit.join_stack(stack_id)?;
+            }
+            members.push(StackMember::new(member, unit.mac()).discovered_as(device_id));
+        }
+        let mut check = stack;
+        members.iter().try_for_each(|member| check.add_stack_member(*member))?;
+
+        let mut joined = Vec::with_capacity(units.len());
+        for &(device_id, _) in units.iter().filter(|(device_id, _)| *device_id != stack_id) {
+            if let Err(e) = self.commit(device_id, |agg| agg.join_stack(stack_id)).await {
+                self.undo_stack_joins(stack_id, &joined).await;
+                return Err(e);
+            }
+            joined.push(device_id);
+        }
+        if let Err(e) = self
+            .commit(stack_id, |agg| members.iter().try_for_each(|member| agg.add_stack_member(*member)))
+            .await
+        {
+            self.undo_stack_joins(stack_id, &joined).await;
+            return Err(e);
+        }
+        for device_id in joined {
+            self.devices.remove(&device_id);
+        }
+
+        tracing::info!("Merged {} units into stack {}", members.len(), stack_id);
+        Ok(())
+    }
+
+    /// Take units back out of a stack whose merge failed part way
+    ///
+    /// A unit that cannot be taken back out is logged and left joined.
+    async fn undo_stack_joins(&self, stack_id: DeviceId, joined: &[DeviceId]) {
+        for &device_id in joined {
+            let left = self.commit(device_id, |agg| {
+                agg.leave_stack();
+                Ok(())
+            });
+            if let Err(e) = left.await {
+                tracing::error!("Failed to take unit {} back out of stack {}: {}", device_id, stack_id, e);
+            }
+        }
+    }
+
+    /// Split a stack device back into its units
+    ///
+    /// Every unit other than the stack device itself becomes a device again:
+    /// the aggregate it was discovered as if it still has one, otherwise a
+    /// newly discovered device. Returns the IDs of the split-out devices.
+    ///
+    /// The units are restored before the stack drops its members, and units
+    /// already restored are joined back if a later step fails, so a failed
+    /// split leaves the stack whole rather than orphaning its units.
+    #[tracing::instrument(skip_all, fields(%stack_id, correlation_id = telemetry::current_correlation_id()))]
+    pub async fn split_stack(&self, stack_id: DeviceId) -> Result<Vec<DeviceId>, ServiceError> {
+        let stack = self.get_device(stack_id).await
+            .ok_or(ServiceError::DeviceNotFound(stack_id))?;
+        let members = stack.stack_members().to_vec();
+
+        let mut split = Vec::new();
+        for member in members.iter().filter(|member| member.mac != stack.mac()) {
+            match self.restore_stack_unit(&stack, member).await {
+                Ok(device_id) => split.push(device_id),
+                Err(e) => {
+                    self.undo_stack_split(stack_id, &split).await;
+                    return Err(e);
+                }
+            }
+        }
+        let removed = self.commit(stack_id, |agg| {
+            for member in &members {
+                agg.remove_stack_member(member.mac);
+            }
+            Ok(())
+        });
+        if let Err(e) = removed.await {
+            self.undo_stack_split(stack_id, &split).await;
+            return Err(e);
+        }
+
+        tracing::info!("Split stack {} into {} devices", stack_id, split.len());
+        Ok(split)
+    }
+
+    /// Make one unit of `stack` a device of its own again
+    async fn restore_stack_unit(
+        &self,
+        stack: &NetworkDeviceAggregate,
+        member: &StackMember,
+    ) -> Result<DeviceId, ServiceError> {
+        let merged = match member.device_id {
+            Some(device_id) => self.replay_events(&device_id.to_string()).await?,
+            None => None,
+        };
+        let (mut unit, expected_version) = match merged {
+            Some(unit) => {
+                let version = unit.version();
+                (unit, version)
+            }
+            None => (NetworkDeviceAggregate::new_discovered(member.mac, stack.device_type().clone(), None), 0),
+        };
+        unit.leave_stack();
+
+        self.event_store
+            .append_expected(&unit.id().to_string(), expected_version, unit.take_pending_events())
+            .await?;
+        let device_id = unit.id();
+        self.devices.insert(device_id, unit);
+        Ok(device_id)
+    }
+
+    /// Join units back into a stack whose split failed part way
+    ///
+    /// A unit that cannot be joined back is logged and left standalone.
+    async fn undo_stack_split(&self, stack_id: DeviceId, split: &[DeviceId]) {
+        for &device_id in split {
+            match self.commit(device_id, |agg| agg.join_stack(stack_id)).await {
+                Ok(_) => {
+                    self.devices.remove(&device_id);
+                }
+                Err(e) => {
+                    tracing::error!("Failed to join unit {} back into stack {}: {}", device_id, stack_id, e);
+                }
+            }
+        }
+    }
+
+    /// Merge units the vendor reports as stacked into their master's device
+    ///
+    /// Units already in their stack are skipped. A failed merge is logged
+    /// and leaves the units as devices of their own.
+    async fn merge_reported_stacks(&self, reported: &[ReportedStackUnit]) {
+        let mut masters: Vec<MacAddress> = Vec::new();
+        for (master, ..) in reported {
+            if !masters.contains(master) {
+                masters.push(*master);
+            }
+        }
+
+        for master in masters {
+            let Some(stack) = self.devices.iter().find(|d| d.mac() == master).map(|d| d.value().clone()) else {
+                continue;
+            };
+            let mut units = Vec::new();
+            for &(_, mac, member) in reported.iter().filter(|(m, ..)| *m == master) {
+                if stack.stack_members().iter().any(|m| m.mac == mac) {
+                    continue;
+                }
+                if let Some(device_id) = self.find_device_by_mac(&mac).await {
+                    units.push((device_id, member));
+                }
+            }
+            if units.iter().all(|(device_id, _)| *device_id == stack.id()) {
+                continue;
+            }
+
+            if let Err(e) = self.merge_stack(stack.id(), &units).await {
+                tracing::warn!("Failed to merge stack {}: {}", stack.id(), e);
+            }
+        }
+    }
+
+    /// Run related commands together, persisting them only if all succeed
+    ///
+    /// The commands run in order through `CommandHandler` against copies of
+    /// the cached aggregates, each seeing the events of those before it. If
+    /// one is rejected, nothing is appended and the first failure is returned
+    /// as `BatchCommandFailed` with its index. Otherwise each aggregate's
+    /// events are appended with `append_expected` at the version the batch
+    /// started from, so a concurrent change fails the batch with
+    /// `ConcurrencyConflict`. Returns the appended events, in order.
+    ///
+    /// The event store appends one aggregate at a time, so before the first
+    /// append every aggregate the batch touches is checked against the store
+    /// and the batch fails with `ConcurrencyConflict`, appending nothing, if
+    /// any has moved on. Only a writer racing in between that check and the
+    /// appends can still leave a batch over several aggregates part applied.
+    #[tracing::instrument(skip_all, fields(commands = commands.len(), correlation_id = telemetry::current_correlation_id()))]
+    pub async fn execute_batch(&self, commands: Vec<NetworkCommand>) -> Result<Vec<NetworkEvent>, ServiceError> {
+        let devices: HashMap<String, NetworkDeviceAggregate> = self.devices
+            .iter()
+            .map(|device| (device.id().to_string(), device.value().clone()))
+            .collect();
+        let connections: HashMap<String, NetworkConnectionAggregate> = self.connections.read().await
+            .values()
+            .map(|connection| (connection.id().to_string(), connection.clone()))
+            .collect();
+
+        let mut repository = MemoryRepository::default();
+        devices.values().cloned().for_each(|device| repository.insert_device(device));
+        connections.values().cloned().for_each(|connection| repository.insert_connection(connection));
+        let mut handler = CommandHandler::new(repository);
+
+        let mut pending = Vec::new();
+        for (index, command) in commands.into_iter().enumerate() {
+            let events = handler.handle(command).map_err(|e| ServiceError::BatchCommandFailed {
+                index,
+                source: Box::new(e.into()),
+            })?;
+            handler.repository_mut().apply(events.clone());
+            pending.extend(events);
+        }
+
+        // One append per aggregate, in the order the batch first touched them
+        let mut by_aggregate: Vec<(String, Vec<NetworkEvent>)> = Vec::new();
+        for event in &pending {
+            let aggregate_id = event.aggregate_id();
+            match by_aggregate.iter_mut().find(|(id, _)| *id == aggregate_id) {
+                Some((_, events)) => events.push(event.clone()),
+                None => by_aggregate.push((aggregate_id, vec![event.clone()])),
+            }
+        }
+
+        let expected_version = |aggregate_id: &str| match devices.get(aggregate_id) {
+            Some(device) => device.version(),
+            None => connections.get(aggregate_id).map_or(0, |connection| connection.version()),
+        };
+        for (aggregate_id, _) in &by_aggregate {
+            let expected = expected_version(aggregate_id);
+            let moved_on = self.event_store.load_events_after(aggregate_id, expected).await?.len() as u64;
+            if moved_on > 0 {
+                return Err(ServiceError::ConcurrencyConflict { expected, actual: expected + moved_on });
+            }
+        }
+
+        for (aggregate_id, events) in by_aggregate {
+            if let Some(device) = devices.get(&aggregate_id) {
+                self.event_store.append_expected(&aggregate_id, device.version(), events.clone()).await?;
+                let mut updated = device.clone();
+                updated.apply_history(events);
+                self.devices.insert(updated.id(), updated);
+            } else {
+                let base = connections.get(&aggregate_id);
+                self.event_store.append_expected(&aggregate_id, expected_version(&aggregate_id), events.clone()).await?;
+                let updated = events.into_iter().fold(base.cloned(), NetworkConnectionAggregate::replay_event);
+                if let Some(connection) = updated {
+                    self.connections.write().await.insert(connection.id(), connection);
+                }
+            }
+        }
+
+        tracing::info!("Batch of {} events committed", pending.len());
+        Ok(pending)
+    }
+
+    /// Plan a connection between ports on two known devices
+    ///
+    /// Fails with `AggregateError::DuplicateConnection` if a connection that
+    /// has not been removed already describes the same link; see
+    /// `NetworkConnectionAggregate::is_same_link`.
+    pub async fn plan_connection(
+        &self,
+        source_device: DeviceId,
+        source_port: PortId,
+        target_device: DeviceId,
+        target_port: PortId,
+        connection_type: ConnectionType,
+    ) -> Result<ConnectionId, ServiceError> {
+        for device_id in [source_device, target_device] {
+            if self.get_device(device_id).await.is_none() {
+                return Err(ServiceError::DeviceNotFound(device_id));
+            }
+        }
+
+        let mut aggregate = NetworkConnectionAggregate::new_planned(
+            source_device,
+            source_port,
+            target_device,
+            target_port,
+            connection_type,
+        );
+        if let Some(existing) = self.connections.read().await
+            .values()
+            .find(|c| c.state() != ConnectionState::Removed && c.is_same_link(&aggregate))
+        {
+            return Err(AggregateError::DuplicateConnection { existing: existing.id() }.into());
+        }
+        let connection_id = aggregate.id();
+        self.event_store
+            .append_expected(&connection_id.to_string(), 0, aggregate.take_pending_events())
+            .await?;
+        self.connections.write().await.insert(connection_id, aggregate);
+
+        tracing::info!("Connection {} planned", connection_id);
+        Ok(connection_id)
+    }
+
+    /// Record that a planned connection has been cabled
+    ///
+    /// The cable is synced to the inventory, if one is configured.
+    pub async fn cable_connection(&self, connection_id: ConnectionId) -> Result<(), ServiceError> {
+        let aggregate = self.commit_connection(connection_id, |agg| agg.cable()).await?;
+
+        if let Some(ref inventory) = self.inventory_adapter {
+            inventory.sync_connection(&connection_info(&aggregate)).await?;
+            tracing::info!("Connection {} synced to inventory", connection_id);
+        }
+
+        tracing::info!("Connection {} cabled", connection_id);
+        Ok(())
+    }
+
+    /// Record that a connection's link is up, or has recovered
+    pub async fn activate_connection(
+        &self,
+        connection_id: ConnectionId,
+        speed: Option<LinkSpeed>,
+    ) -> Result<(), ServiceError> {
+        self.commit_connection(connection_id, |agg| agg.activate(speed)).await?;
+        tracing::info!("Connection {} active", connection_id);
+        Ok(())
+    }
+
+    /// Record that a connection's link is impaired
+    pub async fn degrade_connection(&self, connection_id: ConnectionId, reason: String) -> Result<(), ServiceError> {
+        self.commit_connection(connection_id, |agg| agg.degrade(reason)).await?;
+        tracing::warn!("Connection {} degraded", connection_id);
+        Ok(())
+    }
+
+    /// Remove a connection, and its cable from the inventory
+    pub async fn remove_connection(&self, connection_id: ConnectionId) -> Result<(), ServiceError> {
+        self.commit_connection(connection_id, |agg| agg.remove()).await?;
+
+        if let Some(ref inventory) = self.inventory_adapter {
+            let _ = inventory.remove_connection(connection_id).await;
+        }
+
+        tracing::info!("Connection {} removed", connection_id);
+        Ok(())
+    }
+
+    /// Create an empty site
+    pub async fn create_site(&self, name: impl Into<String>) -> Result<SiteId, ServiceError> {
+        let mut aggregate = SiteAggregate::new(name);
+        let site_id = aggregate.id();
+        self.event_store
+            .append_expected(&site_id.to_string(), 0, aggregate.take_pending_events())
+            .await?;
+        self.sites.write().await.insert(site_id, aggregate);
+
+        tracing::info!("Site {} created", site_id);
+        Ok(site_id)
+    }
+
+    /// Assign a known device to a site
+    ///
+    /// A device belongs to at most one site: it is first unassigned from any
+    /// other site it is in. Assigning it to its current site is a no-op.
+    pub async fn assign_device_to_site(&self, site_id: SiteId, device_id: DeviceId) -> Result<(), ServiceError> {
+        if self.get_device(device_id).await.is_none() {
+            return Err(ServiceError::DeviceNotFound(device_id));
+        }
+        if self.get_site(site_id).await.is_none() {
+            return Err(ServiceError::SiteNotFound(site_id));
+        }
+
+        if let Some(previous) = self.site_of(device_id).await {
+            if previous.id() == site_id {
+                return Ok(());
+            }
+            self.commit_site(previous.id(), |agg| {
+                agg.unassign_device(device_id);
+                Ok(())
+            }).await?;
+        }
+        self.commit_site(site_id, |agg| {
+            agg.assign_device(device_id);
+            Ok(())
+        }).await?;
+
+        tracing::info!("Device {} assigned to site {}", device_id, site_id);
+        Ok(())
+    }
+
+    /// Allocate an IP prefix to a site
+    ///
+    /// Fails with `AggregateError::OverlappingPrefix` if it overlaps a
+    /// prefix already allocated to the site.
+    pub async fn allocate_site_prefix(&self, site_id: SiteId, prefix: IpNetwork) -> Result<(), ServiceError> {
+        self.commit_site(site_id, |agg| agg.allocate_prefix(prefix)).await?;
+        tracing::info!("Prefix {} allocated to site {}", prefix, site_id);
+        Ok(())
+    }
+
+    /// Poll a device's statistics every `interval` on a background task
+    ///
+    /// Port link edges and health threshold crossings between polls are
+    /// recorded as `PortLinkChanged` and `DeviceHealthDegraded` events (see
+    /// the `monitor` module). Failed polls are logged and retried on the
+    /// next tick. The monitor runs until its handle is stopped or dropped.
+    pub async fn start_stats_monitor(
+        self: &Arc<Self>,
+        device_id: DeviceId,
+        interval: Duration,
+    ) -> Result<StatsMonitorHandle, ServiceError> {
+        if self.get_device(device_id).await.is_none() {
+            return Err(ServiceError::DeviceNotFound(device_id));
+        }
+
+        let service = Arc::clone(self);
+        let (stop, mut stopped) = tokio::sync::oneshot::channel::<()>();
+        let task = tokio::spawn(async move {
+            let mut tracker = StatsTracker::new(service.health_thresholds);
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = &mut stopped => break,
+                    _ = ticker.tick() => {
+                        if let Err(e) = service.poll_stats(device_id, &mut tracker).await {
+                            tracing::warn!("Stats poll for device {} failed: {}", device_id, e);
+                        }
+                    }
+                }
+            }
+            tracing::debug!("Stats monitor for device {} stopped", device_id);
+        });
+
+        Ok(StatsMonitorHandle::new(device_id, stop, task))
+    }
+
+    /// Poll a device's statistics once and record what changed since the last poll
+    async fn poll_stats(&self, device_id: DeviceId, tracker: &mut StatsTracker) -> Result<(), ServiceError> {
+        let device = self.get_device(device_id).await
+            .ok_or(ServiceError::DeviceNotFound(device_id))?;
+        let vendor_id = device.vendor_id()
+            .map(str::to_string)
+            .unwrap_or_else(|| device.mac().to_string());
+
+        let stats = self.vendor_adapter.get_device_stats(&vendor_id).await?;
+        let changes = tracker.observe(&stats);
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        self.commit(device_id, |agg| {
+            for change in changes {
+                match change {
+                    StatsChange::PortLink { port_id, up } => agg.record_port_link(port_id, up),
+                    StatsChange::HealthDegraded { metric, value } => agg.record_health_degraded(metric, value),
+                }
+            }
+            Ok(())
+        })
+        .await
+        .map(|_| ())
+    }
+
+    /// Probe every configured adapter without listing devices
+    ///
+    /// The vendor and inventory probes run concurrently.
+    pub async fn check_health(&self) -> ServiceHealth {
+        let vendor = async {
+            AdapterHealth {
+                name: self.vendor_adapter.vendor_name().to_string(),
+                status: self.vendor_adapter.health_check().await,
+            }
+        };
+        let inventory = async {
+            match &self.inventory_adapter {
+                Some(inventory) => Some(AdapterHealth {
+                    name: inventory.system_name().to_string(),
+                    status: inventory.health_check().await,
+                }),
+                None => None,
+            }
+        };
+
+        let (vendor, inventory) = futures::join!(vendor, inventory);
+        ServiceHealth { vendor, inventory }
+    }
+
+    /// Run a command against a copy of the cached aggregate and persist its events
+    ///
+    /// Events are appended with the aggregate's loaded version as the expected
+    /// version, so a concurrent writer causes `ConcurrencyConflict`. No cache
+    /// entry is borrowed while appending; the cached aggregate is only replaced
+    /// once the append succeeds, and the updated aggregate is returned.
+    async fn commit<F>(&self, device_id: DeviceId, command: F) -> Result<NetworkDeviceAggregate, ServiceError>
+    where
+        F: FnOnce(&mut NetworkDeviceAggregate) -> Result<(), AggregateError>,
+    {
+        let mut updated = self.get_device(device_id).await
+            .ok_or(ServiceError::DeviceNotFound(device_id))?;
+        let expected_version = updated.version();
+
+        command(&mut updated)?;
+
+        let events = updated.take_pending_events();
+        self.event_store
+            .append_expected(&device_id.to_string(), expected_version, events)
+            .await?;
+
+        self.devices.insert(device_id, updated.clone());
+        Ok(updated)
+    }
+
+    /// Run `command` against a cached connection and persist its events
+    async fn commit_connection<F>(
+        &self,
+        connection_id: ConnectionId,
+        command: F,
+    ) -> Result<NetworkConnectionAggregate, ServiceError>
+    where
+        F: FnOnce(&mut NetworkConnectionAggregate) -> Result<(), AggregateError>,
+    {
+        let mut updated = self.get_connection(connection_id).await
+            .ok_or(ServiceError::ConnectionNotFound(connection_id))?;
+        let expected_version = updated.version();
+
+        command(&mut updated)?;
+
+        let events = updated.take_pending_events();
+        self.event_store
+            .append_expected(&connection_id.to_string(), expected_version, events)
+            .await?;
+
+        self.connections.write().await.insert(connection_id, updated.clone());
+        Ok(updated)
+    }
+
+    /// Run `command` against a cached site and persist its events
+    async fn commit_site<F>(&self, site_id: SiteId, command: F) -> Result<SiteAggregate, ServiceError>
+    where
+        F: FnOnce(&mut SiteAggregate) -> Result<(), AggregateError>,
+    {
+        let mut updated = self.get_site(site_id).await
+            .ok_or(ServiceError::SiteNotFound(site_id))?;
+        let expected_version = updated.version();
+
+        command(&mut updated)?;
+
+        let events = updated.take_pending_events();
+        if events.is_empty() {
+            return Ok(updated);
+        }
+        self.event_store
+            .append_expected(&site_id.to_string(), expected_version, events)
+            .await?;
+
+        self.sites.write().await.insert(site_id, updated.clone());
+        Ok(updated)
+    }
+
+    /// Get a connection by ID
+    pub async fn get_connection(&self, connection_id: ConnectionId) -> Option<NetworkConnectionAggregate> {
+        self.connections.read().await.get(&connection_id).cloned()
+    }
+
+    /// List all connections, including removed ones
+    pub async fn list_connections(&self) -> Vec<NetworkConnectionAggregate> {
+        self.connections.read().await.values().cloned().collect()
+    }
+
+    /// Get a site by ID
+    pub async fn get_site(&self, site_id: SiteId) -> Option<SiteAggregate> {
+        self.sites.read().await.get(&site_id).cloned()
+    }
+
+    /// List all sites
+    pub async fn list_sites(&self) -> Vec<SiteAggregate> {
+        self.sites.read().await.values().cloned().collect()
+    }
+
+    /// The site a device is assigned to, if any
+    pub async fn site_of(&self, device_id: DeviceId) -> Option<SiteAggregate> {
+        self.sites.read().await
+            .values()
+            .find(|site| site.contains_device(device_id))
+            .cloned()
+    }
+
+    /// Devices assigned to a site, in assignment order
+    pub async fn list_devices_in_site(&self, site_id: SiteId) -> Result<Vec<NetworkDeviceAggregate>, ServiceError> {
+        let site = self.get_site(site_id).await
+            .ok_or(ServiceError::SiteNotFound(site_id))?;
+        Ok(site.devices().iter().filter_map(|id| self.devices.get(id).map(|d| d.value().clone())).collect())
+    }
+
+    /// Get a device by ID
+    pub async fn get_device(&self, device_id: DeviceId) -> Option<NetworkDeviceAggregate> {
+        self.devices.get(&device_id).map(|d| d.value().clone())
+    }
+
+    /// List all devices
+    pub async fn list_devices(&self) -> Vec<NetworkDeviceAggregate> {
+        self.devices.iter().map(|d| d.value().clone()).collect()
+    }
+
+    /// List devices by state
+    pub async fn list_devices_by_state(&self, state: DeviceState) -> Vec<NetworkDeviceAggregate> {
+        self.devices.iter()
+            .filter(|d| d.state() == state)
+            .map(|d| d.value().clone())
+            .collect()
+    }
+
+    /// Find devices matching every condition of `filter`
+    pub async fn find_devices(&self, filter: DeviceFilter) -> Vec<NetworkDeviceAggregate> {
+        self.devices.iter()
+            .filter(|d| filter.matches(d))
+            .map(|d| d.value().clone())
+            .collect()
+    }
+
+    /// Stream events appended on `subject` from now on
+    ///
+    /// Backed by `EventStorePort::subscribe_stream`; events recorded before
+    /// the call are not included.
+    pub async fn subscribe_events(&self, subject: &str) -> Result<EventStream, ServiceError> {
+        Ok(self.event_store.subscribe_stream(subject).await?)
+    }
+
+    /// Find device by MAC address
+    async fn find_device_by_mac(&self, mac: &MacAddress) -> Option<DeviceId> {
+        self.devices.iter()
+            .find(|d| d.has_mac(*mac))
+            .map(|d| d.id())
+    }
+
+    /// Replay events from the event store to rebuild state
+    ///
+    /// Starts from the newest usable snapshot (if any) and only folds events
+    /// recorded after it. When a snapshot threshold is configured and more
+    /// than that many events had to be folded, a fresh snapshot is saved.
+    /// A unit merged into a stack is returned but not cached.
+    pub async fn replay_events(&self, aggregate_id: &str) -> Result<Option<NetworkDeviceAggregate>, ServiceError> {
+        let (aggregate, folded) = match self.load_snapshot(aggregate_id).await {
+            Some(mut aggregate) => {
+                let events = self.event_store
+                    .load_events_after(aggregate_id, aggregate.version())
+                    .await?;
+                let folded = events.len();
+                aggregate.apply_history(events);
+                (Some(aggregate), folded)
+            }
+            None => self.fold_event_stream(aggregate_id, None).await?,
+        };
+
+        let Some(aggregate) = aggregate else {
+            return Ok(None);
+        };
+
+        if let Some(threshold) = self.snapshot_threshold {
+            if folded as u64 >= threshold {
+                if let Err(e) = self.save_snapshot(&aggregate).await {
+                    tracing::warn!("Failed to snapshot aggregate {}: {}", aggregate_id, e);
+                }
+            }
+        }
+
+        // Cache the reconstructed aggregate
+        if aggregate.stacked_into().is_none() {
+            self.devices.insert(aggregate.id(), aggregate.clone());
+        } else {
+            self.devices.remove(&aggregate.id());
+        }
+
+        Ok(Some(aggregate))
+    }
+
+    /// Reconstruct a device as it was after its first `version` events
+    ///
+    /// Folding stops as soon as the target version is reached, starting from
+    /// the snapshot when one exists at or before it. An aggregate with fewer
+    /// events is returned at its latest version. The historical aggregate is
+    /// not cached, so `get_device` keeps answering with the current state.
+    pub async fn replay_to_version(
+        &self,
+        aggregate_id: &str,
+        version: u64,
+    ) -> Result<Option<NetworkDeviceAggregate>, ServiceError> {
+        if version == 0 {
+            return Ok(None);
+        }
+
+        let snapshot = self.load_snapshot(aggregate_id).await
+            .filter(|aggregate| aggregate.version() <= version);
+
+        match snapshot {
+            Some(mut aggregate) => {
+                let remaining = (version - aggregate.version()) as usize;
+                let events = self.event_store
+                    .load_events_after(aggregate_id, aggregate.version())
+                    .await?;
+                aggregate.apply_history(events.into_iter().take(remaining));
+                Ok(Some(aggregate))
+            }
+            None => Ok(self.fold_event_stream(aggregate_id, Some(version)).await?.0),
+        }
+    }
+
+    /// Reconstruct a device as it was at `when`
+    ///
+    /// Folds the events the store recorded at or before `when`, using the
+    /// per-event timestamps the store keeps (the `CIM-Timestamp` header for
+    /// NATS). Returns `None` if the device did not exist yet. Like
+    /// `replay_to_version`, the result is not cached.
+    pub async fn replay_to_timestamp(
+        &self,
+        aggregate_id: &str,
+        when: DateTime<Utc>,
+    ) -> Result<Option<NetworkDeviceAggregate>, ServiceError> {
+        let events = self.event_store.load_timestamped_events(aggregate_id).await?;
+
+        Ok(events
+            .into_iter()
+            .take_while(|recorded| recorded.recorded_at <= when)
+            .fold(None, |aggregate, recorded| NetworkDeviceAggregate::replay_event(aggregate, recorded.event)))
+    }
+
+    /// Everything recorded for an aggregate, with who and when
+    ///
+    /// Entries come in the order the store recorded them, not the order the
+    /// aggregate's version would suggest, and carry the correlation and
+    /// causation ids the events were appended with. Needs a store that keeps
+    /// per-event metadata, such as NATS.
+    pub async fn audit_trail(&self, aggregate_id: &str) -> Result<Vec<AuditEntry>, ServiceError> {
+        let events = self.event_store.load_recorded_events(aggregate_id).await?;
+        Ok(events.into_iter().map(AuditEntry::from).collect())
+    }
+
+    /// Rebuild the device cache from the event store
+    ///
+    /// Replays every device aggregate the store knows about, so `get_device`
+    /// and `list_devices` answer after a restart without waiting for
+    /// discovery. Returns the IDs of the devices loaded; units merged into a
+    /// stack are replayed but not loaded.
+    pub async fn rehydrate(&self) -> Result<Vec<DeviceId>, ServiceError> {
+        let aggregate_ids = self.event_store.list_aggregate_ids("device").await?;
+        let mut loaded = Vec::with_capacity(aggregate_ids.len());
+
+        for aggregate_id in &aggregate_ids {
+            match self.replay_events(aggregate_id).await? {
+                Some(aggregate) if aggregate.stacked_into().is_none() => loaded.push(aggregate.id()),
+                _ => {}
+            }
+        }
+
+        tracing::info!("Rehydrated {} devices from the event store", loaded.len());
+        Ok(loaded)
+    }
+
+    /// Rebuild an aggregate by folding its event stream one event at a time
+    ///
+    /// Returns the aggregate (if any) and the number of events folded.
+    /// Undecodable events are logged and skipped. With `stop_at`, the rest of
+    /// the stream is dropped unread once the aggregate reaches that version.
+    async fn fold_event_stream(
+        &self,
+        aggregate_id: &str,
+        stop_at: Option<u64>,
+    ) -> Result<(Option<NetworkDeviceAggregate>, usize), PortError> {
+        let mut stream = self.event_store.load_events_stream(aggregate_id).await?;
+        let mut aggregate = None;
+        let mut folded = 0;
+
+        while let Some(item) = stream.next().await {
+            match item {
+                Ok(event) => {
+                    aggregate = NetworkDeviceAggregate::replay_event(aggregate, event);
+                    folded += 1;
+                    if stop_at.is_some_and(|target| aggregate.as_ref().is_some_and(|a| a.version() >= target)) {
+                        break;
+                    }
+                }
+                Err(PortError::Deserialization(e)) => {
+                    tracing::warn!("Skipping undecodable event for {}: {}", aggregate_id, e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok((aggregate, folded))
+    }
+
+    /// Save a snapshot of a cached device to the event store
+    pub async fn snapshot_device(&self, device_id: DeviceId) -> Result<(), ServiceError> {
+        let aggregate = self.devices.get(&device_id)
+            .map(|d| d.value().clone())
+            .ok_or(ServiceError::DeviceNotFound(device_id))?;
+
+        Ok(self.save_snapshot(&aggregate).await?)
+    }
+
+    /// Serialize and persist an aggregate snapshot
+    async fn save_snapshot(&self, aggregate: &NetworkDeviceAggregate) -> Result<(), PortError> {
+        let mut data = vec![SNAPSHOT_FORMAT_VERSION];
+        serde_json::to_writer(&mut data, aggregate)
+            .map_err(|e| PortError::VendorError(format!("Snapshot serialization failed: {}", e)))?;
+
+        self.event_store
+            .save_snapshot(&aggregate.id().to_string(), aggregate.version(), data)
+            .await
+    }
+
+    /// Load and decode the latest snapshot, ignoring unusable ones
+    async fn load_snapshot(&self, aggregate_id: &str) -> Option<NetworkDeviceAggregate> {
+        let snapshot = match self.event_store.load_latest_snapshot(aggregate_id).await {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => return None,
+            Err(e) => {
+                tracing::warn!("Failed to load snapshot for {}: {}", aggregate_id, e);
+                return None;
+            }
+        };
+
+        match snapshot.data.split_first() {
+            Some((&SNAPSHOT_FORMAT_VERSION, body)) => {
+                match serde_json::from_slice::<NetworkDeviceAggregate>(body) {
+                    Ok(aggregate) if aggregate.version() == snapshot.version => Some(aggregate),
+                    Ok(_) => {
+                        tracing::warn!("Ignoring snapshot for {} with mismatched version", aggregate_id);
+                        None
+                    }
+                    Err(e) => {
+                        tracing::warn!("Ignoring undecodable snapshot for {}: {}", aggregate_id, e);
+                        None
+                    }
+                }
+            }
+            _ => {
+                tracing::debug!("Ignoring snapshot for {} with unknown format", aggregate_id);
+                None
+            }
+        }
+    }
+
+    /// Full discovery and provisioning workflow
+    ///
+    /// 1. Discover devices from vendor
+    /// 2. Adopt any unadopted devices
+    /// 3. Sync all to inventory, except devices whose sync is queued and not
+    ///    yet due or was dead-lettered; those are left to the sync worker
+    ///
+    /// Adoption and inventory sync run up to the configured provisioning
+    /// concurrency at a time. A failure for one device does not stop the run;
+    /// it is recorded in the returned report. Only a failed discovery is an error.
+    #[tracing::instrument(skip_all, fields(correlation_id = telemetry::current_correlation_id()))]
+    pub async fn discover_and_provision(&self) -> Result<ProvisioningReport, ServiceError> {
+        // Step 1: Discover
+        let discovered = self.discover_devices().await?;
+
+        // Step 2: Adopt discovered devices
+        let adoption = self
+            .for_each_device(discovered.clone(), |device_id| self.adopt_device(device_id))
+            .await;
+        for (device_id, e) in &adoption.failed {
+            tracing::warn!("Failed to adopt device {}: {}", device_id, e);
+        }
+
+        // Step 3: Sync all devices to inventory
+        let inventory_sync = if let Some(inventory) = &self.inventory_adapter {
+            let now = self.clock.now();
+            let device_ids = self.devices.iter()
+                .filter(|d| !Self::sync_is_queued(d.value(), inventory.system_name(), now))
+                .map(|d| *d.key())
+                .collect();
+            self.for_each_device(device_ids, |device_id| async move {
+                self.sync_to_inventory(device_id).await.map(|_| ())
+            })
+            .await
+        } else {
+            PhaseReport::default()
+        };
+        for (device_id, e) in &inventory_sync.failed {
+            tracing::warn!("Failed to sync device {} to inventory: {}", device_id, e);
+        }
+
+        tracing::info!(
+            "Provisioning complete: {} discovered, {}/{} adopted, {}/{} synced",
+            discovered.len(),
+            adoption.succeeded.len(),
+            discovered.len(),
+            inventory_sync.succeeded.len(),
+            inventory_sync.succeeded.len() + inventory_sync.failed.len()
+        );
+
+        Ok(ProvisioningReport { discovered, adoption, inventory_sync })
+    }
+
+    /// Preview `discover_and_provision` without changing anything
+    ///
+    /// Lists the vendor's devices as a real run would, then computes the
+    /// discovery, adoption and inventory sync events and adapter calls
+    /// against copies of the cached aggregates. Nothing is appended to the
+    /// event store, no vendor or inventory call that changes state is made,
+    /// and the discovery cursor is not advanced.
+    #[tracing::instrument(skip_all, fields(correlation_id = telemetry::current_correlation_id()))]
+    pub async fn plan_provisioning(&self) -> Result<ChangePlan, ServiceError> {
+        let cursor = self.discovery_cursor.read().await.clone();
+        let (vendor_devices, _) = self
+            .retry_transient(|| self.vendor_adapter.list_devices_since(cursor.clone()))
+            .await?;
+        let mut devices: HashMap<DeviceId, NetworkDeviceAggregate> = self.devices
+            .iter()
+            .map(|d| (*d.key(), d.value().clone()))
+            .collect();
+        let mut plan = ChangePlan::default();
+
+        // Discovery
+        for vendor_device in vendor_devices {
+            if devices.values().any(|d| d.mac() == vendor_device.mac) {
+                continue;
+            }
+            let mut aggregate = {
+                let taken: Vec<&str> = devices.values().map(|d| d.name()).collect();
+                self.new_device_aggregate(&vendor_device, &taken)
+            };
+            plan.events.extend(aggregate.take_pending_events());
+            plan.discovered.push(aggregate.id());
+            devices.insert(aggregate.id(), aggregate);
+        }
+
+        // Adoption of the newly discovered devices
+        for device_id in &plan.discovered {
+            let Some(aggregate) = devices.get_mut(device_id) else {
+                continue;
+            };
+            let vendor_id = aggregate.mac().to_string();
+            aggregate.adopt(vendor_id.clone())?;
+            plan.events.extend(aggregate.take_pending_events());
+            plan.calls.push(PlannedCall::AdoptDevice { device_id: *device_id, vendor_id });
+        }
+
+        // Inventory sync of every device whose inventory fields changed
+        if let Some(ref inventory) = self.inventory_adapter {
+            let system = inventory.system_name();
+            for aggregate in devices.values_mut() {
+                if !aggregate.needs_inventory_sync(system) {
+                    continue;
+                }
+                plan.calls.push(PlannedCall::SyncDevice { device_id: aggregate.id(), system: system.to_string() });
+                aggregate.record_inventory_sync(format!("{}-{}", system, aggregate.id()), system.to_string());
+                plan.events.extend(aggregate.take_pending_events());
+            }
+        }
+
+        tracing::info!(
+            "Provisioning plan: {} to discover, {} events, {} adapter calls",
+            plan.discovered.len(),
+            plan.events.len(),
+            plan.calls.len()
+        );
+        Ok(plan)
+    }
+
+    /// Run `operation` for each device, at most `provisioning_concurrency` at once
+    async fn for_each_device<F, Fut>(&self, device_ids: Vec<DeviceId>, operation: F) -> PhaseReport
+    where
+        F: Fn(DeviceId) -> Fut,
+        Fut: std::future::Future<Output = Result<(), ServiceError>>,
+    {
+        let results = stream::iter(device_ids)
+            .map(|device_id| {
+                let pending = operation(device_id);
+                async move { (device_id, pending.await) }
+            })
+            .buffer_unordered(self.provisioning_concurrency)
+            .collect()
+            .await;
+
+        PhaseReport::from_results(results)
+    }
+
+    /// Run an adapter call, retrying transient failures per the retry policy
+    ///
+    /// Waits as long as a rate-limited call asks, or the policy's backoff
+    /// otherwise. Permanent failures are returned at once.
+    async fn retry_transient<T, F, Fut>(&self, call: F) -> Result<T, PortError>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<T, PortError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match call().await {
+                Err(e) if e.is_transient() && attempt < self.retry_policy.max_attempts => {
+                    let delay = e.retry_after()
+                        .unwrap_or_else(|| self.retry_policy.backoff(attempt))
+                        .min(self.retry_policy.max_delay);
+                    tracing::warn!(
+                        "{}; retrying in {:?} (attempt {} of {})",
+                        e,
+                        delay,
+                        attempt + 1,
+                        self.retry_policy.max_attempts
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Builder for NetworkService
+pub struct NetworkServiceBuilder {
+    event_store: Option<Arc<dyn EventStorePort>>,
+    vendor_adapter: Option<Arc<dyn DeviceControlPort>>,
     inventory_adapter: Option<Arc<dyn InventoryPort>>,
+    snapshot_threshold: Option<u64>,
+    provisioning_concurrency: usize,
+    purge_after: Option<Duration>,
+    retry_policy: RetryPolicy,
+    inventory_sync_retry: RetryPolicy,
+    health_thresholds: HealthThresholds,
+    naming: Arc<dyn NamingStrategy>,
+    clock: Arc<dyn Clock>,
+}
+
+impl NetworkServiceBuilder {
+    /// Create a new builder
+    pub fn new() -> Self {
+        Self {
+            event_store: None,
+            vendor_adapter: None,
+            inventory_adapter: None,
+            snapshot_threshold: None,
+            provisioning_concurrency: DEFAULT_PROVISIONING_CONCURRENCY,
+            purge_after: None,
+            retry_policy: RetryPolicy::none(),
+            inventory_sync_retry: DEFAULT_INVENTORY_SYNC_RETRY,
+            health_thresholds: HealthThresholds::default(),
+            naming: Arc::new(SequentialNaming::default()),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Set the event store
+    pub fn event_store<E: EventStorePort + 'static>(mut self, store: E) -> Self {
+        self.event_store = Some(Arc::new(store));
+        self
+    }
+
+    /// Set the event store from Arc
+    pub fn event_store_arc(mut self, store: Arc<dyn EventStorePort>) -> Self {
+        self.event_store = Some(store);
+        self
+    }
+
+    /// Set the vendor adapter
+    pub fn vendor_adapter<V: DeviceControlPort + 'static>(mut self, adapter: V) -> Self {
+        self.vendor_adapter = Some(Arc::new(adapter));
+        self
+    }
+
+    /// Set the vendor adapter from Arc
+    pub fn vendor_adapter_arc(mut self, adapter: Arc<dyn DeviceControlPort>) -> Self {
+        self.vendor_adapter = Some(adapter);
+        self
+    }
+
+    /// Set the inventory adapter
+    pub fn inventory_adapter<I: InventoryPort + 'static>(mut self, adapter: I) -> Self {
+        self.inventory_adapter = Some(Arc::new(adapter));
+        self
+    }
+
+    /// Set the inventory adapter from Arc
+    pub fn inventory_adapter_arc(mut self, adapter: Arc<dyn InventoryPort>) -> Self {
+        self.inventory_adapter = Some(adapter);
+        self
+    }
+
+    /// Snapshot an aggregate whenever replay folds at least `events` events
+    pub fn snapshot_threshold(mut self, events: u64) -> Self {
+        self.snapshot_threshold = Some(events);
+        self
+    }
+
+    /// Purge each device's history `retention` after it is decommissioned
+    ///
+    /// The purge runs on a background task. One still waiting when the
+    /// process stops is lost; use `NetworkService::purge_device` for devices
+    /// decommissioned before a restart.
+    pub fn purge_decommissioned_after(mut self, retention: Duration) -> Self {
+        self.purge_after = Some(retention);
+        self
+    }
+
+    /// Adopt or sync at most `limit` devices at once (default 8, minimum 1)
+    pub fn provisioning_concurrency(mut self, limit: usize) -> Self {
+        self.provisioning_concurrency = limit.max(1);
+        self
+    }
+
+    /// Retry discovery, adoption and inventory calls that fail transiently
+    ///
+    /// Off by default: the HTTP adapters already retry throttled requests
+    /// themselves. See `PortError::is_transient` for what is retried.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Retry failed inventory syncs from the sync queue following `policy`
+    ///
+    /// A sync is dead-lettered once it has failed `max_attempts` times. See
+    /// the `sync_queue` module.
+    pub fn inventory_sync_retry(mut self, policy: RetryPolicy) -> Self {
+        self.inventory_sync_retry = policy;
+        self
+    }
+
+    /// Report devices as degraded above these levels (see `start_stats_monitor`)
+    pub fn health_thresholds(mut self, thresholds: HealthThresholds) -> Self {
+        self.health_thresholds = thresholds;
+        self
+    }
+
+    /// Name discovered devices the vendor reports without a name
+    ///
+    /// Defaults to `SequentialNaming::default()` (`switch-default-01`, ...).
+    pub fn naming_strategy<N: NamingStrategy + 'static>(mut self, naming: N) -> Self {
+        self.naming = Arc::new(naming);
+        self
+    }
+
+    /// Set the naming strategy from Arc
+    pub fn naming_strategy_arc(mut self, naming: Arc<dyn NamingStrategy>) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Read the time recorded in events from `clock`
+    ///
+    /// Defaults to `SystemClock`; tests inject a `MockClock`.
+    pub fn clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Build the service
+    pub fn build(self) -> Result<NetworkService, PortError> {
+        let event_store = self.event_store
+            .ok_or_else(|| PortError::NotSupported("Event store is required".to_string()))?;
+
+        let vendor_adapter = self.vendor_adapter
+            .ok_or_else(|| PortError::NotSupported("Vendor adapter is required".to_string()))?;
+
+        Ok(NetworkService {
+            event_store,
+            vendor_adapter,
+            inventory_adapter: self.inventory_adapter,
+            devices: Arc::new(DashMap::new()),
+            connections: Arc::new(RwLock::new(HashMap::new())),
+            sites: Arc::new(RwLock::new(HashMap::new())),
+            last_seen: Arc::new(RwLock::new(HashMap::new())),
+            discovery_cursor: Arc::new(RwLock::new(None)),
+            snapshot_threshold: self.snapshot_threshold,
+            provisioning_concurrency: self.provisioning_concurrency,
+            purge_after: self.purge_after,
+            retry_policy: self.retry_policy,
+            inventory_sync_retry: self.inventory_sync_retry,
+            health_thresholds: self.health_thresholds,
+            naming: self.naming,
+            clock: self.clock,
+            metrics: ServiceMetrics::new(),
+        })
+    }
+}
+
+impl Default for NetworkServiceBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Inventory view of a connection
+fn connection_info(connection: &NetworkConnectionAggregate) -> ConnectionInfo {
+    ConnectionInfo {
+        connection_id: connection.id(),
+        source_device: connection.source_device(),
+        source_port: connection.source_port().clone(),
+        target_device: connection.target_device(),
+        target_port: connection.target_port().clone(),
+        connection_type: connection.connection_type().clone(),
+        speed: connection.speed(),
+    }
+}
+
+/// Purge a device's history, record its tombstone and drop it from the caches
+///
+/// Shared by `purge_device` and the purge scheduled by `decommission_device`.
+async fn purge_device_history(
+    event_store: &dyn EventStorePort,
+    devices: &DashMap<DeviceId, NetworkDeviceAggregate>,
+    last_seen: &RwLock<HashMap<DeviceId, DateTime<Utc>>>,
+    device_id: DeviceId,
+) -> Result<(), PortError> {
+    event_store.purge_aggregate(&device_id.to_string()).await?;
+    event_store.append_tombstone(NetworkEvent::DevicePurged { device_id }).await?;
+
+    devices.remove(&device_id);
+    last_seen.write().await.remove(&device_id);
+    tracing::info!("Device {} purged", device_id);
+    Ok(())
+}
+
+/// A unit the vendor reports as stacked: master MAC, unit MAC, member number
+type ReportedStackUnit = (MacAddress, MacAddress, u32);
+
+/// Infer device type from model string
+fn infer_device_type(model: &str) -> DeviceType {
+    let model_lower = model.to_lowercase();
+
+    // Checked first: firewall models such as `PA-440` would otherwise match
+    // the access point substrings below
+    if model_lower.contains("firewall") || model_lower.contains("fortigate") || model_lower.starts_with("fgt")
+        || model_lower.starts_with("pa-") || model_lower.contains("opnsense") || model_lower.contains("pfsense")
+    {
+        DeviceType::Firewall
+    } else if model_lower.contains("gateway") || model_lower.contains("ugw") || model_lower.contains("udm") {
+        DeviceType::Gateway
+    } else if model_lower.contains("switch") || model_lower.contains("usw") {
+        DeviceType::Switch
+    } else if model_lower.contains("ap") || model_lower.contains("uap") || model_lower.contains("u6") {
+        DeviceType::AccessPoint
+    } else {
+        DeviceType::Generic { model: model.to_string() }
+    }
 }
 
-impl NetworkServiceBuilder {
-    /// Create a new builder
-    pub fn new() -> Self {
-        Self {
-            event_store: None,
-            vendor_adapter: None,
-            inventory_adapter: None,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::VlanConfig;
+    use crate::domain::events::NetworkEvent;
+    use crate::domain::clock::MockClock;
+    use crate::domain::config_template::ConfigTemplate;
+    use crate::domain::projections::DeviceCountByState;
+    use crate::domain::ports::{
+        AggregateSnapshot, DeviceStats, EventStream, EventSubscription,
+        IpAssignment, PortStats, TimestampedEvent, VendorConfig, VendorDevice,
+        STACK_MASTER_PROPERTY, STACK_MEMBER_PROPERTY,
+    };
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    // ========================================================================
+    // Test doubles
+    // ========================================================================
+
+    /// In-memory event store that counts how many events it hands out
+    #[derive(Default)]
+    struct MemoryEventStore {
+        events: Mutex<Vec<NetworkEvent>>,
+        snapshots: Mutex<HashMap<String, AggregateSnapshot>>,
+        tombstones: Mutex<Vec<NetworkEvent>>,
+        events_read: AtomicUsize,
+        /// Yield a deserialization error in place of the event at this index
+        undecodable_at: Mutex<Option<usize>>,
+    }
+
+    #[async_trait]
+    impl EventStorePort for MemoryEventStore {
+        async fn append(&self, events: Vec<NetworkEvent>) -> Result<(), PortError> {
+            self.events.lock().unwrap().extend(events);
+            Ok(())
+        }
+
+        async fn append_expected(
+            &self,
+            aggregate_id: &str,
+            expected_version: u64,
+            events: Vec<NetworkEvent>,
+        ) -> Result<(), PortError> {
+            let mut stored = self.events.lock().unwrap();
+            let actual = stored.iter().filter(|e| e.aggregate_id() == aggregate_id).count() as u64;
+            if actual != expected_version {
+                return Err(AggregateError::ConcurrencyConflict { expected: expected_version, actual }.into());
+            }
+            stored.extend(events);
+            Ok(())
+        }
+
+        async fn load_events(&self, aggregate_id: &str) -> Result<Vec<NetworkEvent>, PortError> {
+            self.load_events_after(aggregate_id, 0).await
+        }
+
+        async fn load_events_after(&self, aggregate_id: &str, version: u64) -> Result<Vec<NetworkEvent>, PortError> {
+            let events: Vec<NetworkEvent> = self.events.lock().unwrap()
+                .iter()
+                .filter(|e| e.aggregate_id() == aggregate_id)
+                .skip(version as usize)
+                .cloned()
+                .collect();
+            self.events_read.fetch_add(events.len(), Ordering::SeqCst);
+            Ok(events)
+        }
+
+        async fn load_events_stream(&self, aggregate_id: &str) -> Result<EventStream, PortError> {
+            let events: Vec<NetworkEvent> = self.events.lock().unwrap()
+                .iter()
+                .filter(|e| e.aggregate_id() == aggregate_id)
+                .cloned()
+                .collect();
+            let undecodable_at = *self.undecodable_at.lock().unwrap();
+            self.events_read.fetch_add(events.len(), Ordering::SeqCst);
+
+            Ok(Box::pin(futures::stream::iter(events.into_iter().enumerate().map(move |(i, event)| {
+                if Some(i) == undecodable_at {
+                    Err(PortError::Deserialization(format!("corrupt payload at {}", i)))
+                } else {
+                    Ok(event)
+                }
+            }))))
+        }
+
+        /// Each event is stamped one second after the one appended before it
+        async fn load_timestamped_events(&self, aggregate_id: &str) -> Result<Vec<TimestampedEvent>, PortError> {
+            Ok(self.events.lock().unwrap()
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| e.aggregate_id() == aggregate_id)
+                .map(|(i, e)| TimestampedEvent {
+                    recorded_at: DateTime::UNIX_EPOCH + chrono::Duration::seconds(i as i64),
+                    event: e.clone(),
+                })
+                .collect())
+        }
+
+        /// Sequences count every event appended; ids are derived from them
+        async fn load_recorded_events(&self, aggregate_id: &str) -> Result<Vec<RecordedEvent>, PortError> {
+            Ok(self.events.lock().unwrap()
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| e.aggregate_id() == aggregate_id)
+                .map(|(i, e)| RecordedEvent {
+                    sequence: i as u64 + 1,
+                    recorded_at: DateTime::UNIX_EPOCH + chrono::Duration::seconds(i as i64),
+                    event_id: Some(format!("evt-{}", i + 1)),
+                    correlation_id: None,
+                    causation_id: None,
+                    event: e.clone(),
+                })
+                .collect())
+        }
+
+        async fn subscribe(&self, subject: &str) -> Result<EventSubscription, PortError> {
+            Ok(EventSubscription::with_subject(subject))
+        }
+
+        async fn list_aggregate_ids(&self, aggregate_type: &str) -> Result<Vec<String>, PortError> {
+            let ids: std::collections::BTreeSet<String> = self.events.lock().unwrap()
+                .iter()
+                .filter(|e| e.aggregate_type() == aggregate_type)
+                .map(|e| e.aggregate_id())
+                .collect();
+            Ok(ids.into_iter().collect())
+        }
+
+        async fn save_snapshot(&self, aggregate_id: &str, version: u64, data: Vec<u8>) -> Result<(), PortError> {
+            self.snapshots.lock().unwrap().insert(
+                aggregate_id.to_string(),
+                AggregateSnapshot { aggregate_id: aggregate_id.to_string(), version, data },
+            );
+            Ok(())
+        }
+
+        async fn load_latest_snapshot(&self, aggregate_id: &str) -> Result<Option<AggregateSnapshot>, PortError> {
+            Ok(self.snapshots.lock().unwrap().get(aggregate_id).cloned())
+        }
+
+        async fn purge_aggregate(&self, aggregate_id: &str) -> Result<(), PortError> {
+            self.events.lock().unwrap().retain(|e| e.aggregate_id() != aggregate_id);
+            self.snapshots.lock().unwrap().remove(aggregate_id);
+            Ok(())
+        }
+
+        async fn append_tombstone(&self, event: NetworkEvent) -> Result<(), PortError> {
+            self.tombstones.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    struct NullVendorAdapter;
+
+    #[async_trait]
+    impl DeviceControlPort for NullVendorAdapter {
+        fn vendor_name(&self) -> &str { "Null" }
+        async fn connect(&self) -> Result<(), PortError> { Ok(()) }
+        async fn disconnect(&self) -> Result<(), PortError> { Ok(()) }
+        fn is_connected(&self) -> bool { true }
+        async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError> { Ok(vec![]) }
+        async fn get_device(&self, _vendor_id: &str) -> Result<VendorDevice, PortError> {
+            Err(PortError::NotSupported("null adapter".to_string()))
+        }
+        async fn adopt_device(&self, _vendor_id: &str) -> Result<(), PortError> { Ok(()) }
+        async fn apply_config(&self, _vendor_id: &str, _config: DeviceConfigRequest) -> Result<(), PortError> { Ok(()) }
+        async fn apply_raw_config(&self, _vendor_id: &str, _config: VendorConfig) -> Result<(), PortError> { Ok(()) }
+        async fn restart_device(&self, _vendor_id: &str) -> Result<(), PortError> { Ok(()) }
+        async fn get_device_stats(&self, _vendor_id: &str) -> Result<DeviceStats, PortError> {
+            Err(PortError::NotSupported("null adapter".to_string()))
+        }
+    }
+
+    /// Vendor adapter whose device list can be changed between runs
+    ///
+    /// Records firmware upgrades and rejects those to `reject_firmware`.
+    /// Reports `health` from its health check, if set. Records the cursors
+    /// discovery passes in and hands out `run-N` for the Nth listing. Fails
+    /// adoptions with `adopt_failures`, in order, until they run out.
+    #[derive(Default)]
+    struct ScriptedVendorAdapter {
+        devices: Mutex<Vec<VendorDevice>>,
+        cursors: Mutex<Vec<Option<String>>>,
+        adopt_failures: Mutex<Vec<PortError>>,
+        adopt_attempts: AtomicUsize,
+        upgrades: Mutex<Vec<(String, String)>>,
+        reject_firmware: Option<String>,
+        health: Option<HealthStatus>,
+        running_config: Option<DeviceConfigRequest>,
+    }
+
+    impl ScriptedVendorAdapter {
+        fn with_devices(self, devices: Vec<VendorDevice>) -> Self {
+            self.set_devices(devices);
+            self
+        }
+
+        fn set_devices(&self, devices: Vec<VendorDevice>) {
+            *self.devices.lock().unwrap() = devices;
+        }
+    }
+
+    #[async_trait]
+    impl DeviceControlPort for ScriptedVendorAdapter {
+        fn vendor_name(&self) -> &str { "Scripted" }
+        async fn connect(&self) -> Result<(), PortError> { Ok(()) }
+        async fn disconnect(&self) -> Result<(), PortError> { Ok(()) }
+        fn is_connected(&self) -> bool { true }
+        async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError> {
+            Ok(self.devices.lock().unwrap().clone())
+        }
+        async fn list_devices_since(
+            &self,
+            cursor: Option<String>,
+        ) -> Result<(Vec<VendorDevice>, Option<String>), PortError> {
+            let mut cursors = self.cursors.lock().unwrap();
+            cursors.push(cursor);
+            Ok((self.devices.lock().unwrap().clone(), Some(format!("run-{}", cursors.len()))))
+        }
+        async fn get_device(&self, vendor_id: &str) -> Result<VendorDevice, PortError> {
+            self.devices.lock().unwrap()
+                .iter()
+                .find(|d| d.vendor_id == vendor_id)
+                .cloned()
+                .ok_or_else(|| PortError::VendorError(format!("unknown device {}", vendor_id)))
+        }
+        async fn adopt_device(&self, _vendor_id: &str) -> Result<(), PortError> {
+            self.adopt_attempts.fetch_add(1, Ordering::SeqCst);
+            let mut failures = self.adopt_failures.lock().unwrap();
+            if failures.is_empty() { Ok(()) } else { Err(failures.remove(0)) }
+        }
+        async fn apply_config(&self, _vendor_id: &str, _config: DeviceConfigRequest) -> Result<(), PortError> { Ok(()) }
+        async fn apply_raw_config(&self, _vendor_id: &str, _config: VendorConfig) -> Result<(), PortError> { Ok(()) }
+        async fn upgrade_firmware(&self, vendor_id: &str, target_version: &str) -> Result<(), PortError> {
+            if self.reject_firmware.as_deref() == Some(target_version) {
+                return Err(PortError::VendorError("firmware rejected".to_string()));
+            }
+            self.upgrades.lock().unwrap().push((vendor_id.to_string(), target_version.to_string()));
+            Ok(())
+        }
+        async fn restart_device(&self, _vendor_id: &str) -> Result<(), PortError> { Ok(()) }
+        async fn get_device_stats(&self, _vendor_id: &str) -> Result<DeviceStats, PortError> {
+            Err(PortError::NotSupported("scripted adapter".to_string()))
+        }
+        async fn health_check(&self) -> Result<HealthStatus, PortError> {
+            self.health.clone().ok_or_else(|| PortError::NotSupported("scripted adapter".to_string()))
+        }
+        async fn get_running_config(&self, _vendor_id: &str) -> Result<DeviceConfigRequest, PortError> {
+            self.running_config.clone().ok_or_else(|| PortError::NotSupported("scripted adapter".to_string()))
+        }
+    }
+
+    /// Vendor adapter that takes `delay` to adopt each device and rejects `reject_mac`
+    struct SlowVendorAdapter {
+        devices: Vec<VendorDevice>,
+        delay: Duration,
+        reject_mac: Option<String>,
+    }
+
+    #[async_trait]
+    impl DeviceControlPort for SlowVendorAdapter {
+        fn vendor_name(&self) -> &str { "Slow" }
+        async fn connect(&self) -> Result<(), PortError> { Ok(()) }
+        async fn disconnect(&self) -> Result<(), PortError> { Ok(()) }
+        fn is_connected(&self) -> bool { true }
+        async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError> { Ok(self.devices.clone()) }
+        async fn get_device(&self, _vendor_id: &str) -> Result<VendorDevice, PortError> {
+            Err(PortError::NotSupported("slow adapter".to_string()))
+        }
+        async fn adopt_device(&self, vendor_id: &str) -> Result<(), PortError> {
+            tokio::time::sleep(self.delay).await;
+            match &self.reject_mac {
+                Some(mac) if mac == vendor_id => Err(PortError::VendorError("adoption rejected".to_string())),
+                _ => Ok(()),
+            }
+        }
+        async fn apply_config(&self, _vendor_id: &str, _config: DeviceConfigRequest) -> Result<(), PortError> { Ok(()) }
+        async fn apply_raw_config(&self, _vendor_id: &str, _config: VendorConfig) -> Result<(), PortError> { Ok(()) }
+        async fn restart_device(&self, _vendor_id: &str) -> Result<(), PortError> { Ok(()) }
+        async fn get_device_stats(&self, _vendor_id: &str) -> Result<DeviceStats, PortError> {
+            Err(PortError::NotSupported("slow adapter".to_string()))
+        }
+    }
+
+    /// Vendor adapter serving one device whose stats follow a script
+    ///
+    /// Each poll takes the next scripted stats; the last ones repeat.
+    struct StatsVendorAdapter {
+        device: VendorDevice,
+        script: Mutex<std::collections::VecDeque<DeviceStats>>,
+        polls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl DeviceControlPort for StatsVendorAdapter {
+        fn vendor_name(&self) -> &str { "Stats" }
+        async fn connect(&self) -> Result<(), PortError> { Ok(()) }
+        async fn disconnect(&self) -> Result<(), PortError> { Ok(()) }
+        fn is_connected(&self) -> bool { true }
+        async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError> { Ok(vec![self.device.clone()]) }
+        async fn get_device(&self, _vendor_id: &str) -> Result<VendorDevice, PortError> { Ok(self.device.clone()) }
+        async fn adopt_device(&self, _vendor_id: &str) -> Result<(), PortError> { Ok(()) }
+        async fn apply_config(&self, _vendor_id: &str, _config: DeviceConfigRequest) -> Result<(), PortError> { Ok(()) }
+        async fn apply_raw_config(&self, _vendor_id: &str, _config: VendorConfig) -> Result<(), PortError> { Ok(()) }
+        async fn restart_device(&self, _vendor_id: &str) -> Result<(), PortError> { Ok(()) }
+        async fn get_device_stats(&self, _vendor_id: &str) -> Result<DeviceStats, PortError> {
+            self.polls.fetch_add(1, Ordering::SeqCst);
+            let mut script = self.script.lock().unwrap();
+            let stats = if script.len() > 1 { script.pop_front() } else { script.front().cloned() };
+            stats.ok_or_else(|| PortError::NotSupported("no scripted stats".to_string()))
+        }
+    }
+
+    fn port_stats(link_up: bool) -> DeviceStats {
+        DeviceStats {
+            uptime_seconds: 60,
+            cpu_percent: Some(12.0),
+            memory_percent: Some(40.0),
+            temperature_celsius: None,
+            port_stats: vec![PortStats {
+                port_id: PortId::with_index("port", 1),
+                link_up,
+                speed: Some(LinkSpeed::Gbps1),
+                rx_bytes: 0,
+                tx_bytes: 0,
+                rx_errors: 0,
+                tx_errors: 0,
+                rx_bps: None,
+                tx_bps: None,
+            }],
+        }
+    }
+
+    /// Inventory that takes `delay` to sync each device
+    struct SlowInventory {
+        delay: Duration,
+        synced: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl InventoryPort for SlowInventory {
+        fn system_name(&self) -> &str { "slow" }
+        async fn sync_device(&self, _device: &NetworkDeviceAggregate) -> Result<(), PortError> {
+            tokio::time::sleep(self.delay).await;
+            self.synced.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+        async fn remove_device(&self, _device_id: DeviceId) -> Result<(), PortError> { Ok(()) }
+        async fn sync_connection(&self, _connection: &ConnectionInfo) -> Result<(), PortError> { Ok(()) }
+        async fn get_ip_assignments(&self, _prefix: &str) -> Result<Vec<IpAssignment>, PortError> { Ok(vec![]) }
+        async fn allocate_ip(&self, _prefix: &str, _device_id: DeviceId) -> Result<IpAssignment, PortError> {
+            Err(PortError::NotSupported("slow inventory".to_string()))
+        }
+    }
+
+    /// Inventory failing its first `failures` device syncs, counting every attempt
+    struct FlakyInventory {
+        failures: usize,
+        attempts: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl InventoryPort for FlakyInventory {
+        fn system_name(&self) -> &str { "flaky" }
+        async fn sync_device(&self, _device: &NetworkDeviceAggregate) -> Result<(), PortError> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(PortError::ConnectionFailed("inventory unreachable".to_string()));
+            }
+            Ok(())
+        }
+        async fn remove_device(&self, _device_id: DeviceId) -> Result<(), PortError> { Ok(()) }
+        async fn sync_connection(&self, _connection: &ConnectionInfo) -> Result<(), PortError> { Ok(()) }
+        async fn get_ip_assignments(&self, _prefix: &str) -> Result<Vec<IpAssignment>, PortError> { Ok(vec![]) }
+        async fn allocate_ip(&self, _prefix: &str, _device_id: DeviceId) -> Result<IpAssignment, PortError> {
+            Err(PortError::NotSupported("flaky inventory".to_string()))
+        }
+    }
+
+    /// Inventory that records the cables it is asked to create and remove
+    #[derive(Default)]
+    struct CableInventory {
+        cabled: Mutex<Vec<ConnectionInfo>>,
+        removed: Mutex<Vec<ConnectionId>>,
+    }
+
+    #[async_trait]
+    impl InventoryPort for CableInventory {
+        fn system_name(&self) -> &str { "cables" }
+        async fn sync_device(&self, _device: &NetworkDeviceAggregate) -> Result<(), PortError> { Ok(()) }
+        async fn remove_device(&self, _device_id: DeviceId) -> Result<(), PortError> { Ok(()) }
+        async fn sync_connection(&self, connection: &ConnectionInfo) -> Result<(), PortError> {
+            self.cabled.lock().unwrap().push(connection.clone());
+            Ok(())
+        }
+        async fn remove_connection(&self, connection_id: ConnectionId) -> Result<(), PortError> {
+            self.removed.lock().unwrap().push(connection_id);
+            Ok(())
+        }
+        async fn get_ip_assignments(&self, _prefix: &str) -> Result<Vec<IpAssignment>, PortError> { Ok(vec![]) }
+        async fn allocate_ip(&self, _prefix: &str, _device_id: DeviceId) -> Result<IpAssignment, PortError> {
+            Err(PortError::NotSupported("cable inventory".to_string()))
+        }
+    }
+
+    fn vendor_device(mac: &str, name: &str) -> VendorDevice {
+        VendorDevice {
+            vendor_id: mac.to_string(),
+            device_id: None,
+            mac: MacAddress::parse(mac).unwrap(),
+            model: "USW-24".to_string(),
+            name: name.to_string(),
+            ip_address: None,
+            adopted: true,
+            properties: HashMap::new(),
+        }
+    }
+
+    fn service_with(store: Arc<MemoryEventStore>) -> NetworkService {
+        NetworkService::builder()
+            .event_store_arc(store)
+            .vendor_adapter(NullVendorAdapter)
+            .build()
+            .unwrap()
+    }
+
+    /// Discovery followed by `cycles` error/rename rounds
+    fn device_history(device_id: DeviceId, cycles: usize) -> Vec<NetworkEvent> {
+        let mut events = vec![NetworkEvent::DeviceDiscovered {
+            device_id,
+            mac: MacAddress::parse("00:11:22:33:44:55").unwrap(),
+            device_type: DeviceType::Switch,
+            ip_address: None,
+        }];
+        for i in 0..cycles {
+            events.push(NetworkEvent::DeviceRenamed {
+                device_id,
+                old_name: format!("switch-{}", i),
+                new_name: format!("switch-{}", i + 1),
+            });
+        }
+        events
+    }
+
+    // ========================================================================
+    // Snapshot Tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn test_replay_after_snapshot_reads_no_prior_events() {
+        let store = Arc::new(MemoryEventStore::default());
+        let service = service_with(store.clone());
+        let device_id = DeviceId::new();
+
+        store.append(device_history(device_id, 50)).await.unwrap();
+        service.replay_events(&device_id.to_string()).await.unwrap().unwrap();
+        service.snapshot_device(device_id).await.unwrap();
+
+        store.events_read.store(0, Ordering::SeqCst);
+        let device = service.replay_events(&device_id.to_string()).await.unwrap().unwrap();
+
+        assert_eq!(store.events_read.load(Ordering::SeqCst), 0);
+        assert_eq!(device.version(), 51);
+        assert_eq!(device.name(), "switch-50");
+    }
+
+    #[tokio::test]
+    async fn test_replay_folds_only_events_after_snapshot() {
+        let store = Arc::new(MemoryEventStore::default());
+        let service = service_with(store.clone());
+        let device_id = DeviceId::new();
+
+        store.append(device_history(device_id, 10)).await.unwrap();
+        service.replay_events(&device_id.to_string()).await.unwrap();
+        service.snapshot_device(device_id).await.unwrap();
+
+        store.append(vec![NetworkEvent::DeviceRenamed {
+            device_id,
+            old_name: "switch-10".to_string(),
+            new_name: "core-1".to_string(),
+        }]).await.unwrap();
+
+        store.events_read.store(0, Ordering::SeqCst);
+        let device = service.replay_events(&device_id.to_string()).await.unwrap().unwrap();
+
+        assert_eq!(store.events_read.load(Ordering::SeqCst), 1);
+        assert_eq!(device.version(), 12);
+        assert_eq!(device.name(), "core-1");
+    }
+
+    #[tokio::test]
+    async fn test_replay_ignores_unknown_snapshot_format() {
+        let store = Arc::new(MemoryEventStore::default());
+        let service = service_with(store.clone());
+        let device_id = DeviceId::new();
+
+        store.append(device_history(device_id, 3)).await.unwrap();
+        store.save_snapshot(&device_id.to_string(), 4, vec![0, b'{', b'}']).await.unwrap();
+
+        let device = service.replay_events(&device_id.to_string()).await.unwrap().unwrap();
+
+        assert_eq!(store.events_read.load(Ordering::SeqCst), 4);
+        assert_eq!(device.name(), "switch-3");
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_threshold_saves_on_replay() {
+        let store = Arc::new(MemoryEventStore::default());
+        let service = NetworkService::builder()
+            .event_store_arc(store.clone())
+            .vendor_adapter(NullVendorAdapter)
+            .snapshot_threshold(5)
+            .build()
+            .unwrap();
+        let device_id = DeviceId::new();
+
+        store.append(device_history(device_id, 5)).await.unwrap();
+        service.replay_events(&device_id.to_string()).await.unwrap();
+
+        let snapshot = store.load_latest_snapshot(&device_id.to_string()).await.unwrap().unwrap();
+        assert_eq!(snapshot.version, 6);
+        assert_eq!(snapshot.data[0], SNAPSHOT_FORMAT_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_replay_skips_undecodable_stream_items() {
+        let store = Arc::new(MemoryEventStore::default());
+        let service = service_with(store.clone());
+        let device_id = DeviceId::new();
+
+        store.append(device_history(device_id, 3)).await.unwrap();
+        *store.undecodable_at.lock().unwrap() = Some(2);
+
+        let device = service.replay_events(&device_id.to_string()).await.unwrap().unwrap();
+
+        // The corrupt rename is skipped; the rest of the history still folds
+        assert_eq!(device.version(), 3);
+        assert_eq!(device.name(), "switch-3");
+    }
+
+    // ========================================================================
+    // Point-in-Time Replay Tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn test_replay_to_version_reconstructs_intermediate_state() {
+        let store = Arc::new(MemoryEventStore::default());
+        let service = service_with(store.clone());
+        let device_id = DeviceId::new();
+
+        store.append(device_history(device_id, 5)).await.unwrap();
+        service.replay_events(&device_id.to_string()).await.unwrap();
+
+        let past = service.replay_to_version(&device_id.to_string(), 3).await.unwrap().unwrap();
+
+        assert_eq!(past.version(), 3);
+        assert_eq!(past.name(), "switch-2");
+        // The cache still holds the current state
+        assert_eq!(service.get_device(device_id).await.unwrap().name(), "switch-5");
+
+        let latest = service.replay_to_version(&device_id.to_string(), 100).await.unwrap().unwrap();
+        assert_eq!(latest.version(), 6);
+        assert!(service.replay_to_version(&device_id.to_string(), 0).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_replay_to_version_uses_snapshot_only_when_not_newer() {
+        let store = Arc::new(MemoryEventStore::default());
+        let service = service_with(store.clone());
+        let device_id = DeviceId::new();
+
+        store.append(device_history(device_id, 10)).await.unwrap();
+        service.replay_events(&device_id.to_string()).await.unwrap();
+        service.snapshot_device(device_id).await.unwrap();
+        store.append(vec![NetworkEvent::DeviceRenamed {
+            device_id,
+            old_name: "switch-10".to_string(),
+            new_name: "core-1".to_string(),
+        }]).await.unwrap();
+
+        // Past the snapshot: only the newer event is read
+        store.events_read.store(0, Ordering::SeqCst);
+        let device = service.replay_to_version(&device_id.to_string(), 12).await.unwrap().unwrap();
+        assert_eq!(store.events_read.load(Ordering::SeqCst), 1);
+        assert_eq!(device.name(), "core-1");
+
+        // Before the snapshot: history is folded from the start
+        let device = service.replay_to_version(&device_id.to_string(), 4).await.unwrap().unwrap();
+        assert_eq!(device.version(), 4);
+        assert_eq!(device.name(), "switch-3");
+    }
+
+    #[tokio::test]
+    async fn test_replay_to_timestamp_stops_at_cutoff() {
+        let store = Arc::new(MemoryEventStore::default());
+        let service = service_with(store.clone());
+        let device_id = DeviceId::new();
+        let other_id = DeviceId::new();
+
+        store.append(device_history(other_id, 1)).await.unwrap();
+        store.append(device_history(device_id, 5)).await.unwrap();
+
+        // The device's events were recorded at seconds 2 through 7
+        let at = |seconds| DateTime::UNIX_EPOCH + chrono::Duration::seconds(seconds);
+        let device = service.replay_to_timestamp(&device_id.to_string(), at(4)).await.unwrap().unwrap();
+
+        assert_eq!(device.version(), 3);
+        assert_eq!(device.name(), "switch-2");
+        assert!(service.get_device(device_id).await.is_none());
+        assert!(service.replay_to_timestamp(&device_id.to_string(), at(1)).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_audit_trail_lists_events_in_store_order() {
+        let store = Arc::new(MemoryEventStore::default());
+        let service = service_with(store.clone());
+        let device_id = DeviceId::new();
+        let other_id = DeviceId::new();
+
+        let mut history = device_history(device_id, 1);
+        history.insert(1, NetworkEvent::DeviceAdopting { device_id, vendor_id: "sw-1".to_string() });
+        store.append(history[..1].to_vec()).await.unwrap();
+        store.append(device_history(other_id, 2)).await.unwrap();
+        store.append(history[1..].to_vec()).await.unwrap();
+
+        let trail = service.audit_trail(&device_id.to_string()).await.unwrap();
+
+        let summary: Vec<_> = trail.iter().map(|e| (e.sequence, e.event_type, e.event_id.as_deref())).collect();
+        assert_eq!(summary, vec![
+            (1, "DeviceDiscovered", Some("evt-1")),
+            (5, "DeviceAdopting", Some("evt-5")),
+            (6, "DeviceRenamed", Some("evt-6")),
+        ]);
+        assert!(trail.windows(2).all(|pair| pair[0].recorded_at < pair[1].recorded_at));
+    }
+
+    // ========================================================================
+    // Rehydration Tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn test_rehydrate_restores_devices_after_restart() {
+        let store = Arc::new(MemoryEventStore::default());
+        let first = DeviceId::new();
+        let second = DeviceId::new();
+        store.append(device_history(first, 2)).await.unwrap();
+        store.append(device_history(second, 5)).await.unwrap();
+        store.append(vec![NetworkEvent::TopologyCreated {
+            topology_id: crate::domain::value_objects::TopologyId::new(),
+            name: "lab".to_string(),
+        }]).await.unwrap();
+
+        // A freshly started service knows nothing until it rehydrates
+        let service = service_with(store.clone());
+        assert!(service.list_devices().await.is_empty());
+
+        let mut loaded = service.rehydrate().await.unwrap();
+        loaded.sort_by_key(|id| id.to_string());
+        let mut expected = vec![first, second];
+        expected.sort_by_key(|id| id.to_string());
+        assert_eq!(loaded, expected);
+
+        let devices = service.list_devices().await;
+        assert_eq!(devices.len(), 2);
+        assert_eq!(service.get_device(first).await.unwrap().name(), "switch-2");
+        assert_eq!(service.get_device(second).await.unwrap().name(), "switch-5");
+    }
+
+    // ========================================================================
+    // Purge Tests
+    // ========================================================================
+
+    /// Service that has replayed (and snapshotted) one device from `store`
+    ///
+    /// `configure` adds to the builder, e.g. an inventory adapter and its
+    /// sync retry policy.
+    async fn service_with_device(
+        store: Arc<MemoryEventStore>,
+        configure: impl FnOnce(NetworkServiceBuilder) -> NetworkServiceBuilder,
+    ) -> (NetworkService, DeviceId) {
+        let device_id = DeviceId::new();
+        store.append(device_history(device_id, 2)).await.unwrap();
+        let builder = NetworkService::builder()
+            .event_store_arc(store)
+            .vendor_adapter(NullVendorAdapter)
+            .snapshot_threshold(1);
+        let service = configure(builder).build().unwrap();
+        service.replay_events(&device_id.to_string()).await.unwrap().unwrap();
+        (service, device_id)
+    }
+
+    #[tokio::test]
+    async fn test_purged_device_replays_as_none() {
+        let store = Arc::new(MemoryEventStore::default());
+        let (service, device_id) = service_with_device(store.clone(), |builder| builder).await;
+        let aggregate_id = device_id.to_string();
+        assert!(store.load_latest_snapshot(&aggregate_id).await.unwrap().is_some());
+
+        service.decommission_device(device_id).await.unwrap();
+        service.purge_device(device_id).await.unwrap();
+
+        assert!(store.load_events(&aggregate_id).await.unwrap().is_empty());
+        assert!(store.load_latest_snapshot(&aggregate_id).await.unwrap().is_none());
+        assert!(service.get_device(device_id).await.is_none());
+        assert!(service.replay_events(&aggregate_id).await.unwrap().is_none());
+        assert!(matches!(
+            store.tombstones.lock().unwrap()[..],
+            [NetworkEvent::DevicePurged { device_id: id }] if id == device_id
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_purge_requires_decommissioned_device() {
+        let store = Arc::new(MemoryEventStore::default());
+        let (service, device_id) = service_with_device(store.clone(), |builder| builder).await;
+
+        let result = service.purge_device(device_id).await;
+
+        assert!(matches!(
+            result,
+            Err(ServiceError::Aggregate(AggregateError::InvalidState { ref operation, .. })) if operation == "purge"
+        ));
+        assert!(!store.load_events(&device_id.to_string()).await.unwrap().is_empty());
+        assert!(store.tombstones.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_decommission_purges_after_retention_window() {
+        let store = Arc::new(MemoryEventStore::default());
+        let (service, device_id) = service_with_device(store.clone(), |builder| builder.purge_decommissioned_after(Duration::from_millis(50))).await;
+
+        service.decommission_device(device_id).await.unwrap();
+        assert!(!store.load_events(&device_id.to_string()).await.unwrap().is_empty());
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(store.load_events(&device_id.to_string()).await.unwrap().is_empty());
+        assert!(service.get_device(device_id).await.is_none());
+        assert_eq!(store.tombstones.lock().unwrap().len(), 1);
+    }
+
+    // ========================================================================
+    // Concurrency Tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn test_concurrent_adopt_conflicts() {
+        let store = Arc::new(MemoryEventStore::default());
+        let device_id = DeviceId::new();
+        store.append(device_history(device_id, 0)).await.unwrap();
+
+        // Two service instances (e.g. two processes) sharing one store
+        let first = service_with(store.clone());
+        let second = service_with(store.clone());
+        first.replay_events(&device_id.to_string()).await.unwrap();
+        second.replay_events(&device_id.to_string()).await.unwrap();
+
+        let (a, b) = tokio::join!(first.adopt_device(device_id), second.adopt_device(device_id));
+        let results = [a, b];
+
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+        assert!(results.iter().any(|r| matches!(
+            r,
+            Err(ServiceError::ConcurrencyConflict { expected: 1, actual: 2 })
+        )));
+        assert_eq!(store.load_events(&device_id.to_string()).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_conflict_leaves_cached_aggregate_unchanged() {
+        let store = Arc::new(MemoryEventStore::default());
+        let device_id = DeviceId::new();
+        store.append(device_history(device_id, 0)).await.unwrap();
+
+        let service = service_with(store.clone());
+        service.replay_events(&device_id.to_string()).await.unwrap();
+
+        // Someone else writes behind our back
+        store.append(device_history(device_id, 1).split_off(1)).await.unwrap();
+
+        let result = service.adopt_device(device_id).await;
+        assert!(matches!(result, Err(ServiceError::ConcurrencyConflict { .. })));
+
+        let cached = service.get_device(device_id).await.unwrap();
+        assert_eq!(cached.state(), DeviceState::Discovered);
+        assert_eq!(cached.version(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_cache_handles_many_tasks_on_distinct_devices() {
+        const DEVICES: usize = 32;
+        const ROUNDS: usize = 20;
+
+        let store = Arc::new(MemoryEventStore::default());
+        let service = Arc::new(service_with(store.clone()));
+        let mut device_ids = Vec::new();
+        for _ in 0..DEVICES {
+            let device_id = DeviceId::new();
+            store.append(device_history(device_id, 0)).await.unwrap();
+            service.replay_events(&device_id.to_string()).await.unwrap();
+            device_ids.push(device_id);
+        }
+
+        // Each task mutates its own device while reading the whole cache
+        let tasks: Vec<_> = device_ids.iter().map(|&device_id| {
+            let service = service.clone();
+            tokio::spawn(async move {
+                for round in 0..ROUNDS {
+                    let tag = Tag::parse(&format!("round:{}", round)).unwrap();
+                    service.tag_device(device_id, tag).await.unwrap();
+                    assert_eq!(service.get_device(device_id).await.unwrap().tags().len(), round + 1);
+                    assert_eq!(service.list_devices().await.len(), DEVICES);
+                    tokio::task::yield_now().await;
+                }
+            })
+        }).collect();
+
+        tokio::time::timeout(Duration::from_secs(30), futures::future::try_join_all(tasks))
+            .await
+            .expect("cache deadlocked")
+            .unwrap();
+
+        for device_id in device_ids {
+            let device = service.get_device(device_id).await.unwrap();
+            assert_eq!(device.tags().len(), ROUNDS);
+            assert_eq!(device.version(), 1 + ROUNDS as u64);
+            assert_eq!(store.load_events(&device_id.to_string()).await.unwrap().len(), 1 + ROUNDS);
+        }
+    }
+
+    // ========================================================================
+    // Provisioning Tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn test_discover_and_provision_runs_devices_concurrently() {
+        const DEVICES: usize = 16;
+        let delay = Duration::from_millis(50);
+
+        let devices: Vec<VendorDevice> = (0..DEVICES)
+            .map(|i| vendor_device(&format!("00:11:22:33:44:{:02x}", i), &format!("sw-{}", i)))
+            .collect();
+        let inventory = Arc::new(SlowInventory { delay, synced: AtomicUsize::new(0) });
+        let service = NetworkService::builder()
+            .event_store(MemoryEventStore::default())
+            .vendor_adapter(SlowVendorAdapter {
+                devices,
+                delay,
+                reject_mac: Some("00:11:22:33:44:03".to_string()),
+            })
+            .inventory_adapter_arc(inventory.clone())
+            .build()
+            .unwrap();
+
+        let started = Instant::now();
+        let report = service.discover_and_provision().await.unwrap();
+        let elapsed = started.elapsed();
+
+        // Serially this is 2 * 16 * 50ms = 1.6s; with 8 at a time it is ~200ms
+        let serial = delay * 2 * DEVICES as u32;
+        assert!(elapsed < serial / 3, "took {:?}, serial would be {:?}", elapsed, serial);
+
+        assert_eq!(report.discovered.len(), DEVICES);
+        assert_eq!(report.adoption.succeeded.len(), DEVICES - 1);
+        assert_eq!(report.adoption.failed.len(), 1);
+        let (rejected, error) = &report.adoption.failed[0];
+        assert!(matches!(error, ServiceError::Port(PortError::VendorError(_))));
+
+        // The rejected device was still recorded as adopting, so it is synced too
+        assert_eq!(report.inventory_sync.succeeded.len(), DEVICES);
+        assert!(report.inventory_sync.failed.is_empty());
+        assert_eq!(inventory.synced.load(Ordering::SeqCst), DEVICES);
+        assert_eq!(service.get_device(*rejected).await.unwrap().state(), DeviceState::Adopting);
+        assert_eq!(service.list_devices_by_state(DeviceState::Adopting).await.len(), DEVICES);
+    }
+
+    #[tokio::test]
+    async fn test_provisioning_concurrency_limit_is_respected() {
+        let delay = Duration::from_millis(20);
+        let devices: Vec<VendorDevice> = (0..4)
+            .map(|i| vendor_device(&format!("00:11:22:33:44:{:02x}", i), &format!("sw-{}", i)))
+            .collect();
+        let service = NetworkService::builder()
+            .event_store(MemoryEventStore::default())
+            .vendor_adapter(SlowVendorAdapter { devices, delay, reject_mac: None })
+            .provisioning_concurrency(1)
+            .build()
+            .unwrap();
+
+        let started = Instant::now();
+        let report = service.discover_and_provision().await.unwrap();
+
+        // One at a time: the four adoptions cannot overlap
+        assert!(started.elapsed() >= delay * 4);
+        assert_eq!(report.adoption.succeeded.len(), 4);
+        assert!(report.inventory_sync.succeeded.is_empty());
+    }
+
+    // ========================================================================
+    // Inventory Sync Tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn test_unchanged_device_is_synced_once() {
+        let store = Arc::new(MemoryEventStore::default());
+        let inventory = Arc::new(SlowInventory { delay: Duration::ZERO, synced: AtomicUsize::new(0) });
+        let service_on = |store: Arc<MemoryEventStore>| {
+            NetworkService::builder()
+                .event_store_arc(store)
+                .vendor_adapter(NullVendorAdapter)
+                .inventory_adapter_arc(inventory.clone())
+                .build()
+                .unwrap()
+        };
+        let device_id = DeviceId::new();
+        store.append(device_history(device_id, 2)).await.unwrap();
+        let synced_events = |store: &MemoryEventStore| {
+            store.events.lock().unwrap()
+                .iter()
+                .filter(|e| matches!(e, NetworkEvent::DeviceSyncedToInventory { .. }))
+                .count()
+        };
+
+        let service = service_on(store.clone());
+        service.replay_events(&device_id.to_string()).await.unwrap();
+        assert_eq!(service.sync_to_inventory(device_id).await.unwrap(), SyncOutcome::Synced);
+        assert_eq!(service.sync_to_inventory(device_id).await.unwrap(), SyncOutcome::Unchanged);
+        assert_eq!(inventory.synced.load(Ordering::SeqCst), 1);
+        assert_eq!(synced_events(&store), 1);
+
+        // A restarted service knows the hash from the recorded event
+        let restarted = service_on(store.clone());
+        restarted.replay_events(&device_id.to_string()).await.unwrap();
+        assert_eq!(restarted.sync_to_inventory(device_id).await.unwrap(), SyncOutcome::Unchanged);
+
+        store.append(vec![NetworkEvent::DeviceRenamed {
+            device_id,
+            old_name: "switch-2".to_string(),
+            new_name: "core-1".to_string(),
+        }]).await.unwrap();
+        restarted.replay_events(&device_id.to_string()).await.unwrap();
+        assert_eq!(restarted.sync_to_inventory(device_id).await.unwrap(), SyncOutcome::Synced);
+        assert_eq!(inventory.synced.load(Ordering::SeqCst), 2);
+        assert_eq!(synced_events(&store), 2);
+    }
+
+    #[tokio::test]
+    async fn test_plan_provisioning_changes_nothing() {
+        let store = Arc::new(MemoryEventStore::default());
+        let vendor = Arc::new(ScriptedVendorAdapter::default());
+        let inventory = Arc::new(SlowInventory { delay: Duration::ZERO, synced: AtomicUsize::new(0) });
+        let service = NetworkService::builder()
+            .event_store_arc(store.clone())
+            .vendor_adapter_arc(vendor.clone())
+            .inventory_adapter_arc(inventory.clone())
+            .build()
+            .unwrap();
+
+        vendor.set_devices(vec![vendor_device("00:11:22:33:44:01", "core-sw")]);
+        let core = service.discover_devices().await.unwrap()[0];
+        vendor.set_devices(vec![
+            vendor_device("00:11:22:33:44:01", "core-sw"),
+            vendor_device("00:11:22:33:44:02", "edge-sw"),
+        ]);
+        let appended = store.events.lock().unwrap().len();
+        let cursor = service.discovery_cursor().await;
+
+        let plan = service.plan_provisioning().await.unwrap();
+
+        // Nothing was appended, adopted, synced or cached
+        assert_eq!(store.events.lock().unwrap().len(), appended);
+        assert_eq!(vendor.adopt_attempts.load(Ordering::SeqCst), 0);
+        assert_eq!(inventory.synced.load(Ordering::SeqCst), 0);
+        assert_eq!(service.list_devices().await.len(), 1);
+        assert_eq!(service.discovery_cursor().await, cursor);
+
+        // ...but the plan lists what a real run would do
+        assert_eq!(plan.discovered.len(), 1);
+        let edge = plan.discovered[0];
+        assert_eq!(
+            plan.events.iter().filter(|e| e.aggregate_id() == edge.to_string()).map(|e| e.event_type()).collect::<Vec<_>>(),
+            ["DeviceDiscovered", "DeviceRenamed", "DeviceAdopting", "DeviceSyncedToInventory"]
+        );
+        assert_eq!(
+            plan.events.iter().filter(|e| e.aggregate_id() == core.to_string()).map(|e| e.event_type()).collect::<Vec<_>>(),
+            ["DeviceSyncedToInventory"]
+        );
+        assert_eq!(plan.calls[0], PlannedCall::AdoptDevice { device_id: edge, vendor_id: "00:11:22:33:44:02".to_string() });
+        let mut synced: Vec<DeviceId> = plan.calls[1..]
+            .iter()
+            .map(|call| match call {
+                PlannedCall::SyncDevice { device_id, system } if system == "slow" => *device_id,
+                other => panic!("unexpected call {:?}", other),
+            })
+            .collect();
+        synced.sort_by_key(|id| id.to_string());
+        let mut expected = vec![core, edge];
+        expected.sort_by_key(|id| id.to_string());
+        assert_eq!(synced, expected);
+    }
+
+    // ========================================================================
+    // Sync Queue Tests
+    // ========================================================================
+
+    const SYNC_WORKER_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Service syncing one replayed device to `inventory`, four attempts per sync
+    async fn service_syncing_to(
+        store: Arc<MemoryEventStore>,
+        inventory: Arc<FlakyInventory>,
+        clock: Arc<MockClock>,
+    ) -> (Arc<NetworkService>, DeviceId) {
+        let (service, device_id) = service_with_device(store, |builder| {
+            builder
+                .inventory_adapter_arc(inventory)
+                .inventory_sync_retry(RetryPolicy::new(4, Duration::from_secs(10)))
+                .clock(clock)
+        })
+        .await;
+        (Arc::new(service), device_id)
+    }
+
+    /// Start the sync worker and run its first tick
+    ///
+    /// The test then runs half an interval behind the worker, so each
+    /// `worker_tick` ends between two ticks.
+    async fn start_sync_worker(service: &Arc<NetworkService>) -> InventorySyncWorkerHandle {
+        let worker = service.start_inventory_sync_worker(SYNC_WORKER_INTERVAL);
+        tokio::time::sleep(SYNC_WORKER_INTERVAL / 2).await;
+        worker
+    }
+
+    /// Move the queue's clock on by `by` and let the worker run one tick
+    async fn worker_tick(clock: &MockClock, by: Duration) {
+        clock.advance(chrono::Duration::from_std(by).unwrap());
+        tokio::time::sleep(SYNC_WORKER_INTERVAL).await;
+    }
+
+    fn sync_clock() -> Arc<MockClock> {
+        Arc::new(MockClock::new(DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z").unwrap().with_timezone(&Utc)))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_queued_sync_succeeds_after_failures() {
+        let store = Arc::new(MemoryEventStore::default());
+        let clock = sync_clock();
+        let inventory = Arc::new(FlakyInventory { failures: 2, attempts: AtomicUsize::new(0) });
+        let (service, device_id) = service_syncing_to(store.clone(), inventory.clone(), clock.clone()).await;
+
+        assert!(service.sync_to_inventory(device_id).await.is_err());
+        assert_eq!(service.get_device(device_id).await.unwrap().inventory_sync_attempts("flaky"), 1);
+        let worker = start_sync_worker(&service).await;
+
+        // Not retried before its backoff has passed
+        worker_tick(&clock, Duration::from_secs(9)).await;
+        assert_eq!(inventory.attempts.load(Ordering::SeqCst), 1);
+
+        worker_tick(&clock, Duration::from_secs(1)).await;
+        assert_eq!(inventory.attempts.load(Ordering::SeqCst), 2);
+        // The backoff doubles: 20s after the second failure
+        worker_tick(&clock, Duration::from_secs(19)).await;
+        assert_eq!(inventory.attempts.load(Ordering::SeqCst), 2);
+        worker_tick(&clock, Duration::from_secs(1)).await;
+        worker.stop().await;
+
+        assert_eq!(inventory.attempts.load(Ordering::SeqCst), 3);
+        let device = service.get_device(device_id).await.unwrap();
+        assert!(!device.needs_inventory_sync("flaky"));
+        assert!(device.inventory_sync_failures().is_empty());
+        assert!(service.dead_lettered_syncs().await.is_empty());
+        let queued: Vec<u32> = store.load_events(&device_id.to_string()).await.unwrap()
+            .into_iter()
+            .filter_map(|e| match e {
+                NetworkEvent::InventorySyncQueued { attempts, .. } => Some(attempts),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(queued, [1, 2]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failing_sync_is_dead_lettered() {
+        let store = Arc::new(MemoryEventStore::default());
+        let clock = sync_clock();
+        let inventory = Arc::new(FlakyInventory { failures: usize::MAX, attempts: AtomicUsize::new(0) });
+        let (service, device_id) = service_syncing_to(store.clone(), inventory.clone(), clock.clone()).await;
+
+        assert!(service.sync_to_inventory(device_id).await.is_err());
+        let worker = start_sync_worker(&service).await;
+        for _ in 0..3 {
+            worker_tick(&clock, Duration::from_secs(60)).await;
+        }
+        assert_eq!(inventory.attempts.load(Ordering::SeqCst), 4);
+
+        // Dead-lettered syncs are not retried
+        worker_tick(&clock, Duration::from_secs(3600)).await;
+        worker.stop().await;
+
+        assert_eq!(inventory.attempts.load(Ordering::SeqCst), 4);
+        assert_eq!(
+            service.dead_lettered_syncs().await,
+            [DeadLetteredSync {
+                device_id,
+                system: "flaky".to_string(),
+                attempts: 4,
+                error: "Connection failed: inventory unreachable".to_string(),
+            }]
+        );
+
+        // The dead letter survives a restart
+        let restarted = service_with(store.clone());
+        restarted.replay_events(&device_id.to_string()).await.unwrap();
+        assert_eq!(restarted.dead_lettered_syncs().await.len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_provisioning_runs_leave_queued_syncs_to_the_queue() {
+        let store = Arc::new(MemoryEventStore::default());
+        let clock = sync_clock();
+        let inventory = Arc::new(FlakyInventory { failures: usize::MAX, attempts: AtomicUsize::new(0) });
+        let (service, device_id) = service_syncing_to(store.clone(), inventory.clone(), clock.clone()).await;
+
+        // Runs before the backoff has passed do not spend attempts
+        for _ in 0..3 {
+            service.discover_and_provision().await.unwrap();
+        }
+        assert_eq!(inventory.attempts.load(Ordering::SeqCst), 1);
+
+        // Each due retry is one attempt, until the sync is dead-lettered
+        // and later runs leave it alone
+        for _ in 0..10 {
+            clock.advance(chrono::Duration::minutes(1));
+            service.discover_and_provision().await.unwrap();
+        }
+        assert_eq!(inventory.attempts.load(Ordering::SeqCst), 4);
+
+        let dead_lettered = store.load_events(&device_id.to_string()).await.unwrap()
+            .into_iter()
+            .filter(|e| matches!(e, NetworkEvent::InventorySyncDeadLettered { .. }))
+            .count();
+        assert_eq!(dead_lettered, 1);
+        assert_eq!(service.dead_lettered_syncs().await[0].attempts, 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_sync_on_provisioning_is_queued() {
+        let store = Arc::new(MemoryEventStore::default());
+        let clock = sync_clock();
+        let inventory = Arc::new(FlakyInventory { failures: 1, attempts: AtomicUsize::new(0) });
+        let (service, device_id) = service_syncing_to(store.clone(), inventory.clone(), clock.clone()).await;
+        service.adopt_device(device_id).await.unwrap();
+
+        // Provisioning is recorded even though the inventory is down
+        service.mark_provisioned(device_id, "USW-24".to_string(), "6.6.0".to_string()).await.unwrap();
+        let device = service.get_device(device_id).await.unwrap();
+        assert_eq!(device.state(), DeviceState::Provisioned);
+        assert_eq!(device.inventory_sync_attempts("flaky"), 1);
+
+        let worker = start_sync_worker(&service).await;
+        worker_tick(&clock, Duration::from_secs(10)).await;
+        worker.stop().await;
+
+        assert_eq!(inventory.attempts.load(Ordering::SeqCst), 2);
+        let device = service.get_device(device_id).await.unwrap();
+        assert!(!device.needs_inventory_sync("flaky"));
+        assert!(device.inventory_sync_failures().is_empty());
+        assert!(store.load_events(&device_id.to_string()).await.unwrap()
+            .iter()
+            .any(|e| matches!(e, NetworkEvent::DeviceSyncedToInventory { .. })));
+    }
+
+    // ========================================================================
+    // Discovery Tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn test_discovery_passes_cursor_to_next_run() {
+        let vendor = Arc::new(ScriptedVendorAdapter::default());
+        let service = NetworkService::builder()
+            .event_store(MemoryEventStore::default())
+            .vendor_adapter_arc(vendor.clone())
+            .build()
+            .unwrap();
+
+        vendor.set_devices(vec![vendor_device("00:11:22:33:44:55", "core-sw")]);
+        assert_eq!(service.discovery_cursor().await, None);
+
+        assert_eq!(service.discover_devices().await.unwrap().len(), 1);
+        assert_eq!(service.discovery_cursor().await.as_deref(), Some("run-1"));
+
+        assert!(service.discover_devices().await.unwrap().is_empty());
+        assert_eq!(*vendor.cursors.lock().unwrap(), vec![None, Some("run-1".to_string())]);
+        assert_eq!(service.discovery_cursor().await.as_deref(), Some("run-2"));
+    }
+
+    async fn discovered_names(service: &NetworkService, ids: &[DeviceId]) -> Vec<String> {
+        let mut names = Vec::new();
+        for id in ids {
+            names.push(service.get_device(*id).await.unwrap().name().to_string());
+        }
+        names
+    }
+
+    #[tokio::test]
+    async fn test_unnamed_devices_get_sequential_names() {
+        let vendor = Arc::new(ScriptedVendorAdapter::default());
+        let service = NetworkService::builder()
+            .event_store(MemoryEventStore::default())
+            .vendor_adapter_arc(vendor.clone())
+            .build()
+            .unwrap();
+
+        vendor.set_devices(vec![
+            vendor_device("00:11:22:33:44:01", ""),
+            vendor_device("00:11:22:33:44:02", "core-sw"),
+            vendor_device("00:11:22:33:44:03", ""),
+        ]);
+        let ids = service.discover_devices().await.unwrap();
+
+        assert_eq!(
+            discovered_names(&service, &ids).await,
+            ["switch-default-01", "core-sw", "switch-default-02"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_custom_naming_strategy_is_used() {
+        struct ByTaken;
+        impl NamingStrategy for ByTaken {
+            fn name(&self, _device_type: &DeviceType, taken: &[&str]) -> String {
+                format!("lab-{}", taken.len())
+            }
+        }
+
+        let vendor = Arc::new(ScriptedVendorAdapter::default());
+        let service = NetworkService::builder()
+            .event_store(MemoryEventStore::default())
+            .vendor_adapter_arc(vendor.clone())
+            .naming_strategy(ByTaken)
+            .build()
+            .unwrap();
+
+        vendor.set_devices(vec![
+            vendor_device("00:11:22:33:44:01", ""),
+            vendor_device("00:11:22:33:44:02", ""),
+        ]);
+        let ids = service.discover_devices().await.unwrap();
+
+        assert_eq!(discovered_names(&service, &ids).await, ["lab-0", "lab-1"]);
+    }
+
+    // ========================================================================
+    // Stack Tests
+    // ========================================================================
+
+    fn stack_unit(mac: &str, name: &str, master: &str, member: u32) -> VendorDevice {
+        let mut device = vendor_device(mac, name);
+        device.properties.insert(STACK_MASTER_PROPERTY.to_string(), serde_json::json!(master));
+        device.properties.insert(STACK_MEMBER_PROPERTY.to_string(), serde_json::json!(member));
+        device
+    }
+
+    #[tokio::test]
+    async fn test_discovery_merges_reported_stack_members() {
+        let vendor = Arc::new(ScriptedVendorAdapter::default());
+        let service = NetworkService::builder()
+            .event_store(MemoryEventStore::default())
+            .vendor_adapter_arc(vendor.clone())
+            .build()
+            .unwrap();
+
+        let master = "00:11:22:33:44:01";
+        vendor.set_devices(vec![
+            stack_unit("00:11:22:33:44:02", "core-stack-2", master, 2),
+            stack_unit(master, "core-stack", master, 1),
+            vendor_device("00:11:22:33:44:03", "edge-sw"),
+        ]);
+
+        let discovered = service.discover_devices().await.unwrap();
+        assert_eq!(discovered.len(), 2);
+        assert_eq!(service.list_devices().await.len(), 2);
+        let stack = service.list_devices().await.into_iter().find(|d| d.is_stack()).unwrap();
+        assert_eq!(stack.name(), "core-stack");
+        assert!(discovered.contains(&stack.id()));
+        assert_eq!(stack.stack_members().iter().map(|m| m.member).collect::<Vec<_>>(), [1, 2]);
+
+        // Units are known by their MAC on the next run
+        assert!(service.discover_devices().await.unwrap().is_empty());
+        assert_eq!(service.list_devices().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_merge_and_split_stack() {
+        let store = Arc::new(MemoryEventStore::default());
+        let vendor = Arc::new(ScriptedVendorAdapter::default());
+        let service = NetworkService::builder()
+            .event_store_arc(store.clone())
+            .vendor_adapter_arc(vendor.clone())
+            .build()
+            .unwrap();
+        vendor.set_devices(vec![
+            vendor_device("00:11:22:33:44:01", "sw-1"),
+            vendor_device("00:11:22:33:44:02", "sw-2"),
+        ]);
+        let ids = service.discover_devices().await.unwrap();
+        let (first, second) = (ids[0], ids[1]);
+
+        service.merge_stack(first, &[(first, 1), (second, 2)]).await.unwrap();
+
+        assert_eq!(service.list_devices().await.len(), 1);
+        assert!(service.get_device(second).await.is_none());
+        let stack = service.get_device(first).await.unwrap();
+        assert_eq!(stack.stack_members().iter().map(|m| m.device_id).collect::<Vec<_>>(), [Some(first), Some(second)]);
+        assert!(matches!(
+            service.merge_stack(second, &[(second, 1)]).await,
+            Err(ServiceError::DeviceNotFound(id)) if id == second
+        ));
+
+        // The merge survives a restart
+        assert_eq!(service_with(store.clone()).rehydrate().await.unwrap(), [first]);
+
+        assert_eq!(service.split_stack(first).await.unwrap(), [second]);
+        assert!(!service.get_device(first).await.unwrap().is_stack());
+        let unit = service.get_device(second).await.unwrap();
+        assert_eq!(unit.stacked_into(), None);
+        assert_eq!(unit.name(), "sw-2");
+        assert_eq!(service.list_devices().await.len(), 2);
+        assert_eq!(service_with(store).rehydrate().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_merge_leaves_units_standalone() {
+        let store = Arc::new(MemoryEventStore::default());
+        let vendor = Arc::new(ScriptedVendorAdapter::default());
+        let service = NetworkService::builder()
+            .event_store_arc(store.clone())
+            .vendor_adapter_arc(vendor.clone())
+            .build()
+            .unwrap();
+        vendor.set_devices(vec![
+            vendor_device("00:11:22:33:44:01", "sw-1"),
+            vendor_device("00:11:22:33:44:02", "sw-2"),
+            vendor_device("00:11:22:33:44:03", "sw-3"),
+        ]);
+        let ids = service.discover_devices().await.unwrap();
+        let (first, second, third) = (ids[0], ids[1], ids[2]);
+
+        // The third unit moved on behind the service's back, so its join conflicts
+        store.append(vec![NetworkEvent::DeviceRenamed {
+            device_id: third,
+            old_name: "sw-3".to_string(),
+            new_name: "renamed-elsewhere".to_string(),
+        }]).await.unwrap();
+
+        let result = service.merge_stack(first, &[(first, 1), (second, 2), (third, 3)]).await;
+
+        assert!(matches!(result, Err(ServiceError::ConcurrencyConflict { .. })), "{:?}", result);
+        assert!(!service.get_device(first).await.unwrap().is_stack());
+        assert_eq!(service.get_device(second).await.unwrap().stacked_into(), None);
+        assert_eq!(service.list_devices().await.len(), 3);
+
+        // The restored state is what a restart sees too
+        let restarted = service_with(store.clone());
+        restarted.rehydrate().await.unwrap();
+        assert_eq!(restarted.list_devices().await.len(), 3);
+        assert!(!restarted.get_device(first).await.unwrap().is_stack());
+        assert_eq!(restarted.get_device(second).await.unwrap().stacked_into(), None);
+    }
+
+    #[tokio::test]
+    async fn test_failed_split_leaves_stack_whole() {
+        let store = Arc::new(MemoryEventStore::default());
+        let vendor = Arc::new(ScriptedVendorAdapter::default());
+        let service = NetworkService::builder()
+            .event_store_arc(store.clone())
+            .vendor_adapter_arc(vendor.clone())
+            .build()
+            .unwrap();
+        vendor.set_devices(vec![
+            vendor_device("00:11:22:33:44:01", "sw-1"),
+            vendor_device("00:11:22:33:44:02", "sw-2"),
+            vendor_device("00:11:22:33:44:03", "sw-3"),
+        ]);
+        let ids = service.discover_devices().await.unwrap();
+        let (first, second, third) = (ids[0], ids[1], ids[2]);
+        service.merge_stack(first, &[(first, 1), (second, 2), (third, 3)]).await.unwrap();
+
+        // The stack moved on behind the service's back, so dropping its
+        // members conflicts after the units were already restored
+        store.append(vec![NetworkEvent::DeviceRenamed {
+            device_id: first,
+            old_name: "sw-1".to_string(),
+            new_name: "renamed-elsewhere".to_string(),
+        }]).await.unwrap();
+
+        let result = service.split_stack(first).await;
+
+        assert!(matches!(result, Err(ServiceError::ConcurrencyConflict { .. })), "{:?}", result);
+        assert_eq!(service.get_device(first).await.unwrap().stack_members().len(), 3);
+        assert!(service.get_device(second).await.is_none());
+        assert!(service.get_device(third).await.is_none());
+        assert_eq!(service.list_devices().await.len(), 1);
+
+        // The restored state is what a restart sees too
+        let restarted = service_with(store.clone());
+        restarted.rehydrate().await.unwrap();
+        assert_eq!(restarted.list_devices().await.len(), 1);
+        assert_eq!(restarted.get_device(first).await.unwrap().stack_members().len(), 3);
+    }
+
+    // ========================================================================
+    // Retry Tests
+    // ========================================================================
+
+    async fn service_adopting_after(failures: Vec<PortError>) -> (NetworkService, Arc<ScriptedVendorAdapter>, DeviceId) {
+        let vendor = Arc::new(ScriptedVendorAdapter {
+            adopt_failures: Mutex::new(failures),
+            ..Default::default()
+        });
+        let service = NetworkService::builder()
+            .event_store(MemoryEventStore::default())
+            .vendor_adapter_arc(vendor.clone())
+            .retry_policy(RetryPolicy::new(3, Duration::from_millis(1)))
+            .build()
+            .unwrap();
+        vendor.set_devices(vec![vendor_device("00:11:22:33:44:55", "core-sw")]);
+        let device_id = service.discover_devices().await.unwrap()[0];
+        (service, vendor, device_id)
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried() {
+        let (service, vendor, device_id) = service_adopting_after(vec![
+            PortError::RateLimited { retry_after: Some(Duration::from_millis(5)) },
+            PortError::Timeout("controller slow".to_string()),
+        ])
+        .await;
+
+        service.adopt_device(device_id).await.unwrap();
+
+        assert_eq!(vendor.adopt_attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(service.get_device(device_id).await.unwrap().state(), DeviceState::Adopting);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_failures_are_not_retried() {
+        let (service, vendor, device_id) =
+            service_adopting_after(vec![PortError::AuthenticationFailed("bad password".to_string())]).await;
+
+        let result = service.adopt_device(device_id).await;
+
+        assert!(matches!(result, Err(ServiceError::Port(PortError::AuthenticationFailed(_)))));
+        assert_eq!(vendor.adopt_attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_adopting_a_provisioned_device_is_an_invalid_transition() {
+        let vendor = Arc::new(ScriptedVendorAdapter::default().with_devices(vec![core_switch()]));
+        let (service, store, device_id) = provisioned_switch(vendor.clone()).await;
+        let before = store.load_events(&device_id.to_string()).await.unwrap().len();
+
+        let result = service.adopt_device(device_id).await;
+
+        assert!(matches!(
+            result,
+            Err(ServiceError::InvalidTransition { from: DeviceState::Provisioned, to: DeviceState::Adopting })
+        ));
+        assert_eq!(store.load_events(&device_id.to_string()).await.unwrap().len(), before);
+        assert_eq!(vendor.adopt_attempts.load(Ordering::SeqCst), 1);
+    }
+
+    // ========================================================================
+    // Reconciliation Tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn test_reconcile_device_goes_missing_and_reappears() {
+        let store = Arc::new(MemoryEventStore::default());
+        let vendor = Arc::new(ScriptedVendorAdapter::default());
+        let service = NetworkService::builder()
+            .event_store_arc(store.clone())
+            .vendor_adapter_arc(vendor.clone())
+            .build()
+            .unwrap();
+
+        vendor.set_devices(vec![
+            vendor_device("00:11:22:33:44:55", "core-sw"),
+            vendor_device("00:11:22:33:44:66", "new-ap"),
+        ]);
+        let discovered = service.discover_devices().await.unwrap();
+        assert_eq!(discovered.len(), 2);
+
+        let core = service.list_devices().await
+            .into_iter()
+            .find(|d| d.name() == "core-sw")
+            .unwrap()
+            .id();
+        service.adopt_device(core).await.unwrap();
+        service.mark_provisioned(core, "USW-24".to_string(), "6.6.0".to_string()).await.unwrap();
+
+        // First run: everything is still reported
+        let report = service.reconcile_devices().await.unwrap();
+        assert_eq!(report, DeviceReconciliation::default());
+
+        // Second run: both devices drop out; only the provisioned one is flagged
+        vendor.set_devices(vec![]);
+        service.discover_devices().await.unwrap();
+        let report = service.reconcile_devices().await.unwrap();
+        assert_eq!(report.missing, vec![core]);
+        assert!(report.reappeared.is_empty());
+        assert_eq!(service.get_device(core).await.unwrap().state(), DeviceState::Missing);
+        assert_eq!(service.list_devices_by_state(DeviceState::Discovered).await.len(), 1);
+
+        let events = store.load_events(&core.to_string()).await.unwrap();
+        assert!(matches!(
+            events.last(),
+            Some(NetworkEvent::DeviceWentMissing { last_seen: Some(_), .. })
+        ));
+
+        // Third run: the device is back
+        vendor.set_devices(vec![vendor_device("00:11:22:33:44:55", "core-sw")]);
+        assert!(service.discover_devices().await.unwrap().is_empty());
+        let report = service.reconcile_devices().await.unwrap();
+        assert_eq!(report.reappeared, vec![core]);
+        assert!(report.missing.is_empty());
+
+        let device = service.get_device(core).await.unwrap();
+        assert_eq!(device.state(), DeviceState::Provisioned);
+
+        // The persisted history replays to the same state
+        let replayed = NetworkDeviceAggregate::from_events(
+            store.load_events(&core.to_string()).await.unwrap(),
+        ).unwrap();
+        assert_eq!(replayed.state(), DeviceState::Provisioned);
+        assert_eq!(replayed.version(), device.version());
+    }
+
+    #[tokio::test]
+    async fn test_missing_device_records_last_seen_from_clock() {
+        let start = DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        let clock = Arc::new(MockClock::new(start));
+        let store = Arc::new(MemoryEventStore::default());
+        let vendor = Arc::new(ScriptedVendorAdapter::default());
+        let service = NetworkService::builder()
+            .event_store_arc(store.clone())
+            .vendor_adapter_arc(vendor.clone())
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+
+        vendor.set_devices(vec![vendor_device("00:11:22:33:44:55", "core-sw")]);
+        let device_id = service.discover_devices().await.unwrap()[0];
+        service.adopt_device(device_id).await.unwrap();
+        service.mark_provisioned(device_id, "USW-24".to_string(), "6.6.0".to_string()).await.unwrap();
+        clock.advance(chrono::Duration::minutes(5));
+        service.reconcile_devices().await.unwrap();
+
+        clock.advance(chrono::Duration::minutes(5));
+        vendor.set_devices(vec![]);
+        service.reconcile_devices().await.unwrap();
+
+        let events = store.load_events(&device_id.to_string()).await.unwrap();
+        let last_seen = start + chrono::Duration::minutes(5);
+        assert!(matches!(
+            events.last(),
+            Some(NetworkEvent::DeviceWentMissing { last_seen: Some(at), .. }) if *at == last_seen
+        ));
+    }
+
+    // ========================================================================
+    // Firmware Tests
+    // ========================================================================
+
+    /// The switch `provisioned_switch` expects its vendor adapter to report
+    fn core_switch() -> VendorDevice {
+        vendor_device("24:5a:4c:00:00:01", "core-sw")
+    }
+
+    /// Service with the vendor's one switch provisioned, running firmware 6.5.59
+    async fn provisioned_switch(
+        vendor: Arc<impl DeviceControlPort + 'static>,
+    ) -> (NetworkService, Arc<MemoryEventStore>, DeviceId) {
+        let store = Arc::new(MemoryEventStore::default());
+        let service = NetworkService::builder()
+            .event_store_arc(store.clone())
+            .vendor_adapter_arc(vendor)
+            .build()
+            .unwrap();
+
+        let device_id = service.discover_devices().await.unwrap()[0];
+        service.adopt_device(device_id).await.unwrap();
+        service.mark_provisioned(device_id, "USW-24".to_string(), "6.5.59".to_string()).await.unwrap();
+        (service, store, device_id)
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_firmware_records_new_version() {
+        let vendor = Arc::new(ScriptedVendorAdapter::default().with_devices(vec![core_switch()]));
+        let (service, store, device_id) = provisioned_switch(vendor.clone()).await;
+
+        service.upgrade_firmware(device_id, "6.6.65".to_string()).await.unwrap();
+
+        let device = service.get_device(device_id).await.unwrap();
+        assert_eq!(device.firmware_version(), Some("6.6.65"));
+        assert_eq!(device.state(), DeviceState::Provisioned);
+        assert_eq!(
+            vendor.upgrades.lock().unwrap().as_slice(),
+            &[("24:5a:4c:00:00:01".to_string(), "6.6.65".to_string())]
+        );
+
+        let events = store.load_events(&device_id.to_string()).await.unwrap();
+        let upgrade: Vec<_> = events.iter().rev().take(2).rev().collect();
+        assert!(matches!(
+            upgrade[0],
+            NetworkEvent::FirmwareUpgradeStarted { from_version: Some(from), target_version, .. }
+                if from == "6.5.59" && target_version == "6.6.65"
+        ));
+        assert!(matches!(
+            upgrade[1],
+            NetworkEvent::FirmwareUpgradeCompleted { firmware_version, .. } if firmware_version == "6.6.65"
+        ));
+
+        // The persisted history replays to the upgraded firmware
+        let replayed = NetworkDeviceAggregate::from_events(events).unwrap();
+        assert_eq!(replayed.firmware_version(), Some("6.6.65"));
+        assert_eq!(replayed.version(), device.version());
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_to_current_firmware_is_noop() {
+        let vendor = Arc::new(ScriptedVendorAdapter::default().with_devices(vec![core_switch()]));
+        let (service, store, device_id) = provisioned_switch(vendor.clone()).await;
+        let before = store.load_events(&device_id.to_string()).await.unwrap().len();
+
+        service.upgrade_firmware(device_id, "6.5.59".to_string()).await.unwrap();
+
+        assert_eq!(store.load_events(&device_id.to_string()).await.unwrap().len(), before);
+        assert!(vendor.upgrades.lock().unwrap().is_empty());
+        assert_eq!(service.get_device(device_id).await.unwrap().state(), DeviceState::Provisioned);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_firmware_upgrade_records_error() {
+        let vendor = ScriptedVendorAdapter {
+            reject_firmware: Some("9.9.9".to_string()),
+            ..Default::default()
+        };
+        let (service, _store, device_id) = provisioned_switch(Arc::new(vendor.with_devices(vec![core_switch()]))).await;
+
+        let result = service.upgrade_firmware(device_id, "9.9.9".to_string()).await;
+
+        assert!(matches!(result, Err(ServiceError::Port(PortError::VendorError(_)))));
+        let device = service.get_device(device_id).await.unwrap();
+        assert_eq!(device.state(), DeviceState::Error);
+        assert_eq!(device.firmware_version(), Some("6.5.59"));
+    }
+
+    // ========================================================================
+    // Configuration Tests
+    // ========================================================================
+
+    /// Vendor adapter that applies configuration one VLAN stanza at a time
+    ///
+    /// The stanza at index `fail_at` is rejected, leaving the earlier ones
+    /// applied until a snapshot is restored.
+    struct StanzaVendorAdapter {
+        device: VendorDevice,
+        running: Mutex<Vec<u16>>,
+        fail_at: usize,
+    }
+
+    #[async_trait]
+    impl DeviceControlPort for StanzaVendorAdapter {
+        fn vendor_name(&self) -> &str { "Stanza" }
+        async fn connect(&self) -> Result<(), PortError> { Ok(()) }
+        async fn disconnect(&self) -> Result<(), PortError> { Ok(()) }
+        fn is_connected(&self) -> bool { true }
+        async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError> { Ok(vec![self.device.clone()]) }
+        async fn get_device(&self, _vendor_id: &str) -> Result<VendorDevice, PortError> { Ok(self.device.clone()) }
+        async fn adopt_device(&self, _vendor_id: &str) -> Result<(), PortError> { Ok(()) }
+        async fn apply_config(&self, _vendor_id: &str, config: DeviceConfigRequest) -> Result<(), PortError> {
+            for (i, vlan) in config.vlans.iter().enumerate() {
+                if i == self.fail_at {
+                    return Err(PortError::VendorError(format!("stanza for VLAN {} rejected", vlan.id)));
+                }
+                self.running.lock().unwrap().push(vlan.id);
+            }
+            Ok(())
+        }
+        async fn apply_raw_config(&self, _vendor_id: &str, _config: VendorConfig) -> Result<(), PortError> { Ok(()) }
+        async fn snapshot_config(&self, _vendor_id: &str) -> Result<VendorConfig, PortError> {
+            Ok(VendorConfig {
+                config_type: "vlans".to_string(),
+                payload: serde_json::json!(*self.running.lock().unwrap()),
+            })
+        }
+        async fn restore_config(&self, _vendor_id: &str, snapshot: VendorConfig) -> Result<(), PortError> {
+            *self.running.lock().unwrap() = serde_json::from_value(snapshot.payload).unwrap();
+            Ok(())
+        }
+        async fn restart_device(&self, _vendor_id: &str) -> Result<(), PortError> { Ok(()) }
+        async fn get_device_stats(&self, _vendor_id: &str) -> Result<DeviceStats, PortError> {
+            Err(PortError::NotSupported("stanza adapter".to_string()))
         }
     }
 
-    /// Set the event store
-    pub fn event_store<E: EventStorePort + 'static>(mut self, store: E) -> Self {
-        self.event_store = Some(Arc::new(store));
-        self
+    async fn stanza_service(fail_at: usize) -> (NetworkService, Arc<MemoryEventStore>, Arc<StanzaVendorAdapter>, DeviceId) {
+        let vendor = Arc::new(StanzaVendorAdapter {
+            device: core_switch(),
+            running: Mutex::new(vec![1]),
+            fail_at,
+        });
+        let (service, store, device_id) = provisioned_switch(vendor.clone()).await;
+        (service, store, vendor, device_id)
     }
 
-    /// Set the event store from Arc
-    pub fn event_store_arc(mut self, store: Arc<dyn EventStorePort>) -> Self {
-        self.event_store = Some(store);
-        self
+    fn three_vlans() -> DeviceConfigRequest {
+        DeviceConfigRequest::default()
+            .with_vlan(VlanConfig::new(10, "users").unwrap())
+            .with_vlan(VlanConfig::new(20, "voice").unwrap())
+            .with_vlan(VlanConfig::new(30, "cameras").unwrap())
     }
 
-    /// Set the vendor adapter
-    pub fn vendor_adapter<V: DeviceControlPort + 'static>(mut self, adapter: V) -> Self {
-        self.vendor_adapter = Some(Arc::new(adapter));
-        self
+    #[tokio::test]
+    async fn test_configure_device_records_applied_config() {
+        let (service, _store, vendor, device_id) = stanza_service(usize::MAX).await;
+
+        let outcome = service.configure_device(device_id, three_vlans()).await.unwrap();
+
+        assert_eq!(outcome, ConfigApplyOutcome::Applied);
+        assert_eq!(*vendor.running.lock().unwrap(), [1, 10, 20, 30]);
+        let device = service.get_device(device_id).await.unwrap();
+        assert_eq!(device.state(), DeviceState::Provisioned);
+        assert_eq!(device.vlans().len(), 3);
     }
 
-    /// Set the vendor adapter from Arc
-    pub fn vendor_adapter_arc(mut self, adapter: Arc<dyn DeviceControlPort>) -> Self {
-        self.vendor_adapter = Some(adapter);
-        self
+    #[tokio::test]
+    async fn test_failed_stanza_rolls_back_config() {
+        let (service, store, vendor, device_id) = stanza_service(2).await;
+
+        let outcome = service.configure_device(device_id, three_vlans()).await.unwrap();
+
+        assert!(matches!(outcome, ConfigApplyOutcome::RolledBack { ref reason } if reason.contains("VLAN 30")));
+        // The two stanzas applied before the failure were undone
+        assert_eq!(*vendor.running.lock().unwrap(), [1]);
+
+        let device = service.get_device(device_id).await.unwrap();
+        assert_eq!(device.state(), DeviceState::Provisioned);
+        assert!(device.vlans().is_empty());
+
+        let events = store.load_events(&device_id.to_string()).await.unwrap();
+        assert!(matches!(events.last(), Some(NetworkEvent::ConfigRolledBack { .. })));
+        let replayed = NetworkDeviceAggregate::from_events(events).unwrap();
+        assert_eq!(replayed.state(), DeviceState::Provisioned);
+        assert!(replayed.vlans().is_empty());
     }
 
-    /// Set the inventory adapter
-    pub fn inventory_adapter<I: InventoryPort + 'static>(mut self, adapter: I) -> Self {
-        self.inventory_adapter = Some(Arc::new(adapter));
-        self
+    fn vlan_template() -> ConfigTemplate {
+        ConfigTemplate::new("vlan-profile", serde_json::json!({
+            "hostname": "{{hostname}}",
+            "vlans": "{{vlans}}",
+        }))
     }
 
-    /// Set the inventory adapter from Arc
-    pub fn inventory_adapter_arc(mut self, adapter: Arc<dyn InventoryPort>) -> Self {
-        self.inventory_adapter = Some(adapter);
-        self
+    #[tokio::test]
+    async fn test_configure_from_template_applies_rendered_config() {
+        let (service, _store, vendor, device_id) = stanza_service(usize::MAX).await;
+        let variables = TemplateVariables::new().vlans(&three_vlans().vlans);
+
+        let outcome = service.configure_from_template(device_id, &vlan_template(), &variables).await.unwrap();
+
+        assert_eq!(outcome, ConfigApplyOutcome::Applied);
+        assert_eq!(*vendor.running.lock().unwrap(), [1, 10, 20, 30]);
+        let device = service.get_device(device_id).await.unwrap();
+        assert_eq!(device.vlans(), three_vlans().vlans.as_slice());
     }
 
-    /// Build the service
-    pub fn build(self) -> Result<NetworkService, PortError> {
-        let event_store = self.event_store
-            .ok_or_else(|| PortError::NotSupported("Event store is required".to_string()))?;
+    #[tokio::test]
+    async fn test_template_missing_variable_is_rejected_before_configuring() {
+        let (service, store, vendor, device_id) = stanza_service(usize::MAX).await;
+        let events_before = store.load_events(&device_id.to_string()).await.unwrap().len();
 
-        let vendor_adapter = self.vendor_adapter
-            .ok_or_else(|| PortError::NotSupported("Vendor adapter is required".to_string()))?;
+        let result = service.configure_from_template(device_id, &vlan_template(), &TemplateVariables::new()).await;
 
-        Ok(NetworkService {
-            event_store,
-            vendor_adapter,
-            inventory_adapter: self.inventory_adapter,
-            devices: Arc::new(RwLock::new(HashMap::new())),
+        assert!(matches!(
+            result,
+            Err(ServiceError::Template(TemplateError::MissingVariables { ref names, .. })) if names == &["vlans"]
+        ));
+        assert_eq!(*vendor.running.lock().unwrap(), [1]);
+        assert_eq!(store.load_events(&device_id.to_string()).await.unwrap().len(), events_before);
+    }
+
+    #[tokio::test]
+    async fn test_detect_drift_reports_only_the_changed_vlan() {
+        let mut running = three_vlans();
+        running.vlans[1].name = "phones".to_string();
+        running.vlans.push(VlanConfig::new(99, "lab").unwrap());
+        let store = Arc::new(MemoryEventStore::default());
+        let vendor = ScriptedVendorAdapter { running_config: Some(running), ..Default::default() };
+        vendor.set_devices(vec![vendor_device("24:5a:4c:00:00:01", "core-sw")]);
+        let service = NetworkService::builder()
+            .event_store_arc(store.clone())
+            .vendor_adapter(vendor)
+            .build()
+            .unwrap();
+        let device_id = service.discover_devices().await.unwrap()[0];
+
+        let diffs = service.detect_drift(device_id, &three_vlans()).await.unwrap();
+
+        // VLAN 99 exists only on the device, which is not drift
+        assert_eq!(diffs, vec![ConfigDiff {
+            setting: "vlan[20]".to_string(),
+            desired: serde_json::json!(VlanConfig::new(20, "voice").unwrap()),
+            running: Some(serde_json::json!(VlanConfig::new(20, "phones").unwrap())),
+        }]);
+        let events = store.load_events(&device_id.to_string()).await.unwrap();
+        assert!(matches!(
+            events.last(),
+            Some(NetworkEvent::ConfigDriftDetected { diffs: recorded, .. }) if *recorded == diffs
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_detect_drift_records_nothing_when_in_sync() {
+        let store = Arc::new(MemoryEventStore::default());
+        let vendor = ScriptedVendorAdapter { running_config: Some(three_vlans()), ..Default::default() };
+        vendor.set_devices(vec![vendor_device("24:5a:4c:00:00:01", "core-sw")]);
+        let service = NetworkService::builder()
+            .event_store_arc(store.clone())
+            .vendor_adapter(vendor)
+            .build()
+            .unwrap();
+        let device_id = service.discover_devices().await.unwrap()[0];
+        let recorded = store.load_events(&device_id.to_string()).await.unwrap().len();
+
+        assert!(service.detect_drift(device_id, &three_vlans()).await.unwrap().is_empty());
+        assert_eq!(store.load_events(&device_id.to_string()).await.unwrap().len(), recorded);
+    }
+
+    // ========================================================================
+    // Batch Tests
+    // ========================================================================
+
+    fn rename_and_configure(device_id: DeviceId) -> Vec<NetworkCommand> {
+        vec![
+            NetworkCommand::RenameDevice { device_id, new_name: "core-sw-01".to_string() },
+            NetworkCommand::ConfigureDevice {
+                device_id,
+                name: None,
+                interfaces: vec![],
+                vlans: vec![VlanConfig::new(10, "users").unwrap()],
+            },
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_execute_batch_persists_every_command() {
+        let vendor = ScriptedVendorAdapter::default().with_devices(vec![core_switch()]);
+        let (service, store, device_id) = provisioned_switch(Arc::new(vendor)).await;
+        let before = store.load_events(&device_id.to_string()).await.unwrap().len();
+
+        let events = service.execute_batch(rename_and_configure(device_id)).await.unwrap();
+
+        assert!(matches!(events.first(), Some(NetworkEvent::DeviceRenamed { .. })));
+        let stored = store.load_events(&device_id.to_string()).await.unwrap();
+        assert_eq!(stored.len(), before + events.len());
+
+        let device = service.get_device(device_id).await.unwrap();
+        assert_eq!(device.name(), "core-sw-01");
+        assert_eq!(device.vlans().len(), 1);
+        assert_eq!(device.state(), DeviceState::Provisioned);
+        assert_eq!(device.version(), NetworkDeviceAggregate::from_events(stored).unwrap().version());
+    }
+
+    #[tokio::test]
+    async fn test_execute_batch_persists_nothing_when_a_command_fails() {
+        let vendor = ScriptedVendorAdapter::default().with_devices(vec![core_switch()]);
+        let (service, store, device_id) = provisioned_switch(Arc::new(vendor)).await;
+        let before = store.load_events(&device_id.to_string()).await.unwrap();
+
+        // A provisioned device cannot be adopted again
+        let mut commands = rename_and_configure(device_id);
+        commands.push(NetworkCommand::AdoptDevice { device_id, vendor_id: "24:5a:4c:00:00:01".to_string() });
+        let result = service.execute_batch(commands).await;
+
+        assert!(matches!(
+            result,
+            Err(ServiceError::BatchCommandFailed { index: 2, ref source })
+                if matches!(**source, ServiceError::InvalidTransition { .. })
+        ));
+        assert_eq!(store.load_events(&device_id.to_string()).await.unwrap().len(), before.len());
+        assert_eq!(service.get_device(device_id).await.unwrap().name(), "core-sw");
+    }
+
+    #[tokio::test]
+    async fn test_execute_batch_fails_if_the_aggregate_moved_on() {
+        let vendor = ScriptedVendorAdapter::default().with_devices(vec![core_switch()]);
+        let (service, store, device_id) = provisioned_switch(Arc::new(vendor)).await;
+        store.append(vec![NetworkEvent::DeviceRenamed {
+            device_id,
+            old_name: "core-sw".to_string(),
+            new_name: "renamed-elsewhere".to_string(),
+        }]).await.unwrap();
+
+        let result = service.execute_batch(rename_and_configure(device_id)).await;
+
+        assert!(matches!(result, Err(ServiceError::ConcurrencyConflict { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_execute_batch_appends_nothing_if_a_later_aggregate_moved_on() {
+        let (service, store, _inventory, core, edge) = connection_service().await;
+        let connection_id = service
+            .plan_connection(core, PortId::new("eth24"), edge, PortId::new("eth1"), ConnectionType::Fiber)
+            .await
+            .unwrap();
+        let device_events = store.load_events(&core.to_string()).await.unwrap().len();
+
+        // The connection, touched second, moved on behind the service's back
+        store.append(vec![NetworkEvent::ConnectionCabled { connection_id }]).await.unwrap();
+
+        let result = service
+            .execute_batch(vec![
+                NetworkCommand::RenameDevice { device_id: core, new_name: "core-sw-01".to_string() },
+                NetworkCommand::DisconnectDevices { connection_id },
+            ])
+            .await;
+
+        assert!(matches!(result, Err(ServiceError::ConcurrencyConflict { .. })), "{:?}", result);
+        assert_eq!(store.load_events(&core.to_string()).await.unwrap().len(), device_events);
+        assert_eq!(service.get_device(core).await.unwrap().name(), "core-sw");
+        assert_eq!(service.get_connection(connection_id).await.unwrap().state(), ConnectionState::Planned);
+    }
+
+    // ========================================================================
+    // Health Tests
+    // ========================================================================
+
+    fn service_with_health(health: HealthStatus) -> NetworkService {
+        NetworkService::builder()
+            .event_store(MemoryEventStore::default())
+            .vendor_adapter(ScriptedVendorAdapter { health: Some(health), ..Default::default() })
+            .inventory_adapter(SlowInventory { delay: Duration::ZERO, synced: AtomicUsize::new(0) })
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_check_health_reports_each_adapter() {
+        let service = service_with_health(HealthStatus {
+            reachable: true,
+            authenticated: true,
+            version: Some("8.0.26".to_string()),
+        });
+
+        let health = service.check_health().await;
+
+        assert_eq!(health.vendor.name, "Scripted");
+        let vendor = health.vendor.status.as_ref().unwrap();
+        assert!(vendor.reachable);
+        assert!(vendor.authenticated);
+        assert_eq!(vendor.version.as_deref(), Some("8.0.26"));
+
+        // The inventory has no probe, which does not count against the service
+        let inventory = health.inventory.as_ref().unwrap();
+        assert_eq!(inventory.name, "slow");
+        assert!(matches!(inventory.status, Err(PortError::NotSupported(_))));
+        assert!(health.is_healthy());
+    }
+
+    #[tokio::test]
+    async fn test_check_health_flags_unauthenticated_vendor() {
+        let service = service_with_health(HealthStatus {
+            reachable: true,
+            authenticated: false,
+            version: Some("8.0.26".to_string()),
+        });
+
+        let health = service.check_health().await;
+
+        assert!(!health.vendor.is_healthy());
+        assert!(!health.is_healthy());
+    }
+
+    // ========================================================================
+    // Tracing Tests
+    // ========================================================================
+
+    /// Span recorded by `SpanCapture`
+    #[derive(Debug, Clone)]
+    struct CapturedSpan {
+        name: &'static str,
+        parent: Option<&'static str>,
+        correlation_id: Option<String>,
+    }
+
+    /// Layer recording each new span's name, parent and correlation id
+    #[derive(Clone, Default)]
+    struct SpanCapture {
+        spans: Arc<Mutex<Vec<CapturedSpan>>>,
+    }
+
+    struct CorrelationVisitor(Option<String>);
+
+    impl tracing::field::Visit for CorrelationVisitor {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            if field.name() == "correlation_id" {
+                self.0 = Some(value.to_string());
+            }
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "correlation_id" {
+                self.0 = Some(format!("{:?}", value));
+            }
+        }
+    }
+
+    impl<S> tracing_subscriber::Layer<S> for SpanCapture
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut visitor = CorrelationVisitor(None);
+            attrs.record(&mut visitor);
+            let parent = ctx.span(id).and_then(|span| span.parent()).map(|parent| parent.name());
+            self.spans.lock().unwrap().push(CapturedSpan {
+                name: attrs.metadata().name(),
+                parent,
+                correlation_id: visitor.0,
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn test_discover_and_adopt_spans_share_correlation() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let capture = SpanCapture::default();
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+        let vendor = Arc::new(ScriptedVendorAdapter::default());
+        vendor.set_devices(vec![vendor_device("24:5a:4c:00:00:01", "core-sw")]);
+        let service = NetworkService::builder()
+            .event_store(MemoryEventStore::default())
+            .vendor_adapter_arc(vendor)
+            .build()
+            .unwrap();
+
+        telemetry::with_correlation("flow-1", async {
+            let device_id = service.discover_devices().await.unwrap()[0];
+            service.adopt_device(device_id).await.unwrap();
         })
+        .await;
+
+        let spans = capture.spans.lock().unwrap();
+        let span = |name: &str| {
+            spans.iter().find(|s| s.name == name).unwrap_or_else(|| panic!("no {} span", name)).clone()
+        };
+        let root = span(telemetry::CORRELATION_SPAN);
+        assert_eq!(root.parent, None);
+        assert_eq!(root.correlation_id.as_deref(), Some("flow-1"));
+        for name in ["discover_devices", "adopt_device"] {
+            assert_eq!(span(name).parent, Some(telemetry::CORRELATION_SPAN));
+            assert_eq!(span(name).correlation_id.as_deref(), Some("flow-1"));
+        }
     }
-}
 
-impl Default for NetworkServiceBuilder {
-    fn default() -> Self {
-        Self::new()
+    // ========================================================================
+    // Metrics Tests
+    // ========================================================================
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_discovery_cycle_updates_metrics() {
+        let vendor = Arc::new(ScriptedVendorAdapter::default());
+        let service = NetworkService::builder()
+            .event_store(MemoryEventStore::default())
+            .vendor_adapter_arc(vendor.clone())
+            .inventory_adapter(SlowInventory { delay: Duration::ZERO, synced: AtomicUsize::new(0) })
+            .build()
+            .unwrap();
+        let sample = |name: &str| {
+            service.metrics_handle().render()
+                .lines()
+                .find(|line| line.starts_with(name))
+                .and_then(|line| line.rsplit(' ').next())
+                .and_then(|value| value.parse::<f64>().ok())
+        };
+
+        vendor.set_devices(vec![
+            vendor_device("00:11:22:33:44:55", "core-sw"),
+            vendor_device("00:11:22:33:44:66", "new-ap"),
+        ]);
+        service.discover_and_provision().await.unwrap();
+
+        assert_eq!(sample(r#"devices_discovered_total{vendor="Scripted"}"#), Some(2.0));
+        assert_eq!(sample(r#"inventory_syncs_total{system="slow",result="success"}"#), Some(2.0));
+        assert_eq!(sample(r#"discovery_duration_seconds_count{vendor="Scripted"}"#), Some(1.0));
+
+        // A second cycle finds one more device and re-adopting a known one fails
+        vendor.set_devices(vec![
+            vendor_device("00:11:22:33:44:55", "core-sw"),
+            vendor_device("00:11:22:33:44:66", "new-ap"),
+            vendor_device("00:11:22:33:44:77", "edge-sw"),
+        ]);
+        let report = service.discover_and_provision().await.unwrap();
+        let core = report.adoption.succeeded[0];
+        assert!(service.adopt_device(core).await.is_err());
+
+        assert_eq!(sample(r#"devices_discovered_total{vendor="Scripted"}"#), Some(3.0));
+        assert_eq!(sample(r#"adoptions_failed_total{vendor="Scripted"}"#), Some(1.0));
+        assert_eq!(sample(r#"discovery_duration_seconds_count{vendor="Scripted"}"#), Some(2.0));
     }
-}
 
-/// Infer device type from model string
-fn infer_device_type(model: &str) -> DeviceType {
-    let model_lower = model.to_lowercase();
+    // ========================================================================
+    // Connection Tests
+    // ========================================================================
 
-    if model_lower.contains("gateway") || model_lower.contains("ugw") || model_lower.contains("udm") {
-        DeviceType::Gateway
-    } else if model_lower.contains("switch") || model_lower.contains("usw") {
-        DeviceType::Switch
-    } else if model_lower.contains("ap") || model_lower.contains("uap") || model_lower.contains("u6") {
-        DeviceType::AccessPoint
-    } else {
-        DeviceType::Generic { model: model.to_string() }
+    /// Service with two discovered switches and a recording inventory
+    async fn connection_service() -> (NetworkService, Arc<MemoryEventStore>, Arc<CableInventory>, DeviceId, DeviceId) {
+        let store = Arc::new(MemoryEventStore::default());
+        let vendor = Arc::new(ScriptedVendorAdapter::default());
+        let inventory = Arc::new(CableInventory::default());
+        let service = NetworkService::builder()
+            .event_store_arc(store.clone())
+            .vendor_adapter_arc(vendor.clone())
+            .inventory_adapter_arc(inventory.clone())
+            .build()
+            .unwrap();
+
+        vendor.set_devices(vec![
+            vendor_device("00:11:22:33:44:55", "core-sw"),
+            vendor_device("00:11:22:33:44:66", "edge-sw"),
+        ]);
+        service.discover_devices().await.unwrap();
+        let core = find_by_name(&service, "core-sw").await;
+        let edge = find_by_name(&service, "edge-sw").await;
+        (service, store, inventory, core, edge)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[tokio::test]
+    async fn test_connection_lifecycle() {
+        let (service, store, inventory, core, edge) = connection_service().await;
+
+        let connection_id = service
+            .plan_connection(core, PortId::new("eth24"), edge, PortId::new("eth1"), ConnectionType::Fiber)
+            .await
+            .unwrap();
+        assert_eq!(service.get_connection(connection_id).await.unwrap().state(), ConnectionState::Planned);
+        assert!(inventory.cabled.lock().unwrap().is_empty());
+
+        service.cable_connection(connection_id).await.unwrap();
+        service.activate_connection(connection_id, Some(LinkSpeed::Gbps10)).await.unwrap();
+        service.degrade_connection(connection_id, "rx power low".to_string()).await.unwrap();
+        service.activate_connection(connection_id, Some(LinkSpeed::Gbps10)).await.unwrap();
+        service.remove_connection(connection_id).await.unwrap();
+
+        let connection = service.get_connection(connection_id).await.unwrap();
+        assert_eq!(connection.state(), ConnectionState::Removed);
+
+        // The cable reached the inventory once cabled, and left it on removal
+        let cabled: Vec<_> = inventory.cabled.lock().unwrap()
+            .iter()
+            .map(|c| (c.connection_id, c.source_device, c.target_device))
+            .collect();
+        assert_eq!(cabled, vec![(connection_id, core, edge)]);
+        assert_eq!(*inventory.removed.lock().unwrap(), vec![connection_id]);
+
+        // Every step was persisted and replays to the same state
+        let events = store.load_events(&connection_id.to_string()).await.unwrap();
+        assert_eq!(events.len() as u64, connection.version());
+        let replayed = NetworkConnectionAggregate::from_events(events).unwrap();
+        assert_eq!(replayed.state(), ConnectionState::Removed);
+    }
+
+    #[tokio::test]
+    async fn test_connection_rejects_invalid_transition() {
+        let (service, store, _inventory, core, edge) = connection_service().await;
+        let connection_id = service
+            .plan_connection(core, PortId::new("eth24"), edge, PortId::new("eth1"), ConnectionType::Ethernet)
+            .await
+            .unwrap();
+        service.cable_connection(connection_id).await.unwrap();
+        service.remove_connection(connection_id).await.unwrap();
+
+        let result = service.activate_connection(connection_id, None).await;
+
+        assert!(matches!(
+            result,
+            Err(ServiceError::Aggregate(AggregateError::InvalidConnectionTransition {
+                from: ConnectionState::Removed,
+                to: ConnectionState::Active,
+            }))
+        ));
+        assert_eq!(store.load_events(&connection_id.to_string()).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_reversed_link_is_a_duplicate_unless_directional() {
+        let (service, _store, _inventory, core, edge) = connection_service().await;
+        let plan = |source, source_port: &str, target, target_port: &str, connection_type| {
+            service.plan_connection(source, PortId::new(source_port), target, PortId::new(target_port), connection_type)
+        };
+
+        let first = plan(core, "eth24", edge, "eth1", ConnectionType::Ethernet).await.unwrap();
+        let reversed = plan(edge, "eth1", core, "eth24", ConnectionType::Ethernet).await;
+        assert!(matches!(
+            reversed,
+            Err(ServiceError::Aggregate(AggregateError::DuplicateConnection { existing })) if existing == first
+        ));
+
+        // Uplinks point at a parent, so each direction is its own connection
+        plan(core, "eth48", edge, "eth2", ConnectionType::Uplink).await.unwrap();
+        plan(edge, "eth2", core, "eth48", ConnectionType::Uplink).await.unwrap();
+        assert!(plan(core, "eth48", edge, "eth2", ConnectionType::Uplink).await.is_err());
+
+        // A removed link can be planned again
+        service.remove_connection(first).await.unwrap();
+        plan(edge, "eth1", core, "eth24", ConnectionType::Ethernet).await.unwrap();
+        assert_eq!(service.list_connections().await.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_plan_connection_requires_known_devices() {
+        let (service, _store, _inventory, core, _edge) = connection_service().await;
+        let stranger = DeviceId::new();
+
+        let result = service
+            .plan_connection(core, PortId::new("eth1"), stranger, PortId::new("eth1"), ConnectionType::Ethernet)
+            .await;
+
+        assert!(matches!(result, Err(ServiceError::DeviceNotFound(id)) if id == stranger));
+        assert!(service.list_connections().await.is_empty());
+        assert!(matches!(
+            service.cable_connection(ConnectionId::new()).await,
+            Err(ServiceError::ConnectionNotFound(_))
+        ));
+    }
+
+    // ========================================================================
+    // Site Tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn test_site_groups_assigned_devices() {
+        let (service, store, _inventory, core, edge) = connection_service().await;
+
+        let site_id = service.create_site("hq").await.unwrap();
+        service.assign_device_to_site(site_id, core).await.unwrap();
+        service.assign_device_to_site(site_id, edge).await.unwrap();
+        service.assign_device_to_site(site_id, core).await.unwrap();
+
+        let members: Vec<_> = service.list_devices_in_site(site_id).await.unwrap()
+            .iter()
+            .map(|d| d.id())
+            .collect();
+        assert_eq!(members, vec![core, edge]);
+        assert_eq!(service.site_of(edge).await.unwrap().name(), "hq");
+
+        // Created + two assignments; the repeat was not persisted
+        let events = store.load_events(&site_id.to_string()).await.unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(SiteAggregate::from_events(events).unwrap().devices(), &[core, edge]);
+    }
+
+    #[tokio::test]
+    async fn test_reassigned_device_leaves_its_old_site() {
+        let (service, _store, _inventory, core, edge) = connection_service().await;
+        let hq = service.create_site("hq").await.unwrap();
+        let branch = service.create_site("branch").await.unwrap();
+        service.assign_device_to_site(hq, core).await.unwrap();
+        service.assign_device_to_site(hq, edge).await.unwrap();
+
+        service.assign_device_to_site(branch, edge).await.unwrap();
+
+        assert_eq!(service.get_site(hq).await.unwrap().devices(), &[core]);
+        assert_eq!(service.get_site(branch).await.unwrap().devices(), &[edge]);
+        assert_eq!(service.list_sites().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_site_commands_require_known_site_and_device() {
+        let (service, _store, _inventory, core, _edge) = connection_service().await;
+        let site_id = service.create_site("hq").await.unwrap();
+        let stranger = DeviceId::new();
+        let unknown_site = SiteId::new();
+
+        assert!(matches!(
+            service.assign_device_to_site(site_id, stranger).await,
+            Err(ServiceError::DeviceNotFound(id)) if id == stranger
+        ));
+        assert!(matches!(
+            service.assign_device_to_site(unknown_site, core).await,
+            Err(ServiceError::SiteNotFound(id)) if id == unknown_site
+        ));
+        assert!(matches!(
+            service.list_devices_in_site(unknown_site).await,
+            Err(ServiceError::SiteNotFound(_))
+        ));
+        assert!(service.site_of(core).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_allocate_site_prefix_rejects_overlap() {
+        let (service, _store, _inventory, _core, _edge) = connection_service().await;
+        let site_id = service.create_site("hq").await.unwrap();
+        let lan = IpNetwork::parse("10.20.0.0/16").unwrap();
+
+        service.allocate_site_prefix(site_id, lan).await.unwrap();
+        let result = service.allocate_site_prefix(site_id, IpNetwork::parse("10.20.30.0/24").unwrap()).await;
+
+        assert!(matches!(
+            result,
+            Err(ServiceError::Aggregate(AggregateError::OverlappingPrefix { existing, .. })) if existing == lan
+        ));
+        assert_eq!(service.get_site(site_id).await.unwrap().prefixes(), &[lan]);
+    }
+
+    // ========================================================================
+    // Stats Monitor Tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn test_stats_monitor_emits_link_edges() {
+        let store = Arc::new(MemoryEventStore::default());
+        let vendor = Arc::new(StatsVendorAdapter {
+            device: vendor_device("24:5a:4c:00:00:01", "core-sw"),
+            script: Mutex::new([port_stats(true), port_stats(false), port_stats(true)].into()),
+            polls: AtomicUsize::new(0),
+        });
+        let service = Arc::new(
+            NetworkService::builder()
+                .event_store_arc(store.clone())
+                .vendor_adapter_arc(vendor.clone())
+                .build()
+                .unwrap(),
+        );
+        let device_id = service.discover_devices().await.unwrap()[0];
+
+        let monitor = service.start_stats_monitor(device_id, Duration::from_millis(5)).await.unwrap();
+        assert_eq!(monitor.device_id(), device_id);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while vendor.polls.load(Ordering::SeqCst) < 5 && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        monitor.stop().await;
+
+        let links: Vec<bool> = store.events.lock().unwrap()
+            .iter()
+            .filter_map(|e| match e {
+                NetworkEvent::PortLinkChanged { device_id: id, port_id, up } if *id == device_id => {
+                    assert_eq!(*port_id, PortId::with_index("port", 1));
+                    Some(*up)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(links, [false, true]);
+
+        // The cached aggregate stays in step with the store
+        let cached = service.get_device(device_id).await.unwrap();
+        assert_eq!(cached.version(), store.load_events(&device_id.to_string()).await.unwrap().len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_stats_monitor_requires_known_device() {
+        let service = Arc::new(service_with(Arc::new(MemoryEventStore::default())));
+
+        let result = service.start_stats_monitor(DeviceId::new(), Duration::from_millis(5)).await;
+        assert!(matches!(result, Err(ServiceError::DeviceNotFound(_))));
+    }
+
+    // ========================================================================
+    // Query Tests
+    // ========================================================================
+
+    /// Service with a Ubiquiti switch and AP, plus a switch of unknown vendor;
+    /// `core-sw` is adopted
+    async fn query_service() -> NetworkService {
+        let vendor = Arc::new(ScriptedVendorAdapter::default());
+        let service = NetworkService::builder()
+            .event_store(MemoryEventStore::default())
+            .vendor_adapter_arc(vendor.clone())
+            .build()
+            .unwrap();
+
+        vendor.set_devices(vec![
+            vendor_device("24:5a:4c:00:00:01", "core-sw"),
+            VendorDevice {
+                model: "UAP-AC-Pro".to_string(),
+                ..vendor_device("24:5a:4c:00:00:02", "lobby-ap")
+            },
+            vendor_device("00:11:22:33:44:55", "Edge-SW"),
+        ]);
+        service.discover_devices().await.unwrap();
+        let core = find_by_name(&service, "core-sw").await;
+        service.adopt_device(core).await.unwrap();
+        service
+    }
+
+    async fn find_by_name(service: &NetworkService, name: &str) -> DeviceId {
+        service.list_devices().await.into_iter().find(|d| d.name() == name).unwrap().id()
+    }
+
+    async fn found_names(service: &NetworkService, filter: DeviceFilter) -> Vec<String> {
+        let mut names: Vec<_> = service.find_devices(filter).await
+            .into_iter()
+            .map(|d| d.name().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn test_find_devices_empty_filter_returns_all() {
+        let service = query_service().await;
+        assert_eq!(found_names(&service, DeviceFilter::new()).await, ["Edge-SW", "core-sw", "lobby-ap"]);
+    }
+
+    #[tokio::test]
+    async fn test_find_devices_by_state() {
+        let service = query_service().await;
+        assert_eq!(found_names(&service, DeviceFilter::new().state(DeviceState::Adopting)).await, ["core-sw"]);
+        assert_eq!(
+            found_names(&service, DeviceFilter::new().state(DeviceState::Discovered)).await,
+            ["Edge-SW", "lobby-ap"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_devices_by_device_type() {
+        let service = query_service().await;
+        assert_eq!(
+            found_names(&service, DeviceFilter::new().device_type(DeviceType::Switch)).await,
+            ["Edge-SW", "core-sw"]
+        );
+        assert_eq!(
+            found_names(&service, DeviceFilter::new().device_type(DeviceType::AccessPoint)).await,
+            ["lobby-ap"]
+        );
+        assert!(found_names(&service, DeviceFilter::new().device_type(DeviceType::Gateway)).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_devices_by_vendor_name() {
+        let service = query_service().await;
+        assert_eq!(
+            found_names(&service, DeviceFilter::new().vendor_name("ubiquiti")).await,
+            ["core-sw", "lobby-ap"]
+        );
+        assert!(found_names(&service, DeviceFilter::new().vendor_name("Cisco")).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_devices_by_name_contains() {
+        let service = query_service().await;
+        assert_eq!(found_names(&service, DeviceFilter::new().name_contains("sw")).await, ["Edge-SW", "core-sw"]);
+        assert_eq!(found_names(&service, DeviceFilter::new().name_contains("lobby")).await, ["lobby-ap"]);
+    }
+
+    #[tokio::test]
+    async fn test_find_devices_by_mac_prefix() {
+        let service = query_service().await;
+        let by_prefix = |prefix: &str| DeviceFilter::new().mac_prefix(MacPrefix::parse(prefix).unwrap());
+
+        assert_eq!(found_names(&service, by_prefix("24:5a:4c:*")).await, ["core-sw", "lobby-ap"]);
+        assert_eq!(found_names(&service, by_prefix("24-5A-4C-00-00-02")).await, ["lobby-ap"]);
+        assert_eq!(found_names(&service, by_prefix("00")).await, ["Edge-SW"]);
+        assert!(found_names(&service, by_prefix("24:5a:4d")).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_devices_combines_conditions() {
+        let service = query_service().await;
+        let switches = DeviceFilter::new()
+            .device_type(DeviceType::Switch)
+            .vendor_name("Ubiquiti")
+            .name_contains("SW");
+
+        assert_eq!(found_names(&service, switches.clone()).await, ["core-sw"]);
+        assert_eq!(found_names(&service, switches.clone().state(DeviceState::Adopting)).await, ["core-sw"]);
+        assert!(found_names(&service, switches.state(DeviceState::Discovered)).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_devices_by_tag() {
+        let service = query_service().await;
+        let tag = |s: &str| Tag::parse(s).unwrap();
+        let core = find_by_name(&service, "core-sw").await;
+        let edge = find_by_name(&service, "Edge-SW").await;
+        service.tag_device(core, tag("env:prod")).await.unwrap();
+        service.tag_device(core, tag("rack:R12")).await.unwrap();
+        service.tag_device(edge, tag("env:prod")).await.unwrap();
+
+        assert_eq!(found_names(&service, DeviceFilter::new().tag(tag("env:prod"))).await, ["Edge-SW", "core-sw"]);
+        assert_eq!(
+            found_names(&service, DeviceFilter::new().tag(tag("env:prod")).tag(tag("rack:R12"))).await,
+            ["core-sw"]
+        );
+        assert!(found_names(&service, DeviceFilter::new().tag(tag("env:dev"))).await.is_empty());
+
+        service.untag_device(core, &tag("env:prod")).await.unwrap();
+        assert_eq!(found_names(&service, DeviceFilter::new().tag(tag("env:prod"))).await, ["Edge-SW"]);
+
+        // Tags survive a rebuild from the event store
+        let replayed = service.replay_events(&core.to_string()).await.unwrap().unwrap();
+        assert_eq!(replayed.tags().iter().map(Tag::to_string).collect::<Vec<_>>(), ["rack:R12"]);
+    }
+
+    // ========================================================================
+    // Projection Tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn test_fold_all_counts_devices_by_state() {
+        let store = MemoryEventStore::default();
+        let [provisioned, failed, missing, decommissioned, discovered] = [(); 5].map(|_| DeviceId::new());
+        let discover = |device_id| NetworkEvent::DeviceDiscovered {
+            device_id,
+            mac: MacAddress::parse("00:11:22:33:44:55").unwrap(),
+            device_type: DeviceType::Switch,
+            ip_address: None,
+        };
+        let adopt = |device_id| NetworkEvent::DeviceAdopting { device_id, vendor_id: "v-1".to_string() };
+        let provision = |device_id| NetworkEvent::DeviceProvisioned {
+            device_id,
+            model: "USW-24".to_string(),
+            firmware_version: "7.0.0".to_string(),
+        };
+
+        store.append(vec![
+            discover(provisioned), adopt(provisioned), provision(provisioned),
+            discover(failed), adopt(failed),
+            discover(missing), adopt(missing), provision(missing),
+            discover(decommissioned),
+            discover(discovered),
+            NetworkEvent::DeviceError { device_id: failed, message: "adoption timed out".to_string() },
+            NetworkEvent::DeviceWentMissing { device_id: missing, last_seen: None },
+            NetworkEvent::DeviceDecommissioned { device_id: decommissioned },
+            // Inventory events share the device's id and must not be folded twice
+            NetworkEvent::DeviceSyncedToInventory {
+                device_id: provisioned,
+                inventory_id: "42".to_string(),
+                system: "netbox".to_string(),
+                content_hash: None,
+            },
+        ]).await.unwrap();
+
+        let mut counts = DeviceCountByState::new();
+        store.fold_all(&mut counts).await.unwrap();
+
+        assert_eq!(counts.total(), 5);
+        assert_eq!(
+            counts.counts(),
+            [
+                (DeviceState::Discovered, 1),
+                (DeviceState::Adopting, 0),
+                (DeviceState::Provisioned, 1),
+                (DeviceState::Configuring, 0),
+                (DeviceState::Error, 1),
+                (DeviceState::Missing, 1),
+                (DeviceState::Decommissioned, 1),
+            ]
+        );
+        assert_eq!(store.events_read.load(Ordering::SeqCst), 14);
+    }
+
+    // ========================================================================
+    // Helper Tests
+    // ========================================================================
 
     #[test]
     fn test_infer_device_type() {
@@ -443,6 +4908,10 @@ mod tests {
         assert!(matches!(infer_device_type("UAP-AC-Pro"), DeviceType::AccessPoint));
         assert!(matches!(infer_device_type("UDM-Pro"), DeviceType::Gateway));
         assert!(matches!(infer_device_type("U6-Pro"), DeviceType::AccessPoint));
+        assert!(matches!(infer_device_type("OPNsense"), DeviceType::Firewall));
+        assert!(matches!(infer_device_type("FGT-60F"), DeviceType::Firewall));
+        assert!(matches!(infer_device_type("FortiGate-100F"), DeviceType::Firewall));
+        assert!(matches!(infer_device_type("PA-440"), DeviceType::Firewall));
         assert!(matches!(infer_device_type("Unknown"), DeviceType::Generic { .. }));
     }
 }
@@ -0,0 +1,282 @@
+//! Device statistics monitoring for `NetworkService`
+//!
+//! `NetworkService::start_stats_monitor` polls a device's statistics on a
+//! background task and compares each poll with the previous one. Edges are
+//! recorded on the device aggregate, and so reach the event store:
+//!
+//! - `PortLinkChanged` when a port's link goes down or comes back up
+//! - `DeviceHealthDegraded` when a metric rises above its threshold
+//!
+//! The first poll only establishes the port baseline. A metric already above
+//! its threshold on the first poll counts as having crossed it.
+//!
+//! `ThroughputTracker` turns the cumulative byte counters of successive polls
+//! into per-port rates for callers that poll stats themselves.
+
+use std::collections::HashMap;
+use std::time::Instant;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use crate::domain::ports::DeviceStats;
+use crate::domain::value_objects::{DeviceId, HealthMetric, PortId};
+
+/// Levels above which the stats monitor reports a device as degraded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HealthThresholds {
+    /// CPU usage, percent
+    pub cpu_percent: f64,
+    /// Memory usage, percent
+    pub memory_percent: f64,
+    /// Temperature, degrees Celsius
+    pub temperature_celsius: f64,
+}
+
+impl HealthThresholds {
+    /// Threshold for `metric`
+    pub fn threshold(&self, metric: HealthMetric) -> f64 {
+        match metric {
+            HealthMetric::CpuPercent => self.cpu_percent,
+            HealthMetric::MemoryPercent => self.memory_percent,
+            HealthMetric::TemperatureCelsius => self.temperature_celsius,
+        }
+    }
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        Self {
+            cpu_percent: 90.0,
+            memory_percent: 90.0,
+            temperature_celsius: 80.0,
+        }
+    }
+}
+
+/// Handle to a running stats monitor
+///
+/// Dropping the handle stops the monitor after its current poll, like `stop`
+/// without waiting for it.
+pub struct StatsMonitorHandle {
+    device_id: DeviceId,
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl StatsMonitorHandle {
+    pub(crate) fn new(device_id: DeviceId, stop: oneshot::Sender<()>, task: JoinHandle<()>) -> Self {
+        Self { device_id, stop, task }
+    }
+
+    /// Device being monitored
+    pub fn device_id(&self) -> DeviceId {
+        self.device_id
+    }
+
+    /// Stop the monitor and wait for its current poll to finish
+    pub async fn stop(self) {
+        let _ = self.stop.send(());
+        let _ = self.task.await;
+    }
+}
+
+/// A change between two polls
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum StatsChange {
+    PortLink { port_id: PortId, up: bool },
+    HealthDegraded { metric: HealthMetric, value: f64 },
+}
+
+/// Previous poll of one device, kept to turn levels into edges
+pub(crate) struct StatsTracker {
+    thresholds: HealthThresholds,
+    links: Option<HashMap<PortId, bool>>,
+    degraded: HashMap<HealthMetric, bool>,
+}
+
+impl StatsTracker {
+    pub(crate) fn new(thresholds: HealthThresholds) -> Self {
+        Self {
+            thresholds,
+            links: None,
+            degraded: HashMap::new(),
+        }
+    }
+
+    /// Compare a poll with the previous one and remember it
+    pub(crate) fn observe(&mut self, stats: &DeviceStats) -> Vec<StatsChange> {
+        let mut changes = Vec::new();
+
+        let links: HashMap<PortId, bool> = stats.port_stats
+            .iter()
+            .map(|port| (port.port_id.clone(), port.link_up))
+            .collect();
+        if let Some(ref previous) = self.links {
+            for port in &stats.port_stats {
+                // Ports missing from the previous poll have no edge yet
+                if previous.get(&port.port_id).is_some_and(|&up| up != port.link_up) {
+                    changes.push(StatsChange::PortLink { port_id: port.port_id.clone(), up: port.link_up });
+                }
+            }
+        }
+        self.links = Some(links);
+
+        let readings = [
+            (HealthMetric::CpuPercent, stats.cpu_percent),
+            (HealthMetric::MemoryPercent, stats.memory_percent),
+            (HealthMetric::TemperatureCelsius, stats.temperature_celsius),
+        ];
+        for (metric, value) in readings {
+            // A metric the poll did not report keeps its previous level
+            let Some(value) = value else { continue };
+            let above = value > self.thresholds.threshold(metric);
+            let was_above = self.degraded.insert(metric, above).unwrap_or(false);
+            if above && !was_above {
+                changes.push(StatsChange::HealthDegraded { metric, value });
+            }
+        }
+
+        changes
+    }
+}
+
+/// Byte counters of one port at one poll
+#[derive(Debug, Clone, Copy)]
+struct CounterSample {
+    at: Instant,
+    rx_bytes: u64,
+    tx_bytes: u64,
+}
+
+/// Computes port throughput from the byte counters of successive polls
+///
+/// Keep one tracker per device. Each `observe` fills in `rx_bps` and
+/// `tx_bps` from the port's previous sample; a port seen for the first time
+/// has no rate yet. A counter lower than before means the counter wrapped
+/// or the device restarted, so that delta is skipped and the rate left unset.
+#[derive(Debug, Clone, Default)]
+pub struct ThroughputTracker {
+    samples: HashMap<PortId, CounterSample>,
+}
+
+impl ThroughputTracker {
+    /// Create a tracker with no samples
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fill in the rates of `stats`, polled at `at`, and remember its counters
+    ///
+    /// Ports missing from this poll are forgotten.
+    pub fn observe(&mut self, stats: &mut DeviceStats, at: Instant) {
+        let mut samples = HashMap::with_capacity(stats.port_stats.len());
+        for port in &mut stats.port_stats {
+            let sample = CounterSample { at, rx_bytes: port.rx_bytes, tx_bytes: port.tx_bytes };
+            let previous = self.samples.get(&port.port_id);
+            port.rx_bps = previous.and_then(|p| bits_per_second(p.rx_bytes, sample.rx_bytes, p.at, at));
+            port.tx_bps = previous.and_then(|p| bits_per_second(p.tx_bytes, sample.tx_bytes, p.at, at));
+            samples.insert(port.port_id.clone(), sample);
+        }
+        self.samples = samples;
+    }
+}
+
+/// Rate between two counter readings, or `None` across a reset or without elapsed time
+fn bits_per_second(before: u64, after: u64, from: Instant, to: Instant) -> Option<f64> {
+    let elapsed = to.checked_duration_since(from)?.as_secs_f64();
+    let delta = after.checked_sub(before)?;
+    (elapsed > 0.0).then(|| delta as f64 * 8.0 / elapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ports::PortStats;
+    use std::time::Duration;
+
+    fn stats(cpu: Option<f64>, links: &[(u32, bool)]) -> DeviceStats {
+        DeviceStats {
+            uptime_seconds: 0,
+            cpu_percent: cpu,
+            memory_percent: None,
+            temperature_celsius: None,
+            port_stats: links
+                .iter()
+                .map(|&(idx, up)| PortStats {
+                    port_id: PortId::with_index("port", idx),
+                    link_up: up,
+                    speed: None,
+                    rx_bytes: 0,
+                    tx_bytes: 0,
+                    rx_errors: 0,
+                    tx_errors: 0,
+                    rx_bps: None,
+                    tx_bps: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_link_edges_need_a_baseline() {
+        let mut tracker = StatsTracker::new(HealthThresholds::default());
+
+        assert!(tracker.observe(&stats(None, &[(1, false), (2, true)])).is_empty());
+        assert_eq!(tracker.observe(&stats(None, &[(1, true), (2, true), (3, false)])), vec![
+            StatsChange::PortLink { port_id: PortId::with_index("port", 1), up: true },
+        ]);
+        assert!(tracker.observe(&stats(None, &[(1, true), (2, true), (3, false)])).is_empty());
+    }
+
+    #[test]
+    fn test_health_degrades_once_per_crossing() {
+        let mut tracker = StatsTracker::new(HealthThresholds::default());
+        let degraded = |value| vec![StatsChange::HealthDegraded { metric: HealthMetric::CpuPercent, value }];
+
+        assert_eq!(tracker.observe(&stats(Some(95.0), &[])), degraded(95.0));
+        assert!(tracker.observe(&stats(Some(97.0), &[])).is_empty());
+        // An unreported metric neither clears nor repeats the crossing
+        assert!(tracker.observe(&stats(None, &[])).is_empty());
+        assert!(tracker.observe(&stats(Some(40.0), &[])).is_empty());
+        assert_eq!(tracker.observe(&stats(Some(91.5), &[])), degraded(91.5));
+    }
+
+    fn counters(rx_bytes: u64, tx_bytes: u64) -> DeviceStats {
+        let mut stats = stats(None, &[(1, true)]);
+        stats.port_stats[0].rx_bytes = rx_bytes;
+        stats.port_stats[0].tx_bytes = tx_bytes;
+        stats
+    }
+
+    #[test]
+    fn test_throughput_from_byte_delta() {
+        let mut tracker = ThroughputTracker::new();
+        let start = Instant::now();
+
+        let mut first = counters(1_000, 5_000);
+        tracker.observe(&mut first, start);
+        assert_eq!((first.port_stats[0].rx_bps, first.port_stats[0].tx_bps), (None, None));
+
+        let mut second = counters(1_000 + 12_500_000, 5_000 + 1_250);
+        tracker.observe(&mut second, start + Duration::from_secs(10));
+        assert_eq!(second.port_stats[0].rx_bps, Some(10_000_000.0));
+        assert_eq!(second.port_stats[0].tx_bps, Some(1_000.0));
+    }
+
+    #[test]
+    fn test_throughput_skips_counter_reset() {
+        let mut tracker = ThroughputTracker::new();
+        let start = Instant::now();
+
+        tracker.observe(&mut counters(9_000, 9_000), start);
+        let mut reset = counters(100, 9_500);
+        tracker.observe(&mut reset, start + Duration::from_secs(10));
+        assert_eq!(reset.port_stats[0].rx_bps, None);
+        assert_eq!(reset.port_stats[0].tx_bps, Some(400.0));
+
+        // The reset sample is the baseline for the next poll
+        let mut after = counters(1_100, 9_500);
+        tracker.observe(&mut after, start + Duration::from_secs(20));
+        assert_eq!(after.port_stats[0].rx_bps, Some(800.0));
+    }
+}
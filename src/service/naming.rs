@@ -0,0 +1,106 @@
+//! Device naming for `NetworkService` discovery
+//!
+//! Devices the vendor reports without a name would otherwise keep the
+//! placeholder `Device-<uuid prefix>` from `NetworkDeviceAggregate`, which
+//! says nothing about the device and changes if it is rediscovered. At
+//! discovery the service asks its `NamingStrategy` for a name instead and
+//! records it as a rename. Names the vendor does report are kept.
+
+use crate::domain::value_objects::DeviceType;
+
+/// Names newly discovered devices the vendor reports without a name
+pub trait NamingStrategy: Send + Sync {
+    /// Name for a device of `device_type`
+    ///
+    /// `taken` holds the names of every device the service already knows;
+    /// the returned name must not be one of them.
+    fn name(&self, device_type: &DeviceType, taken: &[&str]) -> String;
+}
+
+/// Site used by `SequentialNaming::default`
+pub const DEFAULT_SITE: &str = "default";
+
+/// `{device_type}-{site}-{seq}` names, e.g. `switch-default-01`
+///
+/// The sequence continues after the highest one taken for that type and
+/// site, so names stay unique within the site and a removed device's name
+/// is not handed to the next one. It is zero-padded to two digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequentialNaming {
+    site: String,
+}
+
+impl SequentialNaming {
+    /// Name devices of `site`
+    pub fn new(site: impl Into<String>) -> Self {
+        Self { site: site.into() }
+    }
+
+    /// Site embedded in every name
+    pub fn site(&self) -> &str {
+        &self.site
+    }
+}
+
+impl Default for SequentialNaming {
+    fn default() -> Self {
+        Self::new(DEFAULT_SITE)
+    }
+}
+
+impl NamingStrategy for SequentialNaming {
+    fn name(&self, device_type: &DeviceType, taken: &[&str]) -> String {
+        let prefix = format!("{}-{}-", type_slug(device_type), self.site);
+        let highest = taken
+            .iter()
+            .filter_map(|name| name.strip_prefix(&prefix)?.parse::<u32>().ok())
+            .max()
+            .unwrap_or(0);
+        format!("{}{:02}", prefix, highest + 1)
+    }
+}
+
+/// Lowercase, hyphenated name of a device type; generic devices use their model
+fn type_slug(device_type: &DeviceType) -> String {
+    let name = match device_type {
+        DeviceType::Gateway => "gateway",
+        DeviceType::Switch => "switch",
+        DeviceType::AccessPoint => "ap",
+        DeviceType::Firewall => "firewall",
+        DeviceType::Generic { model } => model.as_str(),
+    };
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() { "device".to_string() } else { slug }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence_continues_after_highest_taken() {
+        let naming = SequentialNaming::default();
+
+        assert_eq!(naming.name(&DeviceType::Switch, &[]), "switch-default-01");
+        assert_eq!(
+            naming.name(&DeviceType::Switch, &["switch-default-01", "switch-default-07", "ap-default-09"]),
+            "switch-default-08"
+        );
+        assert_eq!(naming.name(&DeviceType::AccessPoint, &["switch-default-01"]), "ap-default-01");
+        assert_eq!(naming.name(&DeviceType::Gateway, &["gateway-lab-03"]), "gateway-default-01");
+    }
+
+    #[test]
+    fn test_generic_devices_are_named_by_model() {
+        let naming = SequentialNaming::new("lab");
+        let generic = |model: &str| DeviceType::Generic { model: model.to_string() };
+
+        assert_eq!(naming.name(&generic("DCS-7050SX3"), &[]), "dcs-7050sx3-lab-01");
+        assert_eq!(naming.name(&generic(""), &[]), "device-lab-01");
+    }
+}
@@ -0,0 +1,57 @@
+//! Inventory sync queue for `NetworkService`
+//!
+//! A sync the inventory fails is not lost: `NetworkService::sync_to_inventory`
+//! records it on the device aggregate as `InventorySyncQueued`, with when the
+//! next attempt is due, so the queue survives a restart with the event store.
+//!
+//! `NetworkService::start_inventory_sync_worker` retries due syncs on a
+//! background task, backing off following the service's inventory sync
+//! retry policy. A sync still failing after the policy's last attempt is
+//! recorded as `InventorySyncDeadLettered` and left for an operator;
+//! `NetworkService::dead_lettered_syncs` lists them. Any later successful
+//! sync of the device takes it off the queue.
+//!
+//! While a device's sync is queued and not yet due, or dead-lettered,
+//! `discover_and_provision` and `mark_provisioned` leave it alone, so the
+//! retry policy rather than how often they run paces the attempts.
+//! Calling `sync_to_inventory` directly always attempts the sync.
+
+use serde::Serialize;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use crate::domain::value_objects::DeviceId;
+
+/// An inventory sync given up on after repeated failures
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeadLetteredSync {
+    /// Device that could not be synced
+    pub device_id: DeviceId,
+    /// Inventory system it could not be synced to
+    pub system: String,
+    /// Failed attempts in total
+    pub attempts: u32,
+    /// Error of the last attempt
+    pub error: String,
+}
+
+/// Handle to a running inventory sync worker
+///
+/// Dropping the handle stops the worker after its current round, like
+/// `stop` without waiting for it.
+pub struct InventorySyncWorkerHandle {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl InventorySyncWorkerHandle {
+    pub(crate) fn new(stop: oneshot::Sender<()>, task: JoinHandle<()>) -> Self {
+        Self { stop, task }
+    }
+
+    /// Stop the worker and wait for its current round to finish
+    pub async fn stop(self) {
+        let _ = self.stop.send(());
+        let _ = self.task.await;
+    }
+}
@@ -0,0 +1,152 @@
+//! Tracing spans tied to correlation ids
+//!
+//! A flow such as discover → adopt → sync runs inside one correlation: a
+//! root span carrying the correlation id, entered with `with_correlation`.
+//! `NetworkService` spans record the current correlation id as a field, and
+//! events appended to NATS during the flow carry it as `CIM-Correlation-Id`.
+//! A consumer handling those events opens the same root again with
+//! `correlation_span`, so the original flow and its replay line up.
+//!
+//! With the `otel` feature, `otlp_layer` exports spans over OTLP. Root spans
+//! then take their trace id from the correlation id, so every span of one
+//! correlation lands in the same trace, however many processes produced it.
+
+use std::future::Future;
+use tracing::Instrument;
+
+/// Name of the root span opened for a correlation
+pub const CORRELATION_SPAN: &str = "cim.correlation";
+
+tokio::task_local! {
+    static CORRELATION_ID: String;
+}
+
+/// Correlation id of the flow the current task runs in, if any
+pub fn current_correlation_id() -> Option<String> {
+    CORRELATION_ID.try_with(Clone::clone).ok()
+}
+
+/// Root span for `correlation_id`
+///
+/// The span has no parent: a correlation is the root of its trace.
+pub fn correlation_span(correlation_id: &str) -> tracing::Span {
+    let span = tracing::info_span!(parent: None, CORRELATION_SPAN, correlation_id = %correlation_id);
+    #[cfg(feature = "otel")]
+    otel::root_trace(&span, correlation_id);
+    span
+}
+
+/// Run `future` as part of the flow identified by `correlation_id`
+///
+/// The future runs inside the correlation's root span, and
+/// `current_correlation_id` returns `correlation_id` while it runs.
+pub async fn with_correlation<F: Future>(correlation_id: impl Into<String>, future: F) -> F::Output {
+    let correlation_id = correlation_id.into();
+    let span = correlation_span(&correlation_id);
+    CORRELATION_ID.scope(correlation_id, future.instrument(span)).await
+}
+
+#[cfg(feature = "otel")]
+pub use otel::{otlp_layer, trace_id, TelemetryError};
+
+#[cfg(feature = "otel")]
+mod otel {
+    use opentelemetry::trace::{
+        SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState, TracerProvider,
+    };
+    use opentelemetry::Context;
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::trace::{SdkTracerProvider, Tracer};
+    use opentelemetry_sdk::Resource;
+    use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
+    use tracing_subscriber::registry::LookupSpan;
+
+    /// Errors setting up OTLP export
+    #[derive(Debug, thiserror::Error)]
+    pub enum TelemetryError {
+        /// The OTLP exporter could not be built
+        #[error("OTLP exporter error: {0}")]
+        Exporter(String),
+    }
+
+    /// Trace id for a correlation
+    ///
+    /// Correlation ids are UUIDs, whose 16 bytes are used as is. Anything else
+    /// is hashed with 128-bit FNV-1a, which is stable across builds.
+    pub fn trace_id(correlation_id: &str) -> TraceId {
+        if let Ok(uuid) = uuid::Uuid::parse_str(correlation_id) {
+            return TraceId::from_bytes(uuid.into_bytes());
+        }
+        let hash = correlation_id.bytes().fold(0x6c62_272e_07bb_0142_62b8_2175_6295_c58du128, |hash, byte| {
+            (hash ^ u128::from(byte)).wrapping_mul(0x0000_0000_0100_0000_0000_0000_0000_013b)
+        });
+        TraceId::from_bytes(hash.to_be_bytes())
+    }
+
+    /// Make `span` the root of the correlation's trace
+    pub(super) fn root_trace(span: &tracing::Span, correlation_id: &str) {
+        let trace_id = trace_id(correlation_id);
+        // The remote parent stands in for the correlation; its id only has
+        // to be valid, which rules out zero
+        let low = u128::from_be_bytes(trace_id.to_bytes()) as u64;
+        let remote = SpanContext::new(
+            trace_id,
+            SpanId::from_bytes(low.max(1).to_be_bytes()),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+        // Fails only when no OpenTelemetry layer is installed
+        let _ = span.set_parent(Context::new().with_remote_span_context(remote));
+    }
+
+    /// Layer exporting spans to the OTLP/HTTP collector at `endpoint`
+    ///
+    /// Returns the tracer provider too; call `shutdown` on it before exiting
+    /// to flush spans still buffered.
+    pub fn otlp_layer<S>(
+        endpoint: &str,
+        service_name: &str,
+    ) -> Result<(OpenTelemetryLayer<S, Tracer>, SdkTracerProvider), TelemetryError>
+    where
+        S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+    {
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(|e| TelemetryError::Exporter(e.to_string()))?;
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(Resource::builder().with_service_name(service_name.to_string()).build())
+            .build();
+        let tracer = provider.tracer("cim-network");
+        Ok((tracing_opentelemetry::layer().with_tracer(tracer), provider))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_correlation_is_scoped_to_the_flow() {
+        assert_eq!(current_correlation_id(), None);
+
+        let inside = with_correlation("flow-1", async { current_correlation_id() }).await;
+
+        assert_eq!(inside.as_deref(), Some("flow-1"));
+        assert_eq!(current_correlation_id(), None);
+    }
+
+    #[cfg(feature = "otel")]
+    #[test]
+    fn test_trace_id_follows_correlation_id() {
+        let uuid = uuid::Uuid::now_v7();
+        assert_eq!(trace_id(&uuid.to_string()).to_bytes(), uuid.into_bytes());
+
+        // Other ids hash to a stable, distinct trace
+        assert_eq!(trace_id("op-1"), trace_id("op-1"));
+        assert_ne!(trace_id("op-1"), trace_id("op-2"));
+    }
+}
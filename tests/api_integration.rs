@@ -0,0 +1,216 @@
+//! Integration tests for the HTTP API
+//!
+//! These tests serve `cim_network::api::router` on a local port, over a
+//! `NetworkService` wired to an in-process vendor adapter and event store,
+//! and drive it with a plain HTTP client.
+//!
+//! Run with: cargo test --features api --test api_integration
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use cim_network::api::{self, DiscoverResponse, ErrorBody};
+use cim_network::domain::events::NetworkEvent;
+use cim_network::domain::ports::{
+    DeviceConfigRequest, DeviceControlPort, DeviceStats, EventStorePort, EventStream, EventSubscription,
+    PortError, VendorConfig, VendorDevice,
+};
+use cim_network::domain::value_objects::{DeviceId, MacAddress};
+use cim_network::service::NetworkService;
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+/// Vendor adapter reporting two unadopted switches
+struct MockVendorAdapter;
+
+fn vendor_device(mac: &str, name: &str) -> VendorDevice {
+    VendorDevice {
+        vendor_id: mac.to_string(),
+        device_id: None,
+        mac: MacAddress::parse(mac).unwrap(),
+        model: "USW-24".to_string(),
+        name: name.to_string(),
+        ip_address: None,
+        adopted: false,
+        properties: Default::default(),
+    }
+}
+
+#[async_trait]
+impl DeviceControlPort for MockVendorAdapter {
+    fn vendor_name(&self) -> &str { "MockVendor" }
+    async fn connect(&self) -> Result<(), PortError> { Ok(()) }
+    async fn disconnect(&self) -> Result<(), PortError> { Ok(()) }
+    fn is_connected(&self) -> bool { true }
+    async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError> {
+        Ok(vec![
+            vendor_device("24:5a:4c:00:00:01", "core-sw"),
+            vendor_device("24:5a:4c:00:00:02", "edge-sw"),
+        ])
+    }
+    async fn get_device(&self, vendor_id: &str) -> Result<VendorDevice, PortError> {
+        Ok(vendor_device(vendor_id, "core-sw"))
+    }
+    async fn adopt_device(&self, _vendor_id: &str) -> Result<(), PortError> { Ok(()) }
+    async fn apply_config(&self, _vendor_id: &str, _config: DeviceConfigRequest) -> Result<(), PortError> { Ok(()) }
+    async fn apply_raw_config(&self, _vendor_id: &str, _config: VendorConfig) -> Result<(), PortError> { Ok(()) }
+    async fn restart_device(&self, _vendor_id: &str) -> Result<(), PortError> { Ok(()) }
+    async fn get_device_stats(&self, _vendor_id: &str) -> Result<DeviceStats, PortError> {
+        Err(PortError::NotSupported("no stats".to_string()))
+    }
+}
+
+/// Event store keeping events in memory and broadcasting each append
+struct MockEventStore {
+    events: Mutex<Vec<NetworkEvent>>,
+    live: broadcast::Sender<NetworkEvent>,
+}
+
+impl Default for MockEventStore {
+    fn default() -> Self {
+        Self {
+            events: Mutex::new(Vec::new()),
+            live: broadcast::channel(64).0,
+        }
+    }
+}
+
+#[async_trait]
+impl EventStorePort for MockEventStore {
+    async fn append(&self, events: Vec<NetworkEvent>) -> Result<(), PortError> {
+        for event in &events {
+            let _ = self.live.send(event.clone());
+        }
+        self.events.lock().unwrap().extend(events);
+        Ok(())
+    }
+
+    async fn load_events(&self, aggregate_id: &str) -> Result<Vec<NetworkEvent>, PortError> {
+        Ok(self.events.lock().unwrap().iter().filter(|e| e.aggregate_id() == aggregate_id).cloned().collect())
+    }
+
+    async fn subscribe(&self, subject: &str) -> Result<EventSubscription, PortError> {
+        Ok(EventSubscription::with_subject(subject))
+    }
+
+    async fn subscribe_stream(&self, _subject: &str) -> Result<EventStream, PortError> {
+        let receiver = self.live.subscribe();
+        Ok(Box::pin(futures::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.ok().map(|event| (Ok(event), receiver))
+        })))
+    }
+}
+
+/// Serve the API on a local port and return its base URL
+async fn serve() -> String {
+    let service = NetworkService::builder()
+        .vendor_adapter(MockVendorAdapter)
+        .event_store(MockEventStore::default())
+        .build()
+        .unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("Failed to bind API");
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(api::serve(listener, Arc::new(service)));
+    base_url
+}
+
+async fn discover(http: &reqwest::Client, base_url: &str) -> Vec<DeviceId> {
+    let response = http.post(format!("{}/devices/discover", base_url)).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    response.json::<DiscoverResponse>().await.unwrap().device_ids
+}
+
+async fn error_status(response: reqwest::Response) -> (u16, String) {
+    let status = response.status().as_u16();
+    (status, response.json::<ErrorBody>().await.expect("Error body is not JSON").error)
+}
+
+/// Discovered devices are listed and fetched by id as domain aggregates
+#[tokio::test]
+async fn test_discover_then_list_and_get_devices() {
+    let base_url = serve().await;
+    let http = reqwest::Client::new();
+
+    let device_ids = discover(&http, &base_url).await;
+    assert_eq!(device_ids.len(), 2);
+
+    let devices: Vec<Value> = http.get(format!("{}/devices", base_url)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(devices.len(), 2);
+    assert!(devices.iter().all(|d| d["state"] == "Discovered"));
+
+    let device: Value = http.get(format!("{}/devices/{}", base_url, device_ids[0]))
+        .send().await.unwrap()
+        .json().await.unwrap();
+    assert_eq!(device["id"], device_ids[0].to_string());
+}
+
+/// Adopting returns the adopted device; adopting it again is a conflict
+#[tokio::test]
+async fn test_adopt_device_and_conflict_on_repeat() {
+    let base_url = serve().await;
+    let http = reqwest::Client::new();
+    let device_id = discover(&http, &base_url).await[0];
+    let adopt_url = format!("{}/devices/{}/adopt", base_url, device_id);
+
+    let response = http.post(&adopt_url).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let device: Value = response.json().await.unwrap();
+    assert_eq!(device["state"], "Adopting");
+
+    let (status, error) = error_status(http.post(&adopt_url).send().await.unwrap()).await;
+    assert_eq!(status, 409);
+    assert!(error.contains("Invalid state transition"), "{}", error);
+
+    let devices: Vec<Value> = http.get(format!("{}/devices?state=Adopting", base_url))
+        .send().await.unwrap()
+        .json().await.unwrap();
+    assert_eq!(devices.len(), 1);
+}
+
+/// Malformed ids and filters are rejected; unknown devices are not found
+#[tokio::test]
+async fn test_invalid_requests_are_rejected() {
+    let base_url = serve().await;
+    let http = reqwest::Client::new();
+
+    let (status, error) = error_status(http.get(format!("{}/devices/not-a-uuid", base_url)).send().await.unwrap()).await;
+    assert_eq!(status, 400);
+    assert!(error.contains("not-a-uuid"));
+
+    let unknown = http.post(format!("{}/devices/{}/adopt", base_url, DeviceId::new())).send().await.unwrap();
+    assert_eq!(error_status(unknown).await.0, 404);
+
+    let bad_state = http.get(format!("{}/devices?state=Bogus", base_url)).send().await.unwrap();
+    assert_eq!(error_status(bad_state).await.0, 400);
+
+    let bad_subject = http.get(format!("{}/events/stream?subject=network..device", base_url)).send().await.unwrap();
+    assert_eq!(error_status(bad_subject).await.0, 400);
+}
+
+/// Events appended after the stream opens arrive as server-sent events
+#[tokio::test]
+async fn test_event_stream_delivers_new_events() {
+    let base_url = serve().await;
+    let http = reqwest::Client::new();
+    let device_id = discover(&http, &base_url).await[0];
+
+    let mut stream = http.get(format!("{}/events/stream", base_url)).send().await.unwrap();
+    assert_eq!(stream.status(), 200);
+    assert_eq!(stream.headers()["content-type"], "text/event-stream");
+
+    http.post(format!("{}/devices/{}/adopt", base_url, device_id)).send().await.unwrap();
+
+    let mut received = String::new();
+    while !received.contains("\n\n") {
+        let chunk = tokio::time::timeout(Duration::from_secs(5), stream.chunk())
+            .await
+            .expect("No event within 5s")
+            .unwrap()
+            .expect("Stream ended");
+        received.push_str(&String::from_utf8_lossy(&chunk));
+    }
+
+    assert!(received.contains("event: DeviceAdopting"), "{}", received);
+    assert!(received.contains(&device_id.to_string()), "{}", received);
+}
@@ -0,0 +1,257 @@
+//! Integration tests for the Arista EOS adapter
+//!
+//! These tests run `AristaAdapter` against a minimal in-process eAPI server
+//! that answers `runCmds` from canned per-command outputs and records every
+//! command batch, so no switch is required.
+//!
+//! Run with: cargo test --test arista_integration
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use cim_network::adapters::arista::AristaAdapter;
+use cim_network::domain::ports::{DeviceConfigRequest, DeviceControlPort, PortError, VendorConfig};
+use cim_network::domain::value_objects::{LinkSpeed, MacAddress, PortId, VlanConfig};
+use common::{respond, Request, Response};
+
+/// A `runCmds` call as seen by the mock switch
+#[derive(Debug, Clone, PartialEq)]
+struct Recorded {
+    path: String,
+    authorized: bool,
+    cmds: Vec<String>,
+}
+
+/// Canned eAPI: command -> JSON output
+///
+/// Configuration commands answer `{}` unless listed in `rejected`; any other
+/// unknown command fails the batch like EOS does.
+#[derive(Default)]
+struct MockEapi {
+    outputs: HashMap<String, serde_json::Value>,
+    rejected: Vec<String>,
+    batches: Mutex<Vec<Recorded>>,
+}
+
+impl MockEapi {
+    fn output(mut self, cmd: &str, output: serde_json::Value) -> Self {
+        self.outputs.insert(cmd.to_string(), output);
+        self
+    }
+
+    fn reject(mut self, cmd: &str) -> Self {
+        self.rejected.push(cmd.to_string());
+        self
+    }
+
+    /// JSON-RPC response for a batch, stopping at the first failing command
+    fn run(&self, id: &serde_json::Value, cmds: &[String]) -> serde_json::Value {
+        let mut results = Vec::new();
+        for (index, cmd) in cmds.iter().enumerate() {
+            let output = match self.outputs.get(cmd) {
+                Some(output) => Some(output.clone()),
+                None if cmd.starts_with("show ") || self.rejected.contains(cmd) => None,
+                None => Some(serde_json::json!({})),
+            };
+            match output {
+                Some(output) => results.push(output),
+                None => {
+                    results.push(serde_json::json!({ "errors": [format!("Invalid input: '{}'", cmd.trim())] }));
+                    return serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {
+                            "code": 1002,
+                            "message": format!("CLI command {} of {} '{}' failed: invalid command", index + 1, cmds.len(), cmd),
+                            "data": results,
+                        },
+                    });
+                }
+            }
+        }
+        serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": results })
+    }
+
+    /// Serve the canned outputs on a local port and return its base URL
+    async fn serve(self) -> (String, Arc<Self>) {
+        let mock = Arc::new(self);
+        let server = mock.clone();
+        let base_url = common::serve(move |request| std::future::ready(server.answer(request))).await;
+        (base_url, mock)
+    }
+
+    /// Record a `runCmds` batch and answer it
+    fn answer(&self, request: Request) -> Response {
+        let body = request.json().unwrap_or_default();
+        let cmds: Vec<String> = serde_json::from_value(body["params"]["cmds"].clone()).unwrap_or_default();
+        self.batches.lock().unwrap().push(Recorded {
+            path: request.path.clone(),
+            authorized: request.has_basic_auth(),
+            cmds: cmds.clone(),
+        });
+
+        if request.path == "/command-api" && body["method"] == "runCmds" {
+            respond(200, self.run(&body["id"], &cmds).to_string())
+        } else {
+            respond(404, "Not Found")
+        }
+    }
+}
+
+fn switch() -> MockEapi {
+    MockEapi::default()
+        .output("show version", serde_json::json!({
+            "modelName": "DCS-7050SX3-48YC8",
+            "version": "4.28.3M",
+            "serialNumber": "JPE12345678",
+            "systemMacAddress": "00:1c:73:aa:bb:cc",
+            "uptime": 93784.52,
+        }))
+        .output("show hostname", serde_json::json!({ "hostname": "leaf1", "fqdn": "leaf1.dc1.example.net" }))
+        .output("show lldp neighbors", serde_json::json!({
+            "lldpNeighbors": [
+                { "port": "Ethernet49/1", "neighborDevice": "spine1", "neighborPort": "Ethernet1", "ttl": 120 },
+                { "port": "Ethernet50/1", "neighborDevice": "spine2", "neighborPort": "Ethernet1", "ttl": 120 },
+            ],
+        }))
+        .output("show processes top once", serde_json::json!({
+            "cpuInfo": { "%Cpu(s)": { "idle": 88.0, "user": 9.0, "system": 3.0 } },
+            "memInfo": { "physicalMem": { "total": 16000000, "free": 4000000, "used": 12000000 } },
+        }))
+        .output("show interfaces status", serde_json::json!({
+            "interfaceStatuses": {
+                "Ethernet10": { "linkStatus": "notconnect", "bandwidth": 0 },
+                "Ethernet2": { "linkStatus": "connected", "bandwidth": 10000000000u64 },
+                "Ethernet49/1": { "linkStatus": "connected", "bandwidth": 100000000000u64 },
+            },
+        }))
+        .output("show interfaces counters", serde_json::json!({
+            "interfaces": {
+                "Ethernet2": { "inOctets": 123456789, "outOctets": 98765432 },
+                "Ethernet49/1": { "inOctets": 5000, "outOctets": 6000 },
+                "Management1": { "inOctets": 1, "outOctets": 2 },
+            },
+        }))
+        .output("show interfaces counters errors", serde_json::json!({
+            "interfaceErrorCounters": {
+                "Ethernet2": { "inErrors": 3, "outErrors": 1, "fcsErrors": 3 },
+            },
+        }))
+}
+
+#[tokio::test]
+async fn test_list_devices_reads_version_and_lldp() {
+    let (base_url, eapi) = switch().serve().await;
+    let adapter = AristaAdapter::new(&[&base_url], "admin", "secret").unwrap();
+
+    let devices = adapter.list_devices().await.expect("Failed to list devices");
+
+    assert_eq!(devices.len(), 1);
+    let device = &devices[0];
+    assert_eq!(device.vendor_id, base_url);
+    assert_eq!(device.name, "leaf1");
+    assert_eq!(device.model, "DCS-7050SX3-48YC8");
+    assert_eq!(device.mac, MacAddress::parse("00:1c:73:aa:bb:cc").unwrap());
+    assert_eq!(device.ip_address, Some("127.0.0.1".parse().unwrap()));
+    assert_eq!(device.properties["eos_version"], "4.28.3M");
+    assert_eq!(device.properties["lldp_neighbors"][1]["neighborDevice"], "spine2");
+
+    // Inventory is one batch per switch
+    let batches = eapi.batches.lock().unwrap();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].cmds, ["show version", "show hostname", "show lldp neighbors"]);
+}
+
+#[tokio::test]
+async fn test_get_device_stats() {
+    let (base_url, eapi) = switch().serve().await;
+    let adapter = AristaAdapter::new(&[&base_url], "admin", "secret").unwrap();
+
+    let stats = adapter.get_device_stats(&base_url).await.expect("Failed to get stats");
+
+    assert_eq!(stats.uptime_seconds, 93784);
+    assert_eq!(stats.cpu_percent, Some(12.0));
+    assert_eq!(stats.memory_percent, Some(75.0));
+
+    let ports: Vec<&str> = stats.port_stats.iter().map(|p| p.port_id.name.as_str()).collect();
+    assert_eq!(ports, ["Ethernet2", "Ethernet10", "Ethernet49/1"]);
+
+    let eth2 = &stats.port_stats[0];
+    assert_eq!(eth2.port_id, PortId::new("Ethernet2"));
+    assert!(eth2.link_up);
+    assert_eq!(eth2.speed, Some(LinkSpeed::Gbps10));
+    assert_eq!(eth2.rx_bytes, 123456789);
+    assert_eq!(eth2.tx_bytes, 98765432);
+    assert_eq!((eth2.rx_errors, eth2.tx_errors), (3, 1));
+
+    // A port without counters reports zeroes rather than failing the poll
+    let eth10 = &stats.port_stats[1];
+    assert!(!eth10.link_up);
+    assert_eq!(eth10.speed, None);
+    assert_eq!(eth10.rx_bytes, 0);
+
+    assert_eq!(stats.port_stats[2].speed, Some(LinkSpeed::Gbps100));
+    assert!(eapi.batches.lock().unwrap().iter().all(|b| b.authorized && b.path == "/command-api"));
+}
+
+#[tokio::test]
+async fn test_apply_config_runs_one_configure_session() {
+    let (base_url, eapi) = switch().serve().await;
+    let adapter = AristaAdapter::new(&[&base_url], "admin", "secret").unwrap();
+
+    let request = DeviceConfigRequest::default()
+        .with_hostname("leaf1")
+        .with_vlan(VlanConfig::new(10, "users").unwrap())
+        .with_ntp_server("0.pool.ntp.org");
+    adapter.apply_config(&base_url, request).await.expect("Failed to apply config");
+
+    let batches = eapi.batches.lock().unwrap();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].cmds, [
+        "enable",
+        "configure",
+        "hostname leaf1",
+        "vlan 10",
+        " name users",
+        "ntp server 0.pool.ntp.org",
+        "end",
+    ]);
+}
+
+#[tokio::test]
+async fn test_apply_config_reports_rejected_line() {
+    let (base_url, _eapi) = switch().reject("vlan 5000").serve().await;
+    let adapter = AristaAdapter::new(&[&base_url], "admin", "secret").unwrap();
+
+    let result = adapter
+        .apply_raw_config(&base_url, VendorConfig {
+            config_type: "eos".to_string(),
+            payload: serde_json::json!({ "config": "hostname leaf1\nvlan 5000\n name never" }),
+        })
+        .await;
+
+    assert!(matches!(result, Err(PortError::VendorError(msg)) if msg.contains("Invalid input: 'vlan 5000'")));
+}
+
+#[tokio::test]
+async fn test_restart_device_reloads_now() {
+    let (base_url, eapi) = switch().serve().await;
+    let adapter = AristaAdapter::new(&[&base_url], "admin", "secret").unwrap();
+
+    adapter.restart_device(&base_url).await.expect("Failed to reload");
+
+    let batches = eapi.batches.lock().unwrap();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].cmds, ["enable", "reload now"]);
+}
+
+#[tokio::test]
+async fn test_unknown_switch_is_rejected() {
+    let adapter = AristaAdapter::new(&["https://192.0.2.11"], "admin", "secret").unwrap();
+
+    let result = adapter.restart_device("https://192.0.2.99").await;
+
+    assert!(matches!(result, Err(PortError::VendorError(msg)) if msg.contains("not configured")));
+}
@@ -0,0 +1,319 @@
+//! Integration tests for the Cisco IOS adapter
+//!
+//! These tests drive `CiscoIosAdapter` end to end against a mocked IOS device
+//! that answers CLI commands with canned output and records everything it is
+//! sent, so no SSH server is required.
+//!
+//! Run with: cargo test --test cisco_integration
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use cim_network::adapters::cisco::{CiscoError, CiscoIosAdapter, CliTransport};
+use cim_network::domain::aggregates::{AggregateError, NetworkDeviceAggregate};
+use cim_network::domain::events::NetworkEvent;
+use cim_network::domain::functor::{DomainObject, VendorExtension};
+use cim_network::domain::ports::{
+    ConfigApplyOutcome, DeviceConfigRequest, DeviceControlPort, PortError, VendorConfig,
+};
+use cim_network::domain::value_objects::{
+    DeviceId, DeviceType, InterfaceConfig, LinkSpeed, MacAddress, PortId, VlanConfig,
+};
+
+/// Mocked IOS device: canned exec output plus a log of commands and shell sessions
+///
+/// Shell sessions are answered by the response registered for their first
+/// line, falling back to `shell_response`.
+#[derive(Default)]
+struct MockIosDevice {
+    exec_responses: HashMap<String, String>,
+    exec_log: Mutex<Vec<String>>,
+    shell_responses: HashMap<String, String>,
+    shell_response: String,
+    shell_sessions: Mutex<Vec<(String, Vec<String>)>>,
+}
+
+impl MockIosDevice {
+    fn with_exec(mut self, command: &str, output: &str) -> Self {
+        self.exec_responses.insert(command.to_string(), output.to_string());
+        self
+    }
+
+    fn with_shell_response(mut self, output: &str) -> Self {
+        self.shell_response = output.to_string();
+        self
+    }
+
+    fn with_shell_session(mut self, first_line: &str, output: &str) -> Self {
+        self.shell_responses.insert(first_line.to_string(), output.to_string());
+        self
+    }
+}
+
+#[async_trait]
+impl CliTransport for MockIosDevice {
+    async fn exec(&self, _host: &str, command: &str) -> Result<String, CiscoError> {
+        self.exec_log.lock().unwrap().push(command.to_string());
+        Ok(self.exec_responses
+            .get(command)
+            .cloned()
+            .unwrap_or_else(|| "% Invalid input detected at '^' marker.".to_string()))
+    }
+
+    async fn shell(&self, host: &str, lines: &[String]) -> Result<String, CiscoError> {
+        self.shell_sessions.lock().unwrap().push((host.to_string(), lines.to_vec()));
+        Ok(lines.first()
+            .and_then(|first| self.shell_responses.get(first))
+            .unwrap_or(&self.shell_response)
+            .clone())
+    }
+}
+
+const SHOW_VERSION: &str = "\
+Cisco IOS Software, C2960X Software (C2960X-UNIVERSALK9-M), Version 15.2(7)E4, RELEASE SOFTWARE (fc2)
+core-sw1 uptime is 2 days, 3 hours, 10 minutes
+";
+
+const SHOW_INTERFACES: &str = "\
+GigabitEthernet0/1 is up, line protocol is up (connected)
+  MTU 1500 bytes, BW 1000000 Kbit/sec, DLY 10 usec,
+     1000 packets input, 64000 bytes, 0 no buffer
+     1 input errors, 0 CRC, 0 frame, 0 overrun, 0 ignored
+     2000 packets output, 128000 bytes, 0 underruns
+     0 output errors, 0 collisions, 0 interface resets
+TenGigabitEthernet1/1 is administratively down, line protocol is down (disabled)
+  MTU 1500 bytes, BW 10000000 Kbit/sec, DLY 10 usec,
+     0 packets input, 0 bytes, 0 no buffer
+     0 input errors, 0 CRC, 0 frame, 0 overrun, 0 ignored
+     0 packets output, 0 bytes, 0 underruns
+     0 output errors, 0 collisions, 0 interface resets
+";
+
+fn configured_device() -> NetworkDeviceAggregate {
+    let device_id = DeviceId::new();
+    NetworkDeviceAggregate::from_events(vec![
+        NetworkEvent::DeviceDiscovered {
+            device_id,
+            mac: MacAddress::parse("00:11:22:33:44:55").unwrap(),
+            device_type: DeviceType::Switch,
+            ip_address: Some("10.0.0.2".parse().unwrap()),
+        },
+        NetworkEvent::DeviceRenamed {
+            device_id,
+            old_name: "Device".to_string(),
+            new_name: "access-sw1".to_string(),
+        },
+        NetworkEvent::DeviceConfigured {
+            device_id,
+            interfaces: vec![InterfaceConfig {
+                name: "GigabitEthernet0/5".to_string(),
+                ip_address: None,
+                prefix_len: None,
+                vlan_id: Some(20),
+                enabled: true,
+                mtu: None,
+                speed: None,
+            }],
+            vlans: vec![VlanConfig::new(20, "voice").unwrap()],
+        },
+    ])
+    .unwrap()
+}
+
+/// A config generated through the Kan extension is pushed via `configure terminal`
+#[tokio::test]
+async fn test_generated_config_is_applied() {
+    let device = Arc::new(MockIosDevice::default().with_shell_response("access-sw1(config)#end\naccess-sw1#"));
+    let adapter = CiscoIosAdapter::with_transport("10.0.0.1", device.clone());
+
+    let aggregate = configured_device();
+    let representation = VendorExtension::extend(&adapter, &DomainObject::Device(Box::new(aggregate)))
+        .expect("Failed to extend device to Cisco");
+    assert_eq!(representation.vendor, "cisco");
+
+    let config = VendorConfig {
+        config_type: "cli".to_string(),
+        payload: representation.payload,
+    };
+    adapter.apply_raw_config("10.0.0.2", config).await.expect("Failed to apply config");
+
+    let sessions = device.shell_sessions.lock().unwrap();
+    assert_eq!(sessions.len(), 1);
+    let (host, lines) = &sessions[0];
+    assert_eq!(host, "10.0.0.2");
+    assert_eq!(lines, &vec![
+        "configure terminal".to_string(),
+        "hostname access-sw1".to_string(),
+        "vlan 20".to_string(),
+        " name voice".to_string(),
+        "interface GigabitEthernet0/5".to_string(),
+        " switchport mode access".to_string(),
+        " switchport access vlan 20".to_string(),
+        " no shutdown".to_string(),
+        "end".to_string(),
+    ]);
+}
+
+/// A typed config request is translated to IOS lines inside one session
+#[tokio::test]
+async fn test_typed_config_is_applied() {
+    let device = Arc::new(MockIosDevice::default().with_shell_response("core-sw1(config)#end\ncore-sw1#"));
+    let adapter = CiscoIosAdapter::with_transport("10.0.0.1", device.clone());
+
+    let request = DeviceConfigRequest::default()
+        .with_hostname("core-sw1")
+        .with_vlan(VlanConfig::new(30, "cameras").unwrap())
+        .with_ntp_server("10.0.0.10")
+        .with_dns_server("10.0.0.53".parse().unwrap())
+        .with_dns_server("10.0.0.54".parse().unwrap());
+    adapter.apply_config("10.0.0.2", request).await.expect("Failed to apply config");
+
+    let sessions = device.shell_sessions.lock().unwrap();
+    assert_eq!(sessions[0].1, vec![
+        "configure terminal".to_string(),
+        "hostname core-sw1".to_string(),
+        "vlan 30".to_string(),
+        " name cameras".to_string(),
+        "ntp server 10.0.0.10".to_string(),
+        "ip name-server 10.0.0.53 10.0.0.54".to_string(),
+        "end".to_string(),
+    ]);
+}
+
+/// Interface MTUs are rendered, and out-of-range ones never reach the device
+#[tokio::test]
+async fn test_interface_mtu_is_validated_before_apply() {
+    let device = Arc::new(MockIosDevice::default().with_shell_response("sw(config)#end\nsw#"));
+    let adapter = CiscoIosAdapter::with_transport("10.0.0.1", device.clone());
+    let uplink = |mtu| InterfaceConfig {
+        name: "TenGigabitEthernet1/1/1".to_string(),
+        ip_address: None,
+        prefix_len: None,
+        vlan_id: None,
+        enabled: true,
+        mtu: Some(mtu),
+        speed: None,
+    };
+
+    let result = adapter
+        .apply_config("10.0.0.2", DeviceConfigRequest::default().with_interface(uplink(20000)))
+        .await;
+    assert!(matches!(result, Err(PortError::Aggregate(AggregateError::InvalidMtu { mtu: 20000, .. }))));
+    assert!(device.shell_sessions.lock().unwrap().is_empty());
+
+    adapter
+        .apply_config("10.0.0.2", DeviceConfigRequest::default().with_interface(uplink(9000)))
+        .await
+        .unwrap();
+    let sessions = device.shell_sessions.lock().unwrap();
+    assert!(sessions[0].1.contains(&" mtu 9000".to_string()));
+}
+
+/// IOS `%` errors during configuration surface as vendor errors
+#[tokio::test]
+async fn test_rejected_config_is_reported() {
+    let device = Arc::new(MockIosDevice::default().with_shell_response(
+        "sw(config)#vlan 9999\n                 ^\n% Invalid input detected at '^' marker.\n",
+    ));
+    let adapter = CiscoIosAdapter::with_transport("10.0.0.1", device);
+
+    let config = VendorConfig {
+        config_type: "cli".to_string(),
+        payload: serde_json::json!(["vlan 9999"]),
+    };
+    let result = adapter.apply_raw_config("10.0.0.2", config).await;
+
+    assert!(matches!(result, Err(PortError::VendorError(msg)) if msg.contains("Invalid input")));
+}
+
+/// A rejected stanza rolls the device back with `configure replace`
+#[tokio::test]
+async fn test_rejected_config_is_rolled_back() {
+    let device = Arc::new(
+        MockIosDevice::default()
+            .with_shell_session(
+                "copy running-config flash:cim-rollback.cfg",
+                "Destination filename [cim-rollback.cfg]? \n4096 bytes copied in 0.512 secs\n",
+            )
+            .with_shell_response("sw(config)#vlan 9999\n                 ^\n% Invalid input detected at '^' marker.\n")
+            .with_exec("configure replace flash:cim-rollback.cfg force", "Rollback Done\n"),
+    );
+    let adapter = CiscoIosAdapter::with_transport("10.0.0.1", device.clone());
+
+    let request = DeviceConfigRequest::default().with_hostname("core-sw1");
+    let outcome = adapter.apply_config_transactional("10.0.0.2", request).await.unwrap();
+
+    assert!(matches!(outcome, ConfigApplyOutcome::RolledBack { reason } if reason.contains("Invalid input")));
+    let sessions = device.shell_sessions.lock().unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions[0].1[0], "copy running-config flash:cim-rollback.cfg");
+    assert_eq!(sessions[1].1[0], "configure terminal");
+    assert_eq!(*device.exec_log.lock().unwrap(), ["configure replace flash:cim-rollback.cfg force"]);
+}
+
+/// Stats are parsed from `show version`, `show processes` and `show interfaces`
+#[tokio::test]
+async fn test_device_stats_parse() {
+    let device = Arc::new(
+        MockIosDevice::default()
+            .with_exec("show version", SHOW_VERSION)
+            .with_exec(
+                "show processes cpu | include CPU utilization",
+                "CPU utilization for five seconds: 12%/1%; one minute: 9%; five minutes: 8%",
+            )
+            .with_exec(
+                "show processes memory | include Processor Pool",
+                "Processor Pool Total:  400000 Used:  100000 Free:  300000",
+            )
+            .with_exec("show interfaces", SHOW_INTERFACES),
+    );
+    let adapter = CiscoIosAdapter::with_transport("10.0.0.1", device);
+
+    let stats = adapter.get_device_stats("10.0.0.1").await.expect("Failed to get stats");
+
+    assert_eq!(stats.uptime_seconds, 2 * 86400 + 3 * 3600 + 10 * 60);
+    assert_eq!(stats.cpu_percent, Some(9.0));
+    assert_eq!(stats.memory_percent, Some(25.0));
+    assert_eq!(stats.port_stats.len(), 2);
+
+    let gi = &stats.port_stats[0];
+    assert_eq!(gi.port_id, PortId::new("GigabitEthernet0/1"));
+    assert!(gi.link_up);
+    assert_eq!(gi.speed, Some(LinkSpeed::Gbps1));
+    assert_eq!(gi.rx_bytes, 64000);
+    assert_eq!(gi.tx_bytes, 128000);
+    assert_eq!(gi.rx_errors, 1);
+
+    let te = &stats.port_stats[1];
+    assert!(!te.link_up);
+    assert_eq!(te.speed, Some(LinkSpeed::Gbps10));
+}
+
+/// CDP neighbors are listed with MACs resolved from the ARP table
+#[tokio::test]
+async fn test_list_devices_via_cdp() {
+    let device = Arc::new(
+        MockIosDevice::default()
+            .with_exec("show version", SHOW_VERSION)
+            .with_exec(
+                "show cdp neighbors detail",
+                "Device ID: access-sw1\n  IP address: 10.0.0.2\nPlatform: cisco WS-C2960X-48FPD-L,  Capabilities: Switch\n",
+            )
+            .with_exec(
+                "show ip arp",
+                "Internet  10.0.0.2   5   0011.2233.4455  ARPA   Vlan1\n",
+            ),
+    );
+    let adapter = CiscoIosAdapter::with_transport("10.0.0.1", device);
+
+    adapter.connect().await.expect("Failed to connect");
+    assert!(adapter.is_connected());
+
+    let devices = adapter.list_devices().await.expect("Failed to list devices");
+    assert_eq!(devices.len(), 1);
+    assert_eq!(devices[0].vendor_id, "10.0.0.2");
+    assert_eq!(devices[0].name, "access-sw1");
+    assert_eq!(devices[0].model, "WS-C2960X-48FPD-L");
+    assert_eq!(devices[0].mac, MacAddress::parse("00:11:22:33:44:55").unwrap());
+}
@@ -0,0 +1,257 @@
+//! Shared helpers for the adapter integration tests
+//!
+//! `serve` runs a minimal in-process HTTP/1.1 server that hands every
+//! request to a handler closure, so adapters talking HTTP can be tested
+//! against canned responses without the real device or service.
+//! `CannedApi` is such a handler for REST APIs: fixed responses per route,
+//! with every request recorded.
+
+// Each test binary uses a different subset of these helpers
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// A request as received by the mock server
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    /// Path including any query string
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Value of a header, matched case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether the request carries HTTP basic credentials
+    pub fn has_basic_auth(&self) -> bool {
+        self.header("authorization")
+            .is_some_and(|value| value.to_ascii_lowercase().starts_with("basic "))
+    }
+
+    /// The body parsed as JSON, if it is JSON
+    pub fn json(&self) -> Option<serde_json::Value> {
+        serde_json::from_slice(&self.body).ok()
+    }
+}
+
+/// A mock response: (status, extra headers, body)
+///
+/// Every response is sent as `application/json` with `Connection: close`.
+pub type Response = (u16, Vec<(String, String)>, String);
+
+/// A response with no extra headers
+pub fn respond(status: u16, body: impl Into<String>) -> Response {
+    (status, Vec::new(), body.into())
+}
+
+/// Bind a local port, returning the listener and its base URL
+///
+/// For mocks that need their own URL before serving, e.g. for absolute
+/// links in responses; otherwise use `serve`.
+pub async fn bind() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("Failed to bind mock server");
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    (listener, base_url)
+}
+
+/// Serve `handler` on a local port and return its base URL
+pub async fn serve<H, F>(handler: H) -> String
+where
+    H: Fn(Request) -> F + Send + Sync + 'static,
+    F: Future<Output = Response> + Send + 'static,
+{
+    let (listener, base_url) = bind().await;
+    serve_on(listener, handler);
+    base_url
+}
+
+/// Serve `handler` on an already bound listener
+///
+/// Each connection carries one request; synchronous handlers can wrap their
+/// response in `std::future::ready`.
+pub fn serve_on<H, F>(listener: TcpListener, handler: H)
+where
+    H: Fn(Request) -> F + Send + Sync + 'static,
+    F: Future<Output = Response> + Send + 'static,
+{
+    let handler = Arc::new(handler);
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                let header_end = loop {
+                    if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break pos + 4;
+                    }
+                    match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                };
+
+                let head = String::from_utf8_lossy(&request[..header_end]).to_string();
+                let mut lines = head.lines();
+                let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+                let method = request_line.next().unwrap_or_default().to_string();
+                let path = request_line.next().unwrap_or_default().to_string();
+                let headers: Vec<(String, String)> = lines
+                    .filter_map(|l| l.split_once(':'))
+                    .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+                    .collect();
+
+                let content_length = headers
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                    .and_then(|(_, value)| value.parse::<usize>().ok())
+                    .unwrap_or(0);
+                while request.len() < header_end + content_length {
+                    match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+
+                let body = request[header_end..header_end + content_length].to_vec();
+                let (status, extra_headers, body) = handler(Request { method, path, headers, body }).await;
+                let extra_headers: String = extra_headers
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}\r\n", name, value))
+                    .collect();
+                let response = format!(
+                    "HTTP/1.1 {} MOCK\r\nContent-Type: application/json\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    extra_headers,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            });
+        }
+    });
+}
+
+/// A request as recorded by `CannedApi`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recorded {
+    pub method: String,
+    /// Path including any query string
+    pub path: String,
+    /// Whether the request carried HTTP basic credentials
+    pub authorized: bool,
+    /// The body, if it was JSON
+    pub body: Option<serde_json::Value>,
+}
+
+/// Canned REST API: (method, path with query) -> (status, body)
+///
+/// Requests to routes without a response get `404` with the API's own
+/// not-found body.
+pub struct CannedApi {
+    routes: HashMap<(String, String), (u16, String)>,
+    not_found: String,
+    /// Requests still to be answered `429 Too Many Requests`, per route, and the body to send
+    throttled: Mutex<HashMap<(String, String), (usize, String)>>,
+    requests: Mutex<Vec<Recorded>>,
+}
+
+impl CannedApi {
+    /// An API without routes, answering unknown routes `404` with `not_found`
+    pub fn new(not_found: &str) -> Self {
+        Self {
+            routes: HashMap::new(),
+            not_found: not_found.to_string(),
+            throttled: Mutex::new(HashMap::new()),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn route(mut self, method: &str, path: &str, status: u16, body: &str) -> Self {
+        self.routes.insert((method.to_string(), path.to_string()), (status, body.to_string()));
+        self
+    }
+
+    /// Answer the first `times` requests to a route `429 Too Many Requests` with `body`
+    pub fn throttle(self, method: &str, path: &str, times: usize, body: &str) -> Self {
+        self.throttled
+            .lock()
+            .unwrap()
+            .insert((method.to_string(), path.to_string()), (times, body.to_string()));
+        self
+    }
+
+    /// Serve the canned routes on a local port and return its base URL
+    ///
+    /// `{base_url}` in a response body is replaced with the server's URL, for
+    /// absolute links such as pagination `next`.
+    pub async fn serve(mut self) -> (String, Arc<Self>) {
+        let (listener, base_url) = bind().await;
+        for (_, body) in self.routes.values_mut() {
+            *body = body.replace("{base_url}", &base_url);
+        }
+        let api = Arc::new(self);
+        let server = api.clone();
+        serve_on(listener, move |request| std::future::ready(server.answer(request)));
+        (base_url, api)
+    }
+
+    /// Every request so far, in order
+    pub fn requests(&self) -> Vec<Recorded> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// (method, path) of every request so far, in order
+    pub fn calls(&self) -> Vec<(String, String)> {
+        self.requests()
+            .into_iter()
+            .map(|request| (request.method, request.path))
+            .collect()
+    }
+
+    /// JSON bodies of the requests that had one, keyed by (method, path)
+    pub fn bodies(&self) -> Vec<((String, String), serde_json::Value)> {
+        self.requests()
+            .into_iter()
+            .filter_map(|request| Some(((request.method, request.path), request.body?)))
+            .collect()
+    }
+
+    /// Record a request and answer it from the canned routes
+    fn answer(&self, request: Request) -> Response {
+        self.requests.lock().unwrap().push(Recorded {
+            method: request.method.clone(),
+            path: request.path.clone(),
+            authorized: request.has_basic_auth(),
+            body: request.json(),
+        });
+
+        let key = (request.method, request.path);
+        if let Some((remaining, body)) = self.throttled.lock().unwrap().get_mut(&key) {
+            if *remaining > 0 {
+                *remaining -= 1;
+                return respond(429, body.clone());
+            }
+        }
+        let (status, body) = self
+            .routes
+            .get(&key)
+            .cloned()
+            .unwrap_or_else(|| (404, self.not_found.clone()));
+        respond(status, body)
+    }
+}
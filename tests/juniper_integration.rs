@@ -0,0 +1,243 @@
+//! Integration tests for the Juniper Junos adapter
+//!
+//! These tests drive `JuniperAdapter` against a mocked NETCONF server that
+//! answers RPCs with captured Junos replies and records every session, so no
+//! device is required.
+//!
+//! Run with: cargo test --test juniper_integration
+
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+
+use cim_network::adapters::juniper::{reply_error, JuniperAdapter, JuniperError, NetconfTransport};
+use cim_network::domain::ports::{ConfigApplyOutcome, DeviceConfigRequest, DeviceControlPort, PortError};
+use cim_network::domain::value_objects::{LinkSpeed, MacAddress, PortId, VlanConfig};
+
+/// Mocked Junos device: replies keyed by the RPC's opening tag
+///
+/// RPCs without a canned reply answer `<ok/>`, except `<get-…>` RPCs, which
+/// fail like an unknown RPC does on Junos. Sessions stop at the first error
+/// reply, as `NetconfSshTransport` does.
+#[derive(Default)]
+struct MockJunos {
+    replies: Vec<(String, String)>,
+    sessions: Mutex<Vec<Vec<String>>>,
+}
+
+impl MockJunos {
+    fn reply(mut self, rpc_prefix: &str, reply: &str) -> Self {
+        self.replies.push((rpc_prefix.to_string(), reply.to_string()));
+        self
+    }
+
+    fn answer(&self, rpc: &str) -> String {
+        match self.replies.iter().find(|(prefix, _)| rpc.starts_with(prefix.as_str())) {
+            Some((_, reply)) => reply.clone(),
+            None if rpc.starts_with("<get-") => rpc_error("syntax error"),
+            None => "<rpc-reply><ok/></rpc-reply>".to_string(),
+        }
+    }
+
+    fn sessions(&self) -> Vec<Vec<String>> {
+        self.sessions.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl NetconfTransport for MockJunos {
+    async fn session(&self, _host: &str, rpcs: &[String]) -> Result<Vec<String>, JuniperError> {
+        let mut sent = Vec::new();
+        let mut replies = Vec::new();
+        for rpc in rpcs {
+            sent.push(rpc.clone());
+            let reply = self.answer(rpc);
+            let failed = reply_error(&reply).is_some();
+            replies.push(reply);
+            if failed {
+                break;
+            }
+        }
+        self.sessions.lock().unwrap().push(sent);
+        Ok(replies)
+    }
+}
+
+fn rpc_error(message: &str) -> String {
+    format!(
+        "<rpc-reply><rpc-error><error-type>protocol</error-type><error-severity>error</error-severity>\
+         <error-message>\n{}\n</error-message></rpc-error></rpc-reply>",
+        message
+    )
+}
+
+const SYSTEM_INFORMATION: &str = r#"<rpc-reply xmlns:junos="http://xml.juniper.net/junos/21.4R3/junos">
+<system-information>
+<hardware-model>ex2300-24p</hardware-model>
+<os-name>junos</os-name>
+<os-version>21.4R3-S5.4</os-version>
+<serial-number>JW3619290123</serial-number>
+<host-name>access-sw1</host-name>
+</system-information>
+</rpc-reply>"#;
+
+const CHASSIS_MAC: &str = r#"<rpc-reply xmlns:junos="http://xml.juniper.net/junos/21.4R3/junos">
+<chassis-mac-addresses>
+<mac-address-information>
+<public-base-address>f4:a7:39:12:34:00</public-base-address>
+<public-count>64</public-count>
+</mac-address-information>
+</chassis-mac-addresses>
+</rpc-reply>"#;
+
+const ROUTE_ENGINE: &str = r#"<rpc-reply xmlns:junos="http://xml.juniper.net/junos/21.4R3/junos">
+<route-engine-information xmlns="http://xml.juniper.net/junos/21.4R3/junos-chassis">
+<route-engine>
+<slot>0</slot>
+<memory-buffer-utilization>52</memory-buffer-utilization>
+<cpu-idle>88</cpu-idle>
+<up-time junos:seconds="604800">7 days</up-time>
+</route-engine>
+</route-engine-information>
+</rpc-reply>"#;
+
+const INTERFACES: &str = r#"<rpc-reply xmlns:junos="http://xml.juniper.net/junos/21.4R3/junos">
+<interface-information xmlns="http://xml.juniper.net/junos/21.4R3/junos-interface" junos:style="normal">
+<physical-interface>
+<name>
+ge-0/0/0
+</name>
+<admin-status junos:format="Enabled">up</admin-status>
+<oper-status>up</oper-status>
+<speed>1000mbps</speed>
+<current-physical-address>f4:a7:39:12:34:03</current-physical-address>
+<traffic-statistics junos:style="verbose">
+<input-bytes>123456789</input-bytes>
+<output-bytes>987654321</output-bytes>
+</traffic-statistics>
+<input-error-list>
+<input-errors>3</input-errors>
+</input-error-list>
+<output-error-list>
+<output-errors>1</output-errors>
+</output-error-list>
+<logical-interface>
+<name>ge-0/0/0.0</name>
+<traffic-statistics junos:style="brief">
+<input-bytes>42</input-bytes>
+</traffic-statistics>
+</logical-interface>
+</physical-interface>
+<physical-interface>
+<name>
+xe-0/1/0
+</name>
+<oper-status>down</oper-status>
+<speed>10Gbps</speed>
+<traffic-statistics junos:style="verbose">
+<input-bytes>0</input-bytes>
+<output-bytes>0</output-bytes>
+</traffic-statistics>
+</physical-interface>
+</interface-information>
+</rpc-reply>"#;
+
+fn junos_device() -> MockJunos {
+    MockJunos::default()
+        .reply("<get-system-information", SYSTEM_INFORMATION)
+        .reply("<get-chassis-mac-addresses", CHASSIS_MAC)
+        .reply("<get-route-engine-information", ROUTE_ENGINE)
+        .reply("<get-interface-information", INTERFACES)
+}
+
+fn adapter(device: Arc<MockJunos>) -> JuniperAdapter {
+    JuniperAdapter::with_transport(&["192.0.2.21"], device)
+}
+
+/// Devices are identified from system information and the chassis MAC
+#[tokio::test]
+async fn test_device_is_listed() {
+    let adapter = adapter(Arc::new(junos_device()));
+
+    let devices = adapter.list_devices().await.unwrap();
+
+    assert_eq!(devices.len(), 1);
+    assert_eq!(devices[0].vendor_id, "192.0.2.21");
+    assert_eq!(devices[0].name, "access-sw1");
+    assert_eq!(devices[0].model, "ex2300-24p");
+    assert_eq!(devices[0].mac, MacAddress::parse("f4:a7:39:12:34:00").unwrap());
+    assert_eq!(devices[0].properties["junos_version"], "21.4R3-S5.4");
+}
+
+/// Route engine and interface replies become device statistics
+#[tokio::test]
+async fn test_stats_parse() {
+    let adapter = adapter(Arc::new(junos_device()));
+
+    let stats = adapter.get_device_stats("192.0.2.21").await.unwrap();
+
+    assert_eq!(stats.uptime_seconds, 604800);
+    assert_eq!(stats.cpu_percent, Some(12.0));
+    assert_eq!(stats.memory_percent, Some(52.0));
+    assert_eq!(stats.port_stats.len(), 2);
+
+    let ge0 = &stats.port_stats[0];
+    assert_eq!(ge0.port_id, PortId::new("ge-0/0/0"));
+    assert!(ge0.link_up);
+    assert_eq!(ge0.speed, Some(LinkSpeed::Gbps1));
+    assert_eq!((ge0.rx_bytes, ge0.tx_bytes), (123456789, 987654321));
+    assert_eq!((ge0.rx_errors, ge0.tx_errors), (3, 1));
+
+    let xe0 = &stats.port_stats[1];
+    assert!(!xe0.link_up);
+    assert_eq!(xe0.speed, Some(LinkSpeed::Gbps10));
+}
+
+/// Config is loaded into a private candidate and committed in one session
+#[tokio::test]
+async fn test_config_is_committed() {
+    let device = Arc::new(junos_device());
+    let adapter = adapter(device.clone());
+
+    let request = DeviceConfigRequest::default()
+        .with_hostname("access-sw2")
+        .with_vlan(VlanConfig::new(20, "voice").unwrap());
+    adapter.apply_config("192.0.2.21", request).await.unwrap();
+
+    let sessions = device.sessions();
+    assert_eq!(sessions.len(), 1);
+    let rpcs = &sessions[0];
+    assert_eq!(rpcs.len(), 4);
+    assert_eq!(rpcs[0], "<open-configuration><private/></open-configuration>");
+    assert!(rpcs[1].starts_with(r#"<load-configuration action="set" format="text">"#));
+    assert!(rpcs[1].contains("set system host-name access-sw2\nset vlans voice vlan-id 20"));
+    assert_eq!(rpcs[2], "<commit/>");
+    assert_eq!(rpcs[3], "<close-configuration/>");
+}
+
+/// A rejected load is never committed and reports as rolled back
+#[tokio::test]
+async fn test_rejected_load_is_not_committed() {
+    let device = Arc::new(junos_device().reply("<load-configuration", &rpc_error("syntax error, expecting <command>")));
+    let adapter = adapter(device.clone());
+
+    let request = DeviceConfigRequest::default().with_hostname("access-sw2");
+    let outcome = adapter.apply_config_transactional("192.0.2.21", request.clone()).await.unwrap();
+
+    assert!(matches!(outcome, ConfigApplyOutcome::RolledBack { reason } if reason.contains("syntax error")));
+    assert!(device.sessions()[0].iter().all(|rpc| rpc != "<commit/>"));
+
+    let result = adapter.apply_config("192.0.2.21", request).await;
+    assert!(matches!(result, Err(PortError::VendorError(msg)) if msg.contains("syntax error")));
+}
+
+/// Reboots go through `<request-reboot>`; unknown hosts are refused
+#[tokio::test]
+async fn test_restart_and_unknown_hosts() {
+    let device = Arc::new(junos_device());
+    let adapter = adapter(device.clone());
+
+    adapter.restart_device("192.0.2.21").await.unwrap();
+    assert_eq!(device.sessions(), [vec!["<request-reboot/>".to_string()]]);
+
+    assert!(adapter.restart_device("192.0.2.99").await.is_err());
+}
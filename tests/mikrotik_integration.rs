@@ -0,0 +1,182 @@
+//! Integration tests for the MikroTik RouterOS adapter
+//!
+//! These tests run `MikroTikAdapter` against a minimal in-process HTTP server
+//! that serves canned RouterOS REST responses and records every request with
+//! its body, so no router is required.
+//!
+//! Run with: cargo test --test mikrotik_integration
+
+mod common;
+
+use cim_network::adapters::mikrotik::MikroTikAdapter;
+use cim_network::domain::ports::{DeviceControlPort, PortError, VendorConfig};
+use cim_network::domain::value_objects::{LinkSpeed, MacAddress, PortId};
+use common::CannedApi;
+
+/// Body RouterOS answers unknown REST paths with
+const NOT_FOUND: &str = r#"{"error":404,"message":"Not Found"}"#;
+
+const IDENTITY: &str = r#"{"name":"edge-rtr"}"#;
+
+const RESOURCE: &str = r#"{
+    "uptime": "1d2h3m4s",
+    "cpu-load": "7",
+    "free-memory": "805306368",
+    "total-memory": "1073741824",
+    "board-name": "RB5009UG+S+",
+    "version": "7.14.3 (stable)"
+}"#;
+
+const INTERFACES: &str = r#"[
+    {".id":"*1","name":"ether1","type":"ether","mac-address":"48:8F:5A:00:00:01","running":"true","disabled":"false",
+     "rx-byte":"123456789","tx-byte":"98765432","rx-error":"2","tx-error":"0"},
+    {".id":"*2","name":"sfp-sfpplus1","type":"ether","mac-address":"48:8F:5A:00:00:08","running":"false","disabled":"false",
+     "rx-byte":"0","tx-byte":"0","rx-error":"0","tx-error":"0"},
+    {".id":"*9","name":"bridge","type":"bridge","mac-address":"48:8F:5A:00:00:01","running":"true","disabled":"false",
+     "rx-byte":"5000","tx-byte":"6000"}
+]"#;
+
+const ETHERNET: &str = r#"[
+    {"name":"ether1","speed":"1G-baseT-full"},
+    {"name":"sfp-sfpplus1","speed":"10G-baseSR-LR"}
+]"#;
+
+fn router() -> CannedApi {
+    CannedApi::new(NOT_FOUND)
+        .route("GET", "/rest/system/identity", 200, IDENTITY)
+        .route("GET", "/rest/system/resource", 200, RESOURCE)
+        .route("GET", "/rest/interface", 200, INTERFACES)
+        .route("GET", "/rest/interface/ethernet", 200, ETHERNET)
+}
+
+#[tokio::test]
+async fn test_list_devices_reads_identity_and_interfaces() {
+    let (base_url, _router) = router().serve().await;
+    let adapter = MikroTikAdapter::new(&[&base_url], "admin", "secret").unwrap();
+
+    let devices = adapter.list_devices().await.expect("Failed to list devices");
+
+    assert_eq!(devices.len(), 1);
+    let device = &devices[0];
+    assert_eq!(device.vendor_id, base_url);
+    assert_eq!(device.name, "edge-rtr");
+    assert_eq!(device.model, "RB5009UG+S+");
+    assert_eq!(device.mac, MacAddress::parse("48:8f:5a:00:00:01").unwrap());
+    assert_eq!(device.ip_address, Some("127.0.0.1".parse().unwrap()));
+    assert!(device.adopted);
+}
+
+#[tokio::test]
+async fn test_get_device_stats() {
+    let (base_url, router) = router().serve().await;
+    let adapter = MikroTikAdapter::new(&[&base_url], "admin", "secret").unwrap();
+
+    let stats = adapter.get_device_stats(&base_url).await.expect("Failed to get stats");
+
+    assert_eq!(stats.uptime_seconds, 86400 + 2 * 3600 + 3 * 60 + 4);
+    assert_eq!(stats.cpu_percent, Some(7.0));
+    assert_eq!(stats.memory_percent, Some(25.0));
+    assert_eq!(stats.port_stats.len(), 3);
+
+    let ether1 = &stats.port_stats[0];
+    assert_eq!(ether1.port_id, PortId::new("ether1"));
+    assert!(ether1.link_up);
+    assert_eq!(ether1.speed, Some(LinkSpeed::Gbps1));
+    assert_eq!(ether1.rx_bytes, 123456789);
+    assert_eq!(ether1.tx_bytes, 98765432);
+    assert_eq!(ether1.rx_errors, 2);
+
+    let sfp = &stats.port_stats[1];
+    assert!(!sfp.link_up);
+    assert_eq!(sfp.speed, Some(LinkSpeed::Gbps10));
+
+    // Non-ethernet interfaces have no link rate
+    assert_eq!(stats.port_stats[2].speed, None);
+    assert!(router.requests().iter().all(|r| r.authorized));
+}
+
+#[tokio::test]
+async fn test_apply_config_sends_each_call() {
+    let (base_url, router) = router()
+        .route("POST", "/rest/system/identity/set", 200, "[]")
+        .route("PUT", "/rest/ip/address", 201, r#"{".id":"*A","address":"10.0.0.1/24","interface":"ether2"}"#)
+        .route("PATCH", "/rest/interface/*2", 200, r#"{".id":"*2","disabled":"true"}"#)
+        .serve()
+        .await;
+    let adapter = MikroTikAdapter::new(&[&base_url], "admin", "secret").unwrap();
+
+    let calls = serde_json::json!([
+        { "method": "POST", "path": "system/identity/set", "body": { "name": "core-rtr" } },
+        { "path": "ip/address", "body": { "address": "10.0.0.1/24", "interface": "ether2" } },
+        { "method": "PATCH", "path": "interface/*2", "body": { "disabled": "true" } },
+    ]);
+    adapter
+        .apply_raw_config(&base_url, VendorConfig {
+            config_type: "routeros".to_string(),
+            payload: serde_json::json!({ "config": calls }),
+        })
+        .await
+        .expect("Failed to apply config");
+
+    let requests = router.requests();
+    let sent: Vec<_> = requests
+        .iter()
+        .map(|r| (r.method.as_str(), r.path.as_str(), r.body.clone().unwrap_or_default()))
+        .collect();
+    assert_eq!(sent, vec![
+        ("POST", "/rest/system/identity/set", calls[0]["body"].clone()),
+        ("PUT", "/rest/ip/address", calls[1]["body"].clone()),
+        ("PATCH", "/rest/interface/*2", calls[2]["body"].clone()),
+    ]);
+}
+
+#[tokio::test]
+async fn test_apply_config_stops_at_rejected_call() {
+    let (base_url, router) = router()
+        .route(
+            "PUT",
+            "/rest/ip/address",
+            400,
+            r#"{"error":400,"message":"Bad Request","detail":"invalid value for argument address"}"#,
+        )
+        .serve()
+        .await;
+    let adapter = MikroTikAdapter::new(&[&base_url], "admin", "secret").unwrap();
+
+    let result = adapter
+        .apply_raw_config(&base_url, VendorConfig {
+            config_type: "routeros".to_string(),
+            payload: serde_json::json!([
+                { "path": "ip/address", "body": { "address": "10.0.0.300/24" } },
+                { "method": "POST", "path": "system/identity/set", "body": { "name": "never" } },
+            ]),
+        })
+        .await;
+
+    assert!(matches!(result, Err(PortError::VendorError(msg)) if msg.contains("invalid value for argument address")));
+    assert_eq!(router.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_restart_device_posts_reboot() {
+    let (base_url, router) = router()
+        .route("POST", "/rest/system/reboot", 200, "")
+        .serve()
+        .await;
+    let adapter = MikroTikAdapter::new(&[&base_url], "admin", "secret").unwrap();
+
+    adapter.restart_device(&base_url).await.expect("Failed to reboot");
+
+    let requests = router.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!((requests[0].method.as_str(), requests[0].path.as_str()), ("POST", "/rest/system/reboot"));
+}
+
+#[tokio::test]
+async fn test_unknown_router_is_rejected() {
+    let adapter = MikroTikAdapter::new(&["https://192.0.2.1"], "admin", "secret").unwrap();
+
+    let result = adapter.restart_device("https://192.0.2.99").await;
+
+    assert!(matches!(result, Err(PortError::VendorError(msg)) if msg.contains("not configured")));
+}
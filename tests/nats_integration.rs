@@ -7,10 +7,19 @@
 //!
 //! Run with: NATS_URL=nats://apache_nats:4222 cargo test --test nats_integration
 
+use async_trait::async_trait;
 use cim_network::adapters::nats::{NatsEventStore, NatsEventStoreConfig};
-use cim_network::domain::events::NetworkEvent;
-use cim_network::domain::ports::EventStorePort;
+use cim_network::domain::events::{EventEnvelope, NetworkEvent};
+use cim_network::domain::aggregates::AggregateError;
+use cim_network::domain::ports::{
+    DeviceConfigRequest, DeviceControlPort, DeviceStats, EventStorePort, PortError, VendorConfig,
+    VendorDevice,
+};
+use cim_network::domain::projections::{DeviceCountByState, Projection};
+use cim_network::domain::aggregates::DeviceState;
+use cim_network::domain::clock::MockClock;
 use cim_network::domain::value_objects::{DeviceId, DeviceType, MacAddress};
+use futures::StreamExt;
 
 fn init_tracing() {
     let _ = tracing_subscriber::fmt()
@@ -26,6 +35,34 @@ fn get_nats_url() -> String {
     std::env::var("NATS_URL").unwrap_or_else(|_| "nats://localhost:4222".to_string())
 }
 
+/// Vendor adapter with no devices, for service tests
+struct MockVendorAdapter;
+
+#[async_trait]
+impl DeviceControlPort for MockVendorAdapter {
+    fn vendor_name(&self) -> &str { "MockVendor" }
+    async fn connect(&self) -> Result<(), PortError> { Ok(()) }
+    async fn disconnect(&self) -> Result<(), PortError> { Ok(()) }
+    fn is_connected(&self) -> bool { true }
+    async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError> { Ok(vec![]) }
+    async fn get_device(&self, _vendor_id: &str) -> Result<VendorDevice, PortError> {
+        Err(PortError::DeviceNotFound(DeviceId::new()))
+    }
+    async fn adopt_device(&self, _vendor_id: &str) -> Result<(), PortError> { Ok(()) }
+    async fn apply_config(&self, _vendor_id: &str, _config: DeviceConfigRequest) -> Result<(), PortError> { Ok(()) }
+    async fn apply_raw_config(&self, _vendor_id: &str, _config: VendorConfig) -> Result<(), PortError> { Ok(()) }
+    async fn restart_device(&self, _vendor_id: &str) -> Result<(), PortError> { Ok(()) }
+    async fn get_device_stats(&self, _vendor_id: &str) -> Result<DeviceStats, PortError> {
+        Ok(DeviceStats {
+            uptime_seconds: 3600,
+            cpu_percent: Some(25.0),
+            memory_percent: Some(50.0),
+            temperature_celsius: Some(45.0),
+            port_stats: vec![],
+        })
+    }
+}
+
 /// Test basic connectivity to the NATS cluster
 #[tokio::test]
 async fn test_nats_connection() {
@@ -69,6 +106,31 @@ async fn test_append_single_event() {
     tracing::info!("Successfully appended event for device {}", device_id);
 }
 
+/// Appends made right after the connection drops wait for the reconnect
+#[tokio::test]
+async fn test_append_after_reconnect() {
+    init_tracing();
+    let nats_url = get_nats_url();
+
+    let config = NatsEventStoreConfig::for_testing(&nats_url);
+    let store = NatsEventStore::new(config).await
+        .expect("Failed to connect to NATS");
+
+    store.client().force_reconnect().await.expect("Failed to force a reconnect");
+
+    let device_id = DeviceId::new();
+    let event = NetworkEvent::DeviceDiscovered {
+        device_id,
+        mac: MacAddress::parse("00:11:22:33:44:56").unwrap(),
+        device_type: DeviceType::Switch,
+        ip_address: None,
+    };
+    store.append(vec![event]).await.expect("Append failed across the reconnect");
+
+    let events = store.load_events(&device_id.to_string()).await.expect("Failed to load events");
+    assert_eq!(events.len(), 1);
+}
+
 /// Test appending multiple events in a batch
 #[tokio::test]
 async fn test_append_batch_events() {
@@ -160,6 +222,230 @@ async fn test_load_events() {
     }
 }
 
+/// Test that replay consumers are deleted instead of accumulating on the stream
+#[tokio::test]
+async fn test_repeated_replays_do_not_accumulate_consumers() {
+    init_tracing();
+    let nats_url = get_nats_url();
+
+    let config = NatsEventStoreConfig::for_testing(&nats_url);
+    let (stream_name, prefix) = (config.stream_name.clone(), config.subject_prefix.clone());
+    let store = NatsEventStore::new(config).await
+        .expect("Failed to connect to NATS");
+
+    let device_id = DeviceId::new();
+    store.append(vec![NetworkEvent::DeviceDiscovered {
+        device_id,
+        mac: MacAddress::parse("11:22:33:44:55:67").unwrap(),
+        device_type: DeviceType::Switch,
+        ip_address: None,
+    }]).await.expect("Failed to append event");
+
+    for _ in 0..10 {
+        let loaded = store.load_events(&device_id.to_string()).await
+            .expect("Failed to load events");
+        assert_eq!(loaded.len(), 1);
+    }
+    // Subscribing twice to the same subject reuses one durable consumer
+    let subject = format!("{}.device.>", prefix);
+    store.subscribe_events(&subject).await.expect("Failed to subscribe");
+    store.subscribe_events(&subject).await.expect("Failed to subscribe");
+
+    let mut stream = store.jetstream().get_stream(&stream_name).await
+        .expect("Failed to get stream");
+    let info = stream.info().await.expect("Failed to get stream info");
+    assert_eq!(info.state.consumer_count, 1, "replay consumers were left on the stream");
+}
+
+/// Publish an event the way writers did before per-aggregate partitioning
+async fn publish_unpartitioned(store: &NatsEventStore, subject: String, event: &NetworkEvent) {
+    let mut headers = async_nats::HeaderMap::new();
+    headers.insert("CIM-Aggregate-Id", event.aggregate_id().as_str());
+    headers.insert("CIM-Event-Type", event.event_type());
+    headers.insert("CIM-Timestamp", chrono::Utc::now().to_rfc3339().as_str());
+    store.jetstream()
+        .publish_with_headers(subject, headers, event.encode().unwrap().into())
+        .await
+        .expect("Failed to publish event")
+        .await
+        .expect("Event was not acked");
+}
+
+/// Test that migrated legacy events replay in order without scanning legacy subjects
+#[tokio::test]
+async fn test_migrate_legacy_subjects() {
+    init_tracing();
+    let nats_url = get_nats_url();
+
+    let config = NatsEventStoreConfig::for_testing(&nats_url);
+    let prefix = config.subject_prefix.clone();
+    assert!(!config.read_legacy_subjects);
+    let store = NatsEventStore::new(config).await
+        .expect("Failed to connect to NATS");
+
+    let device_id = DeviceId::new();
+    let rename = |from: &str, to: &str| NetworkEvent::DeviceRenamed {
+        device_id,
+        old_name: from.to_string(),
+        new_name: to.to_string(),
+    };
+    let history = [
+        NetworkEvent::DeviceDiscovered {
+            device_id,
+            mac: MacAddress::parse("02:00:00:00:00:07").unwrap(),
+            device_type: DeviceType::Switch,
+            ip_address: None,
+        },
+        rename("", "a"),
+        rename("a", "b"),
+    ];
+    // Two events from before partitioning, one written after it
+    for event in &history[..2] {
+        publish_unpartitioned(&store, event.legacy_nats_subject_with_prefix(&prefix), event).await;
+    }
+    publish_unpartitioned(&store, history[2].nats_subject_with_prefix(&prefix, &device_id.to_string()), &history[2]).await;
+
+    let unmigrated = store.load_events(&device_id.to_string()).await.expect("Failed to load events");
+    assert_eq!(unmigrated.len(), 1, "legacy subjects were scanned with the flag off");
+
+    let migrated = store.migrate_legacy_subjects().await.expect("Migration failed");
+    assert_eq!(migrated, 2);
+
+    let loaded = store.load_events(&device_id.to_string()).await.expect("Failed to load events");
+    let types: Vec<&str> = loaded.iter().map(|e| e.event_type()).collect();
+    assert_eq!(types, ["DeviceDiscovered", "DeviceRenamed", "DeviceRenamed"]);
+    assert!(matches!(&loaded[2], NetworkEvent::DeviceRenamed { new_name, .. } if new_name == "b"));
+
+    // Nothing is left to migrate, and nothing is duplicated
+    assert_eq!(store.migrate_legacy_subjects().await.expect("Migration failed"), 0);
+    assert_eq!(store.load_events(&device_id.to_string()).await.unwrap().len(), 3);
+}
+
+/// Test that a migration hitting the duplicate window keeps the original events
+#[tokio::test]
+async fn test_migrate_legacy_subjects_rolls_back_on_duplicate() {
+    init_tracing();
+    let nats_url = get_nats_url();
+
+    let config = NatsEventStoreConfig {
+        read_legacy_subjects: true,
+        ..NatsEventStoreConfig::for_testing(&nats_url)
+    };
+    let prefix = config.subject_prefix.clone();
+    let store = NatsEventStore::new(config).await
+        .expect("Failed to connect to NATS");
+
+    let device_id = DeviceId::new();
+    let discovered = NetworkEvent::DeviceDiscovered {
+        device_id,
+        mac: MacAddress::parse("02:00:00:00:00:08").unwrap(),
+        device_type: DeviceType::Switch,
+        ip_address: None,
+    };
+    publish_unpartitioned(&store, discovered.legacy_nats_subject_with_prefix(&prefix), &discovered).await;
+    // Appended just now, so its message id is still inside the duplicate window
+    store.append(vec![NetworkEvent::DeviceRenamed {
+        device_id,
+        old_name: String::new(),
+        new_name: "a".to_string(),
+    }]).await.expect("Failed to append event");
+
+    let result = store.migrate_legacy_subjects().await;
+    assert!(matches!(result, Err(PortError::VendorError(ref msg)) if msg.contains("duplicate window")), "{:?}", result);
+
+    let loaded = store.load_events(&device_id.to_string()).await.expect("Failed to load events");
+    assert_eq!(loaded.len(), 2);
+    assert!(matches!(loaded[0], NetworkEvent::DeviceDiscovered { .. }));
+    assert!(matches!(&loaded[1], NetworkEvent::DeviceRenamed { new_name, .. } if new_name == "a"));
+}
+
+/// Test that a purged aggregate loads no events while others keep theirs
+#[tokio::test]
+async fn test_purge_aggregate_removes_history() {
+    init_tracing();
+    let nats_url = get_nats_url();
+
+    let config = NatsEventStoreConfig::for_testing(&nats_url);
+    let retention_stream = config.retention_stream_name.clone();
+    let store = NatsEventStore::new(config).await
+        .expect("Failed to connect to NATS");
+
+    let (purged, kept) = (DeviceId::new(), DeviceId::new());
+    for (device_id, mac) in [(purged, "11:22:33:44:55:70"), (kept, "11:22:33:44:55:71")] {
+        store.append(vec![
+            NetworkEvent::DeviceDiscovered {
+                device_id,
+                mac: MacAddress::parse(mac).unwrap(),
+                device_type: DeviceType::Switch,
+                ip_address: None,
+            },
+            NetworkEvent::DeviceDecommissioned { device_id },
+        ]).await.expect("Failed to append events");
+    }
+    store.save_snapshot(&purged.to_string(), 2, vec![1, 2, 3]).await
+        .expect("Failed to save snapshot");
+
+    store.purge_aggregate(&purged.to_string()).await.expect("Failed to purge");
+    store.append_tombstone(NetworkEvent::DevicePurged { device_id: purged }).await
+        .expect("Failed to record tombstone");
+
+    assert!(store.load_events(&purged.to_string()).await.unwrap().is_empty());
+    assert!(store.load_latest_snapshot(&purged.to_string()).await.unwrap().is_none());
+    assert_eq!(store.load_events(&kept.to_string()).await.unwrap().len(), 2);
+    assert_eq!(
+        store.list_aggregate_ids("device").await.unwrap(),
+        vec![kept.to_string()]
+    );
+
+    let mut retention = store.jetstream().get_stream(&retention_stream).await
+        .expect("Failed to get retention stream");
+    let info = retention.info().await.expect("Failed to get stream info");
+    assert_eq!(info.state.messages, 1);
+}
+
+/// Records the aggregate id of every event it is shown
+#[derive(Default)]
+struct EventOrder(Vec<String>);
+
+impl Projection for EventOrder {
+    fn apply(&mut self, event: &NetworkEvent) {
+        self.0.push(event.aggregate_id());
+    }
+}
+
+/// Test that fold_all shows a projection every event once, in recorded order
+#[tokio::test]
+async fn test_fold_all_streams_events_in_order() {
+    init_tracing();
+    let nats_url = get_nats_url();
+
+    let config = NatsEventStoreConfig::for_testing(&nats_url);
+    let store = NatsEventStore::new(config).await
+        .expect("Failed to connect to NATS");
+
+    let (first, second) = (DeviceId::new(), DeviceId::new());
+    let discover = |device_id, mac: &str| NetworkEvent::DeviceDiscovered {
+        device_id,
+        mac: MacAddress::parse(mac).unwrap(),
+        device_type: DeviceType::Switch,
+        ip_address: None,
+    };
+    store.append(vec![
+        discover(first, "11:22:33:44:55:80"),
+        discover(second, "11:22:33:44:55:81"),
+        NetworkEvent::DeviceDecommissioned { device_id: first },
+    ]).await.expect("Failed to append events");
+
+    let mut order = EventOrder::default();
+    store.fold_all(&mut order).await.expect("Failed to fold events");
+    assert_eq!(order.0, [first.to_string(), second.to_string(), first.to_string()]);
+
+    let mut counts = DeviceCountByState::new();
+    store.fold_all(&mut counts).await.expect("Failed to fold events");
+    assert_eq!(counts.count(DeviceState::Discovered), 1);
+    assert_eq!(counts.count(DeviceState::Decommissioned), 1);
+}
+
 /// Test full device lifecycle through event sourcing
 #[tokio::test]
 async fn test_device_lifecycle() {
@@ -332,6 +618,231 @@ async fn test_concurrent_appends() {
     tracing::info!("All 5 concurrent appends succeeded");
 }
 
+/// Test that replaying one aggregate only reads that aggregate's subjects
+#[tokio::test]
+async fn test_load_events_partitioned_by_aggregate() {
+    init_tracing();
+    let nats_url = get_nats_url();
+
+    let config = NatsEventStoreConfig::for_testing(&nats_url);
+    let store = NatsEventStore::new(config).await
+        .expect("Failed to connect to NATS");
+
+    // 10k events spread over 50 aggregates
+    let device_ids: Vec<DeviceId> = (0..50).map(|_| DeviceId::new()).collect();
+    for round in 0..200u32 {
+        let batch: Vec<NetworkEvent> = device_ids
+            .iter()
+            .enumerate()
+            .map(|(i, device_id)| NetworkEvent::DeviceDiscovered {
+                device_id: *device_id,
+                mac: MacAddress::parse(&format!("02:00:00:00:{:02X}:{:02X}", i, round % 256)).unwrap(),
+                device_type: DeviceType::Switch,
+                ip_address: None,
+            })
+            .collect();
+        store.append(batch).await.expect("Failed to append events");
+    }
+
+    let target = device_ids[17];
+    let started = std::time::Instant::now();
+    let events = store.load_events(&target.to_string()).await
+        .expect("Failed to load events");
+    let elapsed = started.elapsed();
+
+    assert_eq!(events.len(), 200);
+    assert!(events.iter().all(|e| e.aggregate_id() == target.to_string()));
+    assert!(elapsed < std::time::Duration::from_secs(1), "Replay took {:?}", elapsed);
+
+    tracing::info!("Replayed 200 of 10000 events in {:?}", elapsed);
+}
+
+/// Test that every device aggregate is listed and a fresh service rehydrates from them
+#[tokio::test]
+async fn test_list_aggregate_ids_and_rehydrate() {
+    init_tracing();
+    let nats_url = get_nats_url();
+
+    let config = NatsEventStoreConfig::for_testing(&nats_url);
+    let store = std::sync::Arc::new(
+        NatsEventStore::new(config).await
+            .expect("Failed to connect to NATS")
+    );
+
+    let device_ids: Vec<DeviceId> = (0..2).map(|_| DeviceId::new()).collect();
+    for (i, device_id) in device_ids.iter().enumerate() {
+        store.append(vec![
+            NetworkEvent::DeviceDiscovered {
+                device_id: *device_id,
+                mac: MacAddress::parse(&format!("02:00:00:00:01:{:02X}", i)).unwrap(),
+                device_type: DeviceType::Switch,
+                ip_address: None,
+            },
+            NetworkEvent::DeviceRenamed {
+                device_id: *device_id,
+                old_name: String::new(),
+                new_name: format!("switch-{}", i),
+            },
+        ]).await.expect("Failed to append events");
+    }
+
+    let mut expected: Vec<String> = device_ids.iter().map(|id| id.to_string()).collect();
+    expected.sort();
+    let listed = store.list_aggregate_ids("device").await.expect("Failed to list aggregates");
+    assert_eq!(listed, expected);
+
+    let service = cim_network::service::NetworkService::builder()
+        .event_store_arc(store.clone() as std::sync::Arc<dyn EventStorePort>)
+        .vendor_adapter(MockVendorAdapter)
+        .build()
+        .expect("Failed to build service");
+    service.rehydrate().await.expect("Failed to rehydrate");
+
+    let mut names: Vec<String> = service.list_devices().await.iter().map(|d| d.name().to_string()).collect();
+    names.sort();
+    assert_eq!(names, ["switch-0", "switch-1"]);
+}
+
+/// Test that events after a snapshot are loaded without replaying history
+#[tokio::test]
+async fn test_snapshot_roundtrip_and_seek() {
+    init_tracing();
+    let nats_url = get_nats_url();
+
+    let config = NatsEventStoreConfig::for_testing(&nats_url);
+    let store = NatsEventStore::new(config).await
+        .expect("Failed to connect to NATS");
+
+    let device_id = DeviceId::new();
+    let aggregate_id = device_id.to_string();
+    let rename = |from: &str, to: &str| NetworkEvent::DeviceRenamed {
+        device_id,
+        old_name: from.to_string(),
+        new_name: to.to_string(),
+    };
+
+    store.append(vec![
+        NetworkEvent::DeviceDiscovered {
+            device_id,
+            mac: MacAddress::parse("02:00:00:00:00:01").unwrap(),
+            device_type: DeviceType::Switch,
+            ip_address: None,
+        },
+        rename("a", "b"),
+    ]).await.expect("Failed to append events");
+
+    store.save_snapshot(&aggregate_id, 2, b"state".to_vec()).await
+        .expect("Failed to save snapshot");
+    store.append(vec![rename("b", "c")]).await
+        .expect("Failed to append events");
+
+    let snapshot = store.load_latest_snapshot(&aggregate_id).await
+        .expect("Failed to load snapshot")
+        .expect("Snapshot missing");
+    assert_eq!(snapshot.version, 2);
+    assert_eq!(snapshot.data, b"state");
+
+    let events = store.load_events_after(&aggregate_id, snapshot.version).await
+        .expect("Failed to load events after snapshot");
+    assert_eq!(events.len(), 1);
+    assert!(matches!(&events[0], NetworkEvent::DeviceRenamed { new_name, .. } if new_name == "c"));
+}
+
+/// Test that a corrupt event surfaces as a stream item without ending replay
+#[tokio::test]
+async fn test_load_events_stream_surfaces_corrupt_event() {
+    init_tracing();
+    let nats_url = get_nats_url();
+
+    let config = NatsEventStoreConfig::for_testing(&nats_url);
+    let prefix = config.subject_prefix.clone();
+    let store = NatsEventStore::new(config).await
+        .expect("Failed to connect to NATS");
+
+    let device_id = DeviceId::new();
+    let aggregate_id = device_id.to_string();
+    let rename = |to: &str| NetworkEvent::DeviceRenamed {
+        device_id,
+        old_name: "old".to_string(),
+        new_name: to.to_string(),
+    };
+
+    store.append(vec![
+        NetworkEvent::DeviceDiscovered {
+            device_id,
+            mac: MacAddress::parse("02:00:00:00:00:03").unwrap(),
+            device_type: DeviceType::Switch,
+            ip_address: None,
+        },
+        rename("a"),
+    ]).await.expect("Failed to append events");
+
+    // Write a payload that is not a NetworkEvent onto the aggregate's subject
+    let client = async_nats::connect(&nats_url).await.expect("Failed to connect to NATS");
+    async_nats::jetstream::new(client)
+        .publish(format!("{}.device.{}.DeviceRenamed", prefix, aggregate_id), "not json".into())
+        .await
+        .expect("Failed to publish corrupt event")
+        .await
+        .expect("Corrupt event was not acked");
+
+    store.append(vec![rename("b")]).await.expect("Failed to append events");
+
+    let items: Vec<_> = store.load_events_stream(&aggregate_id).await
+        .expect("Failed to open event stream")
+        .collect()
+        .await;
+
+    assert_eq!(items.len(), 4);
+    assert!(matches!(items[0], Ok(NetworkEvent::DeviceDiscovered { .. })));
+    assert!(matches!(items[1], Ok(NetworkEvent::DeviceRenamed { .. })));
+    assert!(matches!(items[2], Err(PortError::Deserialization(_))));
+    assert!(matches!(&items[3], Ok(NetworkEvent::DeviceRenamed { new_name, .. }) if new_name == "b"));
+
+    // The collecting wrapper skips the corrupt event
+    let events = store.load_events(&aggregate_id).await.expect("Failed to load events");
+    assert_eq!(events.len(), 3);
+}
+
+/// Test that a stale expected version is rejected
+#[tokio::test]
+async fn test_append_expected_conflict() {
+    init_tracing();
+    let nats_url = get_nats_url();
+
+    let config = NatsEventStoreConfig::for_testing(&nats_url);
+    let store = NatsEventStore::new(config).await
+        .expect("Failed to connect to NATS");
+
+    let device_id = DeviceId::new();
+    let aggregate_id = device_id.to_string();
+    let rename = |to: &str| NetworkEvent::DeviceRenamed {
+        device_id,
+        old_name: "old".to_string(),
+        new_name: to.to_string(),
+    };
+
+    store.append_expected(&aggregate_id, 0, vec![NetworkEvent::DeviceDiscovered {
+        device_id,
+        mac: MacAddress::parse("02:00:00:00:00:02").unwrap(),
+        device_type: DeviceType::Switch,
+        ip_address: None,
+    }]).await.expect("First append should succeed");
+
+    store.append_expected(&aggregate_id, 1, vec![rename("a")]).await
+        .expect("Append at current version should succeed");
+
+    let stale = store.append_expected(&aggregate_id, 1, vec![rename("b")]).await;
+    assert!(
+        matches!(
+            stale,
+            Err(PortError::Aggregate(AggregateError::ConcurrencyConflict { expected: 1, actual: 2 }))
+        ),
+        "Expected concurrency conflict, got {:?}",
+        stale
+    );
+}
+
 /// Test subscription creation
 #[tokio::test]
 async fn test_subscription() {
@@ -344,11 +855,219 @@ async fn test_subscription() {
         .expect("Failed to connect to NATS");
 
     // Create subscription for device events using the store's prefix
-    let subscription = store.subscribe(&format!("{}.device.*", prefix)).await;
+    let subject = format!("{}.device.>", prefix);
+    let subscription = store.subscribe(&subject).await;
     assert!(subscription.is_ok(), "Failed to create subscription: {:?}", subscription.err());
 
     let sub = subscription.unwrap();
     tracing::info!("Created subscription with ID: {}", sub.id());
+    assert_eq!(sub.subject(), subject);
+
+    // The handle names the durable consumer a subscriber binds to
+    let subscriber = store.subscribe_events(&subject).await.expect("Failed to subscribe");
+    assert_eq!(subscriber.consumer_name(), sub.id());
+}
+
+/// Test that a published event is delivered through a subscription and acked
+#[tokio::test]
+async fn test_subscription_receives_published_event() {
+    init_tracing();
+    let nats_url = get_nats_url();
+
+    let config = NatsEventStoreConfig::for_testing(&nats_url);
+    let prefix = config.subject_prefix.clone();
+    let store = NatsEventStore::new(config).await
+        .expect("Failed to connect to NATS");
+
+    let mut subscriber = store
+        .subscribe_events(&format!("{}.device.>", prefix))
+        .await
+        .expect("Failed to subscribe");
+
+    let device_id = DeviceId::new();
+    let event = NetworkEvent::DeviceDiscovered {
+        device_id,
+        mac: MacAddress::parse("00:11:22:33:44:77").unwrap(),
+        device_type: DeviceType::Switch,
+        ip_address: None,
+    };
+    store.append(vec![event]).await.expect("Failed to append event");
+
+    let (received, ack) = tokio::time::timeout(std::time::Duration::from_secs(10), subscriber.next())
+        .await
+        .expect("Timed out waiting for event")
+        .expect("Subscription ended")
+        .expect("Failed to receive event");
+
+    assert_eq!(received.aggregate_id(), device_id.to_string());
+    assert!(matches!(received, NetworkEvent::DeviceDiscovered { .. }));
+    assert!(ack.correlation_id().is_some());
+    ack.ack().await.expect("Failed to ack event");
+}
+
+/// Test that correlation and causation ids survive the round-trip through the store
+#[tokio::test]
+async fn test_append_envelopes_preserves_causal_chain() {
+    init_tracing();
+    let nats_url = get_nats_url();
+
+    let config = NatsEventStoreConfig::for_testing(&nats_url);
+    let prefix = config.subject_prefix.clone();
+    let store = NatsEventStore::new(config).await
+        .expect("Failed to connect to NATS");
+
+    let mut subscriber = store
+        .subscribe_events(&format!("{}.device.>", prefix))
+        .await
+        .expect("Failed to subscribe");
+
+    let device_id = DeviceId::new();
+    let cause = EventEnvelope::new(NetworkEvent::DeviceDiscovered {
+        device_id,
+        mac: MacAddress::parse("00:11:22:33:44:88").unwrap(),
+        device_type: DeviceType::Switch,
+        ip_address: None,
+    })
+    .with_correlation_id("discovery-run-1");
+    let effect = EventEnvelope::new(NetworkEvent::DeviceAdopting {
+        device_id,
+        vendor_id: "switch-88".to_string(),
+    })
+    .caused_by(&cause);
+
+    store
+        .append_envelopes(vec![cause.clone(), effect.clone()])
+        .await
+        .expect("Failed to append envelopes");
+
+    let mut received = Vec::new();
+    for _ in 0..2 {
+        let (event, ack) = tokio::time::timeout(std::time::Duration::from_secs(10), subscriber.next())
+            .await
+            .expect("Timed out waiting for event")
+            .expect("Subscription ended")
+            .expect("Failed to receive event");
+        received.push((event.event_type(), ack.event_id(), ack.correlation_id(), ack.causation_id()));
+        ack.ack().await.expect("Failed to ack event");
+    }
+
+    let correlation = Some("discovery-run-1".to_string());
+    assert_eq!(received, vec![
+        ("DeviceDiscovered", Some(cause.event_id.clone()), correlation.clone(), None),
+        ("DeviceAdopting", Some(effect.event_id.clone()), correlation, Some(cause.event_id.clone())),
+    ]);
+}
+
+/// Test that `CIM-Timestamp` headers come from the injected clock
+#[tokio::test]
+async fn test_append_stamps_events_from_clock() {
+    init_tracing();
+    let nats_url = get_nats_url();
+
+    let config = NatsEventStoreConfig::for_testing(&nats_url);
+    let prefix = config.subject_prefix.clone();
+    let start = chrono::DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z").unwrap().with_timezone(&chrono::Utc);
+    let clock = std::sync::Arc::new(MockClock::new(start));
+    let store = NatsEventStore::new(config).await
+        .expect("Failed to connect to NATS")
+        .with_clock(clock.clone());
+
+    let mut subscriber = store
+        .subscribe_events(&format!("{}.device.>", prefix))
+        .await
+        .expect("Failed to subscribe");
+
+    let device_id = DeviceId::new();
+    store
+        .append(vec![NetworkEvent::DeviceDiscovered {
+            device_id,
+            mac: MacAddress::parse("00:11:22:33:44:99").unwrap(),
+            device_type: DeviceType::Switch,
+            ip_address: None,
+        }])
+        .await
+        .expect("Failed to append first event");
+    clock.advance(chrono::Duration::seconds(30));
+    store
+        .append(vec![NetworkEvent::DeviceAdopting { device_id, vendor_id: "switch-99".to_string() }])
+        .await
+        .expect("Failed to append second event");
+
+    let mut timestamps = Vec::new();
+    for _ in 0..2 {
+        let (_, ack) = tokio::time::timeout(std::time::Duration::from_secs(10), subscriber.next())
+            .await
+            .expect("Timed out waiting for event")
+            .expect("Subscription ended")
+            .expect("Failed to receive event");
+        timestamps.push(ack.timestamp());
+        ack.ack().await.expect("Failed to ack event");
+    }
+
+    assert_eq!(timestamps, vec![
+        Some("2024-05-01T12:00:00+00:00".to_string()),
+        Some("2024-05-01T12:00:30+00:00".to_string()),
+    ]);
+}
+
+/// Test that the audit trail returns each event with the metadata it was appended with
+#[tokio::test]
+async fn test_audit_trail_keeps_order_and_metadata() {
+    init_tracing();
+    let nats_url = get_nats_url();
+
+    let store = std::sync::Arc::new(
+        NatsEventStore::new(NatsEventStoreConfig::for_testing(&nats_url)).await
+            .expect("Failed to connect to NATS")
+    );
+    let service = cim_network::service::NetworkService::builder()
+        .event_store_arc(store.clone() as std::sync::Arc<dyn EventStorePort>)
+        .vendor_adapter(MockVendorAdapter)
+        .build()
+        .expect("Failed to build service");
+
+    let device_id = DeviceId::new();
+    let discovered = EventEnvelope::new(NetworkEvent::DeviceDiscovered {
+        device_id,
+        mac: MacAddress::parse("00:11:22:33:44:77").unwrap(),
+        device_type: DeviceType::Switch,
+        ip_address: None,
+    })
+    .with_correlation_id("audit-run-1");
+    let adopting = EventEnvelope::new(NetworkEvent::DeviceAdopting {
+        device_id,
+        vendor_id: "switch-77".to_string(),
+    })
+    .caused_by(&discovered);
+    let renamed = EventEnvelope::new(NetworkEvent::DeviceRenamed {
+        device_id,
+        old_name: "switch-77".to_string(),
+        new_name: "core-sw-1".to_string(),
+    })
+    .with_correlation_id("rename-request-9");
+
+    for envelope in [&discovered, &adopting, &renamed] {
+        store.append_envelopes(vec![envelope.clone()]).await.expect("Failed to append event");
+    }
+
+    let trail = service.audit_trail(&device_id.to_string()).await.expect("Failed to load audit trail");
+
+    let entries: Vec<_> = trail
+        .iter()
+        .map(|e| (e.event_type, e.event_id.clone(), e.correlation_id.clone(), e.causation_id.clone()))
+        .collect();
+    assert_eq!(entries, vec![
+        ("DeviceDiscovered", Some(discovered.event_id.clone()), Some("audit-run-1".to_string()), None),
+        (
+            "DeviceAdopting",
+            Some(adopting.event_id.clone()),
+            Some("audit-run-1".to_string()),
+            Some(discovered.event_id.clone()),
+        ),
+        ("DeviceRenamed", Some(renamed.event_id.clone()), Some("rename-request-9".to_string()), None),
+    ]);
+    assert!(trail.windows(2).all(|pair| pair[0].sequence < pair[1].sequence));
+    assert!(trail.windows(2).all(|pair| pair[0].recorded_at <= pair[1].recorded_at));
 }
 
 /// Test with the service layer
@@ -363,36 +1082,6 @@ async fn test_service_integration() {
             .expect("Failed to connect to NATS")
     );
 
-    // Create a mock vendor adapter for testing
-    use async_trait::async_trait;
-    use cim_network::domain::ports::{DeviceControlPort, DeviceStats, PortError, VendorConfig, VendorDevice};
-
-    struct MockVendorAdapter;
-
-    #[async_trait]
-    impl DeviceControlPort for MockVendorAdapter {
-        fn vendor_name(&self) -> &str { "MockVendor" }
-        async fn connect(&self) -> Result<(), PortError> { Ok(()) }
-        async fn disconnect(&self) -> Result<(), PortError> { Ok(()) }
-        fn is_connected(&self) -> bool { true }
-        async fn list_devices(&self) -> Result<Vec<VendorDevice>, PortError> { Ok(vec![]) }
-        async fn get_device(&self, _vendor_id: &str) -> Result<VendorDevice, PortError> {
-            Err(PortError::DeviceNotFound(DeviceId::new()))
-        }
-        async fn adopt_device(&self, _vendor_id: &str) -> Result<(), PortError> { Ok(()) }
-        async fn apply_config(&self, _vendor_id: &str, _config: VendorConfig) -> Result<(), PortError> { Ok(()) }
-        async fn restart_device(&self, _vendor_id: &str) -> Result<(), PortError> { Ok(()) }
-        async fn get_device_stats(&self, _vendor_id: &str) -> Result<DeviceStats, PortError> {
-            Ok(DeviceStats {
-                uptime_seconds: 3600,
-                cpu_percent: Some(25.0),
-                memory_percent: Some(50.0),
-                temperature_celsius: Some(45.0),
-                port_stats: vec![],
-            })
-        }
-    }
-
     // Build the service
     use cim_network::service::NetworkService;
 
@@ -0,0 +1,676 @@
+//! Integration tests for the NetBox adapter
+//!
+//! These tests run `NetBoxAdapter` against a minimal in-process HTTP server
+//! that serves canned NetBox API responses and records every request, so no
+//! NetBox instance is required.
+//!
+//! Run with: cargo test --test netbox_integration
+
+mod common;
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tokio::net::TcpListener;
+
+use cim_network::adapters::netbox::{NetBoxAdapter, NetBoxConfig};
+use cim_network::adapters::retry::RetryPolicy;
+use cim_network::domain::ports::{ConnectionInfo, HealthStatus, InventoryPort, IpStatus, PortError};
+use cim_network::domain::aggregates::{NetworkDeviceAggregate, SiteAggregate};
+use cim_network::domain::value_objects::{
+    ConnectionId, ConnectionType, DeviceId, DeviceType, InterfaceConfig, MacAddress, PortId, StackMember, Tag,
+};
+use common::CannedApi;
+
+/// Body NetBox answers unknown API paths with
+const NOT_FOUND: &str = r#"{"detail":"Not found."}"#;
+
+/// Body NetBox answers throttled requests with
+const THROTTLED: &str = r#"{"detail":"Request was throttled."}"#;
+
+fn device_list(devices: &[(u64, &str, &str)]) -> String {
+    let results: Vec<_> = devices
+        .iter()
+        .map(|(id, name, cim_device_id)| {
+            serde_json::json!({
+                "id": id,
+                "name": name,
+                "custom_fields": { "cim_device_id": cim_device_id },
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "count": results.len(),
+        "next": null,
+        "previous": null,
+        "results": results,
+    })
+    .to_string()
+}
+
+/// With an empty cache (e.g. after a restart) the device is found by `cim_device_id`
+#[tokio::test]
+async fn test_remove_device_resolves_by_custom_field() {
+    let device_id = DeviceId::new();
+    let lookup = format!("/api/dcim/devices/?cf_cim_device_id={}", device_id);
+
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", &lookup, 200, &device_list(&[(42, "core-sw1", &device_id.to_string())]))
+        .route("DELETE", "/api/dcim/devices/42/", 204, "")
+        .serve()
+        .await;
+    let adapter = NetBoxAdapter::new(&base_url, "token").unwrap();
+
+    adapter.remove_device(device_id).await.expect("Failed to remove device");
+
+    let requests = netbox.calls();
+    assert_eq!(requests.as_slice(), &[
+        ("GET".to_string(), lookup),
+        ("DELETE".to_string(), "/api/dcim/devices/42/".to_string()),
+    ]);
+}
+
+/// A NetBox that ignores the custom field filter must not cause the wrong device to be deleted
+#[tokio::test]
+async fn test_remove_device_ignores_unfiltered_results() {
+    let device_id = DeviceId::new();
+    let other_id = DeviceId::new();
+    let lookup = format!("/api/dcim/devices/?cf_cim_device_id={}", device_id);
+
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", &lookup, 200, &device_list(&[(7, "other-sw", &other_id.to_string())]))
+        .serve()
+        .await;
+    let adapter = NetBoxAdapter::new(&base_url, "token").unwrap();
+
+    let result = adapter.remove_device(device_id).await;
+
+    assert!(matches!(result, Err(PortError::DeviceNotFound(id)) if id == device_id));
+    assert!(netbox.calls().iter().all(|(method, _)| method != "DELETE"));
+}
+
+fn cable_list(cables: &[(u64, &str)]) -> String {
+    let results: Vec<_> = cables
+        .iter()
+        .map(|(id, label)| serde_json::json!({
+            "id": id,
+            "type": "cat6",
+            "a_terminations": [],
+            "b_terminations": [],
+            "label": label,
+        }))
+        .collect();
+    serde_json::json!({
+        "count": results.len(),
+        "next": null,
+        "previous": null,
+        "results": results,
+    })
+    .to_string()
+}
+
+/// Cables are found by the connection ID they were labelled with, and only that cable is deleted
+#[tokio::test]
+async fn test_remove_connection_deletes_labelled_cable() {
+    let connection_id = ConnectionId::new();
+    let lookup = format!("/api/dcim/cables/?label={}", connection_id);
+
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", &lookup, 200, &cable_list(&[(3, "patch-panel"), (9, &connection_id.to_string())]))
+        .route("DELETE", "/api/dcim/cables/9/", 204, "")
+        .serve()
+        .await;
+    let adapter = NetBoxAdapter::new(&base_url, "token").unwrap();
+
+    adapter.remove_connection(connection_id).await.expect("Failed to remove connection");
+
+    let requests = netbox.calls();
+    assert_eq!(requests.as_slice(), &[
+        ("GET".to_string(), lookup),
+        ("DELETE".to_string(), "/api/dcim/cables/9/".to_string()),
+    ]);
+}
+
+fn interface_list(interfaces: &[(u64, &str, u64)]) -> String {
+    let results: Vec<_> = interfaces
+        .iter()
+        .map(|(id, name, device)| serde_json::json!({
+            "id": id,
+            "name": name,
+            "device": { "id": device, "name": format!("device-{}", device) },
+        }))
+        .collect();
+    serde_json::json!({
+        "count": results.len(),
+        "next": null,
+        "previous": null,
+        "results": results,
+    })
+    .to_string()
+}
+
+fn connection(source: DeviceId, source_port: &str, target: DeviceId, target_port: &str) -> ConnectionInfo {
+    ConnectionInfo {
+        connection_id: ConnectionId::new(),
+        source_device: source,
+        source_port: PortId::new(source_port),
+        target_device: target,
+        target_port: PortId::new(target_port),
+        connection_type: ConnectionType::Ethernet,
+        speed: None,
+    }
+}
+
+/// Cables terminate on the NetBox interfaces resolved by device and port name
+#[tokio::test]
+async fn test_sync_connection_resolves_interfaces() {
+    let (core, edge) = (DeviceId::new(), DeviceId::new());
+    let core_lookup = format!("/api/dcim/devices/?cf_cim_device_id={}", core);
+    let edge_lookup = format!("/api/dcim/devices/?cf_cim_device_id={}", edge);
+    let core_port = "/api/dcim/interfaces/?device_id=42&name=Gi1%2F0%2F24";
+    let edge_port = "/api/dcim/interfaces/?device_id=43&name=eth1";
+
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", &core_lookup, 200, &device_list(&[(42, "core-sw1", &core.to_string())]))
+        .route("GET", &edge_lookup, 200, &device_list(&[(43, "edge-sw1", &edge.to_string())]))
+        // The first result belongs to another device, as from a NetBox ignoring `device_id`
+        .route("GET", core_port, 200, &interface_list(&[(500, "Gi1/0/24", 7), (1024, "Gi1/0/24", 42)]))
+        .route("GET", edge_port, 200, &interface_list(&[(2001, "eth1", 43)]))
+        .route("POST", "/api/dcim/cables/", 201, r#"{"id":5,"a_terminations":[],"b_terminations":[]}"#)
+        .serve()
+        .await;
+    let adapter = NetBoxAdapter::new(&base_url, "token").unwrap();
+
+    let first = connection(core, "Gi1/0/24", edge, "eth1");
+    adapter.sync_connection(&first).await.expect("Failed to sync connection");
+    // A second cable between the same ports resolves from the cache
+    adapter.sync_connection(&connection(core, "Gi1/0/24", edge, "eth1")).await.expect("Failed to sync connection");
+
+    let bodies = netbox.bodies();
+    let cable = &bodies[0].1;
+    assert_eq!(cable["a_terminations"], serde_json::json!([{ "object_type": "dcim.interface", "object_id": 1024 }]));
+    assert_eq!(cable["b_terminations"], serde_json::json!([{ "object_type": "dcim.interface", "object_id": 2001 }]));
+    assert_eq!(cable["label"], first.connection_id.to_string());
+
+    let lookups = netbox.calls().iter().filter(|(method, _)| method == "GET").count();
+    assert_eq!(lookups, 4);
+}
+
+/// An unresolvable port fails the sync instead of cabling some other interface
+#[tokio::test]
+async fn test_sync_connection_rejects_unknown_interface() {
+    let (core, edge) = (DeviceId::new(), DeviceId::new());
+
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", &format!("/api/dcim/devices/?cf_cim_device_id={}", core), 200, &device_list(&[(42, "core-sw1", &core.to_string())]))
+        .route("GET", &format!("/api/dcim/devices/?cf_cim_device_id={}", edge), 200, &device_list(&[]))
+        .route("GET", "/api/dcim/interfaces/?device_id=42&name=eth9", 200, &interface_list(&[]))
+        .serve()
+        .await;
+    let adapter = NetBoxAdapter::new(&base_url, "token").unwrap();
+
+    let missing_port = adapter.sync_connection(&connection(core, "eth9", edge, "eth1")).await;
+    assert!(matches!(missing_port, Err(PortError::InventoryError(msg)) if msg.contains("eth9")));
+
+    let unsynced_device = adapter.sync_connection(&connection(edge, "eth1", core, "eth9")).await;
+    assert!(matches!(unsynced_device, Err(PortError::InventoryError(msg)) if msg.contains(&edge.to_string())));
+
+    assert!(netbox.calls().iter().all(|(method, _)| method != "POST"));
+}
+
+fn ip_address(id: u64, address: &str, status: &str, interface: Option<(u64, &str)>) -> serde_json::Value {
+    let assigned_object = interface.map(|(device, name)| {
+        serde_json::json!({
+            "id": id * 100,
+            "name": name,
+            "device": { "id": device, "name": "core-sw1" },
+        })
+    });
+    serde_json::json!({
+        "id": id,
+        "address": address,
+        "status": { "value": status, "label": status },
+        "assigned_object_type": interface.map(|_| "dcim.interface"),
+        "assigned_object_id": interface.map(|_| id * 100),
+        "assigned_object": assigned_object,
+    })
+}
+
+/// Every page of a large prefix is returned, with owners resolved from `assigned_object`
+#[tokio::test]
+async fn test_get_ip_assignments_follows_pagination() {
+    let device_id = DeviceId::new();
+    let first_page = "/api/ipam/ip-addresses/?parent=10.0.0.0%2F24";
+    let second_page = "/api/ipam/ip-addresses/?limit=2&offset=2&parent=10.0.0.0%2F24";
+
+    let page_one = serde_json::json!({
+        "count": 3,
+        "next": format!("{{base_url}}{}", second_page),
+        "previous": null,
+        "results": [
+            ip_address(1, "10.0.0.1/24", "active", Some((42, "eth0"))),
+            ip_address(2, "10.0.0.2/24", "active", None),
+        ],
+    });
+    let page_two = serde_json::json!({
+        "count": 3,
+        "next": null,
+        "previous": format!("{{base_url}}{}", first_page),
+        "results": [
+            ip_address(3, "10.0.0.3/24", "reserved", Some((42, "eth1"))),
+        ],
+    });
+    let device = serde_json::json!({
+        "id": 42,
+        "name": "core-sw1",
+        "custom_fields": { "cim_device_id": device_id.to_string() },
+    });
+
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", first_page, 200, &page_one.to_string())
+        .route("GET", second_page, 200, &page_two.to_string())
+        .route("GET", "/api/dcim/devices/42/", 200, &device.to_string())
+        .serve()
+        .await;
+    let adapter = NetBoxAdapter::new(&base_url, "token").unwrap();
+
+    let assignments = adapter.get_ip_assignments("10.0.0.0/24").await.expect("Failed to get IPs");
+
+    let summary: Vec<_> = assignments
+        .iter()
+        .map(|a| (a.address.to_string(), a.device_id, a.interface.as_deref(), a.status.clone()))
+        .collect();
+    assert_eq!(summary, vec![
+        ("10.0.0.1".to_string(), Some(device_id), Some("eth0"), IpStatus::Active),
+        ("10.0.0.2".to_string(), None, None, IpStatus::Active),
+        ("10.0.0.3".to_string(), Some(device_id), Some("eth1"), IpStatus::Reserved),
+    ]);
+
+    // The owning device is looked up once and then served from the cache
+    let requests = netbox.calls();
+    let device_lookups = requests.iter().filter(|(_, path)| path == "/api/dcim/devices/42/").count();
+    assert_eq!(device_lookups, 1);
+    assert!(requests.iter().any(|(_, path)| path == second_page));
+}
+
+const STATUS: &str = r#"{
+    "django-version": "4.2.9",
+    "installed-apps": {},
+    "netbox-version": "3.7.2",
+    "plugins": {},
+    "python-version": "3.11.6",
+    "rq-workers-running": 1
+}"#;
+
+#[tokio::test]
+async fn test_health_check_reports_version() {
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", "/api/status/", 200, STATUS)
+        .serve()
+        .await;
+    let adapter = NetBoxAdapter::new(&base_url, "token").unwrap();
+
+    let health = adapter.health_check().await.expect("Failed to check health");
+
+    assert_eq!(health, HealthStatus {
+        reachable: true,
+        authenticated: true,
+        version: Some("3.7.2".to_string()),
+    });
+    assert_eq!(netbox.calls().as_slice(), &[("GET".to_string(), "/api/status/".to_string())]);
+}
+
+#[tokio::test]
+async fn test_health_check_with_rejected_token() {
+    let (base_url, _netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", "/api/status/", 403, r#"{"detail":"Invalid token"}"#)
+        .serve()
+        .await;
+    let adapter = NetBoxAdapter::new(&base_url, "wrong").unwrap();
+
+    let health = adapter.health_check().await.expect("Failed to check health");
+
+    assert!(health.reachable);
+    assert!(!health.authenticated);
+    assert!(!health.is_healthy());
+}
+
+#[tokio::test]
+async fn test_health_check_when_unreachable() {
+    // Bind and drop a listener so nothing answers on the port
+    let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+    let adapter = NetBoxAdapter::new(&format!("http://127.0.0.1:{}", port), "token").unwrap();
+
+    let health = adapter.health_check().await.expect("Failed to check health");
+
+    assert_eq!(health, HealthStatus::unreachable());
+}
+
+fn retrying_adapter(base_url: &str) -> NetBoxAdapter {
+    let config = NetBoxConfig {
+        retry: RetryPolicy::new(3, Duration::from_millis(20)),
+        ..Default::default()
+    };
+    NetBoxAdapter::with_config(base_url, "token", config).unwrap()
+}
+
+/// Throttled requests are retried with backoff until NetBox lets them through
+#[tokio::test]
+async fn test_throttled_request_is_retried_with_backoff() {
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", "/api/status/", 200, STATUS)
+        .throttle("GET", "/api/status/", 2, THROTTLED)
+        .serve()
+        .await;
+    let adapter = retrying_adapter(&base_url);
+
+    let started = Instant::now();
+    let health = adapter.health_check().await.expect("Failed to check health");
+
+    assert!(health.is_healthy());
+    assert_eq!(netbox.calls().len(), 3);
+    // 20ms before the first retry, 40ms before the second
+    assert!(started.elapsed() >= Duration::from_millis(60));
+}
+
+/// Once the attempts are used up the throttled response is reported, not retried forever
+#[tokio::test]
+async fn test_throttled_request_gives_up_after_max_attempts() {
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", "/api/status/", 200, STATUS)
+        .throttle("GET", "/api/status/", 5, THROTTLED)
+        .serve()
+        .await;
+    let adapter = retrying_adapter(&base_url);
+
+    let result = adapter.health_check().await;
+
+    assert!(matches!(result, Err(PortError::RateLimited { retry_after: None })));
+    assert!(result.unwrap_err().is_transient());
+    assert_eq!(netbox.calls().len(), 3);
+}
+
+/// A rejected token is reported as such, and is not worth retrying
+#[tokio::test]
+async fn test_rejected_token_is_authentication_failure() {
+    let device_id = DeviceId::new();
+    let lookup = format!("/api/dcim/devices/?cf_cim_device_id={}", device_id);
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", &lookup, 401, r#"{"detail":"Invalid token."}"#)
+        .serve()
+        .await;
+    let adapter = retrying_adapter(&base_url);
+
+    let result = adapter.remove_device(device_id).await;
+
+    assert!(matches!(result, Err(PortError::AuthenticationFailed(_))));
+    assert!(!result.unwrap_err().is_transient());
+    assert_eq!(netbox.calls().len(), 1);
+}
+
+/// Client errors such as 404 are not worth retrying
+#[tokio::test]
+async fn test_not_found_is_not_retried() {
+    let device_id = DeviceId::new();
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND).serve().await;
+    let adapter = retrying_adapter(&base_url);
+
+    let result = adapter.remove_device(device_id).await;
+
+    assert!(result.is_err());
+    assert_eq!(netbox.calls().len(), 1);
+}
+
+fn discovered_switch(name: &str, mac: &str) -> NetworkDeviceAggregate {
+    let mut device = NetworkDeviceAggregate::new_discovered(
+        MacAddress::parse(mac).unwrap(),
+        DeviceType::Generic { model: "DCS-7050SX3-48YC8".to_string() },
+        None,
+    );
+    device.rename(name.to_string()).unwrap();
+    device
+}
+
+const NO_RESULTS: &str = r#"{"count":0,"next":null,"previous":null,"results":[]}"#;
+
+/// An unmapped model gets its manufacturer and device type created once, then the cached id is reused
+#[tokio::test]
+async fn test_unmapped_model_auto_creates_device_type() {
+    let model_lookup = "/api/dcim/device-types/?manufacturer_id=3&model=DCS-7050SX3-48YC8";
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", "/api/dcim/devices/?name=leaf1", 200, NO_RESULTS)
+        .route("GET", "/api/dcim/devices/?name=leaf2", 200, NO_RESULTS)
+        .route("GET", "/api/dcim/manufacturers/?slug=arista", 200, NO_RESULTS)
+        .route("POST", "/api/dcim/manufacturers/", 201, r#"{"id":3,"name":"Arista","slug":"arista"}"#)
+        .route("GET", model_lookup, 200, NO_RESULTS)
+        .route("POST", "/api/dcim/device-types/", 201, r#"{"id":9,"model":"DCS-7050SX3-48YC8","manufacturer":{"id":3,"name":"Arista","slug":"arista"}}"#)
+        .route("POST", "/api/dcim/devices/", 201, r#"{"id":100,"name":"leaf","custom_fields":{}}"#)
+        .serve()
+        .await;
+    let config = NetBoxConfig {
+        auto_create_device_types: true,
+        ..Default::default()
+    };
+    let adapter = NetBoxAdapter::with_config(&base_url, "token", config).unwrap();
+
+    adapter.sync_device(&discovered_switch("leaf1", "00:1c:73:00:00:01")).await.expect("Failed to sync leaf1");
+    adapter.sync_device(&discovered_switch("leaf2", "00:1c:73:00:00:02")).await.expect("Failed to sync leaf2");
+
+    let requests = netbox.calls();
+    let post = |path: &str| ("POST".to_string(), path.to_string());
+    assert_eq!(requests.iter().filter(|r| **r == post("/api/dcim/manufacturers/")).count(), 1);
+    assert_eq!(requests.iter().filter(|r| **r == post("/api/dcim/device-types/")).count(), 1);
+    assert_eq!(requests.iter().filter(|(_, path)| path == model_lookup).count(), 1);
+
+    let bodies = netbox.bodies();
+    let posted = |path: &str| -> Vec<serde_json::Value> {
+        bodies.iter().filter(|(key, _)| *key == post(path)).map(|(_, body)| body.clone()).collect()
+    };
+    let manufacturer = &posted("/api/dcim/manufacturers/")[0];
+    assert_eq!(manufacturer["name"], "Arista");
+    assert_eq!(manufacturer["slug"], "arista");
+    let device_type = &posted("/api/dcim/device-types/")[0];
+    assert_eq!(device_type["manufacturer"], 3);
+    assert_eq!(device_type["slug"], "dcs-7050sx3-48yc8");
+    let device_types: Vec<_> = posted("/api/dcim/devices/").iter().map(|device| device["device_type"].clone()).collect();
+    assert_eq!(device_types, [9, 9]);
+}
+
+/// Without auto-creation an unmapped model is refused rather than attached to device type 1
+#[tokio::test]
+async fn test_unmapped_model_is_rejected_by_default() {
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", "/api/dcim/devices/?name=leaf1", 200, NO_RESULTS)
+        .serve()
+        .await;
+    let adapter = NetBoxAdapter::new(&base_url, "token").unwrap();
+
+    let result = adapter.sync_device(&discovered_switch("leaf1", "00:1c:73:00:00:01")).await;
+
+    assert!(matches!(result, Err(PortError::InventoryError(ref e)) if e.contains("DCS-7050SX3-48YC8")));
+    assert!(netbox.calls().iter().all(|(method, _)| method != "POST"));
+}
+
+/// Device tags become NetBox tags, looked up or created once and attached by id
+#[tokio::test]
+async fn test_sync_device_attaches_tags() {
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", "/api/dcim/devices/?name=leaf1", 200, NO_RESULTS)
+        .route("GET", "/api/dcim/devices/?name=leaf2", 200, &device_list(&[(101, "leaf2", "")]))
+        .route("GET", "/api/extras/tags/?name=env%3Aprod", 200, r#"{"count":1,"next":null,"previous":null,"results":[{"id":4,"name":"env:prod","slug":"env-prod"}]}"#)
+        .route("GET", "/api/extras/tags/?name=rack%3AR12", 200, NO_RESULTS)
+        .route("POST", "/api/extras/tags/", 201, r#"{"id":5,"name":"rack:R12","slug":"rack-r12"}"#)
+        .route("POST", "/api/dcim/devices/", 201, r#"{"id":100,"name":"leaf1","custom_fields":{}}"#)
+        .route("PATCH", "/api/dcim/devices/101/", 200, r#"{"id":101,"name":"leaf2","custom_fields":{}}"#)
+        .serve()
+        .await;
+    let config = NetBoxConfig {
+        device_type_mappings: HashMap::from([("DCS-7050SX3-48YC8".to_string(), 9)]),
+        ..Default::default()
+    };
+    let adapter = NetBoxAdapter::with_config(&base_url, "token", config).unwrap();
+
+    let mut leaf1 = discovered_switch("leaf1", "00:1c:73:00:00:01");
+    leaf1.add_tag(Tag::parse("rack:R12").unwrap()).unwrap();
+    leaf1.add_tag(Tag::parse("env:prod").unwrap()).unwrap();
+    let mut leaf2 = discovered_switch("leaf2", "00:1c:73:00:00:02");
+    leaf2.add_tag(Tag::parse("env:prod").unwrap()).unwrap();
+
+    adapter.sync_device(&leaf1).await.expect("Failed to sync leaf1");
+    adapter.sync_device(&leaf2).await.expect("Failed to sync leaf2");
+
+    let requests = netbox.calls();
+    assert_eq!(requests.iter().filter(|(_, path)| path.starts_with("/api/extras/tags/?")).count(), 2);
+
+    let bodies = netbox.bodies();
+    let body = |method: &str, path: &str| {
+        bodies.iter().find(|((m, p), _)| m == method && p == path).map(|(_, body)| body.clone()).unwrap()
+    };
+    let created_tag = body("POST", "/api/extras/tags/");
+    assert_eq!(created_tag["name"], "rack:R12");
+    assert_eq!(created_tag["slug"], "rack-r12");
+    assert_eq!(body("POST", "/api/dcim/devices/")["tags"], serde_json::json!([{"id": 4}, {"id": 5}]));
+    assert_eq!(body("PATCH", "/api/dcim/devices/101/")["tags"], serde_json::json!([{"id": 4}]));
+}
+
+/// A stack is one NetBox device placed in, and mastering, a virtual chassis
+#[tokio::test]
+async fn test_sync_stack_as_virtual_chassis() {
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", "/api/dcim/devices/?name=core-stack", 200, NO_RESULTS)
+        .route("POST", "/api/dcim/devices/", 201, r#"{"id":100,"name":"core-stack","custom_fields":{}}"#)
+        .route("GET", "/api/dcim/virtual-chassis/?name=core-stack", 200, NO_RESULTS)
+        .route("POST", "/api/dcim/virtual-chassis/", 201, r#"{"id":12,"name":"core-stack"}"#)
+        .route("PATCH", "/api/dcim/devices/100/", 200, r#"{"id":100,"name":"core-stack","custom_fields":{}}"#)
+        .route("PATCH", "/api/dcim/virtual-chassis/12/", 200, r#"{"id":12,"name":"core-stack"}"#)
+        .serve()
+        .await;
+    let config = NetBoxConfig {
+        device_type_mappings: HashMap::from([("DCS-7050SX3-48YC8".to_string(), 9)]),
+        ..Default::default()
+    };
+    let adapter = NetBoxAdapter::with_config(&base_url, "token", config).unwrap();
+
+    let mut stack = discovered_switch("core-stack", "00:1c:73:00:00:01");
+    stack.add_stack_member(StackMember::new(1, stack.mac())).unwrap();
+    stack.add_stack_member(StackMember::new(2, MacAddress::parse("00:1c:73:00:00:02").unwrap())).unwrap();
+    adapter.sync_device(&stack).await.expect("Failed to sync stack");
+
+    let requests = netbox.calls();
+    assert_eq!(requests.iter().filter(|(method, path)| method == "POST" && path == "/api/dcim/devices/").count(), 1);
+
+    let bodies = netbox.bodies();
+    let body = |method: &str, path: &str| {
+        bodies.iter().find(|((m, p), _)| m == method && p == path).map(|(_, body)| body.clone()).unwrap()
+    };
+    assert_eq!(body("POST", "/api/dcim/virtual-chassis/"), serde_json::json!({"name": "core-stack"}));
+    assert_eq!(
+        body("PATCH", "/api/dcim/devices/100/"),
+        serde_json::json!({"virtual_chassis": 12, "vc_position": 1})
+    );
+    assert_eq!(body("PATCH", "/api/dcim/virtual-chassis/12/"), serde_json::json!({"master": 100}));
+}
+
+fn interface(name: &str, ip: Option<&str>) -> InterfaceConfig {
+    InterfaceConfig {
+        name: name.to_string(),
+        ip_address: ip.map(|ip| ip.parse().unwrap()),
+        prefix_len: ip.map(|_| 24),
+        vlan_id: None,
+        enabled: true,
+        mtu: None,
+        speed: None,
+    }
+}
+
+/// A device's new address is created on its interface and the one it lost is released
+#[tokio::test]
+async fn test_sync_ip_assignments_creates_new_and_releases_stale() {
+    let mut device = discovered_switch("core-sw1", "00:1c:73:00:00:01");
+    device.adopt("vendor-1".to_string()).unwrap();
+    device.mark_provisioned("DCS-7050SX3-48YC8".to_string(), "4.30.1F".to_string()).unwrap();
+    device.start_configuration().unwrap();
+    device
+        .complete_configuration(vec![interface("eth0", Some("10.0.0.6")), interface("eth1", Some("10.0.1.1"))], vec![])
+        .unwrap();
+
+    let recorded = serde_json::json!({
+        "count": 2,
+        "next": null,
+        "previous": null,
+        "results": [
+            // eth0 used to have .5; eth1's address is already recorded on it
+            ip_address(11, "10.0.0.5/24", "active", Some((42, "eth0"))),
+            ip_address(12, "10.0.1.1/24", "active", Some((42, "eth1"))),
+        ],
+    });
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", &format!("/api/dcim/devices/?cf_cim_device_id={}", device.id()), 200, &device_list(&[(42, "core-sw1", &device.id().to_string())]))
+        .route("GET", "/api/ipam/ip-addresses/?device_id=42", 200, &recorded.to_string())
+        .route("GET", "/api/dcim/interfaces/?device_id=42&name=eth0", 200, &interface_list(&[(1100, "eth0", 42)]))
+        .route("GET", "/api/dcim/interfaces/?device_id=42&name=eth1", 200, &interface_list(&[(1200, "eth1", 42)]))
+        .route("GET", "/api/ipam/ip-addresses/?address=10.0.0.6", 200, NO_RESULTS)
+        .route("POST", "/api/ipam/ip-addresses/", 201, &ip_address(13, "10.0.0.6/24", "active", Some((42, "eth0"))).to_string())
+        .route("DELETE", "/api/ipam/ip-addresses/11/", 204, "")
+        .serve()
+        .await;
+    let adapter = NetBoxAdapter::new(&base_url, "token").unwrap();
+
+    adapter.sync_ip_assignments(&device).await.expect("Failed to sync IP assignments");
+
+    let requests = netbox.calls();
+    let writes: Vec<_> = requests.iter().filter(|(method, _)| method != "GET").cloned().collect();
+    assert_eq!(writes, vec![
+        ("POST".to_string(), "/api/ipam/ip-addresses/".to_string()),
+        ("DELETE".to_string(), "/api/ipam/ip-addresses/11/".to_string()),
+    ]);
+
+    let bodies = netbox.bodies();
+    assert_eq!(bodies[0].1, serde_json::json!({
+        "address": "10.0.0.6/24",
+        "status": "active",
+        "assigned_object_type": "dcim.interface",
+        "assigned_object_id": 1100,
+    }));
+}
+
+/// A site is created once; synced members move into it and new devices are created in it
+#[tokio::test]
+async fn test_sync_site_places_member_devices() {
+    let leaf1 = discovered_switch("leaf1", "00:1c:73:00:00:01");
+    let leaf2 = discovered_switch("leaf2", "00:1c:73:00:00:02");
+    let mut site = SiteAggregate::new("Branch Office");
+    site.assign_device(leaf1.id());
+    site.assign_device(leaf2.id());
+
+    let (base_url, netbox) = CannedApi::new(NOT_FOUND)
+        .route("GET", "/api/dcim/sites/?slug=branch-office", 200, NO_RESULTS)
+        .route("POST", "/api/dcim/sites/", 201, r#"{"id":7,"name":"Branch Office","slug":"branch-office"}"#)
+        .route("GET", &format!("/api/dcim/devices/?cf_cim_device_id={}", leaf1.id()), 200, &device_list(&[(42, "leaf1", &leaf1.id().to_string())]))
+        .route("GET", &format!("/api/dcim/devices/?cf_cim_device_id={}", leaf2.id()), 200, NO_RESULTS)
+        .route("PATCH", "/api/dcim/devices/42/", 200, r#"{"id":42,"name":"leaf1","custom_fields":{}}"#)
+        .route("GET", "/api/dcim/devices/?name=leaf2", 200, NO_RESULTS)
+        .route("POST", "/api/dcim/devices/", 201, r#"{"id":43,"name":"leaf2","custom_fields":{}}"#)
+        .serve()
+        .await;
+    let config = NetBoxConfig {
+        device_type_mappings: HashMap::from([("DCS-7050SX3-48YC8".to_string(), 9)]),
+        ..Default::default()
+    };
+    let adapter = NetBoxAdapter::with_config(&base_url, "token", config).unwrap();
+
+    adapter.sync_site(&site).await.expect("Failed to sync site");
+    adapter.sync_device(&leaf2).await.expect("Failed to sync leaf2");
+    // Nothing changed, so the second sync makes no requests
+    let before = netbox.calls().len();
+    adapter.sync_site(&site).await.expect("Failed to resync site");
+    assert_eq!(netbox.calls().len(), before);
+
+    let bodies = netbox.bodies();
+    let body = |method: &str, path: &str| {
+        bodies.iter().find(|((m, p), _)| m == method && p == path).map(|(_, body)| body.clone()).unwrap()
+    };
+    assert_eq!(body("POST", "/api/dcim/sites/"), serde_json::json!({"name": "Branch Office", "slug": "branch-office"}));
+    assert_eq!(body("PATCH", "/api/dcim/devices/42/"), serde_json::json!({"site": 7}));
+    assert_eq!(body("POST", "/api/dcim/devices/")["site"], 7);
+}
@@ -0,0 +1,197 @@
+//! Integration tests for the OPNsense adapter
+//!
+//! These tests run `OpnSenseAdapter` against a minimal in-process HTTP server
+//! that serves canned OPNsense API responses and records every request with
+//! its body, so no firewall is required.
+//!
+//! Run with: cargo test --test opnsense_integration
+
+mod common;
+
+use cim_network::adapters::opnsense::OpnSenseAdapter;
+use cim_network::domain::ports::{DeviceControlPort, PortError, VendorConfig};
+use cim_network::domain::value_objects::{LinkSpeed, MacAddress, PortId};
+use common::CannedApi;
+
+/// Body OPNsense answers unknown API endpoints with
+const NOT_FOUND: &str = r#"{"errorMessage":"Endpoint not found"}"#;
+
+const FIRMWARE: &str = r#"{"status":"none","product":{"product_name":"OPNsense","product_version":"24.1.5"}}"#;
+
+const SYSTEM_INFORMATION: &str = r#"{"name":"fw1.example.net","versions":["OPNsense 24.1.5"]}"#;
+
+const SYSTEM_RESOURCES: &str = r#"{"memory":{"total":"8589934592","total_frmt":"8192 MB","used":2147483648,"used_frmt":"2048 MB"}}"#;
+
+const SYSTEM_TIME: &str = r#"{"uptime":"3 days, 04:05:06","datetime":"Fri Oct 16 12:00:00 UTC 2026"}"#;
+
+const ACTIVITY: &str = r#"{"headers":[
+    "last pid: 81234;  load averages:  0.21,  0.18,  0.17  up 3+04:05:06",
+    "CPU:  4.5% user,  0.0% nice,  2.5% system,  0.4% interrupt, 92.6% idle"
+],"details":[]}"#;
+
+const INTERFACES: &str = r#"{"total":3,"rowCount":3,"current":1,"rows":[
+    {"device":"igb0","identifier":"wan","description":"WAN","status":"up","enabled":true,
+     "macaddr":"00:0d:b9:5e:00:01","media":"1000baseT <full-duplex>",
+     "statistics":{"bytes received":"987654321","bytes transmitted":123456789,"input errors":"3","output errors":0}},
+    {"device":"igb1","identifier":"lan","description":"LAN","status":"up","enabled":true,
+     "macaddr":"00:0d:b9:5e:00:02","media":"2500Base-T <full-duplex>",
+     "statistics":{"bytes received":"5000","bytes transmitted":"6000","input errors":"0","output errors":"1"}},
+    {"device":"igb2","identifier":"opt1","description":"IOT","status":"no carrier","enabled":true,
+     "macaddr":"00:0d:b9:5e:00:03","media":"autoselect","statistics":{}}
+]}"#;
+
+fn firewall() -> CannedApi {
+    CannedApi::new(NOT_FOUND)
+        .route("GET", "/api/core/firmware/status", 200, FIRMWARE)
+        .route("GET", "/api/diagnostics/system/systemInformation", 200, SYSTEM_INFORMATION)
+        .route("GET", "/api/diagnostics/system/systemResources", 200, SYSTEM_RESOURCES)
+        .route("GET", "/api/diagnostics/system/systemTime", 200, SYSTEM_TIME)
+        .route("GET", "/api/diagnostics/activity/getActivity", 200, ACTIVITY)
+        .route("GET", "/api/interfaces/overview/interfacesInfo", 200, INTERFACES)
+}
+
+#[tokio::test]
+async fn test_list_devices_reports_firewall_as_gateway() {
+    let (base_url, _firewall) = firewall().serve().await;
+    let adapter = OpnSenseAdapter::new(&[&base_url], "key", "secret").unwrap();
+
+    let devices = adapter.list_devices().await.expect("Failed to list devices");
+
+    assert_eq!(devices.len(), 1);
+    let device = &devices[0];
+    assert_eq!(device.vendor_id, base_url);
+    assert_eq!(device.name, "fw1.example.net");
+    assert_eq!(device.model, "OPNsense");
+    assert_eq!(device.mac, MacAddress::parse("00:0d:b9:5e:00:02").unwrap());
+    assert_eq!(device.ip_address, Some("127.0.0.1".parse().unwrap()));
+    assert_eq!(device.properties["firmware_version"], "24.1.5");
+    assert!(device.adopted);
+}
+
+#[tokio::test]
+async fn test_get_device_stats() {
+    let (base_url, firewall) = firewall().serve().await;
+    let adapter = OpnSenseAdapter::new(&[&base_url], "key", "secret").unwrap();
+
+    let stats = adapter.get_device_stats(&base_url).await.expect("Failed to get stats");
+
+    assert_eq!(stats.uptime_seconds, 3 * 86400 + 4 * 3600 + 5 * 60 + 6);
+    assert_eq!(stats.cpu_percent, Some(7.4));
+    assert_eq!(stats.memory_percent, Some(25.0));
+    assert_eq!(stats.port_stats.len(), 3);
+
+    let wan = &stats.port_stats[0];
+    assert_eq!(wan.port_id, PortId::new("igb0"));
+    assert!(wan.link_up);
+    assert_eq!(wan.speed, Some(LinkSpeed::Gbps1));
+    assert_eq!(wan.rx_bytes, 987654321);
+    assert_eq!(wan.tx_bytes, 123456789);
+    assert_eq!(wan.rx_errors, 3);
+
+    let lan = &stats.port_stats[1];
+    assert_eq!(lan.speed, Some(LinkSpeed::Gbps2_5));
+    assert_eq!(lan.tx_errors, 1);
+
+    let iot = &stats.port_stats[2];
+    assert!(!iot.link_up);
+    assert_eq!(iot.speed, None);
+    assert_eq!(iot.rx_bytes, 0);
+
+    assert!(firewall.requests().iter().all(|r| r.authorized));
+}
+
+#[tokio::test]
+async fn test_apply_config_saves_then_activates() {
+    let saved = r#"{"result":"saved","uuid":"4f1c0b8e-0000-4000-8000-000000000001"}"#;
+    let (base_url, firewall) = firewall()
+        .route("POST", "/api/interfaces/vlan_settings/addItem", 200, saved)
+        .route("POST", "/api/interfaces/vlan_settings/reconfigure", 200, r#"{"status":"ok"}"#)
+        .route("POST", "/api/firewall/filter/addRule", 200, saved)
+        .route("POST", "/api/firewall/filter/setRule/8a2d", 200, r#"{"result":"saved"}"#)
+        .route("POST", "/api/firewall/filter/apply", 200, r#"{"status":"OK\n\n"}"#)
+        .serve()
+        .await;
+    let adapter = OpnSenseAdapter::new(&[&base_url], "key", "secret").unwrap();
+
+    let vlan = serde_json::json!({ "if": "igb1", "tag": "20", "descr": "IoT" });
+    let new_rule = serde_json::json!({ "action": "pass", "interface": "opt1", "destination_net": "any" });
+    let changed_rule = serde_json::json!({ "action": "block", "interface": "opt1", "destination_net": "lan" });
+    adapter
+        .apply_raw_config(&base_url, VendorConfig {
+            config_type: "opnsense".to_string(),
+            payload: serde_json::json!({
+                "firewall_rules": [
+                    { "rule": new_rule },
+                    { "uuid": "8a2d", "rule": changed_rule },
+                ],
+                "vlans": [{ "vlan": vlan }],
+            }),
+        })
+        .await
+        .expect("Failed to apply config");
+
+    let requests = firewall.requests();
+    let sent: Vec<_> = requests
+        .iter()
+        .map(|r| (r.method.as_str(), r.path.as_str(), r.body.clone().unwrap_or_default()))
+        .collect();
+    assert_eq!(sent, vec![
+        ("POST", "/api/interfaces/vlan_settings/addItem", serde_json::json!({ "vlan": vlan })),
+        ("POST", "/api/interfaces/vlan_settings/reconfigure", serde_json::json!({})),
+        ("POST", "/api/firewall/filter/addRule", serde_json::json!({ "rule": new_rule })),
+        ("POST", "/api/firewall/filter/setRule/8a2d", serde_json::json!({ "rule": changed_rule })),
+        ("POST", "/api/firewall/filter/apply", serde_json::json!({})),
+    ]);
+}
+
+/// Validation failures come back as `200 OK` and must still stop the apply
+#[tokio::test]
+async fn test_apply_config_stops_at_failed_validation() {
+    let (base_url, firewall) = firewall()
+        .route(
+            "POST",
+            "/api/firewall/filter/addRule",
+            200,
+            r#"{"result":"failed","validations":{"rule.source_net":"10.0.0.300 is not a valid source IP address or alias."}}"#,
+        )
+        .serve()
+        .await;
+    let adapter = OpnSenseAdapter::new(&[&base_url], "key", "secret").unwrap();
+
+    let result = adapter
+        .apply_raw_config(&base_url, VendorConfig {
+            config_type: "opnsense".to_string(),
+            payload: serde_json::json!({
+                "firewall_rules": [{ "rule": { "action": "pass", "source_net": "10.0.0.300" } }],
+            }),
+        })
+        .await;
+
+    assert!(matches!(result, Err(PortError::VendorError(msg)) if msg.contains("not a valid source IP")));
+    // The rule set is never applied
+    assert_eq!(firewall.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_restart_device_posts_reboot() {
+    let (base_url, firewall) = firewall()
+        .route("POST", "/api/core/system/reboot", 200, r#"{"status":"ok"}"#)
+        .serve()
+        .await;
+    let adapter = OpnSenseAdapter::new(&[&base_url], "key", "secret").unwrap();
+
+    adapter.restart_device(&base_url).await.expect("Failed to reboot");
+
+    let requests = firewall.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!((requests[0].method.as_str(), requests[0].path.as_str()), ("POST", "/api/core/system/reboot"));
+}
+
+#[tokio::test]
+async fn test_unknown_firewall_is_rejected() {
+    let adapter = OpnSenseAdapter::new(&["https://192.0.2.1"], "key", "secret").unwrap();
+
+    let result = adapter.restart_device("https://192.0.2.99").await;
+
+    assert!(matches!(result, Err(PortError::VendorError(msg)) if msg.contains("not configured")));
+}
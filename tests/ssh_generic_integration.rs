@@ -0,0 +1,159 @@
+//! Integration tests for the generic SSH adapter
+//!
+//! These tests drive `GenericSshAdapter` with the Linux profile against a
+//! mocked host that answers commands with captured output and records what
+//! it is sent, so no SSH server is required.
+//!
+//! Run with: cargo test --test ssh_generic_integration
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use cim_network::adapters::cisco::{CiscoError, CliTransport};
+use cim_network::adapters::ssh_generic::{CommandProfile, GenericSshAdapter};
+use cim_network::domain::ports::{DeviceConfigRequest, DeviceControlPort, PortError};
+use cim_network::domain::value_objects::{MacAddress, VlanConfig};
+
+/// Mocked Linux host: canned exec output plus a log of every command
+#[derive(Default)]
+struct MockHost {
+    responses: HashMap<String, String>,
+    commands: Mutex<Vec<(String, String)>>,
+}
+
+impl MockHost {
+    fn with_exec(mut self, command: &str, output: &str) -> Self {
+        self.responses.insert(command.to_string(), output.to_string());
+        self
+    }
+
+    fn commands(&self) -> Vec<String> {
+        self.commands.lock().unwrap().iter().map(|(_, command)| command.clone()).collect()
+    }
+}
+
+#[async_trait]
+impl CliTransport for MockHost {
+    async fn exec(&self, host: &str, command: &str) -> Result<String, CiscoError> {
+        self.commands.lock().unwrap().push((host.to_string(), command.to_string()));
+        Ok(self.responses
+            .get(command)
+            .cloned()
+            .unwrap_or_else(|| format!("bash: {}: command not found", command)))
+    }
+
+    async fn shell(&self, _host: &str, _lines: &[String]) -> Result<String, CiscoError> {
+        Err(CiscoError::Ssh("the Linux profile does not use a shell".to_string()))
+    }
+}
+
+const IP_LINK: &str = r#"[{"ifindex":1,"ifname":"lo","flags":["LOOPBACK","UP","LOWER_UP"],"mtu":65536,"qdisc":"noqueue","operstate":"UNKNOWN","linkmode":"DEFAULT","group":"default","txqlen":1000,"link_type":"loopback","address":"00:00:00:00:00:00","broadcast":"00:00:00:00:00:00"},{"ifindex":2,"ifname":"enp1s0","flags":["BROADCAST","MULTICAST","UP","LOWER_UP"],"mtu":1500,"qdisc":"fq_codel","operstate":"UP","linkmode":"DEFAULT","group":"default","txqlen":1000,"link_type":"ether","address":"52:54:00:ab:cd:ef","broadcast":"ff:ff:ff:ff:ff:ff"}]"#;
+
+const MEMINFO: &str = "\
+MemTotal:       16000000 kB
+MemFree:         4000000 kB
+MemAvailable:   12000000 kB
+";
+
+fn linux_host() -> MockHost {
+    MockHost::default()
+        .with_exec("hostname", "bastion\n")
+        .with_exec("uname -r", "6.6.30\n")
+        .with_exec("ip -j link", IP_LINK)
+        .with_exec("cat /proc/uptime", "3600.50 14000.10\n")
+        .with_exec("head -n 1 /proc/stat", "cpu  100 0 100 1800 0 0 0 0 0 0\n")
+        .with_exec("cat /proc/meminfo", MEMINFO)
+        .with_exec("hostnamectl set-hostname jump-1", "")
+        .with_exec("systemctl reboot", "")
+}
+
+fn adapter(host: Arc<MockHost>) -> GenericSshAdapter {
+    GenericSshAdapter::with_transport(CommandProfile::linux(), &["192.0.2.10"], host).unwrap()
+}
+
+#[tokio::test]
+async fn test_linux_host_is_listed() {
+    let adapter = adapter(Arc::new(linux_host()));
+
+    let devices = adapter.list_devices().await.unwrap();
+
+    assert_eq!(devices.len(), 1);
+    assert_eq!(devices[0].vendor_id, "192.0.2.10");
+    assert_eq!(devices[0].name, "bastion");
+    assert_eq!(devices[0].model, "linux");
+    assert_eq!(devices[0].mac, MacAddress::parse("52:54:00:ab:cd:ef").unwrap());
+    assert_eq!(devices[0].ip_address, Some("192.0.2.10".parse().unwrap()));
+    assert_eq!(devices[0].properties["software_version"], "6.6.30");
+}
+
+#[tokio::test]
+async fn test_linux_stats() {
+    let adapter = adapter(Arc::new(linux_host()));
+
+    let stats = adapter.get_device_stats("192.0.2.10").await.unwrap();
+
+    assert_eq!(stats.uptime_seconds, 3600);
+    assert_eq!(stats.cpu_percent, Some(10.0));
+    assert_eq!(stats.memory_percent, Some(25.0));
+    assert_eq!(stats.port_stats.len(), 2);
+    assert!(stats.port_stats[1].link_up);
+}
+
+#[tokio::test]
+async fn test_config_runs_rendered_commands_and_rejects_the_rest() {
+    let host = Arc::new(linux_host());
+    let adapter = adapter(host.clone());
+
+    adapter
+        .apply_config("192.0.2.10", DeviceConfigRequest::default().with_hostname("jump-1"))
+        .await
+        .unwrap();
+    assert_eq!(host.commands(), ["hostnamectl set-hostname jump-1"]);
+
+    let vlan = DeviceConfigRequest::default().with_vlan(VlanConfig::new(10, "users").unwrap());
+    let result = adapter.apply_config("192.0.2.10", vlan).await;
+    assert!(matches!(result, Err(PortError::NotSupported(_))));
+    assert_eq!(host.commands().len(), 1);
+}
+
+#[tokio::test]
+async fn test_restart_and_unknown_hosts() {
+    let host = Arc::new(linux_host());
+    let adapter = adapter(host.clone());
+
+    adapter.restart_device("192.0.2.10").await.unwrap();
+    assert_eq!(host.commands(), ["systemctl reboot"]);
+
+    assert!(adapter.restart_device("192.0.2.99").await.is_err());
+}
+
+#[tokio::test]
+async fn test_error_pattern_fails_the_operation() {
+    let profile = CommandProfile {
+        error_pattern: Some("command not found".to_string()),
+        ..CommandProfile::linux()
+    };
+    // /proc/stat is missing from this host's responses
+    let host = Arc::new(MockHost::default().with_exec("cat /proc/uptime", "1.0 1.0\n"));
+    let adapter = GenericSshAdapter::with_transport(profile, &["192.0.2.10"], host).unwrap();
+
+    let result = adapter.get_device_stats("192.0.2.10").await;
+
+    assert!(matches!(result, Err(PortError::VendorError(msg)) if msg.contains("command not found")));
+}
+
+#[tokio::test]
+async fn test_blank_profile_reports_not_supported() {
+    let adapter = GenericSshAdapter::with_transport(
+        CommandProfile::custom("acme-os"),
+        &["192.0.2.10"],
+        Arc::new(MockHost::default()),
+    )
+    .unwrap();
+
+    assert_eq!(adapter.vendor_name(), "acme-os");
+    assert!(matches!(adapter.list_devices().await, Err(PortError::NotSupported(_))));
+    assert!(matches!(adapter.get_device_stats("192.0.2.10").await, Err(PortError::NotSupported(_))));
+    assert!(matches!(adapter.restart_device("192.0.2.10").await, Err(PortError::NotSupported(_))));
+}
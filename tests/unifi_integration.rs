@@ -0,0 +1,291 @@
+//! Integration tests for the UniFi controller client
+//!
+//! These tests run `UniFiClient` and `UniFiAdapter` against a minimal
+//! in-process controller that hands out a CSRF token per login and answers
+//! `401 Unauthorized` to any other token, so session expiry can be simulated
+//! without a controller.
+//!
+//! Run with: cargo test --test unifi_integration
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use cim_network::adapters::credentials::EnvCredentials;
+use cim_network::adapters::retry::RetryPolicy;
+use cim_network::adapters::unifi::{UniFiClient, UniFiError};
+use cim_network::adapters::UniFiAdapter;
+use cim_network::domain::ports::{DeviceControlPort, PortError};
+use serde_json::{json, Value};
+use common::{respond, Request, Response};
+
+const OK: &str = r#"{"meta":{"rc":"ok"},"data":[]}"#;
+const LOGIN_REQUIRED: &str = r#"{"meta":{"rc":"error","msg":"api.err.LoginRequired"},"data":[]}"#;
+const INVALID_LOGIN: &str = r#"{"meta":{"rc":"error","msg":"api.err.Invalid"},"data":[]}"#;
+
+/// Controller session state
+#[derive(Default)]
+struct Sessions {
+    /// Successful logins so far
+    logins: usize,
+    /// Token of the live session, if any
+    valid_token: Option<String>,
+    /// Whether logins are refused
+    refuse_logins: bool,
+}
+
+/// Mock UniFi controller
+#[derive(Default)]
+struct MockController {
+    sessions: Mutex<Sessions>,
+    /// Paths of the requests that were answered 401
+    rejected: Mutex<Vec<String>>,
+    /// Devices reported by `stat/device`
+    devices: Mutex<Vec<Value>>,
+    /// Requests still to be answered `429 Too Many Requests`
+    throttled: Mutex<usize>,
+    /// Password sent with each login, in order
+    login_passwords: Mutex<Vec<String>>,
+}
+
+impl MockController {
+    /// Let the live session lapse, as the controller does after its timeout
+    fn expire(&self) {
+        self.sessions.lock().unwrap().valid_token = None;
+    }
+
+    fn refuse_logins(&self, refuse: bool) {
+        self.sessions.lock().unwrap().refuse_logins = refuse;
+    }
+
+    fn logins(&self) -> usize {
+        self.sessions.lock().unwrap().logins
+    }
+
+    /// Answer a request, checking its `X-CSRF-Token` against the live session
+    async fn answer(&self, request: Request) -> Response {
+        let path = request.path.as_str();
+        if (request.method.as_str(), path) == ("POST", "/api/login") {
+            if let Some(login) = request.json() {
+                let password = login["password"].as_str().unwrap_or_default().to_string();
+                self.login_passwords.lock().unwrap().push(password);
+            }
+            // Logging in takes a moment, so concurrent 401s pile up behind it
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            let mut sessions = self.sessions.lock().unwrap();
+            if sessions.refuse_logins {
+                return respond(400, INVALID_LOGIN);
+            }
+            sessions.logins += 1;
+            let token = format!("token-{}", sessions.logins);
+            sessions.valid_token = Some(token.clone());
+            return (200, vec![("X-CSRF-Token".to_string(), token)], OK.to_string());
+        }
+
+        {
+            let mut throttled = self.throttled.lock().unwrap();
+            if *throttled > 0 {
+                *throttled -= 1;
+                return (429, vec![("Retry-After".to_string(), "7".to_string())], OK.to_string());
+            }
+        }
+
+        let valid = self.sessions.lock().unwrap().valid_token.clone();
+        if valid.is_none() || request.header("x-csrf-token") != valid.as_deref() {
+            self.rejected.lock().unwrap().push(path.to_string());
+            return respond(401, LOGIN_REQUIRED);
+        }
+        if path == "/api/s/default/stat/device" {
+            let body = json!({ "meta": { "rc": "ok" }, "data": *self.devices.lock().unwrap() });
+            return respond(200, body.to_string());
+        }
+        respond(200, OK)
+    }
+
+    /// Serve the controller on a local port and return its base URL
+    async fn serve(self) -> (String, Arc<Self>) {
+        let mock = Arc::new(self);
+        let server = mock.clone();
+        let base_url = common::serve(move |request| {
+            let server = server.clone();
+            async move { server.answer(request).await }
+        })
+        .await;
+        (base_url, mock)
+    }
+}
+
+async fn logged_in_client() -> (Arc<UniFiClient>, Arc<MockController>) {
+    let (base_url, controller) = MockController::default().serve().await;
+    let client = UniFiClient::new(&base_url, "admin", "secret").await.unwrap();
+    client.login().await.expect("Failed to log in");
+    (Arc::new(client), controller)
+}
+
+/// An expired session is renewed transparently and the original call succeeds
+#[tokio::test]
+async fn test_expired_session_logs_in_again() {
+    let (client, controller) = logged_in_client().await;
+    controller.expire();
+
+    let devices = client.list_devices("default").await.expect("Call after expiry failed");
+
+    assert!(devices.is_empty());
+    assert_eq!(controller.logins(), 2);
+    assert_eq!(controller.rejected.lock().unwrap().as_slice(), ["/api/s/default/stat/device"]);
+    assert!(client.is_authenticated());
+}
+
+/// A burst of calls rejected by the same expiry shares one re-login
+#[tokio::test]
+async fn test_concurrent_rejections_share_one_login() {
+    let (client, controller) = logged_in_client().await;
+    controller.expire();
+
+    let calls: Vec<_> = (0..8)
+        .map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move { client.list_devices("default").await })
+        })
+        .collect();
+    for call in calls {
+        call.await.unwrap().expect("Call after expiry failed");
+    }
+
+    assert_eq!(controller.logins(), 2);
+}
+
+/// A failed re-login leaves the client unauthenticated, and a later call recovers
+#[tokio::test]
+async fn test_failed_login_is_reported_and_retried_later() {
+    let (client, controller) = logged_in_client().await;
+    controller.expire();
+    controller.refuse_logins(true);
+
+    let result = client.list_devices("default").await;
+
+    assert!(matches!(result, Err(UniFiError::Auth(_))));
+    assert!(!client.is_authenticated());
+
+    controller.refuse_logins(false);
+    client.list_devices("default").await.expect("Call after recovery failed");
+    assert!(client.is_authenticated());
+}
+
+/// Without a first login there is no session to renew
+#[tokio::test]
+async fn test_calls_before_login_are_refused() {
+    let (base_url, controller) = MockController::default().serve().await;
+    let client = UniFiClient::new(&base_url, "admin", "secret").await.unwrap();
+
+    let result = client.list_devices("default").await;
+
+    assert!(matches!(result, Err(UniFiError::Auth(_))));
+    assert_eq!(controller.logins(), 0);
+}
+
+/// Credentials come from the environment at each login, never from the constructor
+#[tokio::test]
+async fn test_credentials_resolve_from_env_at_login() {
+    std::env::set_var("CIM_NETWORK_TEST_UNIFI_USER", "admin");
+    std::env::set_var("CIM_NETWORK_TEST_UNIFI_PASSWORD", "first-password");
+    let (base_url, controller) = MockController::default().serve().await;
+    let source = EnvCredentials::new("CIM_NETWORK_TEST_UNIFI_PASSWORD")
+        .with_username_var("CIM_NETWORK_TEST_UNIFI_USER");
+    let adapter = UniFiAdapter::with_credentials(&base_url, "default", source).await.unwrap();
+
+    adapter.connect().await.expect("Failed to connect");
+    std::env::set_var("CIM_NETWORK_TEST_UNIFI_PASSWORD", "rotated-password");
+    controller.expire();
+    adapter.list_devices().await.expect("Call after expiry failed");
+
+    assert_eq!(
+        controller.login_passwords.lock().unwrap().as_slice(),
+        ["first-password", "rotated-password"]
+    );
+}
+
+/// Neither the adapter nor its client reveals the password through `Debug`
+#[tokio::test]
+async fn test_debug_does_not_leak_password() {
+    let (base_url, _controller) = MockController::default().serve().await;
+    let adapter = UniFiAdapter::new(&base_url, "admin", "hunter2", "default").await.unwrap();
+    adapter.connect().await.expect("Failed to connect");
+
+    let debug = format!("{:?}", adapter);
+    assert!(debug.contains("admin"));
+    assert!(!debug.contains("hunter2"));
+}
+
+fn device(mac: &str, name: &str, last_seen: u64) -> Value {
+    json!({
+        "_id": mac.replace(':', ""),
+        "mac": mac,
+        "model": "USW-24",
+        "name": name,
+        "adopted": true,
+        "type": "usw",
+        "last_seen": last_seen,
+    })
+}
+
+/// Listing with the returned cursor yields only devices heard from since
+#[tokio::test]
+async fn test_list_devices_since_returns_only_changed_devices() {
+    let (base_url, controller) = MockController::default().serve().await;
+    *controller.devices.lock().unwrap() = vec![
+        device("00:11:22:33:44:55", "core-sw", 1_700_000_000),
+        device("00:11:22:33:44:66", "edge-sw", 1_700_000_100),
+    ];
+    let adapter = UniFiAdapter::new(&base_url, "admin", "secret", "default").await.unwrap();
+    adapter.connect().await.unwrap();
+
+    let (devices, cursor) = adapter.list_devices_since(None).await.unwrap();
+    assert_eq!(devices.len(), 2);
+    assert_eq!(cursor.as_deref(), Some("1700000100"));
+
+    controller.devices.lock().unwrap()[0]["last_seen"] = json!(1_700_000_200);
+    let (devices, cursor) = adapter.list_devices_since(cursor).await.unwrap();
+
+    assert_eq!(devices.len(), 1);
+    assert_eq!(devices[0].name, "core-sw");
+    assert_eq!(cursor.as_deref(), Some("1700000200"));
+}
+
+async fn connected_adapter() -> (UniFiAdapter, Arc<MockController>) {
+    let (base_url, controller) = MockController::default().serve().await;
+    let adapter = UniFiAdapter::with_retry_policy(&base_url, "admin", "secret", "default", RetryPolicy::none())
+        .await
+        .unwrap();
+    adapter.connect().await.unwrap();
+    (adapter, controller)
+}
+
+/// A throttled call is reported with the controller's `Retry-After`
+#[tokio::test]
+async fn test_throttled_call_is_rate_limited() {
+    let (adapter, controller) = connected_adapter().await;
+    *controller.throttled.lock().unwrap() = 1;
+
+    let result = adapter.list_devices().await;
+
+    assert!(matches!(
+        result,
+        Err(PortError::RateLimited { retry_after: Some(delay) }) if delay == Duration::from_secs(7)
+    ));
+    assert!(adapter.list_devices().await.is_ok());
+}
+
+/// A session the controller rejects, and will not renew, is an authentication failure
+#[tokio::test]
+async fn test_rejected_session_is_authentication_failure() {
+    let (adapter, controller) = connected_adapter().await;
+    controller.expire();
+    controller.refuse_logins(true);
+
+    let result = adapter.list_devices().await;
+
+    assert!(matches!(result, Err(PortError::AuthenticationFailed(_))));
+    assert!(!result.unwrap_err().is_transient());
+}
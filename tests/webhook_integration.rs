@@ -0,0 +1,179 @@
+//! Integration tests for the webhook forwarder
+//!
+//! These tests run `WebhookForwarder` against a minimal in-process HTTP
+//! endpoint that answers every request with a fixed status and records its
+//! headers and raw body, fed by an in-memory `EventSource` whose ack handles
+//! record how each event was settled.
+//!
+//! Run with: cargo test --test webhook_integration
+
+mod common;
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use cim_network::adapters::credentials::Secret;
+use cim_network::adapters::retry::RetryPolicy;
+use cim_network::adapters::webhook::{self, WebhookConfig, WebhookEnvelope, WebhookForwarder};
+use cim_network::domain::events::NetworkEvent;
+use cim_network::domain::ports::{EventAck, EventSource, PortError};
+use cim_network::domain::value_objects::DeviceId;
+use common::{respond, Request, Response};
+
+const SECRET: &str = "webhook-test-secret";
+
+/// A request as seen by the mock endpoint
+#[derive(Debug, Clone)]
+struct Recorded {
+    path: String,
+    signature: Option<String>,
+    event_type: Option<String>,
+    body: Vec<u8>,
+}
+
+/// HTTP endpoint answering every request with `status`
+struct MockEndpoint {
+    status: u16,
+    requests: Mutex<Vec<Recorded>>,
+}
+
+impl MockEndpoint {
+    /// Serve on a local port and return its base URL
+    async fn serve(status: u16) -> (String, Arc<Self>) {
+        let mock = Arc::new(Self { status, requests: Mutex::new(Vec::new()) });
+        let server = mock.clone();
+        let base_url = common::serve(move |request| std::future::ready(server.answer(request))).await;
+        (base_url, mock)
+    }
+
+    /// Record a delivery and answer it with the fixed status
+    fn answer(&self, request: Request) -> Response {
+        self.requests.lock().unwrap().push(Recorded {
+            signature: request.header(webhook::SIGNATURE_HEADER).map(str::to_string),
+            event_type: request.header(webhook::EVENT_TYPE_HEADER).map(str::to_string),
+            path: request.path,
+            body: request.body,
+        });
+        respond(self.status, "")
+    }
+
+    fn requests(&self) -> Vec<Recorded> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// How the forwarder settled an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Settled {
+    Acked,
+    Nacked,
+}
+
+/// Outcome of each delivered event, by delivery index
+type SettledLog = Arc<Mutex<Vec<(usize, Settled)>>>;
+
+/// Ack handle recording the outcome in a shared log
+struct RecordingAck {
+    index: usize,
+    log: SettledLog,
+}
+
+#[async_trait]
+impl EventAck for RecordingAck {
+    async fn ack(self) -> Result<(), PortError> {
+        self.log.lock().unwrap().push((self.index, Settled::Acked));
+        Ok(())
+    }
+
+    async fn nak(self) -> Result<(), PortError> {
+        self.log.lock().unwrap().push((self.index, Settled::Nacked));
+        Ok(())
+    }
+}
+
+/// Event source yielding a fixed list of events, then ending
+struct MockEventSource {
+    events: VecDeque<NetworkEvent>,
+    delivered: usize,
+    log: SettledLog,
+}
+
+impl MockEventSource {
+    fn new(events: Vec<NetworkEvent>) -> (Self, SettledLog) {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let source = Self {
+            events: events.into(),
+            delivered: 0,
+            log: log.clone(),
+        };
+        (source, log)
+    }
+}
+
+#[async_trait]
+impl EventSource for MockEventSource {
+    type Ack = RecordingAck;
+
+    async fn next(&mut self) -> Option<Result<(NetworkEvent, RecordingAck), PortError>> {
+        let event = self.events.pop_front()?;
+        let ack = RecordingAck { index: self.delivered, log: self.log.clone() };
+        self.delivered += 1;
+        Some(Ok((event, ack)))
+    }
+}
+
+fn provisioned() -> NetworkEvent {
+    NetworkEvent::DeviceProvisioned {
+        device_id: DeviceId::new(),
+        model: "USW-24".to_string(),
+        firmware_version: "7.1.66".to_string(),
+    }
+}
+
+fn forwarder(config: WebhookConfig) -> WebhookForwarder {
+    WebhookForwarder::new(config.with_retry(RetryPolicy::new(2, Duration::from_millis(10)))).unwrap()
+}
+
+/// A matching event is POSTed with a valid signature and acked; others are skipped
+#[tokio::test]
+async fn test_device_provisioned_delivered_with_valid_signature() {
+    let (base_url, endpoint) = MockEndpoint::serve(204).await;
+    let forwarder = forwarder(WebhookConfig::new(Secret::new(SECRET))
+        .route("DeviceProvisioned", format!("{}/hooks/provisioned", base_url)));
+
+    let event = provisioned();
+    let unrouted = NetworkEvent::DeviceConfiguring { device_id: DeviceId::new() };
+    let (source, settled) = MockEventSource::new(vec![event.clone(), unrouted]);
+    forwarder.run(source).await;
+
+    let requests = endpoint.requests();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request.path, "/hooks/provisioned");
+    assert_eq!(request.event_type.as_deref(), Some("DeviceProvisioned"));
+    assert_eq!(request.signature, Some(webhook::sign(&Secret::new(SECRET), &request.body)));
+    assert_ne!(request.signature, Some(webhook::sign(&Secret::new("other-secret"), &request.body)));
+
+    let envelope: WebhookEnvelope = serde_json::from_slice(&request.body).unwrap();
+    assert_eq!(envelope.event_type, "DeviceProvisioned");
+    assert_eq!(envelope.aggregate_id, event.aggregate_id());
+    assert_eq!(serde_json::to_value(&envelope.event).unwrap(), serde_json::to_value(&event).unwrap());
+
+    assert_eq!(*settled.lock().unwrap(), vec![(0, Settled::Acked), (1, Settled::Acked)]);
+}
+
+/// An endpoint that keeps failing is retried, then the event is nacked
+#[tokio::test]
+async fn test_failing_endpoint_nacks_event() {
+    let (base_url, endpoint) = MockEndpoint::serve(503).await;
+    let forwarder = forwarder(WebhookConfig::new(Secret::new(SECRET)).route("Device*", base_url));
+
+    let (source, settled) = MockEventSource::new(vec![provisioned()]);
+    forwarder.run(source).await;
+
+    assert_eq!(endpoint.requests().len(), 2, "Expected one retry");
+    assert_eq!(*settled.lock().unwrap(), vec![(0, Settled::Nacked)]);
+}